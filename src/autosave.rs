@@ -0,0 +1,236 @@
+//! Periodic saving of the graph, so that it can be restored after a crash.
+//!
+//! Snapshots of the graph are sent to a writer thread, which writes them into a rotating set of
+//! files in the cache directory. Regular snapshots are written at most once per
+//! [`Config::autosave_interval`](crate::config::Config::autosave_interval), snapshots taken before
+//! destructive operations are written as soon as [`MIN_WRITE_INTERVAL`] allows.
+
+use crate::config::SharedConfig;
+use crate::graph::file::{GraphFile, GraphFileError, GraphMetadata};
+use crate::graph::Graph;
+use crate::logging::Category;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// The number of autosave files, the oldest one is overwritten by the next save.
+const ROTATION_LENGTH: usize = 3;
+/// The minimum duration between two writes, so that rapid edits do not thrash the disk.
+const MIN_WRITE_INTERVAL: Duration = Duration::from_secs(5);
+/// The minimum duration between two snapshots taken by the UI thread.
+const MIN_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+/// Stores the path of the file the graph was last explicitly saved to.
+const LAST_SAVED_FILE_NAME: &str = "last_saved";
+
+enum AutosaveRequest {
+    Snapshot(GraphFile),
+    /// Taken before a destructive operation, kept until written even if newer snapshots arrive.
+    UrgentSnapshot(GraphFile),
+    ExplicitSave(PathBuf),
+}
+
+fn directory() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("dvsynth").join("autosave"))
+}
+
+fn file_path(directory: &Path, index: usize) -> PathBuf {
+    directory.join(format!("autosave-{}.json", index))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Finds the newest autosave, if it is newer than the file the graph was last explicitly saved
+/// to.
+pub fn find_restorable() -> Option<PathBuf> {
+    let directory = directory()?;
+    let (newest_path, newest_modified) = (0..ROTATION_LENGTH)
+        .map(|index| file_path(&directory, index))
+        .filter_map(|path| modified(&path).map(|modified| (path, modified)))
+        .max_by_key(|(_, modified)| *modified)?;
+    let last_saved_modified = fs::read_to_string(directory.join(LAST_SAVED_FILE_NAME))
+        .ok()
+        .and_then(|last_saved_path| modified(Path::new(last_saved_path.trim())));
+
+    match last_saved_modified {
+        Some(last_saved_modified) if last_saved_modified >= newest_modified => None,
+        _ => Some(newest_path),
+    }
+}
+
+/// The UI side of the autosave service. Never blocks, all I/O happens on the writer thread.
+pub struct Autosave {
+    sender: Sender<AutosaveRequest>,
+    config: Arc<SharedConfig>,
+    /// Whether the graph has changed since the last snapshot.
+    dirty: bool,
+    last_snapshot: Option<Instant>,
+}
+
+impl Autosave {
+    pub fn spawn(config: Arc<SharedConfig>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let writer_config = config.clone();
+
+        thread::spawn(move || AutosaveWriter::new(writer_config).run(receiver));
+
+        Self { sender, config, dirty: false, last_snapshot: None }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.config.get().autosave_interval().is_some()
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Sends a snapshot of the graph to the writer, if the graph has changed and the previous
+    /// snapshot is not too recent. Should be called after every update.
+    pub fn snapshot(&mut self, graph: &Graph, metadata: &GraphMetadata) {
+        if !self.dirty || !self.is_enabled() {
+            return;
+        }
+
+        if let Some(last_snapshot) = self.last_snapshot {
+            if last_snapshot.elapsed() < MIN_SNAPSHOT_INTERVAL {
+                return;
+            }
+        }
+
+        self.dirty = false;
+        self.last_snapshot = Some(Instant::now());
+        let _ = self.sender.send(AutosaveRequest::Snapshot(GraphFile::from_graph(graph, metadata)));
+    }
+
+    /// Sends a snapshot of the graph to be written as soon as possible, before a destructive
+    /// operation is applied to the graph.
+    pub fn snapshot_urgent(&mut self, graph: &Graph, metadata: &GraphMetadata) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let _ = self.sender.send(AutosaveRequest::UrgentSnapshot(GraphFile::from_graph(graph, metadata)));
+    }
+
+    /// Records the path the graph was explicitly saved to, so that older autosaves are not
+    /// offered to be restored.
+    pub fn record_explicit_save(&mut self, path: &Path) {
+        self.dirty = false;
+        let _ = self.sender.send(AutosaveRequest::ExplicitSave(path.to_path_buf()));
+    }
+}
+
+struct AutosaveWriter {
+    config: Arc<SharedConfig>,
+    pending: Option<GraphFile>,
+    pending_urgent: Option<GraphFile>,
+    last_write: Option<Instant>,
+    next_file_index: usize,
+}
+
+impl AutosaveWriter {
+    fn new(config: Arc<SharedConfig>) -> Self {
+        // Continue the rotation by overwriting the oldest or a missing file.
+        let next_file_index = directory()
+            .and_then(|directory| {
+                (0..ROTATION_LENGTH).min_by_key(|index| modified(&file_path(&directory, *index)))
+            })
+            .unwrap_or(0);
+
+        Self { config, pending: None, pending_urgent: None, last_write: None, next_file_index }
+    }
+
+    /// The time at which the pending snapshots should be written, `None` if there are none.
+    fn deadline(&self, interval: Duration) -> Option<Instant> {
+        let min_interval = if self.pending_urgent.is_some() {
+            MIN_WRITE_INTERVAL
+        } else if self.pending.is_some() {
+            interval.max(MIN_WRITE_INTERVAL)
+        } else {
+            return None;
+        };
+
+        Some(self.last_write.map(|last_write| last_write + min_interval).unwrap_or_else(Instant::now))
+    }
+
+    fn run(mut self, receiver: Receiver<AutosaveRequest>) {
+        loop {
+            let interval = if let Some(interval) = self.config.get().autosave_interval() {
+                interval
+            } else {
+                // Autosaving has been disabled.
+                self.pending = None;
+                self.pending_urgent = None;
+                Duration::from_secs(0)
+            };
+            let request = match self.deadline(interval) {
+                Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match request {
+                Ok(AutosaveRequest::Snapshot(file)) => self.pending = Some(file),
+                Ok(AutosaveRequest::UrgentSnapshot(file)) => {
+                    // Keep the state before the first of consecutive destructive operations.
+                    if self.pending_urgent.is_none() {
+                        self.pending_urgent = Some(file);
+                    }
+                }
+                Ok(AutosaveRequest::ExplicitSave(path)) => {
+                    self.pending = None;
+                    self.pending_urgent = None;
+                    self.write_last_saved(&path);
+                }
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            // Checked after every request, so that frequent snapshots cannot postpone the write.
+            if self.deadline(interval).map_or(false, |deadline| deadline <= Instant::now()) {
+                if let Some(file) = self.pending_urgent.take().or_else(|| self.pending.take()) {
+                    self.write(&file);
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, file: &GraphFile) {
+        self.last_write = Some(Instant::now());
+
+        let directory = if let Some(directory) = directory() {
+            directory
+        } else {
+            error!(Category::Ui, "Could not determine the cache directory, the graph was not autosaved.");
+            return;
+        };
+        let path = file_path(&directory, self.next_file_index);
+        let result =
+            fs::create_dir_all(&directory).map_err(GraphFileError::from).and_then(|()| file.save(&path));
+
+        match result {
+            Ok(()) => self.next_file_index = (self.next_file_index + 1) % ROTATION_LENGTH,
+            Err(error) => {
+                error!(Category::Ui, "Could not autosave the graph to `{}`: {}", path.display(), error)
+            }
+        }
+    }
+
+    fn write_last_saved(&self, path: &Path) {
+        let result = directory()
+            .ok_or_else(|| "Could not determine the cache directory.".to_string())
+            .and_then(|directory| {
+                fs::create_dir_all(&directory).map_err(|error| error.to_string())?;
+                fs::write(directory.join(LAST_SAVED_FILE_NAME), path.display().to_string())
+                    .map_err(|error| error.to_string())
+            });
+
+        if let Err(error) = result {
+            error!(Category::Ui, "Could not record the path of the saved graph: {}", error);
+        }
+    }
+}
@@ -0,0 +1,571 @@
+//! Persistent application settings.
+//!
+//! The settings are stored as TOML in the platform-specific config directory. Invalid or missing
+//! fields fall back to their default values individually, so that a partially corrupted file
+//! does not discard the remaining settings.
+//!
+//! Modified settings are written by a writer thread, at most once per [`SAVE_DELAY`], so that
+//! rapid changes, such as dragging a slider in the preferences, neither block the UI thread nor
+//! thrash the disk.
+
+use crate::keymap::Keymap;
+use crate::logging::{Category, LevelFilters};
+use crate::palette::NodeUsageHistory;
+use crate::style::{self, Theme};
+use crate::threading::ThreadPriority;
+use arc_swap::ArcSwap;
+use flume::{RecvTimeoutError, Sender};
+use iced_wgpu::wgpu;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The maximum duration between modifying the settings and writing them.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeChoice {
+    Dark,
+    /// Uses colors distinguishable with the common kinds of color blindness.
+    HighContrast,
+}
+
+impl ThemeChoice {
+    pub const VALUES: [ThemeChoice; 2] = [ThemeChoice::Dark, ThemeChoice::HighContrast];
+
+    pub fn theme(&self) -> Box<dyn Theme> {
+        match self {
+            ThemeChoice::Dark => Box::new(style::Dark),
+            ThemeChoice::HighContrast => Box::new(style::HighContrast),
+        }
+    }
+}
+
+impl Display for ThemeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeChoice::Dark => write!(f, "Dark"),
+            ThemeChoice::HighContrast => write!(f, "High Contrast"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdapterPreference {
+    Default,
+    LowPower,
+    HighPerformance,
+}
+
+impl AdapterPreference {
+    pub const VALUES: [AdapterPreference; 3] =
+        [AdapterPreference::Default, AdapterPreference::LowPower, AdapterPreference::HighPerformance];
+
+    pub fn power_preference(&self) -> wgpu::PowerPreference {
+        match self {
+            AdapterPreference::Default => wgpu::PowerPreference::Default,
+            AdapterPreference::LowPower => wgpu::PowerPreference::LowPower,
+            AdapterPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        }
+    }
+}
+
+impl Display for AdapterPreference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdapterPreference::Default => write!(f, "Default"),
+            AdapterPreference::LowPower => write!(f, "Low Power"),
+            AdapterPreference::HighPerformance => write!(f, "High Performance"),
+        }
+    }
+}
+
+/// What happens to the connections passing through a node that is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconnectOnRemoval {
+    /// A node between a single source and a single consumer is bypassed without asking, the
+    /// reconnections of other nodes are offered to be chosen from.
+    Silently,
+    /// The reconnections are always offered to be chosen from.
+    Prompt,
+    Never,
+}
+
+impl ReconnectOnRemoval {
+    pub const VALUES: [ReconnectOnRemoval; 3] =
+        [ReconnectOnRemoval::Silently, ReconnectOnRemoval::Prompt, ReconnectOnRemoval::Never];
+}
+
+impl Display for ReconnectOnRemoval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReconnectOnRemoval::Silently => write!(f, "Silently"),
+            ReconnectOnRemoval::Prompt => write!(f, "Ask first"),
+            ReconnectOnRemoval::Never => write!(f, "Never"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    pub theme: ThemeChoice,
+    /// The number of graph executions per second, or `0.0` for no limit.
+    pub executor_target_rate: f32,
+    /// The time in seconds a node may execute for before it is reported as stuck and asked to
+    /// cancel its execution, or `0.0` to disable the watchdog.
+    pub watchdog_threshold: f32,
+    /// The scheduling priority of the executor thread. Only takes effect once the executor is
+    /// restarted, which currently requires restarting the application, regenerating the schedule
+    /// is not enough.
+    pub executor_priority: ThreadPriority,
+    /// The CPUs the executor thread is restricted to, one bit per CPU, or `0` to run it on any
+    /// CPU. Only takes effect once the executor is restarted, like the priority.
+    pub executor_affinity_mask: u64,
+    /// The size of the grid floating panes snap to when moved, or `0.0` to disable snapping.
+    pub grid_size: f32,
+    /// Only takes effect after a restart.
+    pub adapter_preference: AdapterPreference,
+    /// The interval between automatic saves of the graph in seconds, or `0` to disable autosaving.
+    pub autosave_interval: u64,
+    /// Removing a node with at least this many connections has to be confirmed, or `0` to never
+    /// ask for a confirmation.
+    pub node_removal_confirmation_threshold: usize,
+    /// Whether removing a node connects the nodes it was between to each other.
+    pub reconnect_on_removal: ReconnectOnRemoval,
+    /// Whether edits leaving other nodes without inputs or invalidating the graph list what they
+    /// break, to be confirmed first. Otherwise, the consequences are logged once applied.
+    pub confirm_breaking_edits: bool,
+    /// Whether dropping a pending connection on empty space creates a node connected to it.
+    pub create_nodes_on_drop: bool,
+    /// Whether saving over a graph file lists the changes to it, to be confirmed first.
+    pub review_changes_before_saving: bool,
+    /// Whether the number of values consumed per second is displayed next to each connection.
+    pub show_throughput: bool,
+    /// Whether the number of frames of the UI drawn per second is displayed along with the status
+    /// of the schedule, to tell whether the idle editor stops redrawing.
+    pub show_frame_rate: bool,
+    /// Whether each node is numbered by its position in the order of execution.
+    pub show_execution_order: bool,
+    /// Whether the parts of connections passing over panes other than their own are dimmed.
+    pub dim_crossing_connections: bool,
+    /// Whether the processed messages and input events are recorded into session logs in the cache
+    /// directory, to be attached to bug reports and replayed with `--replay-session`.
+    pub record_sessions: bool,
+    /// The maximum number of items of the lists generated by nodes, so that a mistyped count does
+    /// not allocate gigabytes of memory.
+    pub max_list_length: usize,
+    /// The number of past values recorded by inspecting nodes, to be scrubbed through.
+    pub history_depth: usize,
+    /// The maximum number of bytes the values recorded by a single node may occupy, which limits
+    /// the depth of the history of large values.
+    pub history_memory_cap: usize,
+    pub plugin_directory: Option<PathBuf>,
+    /// The graphs most recently opened or saved, the most recent first.
+    pub recent_graphs: Vec<PathBuf>,
+    /// The names of the kinds of nodes pinned to the top of the node palette.
+    pub favorite_nodes: BTreeSet<String>,
+    /// The names of the kinds of nodes disabled permanently from the banner of safe mode, which
+    /// are replaced by placeholders when graphs are opened, even outside of safe mode.
+    pub disabled_behaviours: BTreeSet<String>,
+    /// The most verbose level of the diagnostic messages recorded, per category. Stored as a table,
+    /// like the shortcuts.
+    pub log_levels: LevelFilters,
+    /// How often and when each kind of node has been inserted from the node palette or a preset.
+    /// Stored as a table, like the shortcuts.
+    pub node_usage: NodeUsageHistory,
+    /// Stored as a table, which has to follow all other fields.
+    pub shortcuts: Keymap,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: ThemeChoice::Dark,
+            executor_target_rate: 0.0,
+            watchdog_threshold: 2.0,
+            executor_priority: ThreadPriority::Default,
+            executor_affinity_mask: 0,
+            grid_size: 0.0,
+            adapter_preference: AdapterPreference::HighPerformance,
+            autosave_interval: 300,
+            node_removal_confirmation_threshold: 3,
+            reconnect_on_removal: ReconnectOnRemoval::Silently,
+            confirm_breaking_edits: true,
+            create_nodes_on_drop: true,
+            review_changes_before_saving: true,
+            show_throughput: false,
+            show_frame_rate: false,
+            show_execution_order: false,
+            dim_crossing_connections: false,
+            record_sessions: false,
+            max_list_length: 10_000_000,
+            history_depth: 600,
+            history_memory_cap: 1 << 20,
+            plugin_directory: None,
+            recent_graphs: Vec::new(),
+            favorite_nodes: BTreeSet::new(),
+            disabled_behaviours: BTreeSet::new(),
+            log_levels: LevelFilters::default(),
+            node_usage: NodeUsageHistory::default(),
+            shortcuts: Keymap::default(),
+        }
+    }
+}
+
+impl Config {
+    const FILE_NAME: &'static str = "config.toml";
+    /// The maximum number of graphs kept in [`Config::recent_graphs`].
+    pub const MAX_RECENT_GRAPHS: usize = 10;
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("dvsynth").join(Self::FILE_NAME))
+    }
+
+    /// Parses the config, falling back to the default value of each field that is missing or
+    /// invalid.
+    pub fn parse(source: &str) -> Self {
+        let table = match source.parse::<toml::Value>() {
+            Ok(toml::Value::Table(table)) => table,
+            Ok(_) => {
+                warn!(Category::Ui, "The config file does not contain a table, using the default settings.");
+                return Self::default();
+            }
+            Err(error) => {
+                warn!(Category::Ui, "Could not parse the config file, using the default settings: {}", error);
+                return Self::default();
+            }
+        };
+        let default = Self::default();
+
+        Self {
+            theme: field(&table, "theme", default.theme),
+            executor_target_rate: field(&table, "executor_target_rate", default.executor_target_rate),
+            watchdog_threshold: field(&table, "watchdog_threshold", default.watchdog_threshold),
+            executor_priority: field(&table, "executor_priority", default.executor_priority),
+            executor_affinity_mask: field(&table, "executor_affinity_mask", default.executor_affinity_mask),
+            grid_size: field(&table, "grid_size", default.grid_size),
+            adapter_preference: field(&table, "adapter_preference", default.adapter_preference),
+            autosave_interval: field(&table, "autosave_interval", default.autosave_interval),
+            node_removal_confirmation_threshold: field(
+                &table,
+                "node_removal_confirmation_threshold",
+                default.node_removal_confirmation_threshold,
+            ),
+            reconnect_on_removal: field(&table, "reconnect_on_removal", default.reconnect_on_removal),
+            confirm_breaking_edits: field(&table, "confirm_breaking_edits", default.confirm_breaking_edits),
+            create_nodes_on_drop: field(&table, "create_nodes_on_drop", default.create_nodes_on_drop),
+            review_changes_before_saving: field(
+                &table,
+                "review_changes_before_saving",
+                default.review_changes_before_saving,
+            ),
+            show_throughput: field(&table, "show_throughput", default.show_throughput),
+            show_frame_rate: field(&table, "show_frame_rate", default.show_frame_rate),
+            show_execution_order: field(&table, "show_execution_order", default.show_execution_order),
+            dim_crossing_connections: field(
+                &table,
+                "dim_crossing_connections",
+                default.dim_crossing_connections,
+            ),
+            record_sessions: field(&table, "record_sessions", default.record_sessions),
+            max_list_length: field(&table, "max_list_length", default.max_list_length),
+            history_depth: field(&table, "history_depth", default.history_depth),
+            history_memory_cap: field(&table, "history_memory_cap", default.history_memory_cap),
+            plugin_directory: field(&table, "plugin_directory", default.plugin_directory),
+            recent_graphs: field(&table, "recent_graphs", default.recent_graphs),
+            favorite_nodes: field(&table, "favorite_nodes", default.favorite_nodes),
+            disabled_behaviours: field(&table, "disabled_behaviours", default.disabled_behaviours),
+            log_levels: field(&table, "log_levels", default.log_levels),
+            node_usage: field(&table, "node_usage", default.node_usage),
+            shortcuts: Keymap::parse(table.get("shortcuts")),
+        }
+    }
+
+    /// Lists the fields of the config that [`Config::parse`] replaces with their default values or
+    /// ignores. Fails if the config cannot be used at all.
+    pub fn validate(source: &str) -> Result<Vec<String>, String> {
+        let table = match source.parse::<toml::Value>() {
+            Ok(toml::Value::Table(table)) => table,
+            Ok(_) => return Err("The config file does not contain a table.".to_string()),
+            Err(error) => return Err(format!("Could not parse the config file: {}", error)),
+        };
+        let default = Self::default();
+        let default_table = match toml::Value::try_from(&default) {
+            Ok(toml::Value::Table(default_table)) => default_table,
+            _ => return Err("The default settings cannot be serialized.".to_string()),
+        };
+
+        if toml::Value::Table(default_table.clone())
+            .try_into::<Self>()
+            .map_or(true, |config| config != default)
+        {
+            return Err("The default settings do not survive being saved and loaded.".to_string());
+        }
+
+        let mut problems = Vec::new();
+
+        for (key, value) in &table {
+            // Parsed binding by binding, invalid bindings are reported as conflicts instead.
+            if key == "shortcuts" {
+                continue;
+            }
+
+            let mut merged = default_table.clone();
+
+            merged.insert(key.clone(), value.clone());
+
+            match toml::Value::Table(merged).try_into::<Self>() {
+                Err(error) => {
+                    problems.push(format!("Invalid value of `{}`, the default is used: {}", key, error))
+                }
+                // Unknown fields are ignored when deserializing, so they leave the defaults as they are.
+                // Optional fields are missing from the defaults too, but any value of them is kept.
+                Ok(config) if config == default && !default_table.contains_key(key) => {
+                    problems.push(format!("Unknown setting `{}`, it is ignored.", key))
+                }
+                Ok(_) => (),
+            }
+        }
+
+        Ok(problems)
+    }
+
+    pub fn load() -> Self {
+        let path = if let Some(path) = Self::path() {
+            path
+        } else {
+            warn!(Category::Ui, "Could not determine the config directory, using the default settings.");
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(source) => Self::parse(&source),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(error) => {
+                error!(Category::Ui, "Could not read the config file `{}`: {}", path.display(), error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or_else(|| "Could not determine the config directory.".to_string())?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+        }
+
+        let source = toml::to_string_pretty(self).map_err(|error| error.to_string())?;
+
+        fs::write(&path, source).map_err(|error| error.to_string())
+    }
+
+    /// The minimum duration of a single graph execution, if the execution rate is limited.
+    pub fn executor_target_period(&self) -> Option<std::time::Duration> {
+        if self.executor_target_rate > 0.0 && self.executor_target_rate.is_finite() {
+            Some(std::time::Duration::from_secs_f32(1.0 / self.executor_target_rate))
+        } else {
+            None
+        }
+    }
+
+    /// The duration after which an executing node is considered stuck, if the watchdog is enabled.
+    pub fn watchdog_threshold(&self) -> Option<std::time::Duration> {
+        if self.watchdog_threshold > 0.0 && self.watchdog_threshold.is_finite() {
+            Some(std::time::Duration::from_secs_f32(self.watchdog_threshold))
+        } else {
+            None
+        }
+    }
+
+    pub fn executor_affinity_mask(&self) -> Option<u64> {
+        if self.executor_affinity_mask != 0 {
+            Some(self.executor_affinity_mask)
+        } else {
+            None
+        }
+    }
+
+    pub fn autosave_interval(&self) -> Option<std::time::Duration> {
+        if self.autosave_interval > 0 {
+            Some(std::time::Duration::from_secs(self.autosave_interval))
+        } else {
+            None
+        }
+    }
+
+    pub fn node_removal_confirmation_threshold(&self) -> Option<usize> {
+        if self.node_removal_confirmation_threshold > 0 {
+            Some(self.node_removal_confirmation_threshold)
+        } else {
+            None
+        }
+    }
+
+    /// The number of values of the size to record, within both the depth and the memory cap.
+    pub fn history_capacity(&self, value_size: usize) -> usize {
+        self.history_depth.min(self.history_memory_cap / value_size.max(1))
+    }
+
+    /// Moves the graph to the front of the recently opened graphs.
+    pub fn record_recent_graph(&mut self, path: &Path) {
+        self.recent_graphs.retain(|recent| recent != path);
+        self.recent_graphs.insert(0, path.to_path_buf());
+        self.recent_graphs.truncate(Self::MAX_RECENT_GRAPHS);
+    }
+
+    /// Pins the kind of node to the favorites of the node palette, or unpins it.
+    pub fn toggle_favorite_node(&mut self, name: &str) {
+        if !self.favorite_nodes.remove(name) {
+            self.favorite_nodes.insert(name.to_string());
+        }
+    }
+
+    pub fn grid_size(&self) -> Option<f32> {
+        if self.grid_size > 0.0 && self.grid_size.is_finite() {
+            Some(self.grid_size)
+        } else {
+            None
+        }
+    }
+}
+
+fn field<T: DeserializeOwned>(table: &toml::value::Table, key: &str, default: T) -> T {
+    match table.get(key) {
+        Some(value) => value.clone().try_into().unwrap_or_else(|error| {
+            warn!(
+                Category::Ui,
+                "Invalid value of `{}` in the config file, using the default value: {}", key, error
+            );
+            default
+        }),
+        None => default,
+    }
+}
+
+enum ConfigWriteRequest {
+    Save(Arc<Config>),
+    /// Writes the pending settings immediately and acknowledges it.
+    Flush(Sender<()>),
+}
+
+/// Writes the latest of the settings received within [`SAVE_DELAY`] of each other, until all
+/// senders are dropped.
+fn run_writer(receiver: flume::Receiver<ConfigWriteRequest>) {
+    // The settings to write along with the time to write them at.
+    let mut pending: Option<(Arc<Config>, Instant)> = None;
+
+    loop {
+        let request = match &pending {
+            Some((_, deadline)) => receiver.recv_deadline(*deadline),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match request {
+            Ok(ConfigWriteRequest::Save(config)) => {
+                // Not postponed by further changes, so that continuous changes are still written.
+                let deadline =
+                    pending.take().map_or_else(|| Instant::now() + SAVE_DELAY, |(_, deadline)| deadline);
+
+                pending = Some((config, deadline));
+            }
+            Ok(ConfigWriteRequest::Flush(acknowledgement)) => {
+                write_pending(pending.take());
+                let _ = acknowledgement.send(());
+            }
+            Err(RecvTimeoutError::Timeout) => write_pending(pending.take()),
+            Err(RecvTimeoutError::Disconnected) => {
+                write_pending(pending.take());
+                return;
+            }
+        }
+    }
+}
+
+fn write_pending(pending: Option<(Arc<Config>, Instant)>) {
+    if let Some((config, _)) = pending {
+        if let Err(error) = config.save() {
+            error!(Category::Ui, "Could not save the settings: {}", error);
+        }
+    }
+}
+
+/// The settings shared between the UI thread and the executor.
+/// The executor polls [`SharedConfig::revision`] to find out whether the settings have changed.
+#[derive(Debug)]
+pub struct SharedConfig {
+    current: ArcSwap<Config>,
+    revision: AtomicUsize,
+    /// Sends the modified settings to the writer thread, `None` if they are not persisted.
+    writer: Option<Sender<ConfigWriteRequest>>,
+}
+
+impl SharedConfig {
+    /// Settings kept in memory only, such as those of headless contexts.
+    pub fn new(config: Config) -> Self {
+        Self { current: ArcSwap::from_pointee(config), revision: AtomicUsize::new(0), writer: None }
+    }
+
+    /// Settings written into the config file whenever they are modified, see
+    /// [`SharedConfig::modify`].
+    pub fn persisted(config: Config) -> Self {
+        let (sender, receiver) = flume::unbounded();
+
+        thread::Builder::new()
+            .name("config-writer".to_string())
+            .spawn(move || run_writer(receiver))
+            .expect("Could not spawn the config writer thread.");
+
+        Self { writer: Some(sender), ..Self::new(config) }
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        self.current.load_full()
+    }
+
+    /// Incremented every time the settings are changed.
+    pub fn revision(&self) -> usize {
+        self.revision.load(Ordering::SeqCst)
+    }
+
+    /// Applies the modification and schedules the settings to be saved, if they have been changed.
+    pub fn modify(&self, modify: impl FnOnce(&mut Config)) {
+        let current = self.get();
+        let mut config = (*current).clone();
+
+        modify(&mut config);
+
+        if config == *current {
+            return;
+        }
+
+        let config = Arc::new(config);
+
+        if let Some(writer) = &self.writer {
+            let _ = writer.send(ConfigWriteRequest::Save(config.clone()));
+        }
+
+        self.current.store(config);
+        self.revision.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Blocks until the modified settings have been written, such as before exiting.
+    pub fn flush(&self) {
+        if let Some(writer) = &self.writer {
+            let (sender, receiver) = flume::bounded(1);
+
+            if writer.send(ConfigWriteRequest::Flush(sender)).is_ok() {
+                let _ = receiver.recv();
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! A short history of removed connections, so that they can be recreated.
+//!
+//! Connections are recorded under both of the nodes they connected, and are not updated when the
+//! graph changes. Entries referring to removed nodes or to channels that cannot be connected
+//! anymore are filtered out when the candidates for reconnection are collected.
+
+use crate::graph::{ChannelIdentifier, Connection, ExecutionGraph};
+use crate::ChannelDirection;
+use petgraph::graph::NodeIndex;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+/// The number of removed connections remembered per node, older ones are forgotten.
+const HISTORY_LENGTH: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct RemovedConnection {
+    pub connection: Connection,
+    pub removed_at: Instant,
+}
+
+/// A removed connection which can currently be recreated.
+#[derive(Debug, Clone)]
+pub struct ReconnectCandidate {
+    pub connection: Connection,
+    pub removed_at: Instant,
+    /// The labels of the output and input channels, see [`ExecutionGraph::channel_label`].
+    pub from_label: String,
+    pub to_label: String,
+}
+
+impl ReconnectCandidate {
+    /// The label of the channel on the opposite side of the connection from `channel`.
+    pub fn counterpart_label(&self, channel: ChannelIdentifier) -> &str {
+        match channel.channel_direction {
+            ChannelDirection::In => &self.from_label,
+            ChannelDirection::Out => &self.to_label,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ConnectionHistory {
+    /// The most recently removed connections of each node, the newest first.
+    per_node: HashMap<NodeIndex, VecDeque<RemovedConnection>>,
+}
+
+impl ConnectionHistory {
+    pub fn record(&mut self, connection: Connection) {
+        let removed_at = Instant::now();
+        let nodes = [connection.from().node_index, connection.to().node_index];
+
+        for node in nodes.iter() {
+            let history = self.per_node.entry(*node).or_insert_with(VecDeque::new);
+
+            history.retain(|removed| removed.connection != connection);
+            history.push_front(RemovedConnection { connection: connection.clone(), removed_at });
+            history.truncate(HISTORY_LENGTH);
+        }
+    }
+
+    /// Forgets all connections, used when the graph is replaced.
+    pub fn clear(&mut self) {
+        self.per_node.clear();
+    }
+
+    /// Collects the removed connections which can be recreated in the current graph, the most
+    /// recently removed first.
+    pub fn candidates(&self, graph: &ExecutionGraph) -> Vec<ReconnectCandidate> {
+        let mut candidates: Vec<ReconnectCandidate> = Vec::new();
+
+        for removed in self.per_node.values().flatten() {
+            if candidates.iter().any(|candidate| candidate.connection == removed.connection)
+                || !graph.can_connect(&removed.connection)
+            {
+                continue;
+            }
+
+            let from_label = graph.channel_label(removed.connection.from());
+            let to_label = graph.channel_label(removed.connection.to());
+
+            if let (Some(from_label), Some(to_label)) = (from_label, to_label) {
+                candidates.push(ReconnectCandidate {
+                    connection: removed.connection.clone(),
+                    removed_at: removed.removed_at,
+                    from_label,
+                    to_label,
+                });
+            }
+        }
+
+        candidates.sort_by(|a, b| b.removed_at.cmp(&a.removed_at));
+        candidates
+    }
+
+    /// The most recently removed connection which can be recreated in the current graph.
+    pub fn most_recent(&self, graph: &ExecutionGraph) -> Option<Connection> {
+        self.per_node
+            .values()
+            .flatten()
+            .filter(|removed| graph.can_connect(&removed.connection))
+            .max_by_key(|removed| removed.removed_at)
+            .map(|removed| removed.connection.clone())
+    }
+}
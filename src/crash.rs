@@ -0,0 +1,274 @@
+//! Reports of panics, so that the context of a crash is not lost.
+//!
+//! The panic hook writes a report into the cache directory, along with the last snapshot of the
+//! focused graph as a recovery file. The panicking thread may own the locks of the live
+//! structures, the hook therefore only reads atomics and the snapshot serialized ahead of time by
+//! the UI thread, see [`CrashReporter::snapshot`]. The report is offered on the next startup, see
+//! [`find_report`].
+
+use crate::graph::alloc::Allocator;
+use crate::graph::file::{GraphFile, GraphMetadata};
+use crate::graph::{ExecutingGeneration, ExecutionWatchdog, Graph, NodeIndex};
+use arc_swap::ArcSwapOption;
+use std::backtrace::Backtrace;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::panic::{self, PanicInfo};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The minimum duration between two snapshots taken by the UI thread.
+const MIN_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+const REPORT_FILE_NAME: &str = "crash-report.txt";
+const RECOVERY_FILE_NAME: &str = "recovery.json";
+/// Separates the summary of the report from the backtrace.
+const BACKTRACE_HEADER: &str = "Backtrace:";
+
+thread_local! {
+    /// Set while the hook is running on the thread, so that a panic within the hook is not
+    /// reported again.
+    static IN_HOOK: Cell<bool> = Cell::new(false);
+}
+
+fn directory() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("dvsynth").join("crash"))
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+/// The state of the focused graph, prepared by the UI thread for the panic hook.
+struct GraphSnapshot {
+    /// The behaviour names and the titles of the nodes.
+    nodes: HashMap<NodeIndex, (String, String)>,
+    /// The graph serialized as a [`GraphFile`], `None` if it could not be serialized.
+    serialized: Option<Vec<u8>>,
+    /// The file the graph was last saved to or opened from.
+    path: Option<PathBuf>,
+}
+
+/// The state read by the panic hook, without taking any locks.
+struct CrashContext {
+    watchdog: Arc<ExecutionWatchdog>,
+    executing_generation: Arc<ExecutingGeneration>,
+    snapshot: ArcSwapOption<GraphSnapshot>,
+}
+
+impl CrashContext {
+    fn write_report(&self, info: &PanicInfo) -> io::Result<PathBuf> {
+        let directory = directory()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the cache directory is unknown"))?;
+
+        fs::create_dir_all(&directory)?;
+
+        let snapshot = self.snapshot.load_full();
+        let recovery_path = directory.join(RECOVERY_FILE_NAME);
+        let serialized = snapshot.as_ref().and_then(|snapshot| snapshot.serialized.as_ref());
+        let recovery_written =
+            serialized.map_or(false, |serialized| fs::write(&recovery_path, serialized).is_ok());
+
+        // Do not offer the snapshot of an earlier crash.
+        if !recovery_written {
+            let _ = fs::remove_file(&recovery_path);
+        }
+
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("Box<dyn Any>");
+        let location = info
+            .location()
+            .map(|location| format!("{}:{}:{}", location.file(), location.line(), location.column()))
+            .unwrap_or_else(|| "unknown".to_string());
+        let thread = thread::current();
+        let generation = self
+            .executing_generation
+            .get()
+            .map(|generation| generation.to_string())
+            .unwrap_or_else(|| "none".to_string());
+        let executing_node = match self.watchdog.current_task() {
+            Some(task) => {
+                let (behaviour, title) = snapshot
+                    .as_ref()
+                    .and_then(|snapshot| snapshot.nodes.get(&task.node))
+                    .map(|(behaviour, title)| (behaviour.as_str(), title.as_str()))
+                    .unwrap_or(("unknown behaviour", "unknown"));
+
+                format!(
+                    "#{} `{}` ({}), executing for {:?}",
+                    task.node.index(),
+                    title,
+                    behaviour,
+                    task.elapsed
+                )
+            }
+            None => "none".to_string(),
+        };
+        let statistics = Allocator::get().statistics();
+        let document = snapshot
+            .as_ref()
+            .and_then(|snapshot| snapshot.path.as_ref())
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "unsaved".to_string());
+        let recovery =
+            if recovery_written { recovery_path.display().to_string() } else { "not available".to_string() };
+
+        // A report of an earlier panic of this session is kept, it is usually the cause.
+        let report_path = directory.join(REPORT_FILE_NAME);
+        let mut file = OpenOptions::new().create(true).append(true).open(&report_path)?;
+
+        writeln!(file, "DVSynth {} panicked", env!("CARGO_PKG_VERSION"))?;
+        writeln!(file, "Time: {} (seconds since the Unix epoch)", unix_time())?;
+        writeln!(file, "Thread: {}", thread.name().unwrap_or("unnamed"))?;
+        writeln!(file, "Message: {}", message)?;
+        writeln!(file, "Location: {}", location)?;
+        writeln!(file, "Executing schedule generation: {}", generation)?;
+        writeln!(file, "Executing node: {}", executing_node)?;
+        writeln!(
+            file,
+            "Allocator: {} live, {} allocated, {} deallocated, {} double frees",
+            statistics.live,
+            statistics.total_allocated,
+            statistics.total_deallocated,
+            statistics.double_frees
+        )?;
+        writeln!(file, "Document: {}", document)?;
+        writeln!(file, "Recovery snapshot: {}", recovery)?;
+        writeln!(file, "{}", BACKTRACE_HEADER)?;
+        writeln!(file, "{}", Backtrace::force_capture())?;
+        writeln!(file)?;
+
+        Ok(report_path)
+    }
+}
+
+/// Installs the panic hook, which runs the previously installed hook before writing the report.
+pub fn install(
+    watchdog: Arc<ExecutionWatchdog>,
+    executing_generation: Arc<ExecutingGeneration>,
+) -> CrashReporter {
+    let context =
+        Arc::new(CrashContext { watchdog, executing_generation, snapshot: ArcSwapOption::from(None) });
+    let hook_context = context.clone();
+    let previous_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        previous_hook(info);
+
+        // The thread local may already be destroyed, if the thread is exiting.
+        let reentered = IN_HOOK.try_with(|in_hook| in_hook.replace(true)).unwrap_or(true);
+
+        if reentered {
+            return;
+        }
+
+        // The logger locks its records, write to the standard error output directly instead.
+        match hook_context.write_report(info) {
+            Ok(path) => {
+                let _ = writeln!(io::stderr(), "The crash report was written to `{}`.", path.display());
+            }
+            Err(error) => {
+                let _ = writeln!(io::stderr(), "Could not write the crash report: {}", error);
+            }
+        }
+
+        let _ = IN_HOOK.try_with(|in_hook| in_hook.set(false));
+    }));
+
+    CrashReporter { context, dirty: true, last_snapshot: None }
+}
+
+/// The UI side of the crash reporter, which keeps the snapshot of the focused graph up to date.
+pub struct CrashReporter {
+    context: Arc<CrashContext>,
+    /// Whether the graph has changed since the last snapshot.
+    dirty: bool,
+    last_snapshot: Option<Instant>,
+}
+
+impl CrashReporter {
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Serializes the graph for the panic hook, if the graph has changed and the previous snapshot
+    /// is not too recent. Should be called after every update.
+    pub fn snapshot(&mut self, graph: &Graph, metadata: &GraphMetadata, path: Option<&Path>) {
+        if !self.dirty {
+            return;
+        }
+
+        if let Some(last_snapshot) = self.last_snapshot {
+            if last_snapshot.elapsed() < MIN_SNAPSHOT_INTERVAL {
+                return;
+            }
+        }
+
+        self.dirty = false;
+        self.last_snapshot = Some(Instant::now());
+
+        let nodes = graph
+            .node_indices()
+            .map(|node_index| {
+                let node = &graph[node_index];
+                (node_index, (node.behaviour.name().to_string(), node.title.clone()))
+            })
+            .collect();
+        let serialized = serde_json::to_vec_pretty(&GraphFile::from_graph(graph, metadata)).ok();
+
+        self.context.snapshot.store(Some(Arc::new(GraphSnapshot {
+            nodes,
+            serialized,
+            path: path.map(Path::to_path_buf),
+        })));
+    }
+}
+
+/// A report left by a crash of a previous session.
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    pub path: PathBuf,
+    /// The report without the backtrace.
+    pub summary: String,
+    /// The snapshot of the graph taken before the crash.
+    pub recovery_path: Option<PathBuf>,
+}
+
+impl CrashReport {
+    /// Opens the directory containing the report in the file manager.
+    pub fn open_location(&self) -> io::Result<()> {
+        let directory = self.path.parent().unwrap_or(&self.path);
+        let program = if cfg!(target_os = "windows") {
+            "explorer"
+        } else if cfg!(target_os = "macos") {
+            "open"
+        } else {
+            "xdg-open"
+        };
+
+        Command::new(program).arg(directory).spawn().map(|_| ())
+    }
+}
+
+/// Finds the report of a crash of the previous session. The report is renamed, so that it is only
+/// offered once, and so that it is not appended to by the next crash.
+pub fn find_report() -> Option<CrashReport> {
+    let directory = directory()?;
+    let source = fs::read_to_string(directory.join(REPORT_FILE_NAME)).ok()?;
+    let path = directory.join(format!("crash-report-{}.txt", unix_time()));
+
+    fs::rename(directory.join(REPORT_FILE_NAME), &path).ok()?;
+
+    let summary = source.lines().take_while(|line| *line != BACKTRACE_HEADER).collect::<Vec<_>>().join("\n");
+    let recovery_path = Some(directory.join(RECOVERY_FILE_NAME)).filter(|path| path.is_file());
+
+    Some(CrashReport { path, summary, recovery_path })
+}
@@ -0,0 +1,115 @@
+//! The graphs open in the tabs of the workspace.
+//!
+//! Each document owns its graph along with the state of editing it, such as the file it is
+//! associated with and the layout of its panes. The focused document is executed, the schedule
+//! slot shared with the executor is handed over whenever another document is focused, see
+//! [`GraphController::take_over_execution`]. Background documents are only executed while they
+//! are pinned to keep running, see [`PinnedSchedules`](crate::graph::PinnedSchedules).
+
+use crate::connection_history::ConnectionHistory;
+use crate::graph::controller::GraphController;
+use crate::graph::file::{GraphFile, GraphFileError, GraphMetadata};
+use crate::graph::{ExecutionGraph, Graph};
+use crate::safe_mode::Substitutions;
+use crate::widgets::{FloatingPanesBehaviourState, FloatingPanesState};
+use petgraph::graph::NodeIndex;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The maximum number of characters of the title of a tab.
+const MAX_TAB_TITLE_LENGTH: usize = 24;
+
+/// The next identifier to be assigned by [`DocumentId::generate`].
+static NEXT_DOCUMENT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies an open document, unlike the position of its tab, which changes as other tabs are
+/// closed. Messages of the graph view are tagged with it, so that they are only applied to the
+/// document they have been produced for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DocumentId(u64);
+
+impl DocumentId {
+    pub fn generate() -> Self {
+        DocumentId(NEXT_DOCUMENT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+pub struct Document {
+    pub id: DocumentId,
+    pub graph: GraphController,
+    /// The file the graph was last saved to or opened from.
+    pub path: Option<PathBuf>,
+    pub metadata: GraphMetadata,
+    /// Whether the graph has changed since it was last saved or opened.
+    pub unsaved_changes: bool,
+    pub connection_history: ConnectionHistory,
+    pub floating_panes_state: FloatingPanesState,
+    pub floating_panes_content_state: FloatingPanesBehaviourState,
+    /// Whether the graph keeps being executed while another document is focused. Not persisted.
+    pub run_in_background: bool,
+    /// The collapsed node the cursor is resting on, whose content is previewed after a delay.
+    pub hovered_collapsed_node: Option<NodeIndex>,
+}
+
+impl Document {
+    pub fn new(graph: ExecutionGraph) -> Self {
+        Self {
+            id: DocumentId::generate(),
+            graph: GraphController::new(graph),
+            path: None,
+            metadata: Default::default(),
+            unsaved_changes: false,
+            connection_history: Default::default(),
+            floating_panes_state: Default::default(),
+            floating_panes_content_state: Default::default(),
+            run_in_background: false,
+            hovered_collapsed_node: None,
+        }
+    }
+
+    /// An empty, unsaved document, which is not executed until it is focused.
+    pub fn empty() -> Self {
+        Self::new(Graph::new().into())
+    }
+
+    /// The title stored in the metadata, or the name of the file of the document. `None` if the
+    /// document has neither.
+    pub fn name(&self) -> Option<String> {
+        if !self.metadata.title.is_empty() {
+            Some(self.metadata.title.clone())
+        } else {
+            self.path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|file_name| file_name.to_string_lossy().into_owned())
+        }
+    }
+
+    /// Recreates the graph from its serialized form with the substitutions, which restores the
+    /// nodes replaced by placeholders that are no longer chosen by them, see [`crate::safe_mode`].
+    pub fn restore_placeholders(&mut self, substitutions: &Substitutions) -> Result<(), GraphFileError> {
+        let graph = GraphFile::from_graph(&self.graph, &self.metadata).into_graph(substitutions)?;
+
+        self.graph.replace(graph);
+        // The node indices of the view state and of the history refer to the replaced graph.
+        self.floating_panes_content_state = Default::default();
+        self.hovered_collapsed_node = None;
+        self.connection_history.clear();
+
+        Ok(())
+    }
+
+    /// The title of the tab of the document, marked if the document has unsaved changes.
+    pub fn tab_title(&self) -> String {
+        let name = crate::util::truncate(
+            self.name().unwrap_or_else(|| "Untitled".to_string()),
+            MAX_TAB_TITLE_LENGTH,
+        );
+
+        if self.unsaved_changes {
+            format!("{} *", name)
+        } else {
+            name
+        }
+    }
+}
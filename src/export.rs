@@ -0,0 +1,222 @@
+//! Rendering of the whole graph into an image, independently of the current viewport.
+//!
+//! The panes are laid out and drawn into primitives on the UI thread, see [`prepare_graph_image`].
+//! The primitives are then rendered on a worker thread, see [`GraphImage::save`], in tiles no
+//! larger than the maximum texture size, which are stitched together into the image.
+
+use crate::graph::{OwnedTexture, Renderer};
+use crate::logging::Category;
+use crate::widgets::{FloatingPanesBehaviourState, FloatingPanesState};
+use crate::ApplicationState;
+use iced_graphics::{Primitive, Viewport};
+use iced_native::{layout::Limits, mouse, Cache, Point, Size, UserInterface, Widget};
+use iced_wgpu::wgpu;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::thread;
+use vek::Vec2;
+
+/// The margin around the bounding box of all panes, in logical pixels.
+const MARGIN: f32 = 32.0;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+/// The maximum width and height of the image, larger graphs are downscaled to fit, so that the
+/// image fits in memory.
+const MAX_IMAGE_DIMENSION: u32 = 16384;
+
+#[derive(Debug)]
+pub enum ExportError {
+    EmptyGraph,
+    Image(image::ImageError),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::EmptyGraph => write!(f, "The graph contains no nodes."),
+            ExportError::Image(error) => write!(f, "Could not save the image: {}", error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExportSummary {
+    /// The size of the image in pixels.
+    pub size: Vec2<u32>,
+    /// The ratio of image pixels to logical pixels, less than `1.0` if the graph was downscaled.
+    pub scale: f32,
+}
+
+/// The drawn panes and connections of the graph, to be rendered into an image.
+pub struct GraphImage {
+    output: (Primitive, mouse::Interaction),
+    /// The size of the image in pixels.
+    size: Vec2<u32>,
+    /// The ratio of image pixels to logical pixels.
+    scale: f32,
+}
+
+/// Draws all panes and connections of the graph. Cheap compared to rendering the image, which is
+/// left to [`GraphImage::save`].
+pub fn prepare_graph_image(
+    state: &mut ApplicationState,
+    renderer: &Renderer,
+) -> Result<GraphImage, ExportError> {
+    let config = state.config.get();
+    let theme = config.theme.theme();
+    let mut ui_renderer = iced_wgpu::Renderer::new(iced_wgpu::Backend::new(
+        &renderer.device,
+        iced_wgpu::Settings { format: FORMAT, ..iced_wgpu::Settings::default() },
+    ));
+    // Use separate states, so that the current pan and hovered elements do not affect the image.
+    let mut panes_state = FloatingPanesState::default();
+    let mut content_state = FloatingPanesBehaviourState::default();
+    let validation_errors = state.document.graph.validation_errors().clone();
+
+    // Lay out the panes without any limits to find their bounding box.
+    let (min, max) = {
+        let panes = ApplicationState::view_graph(
+            state.document.graph.graph_mut(),
+            &mut panes_state,
+            &mut content_state,
+            &validation_errors,
+            Vec::new(),
+            &config,
+            theme.as_ref(),
+        );
+        let layout = Widget::layout(&panes, &ui_renderer, &Limits::new(Size::ZERO, Size::INFINITY));
+
+        layout
+            .children()
+            .iter()
+            .map(|child| {
+                let bounds = child.bounds();
+                let min = Vec2::new(bounds.x, bounds.y);
+                (min, min + Vec2::new(bounds.width, bounds.height))
+            })
+            .fold_first(|(min_a, max_a), (min_b, max_b)| {
+                (Vec2::partial_min(min_a, min_b), Vec2::partial_max(max_a, max_b))
+            })
+            .ok_or(ExportError::EmptyGraph)?
+    };
+    let logical_size = max - min + Vec2::broadcast(2.0 * MARGIN);
+    let scale = (MAX_IMAGE_DIMENSION as f32 / logical_size.reduce_partial_max()).min(1.0);
+    let size = (logical_size * scale).map(|coord| (coord.ceil() as u32).max(1));
+
+    panes_state.panes_offset = Vec2::broadcast(MARGIN) - min;
+
+    let panes = ApplicationState::view_graph(
+        state.document.graph.graph_mut(),
+        &mut panes_state,
+        &mut content_state,
+        &validation_errors,
+        Vec::new(),
+        &config,
+        theme.as_ref(),
+    );
+    let mut user_interface = UserInterface::build(
+        panes,
+        Size::new(logical_size[0], logical_size[1]),
+        Cache::default(),
+        &mut ui_renderer,
+    );
+    // Place the cursor outside of the image, so that nothing is drawn as hovered.
+    let output = user_interface.draw(&mut ui_renderer, Point::new(-1.0, -1.0));
+
+    Ok(GraphImage { output, size, scale })
+}
+
+impl GraphImage {
+    /// Saves the image on a worker thread, logging the outcome.
+    pub fn spawn_save(self, renderer: Renderer, path: PathBuf) {
+        thread::Builder::new()
+            .name("graph-export".to_string())
+            .spawn(move || match self.save(&renderer, &path) {
+                Ok(summary) if summary.scale < 1.0 => info!(
+                    Category::Ui,
+                    "Exported the graph to `{}`, downscaled to {:.0}% ({}x{}) to fit the image size limit.",
+                    path.display(),
+                    summary.scale * 100.0,
+                    summary.size[0],
+                    summary.size[1],
+                ),
+                Ok(summary) => info!(
+                    Category::Ui,
+                    "Exported the graph to `{}` ({}x{}).",
+                    path.display(),
+                    summary.size[0],
+                    summary.size[1],
+                ),
+                Err(error) => error!(Category::Ui, "Could not export the graph: {}", error),
+            })
+            .expect("Could not spawn the graph export thread.");
+    }
+
+    /// Renders the image and saves it as a PNG image. Blocks until the GPU has finished rendering,
+    /// to be called off the UI thread.
+    pub fn save(self, renderer: &Renderer, path: &Path) -> Result<ExportSummary, ExportError> {
+        let mut ui_renderer = iced_wgpu::Renderer::new(iced_wgpu::Backend::new(
+            &renderer.device,
+            iced_wgpu::Settings { format: FORMAT, ..iced_wgpu::Settings::default() },
+        ));
+        let tile_dimension = renderer.capabilities.max_texture_dimension_2d;
+        let row_length = self.size[0] as usize * 4;
+        let mut data = vec![0; row_length * self.size[1] as usize];
+
+        for tile_y in (0..self.size[1]).step_by(tile_dimension as usize) {
+            for tile_x in (0..self.size[0]).step_by(tile_dimension as usize) {
+                let tile_position = Vec2::new(tile_x, tile_y);
+                let tile_size = (self.size - tile_position).map(|coord| coord.min(tile_dimension));
+                let tile_data = self.render_tile(renderer, &mut ui_renderer, tile_position, tile_size);
+                let tile_row_length = tile_size[0] as usize * 4;
+
+                for (row, tile_row) in tile_data.chunks_exact(tile_row_length).enumerate() {
+                    let start = (tile_y as usize + row) * row_length + tile_x as usize * 4;
+
+                    data[start..start + tile_row_length].copy_from_slice(tile_row);
+                }
+            }
+        }
+
+        image::save_buffer(path, &data, self.size[0], self.size[1], image::ColorType::Rgba8)
+            .map_err(ExportError::Image)?;
+
+        Ok(ExportSummary { size: self.size, scale: self.scale })
+    }
+
+    /// Renders the part of the image at the position in pixels, returning its pixels.
+    fn render_tile(
+        &self,
+        renderer: &Renderer,
+        ui_renderer: &mut iced_wgpu::Renderer,
+        position: Vec2<u32>,
+        size: Vec2<u32>,
+    ) -> Vec<u8> {
+        let viewport = Viewport::with_physical_size(Size::new(size[0], size[1]), self.scale as f64);
+        let (primitive, interaction) = &self.output;
+        let output = (
+            Primitive::Translate {
+                translation: position.map(|coord| -(coord as f32) / self.scale).into_array().into(),
+                content: Box::new(primitive.clone()),
+            },
+            *interaction,
+        );
+        let texture = OwnedTexture::new(&renderer.device, size, FORMAT);
+        let mut encoder =
+            renderer.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let mut staging_belt = wgpu::util::StagingBelt::new(5 * 1024);
+
+        texture.clear(&mut encoder);
+        ui_renderer.backend_mut().draw(
+            &renderer.device,
+            &mut staging_belt,
+            &mut encoder,
+            &texture.view,
+            &viewport,
+            &output,
+            &[] as &[String],
+        );
+        staging_belt.finish();
+        renderer.queue.submit(Some(encoder.finish()));
+        texture.read(renderer)
+    }
+}
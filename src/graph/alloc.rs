@@ -3,6 +3,7 @@ use std::borrow::Cow;
 use std::collections::HashSet;
 use std::collections::{hash_map::Entry, HashMap};
 use std::convert::TryInto;
+use std::fmt::Display;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Mutex, RwLock};
@@ -10,7 +11,9 @@ use std::sync::{Mutex, RwLock};
 use crossbeam::deque::Injector;
 use crossbeam::deque::Steal;
 use lazy_static::lazy_static;
+use serde::Serialize;
 
+use crate::logging::Category;
 use crate::node::behaviour::AllocatorHandle;
 use crate::node::{
     AllocationPointer, Bytes, BytesMut, DynTypeDescriptor, Refcounter, SizedTypeExt, TypeEnum, TypeExt,
@@ -21,7 +24,9 @@ use super::{DynTypeTrait, NodeIndex, Schedule};
 
 #[derive(Default, Debug)]
 pub struct TaskRefCounters {
-    pub counters: RwLock<HashMap<NodeIndex, Mutex<TaskRefCounter>>>,
+    /// The generation of the schedule the counters have been prepared for.
+    pub generation: usize,
+    pub counters: HashMap<NodeIndex, Mutex<TaskRefCounter>>,
 }
 
 /// Counts the changes to refcounts that happen during a single invocation of a task.
@@ -31,6 +36,73 @@ pub struct TaskRefCounter {
     pub refcount_deltas: HashMap<AllocationPointer, isize>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyRefcountsError {
+    /// A thread panicked while holding the task refcounters.
+    Poisoned,
+    /// The refcounters have been prepared for a different schedule than the one of the task.
+    GenerationMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// The node is not part of the schedule the refcounters have been prepared for.
+    UnknownTask(NodeIndex),
+    InvalidPointer(AllocationPointer),
+}
+
+impl Display for ApplyRefcountsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use ApplyRefcountsError::*;
+        match self {
+            Poisoned => write!(f, "The task refcounters are poisoned."),
+            GenerationMismatch { expected, found } => write!(
+                f,
+                "The task refcounters belong to schedule generation {}, but the task belongs to generation {}.",
+                found, expected
+            ),
+            UnknownTask(node) => write!(f, "Node #{} has no task refcounter.", node.index()),
+            InvalidPointer(ptr) => write!(f, "No allocation exists for pointer {}.", ptr.as_u64()),
+        }
+    }
+}
+
+/// Alters the global refcount directly, bypassing the task-wise refcounts.
+/// Used to release values outside of the execution of tasks.
+pub(crate) struct GlobalRefcounter;
+
+impl Refcounter for GlobalRefcounter {
+    fn refcount_increment(&self, ptr: AllocationPointer) {
+        if unsafe { Allocator::get().refcount_global_add(ptr, 1) }.is_err() {
+            error!(
+                Category::Allocator,
+                "Attempt to increment the refcount of an invalid pointer {}.",
+                ptr.as_u64()
+            );
+        }
+    }
+
+    fn refcount_decrement(&self, ptr: AllocationPointer) {
+        if unsafe { Allocator::get().refcount_global_add(ptr, -1) }.is_err() {
+            error!(
+                Category::Allocator,
+                "Attempt to decrement the refcount of an invalid pointer {}.",
+                ptr.as_u64()
+            );
+        }
+    }
+}
+
+/// A snapshot of the allocator counters, used to detect leaks and double frees.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct AllocatorStatistics {
+    /// The number of allocations currently in use.
+    pub live: usize,
+    pub total_allocated: usize,
+    pub total_deallocated: usize,
+    /// The number of attempts to free an allocation that has already been freed.
+    pub double_frees: usize,
+}
+
 impl AllocationPointer {
     pub(crate) fn new(index: u64) -> Self {
         Self { index }
@@ -141,7 +213,7 @@ impl AllocationType {
     pub fn as_mut(&mut self) -> BytesMut<'_> {
         match self {
             AllocationType::Bytes(inner) => BytesMut::Bytes(inner.as_mut()),
-            AllocationType::Object { ty_name, data } => BytesMut::Object { ty_name, data: data.as_mut() },
+            AllocationType::Object { ty_name, data } => BytesMut::Object { ty_name, data },
         }
     }
 }
@@ -285,7 +357,10 @@ pub struct Allocator {
     // collector: Collector,
     // allocations: Pool<Allocation>,
     /// For task-wise refcounting
-    task_ref_counters: TaskRefCounters,
+    task_ref_counters: RwLock<TaskRefCounters>,
+    total_allocated: AtomicUsize,
+    total_deallocated: AtomicUsize,
+    double_frees: AtomicUsize,
     // inner: RwLock<AllocatorImpl>,
 }
 
@@ -303,18 +378,88 @@ impl Allocator {
     //   need to be able to signal the removed node to be destructured. Keep generation ID based
     //   on the number of times the node was removed?
     pub(crate) fn prepare_for_schedule(&self, schedule: &Schedule) {
-        let mut task_ref_counters = self.task_ref_counters.counters.write().unwrap();
-        task_ref_counters.clear();
+        let mut task_ref_counters = self.task_ref_counters.write().unwrap();
+
+        // Deltas are applied after each task execution, so there should be none left over.
+        // Apply them anyway, instead of leaking the allocations they refer to.
+        for (node, task_ref_counter) in task_ref_counters.counters.drain() {
+            let task_ref_counter = task_ref_counter.into_inner().unwrap_or_else(|error| error.into_inner());
+
+            for (ptr, delta) in task_ref_counter.refcount_deltas {
+                if delta != 0 {
+                    warn!(
+                        Category::Allocator,
+                        "Applying a leftover refcount delta of {} to pointer {} of node #{} from schedule generation {}.",
+                        delta,
+                        ptr.as_u64(),
+                        node.index(),
+                        task_ref_counters.generation
+                    );
+
+                    unsafe {
+                        let _ = self.refcount_global_add(ptr, delta);
+                    }
+                }
+            }
+        }
+
+        task_ref_counters.generation = schedule.generation;
 
         for task in &*schedule.tasks {
             if let Some(task) = task {
-                task_ref_counters.insert(task.node_index, Default::default());
+                task_ref_counters.counters.insert(task.node_index, Default::default());
             }
         }
     }
 
+    /// The generation of the schedule the task refcounters have last been prepared for.
+    pub(crate) fn prepared_generation(&self) -> usize {
+        self.task_ref_counters.read().unwrap().generation
+    }
+
+    pub fn statistics(&self) -> AllocatorStatistics {
+        let total_allocated = self.total_allocated.load(Ordering::SeqCst);
+        let total_deallocated = self.total_deallocated.load(Ordering::SeqCst);
+
+        AllocatorStatistics {
+            live: total_allocated.saturating_sub(total_deallocated),
+            total_allocated,
+            total_deallocated,
+            double_frees: self.double_frees.load(Ordering::SeqCst),
+        }
+    }
+
     /// Allocates the value with refcount set to 1.
     fn allocate_value(&self, inner: AllocationInner, handle: AllocatorHandle<'_, '_>) -> AllocationPointer {
+        let ptr = self.claim(inner);
+
+        unsafe {
+            self.refcount_owned_increment(ptr, handle.node).unwrap();
+        }
+
+        ptr
+    }
+
+    /// Allocates the value with the global refcount set to 1, for values created outside of the
+    /// execution of tasks. Released with [`GlobalRefcounter`].
+    #[cfg(test)]
+    pub(crate) fn allocate_object_global<T: DynTypeTrait>(
+        &self,
+        descriptor: T::Descriptor,
+    ) -> AllocationPointer {
+        let ty = descriptor.get_type();
+        let value = T::create_value_from_descriptor(descriptor);
+        let ptr = self.claim(AllocationInner::new_object(value, ty));
+
+        unsafe {
+            self.refcount_global_add(ptr, 1).unwrap();
+        }
+
+        ptr
+    }
+
+    /// Claims a free slot for the value, with refcount set to 0.
+    fn claim(&self, inner: AllocationInner) -> AllocationPointer {
         const EXPAND_BY: usize = 64;
 
         let free_index = loop {
@@ -353,11 +498,9 @@ impl Allocator {
             allocation.claim_with(inner);
         }
 
-        unsafe {
-            self.refcount_owned_increment(ptr, handle.node).unwrap();
-        }
+        self.total_allocated.fetch_add(1, Ordering::SeqCst);
 
-        debugln!("Allocated: {:?}", &ptr);
+        trace!(Category::Allocator, "Allocated: {:?}", &ptr);
 
         ptr
     }
@@ -389,6 +532,7 @@ impl Allocator {
 
         if allocation.deallocating.compare_and_swap(false, true, Ordering::SeqCst) {
             // Already deallocated.
+            self.double_frees.fetch_add(1, Ordering::SeqCst);
             return;
         }
 
@@ -396,22 +540,43 @@ impl Allocator {
             allocation.free();
         }
 
+        self.total_deallocated.fetch_add(1, Ordering::SeqCst);
+
         self.free_indices.push(allocation_ptr.as_u64());
-        debugln!("Deallocated: {:?}", allocation_ptr);
+        trace!(Category::Allocator, "Deallocated: {:?}", allocation_ptr);
     }
 
-    pub unsafe fn apply_owned_and_output_refcounts(&self, node: NodeIndex) -> Result<(), ()> {
-        let task_ref_counters = self.task_ref_counters.counters.write().map_err(|_| ())?;
+    /// Applies the refcount deltas of a task of the schedule with the given `generation`.
+    /// Refuses to apply the deltas, if the refcounters have been prepared for a different schedule.
+    pub unsafe fn apply_owned_and_output_refcounts(
+        &self,
+        node: NodeIndex,
+        generation: usize,
+    ) -> Result<(), ApplyRefcountsError> {
+        let task_ref_counters = self.task_ref_counters.write().map_err(|_| ApplyRefcountsError::Poisoned)?;
+
+        if task_ref_counters.generation != generation {
+            return Err(ApplyRefcountsError::GenerationMismatch {
+                expected: generation,
+                found: task_ref_counters.generation,
+            });
+        }
 
         {
-            let mut task_ref_counter = task_ref_counters[&node].lock().map_err(|_| ())?;
+            let mut task_ref_counter = task_ref_counters
+                .counters
+                .get(&node)
+                .ok_or(ApplyRefcountsError::UnknownTask(node))?
+                .lock()
+                .map_err(|_| ApplyRefcountsError::Poisoned)?;
             let altered_ptrs: HashSet<AllocationPointer> =
                 task_ref_counter.refcount_deltas.keys().copied().collect();
 
             for altered_ptr in altered_ptrs {
                 let delta = task_ref_counter.refcount_deltas[&altered_ptr];
 
-                self.refcount_global_add(altered_ptr, delta)?;
+                self.refcount_global_add(altered_ptr, delta)
+                    .map_err(|_| ApplyRefcountsError::InvalidPointer(altered_ptr))?;
             }
 
             task_ref_counter.refcount_deltas.clear();
@@ -445,8 +610,8 @@ impl Allocator {
         node: NodeIndex,
         delta: isize,
     ) -> Result<(), ()> {
-        let task_ref_counters = self.task_ref_counters.counters.read().map_err(|_| ())?;
-        let mut task_ref_counter = task_ref_counters[&node].lock().map_err(|_| ())?;
+        let task_ref_counters = self.task_ref_counters.read().map_err(|_| ())?;
+        let mut task_ref_counter = task_ref_counters.counters.get(&node).ok_or(())?.lock().map_err(|_| ())?;
 
         match task_ref_counter.refcount_deltas.entry(allocation_ptr) {
             Entry::Occupied(mut entry) => {
@@ -457,7 +622,8 @@ impl Allocator {
             }
         }
 
-        debugln!(
+        trace!(
+            Category::Allocator,
             "Owned refcount of pointer {ptr} for node {node:?} changed by {delta}: {result}",
             ptr = allocation_ptr.as_u64(),
             node = node,
@@ -525,6 +691,20 @@ impl Allocator {
         }
     }
 
+    /// The global refcount of the allocation, or `None`, if no such allocation exists or it has
+    /// been freed.
+    /// Only the atomic counters are read, so it is safe to query the refcount from the UI while the
+    /// executor runs, though the result may be outdated by the time it is displayed.
+    pub fn refcount(&self, allocation_ptr: AllocationPointer) -> Option<usize> {
+        let allocations = self.allocations.read().unwrap();
+
+        allocations
+            .vec
+            .get(allocation_ptr.as_usize())
+            .filter(|allocation| !allocation.deallocating.load(Ordering::SeqCst))
+            .map(|allocation| allocation.refcount.load(Ordering::SeqCst))
+    }
+
     /// Safety: Access safety must be ensured externally by the execution graph.
     ///         Extra caution must be taken to request a correct lifetime 'a.
     pub unsafe fn deref_ptr<'a>(
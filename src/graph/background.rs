@@ -0,0 +1,501 @@
+//! Background tasks of nodes, for work that does not fit into the executions of the node, such as
+//! waiting for files to change or for network packets to arrive.
+//!
+//! A task is spawned on its own thread with [`ExecutionContext::spawn_background`] and owned by the
+//! state of the node through the returned [`BackgroundTask`]. The task polls
+//! [`BackgroundHandle::is_cancelled`] and sends its results through a bounded channel, received by
+//! the state with [`BackgroundTask::try_iter`]. Once the state is dropped, because the node has been
+//! removed or its state has not been carried over to a new schedule, the task is cancelled. Tasks
+//! that do not finish within [`EXIT_TIMEOUT`] of being cancelled are reported by name and left
+//! running detached, rather than blocking the executor or the exit of the application.
+//!
+//! [`ExecutionContext::spawn_background`]: crate::node::behaviour::ExecutionContext::spawn_background
+
+use super::NodeIndex;
+use crate::logging::Category;
+use std::fmt::Display;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Condvar, Mutex, Once, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long cancelled tasks may take to finish, before they are reported as unresponsive.
+pub const EXIT_TIMEOUT: Duration = Duration::from_secs(2);
+/// How often cancelled tasks are checked for having finished.
+const REAP_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BackgroundTaskStatus {
+    Running,
+    /// The task has returned on its own, before being cancelled.
+    Finished,
+    Panicked,
+    /// The task has been cancelled and has not finished yet.
+    Cancelling,
+    /// The task has not finished within [`EXIT_TIMEOUT`] of being cancelled.
+    Unresponsive,
+}
+
+/// The state shared by the task and its owner.
+#[derive(Debug, Default)]
+struct Shared {
+    cancelled: AtomicBool,
+    /// Whether the task has returned, and whether it panicked.
+    finished: Mutex<Option<bool>>,
+    /// Notified when the task is cancelled or finishes.
+    changed: Condvar,
+}
+
+impl Shared {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        // Taken so that the notification cannot fall between the check and the wait of `sleep`.
+        let _finished = self.finished.lock().unwrap();
+        self.changed.notify_all();
+    }
+
+    fn finish(&self, panicked: bool) {
+        *self.finished.lock().unwrap() = Some(panicked);
+        self.changed.notify_all();
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished.lock().unwrap().is_some()
+    }
+}
+
+/// Passed to the task, to check whether it should exit and to send results to its owner.
+#[derive(Debug)]
+pub struct BackgroundHandle<T> {
+    shared: Arc<Shared>,
+    sender: SyncSender<T>,
+}
+
+impl<T> BackgroundHandle<T> {
+    /// Whether the task should return as soon as possible.
+    pub fn is_cancelled(&self) -> bool {
+        self.shared.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Sleeps for the duration, waking up early if the task is cancelled. Returns `false` if it
+    /// has been cancelled.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let finished = self.shared.finished.lock().unwrap();
+        let _finished =
+            self.shared.changed.wait_timeout_while(finished, duration, |_| !self.is_cancelled()).unwrap();
+
+        !self.is_cancelled()
+    }
+
+    /// Sends the value to the owner, waiting while the channel is full. Returns `false` without
+    /// sending it if the task is cancelled meanwhile or the owner has been dropped.
+    pub fn send(&self, mut value: T) -> bool {
+        loop {
+            match self.sender.try_send(value) {
+                Ok(()) => return true,
+                Err(TrySendError::Full(returned)) => {
+                    if !self.sleep(REAP_INTERVAL) {
+                        return false;
+                    }
+
+                    value = returned;
+                }
+                Err(TrySendError::Disconnected(_)) => return false,
+            }
+        }
+    }
+
+    /// Sends the value to the owner, unless the channel is full. Returns `false` if the value has
+    /// been dropped.
+    pub fn try_send(&self, value: T) -> bool {
+        self.sender.try_send(value).is_ok()
+    }
+}
+
+/// Owns a task spawned in the background. Dropping it cancels the task.
+#[derive(Debug)]
+pub struct BackgroundTask<T> {
+    id: u64,
+    tasks: Arc<BackgroundTasks>,
+    shared: Arc<Shared>,
+    /// Locked only through `&mut self`, to make the task shareable by executor closures.
+    receiver: Mutex<Receiver<T>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl<T> BackgroundTask<T> {
+    /// All values sent by the task since they were last received.
+    pub fn try_iter(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.receiver.get_mut().unwrap().try_iter()
+    }
+
+    pub fn status(&self) -> BackgroundTaskStatus {
+        match *self.shared.finished.lock().unwrap() {
+            Some(true) => BackgroundTaskStatus::Panicked,
+            Some(false) => BackgroundTaskStatus::Finished,
+            None => BackgroundTaskStatus::Running,
+        }
+    }
+}
+
+impl<T> Drop for BackgroundTask<T> {
+    fn drop(&mut self) {
+        self.shared.cancel();
+        self.tasks.retire(self.id, self.join_handle.take());
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    id: u64,
+    name: String,
+    node: Option<NodeIndex>,
+    shared: Arc<Shared>,
+    /// When the task was cancelled by dropping its owner, along with the handle to join it with.
+    retired: Option<(Instant, Option<JoinHandle<()>>)>,
+    /// Whether the task has been reported as unresponsive.
+    reported: bool,
+}
+
+impl Entry {
+    fn status(&self) -> BackgroundTaskStatus {
+        match (*self.shared.finished.lock().unwrap(), &self.retired) {
+            (Some(true), _) => BackgroundTaskStatus::Panicked,
+            (Some(false), _) => BackgroundTaskStatus::Finished,
+            (None, Some(_)) if self.reported => BackgroundTaskStatus::Unresponsive,
+            (None, Some(_)) => BackgroundTaskStatus::Cancelling,
+            (None, None) => BackgroundTaskStatus::Running,
+        }
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}`", self.name)?;
+
+        if let Some(node) = self.node {
+            write!(f, " of node #{}", node.index())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps track of all background tasks, until they have finished after being cancelled.
+#[derive(Debug)]
+pub struct BackgroundTasks {
+    entries: Mutex<Vec<Entry>>,
+    next_id: AtomicU64,
+    /// Spawns the reaper along with the first task.
+    reaper: Once,
+}
+
+impl Default for BackgroundTasks {
+    fn default() -> Self {
+        Self { entries: Default::default(), next_id: AtomicU64::new(0), reaper: Once::new() }
+    }
+}
+
+impl BackgroundTasks {
+    /// Spawns the task on its own thread named after it, with a channel for up to `capacity` values
+    /// to be sent to the returned owner. Panics only stop the task, though they are still reported by
+    /// the [crash reporter](crate::crash).
+    pub fn spawn<T: Send + 'static>(
+        self: &Arc<Self>,
+        node: Option<NodeIndex>,
+        name: impl Into<String>,
+        capacity: usize,
+        task: impl FnOnce(BackgroundHandle<T>) + Send + 'static,
+    ) -> io::Result<BackgroundTask<T>> {
+        let name = name.into();
+        let shared = Arc::new(Shared::default());
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let handle = BackgroundHandle { shared: shared.clone(), sender };
+        let task_shared = shared.clone();
+        let join_handle = thread::Builder::new().name(format!("background: {}", name)).spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(move || task(handle)));
+
+            task_shared.finish(result.is_err());
+        })?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        self.start_reaper();
+        self.entries.lock().unwrap().push(Entry {
+            id,
+            name,
+            node,
+            shared: shared.clone(),
+            retired: None,
+            reported: false,
+        });
+
+        Ok(BackgroundTask {
+            id,
+            tasks: self.clone(),
+            shared,
+            receiver: Mutex::new(receiver),
+            join_handle: Some(join_handle),
+        })
+    }
+
+    /// All tracked tasks, the ones that have not been cancelled yet and the cancelled ones that have
+    /// not finished yet.
+    pub fn statuses(&self) -> Vec<BackgroundTaskStatus> {
+        self.entries.lock().unwrap().iter().map(Entry::status).collect()
+    }
+
+    /// Cancels all tasks and waits for them to finish, for up to [`EXIT_TIMEOUT`] in total. The
+    /// cancelled tasks that have finished are joined, the tasks that have not finished by then are
+    /// reported. Invoked once the application exits.
+    pub fn shutdown(&self) {
+        let entries =
+            self.entries.lock().unwrap().iter().map(|entry| entry.shared.clone()).collect::<Vec<_>>();
+
+        for shared in &entries {
+            shared.cancel();
+        }
+
+        let deadline = Instant::now() + EXIT_TIMEOUT;
+
+        for shared in &entries {
+            let finished = shared.finished.lock().unwrap();
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let _finished =
+                shared.changed.wait_timeout_while(finished, timeout, |finished| finished.is_none()).unwrap();
+        }
+
+        let finished = {
+            let mut entries = self.entries.lock().unwrap();
+            let (finished, remaining) = entries
+                .drain(..)
+                .partition::<Vec<_>, _>(|entry| entry.retired.is_some() && entry.shared.is_finished());
+
+            *entries = remaining;
+            finished
+        };
+
+        for entry in finished {
+            if let Some((_, Some(join_handle))) = entry.retired {
+                // Panics have been caught by the task already.
+                let _ = join_handle.join();
+            }
+        }
+
+        let unresponsive = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| !entry.shared.is_finished())
+            .map(|entry| entry.to_string())
+            .collect::<Vec<_>>();
+
+        if !unresponsive.is_empty() {
+            warn!(
+                Category::Executor,
+                "Exiting without waiting for the background tasks {}, which did not finish within {:?}.",
+                unresponsive.join(", "),
+                EXIT_TIMEOUT
+            );
+        }
+    }
+
+    /// Hands the cancelled task over to the reaper, which joins it once it has finished.
+    fn retire(&self, id: u64, join_handle: Option<JoinHandle<()>>) {
+        if let Some(entry) = self.entries.lock().unwrap().iter_mut().find(|entry| entry.id == id) {
+            entry.retired = Some((Instant::now(), join_handle));
+        }
+    }
+
+    fn start_reaper(self: &Arc<Self>) {
+        let tasks = Arc::downgrade(self);
+
+        self.reaper.call_once(move || {
+            let spawned =
+                thread::Builder::new().name("background-reaper".to_string()).spawn(move || Self::reap(tasks));
+
+            if let Err(error) = spawned {
+                error!(Category::Executor, "Could not spawn the reaper of background tasks: {}", error);
+            }
+        });
+    }
+
+    /// Joins the cancelled tasks that have finished and reports the ones that do not finish in
+    /// time, until the tasks are no longer tracked.
+    fn reap(tasks: Weak<Self>) {
+        loop {
+            thread::sleep(REAP_INTERVAL);
+
+            let tasks = match tasks.upgrade() {
+                Some(tasks) => tasks,
+                None => return,
+            };
+            let mut entries = tasks.entries.lock().unwrap();
+            let mut finished_ids = Vec::new();
+
+            for entry in entries.iter_mut() {
+                let retired_at = match &entry.retired {
+                    Some((retired_at, _)) => *retired_at,
+                    None => continue,
+                };
+
+                if entry.shared.is_finished() {
+                    finished_ids.push(entry.id);
+                } else if !entry.reported && retired_at.elapsed() >= EXIT_TIMEOUT {
+                    entry.reported = true;
+                    warn!(
+                        Category::Executor,
+                        "The background task {} did not finish within {:?} of being cancelled.",
+                        entry,
+                        EXIT_TIMEOUT
+                    );
+                }
+            }
+
+            let (finished, remaining) =
+                entries.drain(..).partition::<Vec<_>, _>(|entry| finished_ids.contains(&entry.id));
+
+            *entries = remaining;
+            drop(entries);
+
+            for entry in finished {
+                if let Some((_, Some(join_handle))) = entry.retired {
+                    // Panics have been caught by the task already.
+                    let _ = join_handle.join();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Polls the condition until it holds, for up to twice the time cancelled tasks are given.
+    fn wait_until(mut condition: impl FnMut() -> bool) -> bool {
+        let deadline = Instant::now() + 2 * EXIT_TIMEOUT;
+
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        condition()
+    }
+
+    /// Sends increasing numbers until it is cancelled.
+    fn counting(tasks: &Arc<BackgroundTasks>) -> BackgroundTask<u32> {
+        tasks
+            .spawn(None, "counting", 4, |handle| {
+                let mut count = 0;
+
+                while handle.send(count) && handle.sleep(Duration::from_millis(1)) {
+                    count += 1;
+                }
+            })
+            .unwrap()
+    }
+
+    /// Ignores being cancelled until the returned sender is dropped.
+    fn ignoring_cancellation(tasks: &Arc<BackgroundTasks>) -> (BackgroundTask<()>, SyncSender<()>) {
+        let (release, released) = mpsc::sync_channel::<()>(0);
+        let task = tasks
+            .spawn(None, "ignoring cancellation", 1, move |_handle| {
+                let _ = released.recv();
+            })
+            .unwrap();
+
+        (task, release)
+    }
+
+    #[test]
+    fn values_are_received_until_cancelled() {
+        let tasks = Arc::new(BackgroundTasks::default());
+        let mut task = counting(&tasks);
+
+        assert!(wait_until(|| task.try_iter().next().is_some()));
+        assert_eq!(task.status(), BackgroundTaskStatus::Running);
+        assert_eq!(tasks.statuses(), vec![BackgroundTaskStatus::Running]);
+
+        drop(task);
+
+        // The reaper stops tracking the task once it has finished.
+        assert!(wait_until(|| tasks.statuses().is_empty()));
+    }
+
+    #[test]
+    fn sleeping_tasks_wake_up_when_cancelled() {
+        let tasks = Arc::new(BackgroundTasks::default());
+        let task = tasks
+            .spawn(None, "sleeping", 1, |handle: BackgroundHandle<()>| {
+                handle.sleep(Duration::from_secs(3600));
+            })
+            .unwrap();
+        let shared = task.shared.clone();
+        let start = Instant::now();
+
+        drop(task);
+
+        assert!(wait_until(|| shared.is_finished()));
+        assert!(start.elapsed() < EXIT_TIMEOUT);
+    }
+
+    #[test]
+    fn tasks_not_finishing_in_time_are_reported_unresponsive() {
+        let tasks = Arc::new(BackgroundTasks::default());
+        let (task, release) = ignoring_cancellation(&tasks);
+
+        drop(task);
+        assert_eq!(tasks.statuses(), vec![BackgroundTaskStatus::Cancelling]);
+        assert!(wait_until(|| tasks.statuses() == vec![BackgroundTaskStatus::Unresponsive]));
+
+        // Unresponsive tasks are still joined once they finish.
+        drop(release);
+        assert!(wait_until(|| tasks.statuses().is_empty()));
+    }
+
+    #[test]
+    fn panics_only_stop_the_task() {
+        let tasks = Arc::new(BackgroundTasks::default());
+        let task =
+            tasks.spawn(None, "panicking", 1, |_handle: BackgroundHandle<()>| panic!("expected")).unwrap();
+
+        assert!(wait_until(|| task.status() == BackgroundTaskStatus::Panicked));
+    }
+
+    #[test]
+    fn shutdown_cancels_and_joins_the_tasks() {
+        let tasks = Arc::new(BackgroundTasks::default());
+        let owned = counting(&tasks);
+        let retired = counting(&tasks);
+
+        drop(retired);
+        tasks.shutdown();
+
+        assert_eq!(owned.status(), BackgroundTaskStatus::Finished);
+        // The retired task has been joined, the owned one is joined once its owner is dropped.
+        assert_eq!(tasks.statuses(), vec![BackgroundTaskStatus::Finished]);
+    }
+
+    #[test]
+    fn shutdown_does_not_wait_for_unresponsive_tasks_indefinitely() {
+        let tasks = Arc::new(BackgroundTasks::default());
+        let (task, release) = ignoring_cancellation(&tasks);
+        let start = Instant::now();
+
+        tasks.shutdown();
+
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= EXIT_TIMEOUT && elapsed < 2 * EXIT_TIMEOUT, "{:?}", elapsed);
+        assert_eq!(task.status(), BackgroundTaskStatus::Running);
+        drop(release);
+    }
+}
@@ -0,0 +1,1509 @@
+//! Edits of the graph, applied as typed commands through the [`GraphController`], so that the
+//! invariants of the graph are maintained in a single place.
+//!
+//! Each command returns the information needed to revert it, such as the removed node along with
+//! its connections. Commands are applied to the graph immediately, while the schedule is rebuilt
+//! once for all commands applied before the controller is flushed.
+
+use super::{
+    ChannelIdentifier, ChannelOrder, Connection, EdgeData, EdgeEndpoint, ExecutionGraph, Graph,
+    GraphValidationErrors, NodeData, NodeIndex, UndirectedChannelIdentifier,
+};
+use crate::logging::Category;
+use crate::node::behaviour::{NodeBehaviourContainer, NodeBehaviourMessage, NodeEvent};
+use crate::node::ChannelDirection;
+use crate::style::NodeColor;
+use crate::widgets::MIN_PANE_OPACITY;
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::ops::Deref;
+use vek::Vec2;
+
+#[derive(Debug, Clone)]
+pub enum GraphCommand {
+    AddNode {
+        title: String,
+        position: Vec2<f32>,
+        behaviour: Box<dyn NodeBehaviourContainer>,
+    },
+    /// Removes the node along with its connections.
+    RemoveNode {
+        node: NodeIndex,
+    },
+    /// Removes the node along with its connections, then creates the connections from the channels
+    /// upstream of the node to the channels downstream of it, so that the values keep flowing past
+    /// it. Nothing is edited unless all of the connections can be created, see
+    /// [`ExecutionGraph::reconnection_candidates`].
+    DissolveNode {
+        node: NodeIndex,
+        reconnections: Vec<Connection>,
+    },
+    /// Connects the channels, replacing the current connection of the input.
+    InsertEdge {
+        connection: Connection,
+        primary: bool,
+    },
+    /// Connects the channels of all connections at once, replacing the current connections of the
+    /// inputs, such as when connecting two nodes channel by channel.
+    InsertEdges {
+        connections: Vec<Connection>,
+    },
+    RemoveEdge {
+        connection: Connection,
+    },
+    /// Removes all connections of the channel.
+    DisconnectChannel {
+        channel: ChannelIdentifier,
+    },
+    /// Moves the value of the output into the connection, rather than into its other connections.
+    SetPrimaryEdge {
+        connection: Connection,
+    },
+    /// Passes the message to the behaviour of the node, which may change its settings.
+    SetNodeParameter {
+        node: NodeIndex,
+        message: Box<dyn NodeBehaviourMessage>,
+    },
+    /// Replaces the settings of the node, as returned by
+    /// [`NodeBehaviour::save`](crate::node::behaviour::NodeBehaviour::save).
+    LoadNodeSettings {
+        node: NodeIndex,
+        settings: serde_json::Value,
+    },
+    MoveNode {
+        node: NodeIndex,
+        position: Vec2<f32>,
+    },
+    /// Moves several nodes at once, such as when aligning them.
+    MoveNodes {
+        positions: Vec<(NodeIndex, Vec2<f32>)>,
+    },
+    SetNodeColor {
+        node: NodeIndex,
+        color: Option<NodeColor>,
+    },
+    SetNodeMuted {
+        node: NodeIndex,
+        muted: bool,
+    },
+    SetNodeSoloed {
+        node: NodeIndex,
+        soloed: bool,
+    },
+    /// Displays only the title bar and the connection points of the node, or its full content.
+    SetNodeCollapsed {
+        node: NodeIndex,
+        collapsed: bool,
+    },
+    /// Collapses or expands several nodes at once, such as all nodes of the graph.
+    SetNodesCollapsed {
+        nodes: Vec<(NodeIndex, bool)>,
+    },
+    /// Draws the pane of the node above all panes that are not pinned, or among them.
+    SetNodePinned {
+        node: NodeIndex,
+        pinned: bool,
+    },
+    /// Draws the pane of the node with the opacity, clamped to the range from
+    /// [`MIN_PANE_OPACITY`](crate::widgets::MIN_PANE_OPACITY) to 1.
+    SetNodeOpacity {
+        node: NodeIndex,
+        opacity: f32,
+    },
+    /// Lets the cursor through the content of the pane of the node.
+    SetNodeClickThrough {
+        node: NodeIndex,
+        click_through: bool,
+    },
+    /// Displays the channel at the position of the target channel, see [`ChannelOrder::move_channel`].
+    MoveChannel {
+        node: NodeIndex,
+        direction: ChannelDirection,
+        channel: usize,
+        target: usize,
+    },
+    /// Displays the channels of the node in the order, the default of which is the order they are
+    /// declared in.
+    SetChannelOrder {
+        node: NodeIndex,
+        order: ChannelOrder,
+    },
+    /// Adds a removed or dissolved node back along with its connections, see
+    /// [`GraphCommandResult::into_restore_command`]. The node is added under a new index, which the
+    /// edges referring to its `previous` index are remapped to. The connections created in place of
+    /// the dissolved node are removed first. Nothing is edited unless all of the edges can be
+    /// recreated.
+    RestoreNode {
+        previous: NodeIndex,
+        data: RemovedNode,
+        edges: Vec<RemovedEdge>,
+        reconnected: Vec<Connection>,
+    },
+}
+
+/// A node removed by a command, see [`GraphCommand::RestoreNode`]. Cloned without its view state,
+/// like [`NodeData::dry_run_copy`], as commands are only cloned to be tried out.
+pub struct RemovedNode(pub Box<NodeData>);
+
+impl Clone for RemovedNode {
+    fn clone(&self) -> Self {
+        RemovedNode(Box::new(self.0.dry_run_copy()))
+    }
+}
+
+impl std::fmt::Debug for RemovedNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RemovedNode").field(&self.0.title).finish()
+    }
+}
+
+/// An edge removed by a command, which is recreated by inserting it again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemovedEdge {
+    pub connection: Connection,
+    pub primary: bool,
+}
+
+impl RemovedEdge {
+    pub fn reinsert(&self) -> GraphCommand {
+        GraphCommand::InsertEdge { connection: self.connection.clone(), primary: self.primary }
+    }
+}
+
+/// The outcome of a command, along with the state it replaced.
+pub enum GraphCommandResult {
+    NodeAdded {
+        node: NodeIndex,
+    },
+    /// The removed node can be added back, though under a different index, see
+    /// [`GraphCommandResult::into_restore_command`].
+    NodeRemoved {
+        node: NodeIndex,
+        data: NodeData,
+        edges: Vec<RemovedEdge>,
+    },
+    /// Like [`GraphCommandResult::NodeRemoved`], along with the connections created in its place.
+    NodeDissolved {
+        node: NodeIndex,
+        data: NodeData,
+        edges: Vec<RemovedEdge>,
+        reconnected: Vec<Connection>,
+    },
+    NodeRestored {
+        node: NodeIndex,
+        /// The recreated connections, referring to the new index of the node.
+        connections: Vec<Connection>,
+        /// The connections created in place of the dissolved node, removed by the restoration.
+        removed: Vec<RemovedEdge>,
+    },
+    EdgeInserted {
+        connection: Connection,
+        /// The previous connection of the input.
+        replaced: Option<RemovedEdge>,
+    },
+    EdgesInserted {
+        connections: Vec<Connection>,
+        /// The previous connections of the inputs.
+        replaced: Vec<RemovedEdge>,
+    },
+    EdgesRemoved {
+        edges: Vec<RemovedEdge>,
+    },
+    PrimaryEdgeSet {
+        connection: Connection,
+        /// The connection previously marked as primary.
+        previous: Option<Connection>,
+    },
+    NodeUpdated {
+        node: NodeIndex,
+        previous_settings: serde_json::Value,
+        /// Whether the settings of the node have changed.
+        modified: bool,
+        /// The edges referring to channels removed by the reconfiguration of the node.
+        pruned: Vec<RemovedEdge>,
+    },
+    NodeMoved {
+        node: NodeIndex,
+        previous: Vec2<f32>,
+    },
+    NodesMoved {
+        previous: Vec<(NodeIndex, Vec2<f32>)>,
+    },
+    NodeColorSet {
+        node: NodeIndex,
+        previous: Option<NodeColor>,
+    },
+    NodeMutedSet {
+        node: NodeIndex,
+        previous: bool,
+    },
+    NodeSoloedSet {
+        node: NodeIndex,
+        previous: bool,
+    },
+    NodeCollapsedSet {
+        node: NodeIndex,
+        previous: bool,
+    },
+    NodesCollapsedSet {
+        previous: Vec<(NodeIndex, bool)>,
+    },
+    ChannelOrderSet {
+        node: NodeIndex,
+        previous: ChannelOrder,
+    },
+    NodePinnedSet {
+        node: NodeIndex,
+        previous: bool,
+    },
+    NodeOpacitySet {
+        node: NodeIndex,
+        previous: f32,
+    },
+    NodeClickThroughSet {
+        node: NodeIndex,
+        previous: bool,
+    },
+}
+
+impl GraphCommandResult {
+    pub fn added_node(&self) -> Option<NodeIndex> {
+        match self {
+            GraphCommandResult::NodeAdded { node } | GraphCommandResult::NodeRestored { node, .. } => {
+                Some(*node)
+            }
+            _ => None,
+        }
+    }
+
+    /// The command adding the removed or dissolved node back, `None` for other results.
+    pub fn into_restore_command(self) -> Option<GraphCommand> {
+        let (previous, data, edges, reconnected) = match self {
+            GraphCommandResult::NodeRemoved { node, data, edges } => (node, data, edges, Vec::new()),
+            GraphCommandResult::NodeDissolved { node, data, edges, reconnected } => {
+                (node, data, edges, reconnected)
+            }
+            _ => return None,
+        };
+
+        Some(GraphCommand::RestoreNode { previous, data: RemovedNode(Box::new(data)), edges, reconnected })
+    }
+
+    /// The edges removed by the command, either explicitly or to maintain the invariants of the
+    /// graph.
+    pub fn removed_edges(&self) -> &[RemovedEdge] {
+        match self {
+            GraphCommandResult::NodeRemoved { edges, .. }
+            | GraphCommandResult::NodeDissolved { edges, .. }
+            | GraphCommandResult::NodeRestored { removed: edges, .. }
+            | GraphCommandResult::EdgesRemoved { edges }
+            | GraphCommandResult::EdgesInserted { replaced: edges, .. } => edges,
+            GraphCommandResult::NodeUpdated { pruned, .. } => pruned,
+            GraphCommandResult::EdgeInserted { replaced: Some(replaced), .. } => {
+                std::slice::from_ref(replaced)
+            }
+            _ => &[],
+        }
+    }
+
+    /// The nodes the checks of which the command may have affected, along with whether it has added
+    /// or removed nodes or connections, see [`ValidationCache`](super::validation::ValidationCache).
+    fn touched_nodes(&self) -> (Vec<NodeIndex>, bool) {
+        use GraphCommandResult::*;
+
+        fn endpoints<'a>(connections: impl IntoIterator<Item = &'a Connection>) -> Vec<NodeIndex> {
+            connections
+                .into_iter()
+                .flat_map(|connection| connection.0.iter().map(|channel| channel.node_index))
+                .collect()
+        }
+
+        match self {
+            NodeAdded { node } => (vec![*node], true),
+            NodeRestored { node, connections, removed } => {
+                let mut nodes = endpoints(connections);
+
+                nodes.extend(endpoints(removed.iter().map(|edge| &edge.connection)));
+                nodes.push(*node);
+                (nodes, true)
+            }
+            NodeRemoved { .. } | NodeDissolved { .. } | EdgesRemoved { .. } => {
+                let mut nodes = endpoints(self.removed_edges().iter().map(|edge| &edge.connection));
+
+                if let NodeDissolved { reconnected, .. } = self {
+                    nodes.extend(endpoints(reconnected));
+                }
+
+                (nodes, true)
+            }
+            EdgeInserted { connection, .. } => {
+                let mut nodes = endpoints(std::iter::once(connection));
+
+                nodes.extend(endpoints(self.removed_edges().iter().map(|edge| &edge.connection)));
+                (nodes, true)
+            }
+            EdgesInserted { connections, .. } => {
+                let mut nodes = endpoints(connections);
+
+                nodes.extend(endpoints(self.removed_edges().iter().map(|edge| &edge.connection)));
+                (nodes, true)
+            }
+            // Only decides which of the connections of the output are aliased.
+            PrimaryEdgeSet { connection, .. } => (vec![connection.from().node_index], false),
+            NodeUpdated { node, pruned, .. } => {
+                let mut nodes = endpoints(pruned.iter().map(|edge| &edge.connection));
+
+                nodes.push(*node);
+                (nodes, !pruned.is_empty())
+            }
+            NodeMoved { .. }
+            | NodesMoved { .. }
+            | NodeColorSet { .. }
+            | NodeMutedSet { .. }
+            | NodeSoloedSet { .. }
+            | NodeCollapsedSet { .. }
+            | NodesCollapsedSet { .. }
+            | ChannelOrderSet { .. }
+            | NodePinnedSet { .. }
+            | NodeOpacitySet { .. }
+            | NodeClickThroughSet { .. } => (Vec::new(), false),
+        }
+    }
+
+    /// Whether the persisted state of the graph has changed.
+    pub fn is_modification(&self) -> bool {
+        match self {
+            GraphCommandResult::NodeUpdated { modified, pruned, .. } => *modified || !pruned.is_empty(),
+            _ => true,
+        }
+    }
+
+    /// Whether the schedule has to be rebuilt, which the layout and the colors of the nodes do not
+    /// affect.
+    pub fn affects_schedule(&self) -> bool {
+        !matches!(
+            self,
+            GraphCommandResult::NodeMoved { .. }
+                | GraphCommandResult::NodesMoved { .. }
+                | GraphCommandResult::NodeColorSet { .. }
+                | GraphCommandResult::NodeCollapsedSet { .. }
+                | GraphCommandResult::NodesCollapsedSet { .. }
+                | GraphCommandResult::ChannelOrderSet { .. }
+                | GraphCommandResult::NodePinnedSet { .. }
+                | GraphCommandResult::NodeOpacitySet { .. }
+                | GraphCommandResult::NodeClickThroughSet { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphError {
+    MissingNode(NodeIndex),
+    /// The channel does not exist in the current configuration of its node.
+    MissingChannel(ChannelIdentifier),
+    MissingConnection(Connection),
+    /// The connection already exists.
+    DuplicateConnection(Connection),
+    /// The channels of the connection are not compatible, the input is connected already or the
+    /// connection would create a cycle.
+    InvalidConnection(Connection),
+    InvalidSettings {
+        node: NodeIndex,
+        message: String,
+    },
+}
+
+impl Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use GraphError::*;
+        match self {
+            MissingNode(node) => write!(f, "Node {} does not exist.", node.index()),
+            MissingChannel(channel) => write!(
+                f,
+                "Channel {:?} #{} of node {} does not exist.",
+                channel.pass_by,
+                channel.channel_index,
+                channel.node_index.index()
+            ),
+            MissingConnection(connection) => write!(
+                f,
+                "There is no connection from node {} to node {}.",
+                connection.from().node_index.index(),
+                connection.to().node_index.index()
+            ),
+            DuplicateConnection(connection) => write!(
+                f,
+                "The connection from node {} to node {} already exists.",
+                connection.from().node_index.index(),
+                connection.to().node_index.index()
+            ),
+            InvalidConnection(connection) => write!(
+                f,
+                "The connection from node {} to node {} cannot be created.",
+                connection.from().node_index.index(),
+                connection.to().node_index.index()
+            ),
+            InvalidSettings { node, message } => {
+                write!(f, "Invalid settings of node {}: {}", node.index(), message)
+            }
+        }
+    }
+}
+
+/// What an edit would break, found by trying it out on a copy of the graph, see
+/// [`GraphController::preview`].
+#[derive(Debug, Clone, Default)]
+pub struct EditConsequences {
+    /// The connections removed by the edit, explicitly or because their channels no longer exist.
+    pub disconnected: Vec<Connection>,
+    /// The nodes whose inputs are all disconnected by the edit, which are not executed anymore.
+    pub unconnected: Vec<NodeIndex>,
+    /// The nodes downstream of the inputs disconnected by the edit.
+    pub downstream: Vec<NodeIndex>,
+    /// The validation errors caused by the edit, such as incomplete inputs or connections of
+    /// channels whose types no longer match.
+    pub errors: Vec<String>,
+}
+
+impl EditConsequences {
+    /// Whether the edit leaves nodes without inputs or invalidates the graph, rather than only
+    /// removing connections nothing else depends on.
+    pub fn is_breaking(&self) -> bool {
+        !self.unconnected.is_empty() || !self.errors.is_empty()
+    }
+
+    /// Summarizes the consequences, before the edit is confirmed or, if `applied`, once it has been
+    /// applied. The nodes are named by their titles in the graph the edit has not been applied to
+    /// yet.
+    pub fn describe(&self, graph: &ExecutionGraph, applied: bool) -> String {
+        let titles = |nodes: &[NodeIndex]| {
+            nodes
+                .iter()
+                .filter_map(|node| graph.node_weight(*node))
+                .map(|node_data| format!("`{}`", node_data.title))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let (disconnect, leave, become_invalid) = if applied {
+            ("The edit disconnected", "left", "The graph is not valid anymore")
+        } else {
+            ("This will disconnect", "leave", "The graph will not be valid anymore")
+        };
+        let mut description = format!("{} {} connections", disconnect, self.disconnected.len());
+
+        if !self.unconnected.is_empty() {
+            description += &format!(
+                " and {} {} nodes without inputs: {}",
+                leave,
+                self.unconnected.len(),
+                titles(&self.unconnected)
+            );
+        }
+
+        description.push('.');
+
+        if !self.downstream.is_empty() {
+            description += &format!(
+                " {} nodes downstream depend on them: {}.",
+                self.downstream.len(),
+                titles(&self.downstream)
+            );
+        }
+
+        if let Some(error) = self.errors.first() {
+            description += &format!(" {}: {}", become_invalid, error);
+
+            if self.errors.len() > 1 {
+                description += &format!(" ({} more problems)", self.errors.len() - 1);
+            }
+        }
+
+        description
+    }
+}
+
+impl GraphCommand {
+    /// Applies the command to the graph without updating the schedule, which
+    /// [`GraphController::apply`] takes care of. Invalidates the cached checks of the part of the
+    /// graph the command has touched.
+    pub fn apply(self, graph: &mut ExecutionGraph) -> Result<GraphCommandResult, GraphError> {
+        let result = self.edit(graph);
+
+        match &result {
+            Ok(result) => {
+                let (nodes, topology_changed) = result.touched_nodes();
+
+                graph.invalidate_validation(nodes, topology_changed);
+            }
+            // The command may have failed halfway through.
+            Err(_) => graph.invalidate_validation_all(),
+        }
+
+        result
+    }
+
+    fn edit(self, graph: &mut ExecutionGraph) -> Result<GraphCommandResult, GraphError> {
+        use GraphCommand::*;
+        Ok(match self {
+            AddNode { title, position, behaviour } => GraphCommandResult::NodeAdded {
+                node: graph.add_node(NodeData::new(title, position, behaviour)),
+            },
+            RemoveNode { node } => {
+                let (data, edges) = remove_node(graph, node)?;
+
+                GraphCommandResult::NodeRemoved { node, data, edges }
+            }
+            DissolveNode { node, reconnections } => {
+                // Tried out on a copy first, so that the node is only removed along with all of the
+                // reconnections.
+                let mut copy = graph.dry_run_copy();
+
+                remove_node(&mut copy, node)?;
+                insert_reconnections(&mut copy, &reconnections)?;
+
+                let (data, edges) = remove_node(graph, node)?;
+
+                insert_reconnections(graph, &reconnections)?;
+
+                GraphCommandResult::NodeDissolved { node, data, edges, reconnected: reconnections }
+            }
+            RestoreNode { previous, data, edges, reconnected } => {
+                // Tried out on a copy first, like dissolving the node.
+                let mut copy = graph.dry_run_copy();
+
+                restore_node(&mut copy, previous, data.0.dry_run_copy(), &edges, &reconnected)?;
+
+                let (node, connections, removed) =
+                    restore_node(graph, previous, *data.0, &edges, &reconnected)?;
+
+                GraphCommandResult::NodeRestored { node, connections, removed }
+            }
+            InsertEdge { connection, primary } => {
+                for channel in [connection.from(), connection.to()].iter() {
+                    if !graph.contains_channel(*channel) {
+                        return Err(GraphError::MissingChannel(*channel));
+                    }
+                }
+
+                if graph.get_connections().contains(&connection) {
+                    return Err(GraphError::DuplicateConnection(connection));
+                }
+
+                // An input may only be connected once.
+                let mut replaced = remove_edges(graph, |existing| existing.to() == connection.to());
+
+                if let Err(error) = insert_connections(graph, std::slice::from_ref(&connection)) {
+                    restore_edges(graph, &replaced);
+                    return Err(error);
+                }
+
+                let replaced = replaced.pop();
+
+                if primary {
+                    graph.set_primary_connection(&connection);
+                }
+
+                GraphCommandResult::EdgeInserted { connection, replaced }
+            }
+            InsertEdges { connections } => {
+                // Fail before inserting any of the edges, so that the edges are inserted all at once.
+                for (index, connection) in connections.iter().enumerate() {
+                    for channel in [connection.from(), connection.to()].iter() {
+                        if !graph.contains_channel(*channel) {
+                            return Err(GraphError::MissingChannel(*channel));
+                        }
+                    }
+
+                    if graph.get_connections().contains(connection)
+                        || connections[..index].iter().any(|previous| previous.to() == connection.to())
+                    {
+                        return Err(GraphError::DuplicateConnection(connection.clone()));
+                    }
+                }
+
+                let replaced = remove_edges(graph, |existing| {
+                    connections.iter().any(|connection| existing.to() == connection.to())
+                });
+
+                if let Err(error) = insert_connections(graph, &connections) {
+                    restore_edges(graph, &replaced);
+                    return Err(error);
+                }
+
+                GraphCommandResult::EdgesInserted { connections, replaced }
+            }
+            RemoveEdge { connection } => {
+                let edges = remove_edges(graph, |existing| *existing == connection);
+
+                if edges.is_empty() {
+                    return Err(GraphError::MissingConnection(connection));
+                }
+
+                GraphCommandResult::EdgesRemoved { edges }
+            }
+            DisconnectChannel { channel } => GraphCommandResult::EdgesRemoved {
+                edges: remove_edges(graph, |connection| connection.contains_channel(channel)),
+            },
+            SetPrimaryEdge { connection } => {
+                let previous = graph
+                    .move_recipients()
+                    .get(&connection.from())
+                    .filter(|recipient| recipient.primary)
+                    .map(|recipient| recipient.connection.clone());
+
+                if !graph.set_primary_connection(&connection) {
+                    return Err(GraphError::MissingConnection(connection));
+                }
+
+                GraphCommandResult::PrimaryEdgeSet { connection, previous }
+            }
+            SetNodeParameter { node, message } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous_settings = node_data.behaviour.save();
+                let modified = node_data.update(NodeEvent::Message(message));
+                let pruned = prune_edges(graph, node);
+
+                GraphCommandResult::NodeUpdated { node, previous_settings, modified, pruned }
+            }
+            LoadNodeSettings { node, settings } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous_settings = node_data.behaviour.save();
+                let modified = previous_settings != settings;
+
+                node_data
+                    .behaviour
+                    .load(settings)
+                    .map_err(|error| GraphError::InvalidSettings { node, message: error.to_string() })?;
+                // Reconfigure the node according to the loaded settings.
+                node_data.update(NodeEvent::Update);
+
+                let pruned = prune_edges(graph, node);
+
+                GraphCommandResult::NodeUpdated { node, previous_settings, modified, pruned }
+            }
+            MoveNode { node, position } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous = std::mem::replace(&mut node_data.floating_pane_state.position, position);
+
+                GraphCommandResult::NodeMoved { node, previous }
+            }
+            MoveNodes { positions } => {
+                // Fail before moving any of the nodes, so that the nodes are moved all at once.
+                let missing =
+                    positions.iter().map(|(node, _)| *node).find(|node| graph.node_weight(*node).is_none());
+
+                if let Some(node) = missing {
+                    return Err(GraphError::MissingNode(node));
+                }
+
+                let previous = positions
+                    .into_iter()
+                    .map(|(node, position)| {
+                        let node_data = &mut graph[node];
+
+                        (node, std::mem::replace(&mut node_data.floating_pane_state.position, position))
+                    })
+                    .collect();
+
+                GraphCommandResult::NodesMoved { previous }
+            }
+            SetNodeColor { node, color } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous = std::mem::replace(&mut node_data.color, color);
+
+                GraphCommandResult::NodeColorSet { node, previous }
+            }
+            SetNodeMuted { node, muted } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous = std::mem::replace(&mut node_data.muted, muted);
+
+                GraphCommandResult::NodeMutedSet { node, previous }
+            }
+            SetNodeSoloed { node, soloed } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous = std::mem::replace(&mut node_data.soloed, soloed);
+
+                GraphCommandResult::NodeSoloedSet { node, previous }
+            }
+            SetNodeCollapsed { node, collapsed } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous = set_collapsed(node_data, collapsed);
+
+                GraphCommandResult::NodeCollapsedSet { node, previous }
+            }
+            SetNodesCollapsed { nodes } => {
+                // Fail before collapsing any of the nodes, like when moving them.
+                let missing =
+                    nodes.iter().map(|(node, _)| *node).find(|node| graph.node_weight(*node).is_none());
+
+                if let Some(node) = missing {
+                    return Err(GraphError::MissingNode(node));
+                }
+
+                let previous = nodes
+                    .into_iter()
+                    .map(|(node, collapsed)| (node, set_collapsed(&mut graph[node], collapsed)))
+                    .collect();
+
+                GraphCommandResult::NodesCollapsedSet { previous }
+            }
+            SetNodePinned { node, pinned } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous = std::mem::replace(&mut node_data.floating_pane_state.pinned, pinned);
+
+                GraphCommandResult::NodePinnedSet { node, previous }
+            }
+            SetNodeOpacity { node, opacity } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let opacity = opacity.max(MIN_PANE_OPACITY).min(1.0);
+                let previous = std::mem::replace(&mut node_data.floating_pane_state.opacity, opacity);
+
+                GraphCommandResult::NodeOpacitySet { node, previous }
+            }
+            SetNodeClickThrough { node, click_through } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous =
+                    std::mem::replace(&mut node_data.floating_pane_state.click_through, click_through);
+
+                GraphCommandResult::NodeClickThroughSet { node, previous }
+            }
+            MoveChannel { node, direction, channel, target } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous = node_data.channel_order.clone();
+
+                node_data.channel_order.move_channel(&node_data.configuration, direction, channel, target);
+
+                GraphCommandResult::ChannelOrderSet { node, previous }
+            }
+            SetChannelOrder { node, order } => {
+                let node_data = graph.node_weight_mut(node).ok_or(GraphError::MissingNode(node))?;
+                let previous = std::mem::replace(&mut node_data.channel_order, order);
+
+                GraphCommandResult::ChannelOrderSet { node, previous }
+            }
+        })
+    }
+}
+
+/// Returns whether the node was collapsed. Ends the preview of its content, so that it is not
+/// displayed expanded once collapsed again.
+fn set_collapsed(node_data: &mut NodeData, collapsed: bool) -> bool {
+    node_data.floating_pane_state.previewed = false;
+    std::mem::replace(&mut node_data.floating_pane_state.collapsed, collapsed)
+}
+
+/// Removes the node along with its connections.
+fn remove_node(
+    graph: &mut ExecutionGraph,
+    node: NodeIndex,
+) -> Result<(NodeData, Vec<RemovedEdge>), GraphError> {
+    if !graph.contains_node(node) {
+        return Err(GraphError::MissingNode(node));
+    }
+
+    let edges = remove_edges(graph, |connection| {
+        connection.from().node_index == node || connection.to().node_index == node
+    });
+    let data = graph.remove_node(node).unwrap();
+
+    Ok((data, edges))
+}
+
+/// Creates the connections one after another, each of which has to pass the validity check with
+/// the previous ones created, without replacing connections of the inputs or creating cycles.
+fn insert_reconnections(graph: &mut ExecutionGraph, connections: &[Connection]) -> Result<(), GraphError> {
+    for connection in connections {
+        let input_connected = graph.get_connections().iter().any(|existing| existing.to() == connection.to());
+
+        if input_connected || !graph.can_connect(connection) || graph.would_create_cycle(connection) {
+            return Err(GraphError::InvalidConnection(connection.clone()));
+        }
+
+        let from = connection.from();
+        let to = connection.to();
+
+        graph.add_edge(
+            from.node_index,
+            to.node_index,
+            EdgeData { endpoint_from: from.into(), endpoint_to: to.into(), primary: false },
+        );
+    }
+
+    Ok(())
+}
+
+/// Creates the connections one after another, each of which has to pass the validity check with
+/// the previous ones created and must not close a cycle. Nothing is created unless all of them can
+/// be.
+fn insert_connections(graph: &mut ExecutionGraph, connections: &[Connection]) -> Result<(), GraphError> {
+    let mut added = Vec::with_capacity(connections.len());
+
+    for connection in connections {
+        if !graph.can_connect(connection) || graph.would_create_cycle(connection) {
+            // Removed in the reverse order, so that the edges added afterwards get the same indices
+            // as if none of these had been added.
+            for edge_index in added.into_iter().rev() {
+                graph.remove_edge(edge_index);
+            }
+
+            return Err(GraphError::InvalidConnection(connection.clone()));
+        }
+
+        let from = connection.from();
+        let to = connection.to();
+
+        added.push(graph.add_edge(
+            from.node_index,
+            to.node_index,
+            EdgeData { endpoint_from: from.into(), endpoint_to: to.into(), primary: false },
+        ));
+    }
+
+    Ok(())
+}
+
+/// Recreates the edges removed by [`remove_edges`], in the reverse order, so that they get their
+/// previous indices back, which decide the recipients of moved values.
+fn restore_edges(graph: &mut ExecutionGraph, edges: &[RemovedEdge]) {
+    for edge in edges.iter().rev() {
+        let from = edge.connection.from();
+        let to = edge.connection.to();
+
+        graph.add_edge(
+            from.node_index,
+            to.node_index,
+            EdgeData { endpoint_from: from.into(), endpoint_to: to.into(), primary: edge.primary },
+        );
+    }
+}
+
+/// Adds the node back, removing the connections created in its place and recreating its edges,
+/// remapped from its previous index to the new one. Reconnections removed since are skipped.
+fn restore_node(
+    graph: &mut ExecutionGraph,
+    previous: NodeIndex,
+    data: NodeData,
+    edges: &[RemovedEdge],
+    reconnected: &[Connection],
+) -> Result<(NodeIndex, Vec<Connection>, Vec<RemovedEdge>), GraphError> {
+    let removed = remove_edges(graph, |existing| reconnected.contains(existing));
+    let node = graph.add_node(data);
+    let remap = |mut channel: UndirectedChannelIdentifier| {
+        if channel.node_index == previous {
+            channel.node_index = node;
+        }
+
+        channel
+    };
+    let connections = edges
+        .iter()
+        .map(|edge| Connection([remap(edge.connection.0[0]), remap(edge.connection.0[1])]))
+        .collect::<Vec<_>>();
+
+    insert_reconnections(graph, &connections)?;
+
+    for (connection, edge) in connections.iter().zip(edges) {
+        if edge.primary {
+            graph.set_primary_connection(connection);
+        }
+    }
+
+    Ok((node, connections, removed))
+}
+
+/// Removes the edges of the connections matching the predicate.
+fn remove_edges(graph: &mut ExecutionGraph, predicate: impl Fn(&Connection) -> bool) -> Vec<RemovedEdge> {
+    let edge_indices = graph.edge_indices().collect::<Vec<_>>();
+    let mut removed_edges = Vec::new();
+
+    for edge_index in edge_indices {
+        let (node_index_from, node_index_to) = graph.edge_endpoints(edge_index).unwrap();
+        let edge = &graph[edge_index];
+        let connection = Connection([
+            edge.endpoint_from.into_undirected_identifier(node_index_from),
+            edge.endpoint_to.into_undirected_identifier(node_index_to),
+        ]);
+
+        if predicate(&connection) {
+            let edge = graph.remove_edge(edge_index).unwrap();
+
+            removed_edges.push(RemovedEdge { connection, primary: edge.primary });
+        }
+    }
+
+    removed_edges
+}
+
+/// Removes the edges of the node referring to channels, which no longer exist after the node has
+/// been reconfigured.
+fn prune_edges(graph: &mut ExecutionGraph, node: NodeIndex) -> Vec<RemovedEdge> {
+    let configuration = &graph[node].configuration;
+    let missing_endpoints = graph
+        .edges_directed(node, Direction::Incoming)
+        .map(|edge_ref| (ChannelDirection::In, edge_ref.weight().endpoint_to))
+        .chain(
+            graph
+                .edges_directed(node, Direction::Outgoing)
+                .map(|edge_ref| (ChannelDirection::Out, edge_ref.weight().endpoint_from)),
+        )
+        .filter(|(direction, endpoint)| {
+            !configuration.channels(*direction).any(|channel_ref| channel_ref.edge_endpoint == *endpoint)
+        })
+        .collect::<Vec<(ChannelDirection, EdgeEndpoint)>>();
+
+    if missing_endpoints.is_empty() {
+        return Vec::new();
+    }
+
+    remove_edges(graph, |connection| {
+        missing_endpoints.iter().any(|(direction, endpoint)| {
+            let channel = connection.channel(*direction);
+
+            channel.node_index == node && EdgeEndpoint::from(channel) == *endpoint
+        })
+    })
+}
+
+/// The errors and the warnings of the graph, as displayed, so that they can be compared across
+/// copies of the graph.
+fn validation_messages(graph: &ExecutionGraph) -> Vec<String> {
+    let errors = match graph.check_graph_validity() {
+        Ok((_, warnings)) => warnings,
+        Err(errors) => errors,
+    };
+
+    errors.iter().map(ToString::to_string).collect()
+}
+
+/// Owns the graph and applies the edits to it, rebuilding the schedule once per batch of edits.
+/// Reading the graph is not restricted.
+pub struct GraphController {
+    graph: ExecutionGraph,
+    /// Whether a command affecting the schedule has been applied since it was last rebuilt.
+    schedule_outdated: bool,
+    /// Whether the persisted state of the graph has changed since the controller was last flushed.
+    modified: bool,
+    validation_errors: GraphValidationErrors,
+    /// Incremented whenever the schedule is rebuilt, successfully or not.
+    edit_generation: usize,
+    /// Whether the most recent rebuild of the schedule failed, in which case the executor keeps
+    /// running an older schedule, if any.
+    schedule_failed: bool,
+}
+
+impl GraphController {
+    pub fn new(graph: ExecutionGraph) -> Self {
+        Self {
+            graph,
+            schedule_outdated: false,
+            modified: false,
+            validation_errors: Default::default(),
+            edit_generation: 0,
+            schedule_failed: false,
+        }
+    }
+
+    /// Applies the command, deferring the rebuild of the schedule until the controller is
+    /// flushed.
+    pub fn apply(&mut self, command: GraphCommand) -> Result<GraphCommandResult, GraphError> {
+        let result = command.apply(&mut self.graph)?;
+
+        self.schedule_outdated |= result.affects_schedule();
+        self.modified |= result.is_modification();
+
+        Ok(result)
+    }
+
+    /// Finds out what the command would break, by applying it to a copy of the graph and comparing
+    /// the validation errors before and after it. The graph itself is left untouched.
+    pub fn preview(&self, command: &GraphCommand) -> Result<EditConsequences, GraphError> {
+        let mut graph = self.graph.dry_run_copy();
+        let errors_before = validation_messages(&self.graph);
+        let result = command.clone().apply(&mut graph)?;
+        let disconnected =
+            result.removed_edges().iter().map(|edge| edge.connection.clone()).collect::<Vec<_>>();
+        let mut disconnected_inputs = Vec::new();
+
+        // The nodes removed by the command are not reported.
+        for connection in &disconnected {
+            let node = connection.to().node_index;
+
+            if graph.contains_node(node) && !disconnected_inputs.contains(&node) {
+                disconnected_inputs.push(node);
+            }
+        }
+
+        let unconnected = disconnected_inputs
+            .iter()
+            .copied()
+            .filter(|node| graph.edges_directed(*node, Direction::Incoming).next().is_none())
+            .collect();
+        let mut visited = disconnected_inputs.iter().copied().collect::<HashSet<_>>();
+        let mut pending = disconnected_inputs;
+        let mut downstream = Vec::new();
+
+        while let Some(node) = pending.pop() {
+            for next in graph.neighbors_directed(node, Direction::Outgoing) {
+                if visited.insert(next) {
+                    downstream.push(next);
+                    pending.push(next);
+                }
+            }
+        }
+
+        let errors =
+            validation_messages(&graph).into_iter().filter(|error| !errors_before.contains(error)).collect();
+
+        Ok(EditConsequences { disconnected, unconnected, downstream, errors })
+    }
+
+    /// Replaces the nodes and connections of the graph, keeping the schedule shared with the
+    /// executor. Replacing the graph is not considered a modification.
+    pub fn replace(&mut self, graph: Graph) {
+        self.graph.graph = graph;
+        self.graph.invalidate_validation_all();
+        self.schedule_outdated = true;
+    }
+
+    /// Allows or disallows connections converting their values implicitly, see
+    /// [`ExecutionGraph::implicit_promotion`]. The setting is persisted in the metadata of the
+    /// document, so changing it is not considered a modification of the graph.
+    pub fn set_implicit_promotion(&mut self, implicit_promotion: bool) {
+        if self.graph.implicit_promotion != implicit_promotion {
+            self.graph.implicit_promotion = implicit_promotion;
+            // Decides the validity of the connections.
+            self.graph.invalidate_validation_all();
+            self.schedule_outdated = true;
+        }
+    }
+
+    /// Replaces the non-finite floating-point values output by the nodes with the substitute, or
+    /// stops replacing them, see [`ExecutionGraph::sanitize_numerics`]. The setting is persisted in
+    /// the metadata of the document, like implicit promotion.
+    pub fn set_sanitize_numerics(&mut self, substitute: Option<f64>) {
+        if self.graph.sanitize_numerics != substitute {
+            self.graph.sanitize_numerics = substitute;
+            self.schedule_outdated = true;
+        }
+    }
+
+    /// Makes the executor execute this graph instead of the graph of `other`, by exchanging the
+    /// schedule slots shared with the executor, while each graph keeps its own schedule. The
+    /// executor keeps the states of the nodes of both graphs while the graph of `other` is pinned,
+    /// see [`PinnedSchedules`](super::PinnedSchedules). A graph that has never been scheduled is
+    /// scheduled on the next flush, nothing is executed until then, nor if it cannot be scheduled.
+    pub fn take_over_execution(&mut self, other: &mut GraphController) {
+        std::mem::swap(&mut self.graph.active_schedule, &mut other.graph.active_schedule);
+        std::mem::swap(&mut self.graph.executing_generation, &mut other.graph.executing_generation);
+
+        let schedule = self.graph.active_schedule.swap(other.graph.active_schedule.load_full());
+        other.graph.active_schedule.store(schedule);
+
+        if self.graph.active_schedule.load().is_none() {
+            self.schedule_outdated = true;
+        }
+    }
+
+    /// Rebuilds the schedule once for all commands applied since the previous flush. Returns
+    /// whether the persisted state of the graph has changed in the meantime, so that it can be
+    /// marked as unsaved and autosaved.
+    pub fn flush(&mut self) -> bool {
+        if self.schedule_outdated {
+            self.schedule_outdated = false;
+            self.update_schedule();
+        }
+
+        std::mem::take(&mut self.modified)
+    }
+
+    fn update_schedule(&mut self) {
+        self.edit_generation = self.edit_generation.wrapping_add(1);
+
+        match self.graph.update_schedule() {
+            Ok(warnings) => {
+                self.validation_errors = warnings.into();
+                self.schedule_failed = false;
+            }
+            Err(vec) => {
+                self.schedule_failed = true;
+
+                for error in &vec {
+                    error!(
+                        Category::Scheduler,
+                        "Could not construct the graph schedule, the previous one keeps running: {}", error
+                    );
+                }
+
+                self.validation_errors = vec.into();
+            }
+        }
+    }
+
+    /// For the view state of the nodes, which is not persisted. The graph itself is edited
+    /// through commands.
+    pub fn graph_mut(&mut self) -> &mut ExecutionGraph {
+        &mut self.graph
+    }
+
+    /// The errors and warnings of the most recent rebuild of the schedule.
+    pub fn validation_errors(&self) -> &GraphValidationErrors {
+        &self.validation_errors
+    }
+
+    pub fn edit_generation(&self) -> usize {
+        self.edit_generation
+    }
+
+    pub fn schedule_failed(&self) -> bool {
+        self.schedule_failed
+    }
+}
+
+impl Deref for GraphController {
+    type Target = ExecutionGraph;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::file::{GraphFile, GraphMetadata};
+    use super::super::testing::{add, connect, connection};
+    use super::*;
+    use crate::node::behaviour::DebugNodeBehaviour;
+    use crate::node::{ChannelPassBy, PrimitiveTypeEnum};
+    use crate::safe_mode::Substitutions;
+
+    fn connections(graph: &ExecutionGraph) -> HashSet<Connection> {
+        graph.get_connections().into_iter().collect()
+    }
+
+    /// Three nodes, each connected to the previous one.
+    fn chain() -> (ExecutionGraph, [NodeIndex; 3]) {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let nodes = [add(&mut graph, "Abs"), add(&mut graph, "Abs"), add(&mut graph, "Abs")];
+
+        connect(&mut graph, nodes[0], nodes[1]);
+        connect(&mut graph, nodes[1], nodes[2]);
+        (graph, nodes)
+    }
+
+    #[test]
+    fn insert_edge_rejects_incompatible_channels() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let float = add(&mut graph, "Abs");
+        let integer = add(&mut graph, "Integer Abs");
+        let command = GraphCommand::InsertEdge { connection: connection(float, integer), primary: false };
+
+        assert_eq!(
+            command.apply(&mut graph).err(),
+            Some(GraphError::InvalidConnection(connection(float, integer)))
+        );
+        assert_eq!(graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn insert_edge_rejects_cycles_and_keeps_the_replaced_connection() {
+        let (mut graph, [_, b, c]) = chain();
+        let before = connections(&graph);
+        // Would replace the connection of the input of `b`.
+        let command = GraphCommand::InsertEdge { connection: connection(c, b), primary: false };
+
+        assert_eq!(command.apply(&mut graph).err(), Some(GraphError::InvalidConnection(connection(c, b))));
+        assert_eq!(connections(&graph), before);
+    }
+
+    #[test]
+    fn insert_edges_inserts_none_unless_all_are_valid() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let [a, b, c, d] =
+            [add(&mut graph, "Abs"), add(&mut graph, "Abs"), add(&mut graph, "Abs"), add(&mut graph, "Abs")];
+        let integer = add(&mut graph, "Integer Abs");
+
+        connect(&mut graph, c, b);
+
+        let before = connections(&graph);
+        let command =
+            GraphCommand::InsertEdges { connections: vec![connection(a, b), connection(d, integer)] };
+
+        assert_eq!(
+            command.apply(&mut graph).err(),
+            Some(GraphError::InvalidConnection(connection(d, integer)))
+        );
+        assert_eq!(connections(&graph), before);
+    }
+
+    #[test]
+    fn removed_node_is_restored_under_a_new_index() {
+        let (mut graph, [a, b, c]) = chain();
+        let restore =
+            GraphCommand::RemoveNode { node: b }.apply(&mut graph).unwrap().into_restore_command().unwrap();
+        // Takes over the index of the removed node.
+        let other = add(&mut graph, "Abs");
+
+        assert_eq!(other, b);
+
+        let restored = restore.apply(&mut graph).unwrap().added_node().unwrap();
+
+        assert_ne!(restored, b);
+        assert_eq!(graph[restored].title, "Abs");
+        assert_eq!(
+            connections(&graph),
+            vec![connection(a, restored), connection(restored, c)].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn dissolved_node_is_restored_in_place_of_the_reconnections() {
+        let (mut graph, [a, b, c]) = chain();
+        let reconnections = graph.reconnection_candidates(b);
+
+        assert_eq!(reconnections, vec![connection(a, c)]);
+
+        let result = GraphCommand::DissolveNode { node: b, reconnections }.apply(&mut graph).unwrap();
+
+        assert_eq!(connections(&graph), std::iter::once(connection(a, c)).collect());
+
+        let result = result.into_restore_command().unwrap().apply(&mut graph).unwrap();
+        let restored = result.added_node().unwrap();
+
+        assert_eq!(result.removed_edges(), &[RemovedEdge { connection: connection(a, c), primary: false }]);
+        assert_eq!(
+            connections(&graph),
+            vec![connection(a, restored), connection(restored, c)].into_iter().collect()
+        );
+    }
+
+    /// Two sources connected to the inputs of a binary operation, whose output is connected to two
+    /// consumers.
+    fn fan_in_fan_out() -> (ExecutionGraph, [NodeIndex; 5]) {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let [lhs, rhs] = [add(&mut graph, "Abs"), add(&mut graph, "Abs")];
+        let operation = add(&mut graph, "Binary Operation");
+        let [first, second] = [add(&mut graph, "Abs"), add(&mut graph, "Abs")];
+        let rhs_input = Connection([
+            connection(rhs, operation).from(),
+            EdgeEndpoint { channel_index: 1, pass_by: ChannelPassBy::Value }
+                .into_undirected_identifier(operation),
+        ]);
+
+        connect(&mut graph, lhs, operation);
+        GraphCommand::InsertEdge { connection: rhs_input, primary: false }.apply(&mut graph).unwrap();
+        connect(&mut graph, operation, first);
+        connect(&mut graph, operation, second);
+        (graph, [lhs, rhs, operation, first, second])
+    }
+
+    /// The first output of `from` borrowed by the first channel of `to`.
+    fn borrow(from: NodeIndex, to: NodeIndex) -> Connection {
+        Connection([
+            connection(from, to).from(),
+            EdgeEndpoint { channel_index: 0, pass_by: ChannelPassBy::SharedReference }
+                .into_undirected_identifier(to),
+        ])
+    }
+
+    #[test]
+    fn nodes_at_the_ends_of_a_chain_have_no_reconnection_candidates() {
+        let (graph, [a, b, c]) = chain();
+
+        assert!(graph.reconnection_candidates(a).is_empty());
+        assert!(graph.reconnection_candidates(c).is_empty());
+        assert_eq!(graph.reconnection_candidates(b), vec![connection(a, c)]);
+    }
+
+    #[test]
+    fn reconnection_candidates_pair_every_source_with_every_consumer() {
+        let (graph, [lhs, rhs, operation, first, second]) = fan_in_fan_out();
+
+        assert_eq!(
+            graph.reconnection_candidates(operation),
+            vec![
+                connection(lhs, first),
+                connection(lhs, second),
+                connection(rhs, first),
+                connection(rhs, second)
+            ]
+        );
+    }
+
+    #[test]
+    fn dissolving_connects_each_input_at_most_once() {
+        let (mut graph, [lhs, rhs, operation, first, second]) = fan_in_fan_out();
+        let before = connections(&graph);
+        let command = GraphCommand::DissolveNode {
+            node: operation,
+            reconnections: graph.reconnection_candidates(operation),
+        };
+
+        // The second source would be connected to the input of the first consumer again.
+        assert_eq!(
+            command.apply(&mut graph).err(),
+            Some(GraphError::InvalidConnection(connection(rhs, first)))
+        );
+        assert!(graph.contains_node(operation));
+        assert_eq!(connections(&graph), before);
+
+        let reconnections = vec![connection(lhs, first), connection(rhs, second)];
+        let command = GraphCommand::DissolveNode { node: operation, reconnections: reconnections.clone() };
+
+        command.apply(&mut graph).unwrap();
+        assert!(!graph.contains_node(operation));
+        assert_eq!(connections(&graph), reconnections.into_iter().collect());
+    }
+
+    #[test]
+    fn reconnections_of_incompatible_channels_are_refused() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let float = add(&mut graph, "Abs");
+        let is_finite = add(&mut graph, "Is Finite");
+        let command = GraphCommand::AddNode {
+            title: "Debug".to_string(),
+            position: Vec2::zero(),
+            behaviour: Box::new(DebugNodeBehaviour::new(PrimitiveTypeEnum::U8)),
+        };
+        let debug = command.apply(&mut graph).unwrap().added_node().unwrap();
+
+        for connection in vec![borrow(float, is_finite), borrow(is_finite, debug)] {
+            GraphCommand::InsertEdge { connection, primary: false }.apply(&mut graph).unwrap();
+        }
+
+        let before = connections(&graph);
+        let command =
+            GraphCommand::DissolveNode { node: is_finite, reconnections: vec![borrow(float, debug)] };
+
+        // The float output cannot be borrowed by the input of bytes.
+        assert!(graph.reconnection_candidates(is_finite).is_empty());
+        assert_eq!(
+            command.apply(&mut graph).err(),
+            Some(GraphError::InvalidConnection(borrow(float, debug)))
+        );
+        assert!(graph.contains_node(is_finite));
+        assert_eq!(connections(&graph), before);
+    }
+
+    /// The connections of the output of `source` whose edges are marked as primary.
+    fn primary_connections(graph: &ExecutionGraph, source: NodeIndex) -> Vec<Connection> {
+        graph
+            .get_connections()
+            .into_iter()
+            .filter(|connection| connection.from().node_index == source)
+            .filter(|connection| {
+                let edge_index = graph.find_edge(source, connection.to().node_index).unwrap();
+
+                graph[edge_index].primary
+            })
+            .collect()
+    }
+
+    /// A node whose output is connected to the inputs of two others, both requiring the value.
+    fn fan_out() -> (ExecutionGraph, [NodeIndex; 3]) {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let nodes = [add(&mut graph, "Abs"), add(&mut graph, "Abs"), add(&mut graph, "Abs")];
+
+        connect(&mut graph, nodes[0], nodes[1]);
+        connect(&mut graph, nodes[0], nodes[2]);
+        (graph, nodes)
+    }
+
+    #[test]
+    fn setting_the_primary_edge_clears_the_other_edges_of_the_output() {
+        let (mut graph, [source, first, second]) = fan_out();
+        let other_source = add(&mut graph, "Abs");
+        let other_consumer = add(&mut graph, "Abs");
+
+        connect(&mut graph, other_source, other_consumer);
+
+        let command = GraphCommand::SetPrimaryEdge { connection: connection(other_source, other_consumer) };
+
+        command.apply(&mut graph).unwrap();
+
+        for (primary, expected_previous) in vec![
+            (connection(source, first), None),
+            (connection(source, second), Some(connection(source, first))),
+            (connection(source, second), Some(connection(source, second))),
+        ] {
+            let command = GraphCommand::SetPrimaryEdge { connection: primary.clone() };
+
+            match command.apply(&mut graph).unwrap() {
+                GraphCommandResult::PrimaryEdgeSet { previous, .. } => {
+                    assert_eq!(previous, expected_previous)
+                }
+                _ => panic!("The primary edge was not set."),
+            }
+
+            assert_eq!(primary_connections(&graph, source), vec![primary]);
+            // The edges of other outputs are left alone.
+            assert_eq!(
+                primary_connections(&graph, other_source),
+                vec![connection(other_source, other_consumer)]
+            );
+        }
+
+        let command = GraphCommand::SetPrimaryEdge { connection: connection(first, second) };
+
+        assert_eq!(
+            command.apply(&mut graph).err(),
+            Some(GraphError::MissingConnection(connection(first, second)))
+        );
+        assert_eq!(primary_connections(&graph, source), vec![connection(source, second)]);
+    }
+
+    #[test]
+    fn move_recipient_is_deterministic_across_regenerations() {
+        let (mut graph, [source, first, second]) = fan_out();
+        let recipient =
+            |graph: &ExecutionGraph| graph.move_recipients()[&connection(source, first).from()].clone();
+
+        // Without a primary edge, the recipient is the edge created first.
+        assert_eq!(recipient(&graph).connection, connection(source, first));
+        assert!(recipient(&graph).is_ambiguous());
+        assert_eq!(recipient(&graph), recipient(&graph));
+
+        GraphCommand::SetPrimaryEdge { connection: connection(source, second) }.apply(&mut graph).unwrap();
+
+        let expected = recipient(&graph);
+
+        assert_eq!(expected.connection, connection(source, second));
+        assert!(!expected.is_ambiguous());
+
+        for _ in 0..3 {
+            // Recreating the edges, in either order, keeps the primary one.
+            for consumer in [first, second].iter().copied() {
+                let result = GraphCommand::RemoveEdge { connection: connection(source, consumer) }
+                    .apply(&mut graph)
+                    .unwrap();
+
+                for edge in result.removed_edges() {
+                    edge.reinsert().apply(&mut graph).unwrap();
+                }
+
+                assert_eq!(recipient(&graph).connection, expected.connection);
+            }
+
+            // As does saving and loading the graph.
+            let file = GraphFile::from_graph(&graph, &GraphMetadata::default());
+
+            graph = file.into_graph(&Substitutions::default()).unwrap().into();
+            assert_eq!(recipient(&graph).connection, expected.connection);
+            assert!(recipient(&graph).primary);
+        }
+    }
+
+    #[test]
+    fn restoring_edits_nothing_unless_all_edges_can_be_recreated() {
+        let (mut graph, [a, b, c]) = chain();
+        let restore =
+            GraphCommand::RemoveNode { node: b }.apply(&mut graph).unwrap().into_restore_command().unwrap();
+
+        GraphCommand::RemoveNode { node: c }.apply(&mut graph).unwrap();
+
+        assert!(restore.apply(&mut graph).is_err());
+        assert_eq!(graph.node_indices().collect::<Vec<_>>(), vec![a]);
+        assert_eq!(graph.edge_count(), 0);
+    }
+}
@@ -0,0 +1,335 @@
+//! Comparison of two snapshots of a graph, such as the saved file and the graph being edited.
+//!
+//! Nodes are matched by their [`NodeId`], so that renaming or moving a node is reported as such
+//! rather than as the node being replaced. The settings of behaviours are compared in their
+//! serialized form, and the keys whose values differ are reported.
+
+use super::file::{EdgeFile, GraphFile, NodeFile};
+use super::{ChannelOrder, EdgeEndpoint, NodeId};
+use crate::style::NodeColor;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{self, Display};
+use vek::Vec2;
+
+/// Nodes moved by less than this distance are not reported, so that the jitter of the layout does
+/// not show up as changes.
+pub const MOVE_THRESHOLD: f32 = 4.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeChange {
+    Added,
+    Removed,
+    Renamed {
+        previous: String,
+    },
+    Moved {
+        previous: Vec2<f32>,
+        current: Vec2<f32>,
+    },
+    /// A property of the node other than the settings of its behaviour, such as its color.
+    PropertyChanged {
+        property: &'static str,
+        previous: String,
+        current: String,
+    },
+    /// A value of the settings of the behaviour. The key is the path to the value within the
+    /// settings, `None` values are missing from the settings.
+    SettingChanged {
+        key: String,
+        previous: Option<serde_json::Value>,
+        current: Option<serde_json::Value>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDiff {
+    pub id: NodeId,
+    /// The current title, or the saved one for removed nodes.
+    pub title: String,
+    pub change: NodeChange,
+}
+
+impl Display for NodeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let title = &self.title;
+
+        match &self.change {
+            NodeChange::Added => write!(f, "Added node `{}`.", title),
+            NodeChange::Removed => write!(f, "Removed node `{}`.", title),
+            NodeChange::Renamed { previous } => write!(f, "Renamed node `{}` to `{}`.", previous, title),
+            NodeChange::Moved { previous, current } => {
+                write!(
+                    f,
+                    "Moved node `{}` by {:.0}, {:.0}.",
+                    title,
+                    current.x - previous.x,
+                    current.y - previous.y
+                )
+            }
+            NodeChange::PropertyChanged { property, previous, current } => {
+                write!(f, "Changed the {} of node `{}`: {} → {}.", property, title, previous, current)
+            }
+            NodeChange::SettingChanged { key, previous, current } => {
+                let format_value = |value: &Option<serde_json::Value>| {
+                    value.as_ref().map(|value| value.to_string()).unwrap_or_else(|| "none".to_string())
+                };
+
+                if key.is_empty() {
+                    write!(f, "Changed the settings of node `{}`", title)?;
+                } else {
+                    write!(f, "Changed `{}` of node `{}`", key, title)?;
+                }
+
+                write!(f, ": {} → {}.", format_value(previous), format_value(current))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeChange {
+    Added,
+    Removed,
+    /// The value of the output is moved into the edge if `true`, or no longer if `false`.
+    Primary(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeDiff {
+    pub from: NodeId,
+    pub to: NodeId,
+    /// The title of the source node, for display.
+    pub from_title: String,
+    /// The title of the target node, for display.
+    pub to_title: String,
+    pub endpoint_from: EdgeEndpoint,
+    pub endpoint_to: EdgeEndpoint,
+    pub change: EdgeChange,
+}
+
+impl Display for EdgeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (verb, suffix) = match self.change {
+            EdgeChange::Added => ("Added", ""),
+            EdgeChange::Removed => ("Removed", ""),
+            EdgeChange::Primary(true) => ("Marked", " as primary"),
+            EdgeChange::Primary(false) => ("Unmarked", " as primary"),
+        };
+
+        write!(
+            f,
+            "{} connection from output #{} of `{}` to input #{} of `{}`{}.",
+            verb,
+            self.endpoint_from.channel_index + 1,
+            self.from_title,
+            self.endpoint_to.channel_index + 1,
+            self.to_title,
+            suffix,
+        )
+    }
+}
+
+/// The changes turning one snapshot of a graph into another.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphDiff {
+    pub nodes: Vec<NodeDiff>,
+    pub edges: Vec<EdgeDiff>,
+}
+
+impl GraphDiff {
+    /// Lists the changes from `previous` to `current`, in the order of the nodes of `current`,
+    /// followed by the removed nodes.
+    pub fn new(previous: &GraphFile, current: &GraphFile) -> Self {
+        let previous_nodes: HashMap<NodeId, &NodeFile> =
+            previous.nodes.iter().map(|node| (node.id, node)).collect();
+        let current_nodes: HashMap<NodeId, &NodeFile> =
+            current.nodes.iter().map(|node| (node.id, node)).collect();
+        let mut nodes = Vec::new();
+
+        for node in &current.nodes {
+            match previous_nodes.get(&node.id) {
+                Some(previous_node) => diff_node(previous_node, node, &mut nodes),
+                None => {
+                    nodes.push(NodeDiff { id: node.id, title: node.title.clone(), change: NodeChange::Added })
+                }
+            }
+        }
+
+        for node in previous.nodes.iter().filter(|node| !current_nodes.contains_key(&node.id)) {
+            nodes.push(NodeDiff { id: node.id, title: node.title.clone(), change: NodeChange::Removed });
+        }
+
+        let previous_edges = edge_keys(previous);
+        let current_edges = edge_keys(current);
+        let title = |id: NodeId| {
+            current_nodes
+                .get(&id)
+                .or_else(|| previous_nodes.get(&id))
+                .map(|node| node.title.clone())
+                .unwrap_or_default()
+        };
+        let edges = current_edges
+            .iter()
+            .filter_map(|(key, primary)| match previous_edges.get(key) {
+                None => Some((key, EdgeChange::Added)),
+                Some(previous_primary) if previous_primary != primary => {
+                    Some((key, EdgeChange::Primary(*primary)))
+                }
+                Some(_) => None,
+            })
+            .chain(
+                previous_edges
+                    .keys()
+                    .filter(|key| !current_edges.contains_key(key))
+                    .map(|key| (key, EdgeChange::Removed)),
+            )
+            .map(|(&(from, to, endpoint_from, endpoint_to), change)| EdgeDiff {
+                from,
+                to,
+                from_title: title(from),
+                to_title: title(to),
+                endpoint_from,
+                endpoint_to,
+                change,
+            })
+            .collect();
+
+        Self { nodes, edges }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len() + self.edges.len()
+    }
+
+    /// A line per change, the nodes first.
+    pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.nodes.iter().map(ToString::to_string).chain(self.edges.iter().map(ToString::to_string))
+    }
+
+    /// Summarizes the changes since the graph was saved, listing at most `max_lines` of them.
+    pub fn describe(&self, max_lines: usize) -> String {
+        let mut description = match self.len() {
+            0 => return "The graph has not changed since it was saved.".to_string(),
+            1 => "1 change since the graph was saved:".to_string(),
+            count => format!("{} changes since the graph was saved:", count),
+        };
+
+        for line in self.lines().take(max_lines) {
+            description.push('\n');
+            description.push_str(&line);
+        }
+
+        if self.len() > max_lines {
+            description.push_str(&format!("\n…and {} more.", self.len() - max_lines));
+        }
+
+        description
+    }
+}
+
+type EdgeKey = (NodeId, NodeId, EdgeEndpoint, EdgeEndpoint);
+
+/// The edges of the file identified by the nodes they connect, rather than by the positions of the
+/// nodes in the file, mapped to their primary flags. Edges referring to missing nodes are left out.
+fn edge_keys(file: &GraphFile) -> BTreeMap<EdgeKey, bool> {
+    file.edges
+        .iter()
+        .filter_map(|edge: &EdgeFile| {
+            let from = file.nodes.get(edge.from)?.id;
+            let to = file.nodes.get(edge.to)?.id;
+
+            Some(((from, to, edge.endpoint_from, edge.endpoint_to), edge.primary))
+        })
+        .collect()
+}
+
+fn diff_node(previous: &NodeFile, current: &NodeFile, diffs: &mut Vec<NodeDiff>) {
+    let mut push = |change| diffs.push(NodeDiff { id: current.id, title: current.title.clone(), change });
+
+    if previous.title != current.title {
+        push(NodeChange::Renamed { previous: previous.title.clone() });
+    }
+
+    if previous.position.distance(current.position) >= MOVE_THRESHOLD {
+        push(NodeChange::Moved { previous: previous.position, current: current.position });
+    }
+
+    let mut push_property = |property, previous: String, current: String| {
+        if previous != current {
+            push(NodeChange::PropertyChanged { property, previous, current });
+        }
+    };
+    let format_color = |color: Option<NodeColor>| {
+        color.map(|color| format!("{:?}", color)).unwrap_or_else(|| "none".to_string())
+    };
+    let format_length =
+        |length: Option<u16>| length.map(|length| length.to_string()).unwrap_or_else(|| "fit".to_string());
+    let format_channel_order = |order: &ChannelOrder| {
+        if order.is_natural() {
+            "natural".to_string()
+        } else {
+            format!("inputs {:?}, outputs {:?}", order.inputs, order.outputs)
+        }
+    };
+
+    push_property("kind", previous.behaviour.clone(), current.behaviour.clone());
+    push_property("color", format_color(previous.color), format_color(current.color));
+    push_property("width", format_length(previous.width), format_length(current.width));
+    push_property("height", format_length(previous.height), format_length(current.height));
+    push_property("collapsed", previous.collapsed.to_string(), current.collapsed.to_string());
+    push_property(
+        "channel order",
+        format_channel_order(&previous.channel_order),
+        format_channel_order(&current.channel_order),
+    );
+    push_property("mute", previous.muted.to_string(), current.muted.to_string());
+    push_property("solo", previous.soloed.to_string(), current.soloed.to_string());
+
+    // The settings of different behaviours are not comparable.
+    if previous.behaviour == current.behaviour {
+        let mut settings_changes = Vec::new();
+
+        diff_values(String::new(), Some(&previous.settings), Some(&current.settings), &mut settings_changes);
+
+        for (key, previous, current) in settings_changes {
+            push(NodeChange::SettingChanged { key, previous, current });
+        }
+    }
+}
+
+/// Collects the paths of the values that differ, descending into objects and into arrays of the
+/// same length. The path of a value is the keys and indices leading to it, separated by dots.
+fn diff_values(
+    path: String,
+    previous: Option<&serde_json::Value>,
+    current: Option<&serde_json::Value>,
+    changes: &mut Vec<(String, Option<serde_json::Value>, Option<serde_json::Value>)>,
+) {
+    use serde_json::Value;
+
+    let child_path =
+        |key: &dyn Display| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+
+    match (previous, current) {
+        (Some(Value::Object(previous)), Some(Value::Object(current))) => {
+            let keys = previous.keys().chain(current.keys()).collect::<BTreeSet<_>>();
+
+            for key in keys {
+                diff_values(child_path(key), previous.get(key), current.get(key), changes);
+            }
+        }
+        (Some(Value::Array(previous)), Some(Value::Array(current))) if previous.len() == current.len() => {
+            for (index, (previous, current)) in previous.iter().zip(current).enumerate() {
+                diff_values(child_path(&index), Some(previous), Some(current), changes);
+            }
+        }
+        (previous, current) if previous != current => {
+            changes.push((path, previous.cloned(), current.cloned()));
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,816 @@
+//! Serialization of graphs into files.
+//!
+//! A graph is stored as JSON. Nodes are stored along with the name of their behaviour and the
+//! settings returned by [`NodeBehaviour::save`](crate::node::behaviour::NodeBehaviour::save), edges
+//! refer to nodes by their position in the list of nodes.
+//!
+//! Files are versioned. Files of older versions are upgraded by [`MIGRATIONS`] before being
+//! deserialized, files of newer versions are rejected.
+
+use super::{ChannelOrder, EdgeData, EdgeEndpoint, Graph, NodeData, NodeId, NodeIndex};
+use crate::library::LibraryOrigin;
+use crate::node::behaviour::{create_behaviour, PlaceholderNodeBehaviour};
+use crate::node::ChannelDirection;
+use crate::safe_mode::Substitutions;
+use crate::style::NodeColor;
+use crate::widgets::{FloatingPaneLength, MIN_PANE_OPACITY};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use vek::Vec2;
+
+/// The version of the file format written by this version of DVSynth.
+pub const FORMAT_VERSION: u32 = MIGRATIONS.len() as u32;
+
+type Migration = fn(&mut serde_json::Value) -> Result<(), String>;
+
+/// Upgrades a document of the version equal to the index of the migration to the next version.
+/// Migrations operate on the parsed JSON rather than on the live types, which may change later.
+const MIGRATIONS: &[Migration] = &[
+    add_metadata as Migration,
+    add_mute_solo,
+    add_node_ids,
+    add_node_heights,
+    add_edge_primary_flags,
+    add_node_collapsed_flags,
+    add_channel_orders,
+    add_node_display_flags,
+    add_collapsed_channel_groups,
+    add_library_origins,
+];
+
+/// Version 0 → 1: Files written before versioning was introduced have no metadata.
+fn add_metadata(document: &mut serde_json::Value) -> Result<(), String> {
+    let now = unix_time();
+    let document = document.as_object_mut().ok_or_else(|| "The document is not an object.".to_string())?;
+
+    document.insert(
+        "metadata".to_string(),
+        serde_json::json!({
+            "title": "",
+            "author": "",
+            "description": "",
+            "created": now,
+            "modified": now,
+            "dvsynth_version": "",
+        }),
+    );
+
+    Ok(())
+}
+
+/// Version 1 → 2: Sink nodes can be muted and soloed, which nodes of older files are not.
+fn add_mute_solo(document: &mut serde_json::Value) -> Result<(), String> {
+    let nodes = document
+        .get_mut("nodes")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| "The document has no list of nodes.".to_string())?;
+
+    for node in nodes {
+        let node = node.as_object_mut().ok_or_else(|| "A node is not an object.".to_string())?;
+
+        node.insert("muted".to_string(), false.into());
+        node.insert("soloed".to_string(), false.into());
+    }
+
+    Ok(())
+}
+
+/// Version 2 → 3: Nodes are identified across saves, nodes of older files are identified by their
+/// position.
+fn add_node_ids(document: &mut serde_json::Value) -> Result<(), String> {
+    let nodes = document
+        .get_mut("nodes")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| "The document has no list of nodes.".to_string())?;
+
+    for (position, node) in nodes.iter_mut().enumerate() {
+        let node = node.as_object_mut().ok_or_else(|| "A node is not an object.".to_string())?;
+
+        node.insert("id".to_string(), position.into());
+    }
+
+    Ok(())
+}
+
+/// Version 3 → 4: Panes can be resized vertically, panes of older files fit their content.
+fn add_node_heights(document: &mut serde_json::Value) -> Result<(), String> {
+    let nodes = document
+        .get_mut("nodes")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| "The document has no list of nodes.".to_string())?;
+
+    for node in nodes {
+        let node = node.as_object_mut().ok_or_else(|| "A node is not an object.".to_string())?;
+
+        node.insert("height".to_string(), serde_json::Value::Null);
+    }
+
+    Ok(())
+}
+
+/// Version 4 → 5: The recipient of a moved value can be designated, edges of older files are not.
+fn add_edge_primary_flags(document: &mut serde_json::Value) -> Result<(), String> {
+    let edges = document
+        .get_mut("edges")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| "The document has no list of edges.".to_string())?;
+
+    for edge in edges {
+        let edge = edge.as_object_mut().ok_or_else(|| "An edge is not an object.".to_string())?;
+
+        edge.insert("primary".to_string(), false.into());
+    }
+
+    Ok(())
+}
+
+/// Version 5 → 6: Panes can be collapsed, panes of older files are expanded.
+fn add_node_collapsed_flags(document: &mut serde_json::Value) -> Result<(), String> {
+    let nodes = document
+        .get_mut("nodes")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| "The document has no list of nodes.".to_string())?;
+
+    for node in nodes {
+        let node = node.as_object_mut().ok_or_else(|| "A node is not an object.".to_string())?;
+
+        node.insert("collapsed".to_string(), false.into());
+    }
+
+    Ok(())
+}
+
+/// Version 6 → 7: Channels can be displayed in a different order, nodes of older files display
+/// them in the natural order.
+fn add_channel_orders(document: &mut serde_json::Value) -> Result<(), String> {
+    let nodes = document
+        .get_mut("nodes")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| "The document has no list of nodes.".to_string())?;
+
+    for node in nodes {
+        let node = node.as_object_mut().ok_or_else(|| "A node is not an object.".to_string())?;
+
+        node.insert("channel_order".to_string(), serde_json::json!({ "inputs": [], "outputs": [] }));
+    }
+
+    Ok(())
+}
+
+/// Version 7 → 8: Panes can be pinned on top, made translucent and made to let the cursor through,
+/// panes of older files are none of these.
+fn add_node_display_flags(document: &mut serde_json::Value) -> Result<(), String> {
+    let nodes = document
+        .get_mut("nodes")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| "The document has no list of nodes.".to_string())?;
+
+    for node in nodes {
+        let node = node.as_object_mut().ok_or_else(|| "A node is not an object.".to_string())?;
+
+        node.insert("pinned".to_string(), false.into());
+        node.insert("opacity".to_string(), 1.0.into());
+        node.insert("click_through".to_string(), false.into());
+    }
+
+    Ok(())
+}
+
+/// Version 8 → 9: Collapsed groups of channels are saved, nodes of older files have all groups
+/// expanded.
+fn add_collapsed_channel_groups(document: &mut serde_json::Value) -> Result<(), String> {
+    let nodes = document
+        .get_mut("nodes")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| "The document has no list of nodes.".to_string())?;
+
+    for node in nodes {
+        let node = node.as_object_mut().ok_or_else(|| "A node is not an object.".to_string())?;
+
+        node.insert("collapsed_channel_groups".to_string(), serde_json::json!([]));
+    }
+
+    Ok(())
+}
+
+/// Version 9 → 10: Nodes remember the library entry they were inserted from, nodes of older files
+/// were not inserted from any.
+fn add_library_origins(document: &mut serde_json::Value) -> Result<(), String> {
+    let nodes = document
+        .get_mut("nodes")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| "The document has no list of nodes.".to_string())?;
+
+    for node in nodes {
+        let node = node.as_object_mut().ok_or_else(|| "A node is not an object.".to_string())?;
+
+        node.insert("library_origin".to_string(), serde_json::Value::Null);
+    }
+
+    Ok(())
+}
+
+/// Upgrades the document to [`FORMAT_VERSION`], returns whether any migrations were applied.
+fn migrate(document: &mut serde_json::Value) -> Result<bool, GraphFileError> {
+    // Files written before versioning was introduced have no version.
+    let version = match document.get("version") {
+        Some(version) => version.as_u64().ok_or(GraphFileError::InvalidVersion)?,
+        None => 0,
+    };
+
+    if version > FORMAT_VERSION as u64 {
+        return Err(GraphFileError::UnsupportedVersion(version));
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(document).map_err(|message| GraphFileError::Migration { from: from as u32, message })?;
+        document["version"] = (from as u32 + 1).into();
+    }
+
+    Ok(version < FORMAT_VERSION as u64)
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+#[derive(Debug)]
+pub enum GraphFileError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnknownBehaviour(String),
+    InvalidBehaviourSettings { node_title: String, error: serde_json::Error },
+    InvalidEdge { from: usize, to: usize },
+    InvalidVersion,
+    UnsupportedVersion(u64),
+    Migration { from: u32, message: String },
+}
+
+impl From<io::Error> for GraphFileError {
+    fn from(error: io::Error) -> Self {
+        GraphFileError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for GraphFileError {
+    fn from(error: serde_json::Error) -> Self {
+        GraphFileError::Json(error)
+    }
+}
+
+impl Display for GraphFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use GraphFileError::*;
+        match self {
+            Io(error) => write!(f, "I/O error: {}", error),
+            Json(error) => write!(f, "The file is not a valid graph: {}", error),
+            UnknownBehaviour(name) => write!(f, "Unknown kind of node `{}`.", name),
+            InvalidBehaviourSettings { node_title, error } => {
+                write!(f, "Invalid settings of node `{}`: {}", node_title, error)
+            }
+            InvalidEdge { from, to } => {
+                write!(f, "The connection from node #{} to node #{} refers to a missing node.", from, to)
+            }
+            InvalidVersion => write!(f, "The version of the file is not a valid number."),
+            UnsupportedVersion(version) => write!(
+                f,
+                "The file was written by a newer version of DVSynth (format version {}, this version supports \
+                 up to {}).",
+                version, FORMAT_VERSION
+            ),
+            Migration { from, message } => {
+                write!(f, "Could not upgrade the file from format version {}: {}", from, message)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeFile {
+    /// Unique within the file, see [`NodeId`].
+    pub id: NodeId,
+    pub title: String,
+    pub behaviour: String,
+    pub settings: serde_json::Value,
+    pub color: Option<NodeColor>,
+    pub muted: bool,
+    pub soloed: bool,
+    pub position: Vec2<f32>,
+    /// `None` if the pane shrinks to fit its content.
+    pub width: Option<u16>,
+    /// `None` if the pane fits its content, up to a maximum height.
+    pub height: Option<u16>,
+    /// Whether only the title bar and the connection points of the pane are displayed.
+    pub collapsed: bool,
+    /// Whether the pane is drawn above all panes that are not pinned.
+    pub pinned: bool,
+    pub opacity: f32,
+    /// Whether the content of the pane lets the cursor through to whatever is below it.
+    pub click_through: bool,
+    pub channel_order: ChannelOrder,
+    /// The groups of channels displayed collapsed, identified by their direction and name.
+    pub collapsed_channel_groups: Vec<(ChannelDirection, String)>,
+    /// The library entry the node was inserted from, see [`LibraryOrigin`].
+    pub library_origin: Option<LibraryOrigin>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeFile {
+    /// The position of the source node in [`GraphFile::nodes`].
+    pub from: usize,
+    /// The position of the target node in [`GraphFile::nodes`].
+    pub to: usize,
+    pub endpoint_from: EdgeEndpoint,
+    pub endpoint_to: EdgeEndpoint,
+    /// Whether the value of the output is moved into this edge.
+    pub primary: bool,
+}
+
+/// Information about a graph, edited by the user except for the timestamps and the version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphMetadata {
+    pub title: String,
+    pub author: String,
+    pub description: String,
+    /// Seconds since the Unix epoch.
+    pub created: u64,
+    /// Seconds since the Unix epoch, updated whenever the graph is saved.
+    pub modified: u64,
+    /// The version of DVSynth the graph was last saved with.
+    pub dvsynth_version: String,
+    /// Whether outputs may be connected to inputs of wider primitive types, converting the values
+    /// implicitly.
+    #[serde(default)]
+    pub implicit_promotion: bool,
+    /// Whether the non-finite floating-point values output by the nodes are replaced with
+    /// `sanitize_substitute`.
+    #[serde(default)]
+    pub sanitize_numerics: bool,
+    #[serde(default)]
+    pub sanitize_substitute: f64,
+}
+
+impl GraphMetadata {
+    /// The value the non-finite values output by the nodes are replaced with, `None` if they are
+    /// not replaced.
+    pub fn sanitize_substitute(&self) -> Option<f64> {
+        Some(self.sanitize_substitute).filter(|_| self.sanitize_numerics)
+    }
+}
+
+impl Default for GraphMetadata {
+    fn default() -> Self {
+        let now = unix_time();
+
+        Self {
+            title: String::new(),
+            author: String::new(),
+            description: String::new(),
+            created: now,
+            modified: now,
+            dvsynth_version: env!("CARGO_PKG_VERSION").to_string(),
+            implicit_promotion: false,
+            sanitize_numerics: false,
+            sanitize_substitute: 0.0,
+        }
+    }
+}
+
+/// A snapshot of a graph, independent of the live UI and executor state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphFile {
+    pub version: u32,
+    pub metadata: GraphMetadata,
+    pub nodes: Vec<NodeFile>,
+    pub edges: Vec<EdgeFile>,
+}
+
+impl GraphFile {
+    pub fn from_graph(graph: &Graph, metadata: &GraphMetadata) -> Self {
+        Self::from_nodes(graph, metadata, |_| true)
+    }
+
+    /// Like [`GraphFile::from_graph`], but only with the nodes for which `include` returns `true`
+    /// and the edges among them.
+    pub fn from_nodes(graph: &Graph, metadata: &GraphMetadata, include: impl Fn(NodeIndex) -> bool) -> Self {
+        let node_positions: HashMap<NodeIndex, usize> = graph
+            .node_indices()
+            .filter(|node_index| include(*node_index))
+            .enumerate()
+            .map(|(enumeration_index, node_index)| (node_index, enumeration_index))
+            .collect();
+        let nodes = graph
+            .node_indices()
+            .filter(|node_index| include(*node_index))
+            .map(|node_index| &graph[node_index])
+            .map(|node| NodeFile {
+                id: node.id,
+                title: node.title.clone(),
+                behaviour: node.behaviour.name().to_string(),
+                settings: node.behaviour.save(),
+                color: node.color,
+                muted: node.muted,
+                soloed: node.soloed,
+                position: node.floating_pane_state.position,
+                width: match node.floating_pane_state.size[0] {
+                    FloatingPaneLength::Shrink => None,
+                    FloatingPaneLength::Units(units) => Some(units),
+                },
+                height: match node.floating_pane_state.size[1] {
+                    FloatingPaneLength::Shrink => None,
+                    FloatingPaneLength::Units(units) => Some(units),
+                },
+                collapsed: node.floating_pane_state.collapsed,
+                pinned: node.floating_pane_state.pinned,
+                opacity: node.floating_pane_state.opacity,
+                click_through: node.floating_pane_state.click_through,
+                channel_order: node.channel_order.clone(),
+                collapsed_channel_groups: node.element_state.collapsed_channel_groups(),
+                library_origin: node.library_origin.clone(),
+            })
+            .collect();
+        let edges = graph
+            .edge_indices()
+            .filter_map(|edge_index| {
+                let (from, to) = graph.edge_endpoints(edge_index).unwrap();
+                let edge = &graph[edge_index];
+
+                Some(EdgeFile {
+                    from: *node_positions.get(&from)?,
+                    to: *node_positions.get(&to)?,
+                    endpoint_from: edge.endpoint_from,
+                    endpoint_to: edge.endpoint_to,
+                    primary: edge.primary,
+                })
+            })
+            .collect();
+
+        Self {
+            version: FORMAT_VERSION,
+            metadata: GraphMetadata {
+                modified: unix_time(),
+                dvsynth_version: env!("CARGO_PKG_VERSION").to_string(),
+                ..metadata.clone()
+            },
+            nodes,
+            edges,
+        }
+    }
+
+    /// Instantiates the nodes and connections of the file. The nodes chosen by the substitutions
+    /// are replaced by placeholders, see [`PlaceholderNodeBehaviour`].
+    pub fn into_graph(self, substitutions: &Substitutions) -> Result<Graph, GraphFileError> {
+        let mut graph = Graph::new();
+        let mut node_indices = Vec::with_capacity(self.nodes.len());
+
+        for node in self.nodes {
+            let mut behaviour = create_behaviour(&node.behaviour)
+                .ok_or_else(|| GraphFileError::UnknownBehaviour(node.behaviour.clone()))?;
+
+            behaviour.load(node.settings).map_err(|error| GraphFileError::InvalidBehaviourSettings {
+                node_title: node.title.clone(),
+                error,
+            })?;
+
+            let mut node_data = NodeData::new(node.title, node.position, behaviour);
+
+            node_data.id = node.id.reserve();
+            node_data.color = node.color;
+            node_data.muted = node.muted;
+            node_data.soloed = node.soloed;
+            node_data.floating_pane_state.size[0] =
+                node.width.map(FloatingPaneLength::Units).unwrap_or(FloatingPaneLength::Shrink);
+            node_data.floating_pane_state.size[1] =
+                node.height.map(FloatingPaneLength::Units).unwrap_or(FloatingPaneLength::Shrink);
+            node_data.floating_pane_state.collapsed = node.collapsed;
+            node_data.floating_pane_state.pinned = node.pinned;
+            node_data.floating_pane_state.opacity = node.opacity.max(MIN_PANE_OPACITY).min(1.0);
+            node_data.floating_pane_state.click_through = node.click_through;
+            node_data.channel_order = node.channel_order;
+            node_data.channel_order.validate(&node_data.configuration);
+            node_data.element_state.set_collapsed_channel_groups(node.collapsed_channel_groups);
+            node_data.library_origin = node.library_origin;
+
+            if let Some(reason) = substitutions.reason(node_data.behaviour.as_ref()) {
+                node_data.behaviour = Box::new(PlaceholderNodeBehaviour::replacing(
+                    reason,
+                    node_data.behaviour.as_ref(),
+                    node_data.configuration.clone(),
+                ));
+            }
+
+            node_indices.push(graph.add_node(node_data));
+        }
+
+        for edge in self.edges {
+            let (from, to) = match (node_indices.get(edge.from), node_indices.get(edge.to)) {
+                (Some(from), Some(to)) => (*from, *to),
+                _ => return Err(GraphFileError::InvalidEdge { from: edge.from, to: edge.to }),
+            };
+
+            graph.add_edge(
+                from,
+                to,
+                EdgeData {
+                    endpoint_from: edge.endpoint_from,
+                    endpoint_to: edge.endpoint_to,
+                    primary: edge.primary,
+                },
+            );
+        }
+
+        Ok(graph)
+    }
+
+    /// Reads the file, upgrading it to the current format version if necessary.
+    /// Also returns whether the file was stored in an older format, and should therefore be saved
+    /// again.
+    pub fn load(path: &Path) -> Result<(Self, bool), GraphFileError> {
+        let source = fs::read(path)?;
+
+        Self::from_document(serde_json::from_slice(&source)?)
+    }
+
+    /// Deserializes the parsed file, upgrading it to the current format version if necessary, such
+    /// as when the graph is embedded in another file.
+    pub fn from_document(mut document: serde_json::Value) -> Result<(Self, bool), GraphFileError> {
+        let migrated = migrate(&mut document)?;
+
+        Ok((serde_json::from_value(document)?, migrated))
+    }
+
+    /// Writes the file into a temporary file first, so that the previous contents remain intact
+    /// if writing fails.
+    pub fn save(&self, path: &Path) -> Result<(), GraphFileError> {
+        let source = serde_json::to_vec_pretty(self)?;
+        let mut temporary_path = path.as_os_str().to_owned();
+
+        temporary_path.push(".tmp");
+
+        fs::write(&temporary_path, source)?;
+        fs::rename(&temporary_path, path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::behaviour::{all_behaviours, Prng};
+    use crate::node::ChannelPassBy;
+
+    const PASS_BY: [ChannelPassBy; 3] =
+        [ChannelPassBy::SharedReference, ChannelPassBy::MutableReference, ChannelPassBy::Value];
+    const DIRECTIONS: [ChannelDirection; 2] = [ChannelDirection::In, ChannelDirection::Out];
+
+    /// A gradient, which is external, connected to a debug node.
+    fn graph() -> Graph {
+        let mut graph = Graph::new();
+        let gradient =
+            graph.add_node(NodeData::new("Gradient", [0.0, 0.0], create_behaviour("Gradient").unwrap()));
+        let debug = graph.add_node(NodeData::new("Debug", [200.0, 0.0], create_behaviour("Debug").unwrap()));
+
+        graph.add_edge(
+            gradient,
+            debug,
+            EdgeData {
+                endpoint_from: EdgeEndpoint { channel_index: 0, pass_by: ChannelPassBy::SharedReference },
+                endpoint_to: EdgeEndpoint { channel_index: 0, pass_by: ChannelPassBy::SharedReference },
+                primary: false,
+            },
+        );
+        graph
+    }
+
+    /// The nodes and edges of the file, without the metadata, which records the time of saving.
+    fn contents(graph: &Graph) -> serde_json::Value {
+        let file = GraphFile::from_graph(graph, &GraphMetadata::default());
+
+        serde_json::json!({ "nodes": file.nodes, "edges": file.edges })
+    }
+
+    /// The contents of the file of the graph as written before versioning was introduced, without
+    /// the fields added by the migrations.
+    fn unversioned_document(graph: &Graph) -> serde_json::Value {
+        let mut document = contents(graph);
+        let node_fields = [
+            "id",
+            "muted",
+            "soloed",
+            "height",
+            "collapsed",
+            "channel_order",
+            "pinned",
+            "opacity",
+            "click_through",
+            "collapsed_channel_groups",
+            "library_origin",
+        ];
+
+        for node in document["nodes"].as_array_mut().unwrap() {
+            let node = node.as_object_mut().unwrap();
+
+            for field in &node_fields {
+                node.remove(*field).unwrap();
+            }
+        }
+
+        for edge in document["edges"].as_array_mut().unwrap() {
+            edge.as_object_mut().unwrap().remove("primary").unwrap();
+        }
+
+        document
+    }
+
+    /// A graph of random nodes with random pane states, connected by random edges.
+    fn random_graph(prng: &mut Prng) -> Graph {
+        let behaviours = all_behaviours();
+        let mut graph = Graph::new();
+        let node_count = 1 + prng.next_u64() as usize % 12;
+        let nodes = (0..node_count)
+            .map(|index| {
+                let behaviour = &behaviours[prng.next_u64() as usize % behaviours.len()];
+                let position = [prng.next_f64() as f32 * 2000.0 - 1000.0, prng.next_f64() as f32 * 2000.0];
+                let mut node_data = NodeData::new(format!("Node #{}", index), position, behaviour.clone());
+                let length = |prng: &mut Prng| match prng.next_u64() % 3 {
+                    0 => FloatingPaneLength::Shrink,
+                    _ => FloatingPaneLength::Units(prng.next_u64() as u16 % 1000),
+                };
+
+                node_data.color = Some(NodeColor::VALUES[prng.next_u64() as usize % NodeColor::VALUES.len()])
+                    .filter(|_| prng.chance(0.5));
+                node_data.muted = prng.chance(0.5);
+                node_data.soloed = prng.chance(0.5);
+                node_data.floating_pane_state.size = [length(prng), length(prng)].into();
+                node_data.floating_pane_state.collapsed = prng.chance(0.5);
+                node_data.floating_pane_state.pinned = prng.chance(0.5);
+                node_data.floating_pane_state.opacity =
+                    MIN_PANE_OPACITY + prng.next_f64() as f32 * (1.0 - MIN_PANE_OPACITY);
+                node_data.floating_pane_state.click_through = prng.chance(0.5);
+                node_data
+                    .element_state
+                    .set_collapsed_channel_groups((0..prng.next_u64() % 3).map(|group| {
+                        (DIRECTIONS[prng.next_u64() as usize % 2], format!("Group #{}", group))
+                    }));
+                node_data.library_origin = Some(LibraryOrigin {
+                    name: format!("Entry #{}", prng.next_u64() % 3),
+                    content_hash: format!("{:016x}", prng.next_u64()),
+                    instance: prng.next_u64() % 3,
+                })
+                .filter(|_| prng.chance(0.5));
+
+                graph.add_node(node_data)
+            })
+            .collect::<Vec<_>>();
+
+        for _ in 0..prng.next_u64() % 20 {
+            let node = |prng: &mut Prng| nodes[prng.next_u64() as usize % nodes.len()];
+            let endpoint = |prng: &mut Prng| EdgeEndpoint {
+                channel_index: prng.next_u64() as usize % 4,
+                pass_by: PASS_BY[prng.next_u64() as usize % PASS_BY.len()],
+            };
+            let (from, to) = (node(prng), node(prng));
+            let edge_data = EdgeData {
+                endpoint_from: endpoint(prng),
+                endpoint_to: endpoint(prng),
+                primary: prng.chance(0.5),
+            };
+
+            graph.add_edge(from, to, edge_data);
+        }
+
+        graph
+    }
+
+    fn placeholders(graph: &Graph) -> Vec<String> {
+        graph
+            .node_weights()
+            .filter(|node_data| node_data.behaviour.is_placeholder())
+            .map(|node_data| node_data.behaviour.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn safe_mode_substitutes_and_restores_external_nodes() {
+        let original = graph();
+        let safe_mode = Substitutions { safe_mode: true, ..Default::default() };
+        let substituted =
+            GraphFile::from_graph(&original, &GraphMetadata::default()).into_graph(&safe_mode).unwrap();
+
+        assert_eq!(placeholders(&substituted), vec!["Gradient".to_string()]);
+        assert_eq!(substituted.edge_count(), 1);
+        // The placeholder is saved as the node it replaces.
+        assert_eq!(contents(&substituted), contents(&original));
+
+        let restored = GraphFile::from_graph(&substituted, &GraphMetadata::default())
+            .into_graph(&Substitutions::default())
+            .unwrap();
+
+        assert!(placeholders(&restored).is_empty());
+        assert_eq!(contents(&restored), contents(&original));
+    }
+
+    #[test]
+    fn disabled_behaviours_stay_substituted_outside_of_safe_mode() {
+        let original = graph();
+        let disabled = Substitutions {
+            safe_mode: false,
+            disabled_behaviours: std::iter::once("Debug".to_string()).collect(),
+        };
+        let substituted =
+            GraphFile::from_graph(&original, &GraphMetadata::default()).into_graph(&disabled).unwrap();
+
+        // Disabling does not require the behaviour to be external.
+        assert_eq!(placeholders(&substituted), vec!["Debug".to_string()]);
+        assert_eq!(contents(&substituted), contents(&original));
+    }
+
+    #[test]
+    fn migrates_unversioned_documents() {
+        let original = graph();
+        let (file, migrated) = GraphFile::from_document(unversioned_document(&original)).unwrap();
+        let mut expected = contents(&original);
+
+        // Nodes of unversioned files are identified by their position.
+        for (position, node) in expected["nodes"].as_array_mut().unwrap().iter_mut().enumerate() {
+            node["id"] = position.into();
+        }
+
+        assert!(migrated);
+        assert_eq!(file.version, FORMAT_VERSION);
+        assert_eq!(serde_json::json!({ "nodes": file.nodes, "edges": file.edges }), expected);
+        assert_eq!(file.into_graph(&Substitutions::default()).unwrap().node_count(), 2);
+    }
+
+    /// Documents upgraded by some of the migrations and saved in between are upgraded by the rest.
+    #[test]
+    fn migrates_documents_of_every_version() {
+        let mut fully_migrated = unversioned_document(&graph());
+
+        migrate(&mut fully_migrated).unwrap();
+
+        for version in 0..=FORMAT_VERSION {
+            let mut document = unversioned_document(&graph());
+
+            for migration in &MIGRATIONS[..version as usize] {
+                migration(&mut document).unwrap();
+            }
+
+            if version > 0 {
+                document["version"] = version.into();
+            }
+
+            assert_eq!(migrate(&mut document).unwrap(), version < FORMAT_VERSION);
+
+            // The metadata added by the first migration records the time of the migration.
+            document["metadata"] = fully_migrated["metadata"].clone();
+
+            assert_eq!(document, fully_migrated, "migrating from version {}", version);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_versions() {
+        let mut newer = serde_json::json!({ "version": FORMAT_VERSION + 1 });
+        let mut invalid = serde_json::json!({ "version": "1" });
+
+        assert!(matches!(
+            migrate(&mut newer),
+            Err(GraphFileError::UnsupportedVersion(version)) if version == FORMAT_VERSION as u64 + 1
+        ));
+        assert!(matches!(migrate(&mut invalid), Err(GraphFileError::InvalidVersion)));
+    }
+
+    #[test]
+    fn reports_the_version_a_migration_failed_at() {
+        let mut document = serde_json::json!({ "version": 1 });
+
+        assert!(matches!(migrate(&mut document), Err(GraphFileError::Migration { from: 1, .. })));
+    }
+
+    #[test]
+    fn randomized_graphs_survive_round_trips() {
+        for seed in 0..100 {
+            let mut prng = Prng::new(seed);
+            let original = random_graph(&mut prng);
+            let document =
+                serde_json::to_value(GraphFile::from_graph(&original, &GraphMetadata::default())).unwrap();
+            let source = serde_json::to_vec_pretty(&document).unwrap();
+            let (file, migrated) =
+                GraphFile::from_document(serde_json::from_slice(&source).unwrap()).unwrap();
+
+            assert!(!migrated);
+            assert_eq!(serde_json::to_value(&file).unwrap(), document, "seed {}", seed);
+
+            let loaded = file.into_graph(&Substitutions::default()).unwrap();
+
+            assert_eq!(contents(&loaded), contents(&original), "seed {}", seed);
+        }
+    }
+}
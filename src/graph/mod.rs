@@ -1,40 +1,80 @@
-use crate::graph::alloc::AllocationInner;
+use crate::config::{Config, SharedConfig};
+use crate::graph::outputs::OutputValues;
+use crate::idle::MainThreadWaker;
+use crate::library::{Divergence, LibraryOrigin};
+use crate::logging::Category;
 use crate::node::behaviour::{
-    AllocatorHandle, ExecutionContext, MainThreadTask, NodeBehaviourContainer, NodeCommand,
-    NodeEventContainer, NodeStateContainer,
+    AllocatorHandle, ConnectedTypeInfo, ContextMenuItem, ExecutionContext, MainThreadTask,
+    NodeBehaviourContainer, NodeCommand, NodeEventContainer, NodeIcon, NodeStateContainer,
+    NodeValidationIssue, Severity,
+};
+use crate::node::ty::{
+    is_pointer, typed_bytes_to_ptr, AllocationPointer, BorrowedRef, BorrowedRefMut, OptionRefExt, OptionType,
+    RefAny, TextureIncompatibility, TypeEnum, TypeExt,
 };
-use crate::node::ty::{BorrowedRef, BorrowedRefMut, OptionRefExt, OptionType, TypeEnum, TypeExt};
 use crate::node::{
-    ChannelDirection, ChannelPassBy, ChannelRef, ChannelValueRefs, ChannelValues, ConnectionPassBy,
-    DynTypeTrait, ListDescriptor, NodeConfiguration, NodeStateRefcounter, OptionRefMutExt, RefAnyExt,
+    BytesRefExt, BytesRefMutExt, ChannelDirection, ChannelPassBy, ChannelRef, ChannelValueRefs,
+    ChannelValues, ConnectionPassBy, DynTypeTrait, ListDescriptor, NodeConfiguration, NodeStateRefcounter,
+    OptionRefMutExt, PrimitiveChannelValue, PrimitiveTypeEnum, RefAnyExt,
 };
-use crate::style::{self, consts, Theme, Themeable};
+use crate::startup;
+use crate::style::{self, consts, NodeColor, Theme, Themeable};
+use crate::threading::{AppliedThreadSettings, ThreadSettings};
 use crate::widgets::{
-    node::FloatingPanesBehaviour, FloatingPane, FloatingPaneBehaviourData, FloatingPaneBehaviourState,
+    node::{ChannelRows, FloatingPanesBehaviour},
+    FloatingPane, FloatingPaneBehaviourData, FloatingPaneBehaviourState, FloatingPaneLength,
     FloatingPaneState, NodeElement, NodeElementState,
 };
-use crate::ApplicationFlags;
 use crate::Message;
 use crate::NodeMessage;
-use alloc::Allocator;
-use arc_swap::ArcSwapOption;
-use iced::{Element, Settings};
-use iced_futures::futures;
+use alloc::{Allocator, GlobalRefcounter};
+use arc_swap::{ArcSwap, ArcSwapOption};
+use background::BackgroundTasks;
+use byteorder::LittleEndian;
+use iced::widget::checkbox::Checkbox;
+use iced::{button, Align, Button, Element, Row, Text};
+use iced_futures::futures::{
+    self,
+    channel::mpsc::{UnboundedReceiver, UnboundedSender},
+    FutureExt,
+};
 use iced_wgpu::wgpu;
-use petgraph::{algo::Cycle, stable_graph::StableGraph, visit::EdgeRef, Directed, Direction};
+use iced_winit::winit::window::{Window, WindowId};
+use indexmap::IndexMap;
+use petgraph::{
+    algo::Cycle, graphmap::DiGraphMap, stable_graph::StableGraph, visit::EdgeRef, Directed, Direction,
+};
+use recording::{RecordingControl, RecordingMode};
+use sanitize::FloatLayout;
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::future::Future;
 use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
 use std::rc::Rc;
-use std::sync::mpsc::{self, Receiver, Sender};
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
 use std::thread;
+use std::time::{Duration, Instant};
+use validation::{CheckResult, ConnectionKey, ValidationCache};
 use vek::Vec2;
 
 pub mod alloc;
+pub mod background;
+pub mod controller;
+pub mod diff;
+pub mod file;
+pub mod outputs;
+pub mod pipeline;
+pub mod recording;
+pub mod sanitize;
+#[cfg(test)]
+pub mod testing;
+pub mod validation;
 
 pub type NodeIndex = petgraph::graph::NodeIndex<u32>;
 pub type Graph = StableGraph<
@@ -58,28 +98,76 @@ pub struct PreparedTask {
     ///
     /// Provided as outputs by move (BorrowedRefMut<OptionType<T>>). After the task has finished
     /// executing, the value must be present.
-    pub output_values: Box<[RwLock<AllocationInner>]>,
+    pub output_values: OutputValues,
+    /// `OptionType`-wrapped values of the inputs with a [`Promotion`], converted from the output
+    /// values of the preceding tasks before each execution and passed to the task instead.
+    /// Pruned inputs are passed an empty value of their own.
+    pub promoted_values: OutputValues,
+    /// The index into `promoted_values` of each input passed by value, `None` for inputs that are
+    /// neither promoted nor pruned.
+    pub promoted_slots: Box<[Option<usize>]>,
+    /// The output channels checked for non-finite values while
+    /// [`ExecutionGraph::sanitize_numerics`] is enabled, along with the layouts of their values.
+    pub sanitized_outputs: Box<[(usize, FloatLayout)]>,
 }
 
 impl PreparedTask {
+    /// Releases the references held by the output values of a task that is being replaced.
+    /// The output values are removed, so that they cannot be released more than once.
+    fn release_output_values(&mut self) {
+        let output_values = std::mem::take(&mut self.output_values);
+
+        for channel_index in 0..output_values.len() {
+            unsafe {
+                output_values
+                    .value(channel_index, &())
+                    .unwrap()
+                    .refcount_decrement_recursive_for(&GlobalRefcounter)
+            };
+        }
+    }
+
     pub fn from(task: &Task, state: NodeStateContainer<'static>) -> Self {
+        let mut promoted_types = Vec::<TypeEnum>::new();
+        let promoted_slots = task
+            .inputs
+            .iter()
+            .zip(&task.configuration.input_channels_by_value)
+            .map(|(input, channel)| {
+                let ty = if input.pruned {
+                    Some(channel.ty.clone())
+                } else {
+                    input.promotion.map(|promotion| promotion.to.into())
+                };
+
+                ty.map(|ty| {
+                    promoted_types.push(OptionType::from_enum_if_sized(ty).unwrap().into());
+                    promoted_types.len() - 1
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        let sanitized_outputs = task
+            .configuration
+            .output_channels_by_value
+            .iter()
+            .enumerate()
+            .filter_map(|(channel_index, channel)| Some((channel_index, FloatLayout::of(&channel.ty)?)))
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+
         Self {
             node_index: task.node_index,
             state: Some(state),
-            output_values: task
-                .configuration
-                .output_channels_by_value
-                .iter()
-                .map(|channel| {
-                    RwLock::new(
-                        AllocationInner::from_enum_if_sized(
-                            OptionType::from_enum_if_sized(channel.ty.clone()).unwrap(),
-                        )
-                        .unwrap(),
-                    )
-                })
-                .collect::<Vec<_>>()
-                .into_boxed_slice(),
+            output_values: OutputValues::new(
+                task.configuration
+                    .output_channels_by_value
+                    .iter()
+                    .map(|channel| OptionType::from_enum_if_sized(channel.ty.clone()).unwrap().into()),
+            ),
+            promoted_values: OutputValues::new(promoted_types),
+            promoted_slots,
+            sanitized_outputs,
         }
     }
 }
@@ -94,8 +182,23 @@ pub struct PreparedExecution {
 static_assertions::assert_impl_all!(Arc<PreparedExecution>: Send, Sync);
 
 impl PreparedExecution {
-    fn from(schedule: &Schedule, context: &mut ApplicationContext, mut previous: Option<Self>) -> Self {
+    pub(crate) fn from(
+        schedule: &Schedule,
+        context: &mut ApplicationContext,
+        mut previous: Option<Self>,
+    ) -> Self {
+        // The outputs of the previous schedule are not carried over, release them before the task
+        // refcounters are reset for the new schedule.
+        if let Some(previous) = previous.as_mut() {
+            for task in previous.tasks.iter().flatten() {
+                task.write().unwrap_or_else(|error| error.into_inner()).release_output_values();
+            }
+        }
+
         Allocator::get().prepare_for_schedule(schedule);
+        // Give nodes that caused GPU errors another chance with the new schedule.
+        context.node_errors.clear_gpu_faults();
+        context.node_errors.retain_scheduled(schedule);
         let previous_node_index_map: Option<HashMap<NodeIndex, usize>> =
             previous.as_ref().map(|prepared_execution| {
                 prepared_execution
@@ -119,7 +222,7 @@ impl PreparedExecution {
                         let state = previous_node_index_map
                             .as_ref()
                             .and_then(|previous_node_index_map| previous_node_index_map.get(&task.node_index))
-                            .map(|task_index| {
+                            .and_then(|task_index| {
                                 let previous_task = &mut previous.as_mut().unwrap().tasks[*task_index]
                                     .as_ref()
                                     .unwrap()
@@ -130,9 +233,12 @@ impl PreparedExecution {
                                     .take()
                                     .expect("Attempt to duplicate reused state during schedule preparation.");
 
-                                task.behaviour.update_state(context, &mut state);
-
-                                state
+                                // The node index may have been reused by a different kind of node.
+                                if task.behaviour.update_state(context, &mut state) {
+                                    Some(state)
+                                } else {
+                                    None
+                                }
                             })
                             .unwrap_or_else(|| task.behaviour.create_state(context));
 
@@ -149,7 +255,24 @@ impl PreparedExecution {
         }
     }
 
-    pub fn execute(&mut self, schedule: &Schedule, context: &mut ApplicationContext) {
+    /// Releases the output values of all tasks, when the schedule is no longer going to be
+    /// executed.
+    pub(crate) fn release(mut self) {
+        for task in self.tasks.iter_mut().flatten() {
+            task.get_mut().unwrap_or_else(|error| error.into_inner()).release_output_values();
+        }
+    }
+
+    pub fn execute(
+        &mut self,
+        schedule: &Schedule,
+        context: &mut ApplicationContext,
+        recording: &mut Option<RecordingMode>,
+    ) {
+        context.update_renderer();
+
+        let mut pass_guards = PassGuards::new(&self.tasks, schedule);
+
         for (task_index, task) in schedule.tasks.iter().enumerate() {
             // Process enabled tasks only
             let task = if let Some(task) = task {
@@ -158,159 +281,45 @@ impl PreparedExecution {
                 continue;
             };
 
-            let (tasks_preceding, tasks_following) = self.tasks.split_at_mut(task_index);
-            let current_task: &mut PreparedTask = &mut tasks_following[0].as_ref().unwrap().write().unwrap();
-
-            {
-                // Borrows
-                let borrow_value_guards = task
-                    .borrows
-                    .iter()
-                    .map(|input| tasks_preceding[input.task_index].as_ref().unwrap().read().unwrap())
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                let borrow_value_guards = borrow_value_guards
-                    .iter()
-                    .zip(&*task.borrows)
-                    .map(|(task_preceding, input)| {
-                        task_preceding.output_values[input.output_value_channel_index].read().unwrap()
-                    })
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                let input_borrows = borrow_value_guards
-                    .iter()
-                    .map(|borrow_value_guard| {
-                        let input_typed_bytes = borrow_value_guard.as_ref(&());
-                        let input_ref_option =
-                            unsafe { BorrowedRef::<OptionType>::from_unchecked_type(input_typed_bytes) };
-                        input_ref_option
-                    })
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                let input_borrow_refs = input_borrows
-                    .iter()
-                    .map(|input_ref_option| input_ref_option.get().unwrap())
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-
-                // Mutable borrows
-                let mut mutable_borrow_value_guards = task
-                    .mutable_borrows
-                    .iter()
-                    .map(|input| tasks_preceding[input.task_index].as_ref().unwrap().read().unwrap())
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                let mut mutable_borrow_value_guards = mutable_borrow_value_guards
-                    .iter()
-                    .zip(&*task.mutable_borrows)
-                    .map(|(task_preceding, input)| {
-                        task_preceding.output_values[input.output_value_channel_index].write().unwrap()
-                    })
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                let mut rcs = vec![(); mutable_borrow_value_guards.len()];
-                let mut input_mutable_borrows = mutable_borrow_value_guards
-                    .iter_mut()
-                    .zip(rcs.iter_mut())
-                    .map(|(mutable_borrow_value_guard, rc)| {
-                        let input_typed_bytes = mutable_borrow_value_guard.as_mut(rc);
-                        let input_ref_option =
-                            unsafe { BorrowedRefMut::<OptionType>::from_unchecked_type(input_typed_bytes) };
-                        input_ref_option
-                    })
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                let mut input_mutable_borrow_refs = input_mutable_borrows
-                    .iter_mut()
-                    .map(|input_ref_option| input_ref_option.get_mut().unwrap())
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-
-                // Input values
-                let mut input_value_guards = task
-                    .inputs
-                    .iter()
-                    .map(|input| tasks_preceding[input.task_index].as_ref().unwrap().read().unwrap())
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                let mut input_value_guards = input_value_guards
-                    .iter()
-                    .zip(&*task.inputs)
-                    .map(|(task_preceding, input)| {
-                        task_preceding.output_values[input.output_value_channel_index].write().unwrap()
-                    })
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                let mut rcs = vec![(); input_value_guards.len()];
-                let mut input_values = input_value_guards
-                    .iter_mut()
-                    .zip(rcs.iter_mut())
-                    .map(|(input_value_guard, rc)| {
-                        let input_typed_bytes = input_value_guard.as_mut(rc);
-                        let input_ref_option =
-                            unsafe { BorrowedRefMut::<OptionType>::from_unchecked_type(input_typed_bytes) };
-                        input_ref_option
-                    })
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-
-                // Output values
-                let mut output_value_guards = current_task
-                    .output_values
-                    .iter_mut()
-                    .map(|output_value| output_value.write().unwrap())
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                let mut rcs = vec![(); output_value_guards.len()];
-                let mut output_values = output_value_guards
-                    .iter_mut()
-                    .zip(rcs.iter_mut())
-                    .map(|(output_value, rc)| {
-                        let output_typed_bytes = output_value.as_mut(rc);
-                        unsafe { BorrowedRefMut::<OptionType>::from_unchecked_type(output_typed_bytes) }
-                    })
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
-                // let ref_guards = HashMap::new();
-                let allocator_handle = unsafe { AllocatorHandle::with_node_index(task.node_index) };
+            // Not borrowed by the pass guards yet, as the tasks only refer to the preceding ones.
+            let current_task: &mut PreparedTask =
+                &mut self.tasks[task_index].as_ref().unwrap().write().unwrap();
+
+            // Source nodes are not executed while replaying a recording, their outputs are injected
+            // from the recording instead.
+            let replayed = match recording {
+                Some(RecordingMode::Replay(replayer)) if task.behaviour.is_source() => {
+                    match replayer.replay_task(task.node_index, &mut current_task.output_values) {
+                        Ok(replayed) => replayed,
+                        Err(error) => {
+                            error!(Category::Executor, "Stopping the replay: {}", error);
+                            *recording = None;
+                            false
+                        }
+                    }
+                }
+                _ => false,
+            };
 
-                {
-                    let execution_context = ExecutionContext {
-                        application_context: &context,
-                        allocator_handle,
-                        borrows: &*input_borrow_refs,
-                        mutable_borrows: &mut *input_mutable_borrow_refs,
-                        inputs: &mut *input_values,
-                        outputs: &mut *output_values,
-                    };
+            // Nodes that caused a GPU error are skipped until the schedule is regenerated.
+            let faulty = context.node_errors.is_gpu_faulty(task.node_index);
+            let executed = !replayed && !faulty;
 
-                    // Execute task
-                    let borrow = current_task.state.as_mut().unwrap();
-                    borrow.execute(execution_context);
-                    drop(borrow);
-                    // (task.executor)(execution_context);
-                }
-            }
+            pass_guards.acquire(task.borrows.iter().chain(&*task.mutable_borrows).chain(&*task.inputs));
 
             // Borrows
-            let borrow_value_guards = task
+            let input_borrows = task
                 .borrows
                 .iter()
-                .map(|input| tasks_preceding[input.task_index].as_ref().unwrap().read().unwrap())
-                .collect::<Vec<_>>()
-                .into_boxed_slice();
-            let borrow_value_guards = borrow_value_guards
-                .iter()
-                .zip(&*task.borrows)
-                .map(|(task_preceding, input)| {
-                    task_preceding.output_values[input.output_value_channel_index].read().unwrap()
-                })
-                .collect::<Vec<_>>()
-                .into_boxed_slice();
-            let input_borrows = borrow_value_guards
-                .iter()
-                .map(|borrow_value_guard| {
-                    let input_typed_bytes = borrow_value_guard.as_ref(&());
+                .map(|input| {
+                    // Safety: Borrowed values are not mutated until the dependent tasks have finished.
+                    let input_typed_bytes = unsafe {
+                        pass_guards
+                            .get(input)
+                            .output_values
+                            .value(input.output_value_channel_index, &())
+                            .unwrap()
+                    };
                     let input_ref_option =
                         unsafe { BorrowedRef::<OptionType>::from_unchecked_type(input_typed_bytes) };
                     input_ref_option
@@ -324,26 +333,20 @@ impl PreparedExecution {
                 .into_boxed_slice();
 
             // Mutable borrows
-            let mut mutable_borrow_value_guards = task
+            let mut rcs = vec![(); task.mutable_borrows.len()];
+            let mut input_mutable_borrows = task
                 .mutable_borrows
                 .iter()
-                .map(|input| tasks_preceding[input.task_index].as_ref().unwrap().read().unwrap())
-                .collect::<Vec<_>>()
-                .into_boxed_slice();
-            let mut mutable_borrow_value_guards = mutable_borrow_value_guards
-                .iter()
-                .zip(&*task.mutable_borrows)
-                .map(|(task_preceding, input)| {
-                    task_preceding.output_values[input.output_value_channel_index].write().unwrap()
-                })
-                .collect::<Vec<_>>()
-                .into_boxed_slice();
-            let mut rcs = vec![(); mutable_borrow_value_guards.len()];
-            let mut input_mutable_borrows = mutable_borrow_value_guards
-                .iter_mut()
                 .zip(rcs.iter_mut())
-                .map(|(mutable_borrow_value_guard, rc)| {
-                    let input_typed_bytes = mutable_borrow_value_guard.as_mut(rc);
+                .map(|(input, rc)| {
+                    // Safety: Mutably borrowed values are not consumed by any task executed afterwards.
+                    let input_typed_bytes = unsafe {
+                        pass_guards
+                            .get(input)
+                            .output_values
+                            .value_mut(input.output_value_channel_index, rc)
+                            .unwrap()
+                    };
                     let input_ref_option =
                         unsafe { BorrowedRefMut::<OptionType>::from_unchecked_type(input_typed_bytes) };
                     input_ref_option
@@ -356,27 +359,51 @@ impl PreparedExecution {
                 .collect::<Vec<_>>()
                 .into_boxed_slice();
 
+            // Convert the values of promoted inputs into the storage of the current task.
+            if executed {
+                for (input, slot) in task.inputs.iter().zip(&*current_task.promoted_slots) {
+                    if let (Some(promotion), Some(slot)) = (input.promotion, slot) {
+                        let mut rc = ();
+                        // Safety: The current task is borrowed exclusively, and the output value is
+                        // not consumed by any task executed afterwards.
+                        let (source, target) = unsafe {
+                            (
+                                pass_guards
+                                    .get(input)
+                                    .output_values
+                                    .value(input.output_value_channel_index, &()),
+                                current_task.promoted_values.value_mut(*slot, &mut rc),
+                            )
+                        };
+
+                        promotion.apply(
+                            source.unwrap().bytes().bytes().unwrap(),
+                            target.unwrap().bytes_mut().bytes_mut().unwrap(),
+                        );
+                    }
+                }
+            }
+
             // Input values
-            let mut input_value_guards = task
+            let mut rcs = vec![(); task.inputs.len()];
+            let mut input_values = task
                 .inputs
                 .iter()
-                .map(|input| tasks_preceding[input.task_index].as_ref().unwrap().read().unwrap())
-                .collect::<Vec<_>>()
-                .into_boxed_slice();
-            let mut input_value_guards = input_value_guards
-                .iter()
-                .zip(&*task.inputs)
-                .map(|(task_preceding, input)| {
-                    task_preceding.output_values[input.output_value_channel_index].write().unwrap()
-                })
-                .collect::<Vec<_>>()
-                .into_boxed_slice();
-            let mut rcs = vec![(); input_value_guards.len()];
-            let mut input_values = input_value_guards
-                .iter_mut()
+                .zip(&*current_task.promoted_slots)
                 .zip(rcs.iter_mut())
-                .map(|(input_value_guard, rc)| {
-                    let input_typed_bytes = input_value_guard.as_mut(rc);
+                .map(|((input, slot), rc)| {
+                    // Safety: Values passed by move are not consumed by any task executed afterwards.
+                    // Promoted values are owned by the current task, which is borrowed exclusively.
+                    let input_typed_bytes = unsafe {
+                        match slot {
+                            Some(slot) => current_task.promoted_values.value_mut(*slot, rc),
+                            None => pass_guards
+                                .get(input)
+                                .output_values
+                                .value_mut(input.output_value_channel_index, rc),
+                        }
+                        .unwrap()
+                    };
                     let input_ref_option =
                         unsafe { BorrowedRefMut::<OptionType>::from_unchecked_type(input_typed_bytes) };
                     input_ref_option
@@ -385,24 +412,97 @@ impl PreparedExecution {
                 .into_boxed_slice();
 
             // Output values
-            let mut output_value_guards = current_task
-                .output_values
-                .iter_mut()
-                .map(|output_value| output_value.write().unwrap())
-                .collect::<Vec<_>>()
-                .into_boxed_slice();
-            let mut rcs = vec![(); output_value_guards.len()];
-            let mut output_values = output_value_guards
+            let mut rcs = vec![(); current_task.output_values.len()];
+            let mut output_values = rcs
                 .iter_mut()
-                .zip(rcs.iter_mut())
-                .map(|(output_value, rc)| {
-                    let output_typed_bytes = output_value.as_mut(rc);
+                .enumerate()
+                .map(|(channel_index, rc)| {
+                    // Safety: The current task is borrowed exclusively.
+                    let output_typed_bytes =
+                        unsafe { current_task.output_values.value_mut(channel_index, rc).unwrap() };
                     unsafe { BorrowedRefMut::<OptionType>::from_unchecked_type(output_typed_bytes) }
                 })
                 .collect::<Vec<_>>()
                 .into_boxed_slice();
 
-            // Apply refcount deltas
+            if executed {
+                let allocator_handle = unsafe { AllocatorHandle::with_node_index(task.node_index) };
+                let execution_context = ExecutionContext {
+                    application_context: &context,
+                    allocator_handle,
+                    borrows: &*input_borrow_refs,
+                    mutable_borrows: &mut *input_mutable_borrow_refs,
+                    inputs: &mut *input_values,
+                    outputs: &mut *output_values,
+                };
+
+                // Execute task
+                let borrow = current_task.state.as_mut().unwrap();
+                let executing_node = context.renderer().map(|renderer| &renderer.executing_node);
+                if let Some(executing_node) = executing_node {
+                    executing_node.set(Some(task.node_index));
+                }
+                context.watchdog.begin_task(task.node_index);
+                let execution_start = Instant::now();
+                borrow.execute(execution_context);
+                schedule.execution_times.record(task_index, execution_start.elapsed());
+                context.watchdog.end_task();
+                if let Some(executing_node) = executing_node {
+                    executing_node.set(None);
+                }
+
+                // Before the refcounts are applied and the values are consumed by other tasks.
+                if let Some(substitute) = schedule.sanitize_numerics {
+                    let sanitized = current_task
+                        .sanitized_outputs
+                        .iter()
+                        .filter_map(|(channel_index, layout)| {
+                            let mut value = output_values[*channel_index].get_mut()?;
+
+                            Some(layout.sanitize(value.as_bytes_mut().ok()?, substitute))
+                        })
+                        .sum::<u64>();
+
+                    if sanitized > 0 {
+                        schedule.execution_times.record_sanitized(task_index, sanitized);
+                    }
+                }
+
+                let inputs = task.borrows.iter().chain(&*task.mutable_borrows).chain(&*task.inputs);
+
+                for (slot, _) in inputs.enumerate().filter(|(_, input)| !input.pruned) {
+                    schedule.throughput.record(task_index, slot);
+                }
+            }
+
+            // Take the requested snapshots of the outputs
+            for channel in context.value_taps.take_requests(task.node_index) {
+                let value = ValueSnapshot::read(task, &output_values, channel);
+
+                context.value_taps.fulfill(ValueSnapshot { channel, value });
+            }
+
+            // Remember the allocations output through pointers, for the UI to display their refcounts
+            for (channel_index, (output_channel, output_value)) in
+                task.configuration.output_channels_by_value.iter().zip(&*output_values).enumerate()
+            {
+                if !is_pointer(&output_channel.ty) {
+                    continue;
+                }
+
+                let ptr =
+                    output_value.get().and_then(|value| typed_bytes_to_ptr(unsafe { value.typed_bytes() }));
+                let channel = ChannelIdentifier {
+                    node_index: task.node_index,
+                    channel_direction: ChannelDirection::Out,
+                    pass_by: ChannelPassBy::Value,
+                    channel_index,
+                };
+
+                context.value_taps.record_pointer(channel, ptr);
+            }
+
+            // Record refcount deltas
             let rc = NodeStateRefcounter(task.node_index);
             output_values.iter().for_each(|output| unsafe { output.refcount_increment_recursive_for(&rc) });
             input_borrow_refs.iter().for_each(|input| unsafe { input.refcount_decrement_recursive_for(&rc) });
@@ -411,8 +511,98 @@ impl PreparedExecution {
                 .for_each(|input| unsafe { input.refcount_decrement_recursive_for(&rc) });
             input_values.iter().for_each(|input| unsafe { input.refcount_decrement_recursive_for(&rc) });
 
-            // Free allocations that are no longer needed.
-            unsafe { Allocator::get().apply_owned_and_output_refcounts(task.node_index).unwrap() }
+            if let Some(RecordingMode::Record(recorder)) = recording {
+                if task.behaviour.is_source() {
+                    drop(output_values);
+
+                    if let Err(error) = recorder.record_task(task.node_index, &mut current_task.output_values)
+                    {
+                        error!(Category::Executor, "Stopping the recording: {}", error);
+                        *recording = None;
+                    }
+                }
+            }
+
+            drop(input_borrow_refs);
+            drop(input_borrows);
+            drop(input_mutable_borrow_refs);
+            drop(input_mutable_borrows);
+            drop(input_values);
+            // The values referred to by the guards may be freed by applying the refcount deltas.
+            pass_guards.release_consumed(task_index, task);
+
+            // Free allocations that are no longer needed
+            if let Err(error) =
+                unsafe { Allocator::get().apply_owned_and_output_refcounts(task.node_index, self.generation) }
+            {
+                error!(
+                    Category::Allocator,
+                    "Could not apply the refcounts of node #{}: {}",
+                    task.node_index.index(),
+                    error
+                );
+            }
+        }
+
+        schedule.execution_times.record_pass();
+    }
+}
+
+/// The read guards of the tasks whose outputs are passed to other tasks during a pass of the
+/// executor, each acquired at most once per pass, however many tasks consume its outputs.
+///
+/// A task is only locked for writing while it is being executed, which precedes all tasks
+/// consuming its outputs, so the guards may be kept until its last consumer has been executed.
+/// Mutable borrows and moves of the outputs remain exclusive, as the schedule does not let any
+/// other task access those outputs in the meantime.
+struct PassGuards<'a> {
+    tasks: &'a [Option<RwLock<PreparedTask>>],
+    /// Indexed by the task index.
+    guards: Vec<Option<RwLockReadGuard<'a, PreparedTask>>>,
+    /// The index of the last task consuming the outputs of the task, indexed by the task index.
+    last_consumers: Vec<Option<usize>>,
+}
+
+impl<'a> PassGuards<'a> {
+    fn new(tasks: &'a [Option<RwLock<PreparedTask>>], schedule: &Schedule) -> Self {
+        let mut last_consumers = vec![None; tasks.len()];
+
+        for (task_index, task) in schedule.tasks.iter().enumerate() {
+            if let Some(task) = task {
+                for input in task.borrows.iter().chain(&*task.mutable_borrows).chain(&*task.inputs) {
+                    if !input.pruned {
+                        last_consumers[input.task_index] = Some(task_index);
+                    }
+                }
+            }
+        }
+
+        Self { tasks, guards: tasks.iter().map(|_| None).collect(), last_consumers }
+    }
+
+    /// Acquires the guards of the tasks referred to by the inputs, unless already acquired during
+    /// the pass.
+    fn acquire<'i>(&mut self, inputs: impl IntoIterator<Item = &'i TaskInput>) {
+        let tasks = self.tasks;
+
+        for input in inputs.into_iter().filter(|input| !input.pruned) {
+            self.guards[input.task_index]
+                .get_or_insert_with(|| tasks[input.task_index].as_ref().unwrap().read().unwrap());
+        }
+    }
+
+    /// The task referred to by the input, which must have been acquired.
+    fn get(&self, input: &TaskInput) -> &PreparedTask {
+        self.guards[input.task_index].as_deref().expect("The task of the input has not been acquired.")
+    }
+
+    /// Releases the guards of the tasks whose outputs are not consumed by any task following the
+    /// executed one, so that the refcount deltas of the executed task may be applied.
+    fn release_consumed(&mut self, task_index: usize, task: &Task) {
+        for input in task.borrows.iter().chain(&*task.mutable_borrows).chain(&*task.inputs) {
+            if self.last_consumers[input.task_index] == Some(task_index) {
+                self.guards[input.task_index] = None;
+            }
         }
     }
 }
@@ -424,6 +614,48 @@ pub struct TaskInput {
     pub task_index: usize,
     /// The channel index of type `ChannelPassBy::Value`.
     pub output_value_channel_index: usize,
+    /// The conversion of the value for an input of a different primitive type, see
+    /// [`ExecutionGraph::implicit_promotion`].
+    pub promotion: Option<Promotion>,
+    /// Whether the input is ignored by the node, see
+    /// [`NodeBehaviour::ignored_inputs`](crate::node::behaviour::NodeBehaviour::ignored_inputs). The source
+    /// task is not accessed and may not even be executed, so the task is passed an empty value.
+    pub pruned: bool,
+}
+
+/// An implicit conversion of the values passed through a connection between primitive channels of
+/// different types, see [`PrimitiveTypeEnum::promotes_to`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Promotion {
+    pub from: PrimitiveTypeEnum,
+    pub to: PrimitiveTypeEnum,
+}
+
+impl Promotion {
+    /// The promotion from the output type to the input type, if both are primitive types and the
+    /// output type promotes to the input type.
+    pub fn find(from: &TypeEnum, to: &TypeEnum) -> Option<Self> {
+        let from = from.as_primitive_type_enum()?;
+        let to = to.as_primitive_type_enum()?;
+
+        Some(Self { from, to }).filter(|_| from.promotes_to(to))
+    }
+
+    /// Converts the `OptionType`-wrapped value of the output into the `OptionType`-wrapped value of
+    /// the input.
+    fn apply(&self, source: &[u8], target: &mut [u8]) {
+        let source_ty = OptionType::from_enum_if_sized(self.from).unwrap();
+        let target_ty = OptionType::from_enum_if_sized(self.to).unwrap();
+        let some = source_ty.is_some_in(source);
+
+        if some {
+            let value = self.from.read::<LittleEndian, _>(source).unwrap();
+
+            value.promote(self.to).unwrap().write::<LittleEndian>(&mut &mut target[..]).unwrap();
+        }
+
+        target_ty.set_some_in(target, some);
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -436,6 +668,23 @@ pub struct Task {
     pub behaviour: Box<dyn NodeBehaviourContainer>,
 }
 
+impl Task {
+    /// The number of values the task consumes per execution, one per input slot.
+    pub fn input_count(&self) -> usize {
+        self.borrows.len() + self.mutable_borrows.len() + self.inputs.len()
+    }
+
+    /// Numbers the input channels of all kinds consecutively: the borrows first, then the mutable
+    /// borrows and the inputs passed by value last.
+    pub fn input_slot(&self, channel: ChannelIdentifier) -> usize {
+        match channel.pass_by {
+            ChannelPassBy::SharedReference => channel.channel_index,
+            ChannelPassBy::MutableReference => self.borrows.len() + channel.channel_index,
+            ChannelPassBy::Value => self.borrows.len() + self.mutable_borrows.len() + channel.channel_index,
+        }
+    }
+}
+
 // impl Debug for Task {
 //     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 //         f.debug_struct("Task")
@@ -449,574 +698,3103 @@ pub struct Task {
 
 #[derive(Clone, Debug)]
 pub struct Schedule {
-    /// Used to check whether the schedule has been updated
+    /// Used to check whether the schedule has been updated. Unique among the schedules of all
+    /// graphs.
     pub generation: usize,
+    /// The graph the schedule has been constructed from, whose states the executor carries over
+    /// from its previous schedule.
+    pub graph_id: GraphId,
     // FIXME: implement proper multithreaded scheduling
     // `None` if the task is disabled.
     pub tasks: Box<[Option<Task>]>,
+    /// The node of each task, including the disabled ones.
+    pub node_indices: Box<[NodeIndex]>,
+    pub throughput: Arc<ThroughputCounters>,
+    pub execution_times: Arc<ExecutionTimes>,
+    /// See [`ExecutionGraph::sanitize_numerics`].
+    pub sanitize_numerics: Option<f64>,
 }
 
-pub struct GraphValidationErrorDisplay<'a> {
-    pub title: Cow<'a, str>,
-    pub description: Cow<'a, str>,
-    pub suggestion: Option<Cow<'a, str>>,
-}
-
-impl<'a> Display for GraphValidationErrorDisplay<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!(
-            "[{title}] Description: {description}",
-            title = &self.title,
-            description = &self.description
-        ))?;
+impl Schedule {
+    /// The position of each scheduled node among the enabled tasks, in the order of execution,
+    /// `None` if its task is disabled. Nodes missing from the map are not scheduled at all.
+    pub fn execution_order(&self) -> HashMap<NodeIndex, Option<usize>> {
+        let mut position = 0;
 
-        if let Some(suggestion) = self.suggestion.as_ref() {
-            f.write_fmt(format_args!("; Suggestion: {suggestion}", suggestion = suggestion))?;
-        }
+        self.node_indices
+            .iter()
+            .zip(self.tasks.iter())
+            .map(|(node_index, task)| {
+                let order = task.as_ref().map(|_| position);
 
-        Ok(())
+                position += order.is_some() as usize;
+                (*node_index, order)
+            })
+            .collect()
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum GraphValidationError {
-    IncompleteInput(UndirectedChannelIdentifier),
-    StronglyConnectedComponent { nodes: Vec<NodeIndex>, connections: Vec<Connection> },
-    InvalidConnection { connection: Connection, error: ConnectionValidityError },
+/// Counts the values consumed by the tasks of a schedule through their inputs, for the UI to
+/// display the throughput of connections. Created along with the schedule, so the counts start
+/// from zero whenever the schedule is regenerated.
+#[derive(Debug)]
+pub struct ThroughputCounters {
+    /// Indexed by the task index and the input slot, see [`Task::input_slot`].
+    counts: Box<[Box<[AtomicU64]>]>,
+    /// The task index and the input slot of the scheduled connections.
+    slots: HashMap<Connection, (usize, usize)>,
 }
 
-impl GraphValidationError {
-    pub fn collect(&self, collect: &mut dyn FnMut(GraphValidationErrorAffectedElement)) {
-        use GraphValidationError::*;
-        match self {
-            IncompleteInput(undirected_channel_id) => {
-                let channel = undirected_channel_id.into_directed(ChannelDirection::In);
-                (collect)(channel.into());
-                (collect)(channel.node_index.into());
-            }
-            StronglyConnectedComponent { nodes, connections } => {
-                for node in nodes {
-                    (collect)((*node).into());
-                }
+impl ThroughputCounters {
+    fn new(tasks: &[Option<Task>], connections: Vec<Connection>) -> Self {
+        let task_indices = tasks
+            .iter()
+            .enumerate()
+            .filter_map(|(task_index, task)| task.as_ref().map(|task| (task.node_index, task_index)))
+            .collect::<HashMap<_, _>>();
+        let slots = connections
+            .into_iter()
+            .filter_map(|connection| {
+                let task_index = *task_indices.get(&connection.to().node_index)?;
+                let slot = tasks[task_index].as_ref().unwrap().input_slot(connection.to());
+
+                Some((connection, (task_index, slot)))
+            })
+            .collect();
+        let counts = tasks
+            .iter()
+            .map(|task| {
+                let input_count = task.as_ref().map(Task::input_count).unwrap_or(0);
 
-                for connection in connections {
-                    (collect)(connection.clone().into());
-                    (collect)(connection.from().into());
-                    (collect)(connection.to().into());
-                }
-            }
-            InvalidConnection { connection, error } => {
-                ConnectionValidityError::collect(error, connection, collect);
-            }
-        }
-    }
+                (0..input_count).map(|_| AtomicU64::new(0)).collect()
+            })
+            .collect();
 
-    pub fn display(&self) -> GraphValidationErrorDisplay<'_> {
-        use GraphValidationError::*;
-        match self {
-            IncompleteInput(undirected_channel_id) => GraphValidationErrorDisplay {
-                title: Cow::Borrowed("Incomplete input"),
-                // TODO: Specify which exact channel is missing the connection.
-                description: Cow::Borrowed("Missing a connection for input channel."),
-                suggestion: Some(Cow::Borrowed(
-                    "Add a connection or disconnect all inputs to disable the node.",
-                )),
-            },
-            StronglyConnectedComponent { nodes, connections } => GraphValidationErrorDisplay {
-                title: Cow::Borrowed("Graph not acyclic"),
-                description: Cow::Borrowed("The graph contains one or more loops."),
-                suggestion: Some(Cow::Borrowed("Remove highlighted loops.")),
-            },
-            InvalidConnection { connection, error } => error.display(connection),
-        }
+        Self { counts, slots }
     }
-}
 
-impl Display for GraphValidationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.display().fmt(f)
+    /// Records a value consumed through the input slot of the task. Relaxed, as the counts are
+    /// only sampled for display.
+    fn record(&self, task_index: usize, slot: usize) {
+        self.counts[task_index][slot].fetch_add(1, Ordering::Relaxed);
     }
-}
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
-pub enum GraphValidationErrorAffectedElement {
-    Node(NodeIndex),
-    Channel(ChannelIdentifier),
-    Connection(Connection),
-}
+    /// The number of values consumed through the connection since the schedule was created,
+    /// `None` if the task of its input is disabled.
+    pub fn count(&self, connection: &Connection) -> Option<u64> {
+        let (task_index, slot) = self.slots.get(connection)?;
 
-impl From<NodeIndex> for GraphValidationErrorAffectedElement {
-    fn from(from: NodeIndex) -> Self {
-        GraphValidationErrorAffectedElement::Node(from)
+        Some(self.counts[*task_index][*slot].load(Ordering::Relaxed))
     }
 }
 
-impl From<ChannelIdentifier> for GraphValidationErrorAffectedElement {
-    fn from(from: ChannelIdentifier) -> Self {
-        GraphValidationErrorAffectedElement::Channel(from)
-    }
+/// Aggregates the counts of the active schedule to the number of values consumed per second
+/// through each connection, sampled periodically by the application.
+#[derive(Debug, Default)]
+pub struct ThroughputRates {
+    /// The counters of the previous sample, along with the time it was taken and its counts.
+    previous: Option<(Arc<ThroughputCounters>, Instant, HashMap<Connection, u64>)>,
+    /// Connections missing from the map are not consumed, as the task of their input is disabled.
+    rates: HashMap<Connection, f64>,
 }
 
-impl From<Connection> for GraphValidationErrorAffectedElement {
+impl ThroughputRates {
+    pub fn sample(&mut self, schedule: Option<&Schedule>) {
+        let counters = if let Some(schedule) = schedule {
+            schedule.throughput.clone()
+        } else {
+            *self = Default::default();
+            return;
+        };
+        let now = Instant::now();
+        let counts = counters
+            .slots
+            .keys()
+            .map(|connection| (connection.clone(), counters.count(connection).unwrap()))
+            .collect::<HashMap<_, _>>();
+
+        self.rates = match self.previous.take() {
+            Some((previous_counters, time, previous_counts))
+                if Arc::ptr_eq(&previous_counters, &counters) =>
+            {
+                let elapsed = now.duration_since(time).as_secs_f64();
+
+                counts
+                    .iter()
+                    .map(|(connection, count)| {
+                        let previous_count = previous_counts.get(connection).copied().unwrap_or(0);
+
+                        (connection.clone(), (count - previous_count) as f64 / elapsed)
+                    })
+                    .collect()
+            }
+            // The counts of a previous schedule are not comparable, wait for the next sample.
+            _ => counts.keys().map(|connection| (connection.clone(), 0.0)).collect(),
+        };
+        self.previous = Some((counters, now, counts));
+    }
+
+    pub fn rates(&self) -> &HashMap<Connection, f64> {
+        &self.rates
+    }
+}
+
+/// The execution times of a task within a window of [`ExecutionTimes`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TaskExecutionTimes {
+    pub executions: u64,
+    pub total: Duration,
+    /// Zero if the task has not been executed.
+    pub min: Duration,
+    pub max: Duration,
+    /// The number of non-finite values replaced in the outputs, see
+    /// [`ExecutionGraph::sanitize_numerics`].
+    pub sanitized: u64,
+}
+
+impl TaskExecutionTimes {
+    fn record(&mut self, duration: Duration) {
+        if self.executions == 0 || duration < self.min {
+            self.min = duration;
+        }
+
+        self.max = self.max.max(duration);
+        self.total += duration;
+        self.executions += 1;
+    }
+
+    /// The average execution time, `None` if the task has not been executed.
+    pub fn mean(&self) -> Option<Duration> {
+        if self.executions == 0 {
+            None
+        } else {
+            Some(Duration::from_secs_f64(self.total.as_secs_f64() / self.executions as f64))
+        }
+    }
+}
+
+/// The executions measured since the previous window was taken.
+#[derive(Debug, Clone)]
+pub struct ExecutionTimesWindow {
+    pub start: Instant,
+    /// The number of passes finished within the window.
+    pub passes: u64,
+    /// Indexed by the task index.
+    pub tasks: Box<[TaskExecutionTimes]>,
+}
+
+impl ExecutionTimesWindow {
+    fn new(task_count: usize) -> Self {
+        Self {
+            start: Instant::now(),
+            passes: 0,
+            tasks: vec![Default::default(); task_count].into_boxed_slice(),
+        }
+    }
+}
+
+/// Measures the execution times of the tasks of a schedule, for the application to sample the
+/// execution statistics. Created along with the schedule, like [`ThroughputCounters`].
+#[derive(Debug)]
+pub struct ExecutionTimes {
+    /// Locked once per executed task. The lock is only contended while the window is taken.
+    window: Mutex<ExecutionTimesWindow>,
+}
+
+impl ExecutionTimes {
+    fn new(task_count: usize) -> Self {
+        Self { window: Mutex::new(ExecutionTimesWindow::new(task_count)) }
+    }
+
+    fn record(&self, task_index: usize, duration: Duration) {
+        self.window.lock().unwrap().tasks[task_index].record(duration);
+    }
+
+    fn record_sanitized(&self, task_index: usize, count: u64) {
+        self.window.lock().unwrap().tasks[task_index].sanitized += count;
+    }
+
+    fn record_pass(&self) {
+        self.window.lock().unwrap().passes += 1;
+    }
+
+    /// The number of passes finished within the current window.
+    pub fn passes(&self) -> u64 {
+        self.window.lock().unwrap().passes
+    }
+
+    /// Takes the executions measured since the previous window was taken, or since the schedule
+    /// was created, as a whole, so that the statistics of the window are consistent.
+    pub fn take_window(&self) -> ExecutionTimesWindow {
+        let mut window = self.window.lock().unwrap();
+        let task_count = window.tasks.len();
+
+        std::mem::replace(&mut *window, ExecutionTimesWindow::new(task_count))
+    }
+}
+
+/// The generation of the schedule being executed, published by the executor so that the
+/// application can tell whether the executor runs the schedule of the graph being edited.
+#[derive(Debug)]
+pub struct ExecutingGeneration {
+    /// [`ExecutingGeneration::NONE`] while no schedule is executed.
+    generation: AtomicUsize,
+    /// Notified whenever the generation changes.
+    sender: UnboundedSender<()>,
+    /// Taken by the subscription of the application.
+    receiver: Mutex<Option<UnboundedReceiver<()>>>,
+}
+
+impl Default for ExecutingGeneration {
+    fn default() -> Self {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+
+        Self { generation: AtomicUsize::new(Self::NONE), sender, receiver: Mutex::new(Some(receiver)) }
+    }
+}
+
+impl ExecutingGeneration {
+    const NONE: usize = usize::MAX;
+
+    /// The generation of the schedule being executed, `None` if no schedule is executed.
+    pub fn get(&self) -> Option<usize> {
+        match self.generation.load(Ordering::Relaxed) {
+            Self::NONE => None,
+            generation => Some(generation),
+        }
+    }
+
+    fn set(&self, generation: Option<usize>) {
+        let previous = self.generation.swap(generation.unwrap_or(Self::NONE), Ordering::Relaxed);
+
+        if previous != generation.unwrap_or(Self::NONE) {
+            // The application may have been closed already.
+            let _ = self.sender.unbounded_send(());
+        }
+    }
+
+    /// Returns the receiver of the notifications about changes of the generation, which can only
+    /// be taken once.
+    pub fn take_receiver(&self) -> Option<UnboundedReceiver<()>> {
+        self.receiver.lock().unwrap().take()
+    }
+}
+
+pub struct GraphValidationErrorDisplay<'a> {
+    pub title: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    pub suggestion: Option<Cow<'a, str>>,
+}
+
+impl<'a> Display for GraphValidationErrorDisplay<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "[{title}] Description: {description}",
+            title = &self.title,
+            description = &self.description
+        ))?;
+
+        if let Some(suggestion) = self.suggestion.as_ref() {
+            f.write_fmt(format_args!("; Suggestion: {suggestion}", suggestion = suggestion))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An inconsistency of the graph encountered during the construction of the schedule, which was
+/// not detected by [`ExecutionGraph::check_graph_validity`].
+#[derive(Debug, Clone)]
+pub enum ScheduleError {
+    /// The input channel of a used node has not been assigned a value.
+    MissingInput { channel: ChannelIdentifier },
+    /// The source node of the connection is not scheduled before the target node.
+    UnscheduledSource { connection: Connection },
+    /// The connection refers to a channel which does not exist in the current configuration of
+    /// its node.
+    MissingChannel { connection: Connection, channel: ChannelIdentifier },
+    /// The value moved through the connection is also borrowed by a node, which cannot be
+    /// executed before the value is moved.
+    BorrowedAfterMove { connection: Connection, borrower: NodeIndex },
+}
+
+impl ScheduleError {
+    pub fn collect(&self, collect: &mut dyn FnMut(GraphValidationErrorAffectedElement)) {
+        use ScheduleError::*;
+        match self {
+            MissingInput { channel } => {
+                (collect)((*channel).into());
+                (collect)(channel.node_index.into());
+            }
+            UnscheduledSource { connection } => {
+                (collect)(connection.clone().into());
+                (collect)(connection.from().node_index.into());
+                (collect)(connection.to().node_index.into());
+            }
+            MissingChannel { connection, channel } => {
+                (collect)(connection.clone().into());
+                (collect)(channel.node_index.into());
+            }
+            BorrowedAfterMove { connection, borrower } => {
+                (collect)(connection.clone().into());
+                (collect)(connection.to().node_index.into());
+                (collect)((*borrower).into());
+            }
+        }
+    }
+
+    pub fn description(&self) -> String {
+        use ScheduleError::*;
+        match self {
+            MissingInput { channel } => format!(
+                "Input channel {:?} #{} of node {} has no value.",
+                channel.pass_by,
+                channel.channel_index,
+                channel.node_index.index(),
+            ),
+            UnscheduledSource { connection } => format!(
+                "Node {} is connected to node {}, but is not scheduled before it.",
+                connection.from().node_index.index(),
+                connection.to().node_index.index(),
+            ),
+            MissingChannel { connection, channel } => format!(
+                "A connection from node {} to node {} refers to {} channel {:?} #{} of node {}, which \
+                 does not exist.",
+                connection.from().node_index.index(),
+                connection.to().node_index.index(),
+                match channel.channel_direction {
+                    ChannelDirection::In => "input",
+                    ChannelDirection::Out => "output",
+                },
+                channel.pass_by,
+                channel.channel_index,
+                channel.node_index.index(),
+            ),
+            BorrowedAfterMove { connection, borrower } => format!(
+                "The value of output #{} of node {} is moved into node {}, but also borrowed by node {}, \
+                 which cannot be executed beforehand.",
+                connection.from().channel_index,
+                connection.from().node_index.index(),
+                connection.to().node_index.index(),
+                borrower.index(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GraphValidationError {
+    IncompleteInput(UndirectedChannelIdentifier),
+    StronglyConnectedComponent { nodes: Vec<NodeIndex>, connections: Vec<Connection> },
+    InvalidConnection { connection: Connection, error: ConnectionValidityError },
+    AmbiguousMove { channel: ChannelIdentifier, connections: Vec<Connection> },
+    NodeExecution { node: NodeIndex, message: String },
+    NodeValidation { node: NodeIndex, issue: NodeValidationIssue },
+    NodeStalled { node: NodeIndex, title: String, elapsed: Duration },
+    LibraryDivergence { nodes: Vec<NodeIndex>, name: String, divergence: Divergence },
+    Schedule(ScheduleError),
+}
+
+impl GraphValidationError {
+    pub fn collect(&self, collect: &mut dyn FnMut(GraphValidationErrorAffectedElement)) {
+        use GraphValidationError::*;
+        match self {
+            IncompleteInput(undirected_channel_id) => {
+                let channel = undirected_channel_id.into_directed(ChannelDirection::In);
+                (collect)(channel.into());
+                (collect)(channel.node_index.into());
+            }
+            StronglyConnectedComponent { nodes, connections } => {
+                for node in nodes {
+                    (collect)((*node).into());
+                }
+
+                for connection in connections {
+                    (collect)(connection.clone().into());
+                    (collect)(connection.from().into());
+                    (collect)(connection.to().into());
+                }
+            }
+            InvalidConnection { connection, error } => {
+                ConnectionValidityError::collect(error, connection, collect);
+            }
+            AmbiguousMove { channel, connections } => {
+                (collect)((*channel).into());
+                (collect)(channel.node_index.into());
+
+                for connection in connections {
+                    (collect)(connection.clone().into());
+                }
+            }
+            NodeExecution { node, .. } | NodeStalled { node, .. } => {
+                (collect)((*node).into());
+            }
+            LibraryDivergence { nodes, .. } => {
+                for node in nodes {
+                    (collect)((*node).into());
+                }
+            }
+            NodeValidation { node, issue } => {
+                (collect)((*node).into());
+
+                if let Some(channel) = issue.channel {
+                    (collect)(
+                        channel.into_undirected_identifier(*node).into_directed(ChannelDirection::In).into(),
+                    );
+                }
+            }
+            Schedule(error) => error.collect(collect),
+        }
+    }
+
+    /// Only warnings are displayed without preventing the graph from being scheduled.
+    pub fn severity(&self) -> Severity {
+        match self {
+            GraphValidationError::NodeValidation { issue, .. } => issue.severity,
+            GraphValidationError::NodeStalled { .. } | GraphValidationError::LibraryDivergence { .. } => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+
+    pub fn display(&self) -> GraphValidationErrorDisplay<'_> {
+        use GraphValidationError::*;
+        match self {
+            IncompleteInput(undirected_channel_id) => GraphValidationErrorDisplay {
+                title: Cow::Borrowed("Incomplete input"),
+                // TODO: Specify which exact channel is missing the connection.
+                description: Cow::Borrowed("Missing a connection for input channel."),
+                suggestion: Some(Cow::Borrowed(
+                    "Add a connection or disconnect all inputs to disable the node.",
+                )),
+            },
+            StronglyConnectedComponent { nodes, connections } => GraphValidationErrorDisplay {
+                title: Cow::Borrowed("Graph not acyclic"),
+                description: Cow::Borrowed("The graph contains one or more loops."),
+                suggestion: Some(Cow::Borrowed("Remove highlighted loops.")),
+            },
+            InvalidConnection { connection, error } => error.display(connection),
+            AmbiguousMove { connections, .. } => GraphValidationErrorDisplay {
+                title: Cow::Borrowed("Ambiguous move"),
+                description: Cow::Owned(format!(
+                    "The value of the output is required by {} connections, but can only be moved \
+                     into one of them.",
+                    connections.len()
+                )),
+                suggestion: Some(Cow::Borrowed(
+                    "Mark the connection to move the value into as primary in its context menu and \
+                     borrow the value through the others.",
+                )),
+            },
+            NodeExecution { message, .. } => GraphValidationErrorDisplay {
+                title: Cow::Borrowed("Node execution failed"),
+                description: Cow::Borrowed(message),
+                suggestion: Some(Cow::Borrowed("Adjust the node settings or its inputs.")),
+            },
+            NodeValidation { issue, .. } => GraphValidationErrorDisplay {
+                title: Cow::Borrowed(match issue.severity {
+                    Severity::Warning => "Node warning",
+                    Severity::Error => "Invalid node",
+                }),
+                description: Cow::Borrowed(&issue.message),
+                suggestion: Some(Cow::Borrowed("Adjust the node settings or its inputs.")),
+            },
+            NodeStalled { title, elapsed, .. } => GraphValidationErrorDisplay {
+                title: Cow::Borrowed("Node not responding"),
+                description: Cow::Owned(format!(
+                    "Node '{}' has been executing for {:.1} s.",
+                    title,
+                    elapsed.as_secs_f32()
+                )),
+                suggestion: Some(Cow::Borrowed(
+                    "The node has been asked to cancel its execution. If it does not respond, remove \
+                     it from the graph.",
+                )),
+            },
+            LibraryDivergence { name, divergence, .. } => GraphValidationErrorDisplay {
+                title: Cow::Owned(format!("Diverged from library entry `{}`", name)),
+                description: Cow::Owned(divergence.to_string()),
+                suggestion: Some(Cow::Borrowed(match divergence {
+                    Divergence::Missing => {
+                        "Push the nodes into the library to recreate the entry, from the context menu of \
+                         any of them."
+                    }
+                    _ => {
+                        "Update the nodes from the library, or push the changes into the library, from \
+                         the context menu of any of them."
+                    }
+                })),
+            },
+            Schedule(error) => GraphValidationErrorDisplay {
+                title: Cow::Borrowed("Inconsistent graph"),
+                description: Cow::Owned(error.description()),
+                suggestion: Some(Cow::Borrowed(
+                    "Reconnect the highlighted channels. The previous schedule keeps running until \
+                     the graph is fixed.",
+                )),
+            },
+        }
+    }
+}
+
+impl Display for GraphValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.display().fmt(f)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum GraphValidationErrorAffectedElement {
+    Node(NodeIndex),
+    Channel(ChannelIdentifier),
+    Connection(Connection),
+}
+
+impl From<NodeIndex> for GraphValidationErrorAffectedElement {
+    fn from(from: NodeIndex) -> Self {
+        GraphValidationErrorAffectedElement::Node(from)
+    }
+}
+
+impl From<ChannelIdentifier> for GraphValidationErrorAffectedElement {
+    fn from(from: ChannelIdentifier) -> Self {
+        GraphValidationErrorAffectedElement::Channel(from)
+    }
+}
+
+impl From<Connection> for GraphValidationErrorAffectedElement {
     fn from(from: Connection) -> Self {
         GraphValidationErrorAffectedElement::Connection(from)
     }
 }
 
-/// A mapping of UI elements to graph validation errors for O(1)-time lookup during rendering.
-#[derive(Default, Clone)]
-pub struct GraphValidationErrors(
-    pub HashMap<GraphValidationErrorAffectedElement, Vec<Rc<GraphValidationError>>>,
-);
+/// A mapping of UI elements to graph validation errors for O(1)-time lookup during rendering.
+#[derive(Default, Clone)]
+pub struct GraphValidationErrors(
+    pub HashMap<GraphValidationErrorAffectedElement, Vec<Rc<GraphValidationError>>>,
+);
+
+impl GraphValidationErrors {
+    pub fn get_related_errors(
+        &self,
+        element: impl Into<GraphValidationErrorAffectedElement>,
+    ) -> &[Rc<GraphValidationError>] {
+        self.get(&element.into()).map(|vec| &vec[..]).unwrap_or(&[])
+    }
+
+    /// Whether any errors, rather than just warnings, are related to the element.
+    pub fn is_invalid(&self, element: impl Into<GraphValidationErrorAffectedElement>) -> bool {
+        self.get_related_errors(element).iter().any(|error| error.severity() == Severity::Error)
+    }
+
+    pub fn has_warnings(&self, element: impl Into<GraphValidationErrorAffectedElement>) -> bool {
+        self.get_related_errors(element).iter().any(|error| error.severity() == Severity::Warning)
+    }
+
+    /// Adds the errors reported by nodes during their execution.
+    pub fn with_node_errors(mut self, node_errors: &NodeErrors) -> Self {
+        for (node, error) in node_errors.get_all() {
+            let error = Rc::new(GraphValidationError::NodeExecution { node, message: error.message });
+
+            self.entry(node.into()).or_insert_with(Vec::new).push(error);
+        }
+
+        self
+    }
+
+    /// Adds a warning about the node blocking the execution of the graph.
+    pub fn with_stalled_task(mut self, task: StalledTask, title: impl ToString) -> Self {
+        let StalledTask { node, elapsed } = task;
+        let error = Rc::new(GraphValidationError::NodeStalled { node, title: title.to_string(), elapsed });
+
+        self.entry(node.into()).or_insert_with(Vec::new).push(error);
+        self
+    }
+
+    /// Adds a warning about the nodes of an instance of the library entry differing from the entry.
+    pub fn with_library_divergence(
+        mut self,
+        nodes: Vec<NodeIndex>,
+        name: &str,
+        divergence: Divergence,
+    ) -> Self {
+        let error = Rc::new(GraphValidationError::LibraryDivergence {
+            nodes: nodes.clone(),
+            name: name.to_string(),
+            divergence,
+        });
+
+        for node in nodes {
+            self.entry(node.into()).or_insert_with(Vec::new).push(error.clone());
+        }
+
+        self
+    }
+}
+
+impl From<Vec<GraphValidationError>> for GraphValidationErrors {
+    fn from(vec: Vec<GraphValidationError>) -> Self {
+        let mut result = Self::default();
+
+        for error in vec {
+            let error = Rc::new(error);
+            error.collect(&mut |element| match result.entry(element) {
+                Entry::Vacant(entry) => {
+                    entry.insert(vec![error.clone()]);
+                }
+                Entry::Occupied(mut entry) => {
+                    entry.get_mut().push(error.clone());
+                }
+            })
+        }
+
+        result
+    }
+}
+
+impl Deref for GraphValidationErrors {
+    type Target = HashMap<GraphValidationErrorAffectedElement, Vec<Rc<GraphValidationError>>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for GraphValidationErrors {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+pub struct ExecutionGraph {
+    pub graph: Graph,
+    /// Kept when the nodes and connections are replaced.
+    id: GraphId,
+    /// Whether connections from primitive outputs to inputs of wider primitive types are allowed,
+    /// converting the values implicitly. Mirrors the setting stored in the metadata of the document.
+    pub implicit_promotion: bool,
+    /// The value the non-finite floating-point values output by the tasks are replaced with, see
+    /// [`sanitize`], `None` if they are passed on as they are. Mirrors the settings stored in the
+    /// metadata of the document.
+    pub sanitize_numerics: Option<f64>,
+    pub active_schedule: Arc<ArcSwapOption<Schedule>>,
+    /// Lags behind the generation of the active schedule until the executor picks it up.
+    pub executing_generation: Arc<ExecutingGeneration>,
+    /// The results of the checks of the previous validation, see [`validation`].
+    validation_cache: Mutex<ValidationCache>,
+}
+
+impl ExecutionGraph {
+    /// A copy of the nodes and connections without their view state and without the schedule,
+    /// which edits can be tried out on to find out what they would break.
+    pub fn dry_run_copy(&self) -> Self {
+        let graph = self.graph.map(
+            |_, node_data| node_data.dry_run_copy(),
+            |_, edge_data| EdgeData {
+                endpoint_from: edge_data.endpoint_from,
+                endpoint_to: edge_data.endpoint_to,
+                primary: edge_data.primary,
+            },
+        );
+
+        Self {
+            implicit_promotion: self.implicit_promotion,
+            sanitize_numerics: self.sanitize_numerics,
+            validation_cache: Mutex::new(self.validation_cache.lock().unwrap().clone()),
+            ..graph.into()
+        }
+    }
+
+    pub fn get_connections(&self) -> Vec<Connection> {
+        let mut connections = Vec::with_capacity(self.graph.edge_count());
+
+        connections.extend(self.graph.edge_indices().map(|edge_index| {
+            let edge_data = &self.graph[edge_index];
+            let (index_from, index_to) = self.graph.edge_endpoints(edge_index).unwrap();
+            let undirected_channel_id_from = edge_data.endpoint_from.into_undirected_identifier(index_from);
+            let undirected_channel_id_to = edge_data.endpoint_to.into_undirected_identifier(index_to);
+            Connection([undirected_channel_id_from, undirected_channel_id_to])
+        }));
+
+        connections
+    }
+
+    /// Whether the channel still exists in the current configuration of its node.
+    pub fn contains_channel(&self, channel: ChannelIdentifier) -> bool {
+        self.graph.node_weight(channel.node_index).map_or(false, |node_data| {
+            node_data
+                .configuration
+                .channels(channel.channel_direction)
+                .any(|channel_ref| channel_ref.edge_endpoint == channel.into())
+        })
+    }
+
+    /// Whether the connection does not exist yet and could be created, as both of its channels
+    /// exist and are compatible.
+    pub fn can_connect(&self, connection: &Connection) -> bool {
+        if !self.contains_channel(connection.from()) || !self.contains_channel(connection.to()) {
+            return false;
+        }
+
+        let connections = self.get_connections();
+
+        if connections.contains(connection) {
+            return false;
+        }
+
+        let receives_move = connection.would_receive_move(&connections);
+
+        connection.is_valid(
+            &|from| {
+                !receives_move
+                    && connections.iter().filter(|connection| connection.from() == from).count() > 1
+            },
+            &|channel| {
+                self.graph[channel.node_index]
+                    .configuration
+                    .channel(channel.channel_direction, channel.into())
+            },
+            self.implicit_promotion,
+        )
+    }
+
+    /// Whether the connection would close a cycle, as its input already leads to its output.
+    pub fn would_create_cycle(&self, connection: &Connection) -> bool {
+        petgraph::algo::has_path_connecting(
+            &self.graph,
+            connection.to().node_index,
+            connection.from().node_index,
+            None,
+        )
+    }
+
+    /// The connections offered to keep the values flowing past the node once it is removed. Each
+    /// output connected to an input of the node is paired with each input its outputs are connected
+    /// to, as long as the connection could be created in the graph without the node, in the order
+    /// of the connections. Created together, the connections may still alias outputs or connect an
+    /// input twice, see [`GraphCommand::DissolveNode`](controller::GraphCommand::DissolveNode).
+    pub fn reconnection_candidates(&self, node: NodeIndex) -> Vec<Connection> {
+        let connections = self.get_connections();
+        let mut sources = Vec::new();
+        let consumers = connections
+            .iter()
+            .filter(|connection| connection.from().node_index == node && connection.to().node_index != node)
+            .map(Connection::to)
+            .collect::<Vec<_>>();
+
+        for connection in &connections {
+            if connection.to().node_index == node
+                && connection.from().node_index != node
+                && !sources.contains(&connection.from())
+            {
+                sources.push(connection.from());
+            }
+        }
+
+        let mut graph = self.dry_run_copy();
+
+        graph.remove_node(node);
+
+        sources
+            .iter()
+            .flat_map(|source| {
+                consumers
+                    .iter()
+                    .map(move |consumer| Connection::try_from_identifiers([*source, *consumer]).unwrap())
+            })
+            .filter(|connection| graph.can_connect(connection) && !graph.would_create_cycle(connection))
+            .collect()
+    }
+
+    /// The conversion of the values passed through the connection, if implicit promotion is
+    /// enabled and the connection requires it.
+    pub fn promotion(&self, connection: &Connection) -> Option<Promotion> {
+        if !self.implicit_promotion {
+            return None;
+        }
+
+        connection.promotion(&|channel| {
+            self.graph[channel.node_index].configuration.channel(channel.channel_direction, channel.into())
+        })
+    }
+
+    /// A label of the channel in the form of `<node> / <channel>`.
+    pub fn channel_label(&self, channel: ChannelIdentifier) -> Option<String> {
+        let node_data = self.graph.node_weight(channel.node_index)?;
+        let channel_ref = node_data
+            .configuration
+            .channels(channel.channel_direction)
+            .find(|channel_ref| channel_ref.edge_endpoint == channel.into())?;
+
+        Some(format!("{} / {}", node_data.title, channel_ref.title))
+    }
+
+    /// Designates the recipient of the value of each output connected to inputs requiring
+    /// exclusive access to it. The edge marked as primary takes precedence over the edge with the
+    /// lowest index, which is the one created first, unless the index of a removed edge has been
+    /// reused. The designation only changes along with the edges, so that regenerated schedules
+    /// move the value into the same connection.
+    pub fn move_recipients(&self) -> IndexMap<ChannelIdentifier, MoveRecipient> {
+        let mut recipients = IndexMap::<ChannelIdentifier, MoveRecipient>::new();
+
+        // Edges are iterated in the order of their indices.
+        for edge_index in self.graph.edge_indices() {
+            let edge = &self.graph[edge_index];
+            let (node_index_from, node_index_to) = self.graph.edge_endpoints(edge_index).unwrap();
+            let connection = Connection([
+                edge.endpoint_from.into_undirected_identifier(node_index_from),
+                edge.endpoint_to.into_undirected_identifier(node_index_to),
+            ]);
+
+            if !connection.requires_exclusive_access() {
+                continue;
+            }
+
+            match recipients.entry(connection.from()) {
+                indexmap::map::Entry::Occupied(mut entry) => {
+                    let recipient = entry.get_mut();
+
+                    if edge.primary && !recipient.primary {
+                        recipient.connection = connection.clone();
+                        recipient.primary = true;
+                    }
+
+                    recipient.candidates.push(connection);
+                }
+                indexmap::map::Entry::Vacant(entry) => {
+                    entry.insert(MoveRecipient {
+                        connection: connection.clone(),
+                        primary: edge.primary,
+                        candidates: vec![connection],
+                    });
+                }
+            }
+        }
+
+        recipients
+    }
+
+    /// Marks the edge of the connection as primary, clearing the flag of the other edges of the
+    /// same output. Returns whether the connection exists.
+    pub fn set_primary_connection(&mut self, connection: &Connection) -> bool {
+        if !self.get_connections().contains(connection) {
+            return false;
+        }
+
+        let from = connection.from();
+        let edges = self
+            .graph
+            .edges_directed(from.node_index, Direction::Outgoing)
+            .filter(|edge_ref| edge_ref.weight().endpoint_from == from.into())
+            .map(|edge_ref| (edge_ref.id(), edge_ref.target()))
+            .collect::<Vec<_>>();
+
+        for (edge_index, node_index_to) in edges {
+            let edge = &mut self.graph[edge_index];
+
+            edge.primary = node_index_to == connection.to().node_index
+                && edge.endpoint_to == EdgeEndpoint::from(connection.to());
+        }
+
+        true
+    }
+
+    /// Invalidates the cached checks reading the nodes, once they have been edited, see
+    /// [`validation`]. `topology_changed` is whether nodes or connections have been added or
+    /// removed.
+    pub fn invalidate_validation(
+        &mut self,
+        nodes: impl IntoIterator<Item = NodeIndex>,
+        topology_changed: bool,
+    ) {
+        let cache = self.validation_cache.get_mut().unwrap();
+
+        for node in nodes {
+            // Removed nodes have no connections left, the nodes downstream of them are touched by
+            // the removal of the connections.
+            cache.invalidate_node(node, self.graph.neighbors_directed(node, Direction::Outgoing));
+        }
+
+        if topology_changed {
+            cache.invalidate_topology();
+        }
+    }
+
+    /// Invalidates all of the cached checks, see [`validation`].
+    pub fn invalidate_validation_all(&mut self) {
+        self.validation_cache.get_mut().unwrap().invalidate_all();
+    }
+
+    /// Returns the nodes in the order they are to be executed, along with the warnings, which do
+    /// not prevent the graph from being scheduled. The warnings are included in the errors, if
+    /// the graph is invalid. Only the checks affected by the edits since the previous validation
+    /// are performed, see [`validation`].
+    pub fn check_graph_validity(
+        &self,
+    ) -> Result<(Vec<NodeIndex>, Vec<GraphValidationError>), Vec<GraphValidationError>> {
+        let mut cache = self.validation_cache.lock().unwrap();
+        let result = self.validate(Some(&mut cache));
+
+        // The cached checks must find exactly the same problems as the full validation.
+        #[cfg(feature = "verify-validation-cache")]
+        {
+            let full = self.validate(None);
+
+            assert_eq!(
+                format!("{:?}", result),
+                format!("{:?}", full),
+                "The cached validation of the graph differs from the full validation."
+            );
+        }
+
+        result
+    }
+
+    /// Validates the graph, reusing the results of the checks cached by the previous validation,
+    /// if a cache is provided.
+    fn validate(
+        &self,
+        mut cache: Option<&mut ValidationCache>,
+    ) -> Result<(Vec<NodeIndex>, Vec<GraphValidationError>), Vec<GraphValidationError>> {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        // Reused for all nodes, so that validating does not allocate for every node.
+        let mut incoming = Vec::new();
+        let mut connected_types = Vec::new();
+
+        if let Some(cache) = cache.as_mut() {
+            cache.begin();
+        }
+
+        for node_index in self.node_indices() {
+            let mut check = || {
+                incoming.clear();
+                incoming.extend(self.edges_directed(node_index, Direction::Incoming).map(|edge_ref| {
+                    let edge = edge_ref.weight();
+                    let source_ty = self.graph[edge_ref.source()]
+                        .configuration
+                        .channels(ChannelDirection::Out)
+                        .find(|channel| channel.edge_endpoint == edge.endpoint_from)
+                        .map(|channel| channel.ty);
+
+                    (edge.endpoint_from, edge.endpoint_to, source_ty)
+                }));
+
+                self.check_node(node_index, &incoming, &mut connected_types)
+            };
+            let result = match cache.as_mut() {
+                Some(cache) => Cow::Borrowed(cache.check_node(node_index, check)),
+                None => Cow::Owned(check()),
+            };
+
+            errors.extend(result.errors.iter().cloned());
+            warnings.extend(result.warnings.iter().cloned());
+        }
+
+        // Check the validity of all connections. Outputs are not aliased for the connections
+        // their values are moved into.
+        let connections = self.get_connections();
+        let move_recipients = self.move_recipients();
+        let mut connection_counts = HashMap::<ChannelIdentifier, usize>::new();
+
+        for connection in &connections {
+            *connection_counts.entry(connection.from()).or_default() += 1;
+        }
+
+        for connection in &connections {
+            let is_aliased = |channel: ChannelIdentifier| {
+                connection_counts.get(&channel).map_or(false, |count| *count > 1)
+                    && move_recipients
+                        .get(&channel)
+                        .map_or(true, |recipient| recipient.connection != *connection)
+            };
+            let get_channel = |channel: ChannelIdentifier| {
+                let node = &self[channel.node_index];
+
+                node.configuration.channel(channel.channel_direction, channel.into())
+            };
+            let check = || {
+                let validity = connection.check_validity(&is_aliased, &get_channel, self.implicit_promotion);
+                let error = validity.err().map(|error| GraphValidationError::InvalidConnection {
+                    connection: connection.clone(),
+                    error,
+                });
+
+                CheckResult { errors: error.into_iter().collect(), warnings: Vec::new() }
+            };
+            let result = match cache.as_mut() {
+                Some(cache) => {
+                    let key = || ConnectionKey {
+                        from_ty: get_channel(connection.from()).ty.clone(),
+                        to_ty: get_channel(connection.to()).ty.clone(),
+                        aliased: is_aliased(connection.from()),
+                        implicit_promotion: self.implicit_promotion,
+                    };
+
+                    Cow::Borrowed(cache.check_connection(connection, key, check))
+                }
+                None => Cow::Owned(check()),
+            };
+
+            errors.extend(result.errors.iter().cloned());
+        }
+
+        for (channel, recipient) in &move_recipients {
+            if recipient.is_ambiguous() {
+                errors.push(GraphValidationError::AmbiguousMove {
+                    channel: *channel,
+                    connections: recipient.candidates.clone(),
+                });
+            }
+        }
+
+        let sorted_nodes = match cache {
+            Some(cache) => {
+                let sorted_nodes = cache.topological_order(|| self.sort_topologically());
+
+                cache.end();
+                sorted_nodes
+            }
+            None => self.sort_topologically(),
+        };
+
+        match sorted_nodes {
+            Ok(sorted_nodes) if errors.is_empty() => {
+                Ok((self.order_move_recipients_last(sorted_nodes, &move_recipients), warnings))
+            }
+            sorted_nodes => {
+                if let Err(cycles) = sorted_nodes {
+                    errors.extend(cycles);
+                }
+
+                errors.extend(warnings);
+                Err(errors)
+            }
+        }
+    }
+
+    /// Checks that the node has either no input channels connected and thus is unused or that all
+    /// input channels are connected and thus is complete, and that its behaviour accepts the
+    /// connected types. `incoming` lists the endpoints of the incoming connections along with the
+    /// types of their outputs.
+    fn check_node<'a>(
+        &'a self,
+        node_index: NodeIndex,
+        incoming: &[(EdgeEndpoint, EdgeEndpoint, Option<&'a TypeEnum>)],
+        connected_types: &mut Vec<Option<&'a TypeEnum>>,
+    ) -> CheckResult {
+        let node = &self.graph[node_index];
+        let input_channels = node
+            .configuration
+            .channels(ChannelDirection::In)
+            .map(|channel_ref| channel_ref.edge_endpoint)
+            .collect::<Vec<_>>();
+        let mut result = CheckResult::default();
+
+        // Unused nodes are not executed, so their constraints do not matter.
+        if !input_channels.is_empty() && incoming.is_empty() {
+            return result;
+        }
+
+        connected_types.clear();
+        connected_types.resize(input_channels.len(), None);
+
+        for (_, endpoint_to, source_ty) in incoming {
+            let global_input_channel_index = node.configuration.get_global_channel_index(*endpoint_to);
+
+            if let Some(connected_type) = connected_types.get_mut(global_input_channel_index) {
+                *connected_type = *source_ty;
+            }
+        }
+
+        // Reported in the order of the channels, so that every validation reports them equally.
+        for input_channel in input_channels {
+            if !incoming.iter().any(|(_, endpoint_to, _)| *endpoint_to == input_channel) {
+                result.errors.push(GraphValidationError::IncompleteInput(
+                    input_channel.into_undirected_identifier(node_index),
+                ));
+            }
+        }
+
+        let connected = ConnectedTypeInfo::new(&node.configuration, connected_types);
+
+        for issue in node.behaviour.validate(&node.configuration, connected) {
+            let severity = issue.severity;
+            let error = GraphValidationError::NodeValidation { node: node_index, issue };
+
+            match severity {
+                Severity::Warning => result.warnings.push(error),
+                Severity::Error => result.errors.push(error),
+            }
+        }
+
+        result
+    }
+
+    /// Sorts the nodes topologically, or returns the strongly connected components preventing it.
+    fn sort_topologically(&self) -> Result<Vec<NodeIndex>, Vec<GraphValidationError>> {
+        petgraph::algo::toposort(&self.graph, None).map_err(|_cycle| {
+            petgraph::algo::tarjan_scc(&self.graph)
+                .into_iter()
+                .filter_map(|scc| {
+                    if scc.len() <= 1 {
+                        return None;
+                    }
+
+                    let node_set = scc.iter().copied().collect::<HashSet<_>>();
+                    let connections = self
+                        .graph
+                        .edge_indices()
+                        .filter_map(|edge_index| {
+                            let (node_from, node_to) = self.graph.edge_endpoints(edge_index).unwrap();
+
+                            if node_set.contains(&node_from) && node_set.contains(&node_to) {
+                                let edge = &self.graph[edge_index];
+
+                                Some(Connection([
+                                    edge.endpoint_from.into_undirected_identifier(node_from),
+                                    edge.endpoint_to.into_undirected_identifier(node_to),
+                                ]))
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<_>>();
+
+                    Some(GraphValidationError::StronglyConnectedComponent { nodes: scc, connections })
+                })
+                .collect()
+        })
+    }
+
+    /// Reorders the topologically sorted nodes, so that the recipients of moved values are
+    /// executed after the other consumers of the outputs, which borrow the values. The order is
+    /// kept if that is impossible, which is reported once the tasks are created.
+    fn order_move_recipients_last(
+        &self,
+        sorted_nodes: Vec<NodeIndex>,
+        move_recipients: &IndexMap<ChannelIdentifier, MoveRecipient>,
+    ) -> Vec<NodeIndex> {
+        let mut constraints = Vec::new();
+
+        for (channel, recipient) in move_recipients {
+            let recipient_node_index = recipient.connection.to().node_index;
+
+            for edge_ref in self.graph.edges_directed(channel.node_index, Direction::Outgoing) {
+                if edge_ref.weight().endpoint_from == EdgeEndpoint::from(*channel)
+                    && edge_ref.target() != recipient_node_index
+                {
+                    constraints.push((edge_ref.target(), recipient_node_index));
+                }
+            }
+        }
+
+        if constraints.is_empty() {
+            return sorted_nodes;
+        }
+
+        // Nodes are inserted in the sorted order, which the graph map preserves.
+        let mut ordering = DiGraphMap::<NodeIndex, ()>::new();
+
+        for node_index in &sorted_nodes {
+            ordering.add_node(*node_index);
+        }
+
+        for edge_index in self.graph.edge_indices() {
+            let (node_index_from, node_index_to) = self.graph.edge_endpoints(edge_index).unwrap();
+
+            ordering.add_edge(node_index_from, node_index_to, ());
+        }
+
+        for (node_index_before, node_index_after) in constraints {
+            ordering.add_edge(node_index_before, node_index_after, ());
+        }
+
+        petgraph::algo::toposort(&ordering, None).unwrap_or(sorted_nodes)
+    }
+
+    fn create_schedule(
+        &mut self,
+    ) -> Result<(Schedule, Vec<GraphValidationError>), Vec<GraphValidationError>> {
+        let (ordered_node_indices, warnings) = self.check_graph_validity()?;
+        let node_indices = ordered_node_indices.clone().into_boxed_slice();
+        let tasks = self
+            .create_tasks(ordered_node_indices)
+            .map_err(|error| vec![GraphValidationError::Schedule(error)])?;
+        let throughput = Arc::new(ThroughputCounters::new(&tasks, self.get_connections()));
+        let execution_times = Arc::new(ExecutionTimes::new(tasks.len()));
+
+        let schedule = Schedule {
+            generation: Self::next_generation(),
+            graph_id: self.id,
+            tasks: tasks.into_boxed_slice(),
+            node_indices,
+            throughput,
+            execution_times,
+            sanitize_numerics: self.sanitize_numerics,
+        };
+
+        Ok((schedule, warnings))
+    }
+
+    /// The nodes excluded from execution by muting and soloing: the muted sink nodes, along with
+    /// the nodes whose outputs are consumed by muted nodes only. While any sink node is soloed,
+    /// all sink nodes that are not soloed are muted. Outputs connected to inputs ignored by their
+    /// nodes, see [`NodeBehaviour::ignored_inputs`](crate::node::behaviour::NodeBehaviour::ignored_inputs),
+    /// are not considered consumed either.
+    pub fn muted_nodes(&self) -> HashSet<NodeIndex> {
+        let any_soloed = self.graph.node_weights().any(|node| node.soloed && node.behaviour.is_sink());
+        let is_muted_sink =
+            |node: &NodeData| node.behaviour.is_sink() && (node.muted || (any_soloed && !node.soloed));
+        let ignored_inputs = self
+            .graph
+            .node_indices()
+            .map(|node_index| (node_index, self.graph[node_index].behaviour.ignored_inputs()))
+            .filter(|(_, ignored_inputs)| !ignored_inputs.is_empty())
+            .collect::<HashMap<_, _>>();
+        let mut muted_nodes = self
+            .graph
+            .node_indices()
+            .filter(|node_index| is_muted_sink(&self.graph[*node_index]))
+            .collect::<HashSet<_>>();
+        let mut pending = muted_nodes.iter().chain(ignored_inputs.keys()).copied().collect::<Vec<_>>();
+
+        while let Some(node_index) = pending.pop() {
+            for source_index in self.graph.neighbors_directed(node_index, Direction::Incoming) {
+                // Unmuted sinks have side effects of their own.
+                let muted = !muted_nodes.contains(&source_index)
+                    && !self.graph[source_index].behaviour.is_sink()
+                    && self.graph.edges_directed(source_index, Direction::Outgoing).all(|edge_ref| {
+                        let endpoint = edge_ref.weight().endpoint_to;
+
+                        muted_nodes.contains(&edge_ref.target())
+                            || endpoint.pass_by == ChannelPassBy::Value
+                                && ignored_inputs
+                                    .get(&edge_ref.target())
+                                    .map_or(false, |ignored| ignored.contains(&endpoint.channel_index))
+                    });
+
+                if muted {
+                    muted_nodes.insert(source_index);
+                    pending.push(source_index);
+                }
+            }
+        }
+
+        muted_nodes
+    }
+
+    /// Creates the tasks of the nodes in the order they are to be executed, `None` for unused
+    /// and muted nodes.
+    fn create_tasks(&self, ordered_node_indices: Vec<NodeIndex>) -> Result<Vec<Option<Task>>, ScheduleError> {
+        let node_index_map: HashMap<NodeIndex, usize> = ordered_node_indices
+            .iter()
+            .enumerate()
+            .map(|(enumeration_index, node_index)| (*node_index, enumeration_index))
+            .collect();
+
+        let muted_nodes = self.muted_nodes();
+        let mut tasks = Vec::<Option<Task>>::with_capacity(ordered_node_indices.len());
+        // The first task moving or mutably borrowing each output value, along with the connection
+        // and the tasks borrowing the value immutably, keyed by the task and the output index.
+        let mut moves = BTreeMap::<(usize, usize), (usize, Connection)>::new();
+        let mut borrowers = BTreeMap::<(usize, usize), Vec<(usize, NodeIndex)>>::new();
+
+        for node_index in ordered_node_indices {
+            let node = self.node_weight(node_index);
+            let node = node.as_ref().unwrap();
+            let ignored_inputs = node.behaviour.ignored_inputs();
+            let mut accesses = Vec::new();
+            let optional_task = 'optional_task: loop {
+                if muted_nodes.contains(&node_index) {
+                    break 'optional_task None;
+                }
+
+                let mut borrows: Vec<Option<TaskInput>> =
+                    vec![None; node.configuration.channels_by_shared_reference.len()];
+                let mut mutable_borrows: Vec<Option<TaskInput>> =
+                    vec![None; node.configuration.channels_by_mutable_reference.len()];
+                let mut inputs: Vec<Option<TaskInput>> =
+                    vec![None; node.configuration.input_channels_by_value.len()];
+                let mut used = borrows.is_empty() && mutable_borrows.is_empty() && inputs.is_empty();
+
+                for edge_ref in self.edges_directed(node_index, Direction::Incoming) {
+                    let edge = edge_ref.weight();
+                    let connection = Connection([
+                        edge.endpoint_from.into_undirected_identifier(edge_ref.source()),
+                        edge.endpoint_to.into_undirected_identifier(node_index),
+                    ]);
+                    // The source must have been scheduled before the current node.
+                    let immediate_source_task_index = node_index_map
+                        .get(&edge_ref.source())
+                        .copied()
+                        .filter(|task_index| *task_index < tasks.len())
+                        .ok_or_else(|| ScheduleError::UnscheduledSource { connection: connection.clone() })?;
+
+                    // Ignored inputs are passed an empty value instead, whether the source is
+                    // executed or not.
+                    if edge.endpoint_to.pass_by == ChannelPassBy::Value
+                        && ignored_inputs.contains(&edge.endpoint_to.channel_index)
+                    {
+                        *inputs.get_mut(edge.endpoint_to.channel_index).ok_or_else(|| {
+                            ScheduleError::MissingChannel {
+                                connection: connection.clone(),
+                                channel: connection.to(),
+                            }
+                        })? = Some(TaskInput {
+                            task_index: immediate_source_task_index,
+                            output_value_channel_index: edge.endpoint_from.channel_index,
+                            promotion: None,
+                            pruned: true,
+                        });
+                        used = true;
+                        continue;
+                    }
+
+                    // Nodes consuming the outputs of a muted sink node are not executed either.
+                    if tasks[immediate_source_task_index].is_none() {
+                        break 'optional_task None;
+                    }
+
+                    // If the input is a reference, transitively derive the value storage.
+                    let task_input = if edge.endpoint_from.pass_by == ChannelPassBy::Value {
+                        TaskInput {
+                            task_index: immediate_source_task_index,
+                            output_value_channel_index: edge.endpoint_from.channel_index,
+                            promotion: None,
+                            pruned: false,
+                        }
+                    } else {
+                        let source_task = tasks[immediate_source_task_index].as_ref().unwrap();
+
+                        let transitive_task_inputs = match edge.endpoint_from.pass_by {
+                            ChannelPassBy::SharedReference => &source_task.borrows,
+                            ChannelPassBy::MutableReference => &source_task.mutable_borrows,
+                            ChannelPassBy::Value => &source_task.inputs,
+                        };
+
+                        transitive_task_inputs.get(edge.endpoint_from.channel_index).cloned().ok_or_else(
+                            || ScheduleError::MissingChannel {
+                                connection: connection.clone(),
+                                channel: connection.from(),
+                            },
+                        )?
+                    };
+
+                    let task_input = TaskInput { promotion: self.promotion(&connection), ..task_input };
+                    // The inputs are indexed among the channels passed the same way, by the index of
+                    // the endpoint, rather than by `NodeConfiguration::get_global_channel_index`,
+                    // which also counts the channels passed the other ways.
+                    let task_inputs = match edge.endpoint_to.pass_by {
+                        ChannelPassBy::SharedReference => &mut borrows,
+                        ChannelPassBy::MutableReference => &mut mutable_borrows,
+                        ChannelPassBy::Value => &mut inputs,
+                    };
+
+                    *task_inputs.get_mut(edge.endpoint_to.channel_index).ok_or_else(|| {
+                        ScheduleError::MissingChannel {
+                            connection: connection.clone(),
+                            channel: connection.to(),
+                        }
+                    })? = Some(task_input.clone());
+                    accesses.push((task_input, connection));
+                    used = true;
+                }
+
+                break 'optional_task if used {
+                    let unwrap_inputs = |values: Vec<Option<TaskInput>>, pass_by: ChannelPassBy| {
+                        values
+                            .into_iter()
+                            .enumerate()
+                            .map(|(channel_index, value)| {
+                                value.ok_or(ScheduleError::MissingInput {
+                                    channel: ChannelIdentifier {
+                                        node_index,
+                                        channel_direction: ChannelDirection::In,
+                                        channel_index,
+                                        pass_by,
+                                    },
+                                })
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                            .map(Vec::into_boxed_slice)
+                    };
+                    let borrows = unwrap_inputs(borrows, ChannelPassBy::SharedReference)?;
+                    let mutable_borrows = unwrap_inputs(mutable_borrows, ChannelPassBy::MutableReference)?;
+                    let inputs = unwrap_inputs(inputs, ChannelPassBy::Value)?;
+
+                    Some(Task {
+                        node_index,
+                        configuration: node.configuration.clone(),
+                        behaviour: node.behaviour.clone(),
+                        borrows,
+                        mutable_borrows,
+                        inputs,
+                    })
+                } else {
+                    None
+                };
+            };
+
+            if optional_task.is_some() {
+                let task_index = tasks.len();
+
+                for (task_input, connection) in accesses {
+                    let storage = (task_input.task_index, task_input.output_value_channel_index);
+
+                    if connection.requires_exclusive_access() {
+                        moves.entry(storage).or_insert((task_index, connection));
+                    } else {
+                        borrowers.entry(storage).or_default().push((task_index, node_index));
+                    }
+                }
+            }
+
+            tasks.push(optional_task);
+        }
+
+        // Values may only be borrowed before they are moved, including by the same node.
+        for (storage, (move_task_index, connection)) in moves {
+            let borrower = borrowers.get(&storage).and_then(|borrowers| {
+                borrowers.iter().find(|(borrow_task_index, _)| *borrow_task_index >= move_task_index)
+            });
+
+            if let Some((_, borrower)) = borrower {
+                return Err(ScheduleError::BorrowedAfterMove { connection, borrower: *borrower });
+            }
+        }
+
+        Ok(tasks)
+    }
+
+    /// Informs the behaviours about the output channels connected to their input channels.
+    fn update_input_sources(&mut self) {
+        let node_indices = self.node_indices().collect::<Vec<_>>();
+
+        for node_index in node_indices {
+            let node = &self.graph[node_index];
+            let mut sources = vec![None; node.configuration.channels(ChannelDirection::In).count()];
+
+            for edge_ref in self.edges_directed(node_index, Direction::Incoming) {
+                let edge = edge_ref.weight();
+                let source_title = self.graph[edge_ref.source()]
+                    .configuration
+                    .channels(ChannelDirection::Out)
+                    .find(|channel| channel.edge_endpoint == edge.endpoint_from)
+                    .map(|channel| channel.title.to_string());
+                let global_input_channel_index =
+                    node.configuration.get_global_channel_index(edge.endpoint_to);
+
+                if let Some(source) = sources.get_mut(global_input_channel_index) {
+                    *source = source_title;
+                }
+            }
+
+            self.graph[node_index].behaviour.update_input_sources(&sources);
+        }
+    }
+
+    /// Replaces the active schedule with one constructed from the current graph. If the graph is
+    /// invalid, the previous schedule is kept running, so that an edit in progress does not stop
+    /// the execution. Returns the warnings about the graph, which do not prevent it from being
+    /// scheduled.
+    pub fn update_schedule(&mut self) -> Result<Vec<GraphValidationError>, Vec<GraphValidationError>> {
+        self.update_input_sources();
+
+        let (schedule, warnings) = self.create_schedule()?;
+
+        self.active_schedule.store(Some(Arc::new(schedule)));
+        Ok(warnings)
+    }
+
+    /// Stops the execution by replacing the active schedule with an empty one. Unlike clearing the
+    /// active schedule, this keeps the generations increasing, so that the executor cannot mistake
+    /// the next schedule for the one it has prepared.
+    pub fn clear_schedule(&mut self) {
+        let schedule = Schedule {
+            generation: Self::next_generation(),
+            graph_id: self.id,
+            tasks: Box::new([]),
+            node_indices: Box::new([]),
+            throughput: Arc::new(ThroughputCounters::new(&[], Vec::new())),
+            execution_times: Arc::new(ExecutionTimes::new(0)),
+            sanitize_numerics: None,
+        };
+
+        self.active_schedule.store(Some(Arc::new(schedule)));
+    }
+
+    /// The generation of the active schedule, `None` if the graph has never been scheduled.
+    pub fn scheduled_generation(&self) -> Option<usize> {
+        self.active_schedule.load().as_ref().map(|schedule| schedule.generation)
+    }
+
+    /// Generations are unique among all graphs, as the executor may execute the schedules of
+    /// several graphs, see [`PinnedSchedules`].
+    fn next_generation() -> usize {
+        NEXT_SCHEDULE_GENERATION.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub fn id(&self) -> GraphId {
+        self.id
+    }
+}
+
+impl From<Graph> for ExecutionGraph {
+    fn from(graph: Graph) -> Self {
+        Self {
+            graph,
+            id: GraphId::generate(),
+            implicit_promotion: false,
+            sanitize_numerics: None,
+            active_schedule: Default::default(),
+            executing_generation: Default::default(),
+            validation_cache: Default::default(),
+        }
+    }
+}
+
+impl Deref for ExecutionGraph {
+    type Target = Graph;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+impl DerefMut for ExecutionGraph {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.graph
+    }
+}
+
+/// The capabilities of the adapter, queried at startup.
+#[derive(Debug, Clone)]
+pub struct RendererCapabilities {
+    pub adapter_info: wgpu::AdapterInfo,
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+    /// The maximum width and height of 2D textures supported by the device.
+    pub max_texture_dimension_2d: u32,
+}
+
+impl RendererCapabilities {
+    pub fn new(adapter: &wgpu::Adapter) -> Self {
+        let adapter_info = adapter.get_info();
+
+        Self {
+            max_texture_dimension_2d: Self::max_texture_dimension_2d(&adapter_info),
+            features: adapter.features(),
+            limits: adapter.limits(),
+            adapter_info,
+        }
+    }
+
+    /// The limit is not part of [`wgpu::Limits`] yet, so it is derived from the limits the
+    /// backend of the device guarantees.
+    fn max_texture_dimension_2d(adapter_info: &wgpu::AdapterInfo) -> u32 {
+        use wgpu::{Backend, DeviceType};
+
+        match (adapter_info.backend, adapter_info.device_type) {
+            // Required by Direct3D feature level 11_0, the lowest one supported by wgpu.
+            (Backend::Dx12, _) | (Backend::Dx11, _) => 16384,
+            // All Mac GPU families support 16384, the iOS ones supported by wgpu 8192.
+            (Backend::Metal, _) if cfg!(target_os = "macos") => 16384,
+            (Backend::Metal, _) => 8192,
+            // Reported by all desktop Vulkan drivers.
+            (Backend::Vulkan, DeviceType::DiscreteGpu) | (Backend::Vulkan, DeviceType::IntegratedGpu) => {
+                16384
+            }
+            // The minimum required by the Vulkan specification.
+            (Backend::Vulkan, _) => 4096,
+            (_, _) => 2048,
+        }
+    }
+
+    /// Checks whether a texture with the given parameters may be created, without touching wgpu.
+    pub fn validate_texture(
+        &self,
+        size: Vec2<u32>,
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsage,
+    ) -> Result<(), TextureCapabilityError> {
+        if size[0] == 0 || size[1] == 0 {
+            return Err(TextureCapabilityError::ZeroSized);
+        }
+
+        if size[0] > self.max_texture_dimension_2d || size[1] > self.max_texture_dimension_2d {
+            return Err(TextureCapabilityError::TooLarge { size, max: self.max_texture_dimension_2d });
+        }
+
+        if !Self::format_supports_usage(format, usage) {
+            return Err(TextureCapabilityError::UnsupportedUsage { format, usage });
+        }
+
+        Ok(())
+    }
+
+    fn format_supports_usage(format: wgpu::TextureFormat, usage: wgpu::TextureUsage) -> bool {
+        use wgpu::TextureFormat::*;
+
+        let compressed = matches!(
+            format,
+            Bc1RgbaUnorm
+                | Bc1RgbaUnormSrgb
+                | Bc2RgbaUnorm
+                | Bc2RgbaUnormSrgb
+                | Bc3RgbaUnorm
+                | Bc3RgbaUnormSrgb
+                | Bc4RUnorm
+                | Bc4RSnorm
+                | Bc5RgUnorm
+                | Bc5RgSnorm
+                | Bc6hRgbUfloat
+                | Bc6hRgbSfloat
+                | Bc7RgbaUnorm
+                | Bc7RgbaUnormSrgb
+        );
+        let renderable = !compressed && !matches!(format, R8Snorm | Rg8Snorm | Rgba8Snorm | Rg11b10Float);
+        let storable = !compressed
+            && !matches!(
+                format,
+                Rgba8UnormSrgb
+                    | Bgra8Unorm
+                    | Bgra8UnormSrgb
+                    | Depth32Float
+                    | Depth24Plus
+                    | Depth24PlusStencil8
+            );
+
+        (!usage.contains(wgpu::TextureUsage::OUTPUT_ATTACHMENT) || renderable)
+            && (!usage.contains(wgpu::TextureUsage::STORAGE) || storable)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextureCapabilityError {
+    ZeroSized,
+    TooLarge { size: Vec2<u32>, max: u32 },
+    UnsupportedUsage { format: wgpu::TextureFormat, usage: wgpu::TextureUsage },
+}
+
+impl Display for TextureCapabilityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextureCapabilityError::ZeroSized => write!(f, "Textures must be at least 1×1 texels large."),
+            TextureCapabilityError::TooLarge { size, max } => write!(
+                f,
+                "The texture size {}×{} exceeds the maximum of {}×{} supported by the device.",
+                size[0], size[1], max, max
+            ),
+            TextureCapabilityError::UnsupportedUsage { format, usage } => {
+                write!(f, "Textures of format {:?} do not support the usage {:?}.", format, usage)
+            }
+        }
+    }
+}
+
+/// Tracks which node is currently submitting GPU commands, so that device errors can be attributed
+/// to it.
+#[derive(Debug)]
+pub struct ExecutingNode(AtomicUsize);
+
+impl ExecutingNode {
+    const NONE: usize = usize::MAX;
+
+    pub fn set(&self, node: Option<NodeIndex>) {
+        self.0.store(node.map(|node| node.index()).unwrap_or(Self::NONE), Ordering::SeqCst);
+    }
+
+    pub fn get(&self) -> Option<NodeIndex> {
+        match self.0.load(Ordering::SeqCst) {
+            Self::NONE => None,
+            index => Some(NodeIndex::new(index)),
+        }
+    }
+}
+
+impl Default for ExecutingNode {
+    fn default() -> Self {
+        Self(AtomicUsize::new(Self::NONE))
+    }
+}
+
+/// A node that has been executing for longer than [`Config::watchdog_threshold`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalledTask {
+    pub node: NodeIndex,
+    pub elapsed: Duration,
+}
+
+/// Tracks the task being executed and since when, so that nodes blocking the executor can be
+/// detected from another thread.
+///
+/// A node that never returns from its execution cannot be interrupted. It can only be cleared by
+/// removing it from the graph, which does not depend on the executor, so that the node is not
+/// executed again once it does return.
+#[derive(Debug)]
+pub struct ExecutionWatchdog {
+    /// The moment the timestamps are relative to.
+    epoch: Instant,
+    node: AtomicUsize,
+    /// The number of microseconds between the epoch and the start of the current task.
+    task_start: AtomicU64,
+    cancellation_requested: AtomicBool,
+    sender: UnboundedSender<Option<StalledTask>>,
+    /// Taken by the subscription of the application.
+    receiver: Mutex<Option<UnboundedReceiver<Option<StalledTask>>>>,
+}
+
+impl Default for ExecutionWatchdog {
+    fn default() -> Self {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+
+        Self {
+            epoch: Instant::now(),
+            node: AtomicUsize::new(Self::NONE),
+            task_start: AtomicU64::new(0),
+            cancellation_requested: AtomicBool::new(false),
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+}
+
+impl ExecutionWatchdog {
+    const NONE: usize = usize::MAX;
+    /// How often the current task is checked.
+    const CHECK_PERIOD: Duration = Duration::from_millis(250);
+
+    fn begin_task(&self, node: NodeIndex) {
+        self.cancellation_requested.store(false, Ordering::SeqCst);
+        self.task_start.store(self.epoch.elapsed().as_micros() as u64, Ordering::SeqCst);
+        self.node.store(node.index(), Ordering::SeqCst);
+    }
+
+    fn end_task(&self) {
+        self.node.store(Self::NONE, Ordering::SeqCst);
+    }
+
+    /// The node being executed and for how long it has been executing.
+    pub fn current_task(&self) -> Option<StalledTask> {
+        let node = self.node.load(Ordering::SeqCst);
+        let task_start = Duration::from_micros(self.task_start.load(Ordering::SeqCst));
+
+        // Another task may have begun between the loads, in which case its start is unknown.
+        if node == Self::NONE || node != self.node.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        Some(StalledTask { node: NodeIndex::new(node), elapsed: self.epoch.elapsed() - task_start })
+    }
+
+    /// Asks the node being executed to stop its execution, if it is the specified one.
+    pub fn request_cancellation(&self, node: NodeIndex) {
+        if self.node.load(Ordering::SeqCst) == node.index() {
+            self.cancellation_requested.store(true, Ordering::SeqCst);
+        }
+    }
+
+    pub fn is_cancellation_requested(&self) -> bool {
+        self.cancellation_requested.load(Ordering::SeqCst)
+    }
+
+    /// Returns the receiver of the reports of stalled tasks, which can only be taken once.
+    /// `None` is reported once the stalled task has finished.
+    pub fn take_receiver(&self) -> Option<UnboundedReceiver<Option<StalledTask>>> {
+        self.receiver.lock().unwrap().take()
+    }
+
+    /// Periodically checks the task being executed and reports it, if it has been executing for
+    /// too long. Stalled tasks are asked to cancel their execution.
+    pub fn spawn(self: Arc<Self>, config: Arc<SharedConfig>) -> std::thread::JoinHandle<()> {
+        ThreadSettings::new("dvsynth-watchdog").spawn(move |_| {
+            let mut stalled = false;
+
+            loop {
+                thread::sleep(Self::CHECK_PERIOD);
+
+                let threshold = config.get().watchdog_threshold();
+                let task = self
+                    .current_task()
+                    .filter(|task| threshold.map(|threshold| task.elapsed >= threshold).unwrap_or(false));
+
+                if let Some(task) = task {
+                    self.request_cancellation(task.node);
+                } else if !stalled {
+                    continue;
+                }
+
+                stalled = task.is_some();
+
+                // The application may have been closed already.
+                if self.sender.unbounded_send(task).is_err() {
+                    return;
+                }
+            }
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Renderer {
+    pub instance: Arc<wgpu::Instance>,
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+    pub capabilities: RendererCapabilities,
+    pub executing_node: Arc<ExecutingNode>,
+}
+
+impl Renderer {
+    pub fn new(config: &Config) -> Self {
+        let instance = Arc::new(wgpu::Instance::new(wgpu::BackendBit::PRIMARY));
+        let (device, queue, capabilities) = {
+            let adapter = startup::span(Category::Executor, "adapter request", || {
+                futures::executor::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: config.adapter_preference.power_preference(),
+                    compatible_surface: None,
+                }))
+            })
+            .expect("No wgpu compatible adapter available.");
+
+            let (device, queue) = startup::span(Category::Executor, "device request", || {
+                futures::executor::block_on(adapter.request_device(
+                    &wgpu::DeviceDescriptor {
+                        features: wgpu::Features::empty(),
+                        limits: wgpu::Limits { max_bind_groups: 2, ..wgpu::Limits::default() },
+                        shader_validation: false,
+                    },
+                    None,
+                ))
+            })
+            .expect("No wgpu compatible device available.");
+            let capabilities = RendererCapabilities::new(&adapter);
+
+            info!(
+                Category::Executor,
+                "Maximum texture dimension of the device: {}", capabilities.max_texture_dimension_2d
+            );
+
+            (Arc::new(device), Arc::new(queue), capabilities)
+        };
+
+        Self { instance, device, queue, capabilities, executing_node: Default::default() }
+    }
+
+    /// Reports device errors as errors of the node that was executing at the time, instead of
+    /// panicking.
+    pub fn install_error_handler(&self, node_errors: Arc<NodeErrors>) {
+        let executing_node = self.executing_node.clone();
+
+        self.device.on_uncaptured_error(move |error| match executing_node.get() {
+            Some(node) => {
+                error!(Category::Executor, "GPU error caused by node #{}: {}", node.index(), error);
+                node_errors.set_gpu_fault(node, format!("GPU error: {}", error));
+            }
+            None => error!(Category::Executor, "GPU error outside of node execution: {}", error),
+        });
+    }
+
+    /// Creates a command encoder labelled with the node, to help identify it in GPU error reports.
+    pub fn create_node_command_encoder(&self, node: NodeIndex) -> wgpu::CommandEncoder {
+        self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some(&format!("node #{}", node.index())),
+        })
+    }
+}
+
+/// Reported as the state of the nodes using the GPU, while it is being acquired, see
+/// [`ApplicationContext::renderer`].
+pub const GPU_INITIALIZING: &str = "Initializing GPU…";
+
+#[derive(Debug, Clone)]
+pub struct NodeError {
+    pub message: String,
+    /// Nodes that caused a GPU error are not executed until the schedule is rebuilt.
+    pub gpu_fault: bool,
+}
+
+/// Errors that occurred during the execution of nodes, displayed alongside graph validation errors.
+#[derive(Debug, Default)]
+pub struct NodeErrors {
+    errors: RwLock<HashMap<NodeIndex, NodeError>>,
+}
+
+impl NodeErrors {
+    pub fn set(&self, node: NodeIndex, message: impl ToString) {
+        let mut errors = self.errors.write().unwrap();
+        let gpu_fault = errors.get(&node).map(|error| error.gpu_fault).unwrap_or(false);
+
+        errors.insert(node, NodeError { message: message.to_string(), gpu_fault });
+    }
+
+    pub fn set_gpu_fault(&self, node: NodeIndex, message: impl ToString) {
+        self.errors
+            .write()
+            .unwrap()
+            .insert(node, NodeError { message: message.to_string(), gpu_fault: true });
+    }
+
+    /// Clears the error of the node, unless it caused a GPU error.
+    pub fn clear(&self, node: NodeIndex) {
+        let mut errors = self.errors.write().unwrap();
+
+        if errors.get(&node).map(|error| !error.gpu_fault).unwrap_or(false) {
+            errors.remove(&node);
+        }
+    }
+
+    /// Clears the error of the node, including a GPU error, once it has been removed.
+    pub fn remove(&self, node: NodeIndex) {
+        self.errors.write().unwrap().remove(&node);
+    }
+
+    /// Clears the errors of all nodes, once the graph has been replaced.
+    pub fn remove_all(&self) {
+        self.errors.write().unwrap().clear();
+    }
+
+    /// Clears the errors of nodes not in the schedule, reported by their last execution after they
+    /// have been removed.
+    pub fn retain_scheduled(&self, schedule: &Schedule) {
+        let scheduled = schedule.node_indices.iter().collect::<HashSet<_>>();
+
+        self.errors.write().unwrap().retain(|node, _| scheduled.contains(node));
+    }
+
+    /// Allows nodes that caused GPU errors to be executed again.
+    pub fn clear_gpu_faults(&self) {
+        self.errors.write().unwrap().retain(|_, error| !error.gpu_fault);
+    }
+
+    pub fn is_gpu_faulty(&self, node: NodeIndex) -> bool {
+        self.errors.read().unwrap().get(&node).map(|error| error.gpu_fault).unwrap_or(false)
+    }
+
+    pub fn get_all(&self) -> Vec<(NodeIndex, NodeError)> {
+        self.errors.read().unwrap().iter().map(|(node, error)| (*node, error.clone())).collect()
+    }
+}
+
+/// The value of an output channel, taken by the executor on request of [`ValueTaps`].
+#[derive(Debug, Clone)]
+pub struct ValueSnapshot {
+    pub channel: ChannelIdentifier,
+    /// The reason why the value could not be taken, if it could not.
+    pub value: Result<PrimitiveChannelValue, String>,
+}
+
+impl ValueSnapshot {
+    /// The reason why values of the type cannot be taken, if they cannot.
+    pub fn unsupported_reason(ty: &TypeEnum) -> Option<&'static str> {
+        match ty {
+            _ if ty.as_primitive_type_enum().is_some() => None,
+            TypeEnum::Texture(_) => Some("Textures reside on the GPU and cannot be frozen."),
+            TypeEnum::Buffer(_) => Some("Buffers reside on the GPU and cannot be frozen."),
+            TypeEnum::List(_) => Some("Lists cannot be frozen yet."),
+            _ => Some("Only values of primitive types can be frozen."),
+        }
+    }
+
+    fn read(
+        task: &Task,
+        output_values: &[BorrowedRefMut<'_, OptionType>],
+        channel: ChannelIdentifier,
+    ) -> Result<PrimitiveChannelValue, String> {
+        let channel_value = task
+            .configuration
+            .output_channels_by_value
+            .get(channel.channel_index)
+            .zip(output_values.get(channel.channel_index))
+            .filter(|_| channel.pass_by == ChannelPassBy::Value);
+        let (output_channel, output_value) = if let Some(channel_value) = channel_value {
+            channel_value
+        } else {
+            return Err("The channel no longer exists.".to_string());
+        };
+
+        if let Some(reason) = Self::unsupported_reason(&output_channel.ty) {
+            return Err(reason.to_string());
+        }
+
+        let ty = output_channel.ty.as_primitive_type_enum().unwrap();
+        let value = output_value.get().ok_or_else(|| "The channel has no value yet.".to_string())?;
+        let bytes = value.as_bytes().map_err(|()| "The value is not readable.".to_string())?;
+
+        ty.read::<LittleEndian, _>(bytes).map_err(|error| error.to_string())
+    }
+}
+
+/// Requests for snapshots of the values of output channels, taken by the executor the next time
+/// the nodes of the channels are executed.
+#[derive(Debug)]
+pub struct ValueTaps {
+    requests: Mutex<Vec<ChannelIdentifier>>,
+    /// The allocations most recently output through the output channels of pointer types.
+    pointers: RwLock<HashMap<ChannelIdentifier, AllocationPointer>>,
+    sender: UnboundedSender<ValueSnapshot>,
+    /// Taken by the subscription of the application.
+    receiver: Mutex<Option<UnboundedReceiver<ValueSnapshot>>>,
+}
+
+impl Default for ValueTaps {
+    fn default() -> Self {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+
+        Self {
+            requests: Default::default(),
+            pointers: Default::default(),
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+}
+
+impl ValueTaps {
+    pub fn request(&self, channel: ChannelIdentifier) {
+        let mut requests = self.requests.lock().unwrap();
+
+        if !requests.contains(&channel) {
+            requests.push(channel);
+        }
+    }
+
+    /// Returns the receiver of the snapshots, which can only be taken once.
+    /// Forgets the pending requests and the recorded pointers, which refer to the channels of a
+    /// graph that is no longer executed.
+    pub fn clear(&self) {
+        self.requests.lock().unwrap().clear();
+        self.pointers.write().unwrap().clear();
+    }
+
+    pub fn take_receiver(&self) -> Option<UnboundedReceiver<ValueSnapshot>> {
+        self.receiver.lock().unwrap().take()
+    }
+
+    fn take_requests(&self, node: NodeIndex) -> Vec<ChannelIdentifier> {
+        let mut requests = self.requests.lock().unwrap();
+
+        if requests.is_empty() {
+            return Vec::new();
+        }
+
+        let (taken, kept) = requests.drain(..).partition(|channel| channel.node_index == node);
+
+        *requests = kept;
+        taken
+    }
+
+    fn fulfill(&self, snapshot: ValueSnapshot) {
+        // The application may have been closed already.
+        let _ = self.sender.unbounded_send(snapshot);
+    }
+
+    /// The allocation most recently output through the output channel of a pointer type, which
+    /// may have been freed since.
+    pub fn pointer(&self, channel: ChannelIdentifier) -> Option<AllocationPointer> {
+        self.pointers.read().unwrap().get(&channel).copied()
+    }
+
+    fn record_pointer(&self, channel: ChannelIdentifier, ptr: Option<AllocationPointer>) {
+        let recorded = self.pointers.read().unwrap().get(&channel).copied();
+
+        // Avoid taking the write lock every execution, as the pointers rarely change.
+        if recorded != ptr {
+            let mut pointers = self.pointers.write().unwrap();
+
+            match ptr {
+                Some(ptr) => pointers.insert(channel, ptr),
+                None => pointers.remove(&channel),
+            };
+        }
+    }
+}
+
+/// A texture owned by a node, which, unlike a swapchain frame, can be used as a copy source and
+/// destination.
+#[derive(Debug)]
+pub struct OwnedTexture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub size: Vec2<u32>,
+    pub format: wgpu::TextureFormat,
+}
+
+impl OwnedTexture {
+    pub fn usage() -> wgpu::TextureUsage {
+        wgpu::TextureUsage::COPY_SRC
+            | wgpu::TextureUsage::COPY_DST
+            | wgpu::TextureUsage::SAMPLED
+            | wgpu::TextureUsage::OUTPUT_ATTACHMENT
+    }
+
+    /// Creates the texture, if it is supported by the device.
+    pub fn try_new(
+        renderer: &Renderer,
+        size: Vec2<u32>,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self, TextureCapabilityError> {
+        renderer.capabilities.validate_texture(size, format, Self::usage())?;
+        Ok(Self::new(&renderer.device, size, format))
+    }
+
+    pub fn new(device: &wgpu::Device, size: Vec2<u32>, format: wgpu::TextureFormat) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d { width: size[0], height: size[1], depth: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: Self::usage(),
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self { texture, view, size, format }
+    }
+
+    pub fn extent(&self) -> wgpu::Extent3d {
+        wgpu::Extent3d { width: self.size[0], height: self.size[1], depth: 1 }
+    }
+
+    /// Records a render pass clearing the texture to transparent black.
+    pub fn clear(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: &self.view,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: true },
+            }],
+            depth_stencil_attachment: None,
+        });
+    }
+
+    /// Records a copy of the whole texture into `destination`, which must be of the same size and
+    /// format.
+    pub fn copy_to(&self, destination: &OwnedTexture, encoder: &mut wgpu::CommandEncoder) {
+        encoder.copy_texture_to_texture(
+            wgpu::TextureCopyView { texture: &self.texture, mip_level: 0, origin: wgpu::Origin3d::ZERO },
+            wgpu::TextureCopyView {
+                texture: &destination.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            self.extent(),
+        );
+    }
+
+    /// Uploads tightly packed texels covering the whole texture.
+    /// Only formats with 4 bytes per texel are supported.
+    pub fn write(&self, queue: &wgpu::Queue, data: &[u8]) {
+        queue.write_texture(
+            wgpu::TextureCopyView { texture: &self.texture, mip_level: 0, origin: wgpu::Origin3d::ZERO },
+            data,
+            wgpu::TextureDataLayout {
+                offset: 0,
+                bytes_per_row: self.size[0] * 4,
+                rows_per_image: self.size[1],
+            },
+            self.extent(),
+        );
+    }
+
+    /// Reads the contents of the texture back to the CPU, blocking until the copy is finished.
+    /// Only formats with 4 bytes per texel are supported.
+    pub fn read(&self, renderer: &Renderer) -> Vec<u8> {
+        let mut encoder =
+            renderer.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let mut readback = TextureReadback::record(&renderer.device, self, &mut encoder);
+
+        renderer.queue.submit(Some(encoder.finish()));
+        readback.start_mapping();
+        readback.try_read(&renderer.device, true).expect("Could not map the readback buffer.").unwrap()
+    }
+}
+
+type BufferMapping = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+/// A copy of a texture into a buffer, which can be read on the CPU once the GPU has finished the
+/// copy, without blocking the executor in the meantime.
+pub struct TextureReadback {
+    buffer: wgpu::Buffer,
+    size: Vec2<u32>,
+    /// Rows of the buffer are padded to [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`].
+    padded_bytes_per_row: u32,
+    /// Started once the copy has been submitted. Behind a mutex only for the readback to be
+    /// `Sync`, as it is only accessed through `&mut self`.
+    mapping: Option<Mutex<BufferMapping>>,
+}
+
+impl TextureReadback {
+    const BYTES_PER_TEXEL: u32 = 4;
+
+    /// Records a copy of the whole texture into a new buffer.
+    /// Only formats with 4 bytes per texel are supported.
+    pub fn record(device: &wgpu::Device, texture: &OwnedTexture, encoder: &mut wgpu::CommandEncoder) -> Self {
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (texture.size[0] * Self::BYTES_PER_TEXEL + align - 1) / align * align;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (padded_bytes_per_row * texture.size[1]) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView { texture: &texture.texture, mip_level: 0, origin: wgpu::Origin3d::ZERO },
+            wgpu::BufferCopyView {
+                buffer: &buffer,
+                layout: wgpu::TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: padded_bytes_per_row,
+                    rows_per_image: texture.size[1],
+                },
+            },
+            texture.extent(),
+        );
+
+        Self { buffer, size: texture.size, padded_bytes_per_row, mapping: None }
+    }
+
+    /// Requests the buffer to be mapped, must be called after the copy has been submitted.
+    pub fn start_mapping(&mut self) {
+        self.mapping = Some(Mutex::new(Box::pin(self.buffer.slice(..).map_async(wgpu::MapMode::Read))));
+    }
+
+    /// Returns the tightly packed texels, or `None` if the copy has not finished yet. Blocks until
+    /// it has, if `wait` is set.
+    pub fn try_read(
+        &mut self,
+        device: &wgpu::Device,
+        wait: bool,
+    ) -> Result<Option<Vec<u8>>, wgpu::BufferAsyncError> {
+        let mapping =
+            self.mapping.as_mut().expect("The mapping of the readback buffer has not been started.");
+        let mapping = mapping.get_mut().unwrap_or_else(|error| error.into_inner());
+
+        if wait {
+            device.poll(wgpu::Maintain::Wait);
+            futures::executor::block_on(mapping)?;
+        } else {
+            device.poll(wgpu::Maintain::Poll);
+
+            match mapping.now_or_never() {
+                Some(result) => result?,
+                None => return Ok(None),
+            }
+        }
+
+        let slice = self.buffer.slice(..);
+        let padded = slice.get_mapped_range();
+        let unpadded_bytes_per_row = (self.size[0] * Self::BYTES_PER_TEXEL) as usize;
+        let mut data = Vec::with_capacity(unpadded_bytes_per_row * self.size[1] as usize);
+
+        for row in padded.chunks(self.padded_bytes_per_row as usize) {
+            data.extend_from_slice(&row[..unpadded_bytes_per_row]);
+        }
+
+        drop(padded);
+        self.buffer.unmap();
+        self.mapping = None;
+        Ok(Some(data))
+    }
+}
+
+impl Debug for TextureReadback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextureReadback")
+            .field("size", &self.size)
+            .field("mapping_started", &self.mapping.is_some())
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub enum TextureAllocation {
+    TextureView(wgpu::TextureView),
+    SwapchainFrame(wgpu::SwapChainFrame),
+    Texture(OwnedTexture),
+    /// A texture also held by the node that created it, which reuses it once it is no longer
+    /// shared.
+    SharedTexture(Arc<OwnedTexture>),
+}
+
+impl TextureAllocation {
+    /// Returns the texture, if it can be copied from.
+    pub fn owned_texture(&self) -> Option<&OwnedTexture> {
+        match self {
+            TextureAllocation::Texture(texture) => Some(texture),
+            TextureAllocation::SharedTexture(texture) => Some(texture),
+            TextureAllocation::TextureView(_) | TextureAllocation::SwapchainFrame(_) => None,
+        }
+    }
+}
+
+impl Deref for TextureAllocation {
+    type Target = wgpu::TextureView;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            TextureAllocation::TextureView(texture_view) => texture_view,
+            TextureAllocation::SwapchainFrame(swapchain_frame) => &swapchain_frame.output.view,
+            TextureAllocation::Texture(texture) => &texture.view,
+            TextureAllocation::SharedTexture(texture) => &texture.view,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MainThreadTaskSubmitError {
+    /// The queue of pending main thread tasks is full.
+    Full,
+    /// The main thread is no longer processing tasks.
+    Disconnected,
+}
+
+impl Display for MainThreadTaskSubmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MainThreadTaskSubmitError::Full => write!(f, "The main thread task queue is full."),
+            MainThreadTaskSubmitError::Disconnected => write!(f, "The main thread task queue is closed."),
+        }
+    }
+}
+
+/// The node a main thread task has been submitted by, along with the scope of the
+/// [`ApplicationContext`] executing it, as node indices are only unique within a graph.
+type MainThreadTaskKey = (usize, NodeIndex);
+
+/// The main thread tasks waiting to be processed, shared by the [`ApplicationContext`]s and the
+/// main thread. At most one task is kept per node, a task replaces the pending task of its node.
+pub struct MainThreadTasks {
+    pending: Mutex<VecDeque<(MainThreadTaskKey, Box<MainThreadTask>)>>,
+    /// Whether the submitted tasks are going to be processed.
+    open: bool,
+    /// The tasks rejected since the rejections were last logged, and when that happened.
+    rejections: Mutex<(usize, Option<Instant>)>,
+    /// The total number of tasks that could not be submitted.
+    rejected: AtomicUsize,
+}
+
+impl MainThreadTasks {
+    /// The maximum number of nodes with tasks waiting to be processed.
+    pub const CAPACITY: usize = 256;
+    /// The minimum time between logging rejected tasks, so that a node retrying its submission
+    /// every execution does not flood the log.
+    const REJECTION_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+    fn new(open: bool) -> Self {
+        Self {
+            pending: Mutex::new(VecDeque::new()),
+            open,
+            rejections: Mutex::new((0, None)),
+            rejected: AtomicUsize::new(0),
+        }
+    }
+
+    fn submit(
+        &self,
+        key: MainThreadTaskKey,
+        task: Box<MainThreadTask>,
+    ) -> Result<(), MainThreadTaskSubmitError> {
+        if !self.open {
+            return Err(MainThreadTaskSubmitError::Disconnected);
+        }
+
+        let mut pending = self.pending.lock().unwrap();
+
+        if let Some((_, pending_task)) = pending.iter_mut().find(|(pending_key, _)| *pending_key == key) {
+            *pending_task = task;
+        } else if pending.len() < Self::CAPACITY {
+            pending.push_back((key, task));
+        } else {
+            return Err(MainThreadTaskSubmitError::Full);
+        }
+
+        Ok(())
+    }
+
+    /// Takes at most `max` tasks to be run, in the order their nodes have submitted them.
+    pub fn take(&self, max: usize) -> Vec<Box<MainThreadTask>> {
+        let mut pending = self.pending.lock().unwrap();
+        let count = max.min(pending.len());
+
+        pending.drain(..count).map(|(_, task)| task).collect()
+    }
+
+    /// Logs the rejection, unless a rejection has been logged recently, in which case it is
+    /// counted towards the next one logged.
+    fn reject(&self, node_index: NodeIndex, error: MainThreadTaskSubmitError) {
+        let total = self.rejected.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut rejections = self.rejections.lock().unwrap();
+        let (unlogged, logged_at) = &mut *rejections;
+        let now = Instant::now();
+
+        *unlogged += 1;
+
+        if logged_at.map_or(false, |logged_at| now - logged_at < Self::REJECTION_LOG_INTERVAL) {
+            return;
+        }
+
+        warn!(
+            Category::Executor,
+            "Could not submit a main thread task of node #{}: {} ({} tasks rejected since last reported, {} \
+             in total)",
+            node_index.index(),
+            error,
+            unlogged,
+            total
+        );
+        *unlogged = 0;
+        *logged_at = Some(now);
+    }
+
+    pub fn rejected(&self) -> usize {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
+
+/// Timing information of the current execution pass, shared by all nodes executed during it, so
+/// that values computed within the same pass are consistent.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameInfo {
+    /// Increases by one with every pass executed.
+    pub pass_index: u64,
+    /// The moment the executor was started at.
+    pub startup: Instant,
+    /// The moment the current pass started at.
+    pub pass_start: Instant,
+    /// The time it took to execute the previous pass, excluding the time spent waiting for the
+    /// next pass.
+    pub previous_pass_duration: Option<Duration>,
+    /// The period passes are executed at, if limited.
+    pub target_period: Option<Duration>,
+}
+
+impl FrameInfo {
+    pub fn new(target_period: Option<Duration>) -> Self {
+        let now = Instant::now();
 
-impl GraphValidationErrors {
-    pub fn get_related_errors(
-        &self,
-        element: impl Into<GraphValidationErrorAffectedElement>,
-    ) -> &[Rc<GraphValidationError>] {
-        self.get(&element.into()).map(|vec| &vec[..]).unwrap_or(&[])
+        Self { pass_index: 0, startup: now, pass_start: now, previous_pass_duration: None, target_period }
     }
 
-    pub fn is_invalid(&self, element: impl Into<GraphValidationErrorAffectedElement>) -> bool {
-        self.contains_key(&element.into())
+    /// The time elapsed between the start of the executor and the start of the current pass.
+    pub fn elapsed(&self) -> Duration {
+        self.pass_start - self.startup
     }
 }
 
-impl From<Vec<GraphValidationError>> for GraphValidationErrors {
-    fn from(vec: Vec<GraphValidationError>) -> Self {
-        let mut result = Self::default();
+/// The sizes of the surfaces of open windows, keyed by the nodes presenting to them.
+#[derive(Debug, Default)]
+pub struct WindowSurfaces {
+    sizes: RwLock<HashMap<NodeIndex, Vec2<u32>>>,
+}
 
-        for error in vec {
-            let error = Rc::new(error);
-            error.collect(&mut |element| match result.entry(element) {
-                Entry::Vacant(entry) => {
-                    entry.insert(vec![error.clone()]);
-                }
-                Entry::Occupied(mut entry) => {
-                    entry.get_mut().push(error.clone());
-                }
-            })
-        }
+impl WindowSurfaces {
+    /// Registers the surface of a window, which is unregistered when the returned handle is dropped.
+    pub fn register(self: &Arc<Self>, node: NodeIndex, size: Vec2<u32>) -> WindowSurfaceHandle {
+        self.sizes.write().unwrap().insert(node, size);
 
-        result
+        WindowSurfaceHandle { surfaces: self.clone(), node }
+    }
+
+    pub fn get(&self, node: NodeIndex) -> Option<Vec2<u32>> {
+        self.sizes.read().unwrap().get(&node).copied()
+    }
+
+    pub fn get_all(&self) -> Vec<(NodeIndex, Vec2<u32>)> {
+        self.sizes.read().unwrap().iter().map(|(node, size)| (*node, *size)).collect()
     }
 }
 
-impl Deref for GraphValidationErrors {
-    type Target = HashMap<GraphValidationErrorAffectedElement, Vec<Rc<GraphValidationError>>>;
+/// Keeps the surface of a window registered in [`WindowSurfaces`].
+#[derive(Debug)]
+pub struct WindowSurfaceHandle {
+    surfaces: Arc<WindowSurfaces>,
+    node: NodeIndex,
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl WindowSurfaceHandle {
+    pub fn set_size(&self, size: Vec2<u32>) {
+        self.surfaces.sizes.write().unwrap().insert(self.node, size);
     }
 }
 
-impl DerefMut for GraphValidationErrors {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl Drop for WindowSurfaceHandle {
+    fn drop(&mut self) {
+        self.surfaces.sizes.write().unwrap().remove(&self.node);
     }
 }
 
-pub struct ExecutionGraph {
-    pub graph: Graph,
-    pub active_schedule: Arc<ArcSwapOption<Schedule>>,
+/// The inner sizes of the windows opened by nodes, as reported by the resize events forwarded by
+/// the event loop of the main thread, see [`WindowSizes::resize`]. Not all platforms allow querying
+/// windows for their size from other threads.
+#[derive(Debug, Default)]
+pub struct WindowSizes {
+    sizes: RwLock<HashMap<WindowId, Vec2<u32>>>,
 }
 
-impl ExecutionGraph {
-    pub fn get_connections(&self) -> Vec<Connection> {
-        let mut connections = Vec::with_capacity(self.graph.edge_count());
+impl WindowSizes {
+    /// Tracks the size of the window until the returned handle is dropped. Must be called on the
+    /// main thread.
+    pub fn track(self: &Arc<Self>, window: &Window) -> WindowSizeHandle {
+        let size = window.inner_size();
 
-        connections.extend(self.graph.edge_indices().map(|edge_index| {
-            let edge_data = &self.graph[edge_index];
-            let (index_from, index_to) = self.graph.edge_endpoints(edge_index).unwrap();
-            let undirected_channel_id_from = edge_data.endpoint_from.into_undirected_identifier(index_from);
-            let undirected_channel_id_to = edge_data.endpoint_to.into_undirected_identifier(index_to);
-            Connection([undirected_channel_id_from, undirected_channel_id_to])
-        }));
+        self.sizes.write().unwrap().insert(window.id(), Vec2::new(size.width, size.height));
 
-        connections
+        WindowSizeHandle { sizes: self.clone(), window_id: window.id() }
     }
 
-    pub fn check_graph_validity(&self) -> Result<Vec<NodeIndex>, Vec<GraphValidationError>> {
-        let mut errors = Vec::new();
+    /// Records the size reported by a resize event. Sizes of windows which are not tracked are
+    /// ignored.
+    pub fn resize(&self, window_id: WindowId, size: Vec2<u32>) {
+        if let Some(current_size) = self.sizes.write().unwrap().get_mut(&window_id) {
+            *current_size = size;
+        }
+    }
 
-        // Ensure that nodes have either no input channels connected and thus are unused or that
-        // all input channels are connected and thus are complete.
-        for node_index in self.node_indices() {
-            let node = self.node_weight(node_index);
-            let node = node.as_ref().unwrap();
-            let mut input_channels = node
-                .configuration
-                .channels(ChannelDirection::In)
-                .map(|channel_ref| channel_ref.edge_endpoint)
-                .collect::<HashSet<EdgeEndpoint>>();
-            let mut used = false;
+    pub fn get(&self, window_id: WindowId) -> Option<Vec2<u32>> {
+        self.sizes.read().unwrap().get(&window_id).copied()
+    }
 
-            for edge_ref in self.edges_directed(node_index, Direction::Incoming) {
-                let edge = edge_ref.weight();
+    /// Whether the window has been opened by a node, rather than being the window of the editor.
+    pub fn is_tracked(&self, window_id: WindowId) -> bool {
+        self.sizes.read().unwrap().contains_key(&window_id)
+    }
+}
 
-                input_channels.remove(&edge.endpoint_to);
-                used = true;
-            }
+/// Keeps the size of a window tracked in [`WindowSizes`].
+#[derive(Debug)]
+pub struct WindowSizeHandle {
+    sizes: Arc<WindowSizes>,
+    window_id: WindowId,
+}
 
-            if used {
-                for input_channel in input_channels {
-                    errors.push(GraphValidationError::IncompleteInput(
-                        input_channel.into_undirected_identifier(node_index),
-                    ));
-                }
-            }
-        }
+impl Drop for WindowSizeHandle {
+    fn drop(&mut self) {
+        self.sizes.sizes.write().unwrap().remove(&self.window_id);
+    }
+}
 
-        // Check the validity of all connections.
-        let connections = self.get_connections();
+pub struct ApplicationContext {
+    main_thread_tasks: Arc<MainThreadTasks>,
+    /// Tells the main thread tasks of the nodes of this context apart from the ones of other
+    /// graphs, see [`ApplicationContext::scoped`].
+    scope: usize,
+    /// Wakes the event loop to run the submitted main thread tasks.
+    pub main_thread_waker: Arc<MainThreadWaker>,
+    /// The renderer, once it has been published to the `shared_renderer`, see
+    /// [`ApplicationContext::renderer`].
+    renderer: Option<Renderer>,
+    /// Where the renderer is published once the GPU has been acquired, which is done in the
+    /// background at startup.
+    pub shared_renderer: Arc<ArcSwapOption<Renderer>>,
+    pub config: Arc<SharedConfig>,
+    pub node_errors: Arc<NodeErrors>,
+    pub value_taps: Arc<ValueTaps>,
+    pub window_surfaces: Arc<WindowSurfaces>,
+    pub window_sizes: Arc<WindowSizes>,
+    pub watchdog: Arc<ExecutionWatchdog>,
+    pub background_tasks: Arc<BackgroundTasks>,
+    /// The settings applied to the executor thread, once it has been spawned.
+    pub executor_thread: Arc<ArcSwapOption<AppliedThreadSettings>>,
+    /// Updated by the executor at the start of every pass.
+    pub frame_info: FrameInfo,
+}
 
-        for edge_index in self.edge_indices() {
-            let edge = &self[edge_index];
-            let (node_index_from, node_index_to) = self.edge_endpoints(edge_index).unwrap();
-            let connection = Connection([
-                edge.endpoint_from.into_undirected_identifier(node_index_from),
-                edge.endpoint_to.into_undirected_identifier(node_index_to),
-            ]);
+impl ApplicationContext {
+    /// The renderer is published to the `shared_renderer` later, once the GPU has been acquired.
+    /// Its error handler should be installed before, see [`Renderer::install_error_handler`].
+    pub fn new(config: Arc<SharedConfig>) -> (Self, Arc<MainThreadTasks>) {
+        let main_thread_tasks = Arc::new(MainThreadTasks::new(true));
+        let context = Self::with_main_thread_tasks(None, config, main_thread_tasks.clone());
 
-            let is_aliased = |channel: ChannelIdentifier| {
-                connections.iter().filter(|connection| connection.from() == channel).count() > 1
-            };
-            let get_channel = |channel: ChannelIdentifier| {
-                let node = &self[channel.node_index];
+        (context, main_thread_tasks)
+    }
 
-                node.configuration.channel(channel.channel_direction, channel.into())
-            };
+    /// A context for executing graphs on the current thread, without the executor, such as by the
+    /// [self-check](crate::self_check). Unlike with [`ApplicationContext::new`], GPU errors are not
+    /// attributed to nodes, and main thread tasks are rejected. Without a renderer, only the nodes
+    /// not using the GPU are executed.
+    pub fn headless(renderer: Option<Renderer>, config: Arc<SharedConfig>) -> Self {
+        Self::with_main_thread_tasks(renderer, config, Arc::new(MainThreadTasks::new(false)))
+    }
 
-            if let Err(error) = connection.check_validity(&is_aliased, &get_channel) {
-                errors.push(GraphValidationError::InvalidConnection { connection, error });
-            }
+    fn with_main_thread_tasks(
+        renderer: Option<Renderer>,
+        config: Arc<SharedConfig>,
+        main_thread_tasks: Arc<MainThreadTasks>,
+    ) -> Self {
+        Self {
+            main_thread_tasks,
+            scope: NEXT_CONTEXT_SCOPE.fetch_add(1, Ordering::Relaxed),
+            main_thread_waker: Default::default(),
+            shared_renderer: Arc::new(ArcSwapOption::from(renderer.clone().map(Arc::new))),
+            renderer,
+            config,
+            node_errors: Default::default(),
+            value_taps: Default::default(),
+            window_surfaces: Default::default(),
+            window_sizes: Default::default(),
+            watchdog: Default::default(),
+            background_tasks: Default::default(),
+            executor_thread: Default::default(),
+            frame_info: FrameInfo::new(None),
         }
+    }
 
-        // Topologically sort the graph.
-        let sorted_nodes = petgraph::algo::toposort(&self.graph, None).map_err(|_cycle| {
-            petgraph::algo::tarjan_scc(&self.graph).into_iter().filter_map(|scc| {
-                if scc.len() <= 1 {
-                    return None;
-                }
+    /// A context for executing another graph alongside the graph this context is used for, see
+    /// [`PinnedSchedules`]. The node errors, value taps, window surfaces and the watchdog are
+    /// keyed by node indices, so that they are not shared with the returned context.
+    pub fn scoped(&self) -> Self {
+        Self {
+            main_thread_tasks: self.main_thread_tasks.clone(),
+            scope: NEXT_CONTEXT_SCOPE.fetch_add(1, Ordering::Relaxed),
+            main_thread_waker: self.main_thread_waker.clone(),
+            renderer: self.renderer.clone(),
+            shared_renderer: self.shared_renderer.clone(),
+            config: self.config.clone(),
+            node_errors: Default::default(),
+            value_taps: Default::default(),
+            window_surfaces: Default::default(),
+            window_sizes: self.window_sizes.clone(),
+            watchdog: Default::default(),
+            background_tasks: self.background_tasks.clone(),
+            executor_thread: self.executor_thread.clone(),
+            frame_info: self.frame_info,
+        }
+    }
 
-                let node_set = scc.iter().copied().collect::<HashSet<_>>();
-                let connections = self
-                    .graph
-                    .edge_indices()
-                    .filter_map(|edge_index| {
-                        let (node_from, node_to) = self.graph.edge_endpoints(edge_index).unwrap();
-
-                        if node_set.contains(&node_from) && node_set.contains(&node_to) {
-                            let edge = &self.graph[edge_index];
-
-                            Some(Connection([
-                                edge.endpoint_from.into_undirected_identifier(node_from),
-                                edge.endpoint_to.into_undirected_identifier(node_to),
-                            ]))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>();
+    /// Submits a task to be executed on the main thread, without blocking. Replaces the task
+    /// previously submitted by the node, if it has not been run yet.
+    /// Fails if too many other nodes have tasks pending.
+    pub fn submit_main_thread_task(
+        &self,
+        node_index: NodeIndex,
+        task: Box<MainThreadTask>,
+    ) -> Result<(), MainThreadTaskSubmitError> {
+        match self.main_thread_tasks.submit((self.scope, node_index), task) {
+            Ok(()) => {
+                self.main_thread_waker.wake();
+                Ok(())
+            }
+            Err(error) => {
+                self.main_thread_tasks.reject(node_index, error);
+                Err(error)
+            }
+        }
+    }
 
-                Some(GraphValidationError::StronglyConnectedComponent { nodes: scc, connections })
-            })
-        });
+    /// The total number of main thread tasks that could not be submitted, by any context.
+    pub fn rejected_main_thread_tasks(&self) -> usize {
+        self.main_thread_tasks.rejected()
+    }
 
-        if sorted_nodes.is_err() {
-            errors.extend(sorted_nodes.as_ref().unwrap_err().clone());
-        }
+    /// The renderer, or `None` while the GPU is being acquired. Nodes using the GPU report
+    /// [`GPU_INITIALIZING`] meantime, while the other nodes are executed as usual, so that graphs
+    /// not using the GPU never wait for it.
+    pub fn renderer(&self) -> Option<&Renderer> {
+        self.renderer.as_ref()
+    }
 
-        if errors.is_empty() {
-            Ok(sorted_nodes.unwrap())
-        } else {
-            Err(errors)
+    /// Picks up the renderer once it has been published, called before every pass, so that it does
+    /// not change during one.
+    fn update_renderer(&mut self) {
+        if self.renderer.is_none() {
+            self.renderer = self.shared_renderer.load_full().map(|renderer| (*renderer).clone());
         }
     }
+}
 
-    fn create_schedule(&mut self) -> Result<Schedule, Vec<GraphValidationError>> {
-        let ordered_node_indices = self.check_graph_validity()?;
-        let node_index_map: HashMap<NodeIndex, usize> = ordered_node_indices
-            .iter()
-            .enumerate()
-            .map(|(enumeration_index, node_index)| (*node_index, enumeration_index))
-            .collect();
+/// The schedules of the graphs of background documents, which are kept running by the executor
+/// after the schedule of the focused graph in every pass.
+pub struct PinnedSchedules {
+    schedules: ArcSwap<Vec<Arc<ArcSwapOption<Schedule>>>>,
+}
 
-        let mut tasks = Vec::<Option<Task>>::with_capacity(ordered_node_indices.len());
+impl Default for PinnedSchedules {
+    fn default() -> Self {
+        Self { schedules: ArcSwap::from_pointee(Vec::new()) }
+    }
+}
 
-        for node_index in ordered_node_indices {
-            let node = self.node_weight(node_index);
-            let node = node.as_ref().unwrap();
-            let optional_task = 'optional_task: loop {
-                let mut borrows: Vec<Option<TaskInput>> =
-                    vec![None; node.configuration.channels_by_shared_reference.len()];
-                let mut mutable_borrows: Vec<Option<TaskInput>> =
-                    vec![None; node.configuration.channels_by_mutable_reference.len()];
-                let mut inputs: Vec<Option<TaskInput>> =
-                    vec![None; node.configuration.input_channels_by_value.len()];
-                let mut used = borrows.is_empty() && mutable_borrows.is_empty() && inputs.is_empty();
+impl PinnedSchedules {
+    /// Replaces the schedule slots of the pinned graphs, see [`ExecutionGraph::active_schedule`].
+    pub fn set(&self, schedules: Vec<Arc<ArcSwapOption<Schedule>>>) {
+        self.schedules.store(Arc::new(schedules));
+    }
 
-                for edge_ref in self.edges_directed(node_index, Direction::Incoming) {
-                    let edge = edge_ref.weight();
-                    let global_input_channel_index =
-                        node.configuration.get_global_channel_index(edge.endpoint_to);
-                    let immediate_source_task_index = *node_index_map.get(&edge_ref.source()).unwrap();
+    pub fn load(&self) -> Arc<Vec<Arc<ArcSwapOption<Schedule>>>> {
+        self.schedules.load_full()
+    }
+}
 
-                    // If the input is a reference, transitively derive the value storage.
-                    let task_input = if edge.endpoint_from.pass_by == ChannelPassBy::Value {
-                        TaskInput {
-                            task_index: immediate_source_task_index,
-                            output_value_channel_index: edge.endpoint_from.channel_index,
-                        }
-                    } else {
-                        let source_task =
-                            if let Some(source_task) = tasks[immediate_source_task_index].as_mut() {
-                                source_task
-                            } else {
-                                break 'optional_task None;
-                            };
+pub struct GraphExecutor {
+    application_context: ApplicationContext,
+    active_schedule: Arc<ArcSwapOption<Schedule>>,
+    executing_generation: Arc<ExecutingGeneration>,
+    pinned_schedules: Arc<PinnedSchedules>,
+    /// Whether the outputs of source nodes are being recorded or replayed.
+    recording: Option<RecordingMode>,
+    /// Switches [`GraphExecutor::recording`] on behalf of the UI.
+    recording_control: Arc<RecordingControl>,
+}
 
-                        let source_node = self.node_weight(source_task.node_index).unwrap();
-                        let global_output_channel_index =
-                            source_node.configuration.get_global_channel_index(edge.endpoint_from);
+impl GraphExecutor {
+    pub fn new(
+        application_context: ApplicationContext,
+        active_schedule: Arc<ArcSwapOption<Schedule>>,
+        executing_generation: Arc<ExecutingGeneration>,
+        pinned_schedules: Arc<PinnedSchedules>,
+        recording_control: Arc<RecordingControl>,
+    ) -> Self {
+        Self {
+            active_schedule,
+            executing_generation,
+            pinned_schedules,
+            application_context,
+            recording: None,
+            recording_control,
+        }
+    }
 
-                        let transitive_task_inputs = match edge.endpoint_from.pass_by {
-                            ChannelPassBy::SharedReference => &mut source_task.borrows,
-                            ChannelPassBy::MutableReference => &mut source_task.mutable_borrows,
-                            ChannelPassBy::Value => &mut source_task.inputs,
-                        };
+    /// Spawns the executor thread with the priority and the CPU affinity of the current settings,
+    /// which are only read here, so that changes to them take effect once the executor is spawned
+    /// again.
+    pub fn spawn(
+        application_context: ApplicationContext,
+        active_schedule: Arc<ArcSwapOption<Schedule>>,
+        executing_generation: Arc<ExecutingGeneration>,
+        pinned_schedules: Arc<PinnedSchedules>,
+        recording_control: Arc<RecordingControl>,
+    ) -> std::thread::JoinHandle<()> {
+        let config = application_context.config.get();
+
+        ThreadSettings::new("dvsynth-executor")
+            .with_priority(config.executor_priority)
+            .with_affinity_mask(config.executor_affinity_mask())
+            .spawn(move |applied_settings| {
+                application_context.executor_thread.store(Some(Arc::new(applied_settings)));
+                Self::new(
+                    application_context,
+                    active_schedule,
+                    executing_generation,
+                    pinned_schedules,
+                    recording_control,
+                )
+                .run()
+            })
+    }
 
-                        transitive_task_inputs[global_output_channel_index].clone()
-                    };
+    fn begin_pass(&mut self, generation: usize, pass_index: u64) {
+        match &mut self.recording {
+            Some(RecordingMode::Record(recorder)) => recorder.begin_pass(generation, pass_index),
+            Some(RecordingMode::Replay(replayer)) => {
+                if !replayer.begin_pass() {
+                    info!(Category::Executor, "Replay finished, continuing with live execution.");
+                    self.recording = None;
+                }
+            }
+            None => (),
+        }
+    }
 
-                    let task_inputs = match edge.endpoint_to.pass_by {
-                        ChannelPassBy::SharedReference => &mut borrows,
-                        ChannelPassBy::MutableReference => &mut mutable_borrows,
-                        ChannelPassBy::Value => &mut inputs,
-                    };
+    fn end_pass(&mut self) {
+        if let Some(RecordingMode::Record(recorder)) = &mut self.recording {
+            if let Err(error) = recorder.end_pass() {
+                error!(Category::Executor, "Stopping the recording: {}", error);
+                self.recording = None;
+            }
+        }
+    }
 
-                    task_inputs[global_input_channel_index] = Some(task_input);
-                    used = true;
+    /// Executes the schedule with the execution prepared for its graph, which is prepared anew when
+    /// the schedule has changed, carrying over the states of the nodes.
+    fn execute_schedule(
+        schedule: &Schedule,
+        prepared_executions: &mut HashMap<GraphId, PreparedExecution>,
+        context: &mut ApplicationContext,
+        recording: &mut Option<RecordingMode>,
+    ) {
+        let prepared_execution = match prepared_executions.remove(&schedule.graph_id) {
+            Some(prepared_execution) if prepared_execution.generation == schedule.generation => {
+                // The refcounters are shared by all graphs, prepare them for this one again.
+                if Allocator::get().prepared_generation() != schedule.generation {
+                    Allocator::get().prepare_for_schedule(schedule);
                 }
 
-                break 'optional_task if used {
-                    let borrows = borrows
-                        .into_iter()
-                        .map(|value| value.expect("An input channel is missing a value."))
-                        .collect::<Vec<_>>()
-                        .into_boxed_slice();
-                    let mutable_borrows = mutable_borrows
-                        .into_iter()
-                        .map(|value| value.expect("An input channel is missing a value."))
-                        .collect::<Vec<_>>()
-                        .into_boxed_slice();
-                    let inputs = inputs
-                        .into_iter()
-                        .map(|value| value.expect("An input channel is missing a value."))
-                        .collect::<Vec<_>>()
-                        .into_boxed_slice();
+                prepared_execution
+            }
+            previous => PreparedExecution::from(schedule, context, previous),
+        };
 
-                    Some(Task {
-                        node_index,
-                        configuration: node.configuration.clone(),
-                        behaviour: node.behaviour.clone(),
-                        borrows,
-                        mutable_borrows,
-                        inputs,
-                    })
-                } else {
-                    None
-                };
-            };
+        prepared_executions
+            .entry(schedule.graph_id)
+            .or_insert(prepared_execution)
+            .execute(schedule, context, recording);
+    }
 
-            tasks.push(optional_task);
-        }
+    pub fn run(mut self) {
+        let mut prepared_executions: HashMap<GraphId, PreparedExecution> = HashMap::new();
+        let mut pinned_contexts: HashMap<GraphId, ApplicationContext> = HashMap::new();
+        let mut pass_index: u64 = 0;
+        let mut config_revision = self.application_context.config.revision();
+        let mut target_period = self.application_context.config.get().executor_target_period();
+        let mut previous_pass_duration = None;
 
-        Ok(Schedule {
-            generation: self
-                .active_schedule
-                .load()
-                .as_ref()
-                .map(|schedule| schedule.generation.wrapping_add(1))
-                .unwrap_or(0),
-            tasks: tasks.into_boxed_slice(),
-        })
-    }
+        self.application_context.frame_info = FrameInfo::new(target_period);
 
-    pub fn update_schedule(&mut self) -> Result<(), Vec<GraphValidationError>> {
-        match self.create_schedule() {
-            Ok(schedule) => {
-                self.active_schedule.store(Some(Arc::new(schedule)));
-                Ok(())
-            }
-            Err(e) => {
-                self.active_schedule.store(None);
-                Err(e)
+        loop {
+            let pass_start = Instant::now();
+
+            if self.application_context.config.revision() != config_revision {
+                config_revision = self.application_context.config.revision();
+                target_period = self.application_context.config.get().executor_target_period();
             }
-        }
-    }
-}
 
-impl From<Graph> for ExecutionGraph {
-    fn from(graph: Graph) -> Self {
-        Self { graph, active_schedule: Default::default() }
-    }
-}
+            self.application_context.frame_info = FrameInfo {
+                pass_index,
+                pass_start,
+                previous_pass_duration,
+                target_period,
+                ..self.application_context.frame_info
+            };
 
-impl Deref for ExecutionGraph {
-    type Target = Graph;
+            if let Some(active_schedule) = self.active_schedule.load().as_ref() {
+                let mut executed_graphs = HashSet::new();
+
+                self.executing_generation.set(Some(active_schedule.generation));
+                self.recording_control.apply_request(&mut self.recording);
+                self.begin_pass(active_schedule.generation, pass_index);
+                Self::execute_schedule(
+                    active_schedule,
+                    &mut prepared_executions,
+                    &mut self.application_context,
+                    &mut self.recording,
+                );
+                self.end_pass();
+                self.recording_control.update_status(&self.recording);
+                executed_graphs.insert(active_schedule.graph_id);
+
+                // Only the focused graph is recorded, as the recording is replayed into it.
+                for pinned_schedule in self.pinned_schedules.load().iter() {
+                    if let Some(pinned_schedule) = pinned_schedule.load().as_ref() {
+                        if !executed_graphs.insert(pinned_schedule.graph_id) {
+                            continue;
+                        }
 
-    fn deref(&self) -> &Self::Target {
-        &self.graph
-    }
-}
+                        let context = pinned_contexts
+                            .entry(pinned_schedule.graph_id)
+                            .or_insert_with(|| self.application_context.scoped());
 
-impl DerefMut for ExecutionGraph {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.graph
-    }
-}
+                        context.frame_info = self.application_context.frame_info;
+                        Self::execute_schedule(pinned_schedule, &mut prepared_executions, context, &mut None);
+                    }
+                }
 
-pub struct Renderer {
-    pub instance: Arc<wgpu::Instance>,
-    pub device: Arc<wgpu::Device>,
-    pub queue: Arc<wgpu::Queue>,
-}
+                // Release the graphs that are neither focused nor pinned anymore.
+                let released_graphs = prepared_executions
+                    .keys()
+                    .filter(|graph_id| !executed_graphs.contains(graph_id))
+                    .copied()
+                    .collect::<Vec<_>>();
 
-impl Renderer {
-    pub fn new(settings: &Settings<ApplicationFlags>) -> Self {
-        let instance = Arc::new(wgpu::Instance::new(wgpu::BackendBit::PRIMARY));
-        let (device, queue) = {
-            let adapter =
-                futures::executor::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: if !settings.antialiasing {
-                        wgpu::PowerPreference::Default
-                    } else {
-                        wgpu::PowerPreference::HighPerformance
-                    },
-                    compatible_surface: None,
-                }))
-                .expect("No wgpu compatible adapter available.");
+                for graph_id in released_graphs {
+                    prepared_executions.remove(&graph_id).unwrap().release();
+                }
 
-            let (device, queue) = futures::executor::block_on(adapter.request_device(
-                &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits { max_bind_groups: 2, ..wgpu::Limits::default() },
-                    shader_validation: false,
-                },
-                None,
-            ))
-            .expect("No wgpu compatible device available.");
+                pinned_contexts.retain(|graph_id, _| executed_graphs.contains(graph_id));
+                startup::reach(startup::Milestone::FirstExecution);
+                pass_index += 1;
+                previous_pass_duration = Some(pass_start.elapsed());
+            } else {
+                self.executing_generation.set(None);
+            }
+
+            if let Some(target_period) = target_period {
+                if let Some(remaining) = target_period.checked_sub(pass_start.elapsed()) {
+                    thread::sleep(remaining);
+                }
+            }
+        }
+    }
+}
 
-            (Arc::new(device), Arc::new(queue))
-        };
+/// The next identifier to be assigned by [`NodeId::generate`].
+static NEXT_NODE_ID: AtomicU64 = AtomicU64::new(0);
+/// The next identifier to be assigned by [`GraphId::generate`].
+static NEXT_GRAPH_ID: AtomicU64 = AtomicU64::new(0);
+/// The generation of the next schedule constructed, of any graph.
+static NEXT_SCHEDULE_GENERATION: AtomicUsize = AtomicUsize::new(0);
+/// The scope of the next [`ApplicationContext`] created.
+static NEXT_CONTEXT_SCOPE: AtomicUsize = AtomicUsize::new(0);
+
+/// Identifies an [`ExecutionGraph`] within the running application, to tell the schedules of
+/// different graphs apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphId(u64);
 
-        Self { instance, device, queue }
+impl GraphId {
+    pub fn generate() -> Self {
+        GraphId(NEXT_GRAPH_ID.fetch_add(1, Ordering::Relaxed))
     }
 }
 
-#[derive(Debug)]
-pub enum TextureAllocation {
-    TextureView(wgpu::TextureView),
-    SwapchainFrame(wgpu::SwapChainFrame),
-}
+/// Identifies a node across saves of the graph, unlike its [`NodeIndex`], which may be reused by
+/// other nodes once the node is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NodeId(pub u64);
 
-impl Deref for TextureAllocation {
-    type Target = wgpu::TextureView;
+impl NodeId {
+    /// An identifier distinct from all identifiers generated or reserved so far.
+    pub fn generate() -> Self {
+        NodeId(NEXT_NODE_ID.fetch_add(1, Ordering::Relaxed))
+    }
 
-    fn deref(&self) -> &Self::Target {
-        match self {
-            TextureAllocation::TextureView(texture_view) => texture_view,
-            TextureAllocation::SwapchainFrame(swapchain_frame) => &swapchain_frame.output.view,
-        }
+    /// Marks the identifier of a loaded node as used, so that it is not generated for new nodes.
+    pub fn reserve(self) -> Self {
+        NEXT_NODE_ID.fetch_max(self.0.saturating_add(1), Ordering::Relaxed);
+        self
     }
 }
 
-pub struct ApplicationContext {
-    pub main_thread_task_sender: Sender<Box<MainThreadTask>>,
-    pub renderer: Renderer,
+/// The order the channels of a node are displayed in, by direction, as a permutation of their
+/// global indices, see [`NodeConfiguration::get_global_channel_index`]. Reordering the channels
+/// does not change the indices connections refer to them by. An empty permutation is the natural
+/// order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelOrder {
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
 }
 
-impl ApplicationContext {
-    pub fn new(renderer: Renderer) -> (Self, Receiver<Box<MainThreadTask>>) {
-        let (main_thread_task_sender, main_thread_task_receiver) = mpsc::channel();
-        let context = Self { main_thread_task_sender, renderer };
-        (context, main_thread_task_receiver)
+impl ChannelOrder {
+    pub fn is_natural(&self) -> bool {
+        self.inputs.is_empty() && self.outputs.is_empty()
     }
 
-    pub fn from_settings(settings: &Settings<ApplicationFlags>) -> (Self, Receiver<Box<MainThreadTask>>) {
-        Self::new(Renderer::new(settings))
+    fn get_mut(&mut self, direction: ChannelDirection) -> &mut Vec<usize> {
+        match direction {
+            ChannelDirection::In => &mut self.inputs,
+            ChannelDirection::Out => &mut self.outputs,
+        }
     }
-}
 
-pub struct GraphExecutor {
-    application_context: ApplicationContext,
-    active_schedule: Arc<ArcSwapOption<Schedule>>,
-}
+    /// The global indices of the `channel_count` channels of the direction, in the order they are
+    /// displayed in. Indices out of range and repeated indices are skipped, channels missing from
+    /// the permutation are displayed last, in their natural order.
+    pub fn resolve(&self, direction: ChannelDirection, channel_count: usize) -> Vec<usize> {
+        let order = match direction {
+            ChannelDirection::In => &self.inputs,
+            ChannelDirection::Out => &self.outputs,
+        };
+        let mut listed = vec![false; channel_count];
+        let mut result = Vec::with_capacity(channel_count);
 
-impl GraphExecutor {
-    pub fn new(
-        application_context: ApplicationContext,
-        active_schedule: Arc<ArcSwapOption<Schedule>>,
-    ) -> Self {
-        Self { active_schedule, application_context }
+        for &index in order {
+            if index < channel_count && !std::mem::replace(&mut listed[index], true) {
+                result.push(index);
+            }
+        }
+
+        result.extend((0..channel_count).filter(|index| !listed[*index]));
+        result
     }
 
-    pub fn spawn(
-        application_context: ApplicationContext,
-        active_schedule: Arc<ArcSwapOption<Schedule>>,
-    ) -> std::thread::JoinHandle<()> {
-        thread::spawn(move || Self::new(application_context, active_schedule).run())
+    fn set(&mut self, direction: ChannelDirection, order: Vec<usize>) {
+        let natural = order.iter().enumerate().all(|(position, index)| position == *index);
+
+        *self.get_mut(direction) = if natural { Vec::new() } else { order };
     }
 
-    pub fn run(mut self) {
-        let mut prepared_execution: Option<PreparedExecution> = None;
-        let mut last_prepared_execution: Option<PreparedExecution> = None;
+    /// Moves the channel to the position the target channel is displayed at, both specified by
+    /// their global indices.
+    pub fn move_channel(
+        &mut self,
+        configuration: &NodeConfiguration,
+        direction: ChannelDirection,
+        channel: usize,
+        target: usize,
+    ) {
+        let mut order = self.resolve(direction, configuration.channels(direction).count());
 
-        loop {
-            if let Some(active_schedule) = self.active_schedule.load().as_ref() {
-                if prepared_execution.is_none()
-                    || prepared_execution.as_ref().unwrap().generation != active_schedule.generation
-                {
-                    prepared_execution = Some(PreparedExecution::from(
-                        &active_schedule,
-                        &mut self.application_context,
-                        prepared_execution.or(last_prepared_execution.take()),
-                    ));
-                }
+        if let (Some(from), Some(to)) = (
+            order.iter().position(|index| *index == channel),
+            order.iter().position(|index| *index == target),
+        ) {
+            let channel = order.remove(from);
+
+            order.insert(to, channel);
+            self.set(direction, order);
+        }
+    }
 
-                let prepared_execution = prepared_execution.as_mut().unwrap();
+    /// Drops the indices of channels the configuration does not have anymore, such that the
+    /// permutation is valid for the current channels.
+    pub fn validate(&mut self, configuration: &NodeConfiguration) {
+        for direction in [ChannelDirection::In, ChannelDirection::Out].iter().copied() {
+            let order = self.resolve(direction, configuration.channels(direction).count());
 
-                prepared_execution.execute(active_schedule, &mut self.application_context);
-            } else {
-                if let Some(prepared_execution) = prepared_execution.take() {
-                    last_prepared_execution = Some(prepared_execution);
-                }
-            }
+            self.set(direction, order);
         }
     }
 }
 
 pub struct NodeData {
+    pub id: NodeId,
     pub title: String,
+    /// A color tag to visually group nodes.
+    pub color: Option<NodeColor>,
+    /// Whether the sink node is excluded from execution, see [`ExecutionGraph::muted_nodes`].
+    pub muted: bool,
+    /// Whether the sink node is executed exclusively, along with other soloed sink nodes.
+    pub soloed: bool,
     pub element_state: NodeElementState,
     pub floating_pane_state: FloatingPaneState,
     pub floating_pane_behaviour_state: FloatingPaneBehaviourState,
+    /// The state of the button toggling [`FloatingPaneState::collapsed`].
+    pub collapse_button_state: button::State,
+    pub channel_order: ChannelOrder,
+    /// Whether the channel rows are dragged to reorder them, instead of to connect the channels.
+    pub arranging_channels: bool,
+    /// The state of the button leaving the arrange mode, see [`Self::arranging_channels`].
+    pub arrange_button_state: button::State,
+    /// Whether the node is selected, to be aligned with other selected nodes. Not persisted.
+    pub selected: bool,
+    /// The library entry the node was inserted from, `None` if it was not inserted from the
+    /// library.
+    pub library_origin: Option<LibraryOrigin>,
     pub behaviour: Box<dyn NodeBehaviourContainer>,
     pub configuration: NodeConfiguration,
 }
 
 impl NodeData {
+    const MIN_WIDTH: f32 = 128.0;
+    /// The height panes fit their content up to, taller content is scrolled.
+    const MAX_FIT_HEIGHT: u32 = 480;
+
     pub fn new(
         title: impl ToString,
         position: impl Into<Vec2<f32>>,
         behaviour: Box<dyn NodeBehaviourContainer>,
     ) -> Self {
         let mut result = Self {
+            id: NodeId::generate(),
             title: title.to_string(),
+            color: None,
+            muted: false,
+            soloed: false,
             element_state: Default::default(),
             floating_pane_state: FloatingPaneState::new().with_position(position).with_width(200),
             floating_pane_behaviour_state: Default::default(),
+            collapse_button_state: Default::default(),
+            channel_order: Default::default(),
+            arranging_channels: false,
+            arrange_button_state: Default::default(),
+            selected: false,
+            library_origin: None,
             configuration: Default::default(),
             behaviour,
         };
@@ -1026,28 +3804,112 @@ impl NodeData {
         result
     }
 
-    pub fn update(&mut self, event: NodeEventContainer) {
+    /// A copy of the node with the default view state, see [`ExecutionGraph::dry_run_copy`].
+    pub fn dry_run_copy(&self) -> Self {
+        Self {
+            id: self.id,
+            title: self.title.clone(),
+            color: self.color,
+            muted: self.muted,
+            soloed: self.soloed,
+            element_state: Default::default(),
+            floating_pane_state: Default::default(),
+            floating_pane_behaviour_state: Default::default(),
+            collapse_button_state: Default::default(),
+            channel_order: self.channel_order.clone(),
+            arranging_channels: false,
+            arrange_button_state: Default::default(),
+            selected: false,
+            library_origin: self.library_origin.clone(),
+            behaviour: self.behaviour.clone(),
+            configuration: self.configuration.clone(),
+        }
+    }
+
+    /// Passes the event to the behaviour and returns whether the persisted settings of the
+    /// behaviour have changed, by comparing its [`NodeBehaviourContainer::save`] output.
+    pub fn update(&mut self, event: NodeEventContainer) -> bool {
+        let saved = self.behaviour.save();
+
         for command in self.behaviour.update(event) {
             match command {
                 NodeCommand::Configure(configuration) => self.configuration = configuration,
             }
         }
+
+        self.channel_order.validate(&self.configuration);
+
+        self.behaviour.save() != saved
+    }
+
+    /// The items contributed by the behaviour to the context menu of the node.
+    pub fn context_menu_items(&self, index: NodeIndex) -> Vec<ContextMenuItem<Message>> {
+        self.behaviour
+            .context_menu_items()
+            .into_iter()
+            .map(|item| {
+                item.map(|message| Message::NodeMessage {
+                    node: index,
+                    message: NodeMessage::NodeBehaviourMessage(message),
+                })
+            })
+            .collect()
     }
 
+    /// Displays the node dimmed, if `muted`, which may be the case even if the node itself has
+    /// not been muted.
     pub fn view(
         &mut self,
         index: NodeIndex,
+        muted: bool,
         theme: &dyn Theme,
     ) -> FloatingPane<'_, Message, iced_wgpu::Renderer, FloatingPanesBehaviour<Message, iced_wgpu::Renderer>>
     {
-        let mut builder = NodeElement::builder(index, &mut self.element_state).node_behaviour_element(
+        let channel_rows = |direction| {
+            let order = self.channel_order.resolve(direction, self.configuration.channels(direction).count());
+
+            ChannelRows::new(&self.configuration, direction, &self.element_state, &order)
+        };
+        let input_channel_rows = channel_rows(ChannelDirection::In);
+        let output_channel_rows = channel_rows(ChannelDirection::Out);
+        // The title bar is laid out by the pane, so its height is estimated.
+        let title_bar_height = (consts::TEXT_SIZE_TITLE + 2 * consts::SPACING_VERTICAL) as f32;
+        // Previewed panes display their full content, while still being collapsed.
+        let collapsed = self.floating_pane_state.displays_collapsed();
+        let previewed = self.floating_pane_state.previewed;
+        // Collapsed panes fit their content, which is measured only after the toggle.
+        let min_height =
+            if collapsed { title_bar_height } else { title_bar_height + self.element_state.min_height() };
+
+        // Enlarge the pane, if its content has grown since it was resized.
+        if let FloatingPaneLength::Units(height) = &mut self.floating_pane_state.size[1] {
+            if !collapsed && !previewed {
+                *height = (*height).max(min_height.ceil() as u16);
+            }
+        }
+
+        let node_behaviour_element = if collapsed {
+            None
+        } else {
             self.behaviour.view(theme).map(Element::from).map(move |element| {
                 element.map(move |message| Message::NodeMessage {
                     node: index,
                     message: NodeMessage::NodeBehaviourMessage(message),
                 })
-            }),
-        );
+            })
+        };
+        let mut builder = NodeElement::builder(index, &mut self.element_state)
+            .node_behaviour_element(node_behaviour_element)
+            .collapsed(collapsed)
+            .on_scroll(move |offset| Message::ScrollNodeContent { node: index, offset });
+
+        if let FloatingPaneLength::Units(width) = self.floating_pane_state.size[0] {
+            builder = builder.channels_width(width);
+        }
+
+        if self.floating_pane_state.size[1] == FloatingPaneLength::Shrink {
+            builder = builder.max_height(Self::MAX_FIT_HEIGHT);
+        }
 
         for input_channel in self.configuration.channels(ChannelDirection::In) {
             builder = builder.push_input_channel(input_channel);
@@ -1057,32 +3919,83 @@ impl NodeData {
             builder = builder.push_output_channel(output_channel);
         }
 
+        builder = builder
+            .channel_rows(ChannelDirection::In, input_channel_rows.clone())
+            .channel_rows(ChannelDirection::Out, output_channel_rows.clone());
+
         let node_element = builder.build(/*|index, new_value| {
             Message::NodeMessage {
                 node: index,
                 message: NodeMessage::UpdateTextInput(new_value),
             }
         }*/);
+        let mut title_controls = Row::new().theme(theme);
+
+        if self.behaviour.is_sink() {
+            title_controls = title_controls
+                .push(
+                    Checkbox::new(self.muted, "M", move |muted| Message::SetNodeMuted { node: index, muted })
+                        .theme(theme),
+                )
+                .push(
+                    Checkbox::new(self.soloed, "S", move |soloed| Message::SetNodeSoloed {
+                        node: index,
+                        soloed,
+                    })
+                    .theme(theme),
+                );
+        }
+
+        if self.arranging_channels {
+            title_controls = title_controls.push(
+                Button::new(&mut self.arrange_button_state, Text::new("Done"))
+                    .on_press(Message::SetArrangingChannels { node: index, arranging: false }),
+            );
+        }
+
+        let expanded = !self.floating_pane_state.collapsed;
+        let title_controls = title_controls
+            .push(
+                Button::new(&mut self.collapse_button_state, Text::new(if expanded { "▾" } else { "▸" }))
+                    .on_press(Message::SetNodeCollapsed { node: index, collapsed: expanded }),
+            )
+            .align_items(Align::Center);
 
         Themeable::theme(
             FloatingPane::builder(
                 node_element,
                 &mut self.floating_pane_state,
                 &mut self.floating_pane_behaviour_state,
-                FloatingPaneBehaviourData { node_configuration: self.configuration.clone() },
+                FloatingPaneBehaviourData {
+                    node_configuration: self.configuration.clone(),
+                    color: self.color,
+                    muted: Some(self.muted).filter(|_| self.behaviour.is_sink()),
+                    collapsed,
+                    arranging_channels: self.arranging_channels,
+                    channel_order_natural: self.channel_order.is_natural(),
+                    selected: self.selected,
+                    input_channel_rows,
+                    output_channel_rows,
+                },
             ),
             theme,
         )
         .title(Some(&self.title))
+        .icon(self.behaviour.icon().map(NodeIcon::as_char))
+        .tint(self.color.map(NodeColor::color))
         .title_size(Some(style::consts::TEXT_SIZE_TITLE))
         .title_margin(consts::SPACING)
+        .title_controls(Some(title_controls))
+        .dimmed(muted)
         .width_resizeable(true)
-        .min_width(128.0)
+        .height_resizeable(expanded)
+        .min_width(Self::MIN_WIDTH)
+        .min_height(min_height)
         .build()
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct EdgeEndpoint {
     pub channel_index: usize,
     pub pass_by: ChannelPassBy,
@@ -1107,6 +4020,31 @@ where T: Into<UndirectedChannelIdentifier>
 pub struct EdgeData {
     pub endpoint_from: EdgeEndpoint,
     pub endpoint_to: EdgeEndpoint,
+    /// Whether the value of the output is moved into this edge, rather than into the edge created
+    /// first, when multiple edges of the output require it. Set on at most one edge per output.
+    pub primary: bool,
+}
+
+/// The connection of an output, which the value of the output is moved into or mutably borrowed
+/// by. The rest of the connections of the output may only borrow the value immutably, and are
+/// executed before it is moved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveRecipient {
+    pub connection: Connection,
+    /// Whether the edge of the connection is marked as primary, rather than having been created
+    /// first.
+    pub primary: bool,
+    /// All connections of the output requiring exclusive access to the value, in the order of
+    /// their edges.
+    pub candidates: Vec<Connection>,
+}
+
+impl MoveRecipient {
+    /// Whether multiple connections require the value, none of which is marked as primary. Values
+    /// are never cloned, so all of the connections but the recipient are invalid.
+    pub fn is_ambiguous(&self) -> bool {
+        !self.primary && self.candidates.len() > 1
+    }
 }
 
 impl EdgeData {
@@ -1118,7 +4056,7 @@ impl EdgeData {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct UndirectedChannelIdentifier {
     pub node_index: NodeIndex,
     pub channel_index: usize,
@@ -1143,7 +4081,7 @@ impl From<ChannelIdentifier> for UndirectedChannelIdentifier {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ChannelIdentifier {
     pub node_index: NodeIndex,
     pub channel_direction: ChannelDirection,
@@ -1168,6 +4106,8 @@ pub enum ConnectionValidityError {
     IncompatiblePassBy,
     /// The types of the channels are incompatible.
     IncompatibleType,
+    /// The textures passed through the channels are incompatible.
+    IncompatibleTexture(TextureIncompatibility),
 }
 
 impl ConnectionValidityError {
@@ -1199,7 +4139,7 @@ impl ConnectionValidityError {
                 // Highlight the affected nodes
                 (collect)(connection.to().node_index.into());
             }
-            IncompatibleType => {
+            IncompatibleType | IncompatibleTexture(_) => {
                 // Highlight the affected connection
                 (collect)(connection.clone().into());
 
@@ -1237,11 +4177,16 @@ impl ConnectionValidityError {
                 ),
                 suggestion: None,
             },
+            IncompatibleTexture(incompatibility) => GraphValidationErrorDisplay {
+                title: Cow::Borrowed("Incompatible textures"),
+                description: Cow::Owned(format!("Texture {}.", incompatibility)),
+                suggestion: None,
+            },
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Connection(pub [UndirectedChannelIdentifier; 2]);
 
 impl From<[UndirectedChannelIdentifier; 2]> for Connection {
@@ -1251,10 +4196,12 @@ impl From<[UndirectedChannelIdentifier; 2]> for Connection {
 }
 
 impl Connection {
+    /// Connections requiring a [`Promotion`] are only valid if `implicit_promotion` is enabled.
     pub fn check_validity<'a>(
         &self,
         is_aliased: &dyn Fn(ChannelIdentifier) -> bool,
         get_channel: &'a dyn Fn(ChannelIdentifier) -> ChannelRef<'a>,
+        implicit_promotion: bool,
     ) -> Result<(), ConnectionValidityError> {
         let from = self.from();
         let to = self.to();
@@ -1272,8 +4219,13 @@ impl Connection {
         let channel_from = get_channel(from);
         let channel_to = get_channel(to);
 
-        if !TypeEnum::is_abi_compatible(&channel_from.ty, &channel_to.ty) {
-            return Err(ConnectionValidityError::IncompatibleType);
+        if !TypeEnum::is_abi_compatible(&channel_from.ty, &channel_to.ty)
+            && !(implicit_promotion && self.promotion(get_channel).is_some())
+        {
+            return Err(match TextureIncompatibility::find(&channel_from.ty, &channel_to.ty) {
+                Some(incompatibility) => ConnectionValidityError::IncompatibleTexture(incompatibility),
+                None => ConnectionValidityError::IncompatibleType,
+            });
         }
 
         Ok(())
@@ -1283,8 +4235,44 @@ impl Connection {
         &self,
         is_aliased: &dyn Fn(ChannelIdentifier) -> bool,
         get_channel: &'a dyn Fn(ChannelIdentifier) -> ChannelRef<'a>,
+        implicit_promotion: bool,
     ) -> bool {
-        self.check_validity(is_aliased, get_channel).is_ok()
+        self.check_validity(is_aliased, get_channel, implicit_promotion).is_ok()
+    }
+
+    /// The conversion of the values passed through the connection, if the output type promotes to
+    /// the type of the input. Only values passed into the input by value are promoted, as the
+    /// input references the promoted value stored by the task of the input.
+    pub fn promotion<'a>(
+        &self,
+        get_channel: &'a dyn Fn(ChannelIdentifier) -> ChannelRef<'a>,
+    ) -> Option<Promotion> {
+        let to = self.to();
+
+        if to.pass_by != ChannelPassBy::Value {
+            return None;
+        }
+
+        Promotion::find(get_channel(self.from()).ty, get_channel(to).ty)
+    }
+
+    /// Whether the input requires the value of the output to be moved or mutably borrowed, rather
+    /// than borrowed immutably.
+    pub fn requires_exclusive_access(&self) -> bool {
+        self.to().pass_by != ChannelPassBy::SharedReference
+    }
+
+    /// Whether the value of the output would be moved into the connection, once it is created
+    /// next to the existing connections, replacing the current connection of its input. Created
+    /// connections are not marked as primary, so they only receive the value if no other
+    /// connection of the output requires it.
+    pub fn would_receive_move<'a>(&self, connections: impl IntoIterator<Item = &'a Connection>) -> bool {
+        self.requires_exclusive_access()
+            && !connections.into_iter().any(|existing| {
+                existing.from() == self.from()
+                    && existing.to() != self.to()
+                    && existing.requires_exclusive_access()
+            })
     }
 
     pub fn try_from_identifiers([a, b]: [ChannelIdentifier; 2]) -> Option<Connection> {
@@ -1325,3 +4313,494 @@ impl Connection {
         self.channel(ChannelDirection::Out)
     }
 }
+
+/// Proposes connections from the outputs of one node to the inputs of another, such as when
+/// dragging between their title bars. Each output, in the order of the channels, is paired with the
+/// first input that is not connected yet, not paired with a previous output and `can_connect`
+/// accepts. Outputs without such an input are left out.
+pub fn pair_channels(
+    (from, from_configuration): (NodeIndex, &NodeConfiguration),
+    (to, to_configuration): (NodeIndex, &NodeConfiguration),
+    is_connected: impl Fn(ChannelIdentifier) -> bool,
+    can_connect: impl Fn(&Connection) -> bool,
+) -> Vec<Connection> {
+    let mut inputs = to_configuration
+        .channels(ChannelDirection::In)
+        .map(|channel_ref| channel_ref.into_identifier(to))
+        .filter(|input| !is_connected(*input))
+        .map(Some)
+        .collect::<Vec<_>>();
+
+    from_configuration
+        .channels(ChannelDirection::Out)
+        .map(|channel_ref| channel_ref.into_identifier(from))
+        .filter_map(|output| {
+            inputs.iter_mut().find_map(|input| {
+                let connection = Connection::try_from_identifiers([output, (*input)?]).unwrap();
+
+                if can_connect(&connection) {
+                    *input = None;
+                    Some(connection)
+                } else {
+                    None
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::controller::GraphCommand;
+    use super::testing::{add, connect, cpu_context, execute, executor_lock, schedule};
+    use super::*;
+    use crate::node::behaviour::{ConstantNodeBehaviour, DebugNodeBehaviour};
+    use crate::node::Channel;
+    use crate::template::GraphBuilder;
+    use test::Bencher;
+
+    const CHAIN_LENGTH: usize = 64;
+    const FAN_OUT: usize = 64;
+    const REGENERATIONS: usize = 300;
+
+    /// A constant passed by value through a chain of nodes.
+    fn chain(length: usize) -> ExecutionGraph {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let mut previous = add(&mut graph, "Constant");
+
+        for _ in 0..length {
+            let node = add(&mut graph, "Abs");
+
+            connect(&mut graph, previous, node);
+            previous = node;
+        }
+
+        graph
+    }
+
+    /// A constant borrowed by many debug nodes, whose guard is shared by all of them.
+    fn fan_out(consumers: usize) -> ExecutionGraph {
+        let mut builder = GraphBuilder::default();
+        let source = builder.node("Constant", [0.0, 0.0], ConstantNodeBehaviour::new(1.0_f32));
+
+        for index in 0..consumers {
+            let position = [250.0, index as f32 * 100.0];
+            let debug = builder.node("Debug", position, DebugNodeBehaviour::new(PrimitiveTypeEnum::F32));
+
+            builder.connect((source, 0), (debug, ChannelPassBy::SharedReference, 0));
+        }
+
+        builder.build().into()
+    }
+
+    fn endpoint(pass_by: ChannelPassBy, channel_index: usize) -> EdgeEndpoint {
+        EdgeEndpoint { channel_index, pass_by }
+    }
+
+    fn value(channel_index: usize) -> EdgeEndpoint {
+        endpoint(ChannelPassBy::Value, channel_index)
+    }
+
+    /// Adds an edge without checking it, as inconsistent graphs are not accepted by the commands.
+    fn add_edge(
+        graph: &mut ExecutionGraph,
+        (from, endpoint_from): (NodeIndex, EdgeEndpoint),
+        (to, endpoint_to): (NodeIndex, EdgeEndpoint),
+    ) {
+        graph.graph.add_edge(from, to, EdgeData { endpoint_from, endpoint_to, primary: false });
+    }
+
+    fn insert_edge(
+        graph: &mut ExecutionGraph,
+        (from, from_index): (NodeIndex, usize),
+        (to, to_index): (NodeIndex, usize),
+    ) {
+        let connection = Connection([
+            value(from_index).into_undirected_identifier(from),
+            value(to_index).into_undirected_identifier(to),
+        ]);
+
+        GraphCommand::InsertEdge { connection, primary: false }.apply(graph).unwrap();
+    }
+
+    fn remove_edge(
+        graph: &mut ExecutionGraph,
+        (from, from_index): (NodeIndex, usize),
+        (to, to_index): (NodeIndex, usize),
+    ) {
+        let connection = Connection([
+            value(from_index).into_undirected_identifier(from),
+            value(to_index).into_undirected_identifier(to),
+        ]);
+
+        GraphCommand::RemoveEdge { connection }.apply(graph).unwrap();
+    }
+
+    #[test]
+    fn unconnected_input_of_used_node_is_reported() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let constant = add(&mut graph, "Constant");
+        let operation = add(&mut graph, "Binary Operation");
+
+        add_edge(&mut graph, (constant, value(0)), (operation, value(0)));
+
+        let error = graph.create_tasks(vec![constant, operation]).unwrap_err();
+
+        assert!(matches!(
+            error,
+            ScheduleError::MissingInput { channel }
+                if channel.node_index == operation && channel.pass_by == ChannelPassBy::Value && channel.channel_index == 1
+        ));
+    }
+
+    #[test]
+    fn source_scheduled_after_its_target_is_reported() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let constant = add(&mut graph, "Constant");
+        let abs = add(&mut graph, "Abs");
+
+        connect(&mut graph, constant, abs);
+
+        let error = graph.create_tasks(vec![abs, constant]).unwrap_err();
+
+        assert!(matches!(
+            error,
+            ScheduleError::UnscheduledSource { connection }
+                if connection.from().node_index == constant && connection.to().node_index == abs
+        ));
+    }
+
+    #[test]
+    fn connection_to_missing_input_is_reported() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let constant = add(&mut graph, "Constant");
+        let abs = add(&mut graph, "Abs");
+
+        add_edge(&mut graph, (constant, value(0)), (abs, value(3)));
+
+        let error = graph.create_tasks(vec![constant, abs]).unwrap_err();
+
+        assert!(matches!(
+            error,
+            ScheduleError::MissingChannel { connection, channel } if channel == connection.to()
+        ));
+    }
+
+    #[test]
+    fn connection_from_missing_reference_is_reported() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let constant = add(&mut graph, "Constant");
+        let abs = add(&mut graph, "Abs");
+        let debug = add(&mut graph, "Debug");
+
+        connect(&mut graph, constant, abs);
+        // The references passed on by the node are looked up among its inputs.
+        add_edge(
+            &mut graph,
+            (abs, endpoint(ChannelPassBy::SharedReference, 2)),
+            (debug, endpoint(ChannelPassBy::SharedReference, 0)),
+        );
+
+        let error = graph.create_tasks(vec![constant, abs, debug]).unwrap_err();
+
+        assert!(matches!(
+            error,
+            ScheduleError::MissingChannel { connection, channel } if channel == connection.from()
+        ));
+    }
+
+    #[test]
+    fn value_borrowed_after_being_moved_is_reported() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let constant = add(&mut graph, "Constant");
+        let abs = add(&mut graph, "Abs");
+        let debug = add(&mut graph, "Debug");
+
+        add_edge(&mut graph, (constant, value(0)), (abs, value(0)));
+        add_edge(&mut graph, (constant, value(0)), (debug, endpoint(ChannelPassBy::SharedReference, 0)));
+
+        let error = graph.create_tasks(vec![constant, abs, debug]).unwrap_err();
+
+        assert!(matches!(
+            error,
+            ScheduleError::BorrowedAfterMove { connection, borrower }
+                if connection.to().node_index == abs && borrower == debug
+        ));
+        assert!(graph.create_tasks(vec![constant, debug, abs]).is_ok());
+    }
+
+    /// The inputs of a task are indexed by the index of the channel among the channels passed the
+    /// same way, not by the index among all input channels of the node, see
+    /// [`NodeConfiguration::get_global_channel_index`].
+    #[test]
+    fn inputs_are_indexed_among_the_channels_passed_the_same_way() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let borrowed = add(&mut graph, "Constant");
+        let moved = add(&mut graph, "Constant");
+        let node = add(&mut graph, "Abs");
+
+        graph.graph[node].configuration = NodeConfiguration::default()
+            .with_borrow(Channel::new("borrowed", PrimitiveTypeEnum::F32))
+            .with_input_value(Channel::new("moved", PrimitiveTypeEnum::F32))
+            .with_output_value(Channel::new("result", PrimitiveTypeEnum::F32));
+        add_edge(&mut graph, (borrowed, value(0)), (node, endpoint(ChannelPassBy::SharedReference, 0)));
+        add_edge(&mut graph, (moved, value(0)), (node, value(0)));
+
+        let tasks = graph.create_tasks(vec![borrowed, moved, node]).unwrap();
+        let task = tasks[2].as_ref().unwrap();
+
+        assert_eq!(task.borrows.len(), 1);
+        assert_eq!(task.inputs.len(), 1);
+        assert_eq!(task.borrows[0].task_index, 0);
+        assert_eq!(task.inputs[0].task_index, 1);
+    }
+
+    #[test]
+    fn active_schedule_survives_invalid_edit_until_fixed() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let lhs = add(&mut graph, "Constant");
+        let rhs = add(&mut graph, "Constant");
+        let operation = add(&mut graph, "Binary Operation");
+
+        insert_edge(&mut graph, (lhs, 0), (operation, 0));
+        insert_edge(&mut graph, (rhs, 0), (operation, 1));
+
+        let valid = schedule(&mut graph);
+
+        remove_edge(&mut graph, (rhs, 0), (operation, 1));
+        assert!(graph.update_schedule().is_err());
+        assert_eq!(graph.scheduled_generation(), Some(valid.generation));
+
+        // The previous schedule keeps being executed in the meantime.
+        {
+            let _lock = executor_lock();
+            let mut context = cpu_context();
+
+            execute(&graph.active_schedule.load_full().unwrap(), &mut context, 2).release();
+            assert!(context.node_errors.get_all().is_empty());
+        }
+
+        insert_edge(&mut graph, (rhs, 0), (operation, 1));
+
+        let fixed = schedule(&mut graph);
+
+        assert_ne!(fixed.generation, valid.generation);
+        assert!(fixed.tasks.iter().flatten().any(|task| task.node_index == operation));
+    }
+
+    /// Every schedule allocates new lists, which are moved or borrowed depending on whether the
+    /// reducing node is connected, and released once the schedule is replaced.
+    #[test]
+    fn regenerated_schedules_neither_leak_nor_double_free() {
+        let _lock = executor_lock();
+        let mut context = cpu_context();
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let constant = add(&mut graph, "Constant");
+        let list = add(&mut graph, "ListConstructor");
+        let reduce = add(&mut graph, "List Reduce");
+        let borrow = Connection([
+            value(0).into_undirected_identifier(list),
+            endpoint(ChannelPassBy::SharedReference, 0).into_undirected_identifier(reduce),
+        ]);
+        let before = Allocator::get().statistics();
+        let mut prepared_execution = None;
+
+        connect(&mut graph, constant, list);
+
+        for regeneration in 0..REGENERATIONS {
+            let command = if regeneration % 2 == 0 {
+                GraphCommand::InsertEdge { connection: borrow.clone(), primary: false }
+            } else {
+                GraphCommand::RemoveEdge { connection: borrow.clone() }
+            };
+
+            command.apply(&mut graph).unwrap();
+
+            let schedule = schedule(&mut graph);
+            let mut execution = PreparedExecution::from(&schedule, &mut context, prepared_execution.take());
+
+            for _ in 0..2 {
+                execution.execute(&schedule, &mut context, &mut None);
+            }
+
+            prepared_execution = Some(execution);
+        }
+
+        prepared_execution.unwrap().release();
+
+        let after = Allocator::get().statistics();
+
+        assert!(after.total_allocated > before.total_allocated);
+        assert_eq!(after.live, before.live);
+        assert_eq!(after.double_frees, before.double_frees);
+        assert!(context.node_errors.get_all().is_empty());
+    }
+
+    /// A task which holds onto the marker until it is dropped.
+    fn main_thread_task(marker: &Arc<()>) -> Box<MainThreadTask> {
+        let marker = marker.clone();
+
+        Box::new(move |_| drop(marker))
+    }
+
+    #[test]
+    fn main_thread_tasks_of_a_node_are_coalesced() {
+        let tasks = MainThreadTasks::new(true);
+        let (older, newer, other) = (Arc::new(()), Arc::new(()), Arc::new(()));
+        let node = NodeIndex::new(0);
+
+        tasks.submit((0, node), main_thread_task(&older)).unwrap();
+        tasks.submit((1, node), main_thread_task(&other)).unwrap();
+        tasks.submit((0, node), main_thread_task(&newer)).unwrap();
+        assert_eq!(Arc::strong_count(&older), 1);
+
+        let taken = tasks.take(usize::MAX);
+
+        assert_eq!(taken.len(), 2);
+        assert_eq!(Arc::strong_count(&newer), 2);
+        assert_eq!(Arc::strong_count(&other), 2);
+        assert!(tasks.take(usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn main_thread_tasks_are_bounded_by_the_number_of_nodes() {
+        let tasks = MainThreadTasks::new(true);
+        let marker = Arc::new(());
+
+        for index in 0..MainThreadTasks::CAPACITY {
+            tasks.submit((0, NodeIndex::new(index)), main_thread_task(&marker)).unwrap();
+        }
+
+        let overflowing = (0, NodeIndex::new(MainThreadTasks::CAPACITY));
+
+        assert_eq!(
+            tasks.submit(overflowing, main_thread_task(&marker)),
+            Err(MainThreadTaskSubmitError::Full)
+        );
+        // Nodes with pending tasks may still replace them.
+        tasks.submit((0, NodeIndex::new(0)), main_thread_task(&marker)).unwrap();
+        assert_eq!(tasks.take(16).len(), 16);
+        tasks.submit(overflowing, main_thread_task(&marker)).unwrap();
+        assert_eq!(
+            MainThreadTasks::new(false).submit(overflowing, main_thread_task(&marker)),
+            Err(MainThreadTaskSubmitError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn errors_of_removed_nodes_are_cleared_by_the_next_schedule() {
+        let mut graph = ExecutionGraph::from(Graph::new());
+        let kept = add(&mut graph, "Constant");
+        let removed = add(&mut graph, "Constant");
+        let node_errors = NodeErrors::default();
+
+        node_errors.set(kept, "kept");
+        node_errors.set_gpu_fault(removed, "removed");
+        GraphCommand::RemoveNode { node: removed }.apply(&mut graph).unwrap();
+        node_errors.retain_scheduled(&schedule(&mut graph));
+
+        let errors = node_errors.get_all();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, kept);
+        assert!(!node_errors.is_gpu_faulty(removed));
+    }
+
+    /// A configuration with a value channel of each of the types.
+    fn configuration(inputs: &[PrimitiveTypeEnum], outputs: &[PrimitiveTypeEnum]) -> NodeConfiguration {
+        let configuration = inputs.iter().fold(NodeConfiguration::default(), |configuration, ty| {
+            configuration.with_input_value(Channel::new("input", *ty))
+        });
+
+        outputs.iter().fold(configuration, |configuration, ty| {
+            configuration.with_output_value(Channel::new("output", *ty))
+        })
+    }
+
+    /// The indices of the outputs of `from` and the inputs of `to` paired by [`pair_channels`], with
+    /// the `connected` inputs already connected to another node.
+    fn pair(from: &NodeConfiguration, to: &NodeConfiguration, connected: &[usize]) -> Vec<(usize, usize)> {
+        let (from_index, to_index) = (NodeIndex::new(0), NodeIndex::new(1));
+        let get_channel = |channel: ChannelIdentifier| {
+            let configuration = if channel.node_index == from_index { from } else { to };
+
+            configuration.channel(channel.channel_direction, channel.into())
+        };
+
+        pair_channels(
+            (from_index, from),
+            (to_index, to),
+            |input| connected.contains(&input.channel_index),
+            |connection| connection.is_valid(&|_| false, &get_channel, false),
+        )
+        .into_iter()
+        .map(|connection| (connection.from().channel_index, connection.to().channel_index))
+        .collect()
+    }
+
+    #[test]
+    fn inputs_are_paired_in_order() {
+        let from = configuration(&[], &[PrimitiveTypeEnum::F32, PrimitiveTypeEnum::F32]);
+        let to = configuration(&[PrimitiveTypeEnum::F32; 3], &[]);
+
+        assert_eq!(pair(&from, &to, &[]), vec![(0, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn connected_inputs_are_skipped() {
+        let from = configuration(&[], &[PrimitiveTypeEnum::F32, PrimitiveTypeEnum::F32]);
+        let to = configuration(&[PrimitiveTypeEnum::F32; 3], &[]);
+
+        assert_eq!(pair(&from, &to, &[0, 2]), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn channels_of_incompatible_types_are_not_paired() {
+        let from = configuration(&[], &[PrimitiveTypeEnum::U32, PrimitiveTypeEnum::F32]);
+        let to = configuration(&[PrimitiveTypeEnum::F32, PrimitiveTypeEnum::U32], &[]);
+
+        assert_eq!(pair(&from, &to, &[]), vec![(0, 1), (1, 0)]);
+
+        let from = configuration(&[], &[PrimitiveTypeEnum::F64]);
+        let to = configuration(&[PrimitiveTypeEnum::U8], &[]);
+
+        assert!(pair(&from, &to, &[]).is_empty());
+    }
+
+    #[test]
+    fn leftover_outputs_are_not_paired() {
+        let from = configuration(&[], &[PrimitiveTypeEnum::F32; 3]);
+        let to = configuration(&[PrimitiveTypeEnum::F32], &[]);
+
+        assert_eq!(pair(&from, &to, &[]), vec![(0, 0)]);
+    }
+
+    fn bench_passes(bencher: &mut Bencher, mut graph: ExecutionGraph) {
+        let _lock = executor_lock();
+        let mut context = cpu_context();
+        let schedule = schedule(&mut graph);
+        let mut prepared_execution = execute(&schedule, &mut context, 1);
+
+        bencher.iter(|| prepared_execution.execute(&schedule, &mut context, &mut None));
+        prepared_execution.release();
+    }
+
+    #[bench]
+    fn execute_chain_64(bencher: &mut Bencher) {
+        bench_passes(bencher, chain(CHAIN_LENGTH));
+    }
+
+    #[bench]
+    fn execute_fan_out_64(bencher: &mut Bencher) {
+        bench_passes(bencher, fan_out(FAN_OUT));
+    }
+
+    /// Compared with [`execute_chain_64`], the cost of checking the output of every node.
+    #[bench]
+    fn execute_chain_64_sanitized(bencher: &mut Bencher) {
+        let mut graph = chain(CHAIN_LENGTH);
+
+        graph.sanitize_numerics = Some(0.0);
+        bench_passes(bencher, graph);
+    }
+}
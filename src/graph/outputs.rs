@@ -0,0 +1,191 @@
+//! Storage of the values of the output channels of a task.
+//!
+//! The values of all outputs of a task are stored in a single contiguous arena rather than in
+//! individually allocated buffers, so that graphs with many small primitive channels keep their
+//! values close together. Each value is only aligned to the alignment of its type, see
+//! [`value_alignment`]. Output values are always sized, as unsized values are allocated by the
+//! [`Allocator`](super::alloc::Allocator) and referred to by pointers.
+//!
+//! The values are not guarded by locks. Instead, access safety is ensured by the schedule, the
+//! same way as for allocations in [`AllocationCell`](super::alloc::AllocationCell):
+//! * the task owning the outputs writes them only while it is being executed, before any of its
+//!   dependent tasks;
+//! * a value consumed by move or by mutable reference has exactly one consumer, which is the only
+//!   task accessing the value during its execution.
+
+use crate::node::{Bytes, BytesMut, Refcounter, TypeEnum, TypeExt, TypedBytes, TypedBytesMut};
+use std::borrow::Cow;
+use std::mem;
+use std::ptr;
+
+/// The unit the arena is allocated in, aligned to the largest alignment of the values.
+#[derive(Clone, Copy, Default)]
+#[repr(C, align(16))]
+struct ArenaChunk([u8; 16]);
+
+const CHUNK_SIZE: usize = mem::size_of::<ArenaChunk>();
+const MAX_ALIGNMENT: usize = mem::align_of::<ArenaChunk>();
+
+/// The alignment of the values of the sized type, that of the primitive values it consists of.
+/// The values are read and written byte by byte, so the alignment only keeps the primitive values
+/// from straddling cache lines.
+fn value_alignment(ty: &TypeEnum) -> usize {
+    match ty {
+        // The flags follow the inner value.
+        TypeEnum::Option(option) => value_alignment(&option.child_ty),
+        TypeEnum::Array(array) => value_alignment(&array.item_type),
+        TypeEnum::Color(_) => mem::align_of::<f32>(),
+        // Primitive values and pointers are aligned to their size.
+        ty => {
+            let size = ty.value_size_if_sized().unwrap_or(0);
+
+            (size & size.wrapping_neg()).clamp(1, MAX_ALIGNMENT)
+        }
+    }
+}
+
+struct OutputSlot {
+    ty: TypeEnum,
+    /// The offset of the value within the arena, in bytes, a multiple of the alignment of the type.
+    offset: usize,
+    size: usize,
+}
+
+pub struct OutputValues {
+    /// Owned, allocated as a boxed slice of `arena_len` chunks.
+    arena: *mut ArenaChunk,
+    arena_len: usize,
+    slots: Box<[OutputSlot]>,
+}
+
+// Safety: The arena is owned and accessed according to the rules described in the module
+// documentation.
+unsafe impl Send for OutputValues {}
+unsafe impl Sync for OutputValues {}
+
+impl OutputValues {
+    /// Allocates zeroed values of the types, which must be sized.
+    pub fn new(types: impl IntoIterator<Item = TypeEnum>) -> Self {
+        let mut arena_size = 0;
+        let slots = types
+            .into_iter()
+            .map(|ty| {
+                let size = ty.value_size_if_sized().expect("Output values must be sized.");
+                let alignment = value_alignment(&ty);
+                let offset = (arena_size + alignment - 1) / alignment * alignment;
+
+                arena_size = offset + size;
+
+                OutputSlot { ty, offset, size }
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        let arena_len = (arena_size + CHUNK_SIZE - 1) / CHUNK_SIZE;
+        let arena = vec![ArenaChunk::default(); arena_len].into_boxed_slice();
+
+        Self { arena: Box::into_raw(arena) as *mut ArenaChunk, arena_len, slots }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn ty(&self, index: usize) -> Option<&TypeEnum> {
+        self.slots.get(index).map(|slot| &slot.ty)
+    }
+
+    fn slot_ptr(&self, slot: &OutputSlot) -> *mut u8 {
+        unsafe { (self.arena as *mut u8).add(slot.offset) }
+    }
+
+    /// Safety: The value must not be mutated for the lifetime of the returned reference, see the
+    /// module documentation.
+    pub unsafe fn value<'a>(&'a self, index: usize, rc: &'a dyn Refcounter) -> Option<TypedBytes<'a>> {
+        self.slots.get(index).map(|slot| {
+            let bytes = std::slice::from_raw_parts(self.slot_ptr(slot), slot.size);
+
+            TypedBytes::from(Bytes::Bytes(bytes), Cow::Borrowed(&slot.ty), rc)
+        })
+    }
+
+    /// Safety: The value must not be accessed by anything else for the lifetime of the returned
+    /// reference, see the module documentation.
+    pub unsafe fn value_mut<'a>(
+        &'a self,
+        index: usize,
+        rc: &'a mut dyn Refcounter,
+    ) -> Option<TypedBytesMut<'a>> {
+        self.slots.get(index).map(move |slot| {
+            let bytes = std::slice::from_raw_parts_mut(self.slot_ptr(slot), slot.size);
+
+            TypedBytesMut::from(BytesMut::Bytes(bytes), Cow::Borrowed(&slot.ty), rc)
+        })
+    }
+
+    /// The type and the bytes of the value, exclusive access is ensured by the borrow.
+    pub fn get_mut(&mut self, index: usize) -> Option<(&TypeEnum, &mut [u8])> {
+        let slot = self.slots.get(index)?;
+        let bytes = unsafe { std::slice::from_raw_parts_mut(self.slot_ptr(slot), slot.size) };
+
+        Some((&slot.ty, bytes))
+    }
+
+    /// The types and the bytes of all values, exclusive access is ensured by the borrow.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&TypeEnum, &mut [u8])> {
+        let arena = self.arena as *mut u8;
+
+        // The slots do not overlap, so the slices are disjoint.
+        self.slots.iter().map(move |slot| {
+            (&slot.ty, unsafe { std::slice::from_raw_parts_mut(arena.add(slot.offset), slot.size) })
+        })
+    }
+}
+
+impl Default for OutputValues {
+    fn default() -> Self {
+        Self::new(std::iter::empty())
+    }
+}
+
+impl Drop for OutputValues {
+    fn drop(&mut self) {
+        unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(self.arena, self.arena_len)) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{ColorType, OptionType, PrimitiveType};
+
+    fn offsets(values: &OutputValues) -> Vec<usize> {
+        values.slots.iter().map(|slot| slot.offset).collect()
+    }
+
+    #[test]
+    fn values_are_aligned_to_their_types() {
+        let values = OutputValues::new(vec![
+            PrimitiveType::<u8>::default().into(),
+            PrimitiveType::<f32>::default().into(),
+            PrimitiveType::<u8>::default().into(),
+            PrimitiveType::<f64>::default().into(),
+            OptionType::new(PrimitiveType::<u16>::default()).into(),
+            ColorType::new().into(),
+        ]);
+
+        assert_eq!(offsets(&values), vec![0, 4, 8, 16, 24, 28]);
+        assert_eq!(values.arena_len, 3);
+    }
+
+    #[test]
+    fn small_values_share_chunks() {
+        let values = OutputValues::new((0..16).map(|_| PrimitiveType::<u8>::default().into()));
+
+        assert_eq!(offsets(&values), (0..16).collect::<Vec<_>>());
+        assert_eq!(values.arena_len, 1);
+    }
+}
@@ -0,0 +1,152 @@
+//! Scaffolding shared by the nodes which draw into a texture with a single triangle covering the
+//! whole render target, such as to resample another texture.
+//!
+//! The SPIR-V binaries of the shaders are compiled from the GLSL sources next to them.
+
+use iced_wgpu::wgpu;
+
+/// The vertex shader drawing a triangle covering the whole viewport, when invoked with 3
+/// vertices. Passes the texture coordinates of the viewport to the fragment shader at location 0.
+pub fn fullscreen_vertex_module(device: &wgpu::Device) -> wgpu::ShaderModule {
+    device.create_shader_module(wgpu::include_spirv!("../shaders/blit.vert.spv"))
+}
+
+/// The fragment shader sampling the texture at binding 0 with the sampler at binding 1, at the
+/// texture coordinates passed by the vertex shader.
+pub fn sample_fragment_module(device: &wgpu::Device) -> wgpu::ShaderModule {
+    device.create_shader_module(wgpu::include_spirv!("../shaders/blit.frag.spv"))
+}
+
+/// A 2D texture with floating-point components, sampled by the fragment shader.
+pub fn sampled_texture_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStage::FRAGMENT,
+        ty: wgpu::BindingType::SampledTexture {
+            dimension: wgpu::TextureViewDimension::D2,
+            component_type: wgpu::TextureComponentType::Float,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+/// A sampler, used by the fragment shader.
+pub fn sampler_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStage::FRAGMENT,
+        ty: wgpu::BindingType::Sampler { comparison: false },
+        count: None,
+    }
+}
+
+/// A uniform buffer, read by the shader stages in `visibility`.
+pub fn uniform_buffer_entry(binding: u32, visibility: wgpu::ShaderStage) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: wgpu::BindingType::UniformBuffer { dynamic: false, min_binding_size: None },
+        count: None,
+    }
+}
+
+/// A render pipeline without vertex buffers, drawing a single triangle covering the whole render
+/// target with the resources of a single bind group.
+pub struct FullscreenPipeline {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl FullscreenPipeline {
+    /// Creates the pipeline drawing into render targets of the format. The vertex shader must
+    /// cover the viewport with 3 vertices, such as [`fullscreen_vertex_module`].
+    pub fn new(
+        device: &wgpu::Device,
+        vertex_module: &wgpu::ShaderModule,
+        fragment_module: &wgpu::ShaderModule,
+        entries: &[wgpu::BindGroupLayoutEntry],
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor { label: None, entries });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: Some(&pipeline_layout),
+            vertex_stage: wgpu::ProgrammableStageDescriptor { module: vertex_module, entry_point: "main" },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: fragment_module,
+                entry_point: "main",
+            }),
+            rasterization_state: None,
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[format.into()],
+            depth_stencil_state: None,
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[],
+            },
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        });
+
+        Self { pipeline, bind_group_layout }
+    }
+
+    /// Creates a bind group of the resources, bound in the order of the entries the pipeline was
+    /// created with.
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        resources: Vec<wgpu::BindingResource<'_>>,
+    ) -> wgpu::BindGroup {
+        let entries = resources
+            .into_iter()
+            .enumerate()
+            .map(|(binding, resource)| wgpu::BindGroupEntry { binding: binding as u32, resource })
+            .collect::<Vec<_>>();
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.bind_group_layout,
+            entries: &entries,
+        })
+    }
+
+    /// Records a render pass clearing the target to the color and drawing over it, unless there is
+    /// no bind group to draw with.
+    pub fn draw(
+        &self,
+        bind_group: Option<&wgpu::BindGroup>,
+        target: &wgpu::TextureView,
+        clear_color: wgpu::Color,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: target,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(clear_color), store: true },
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        if let Some(bind_group) = bind_group {
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
+
+impl std::fmt::Debug for FullscreenPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FullscreenPipeline").finish()
+    }
+}
@@ -0,0 +1,479 @@
+//! Recording of the outputs of source nodes and their deterministic replay.
+//!
+//! A recording consists of a header followed by a sequence of frames, one per execution pass:
+//!
+//! ```text
+//! header: magic b"DVSR", version: u16
+//! frame:  generation: u64, pass_index: u64, value_count: u32, value*
+//! value:  node_index: u32, channel_index: u32, tag_len: u16, tag: [u8], data_len: u32, data: [u8]
+//! ```
+//!
+//! All integers are little endian. The tag is the textual representation of the `TypeEnum` of
+//! the recorded value and is used to validate the type of the value during replay.
+
+use super::outputs::OutputValues;
+use super::NodeIndex;
+use crate::logging::Category;
+use crate::node::{TypeEnum, TypeExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+const MAGIC: &[u8; 4] = b"DVSR";
+const VERSION: u16 = 1;
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(io::Error),
+    /// The file is not a recording or has been created by an incompatible version.
+    InvalidFormat,
+    /// The value of the channel does not have a safe binary representation, e.g. a texture.
+    UnsupportedType { node_index: NodeIndex, channel_index: usize, ty: TypeEnum },
+    /// The recorded value does not match the type of the channel it is being replayed into.
+    TypeMismatch { node_index: NodeIndex, channel_index: usize, expected: String, found: String },
+}
+
+impl From<io::Error> for RecordingError {
+    fn from(error: io::Error) -> Self {
+        RecordingError::Io(error)
+    }
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use RecordingError::*;
+        match self {
+            Io(error) => write!(f, "I/O error: {}", error),
+            InvalidFormat => write!(f, "The file is not a valid recording."),
+            UnsupportedType { node_index, channel_index, ty } => write!(
+                f,
+                "Cannot record channel #{} of node #{}: values of type `{}` cannot be serialized.",
+                channel_index,
+                node_index.index(),
+                ty
+            ),
+            TypeMismatch { node_index, channel_index, expected, found } => write!(
+                f,
+                "Cannot replay channel #{} of node #{}: expected a value of type `{}`, found `{}`.",
+                channel_index,
+                node_index.index(),
+                expected,
+                found
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedValue {
+    pub node_index: NodeIndex,
+    pub channel_index: usize,
+    pub ty_tag: String,
+    pub data: Box<[u8]>,
+}
+
+impl RecordedValue {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u32::<LittleEndian>(self.node_index.index() as u32)?;
+        writer.write_u32::<LittleEndian>(self.channel_index as u32)?;
+        writer.write_u16::<LittleEndian>(self.ty_tag.len() as u16)?;
+        writer.write_all(self.ty_tag.as_bytes())?;
+        writer.write_u32::<LittleEndian>(self.data.len() as u32)?;
+        writer.write_all(&self.data)
+    }
+
+    fn read_from(reader: &mut impl Read) -> Result<Self, RecordingError> {
+        let node_index = NodeIndex::new(reader.read_u32::<LittleEndian>()? as usize);
+        let channel_index = reader.read_u32::<LittleEndian>()? as usize;
+        let mut ty_tag = vec![0; reader.read_u16::<LittleEndian>()? as usize];
+        reader.read_exact(&mut ty_tag)?;
+        let ty_tag = String::from_utf8(ty_tag).map_err(|_| RecordingError::InvalidFormat)?;
+        let mut data = vec![0; reader.read_u32::<LittleEndian>()? as usize];
+        reader.read_exact(&mut data)?;
+
+        Ok(Self { node_index, channel_index, ty_tag, data: data.into_boxed_slice() })
+    }
+}
+
+/// The values of all source node outputs recorded during a single execution pass.
+#[derive(Debug, Clone)]
+pub struct RecordedPass {
+    pub generation: u64,
+    pub pass_index: u64,
+    pub values: Vec<RecordedValue>,
+}
+
+impl RecordedPass {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u64::<LittleEndian>(self.generation)?;
+        writer.write_u64::<LittleEndian>(self.pass_index)?;
+        writer.write_u32::<LittleEndian>(self.values.len() as u32)?;
+
+        for value in &self.values {
+            value.write_to(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns `None` if the end of the recording has been reached.
+    fn read_from(reader: &mut impl Read) -> Result<Option<Self>, RecordingError> {
+        let generation = match reader.read_u64::<LittleEndian>() {
+            Ok(generation) => generation,
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let pass_index = reader.read_u64::<LittleEndian>()?;
+        let value_count = reader.read_u32::<LittleEndian>()?;
+        let values =
+            (0..value_count).map(|_| RecordedValue::read_from(reader)).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(Self { generation, pass_index, values }))
+    }
+}
+
+/// Writes the outputs of source nodes to a file.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    current_pass: Option<RecordedPass>,
+}
+
+impl Recorder {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(MAGIC)?;
+        writer.write_u16::<LittleEndian>(VERSION)?;
+
+        Ok(Self { writer, current_pass: None })
+    }
+
+    pub fn begin_pass(&mut self, generation: usize, pass_index: u64) {
+        self.current_pass =
+            Some(RecordedPass { generation: generation as u64, pass_index, values: Vec::new() });
+    }
+
+    /// Records all output values of a source node.
+    pub fn record_task(
+        &mut self,
+        node_index: NodeIndex,
+        output_values: &mut OutputValues,
+    ) -> Result<(), RecordingError> {
+        let current_pass = self.current_pass.as_mut().expect("No pass is being recorded.");
+
+        for (channel_index, (ty, data)) in output_values.iter_mut().enumerate() {
+            if !ty.has_safe_binary_representation() {
+                return Err(RecordingError::UnsupportedType { node_index, channel_index, ty: ty.clone() });
+            }
+
+            current_pass.values.push(RecordedValue {
+                node_index,
+                channel_index,
+                ty_tag: ty.to_string(),
+                data: (&*data).into(),
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn end_pass(&mut self) -> Result<(), RecordingError> {
+        if let Some(current_pass) = self.current_pass.take() {
+            current_pass.write_to(&mut self.writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(error) = self.writer.flush() {
+            error!(Category::Executor, "Could not finish writing the recording: {}", error);
+        }
+    }
+}
+
+/// Injects the outputs of source nodes from a recording, instead of executing them.
+pub struct Replayer {
+    passes: VecDeque<RecordedPass>,
+    current_pass: Option<RecordedPass>,
+}
+
+impl Replayer {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+
+        reader.read_exact(&mut magic)?;
+
+        if &magic != MAGIC || reader.read_u16::<LittleEndian>()? != VERSION {
+            return Err(RecordingError::InvalidFormat);
+        }
+
+        let mut passes = VecDeque::new();
+
+        while let Some(pass) = RecordedPass::read_from(&mut reader)? {
+            passes.push_back(pass);
+        }
+
+        Ok(Self { passes, current_pass: None })
+    }
+
+    /// Returns `false` if there are no more recorded passes to replay.
+    pub fn begin_pass(&mut self) -> bool {
+        self.current_pass = self.passes.pop_front();
+        self.current_pass.is_some()
+    }
+
+    /// Writes the recorded output values of the source node into `output_values`.
+    /// Returns `false` if the current pass contains no values of the node, in which case
+    /// the node should be executed instead.
+    pub fn replay_task(
+        &self,
+        node_index: NodeIndex,
+        output_values: &mut OutputValues,
+    ) -> Result<bool, RecordingError> {
+        let current_pass = if let Some(current_pass) = self.current_pass.as_ref() {
+            current_pass
+        } else {
+            return Ok(false);
+        };
+        let mut replayed = false;
+
+        for value in current_pass.values.iter().filter(|value| value.node_index == node_index) {
+            let (ty, bytes) = if let Some(output_value) = output_values.get_mut(value.channel_index) {
+                output_value
+            } else {
+                continue;
+            };
+            let ty_tag = ty.to_string();
+
+            if ty_tag != value.ty_tag {
+                return Err(RecordingError::TypeMismatch {
+                    node_index,
+                    channel_index: value.channel_index,
+                    expected: ty_tag,
+                    found: value.ty_tag.clone(),
+                });
+            }
+
+            if bytes.len() != value.data.len() {
+                return Err(RecordingError::InvalidFormat);
+            }
+
+            bytes.copy_from_slice(&value.data);
+
+            replayed = true;
+        }
+
+        Ok(replayed)
+    }
+}
+
+pub enum RecordingMode {
+    Record(Recorder),
+    Replay(Replayer),
+}
+
+impl RecordingMode {
+    /// Parses the `--record <path>` and `--replay <path>` command-line arguments.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, RecordingError> {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" => {
+                    if let Some(path) = args.next() {
+                        return Ok(Some(RecordingMode::Record(Recorder::create(path)?)));
+                    }
+                }
+                "--replay" => {
+                    if let Some(path) = args.next() {
+                        return Ok(Some(RecordingMode::Replay(Replayer::open(path)?)));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// What the executor does with the outputs of source nodes, see [`RecordingControl`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingStatus {
+    Idle,
+    Recording,
+    Replaying,
+}
+
+impl RecordingStatus {
+    fn of(mode: &Option<RecordingMode>) -> Self {
+        match mode {
+            None => RecordingStatus::Idle,
+            Some(RecordingMode::Record(_)) => RecordingStatus::Recording,
+            Some(RecordingMode::Replay(_)) => RecordingStatus::Replaying,
+        }
+    }
+}
+
+/// Starts and stops recordings and replays from the UI. The executor switches to the requested
+/// mode at the start of its next pass.
+#[derive(Default)]
+pub struct RecordingControl {
+    /// `Some(None)` if the current recording or replay is to be stopped.
+    requested: Mutex<Option<Option<RecordingMode>>>,
+    /// The [`RecordingStatus`] of the executor, as of its last pass.
+    status: AtomicU8,
+}
+
+impl RecordingControl {
+    /// Replaces the current recording or replay, if any, with `mode`.
+    pub fn request(&self, mode: Option<RecordingMode>) {
+        *self.requested.lock().unwrap() = Some(mode);
+    }
+
+    pub fn status(&self) -> RecordingStatus {
+        match self.status.load(Ordering::Relaxed) {
+            status if status == RecordingStatus::Recording as u8 => RecordingStatus::Recording,
+            status if status == RecordingStatus::Replaying as u8 => RecordingStatus::Replaying,
+            _ => RecordingStatus::Idle,
+        }
+    }
+
+    /// Applies the requested mode, if any, to the mode of the executor.
+    pub(crate) fn apply_request(&self, mode: &mut Option<RecordingMode>) {
+        if let Some(requested) = self.requested.lock().unwrap().take() {
+            *mode = requested;
+        }
+
+        self.update_status(mode);
+    }
+
+    pub(crate) fn update_status(&self, mode: &Option<RecordingMode>) {
+        self.status.store(RecordingStatus::of(mode) as u8, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{cpu_context, executor_lock, schedule};
+    use super::super::{
+        ApplicationContext, ChannelIdentifier, ExecutionGraph, PreparedExecution, Schedule, ValueSnapshot,
+    };
+    use super::*;
+    use crate::node::behaviour::{BinaryOpNodeBehaviour, CounterNodeBehaviour, DebugNodeBehaviour};
+    use crate::node::{ChannelDirection, ChannelPassBy, PrimitiveChannelValue, PrimitiveTypeEnum};
+    use crate::template::GraphBuilder;
+    use iced_futures::futures::channel::mpsc::UnboundedReceiver;
+    use std::sync::Arc;
+
+    const PASSES: u64 = 5;
+
+    /// Two counters, summed and displayed by a debug node, along with the summing node.
+    fn counters_summed() -> (ExecutionGraph, NodeIndex) {
+        let mut builder = GraphBuilder::default();
+        let lhs = builder.node("Counter", [0.0, 0.0], CounterNodeBehaviour);
+        let rhs = builder.node("Counter", [0.0, 100.0], CounterNodeBehaviour);
+        let sum = builder.node(
+            "Binary Operation",
+            [200.0, 0.0],
+            BinaryOpNodeBehaviour { pick_list_ty_value: PrimitiveTypeEnum::U32, ..Default::default() },
+        );
+        let debug = builder.node("Debug", [400.0, 0.0], DebugNodeBehaviour::new(PrimitiveTypeEnum::U32));
+
+        builder.connect((lhs, 0), (sum, ChannelPassBy::Value, 0));
+        builder.connect((rhs, 0), (sum, ChannelPassBy::Value, 1));
+        builder.connect((sum, 0), (debug, ChannelPassBy::SharedReference, 0));
+
+        (builder.build().into(), sum)
+    }
+
+    /// Executes the schedule pass by pass as the executor does, taking the sum output by the node.
+    struct Passes {
+        schedule: Arc<Schedule>,
+        execution: PreparedExecution,
+        context: ApplicationContext,
+        snapshots: UnboundedReceiver<ValueSnapshot>,
+        sum: ChannelIdentifier,
+        pass_index: u64,
+    }
+
+    impl Passes {
+        fn new(mut context: ApplicationContext) -> Self {
+            let (mut graph, sum) = counters_summed();
+            let schedule = schedule(&mut graph);
+            let execution = PreparedExecution::from(&schedule, &mut context, None);
+            let snapshots = context.value_taps.take_receiver().unwrap();
+            let sum = ChannelIdentifier {
+                node_index: sum,
+                channel_direction: ChannelDirection::Out,
+                channel_index: 0,
+                pass_by: ChannelPassBy::Value,
+            };
+
+            Self { schedule, execution, context, snapshots, sum, pass_index: 0 }
+        }
+
+        fn next(&mut self, recording: &mut Option<RecordingMode>) -> PrimitiveChannelValue {
+            match recording {
+                Some(RecordingMode::Record(recorder)) => {
+                    recorder.begin_pass(self.schedule.generation, self.pass_index)
+                }
+                Some(RecordingMode::Replay(replayer)) => {
+                    if !replayer.begin_pass() {
+                        *recording = None;
+                    }
+                }
+                None => (),
+            }
+
+            self.context.value_taps.request(self.sum);
+            self.execution.execute(&self.schedule, &mut self.context, recording);
+
+            if let Some(RecordingMode::Record(recorder)) = recording {
+                recorder.end_pass().unwrap();
+            }
+
+            self.pass_index += 1;
+            self.snapshots.try_next().unwrap().unwrap().value.unwrap()
+        }
+    }
+
+    /// The replayed sums are the recorded ones, even though the counters have advanced since, as
+    /// source nodes are not executed until the replay ends.
+    #[test]
+    fn replays_the_recorded_outputs_of_source_nodes() {
+        let _lock = executor_lock();
+        let mut passes = Passes::new(cpu_context());
+        let path = std::env::temp_dir().join(format!("dvsynth-recording-{}.dvsr", std::process::id()));
+        let mut recording = Some(RecordingMode::Record(Recorder::create(&path).unwrap()));
+        let recorded = (0..PASSES).map(|_| passes.next(&mut recording)).collect::<Vec<_>>();
+        let expected =
+            (0..PASSES as u32).map(|count| PrimitiveChannelValue::U32(2 * count)).collect::<Vec<_>>();
+
+        assert_eq!(recorded, expected);
+
+        // Flushes the recording.
+        drop(recording);
+
+        let mut recording = Some(RecordingMode::Replay(Replayer::open(&path).unwrap()));
+        let replayed = (0..PASSES).map(|_| passes.next(&mut recording)).collect::<Vec<_>>();
+
+        assert_eq!(replayed, recorded);
+
+        let live = passes.next(&mut recording);
+
+        assert!(recording.is_none());
+        assert_eq!(live, PrimitiveChannelValue::U32(2 * PASSES as u32));
+        assert!(passes.context.node_errors.get_all().is_empty());
+
+        passes.execution.release();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+//! Replacement of the non-finite floating-point values output by the tasks, while
+//! [`ExecutionGraph::sanitize_numerics`] is enabled, so that a single NaN does not poison the state
+//! of every node downstream.
+//!
+//! Only outputs of the `f32` and `f64` primitive types and of arrays of at most
+//! [`MAX_ARRAY_LEN`] of them are checked. Lists, textures and other allocated values are passed on
+//! as they are, as checking them would cost more than the execution of most nodes.
+//!
+//! [`ExecutionGraph::sanitize_numerics`]: super::ExecutionGraph::sanitize_numerics
+
+use crate::node::ty::TypeEnum;
+use crate::node::PrimitiveTypeEnum;
+use std::convert::TryInto;
+
+/// The longest arrays whose items are checked.
+pub const MAX_ARRAY_LEN: usize = 64;
+
+/// How the bytes of a checked output value are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatLayout {
+    /// The number of consecutive `f32` values.
+    F32(usize),
+    /// The number of consecutive `f64` values.
+    F64(usize),
+}
+
+impl FloatLayout {
+    /// The layout of the values of the type, `None` if they are not checked.
+    pub fn of(ty: &TypeEnum) -> Option<Self> {
+        let (item_type, len) = match ty {
+            TypeEnum::Array(array) if array.len <= MAX_ARRAY_LEN => (&*array.item_type, array.len),
+            ty => (ty, 1),
+        };
+
+        match item_type.as_primitive_type_enum()? {
+            PrimitiveTypeEnum::F32 => Some(FloatLayout::F32(len)),
+            PrimitiveTypeEnum::F64 => Some(FloatLayout::F64(len)),
+            _ => None,
+        }
+    }
+
+    /// Replaces the non-finite values stored in the little-endian bytes with the substitute.
+    /// Returns the number of replaced values.
+    pub fn sanitize(self, bytes: &mut [u8], substitute: f64) -> u64 {
+        match self {
+            FloatLayout::F32(len) => sanitize_f32(&mut bytes[..len * 4], substitute as f32),
+            FloatLayout::F64(len) => sanitize_f64(&mut bytes[..len * 8], substitute),
+        }
+    }
+}
+
+// The values are neither aligned nor necessarily in the native byte order, so they are converted
+// one by one. The loops do not branch on the values, which lets them be vectorized.
+
+fn sanitize_f32(bytes: &mut [u8], substitute: f32) -> u64 {
+    let mut replaced = 0;
+
+    for chunk in bytes.chunks_exact_mut(4) {
+        let value = f32::from_le_bytes(chunk.try_into().unwrap());
+        let finite = value.is_finite();
+
+        replaced += !finite as u64;
+        chunk.copy_from_slice(&if finite { value } else { substitute }.to_le_bytes());
+    }
+
+    replaced
+}
+
+fn sanitize_f64(bytes: &mut [u8], substitute: f64) -> u64 {
+    let mut replaced = 0;
+
+    for chunk in bytes.chunks_exact_mut(8) {
+        let value = f64::from_le_bytes(chunk.try_into().unwrap());
+        let finite = value.is_finite();
+
+        replaced += !finite as u64;
+        chunk.copy_from_slice(&if finite { value } else { substitute }.to_le_bytes());
+    }
+
+    replaced
+}
@@ -0,0 +1,64 @@
+//! Helpers for the tests and benchmarks executing graphs.
+
+use super::controller::GraphCommand;
+use super::{ApplicationContext, Connection, EdgeEndpoint, ExecutionGraph, PreparedExecution, Schedule};
+use crate::config::{Config, SharedConfig};
+use crate::node::behaviour::create_behaviour;
+use crate::node::{ChannelPassBy, NodeIndex};
+use std::sync::{Arc, Mutex, MutexGuard};
+use vek::Vec2;
+
+/// Adds a node with the default behaviour of the given name.
+pub fn add(graph: &mut ExecutionGraph, name: &str) -> NodeIndex {
+    let behaviour = create_behaviour(name).unwrap();
+    let command = GraphCommand::AddNode { title: name.to_string(), position: Vec2::zero(), behaviour };
+
+    command.apply(graph).unwrap().added_node().unwrap()
+}
+
+/// Connects the first output of `from` to the first input of `to`, both passed by value.
+pub fn connection(from: NodeIndex, to: NodeIndex) -> Connection {
+    let endpoint = EdgeEndpoint { channel_index: 0, pass_by: ChannelPassBy::Value };
+
+    Connection([endpoint.into_undirected_identifier(from), endpoint.into_undirected_identifier(to)])
+}
+
+pub fn connect(graph: &mut ExecutionGraph, from: NodeIndex, to: NodeIndex) {
+    GraphCommand::InsertEdge { connection: connection(from, to), primary: false }.apply(graph).unwrap();
+}
+
+/// Serializes the tests executing schedules, as the refcounters of the allocator are shared by all
+/// schedules.
+pub fn executor_lock() -> MutexGuard<'static, ()> {
+    lazy_static! {
+        static ref LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    LOCK.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+/// A context for executing graphs of nodes not using the GPU on the current thread, without
+/// acquiring a GPU, so that the tests executing them run on any machine.
+pub fn cpu_context() -> ApplicationContext {
+    ApplicationContext::headless(None, Arc::new(SharedConfig::new(Config::default())))
+}
+
+/// Schedules the graph, which must be valid.
+pub fn schedule(graph: &mut ExecutionGraph) -> Arc<Schedule> {
+    if let Err(errors) = graph.update_schedule() {
+        panic!("The graph could not be scheduled: {:?}", errors);
+    }
+
+    graph.active_schedule.load_full().unwrap()
+}
+
+/// Executes the schedule for the given number of passes, with the execution prepared anew.
+pub fn execute(schedule: &Schedule, context: &mut ApplicationContext, passes: usize) -> PreparedExecution {
+    let mut prepared_execution = PreparedExecution::from(schedule, context, None);
+
+    for _ in 0..passes {
+        prepared_execution.execute(schedule, context, &mut None);
+    }
+
+    prepared_execution
+}
@@ -0,0 +1,280 @@
+//! Caching of the checks performed by [`ExecutionGraph::check_graph_validity`], so that an edit
+//! only repeats the checks of the part of the graph it affected.
+//!
+//! Entries are invalidated by the commands editing the graph, see
+//! [`GraphCommand::apply`](super::controller::GraphCommand::apply), which report the nodes they
+//! have touched. Touching a node invalidates:
+//! - the check of the node, which reads its behaviour, its settings and its configuration,
+//! - the checks of the nodes downstream of it, which read the types of their incoming connections,
+//! - the checks of all of its connections, which read the types of both channels and whether the
+//!   output is aliased by the other connections of the node.
+//!
+//! Adding or removing nodes and connections also invalidates the topological order. The checks
+//! of the elements that have not been invalidated are reused without reading anything else. An
+//! invalidated connection is only checked again if the inputs of its check, its [`ConnectionKey`],
+//! differ from the ones it has been checked with.
+//!
+//! Entries of removed nodes and connections are dropped by the next validation.
+//!
+//! [`ExecutionGraph::check_graph_validity`]: super::ExecutionGraph::check_graph_validity
+
+use super::{Connection, GraphValidationError, NodeIndex};
+use crate::node::TypeEnum;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+/// The problems found by a single check, in the order they were found.
+#[derive(Debug, Clone, Default)]
+pub struct CheckResult {
+    pub errors: Vec<GraphValidationError>,
+    pub warnings: Vec<GraphValidationError>,
+}
+
+/// Everything the check of a connection reads, besides the connection itself. Compared as a whole,
+/// so that different inputs cannot be mistaken for each other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionKey {
+    pub from_ty: TypeEnum,
+    pub to_ty: TypeEnum,
+    pub aliased: bool,
+    pub implicit_promotion: bool,
+}
+
+#[derive(Debug, Clone)]
+struct CachedConnectionCheck {
+    key: ConnectionKey,
+    result: CheckResult,
+}
+
+/// The results of the checks of a kind of element, keyed by the elements.
+#[derive(Debug, Clone)]
+struct CheckCache<E, C> {
+    /// The results of the checks performed or reused by the current validation.
+    current: HashMap<E, C>,
+    /// The results of the previous validation, not looked up yet by the current one.
+    previous: HashMap<E, C>,
+}
+
+impl<E, C> Default for CheckCache<E, C> {
+    fn default() -> Self {
+        Self { current: HashMap::new(), previous: HashMap::new() }
+    }
+}
+
+impl<E: std::hash::Hash + Eq, C> CheckCache<E, C> {
+    /// Starts a validation. Entries of elements not checked until the next one are dropped.
+    fn begin(&mut self) {
+        self.previous = mem::take(&mut self.current);
+    }
+}
+
+/// The cached results of the checks of a graph, see the [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct ValidationCache {
+    nodes: CheckCache<NodeIndex, CheckResult>,
+    connections: CheckCache<Connection, CachedConnectionCheck>,
+    /// The nodes whose checks have been invalidated since the previous validation.
+    invalidated_nodes: HashSet<NodeIndex>,
+    /// The nodes whose connections have been invalidated since the previous validation.
+    invalidated_connections: HashSet<NodeIndex>,
+    /// The sorted nodes or the cycles found, `None` if nodes or connections have been added or
+    /// removed since.
+    topological_order: Option<Result<Vec<NodeIndex>, Vec<GraphValidationError>>>,
+}
+
+impl ValidationCache {
+    /// Invalidates the checks reading the node, see the [module documentation](self).
+    /// `downstream` are the nodes connected to the outputs of the node.
+    pub fn invalidate_node(&mut self, node: NodeIndex, downstream: impl IntoIterator<Item = NodeIndex>) {
+        self.invalidated_nodes.insert(node);
+        self.invalidated_nodes.extend(downstream);
+        self.invalidated_connections.insert(node);
+    }
+
+    /// Invalidates the order of the nodes, once nodes or connections have been added or removed.
+    pub fn invalidate_topology(&mut self) {
+        self.topological_order = None;
+    }
+
+    /// Invalidates all checks, such as when implicit promotion is toggled, which affects all
+    /// connections.
+    pub fn invalidate_all(&mut self) {
+        *self = Default::default();
+    }
+
+    /// Starts a validation of the whole graph.
+    pub fn begin(&mut self) {
+        self.nodes.begin();
+        self.connections.begin();
+    }
+
+    /// Finishes the validation, after all nodes and connections have been checked.
+    pub fn end(&mut self) {
+        self.invalidated_nodes.clear();
+        self.invalidated_connections.clear();
+        self.nodes.previous.clear();
+        self.connections.previous.clear();
+    }
+
+    pub fn check_node(&mut self, node: NodeIndex, check: impl FnOnce() -> CheckResult) -> &CheckResult {
+        let cached = self.nodes.previous.remove(&node).filter(|_| !self.invalidated_nodes.contains(&node));
+        let result = cached.unwrap_or_else(check);
+
+        self.nodes.current.entry(node).or_insert(result)
+    }
+
+    /// The key is only computed for invalidated connections.
+    pub fn check_connection(
+        &mut self,
+        connection: &Connection,
+        key: impl FnOnce() -> ConnectionKey,
+        check: impl FnOnce() -> CheckResult,
+    ) -> &CheckResult {
+        let invalidated = self.invalidated_connections.contains(&connection.from().node_index)
+            || self.invalidated_connections.contains(&connection.to().node_index);
+        let cached = match self.connections.previous.remove(connection) {
+            Some(cached) if !invalidated => cached,
+            cached => {
+                let key = key();
+
+                match cached {
+                    Some(cached) if cached.key == key => cached,
+                    _ => CachedConnectionCheck { key, result: check() },
+                }
+            }
+        };
+
+        &self.connections.current.entry(connection.clone()).or_insert(cached).result
+    }
+
+    /// Sorts the nodes topologically, unless no nodes or connections have been added or removed
+    /// since they were sorted last, so that cycles are only searched for once the topology changes.
+    pub fn topological_order(
+        &mut self,
+        sort: impl FnOnce() -> Result<Vec<NodeIndex>, Vec<GraphValidationError>>,
+    ) -> Result<Vec<NodeIndex>, Vec<GraphValidationError>> {
+        self.topological_order.get_or_insert_with(sort).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::controller::GraphCommand;
+    use super::super::{EdgeData, EdgeEndpoint, ExecutionGraph, Graph, NodeData};
+    use super::*;
+    use crate::node::behaviour::create_behaviour;
+    use crate::node::ChannelPassBy;
+    use test::Bencher;
+
+    const CHAIN_LENGTH: usize = 500;
+
+    fn value_endpoint() -> EdgeEndpoint {
+        EdgeEndpoint { channel_index: 0, pass_by: ChannelPassBy::Value }
+    }
+
+    /// Nodes each connected to the previous one, along with the connections.
+    fn chain(length: usize) -> (ExecutionGraph, Vec<Connection>) {
+        let mut graph = Graph::new();
+        let nodes = (0..length)
+            .map(|index| {
+                let position = [index as f32 * 200.0, 0.0];
+
+                graph.add_node(NodeData::new("Abs", position, create_behaviour("Abs").unwrap()))
+            })
+            .collect::<Vec<_>>();
+        let connections = nodes
+            .array_windows()
+            .map(|[from, to]| {
+                graph.add_edge(
+                    *from,
+                    *to,
+                    EdgeData {
+                        endpoint_from: value_endpoint(),
+                        endpoint_to: value_endpoint(),
+                        primary: false,
+                    },
+                );
+
+                Connection([
+                    value_endpoint().into_undirected_identifier(*from),
+                    value_endpoint().into_undirected_identifier(*to),
+                ])
+            })
+            .collect();
+
+        (graph.into(), connections)
+    }
+
+    fn assert_matches_full_validation(graph: &ExecutionGraph) {
+        let cached = graph.check_graph_validity();
+
+        assert_eq!(format!("{:?}", cached), format!("{:?}", graph.validate(None)));
+    }
+
+    #[test]
+    fn cached_validation_matches_full_validation_after_edits() {
+        let (mut graph, connections) = chain(8);
+        let middle = connections[4].clone();
+
+        assert_matches_full_validation(&graph);
+
+        GraphCommand::RemoveEdge { connection: middle.clone() }.apply(&mut graph).unwrap();
+        assert_matches_full_validation(&graph);
+
+        GraphCommand::InsertEdge { connection: middle.clone(), primary: false }.apply(&mut graph).unwrap();
+        assert_matches_full_validation(&graph);
+
+        // The added node takes over the index of the removed one, whose cached checks must not be
+        // reused.
+        let removed = middle.to().node_index;
+        let behaviour = create_behaviour("Integer Abs").unwrap();
+
+        GraphCommand::RemoveNode { node: removed }.apply(&mut graph).unwrap();
+        assert_matches_full_validation(&graph);
+
+        let command = GraphCommand::AddNode {
+            title: "Integer Abs".to_string(),
+            position: [0.0, 0.0].into(),
+            behaviour,
+        };
+        let added = command.apply(&mut graph).unwrap().added_node().unwrap();
+
+        assert_eq!(added, removed);
+        assert_matches_full_validation(&graph);
+
+        GraphCommand::RemoveNode { node: added }.apply(&mut graph).unwrap();
+        assert_matches_full_validation(&graph);
+    }
+
+    /// Disconnects and reconnects a node in the middle of the chain, validating the graph after
+    /// each edit.
+    fn bench_edits(bencher: &mut Bencher, validate: impl Fn(&ExecutionGraph)) {
+        let (mut graph, connections) = chain(CHAIN_LENGTH);
+        let middle = connections[CHAIN_LENGTH / 2].clone();
+
+        validate(&graph);
+
+        bencher.iter(|| {
+            GraphCommand::RemoveEdge { connection: middle.clone() }.apply(&mut graph).unwrap();
+            validate(&graph);
+            GraphCommand::InsertEdge { connection: middle.clone(), primary: false }
+                .apply(&mut graph)
+                .unwrap();
+            validate(&graph);
+        });
+    }
+
+    #[bench]
+    fn validate_500_nodes_cached(bencher: &mut Bencher) {
+        bench_edits(bencher, |graph| {
+            test::black_box(graph.check_graph_validity().unwrap());
+        });
+    }
+
+    #[bench]
+    fn validate_500_nodes_full(bencher: &mut Bencher) {
+        bench_edits(bencher, |graph| {
+            test::black_box(graph.validate(None).unwrap());
+        });
+    }
+}
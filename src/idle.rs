@@ -0,0 +1,225 @@
+//! Idle detection of the event loop, so that the editor sleeps instead of polling while nothing it
+//! displays changes.
+//!
+//! The event loop wakes up for:
+//! - input and other events of the windows, delivered by winit,
+//! - messages of the application, including those of the subscriptions, which iced delivers
+//!   through its event loop proxy,
+//! - tasks submitted by the executor to the main thread, which wake the event loop through the
+//!   [`MainThreadWaker`], whose wakeups are delivered as messages by a subscription.
+//!
+//! Otherwise, the event loop waits without a timeout. While any of them arrived within the
+//! [`ACTIVITY_GRACE_PERIOD`], the event loop polls, so that gestures and the main thread tasks they
+//! cause are not delayed.
+
+use iced_futures::futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use iced_winit::winit::event::Event;
+use iced_winit::winit::event_loop::ControlFlow;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long the event loop keeps polling after the last activity.
+pub const ACTIVITY_GRACE_PERIOD: Duration = Duration::from_millis(250);
+/// The window the frame rate is averaged over.
+const FRAME_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// The number of frames of the UI drawn per second, as last measured by the [`IdleTracker`].
+#[derive(Debug, Clone, Default)]
+pub struct FrameRate(Arc<AtomicU64>);
+
+impl FrameRate {
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, frame_rate: f64) {
+        self.0.store(frame_rate.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Tracks the activity seen by the event loop and decides how long it may sleep. Owned by the
+/// event handler of the event loop.
+#[derive(Debug)]
+pub struct IdleTracker {
+    last_activity: Instant,
+    /// Whether a window has been redrawn since the events were last cleared.
+    redrawn: bool,
+    frames: u32,
+    frames_since: Instant,
+    frame_rate: FrameRate,
+}
+
+impl IdleTracker {
+    pub fn new(frame_rate: FrameRate) -> Self {
+        let now = Instant::now();
+
+        Self { last_activity: now, redrawn: false, frames: 0, frames_since: now, frame_rate }
+    }
+
+    /// Records the activity caused by the event, if any. Device events are left out, as they are
+    /// also received while the windows are not focused.
+    pub fn observe<T>(&mut self, event: &Event<'_, T>) {
+        match event {
+            Event::WindowEvent { .. } | Event::UserEvent(_) => self.wake(),
+            Event::RedrawRequested(_) => self.redrawn = true,
+            Event::RedrawEventsCleared if self.redrawn => {
+                self.redrawn = false;
+                self.count_frame();
+            }
+            _ => (),
+        }
+    }
+
+    /// Records an activity not caused by an event, such as a main thread task being run.
+    pub fn wake(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// Polls while there has been recent activity, waits for the next event otherwise.
+    pub fn control_flow(&self) -> ControlFlow {
+        self.control_flow_at(Instant::now())
+    }
+
+    fn control_flow_at(&self, now: Instant) -> ControlFlow {
+        if now.saturating_duration_since(self.last_activity) < ACTIVITY_GRACE_PERIOD {
+            ControlFlow::Poll
+        } else {
+            ControlFlow::Wait
+        }
+    }
+
+    /// The frame rate is averaged over the time since the previous measurement, so that the
+    /// frames drawn after being idle for a while lower it accordingly.
+    fn count_frame(&mut self) {
+        let elapsed = self.frames_since.elapsed();
+
+        self.frames += 1;
+
+        if elapsed >= FRAME_RATE_WINDOW {
+            self.frame_rate.set(self.frames as f64 / elapsed.as_secs_f64());
+            self.frames = 0;
+            self.frames_since = Instant::now();
+        }
+    }
+}
+
+/// Wakes the event loop once tasks have been submitted to the main thread, from any thread.
+/// The wakeups are coalesced until the event loop runs the submitted tasks.
+#[derive(Debug)]
+pub struct MainThreadWaker {
+    /// Whether a wakeup has been sent since the event loop last ran the submitted tasks.
+    pending: AtomicBool,
+    sender: UnboundedSender<()>,
+    /// Taken by the subscription of the application.
+    receiver: Mutex<Option<UnboundedReceiver<()>>>,
+}
+
+impl Default for MainThreadWaker {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::unbounded();
+
+        Self { pending: AtomicBool::new(false), sender, receiver: Mutex::new(Some(receiver)) }
+    }
+}
+
+impl MainThreadWaker {
+    /// Called once a task has been submitted to the main thread.
+    pub fn wake(&self) {
+        if !self.pending.swap(true, Ordering::SeqCst) {
+            // The application may have been closed already.
+            let _ = self.sender.unbounded_send(());
+        }
+    }
+
+    /// Called by the event loop before it runs the submitted tasks, so that the tasks submitted
+    /// afterwards wake it again.
+    pub fn clear(&self) {
+        self.pending.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns the receiver of the wakeups, which can only be taken once.
+    pub fn take_receiver(&self) -> Option<UnboundedReceiver<()>> {
+        self.receiver.lock().unwrap().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced_winit::winit::event::{DeviceEvent, DeviceId, WindowEvent};
+    use iced_winit::winit::window::WindowId;
+
+    /// A tracker along with a time at which its last activity lies beyond the grace period.
+    fn idle_tracker() -> (IdleTracker, Instant) {
+        let tracker = IdleTracker::new(FrameRate::default());
+        let now = tracker.last_activity + ACTIVITY_GRACE_PERIOD * 2;
+
+        (tracker, now)
+    }
+
+    fn window_event() -> Event<'static, ()> {
+        Event::WindowEvent { window_id: unsafe { WindowId::dummy() }, event: WindowEvent::Focused(true) }
+    }
+
+    fn device_event() -> Event<'static, ()> {
+        Event::DeviceEvent { device_id: unsafe { DeviceId::dummy() }, event: DeviceEvent::Added }
+    }
+
+    #[test]
+    fn waits_without_timeout_once_idle() {
+        let (tracker, now) = idle_tracker();
+
+        assert_eq!(tracker.control_flow_at(tracker.last_activity), ControlFlow::Poll);
+        assert_eq!(tracker.control_flow_at(now), ControlFlow::Wait);
+    }
+
+    #[test]
+    fn window_events_and_messages_wake_the_event_loop() {
+        for event in vec![window_event(), Event::UserEvent(())] {
+            let (mut tracker, _) = idle_tracker();
+
+            tracker.observe(&event);
+            assert_eq!(tracker.control_flow_at(tracker.last_activity), ControlFlow::Poll);
+            assert_eq!(
+                tracker.control_flow_at(tracker.last_activity + ACTIVITY_GRACE_PERIOD),
+                ControlFlow::Wait
+            );
+        }
+    }
+
+    #[test]
+    fn device_events_do_not_wake_the_event_loop() {
+        let (mut tracker, now) = idle_tracker();
+        let last_activity = tracker.last_activity;
+
+        tracker.observe(&device_event());
+        tracker.observe(&Event::MainEventsCleared);
+        assert_eq!(tracker.last_activity, last_activity);
+        assert_eq!(tracker.control_flow_at(now), ControlFlow::Wait);
+    }
+
+    #[test]
+    fn main_thread_tasks_wake_the_event_loop() {
+        let (mut tracker, _) = idle_tracker();
+
+        tracker.wake();
+        assert_eq!(tracker.control_flow_at(tracker.last_activity), ControlFlow::Poll);
+    }
+
+    #[test]
+    fn wakeups_are_coalesced_until_cleared() {
+        let waker = MainThreadWaker::default();
+        let mut receiver = waker.take_receiver().unwrap();
+
+        waker.wake();
+        waker.wake();
+        assert_eq!(receiver.try_next().unwrap(), Some(()));
+        assert!(receiver.try_next().is_err());
+
+        waker.clear();
+        waker.wake();
+        assert_eq!(receiver.try_next().unwrap(), Some(()));
+        assert!(waker.take_receiver().is_none());
+    }
+}
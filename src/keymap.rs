@@ -0,0 +1,523 @@
+//! Keyboard shortcuts, which can be remapped in the preferences.
+//!
+//! Event handlers look up the [`Action`] bound to a key press in the [`Keymap`] of the config,
+//! instead of matching the key codes themselves.
+
+use crate::logging::Category;
+use iced::keyboard::{KeyCode, ModifiersState};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The actions that can be bound to shortcuts. If multiple actions are bound to the same
+/// shortcut, the one declared first takes precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    TogglePreferences,
+    SaveGraph,
+    /// Lists the changes since the graph was last saved.
+    CompareWithSaved,
+    OpenGraph,
+    /// Shows the recently opened graphs and the templates to start a new graph from.
+    ToggleStartScreen,
+    /// Lists the kinds of nodes to insert into the focused graph.
+    ToggleNodePalette,
+    /// Opens an empty graph in a new tab.
+    NewDocument,
+    /// Closes the focused tab.
+    CloseDocument,
+    ExportGraphImage,
+    /// Writes the last sampled execution statistics into a file.
+    ExportStatistics,
+    /// Allows or disallows connections to inputs of wider primitive types in the focused graph.
+    ToggleImplicitPromotion,
+    /// Replaces the non-finite floating-point values output by the nodes of the focused graph, or
+    /// stops replacing them.
+    ToggleSanitizeNumerics,
+    /// Records the outputs of the source nodes of the focused graph into a file, or stops recording.
+    ToggleRecording,
+    /// Replays a recording into the focused graph, or stops the replay.
+    ToggleReplay,
+    /// Numbers the nodes in the order they are executed in.
+    ToggleExecutionOrder,
+    /// Recreates the most recently removed connection.
+    ReconnectLast,
+    /// Collapses all nodes of the focused graph, or expands them, if all of them are collapsed.
+    ToggleCollapseAll,
+    /// Saves the selected nodes into the library, to be inserted from the node palette.
+    SaveSelectionToLibrary,
+    /// Switches all A/B switch nodes of the focused graph to their other input at once.
+    ToggleABSwitches,
+    /// Removes the node whose title bar is hovered.
+    RemoveNode,
+    /// Aligns the left edges of the selected nodes.
+    AlignLeft,
+    AlignRight,
+    AlignTop,
+    AlignBottom,
+    /// Centers the selected nodes on a vertical line.
+    AlignHorizontalCenters,
+    /// Centers the selected nodes on a horizontal line.
+    AlignVerticalCenters,
+    /// Spaces the selected nodes evenly between the leftmost and the rightmost one.
+    DistributeHorizontally,
+    /// Spaces the selected nodes evenly between the topmost and the bottommost one.
+    DistributeVertically,
+    /// Confirms the open dialog.
+    Confirm,
+    /// Cancels the open dialog or the pending connection.
+    Cancel,
+    Quit,
+}
+
+impl Action {
+    pub const VALUES: [Action; 31] = [
+        Action::TogglePreferences,
+        Action::SaveGraph,
+        Action::CompareWithSaved,
+        Action::OpenGraph,
+        Action::ToggleStartScreen,
+        Action::ToggleNodePalette,
+        Action::NewDocument,
+        Action::CloseDocument,
+        Action::ExportGraphImage,
+        Action::ExportStatistics,
+        Action::ToggleImplicitPromotion,
+        Action::ToggleSanitizeNumerics,
+        Action::ToggleRecording,
+        Action::ToggleReplay,
+        Action::ToggleExecutionOrder,
+        Action::ReconnectLast,
+        Action::ToggleCollapseAll,
+        Action::SaveSelectionToLibrary,
+        Action::ToggleABSwitches,
+        Action::RemoveNode,
+        Action::AlignLeft,
+        Action::AlignRight,
+        Action::AlignTop,
+        Action::AlignBottom,
+        Action::AlignHorizontalCenters,
+        Action::AlignVerticalCenters,
+        Action::DistributeHorizontally,
+        Action::DistributeVertically,
+        Action::Confirm,
+        Action::Cancel,
+        Action::Quit,
+    ];
+
+    /// The name of the action in the config file.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Action::TogglePreferences => "toggle_preferences",
+            Action::SaveGraph => "save_graph",
+            Action::CompareWithSaved => "compare_with_saved",
+            Action::OpenGraph => "open_graph",
+            Action::ToggleStartScreen => "toggle_start_screen",
+            Action::ToggleNodePalette => "toggle_node_palette",
+            Action::NewDocument => "new_document",
+            Action::CloseDocument => "close_document",
+            Action::ExportGraphImage => "export_graph_image",
+            Action::ExportStatistics => "export_statistics",
+            Action::ToggleImplicitPromotion => "toggle_implicit_promotion",
+            Action::ToggleSanitizeNumerics => "toggle_sanitize_numerics",
+            Action::ToggleRecording => "toggle_recording",
+            Action::ToggleReplay => "toggle_replay",
+            Action::ToggleExecutionOrder => "toggle_execution_order",
+            Action::ReconnectLast => "reconnect_last",
+            Action::ToggleCollapseAll => "toggle_collapse_all",
+            Action::SaveSelectionToLibrary => "save_selection_to_library",
+            Action::ToggleABSwitches => "toggle_ab_switches",
+            Action::RemoveNode => "remove_node",
+            Action::AlignLeft => "align_left",
+            Action::AlignRight => "align_right",
+            Action::AlignTop => "align_top",
+            Action::AlignBottom => "align_bottom",
+            Action::AlignHorizontalCenters => "align_horizontal_centers",
+            Action::AlignVerticalCenters => "align_vertical_centers",
+            Action::DistributeHorizontally => "distribute_horizontally",
+            Action::DistributeVertically => "distribute_vertically",
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::VALUES.iter().copied().find(|action| action.key() == key)
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::TogglePreferences => write!(f, "Toggle preferences"),
+            Action::SaveGraph => write!(f, "Save graph"),
+            Action::CompareWithSaved => write!(f, "Compare with saved graph"),
+            Action::OpenGraph => write!(f, "Open graph"),
+            Action::ToggleStartScreen => write!(f, "Open recent graph or template"),
+            Action::ToggleNodePalette => write!(f, "Insert node"),
+            Action::NewDocument => write!(f, "New tab"),
+            Action::CloseDocument => write!(f, "Close tab"),
+            Action::ExportGraphImage => write!(f, "Export graph image"),
+            Action::ExportStatistics => write!(f, "Export execution statistics"),
+            Action::ToggleImplicitPromotion => write!(f, "Toggle implicit type promotion"),
+            Action::ToggleSanitizeNumerics => write!(f, "Toggle replacing non-finite values"),
+            Action::ToggleRecording => write!(f, "Start or stop recording source nodes"),
+            Action::ToggleReplay => write!(f, "Start or stop replaying a recording"),
+            Action::ToggleExecutionOrder => write!(f, "Show or hide the execution order"),
+            Action::ReconnectLast => write!(f, "Reconnect last removed connection"),
+            Action::ToggleCollapseAll => write!(f, "Collapse or expand all nodes"),
+            Action::SaveSelectionToLibrary => write!(f, "Save selected nodes to library"),
+            Action::ToggleABSwitches => write!(f, "Toggle all A/B switches"),
+            Action::RemoveNode => write!(f, "Remove hovered node"),
+            Action::AlignLeft => write!(f, "Align selected nodes left"),
+            Action::AlignRight => write!(f, "Align selected nodes right"),
+            Action::AlignTop => write!(f, "Align selected nodes to the top"),
+            Action::AlignBottom => write!(f, "Align selected nodes to the bottom"),
+            Action::AlignHorizontalCenters => write!(f, "Align horizontal centers of selected nodes"),
+            Action::AlignVerticalCenters => write!(f, "Align vertical centers of selected nodes"),
+            Action::DistributeHorizontally => write!(f, "Distribute selected nodes horizontally"),
+            Action::DistributeVertically => write!(f, "Distribute selected nodes vertically"),
+            Action::Confirm => write!(f, "Confirm dialog"),
+            Action::Cancel => write!(f, "Cancel dialog or connection"),
+            Action::Quit => write!(f, "Quit"),
+        }
+    }
+}
+
+macro_rules! keys {
+    ($($key:ident => $name:literal),*$(,)?) => {
+        fn key_name(key: KeyCode) -> Option<&'static str> {
+            match key {
+                $(KeyCode::$key => Some($name),)*
+                _ => None,
+            }
+        }
+
+        fn parse_key(name: &str) -> Option<KeyCode> {
+            $(
+                if name.eq_ignore_ascii_case($name) {
+                    return Some(KeyCode::$key);
+                }
+            )*
+            None
+        }
+    };
+}
+
+// Modifier keys are left out, so that they cannot be bound on their own.
+keys! {
+    A => "A", B => "B", C => "C", D => "D", E => "E", F => "F", G => "G", H => "H", I => "I",
+    J => "J", K => "K", L => "L", M => "M", N => "N", O => "O", P => "P", Q => "Q", R => "R",
+    S => "S", T => "T", U => "U", V => "V", W => "W", X => "X", Y => "Y", Z => "Z",
+    Key0 => "0", Key1 => "1", Key2 => "2", Key3 => "3", Key4 => "4", Key5 => "5", Key6 => "6",
+    Key7 => "7", Key8 => "8", Key9 => "9",
+    F1 => "F1", F2 => "F2", F3 => "F3", F4 => "F4", F5 => "F5", F6 => "F6", F7 => "F7", F8 => "F8",
+    F9 => "F9", F10 => "F10", F11 => "F11", F12 => "F12",
+    Escape => "Escape", Enter => "Enter", Space => "Space", Tab => "Tab", Backspace => "Backspace",
+    Delete => "Delete", Insert => "Insert", Home => "Home", End => "End", PageUp => "PageUp",
+    PageDown => "PageDown", Left => "Left", Right => "Right", Up => "Up", Down => "Down",
+    Comma => "Comma", Period => "Period", Slash => "Slash", Backslash => "Backslash",
+    Minus => "Minus", Equals => "Equals", Semicolon => "Semicolon", Apostrophe => "Apostrophe",
+    LBracket => "LBracket", RBracket => "RBracket", Grave => "Grave",
+}
+
+/// A key combined with modifiers, written as `Ctrl+Alt+Shift+Super+Key` in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub key: KeyCode,
+    pub control: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub logo: bool,
+}
+
+impl Shortcut {
+    /// Returns `None` for keys that cannot be bound, such as modifier keys.
+    pub fn new(key: KeyCode, modifiers: ModifiersState) -> Option<Self> {
+        key_name(key).map(|_| Self {
+            key,
+            control: modifiers.control,
+            alt: modifiers.alt,
+            shift: modifiers.shift,
+            logo: modifiers.logo,
+        })
+    }
+
+    pub fn with_control(mut self) -> Self {
+        self.control = true;
+        self
+    }
+
+    pub fn with_shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub fn with_alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Shortcuts without modifiers other than Shift would type a character or edit the text of a
+    /// focused text input.
+    pub fn is_plain(&self) -> bool {
+        !self.control && !self.alt && !self.logo
+    }
+}
+
+impl From<KeyCode> for Shortcut {
+    fn from(key: KeyCode) -> Self {
+        Self { key, control: false, alt: false, shift: false, logo: false }
+    }
+}
+
+impl Display for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.control {
+            write!(f, "Ctrl+")?;
+        }
+
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+
+        if self.logo {
+            write!(f, "Super+")?;
+        }
+
+        write!(f, "{}", key_name(self.key).unwrap_or("?"))
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut parts = source.split('+').map(str::trim).collect::<Vec<_>>();
+        let key = parts.pop().filter(|key| !key.is_empty()).ok_or("missing key")?;
+        let key = parse_key(key).ok_or_else(|| format!("unknown key `{}`", key))?;
+        let mut shortcut = Shortcut::from(key);
+
+        for modifier in parts {
+            let flag = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut shortcut.control,
+                "alt" => &mut shortcut.alt,
+                "shift" => &mut shortcut.shift,
+                "super" | "logo" => &mut shortcut.logo,
+                _ => return Err(format!("unknown modifier `{}`", modifier)),
+            };
+
+            *flag = true;
+        }
+
+        Ok(shortcut)
+    }
+}
+
+/// The shortcut bound to an action, stored as an empty string if the action is unbound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding(pub Option<Shortcut>);
+
+impl Serialize for Binding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Some(shortcut) => serializer.serialize_str(&shortcut.to_string()),
+            None => serializer.serialize_str(""),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Binding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+
+        if source.trim().is_empty() {
+            Ok(Binding(None))
+        } else {
+            source.parse().map(|shortcut| Binding(Some(shortcut))).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+/// Two actions bound to the same shortcut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    pub shortcut: Shortcut,
+    /// Takes precedence over [`Conflict::shadowed`].
+    pub action: Action,
+    pub shadowed: Action,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Binding>,
+}
+
+impl Serialize for Keymap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.bindings.iter().map(|(action, binding)| (action.key(), binding)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut keymap = Self::default();
+
+        for (key, binding) in BTreeMap::<String, Binding>::deserialize(deserializer)? {
+            let action = Action::from_key(&key)
+                .ok_or_else(|| serde::de::Error::custom(format!("unknown action `{}`", key)))?;
+
+            keymap.bindings.insert(action, binding);
+        }
+
+        Ok(keymap)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::VALUES
+            .iter()
+            .map(|action| (*action, Binding(Some(Self::default_shortcut(*action)))))
+            .collect();
+
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    pub fn default_shortcut(action: Action) -> Shortcut {
+        match action {
+            Action::TogglePreferences => Shortcut::from(KeyCode::Comma).with_control(),
+            Action::SaveGraph => Shortcut::from(KeyCode::S).with_control(),
+            Action::CompareWithSaved => Shortcut::from(KeyCode::D).with_control().with_shift(),
+            Action::OpenGraph => Shortcut::from(KeyCode::O).with_control(),
+            Action::ToggleStartScreen => Shortcut::from(KeyCode::O).with_control().with_shift(),
+            Action::ToggleNodePalette => Shortcut::from(KeyCode::Space).with_control(),
+            Action::NewDocument => Shortcut::from(KeyCode::T).with_control(),
+            Action::CloseDocument => Shortcut::from(KeyCode::W).with_control(),
+            Action::ExportGraphImage => Shortcut::from(KeyCode::E).with_control(),
+            Action::ExportStatistics => Shortcut::from(KeyCode::E).with_control().with_shift(),
+            Action::ToggleImplicitPromotion => Shortcut::from(KeyCode::P).with_control().with_shift(),
+            Action::ToggleSanitizeNumerics => Shortcut::from(KeyCode::F).with_control().with_shift(),
+            Action::ToggleRecording => Shortcut::from(KeyCode::F9).with_control(),
+            Action::ToggleReplay => Shortcut::from(KeyCode::F10).with_control(),
+            Action::ToggleExecutionOrder => Shortcut::from(KeyCode::N).with_control().with_shift(),
+            Action::ReconnectLast => Shortcut::from(KeyCode::R).with_control().with_shift(),
+            Action::ToggleCollapseAll => Shortcut::from(KeyCode::C).with_control().with_shift(),
+            Action::SaveSelectionToLibrary => Shortcut::from(KeyCode::L).with_control().with_shift(),
+            Action::ToggleABSwitches => Shortcut::from(KeyCode::B).with_control().with_shift(),
+            Action::RemoveNode => Shortcut::from(KeyCode::Delete),
+            Action::AlignLeft => Shortcut::from(KeyCode::Left).with_alt(),
+            Action::AlignRight => Shortcut::from(KeyCode::Right).with_alt(),
+            Action::AlignTop => Shortcut::from(KeyCode::Up).with_alt(),
+            Action::AlignBottom => Shortcut::from(KeyCode::Down).with_alt(),
+            Action::AlignHorizontalCenters => Shortcut::from(KeyCode::H).with_alt(),
+            Action::AlignVerticalCenters => Shortcut::from(KeyCode::V).with_alt(),
+            Action::DistributeHorizontally => Shortcut::from(KeyCode::H).with_alt().with_shift(),
+            Action::DistributeVertically => Shortcut::from(KeyCode::V).with_alt().with_shift(),
+            Action::Confirm => Shortcut::from(KeyCode::Enter),
+            Action::Cancel => Shortcut::from(KeyCode::Escape),
+            Action::Quit => Shortcut::from(KeyCode::Q).with_control(),
+        }
+    }
+
+    /// Parses the `shortcuts` table of the config, falling back to the default shortcut of each
+    /// action that is missing or invalid.
+    pub fn parse(value: Option<&toml::Value>) -> Self {
+        let mut keymap = Self::default();
+        let table = match value {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => {
+                warn!(
+                    Category::Ui,
+                    "The shortcuts in the config file are not a table, using the default shortcuts."
+                );
+                return keymap;
+            }
+            None => return keymap,
+        };
+
+        for (key, value) in table {
+            let action = match Action::from_key(key) {
+                Some(action) => action,
+                None => {
+                    warn!(Category::Ui, "Unknown action `{}` in the shortcuts of the config file.", key);
+                    continue;
+                }
+            };
+
+            match value.clone().try_into::<Binding>() {
+                Ok(binding) => {
+                    keymap.bindings.insert(action, binding);
+                }
+                Err(error) => warn!(
+                    Category::Ui,
+                    "Invalid shortcut of `{}` in the config file, using the default shortcut: {}", key, error
+                ),
+            }
+        }
+
+        for conflict in keymap.conflicts() {
+            warn!(
+                Category::Ui,
+                "The shortcut {} is bound to both `{}` and `{}`, the former takes precedence.",
+                conflict.shortcut,
+                conflict.action,
+                conflict.shadowed
+            );
+        }
+
+        keymap
+    }
+
+    pub fn shortcut(&self, action: Action) -> Option<Shortcut> {
+        self.bindings.get(&action).and_then(|binding| binding.0)
+    }
+
+    /// Binds the shortcut to the action, unbinding it from any other action.
+    pub fn bind(&mut self, action: Action, shortcut: Option<Shortcut>) {
+        if let Some(shortcut) = shortcut {
+            for binding in self.bindings.values_mut() {
+                if binding.0 == Some(shortcut) {
+                    binding.0 = None;
+                }
+            }
+        }
+
+        self.bindings.insert(action, Binding(shortcut));
+    }
+
+    /// The action bound to the shortcut. Plain shortcuts are ignored if the key press has already
+    /// been captured by a widget, so that focused text inputs keep receiving them.
+    pub fn action(&self, shortcut: Shortcut, captured: bool) -> Option<Action> {
+        if captured && shortcut.is_plain() {
+            return None;
+        }
+
+        self.bindings.iter().find(|(_, binding)| binding.0 == Some(shortcut)).map(|(action, _)| *action)
+    }
+
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        let mut bound = BTreeMap::<Action, Shortcut>::new();
+
+        for (action, binding) in &self.bindings {
+            if let Some(shortcut) = binding.0 {
+                if let Some((previous, _)) = bound.iter().find(|(_, previous)| **previous == shortcut) {
+                    conflicts.push(Conflict { shortcut, action: *previous, shadowed: *action });
+                } else {
+                    bound.insert(*action, shortcut);
+                }
+            }
+        }
+
+        conflicts
+    }
+}
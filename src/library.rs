@@ -0,0 +1,568 @@
+//! Groups of connected nodes saved into the library, to be inserted into any graph again.
+//!
+//! Each entry is stored as a graph file of its own in the `library` directory of the settings
+//! directory, so that entries can be shared by copying the files. The graph is stored in the format
+//! of [`GraphFile`], along with a `library` block naming the entry and listing the channels that
+//! were connected to nodes outside of the group. Entries are upgraded by the same migrations as
+//! graph files, and instantiated with the same rules, see [`GraphFile::into_graph`].
+//!
+//! Inserting an entry copies its nodes into the graph. The copies are not updated along with the
+//! entry, but remember it as their [`LibraryOrigin`], so that the instance can be updated from the
+//! entry, or its changes pushed back into the entry, on request. Instances differing from their
+//! entries are reported, see [`Library::divergence`].
+
+use crate::graph::file::{GraphFile, GraphFileError, GraphMetadata};
+use crate::graph::{EdgeEndpoint, ExecutionGraph, NodeIndex};
+use crate::logging::Category;
+use crate::node::ChannelDirection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The key of the block describing the entry, within the graph file.
+const LIBRARY_KEY: &str = "library";
+
+/// A channel of a node of the entry, which was connected to a node outside of the saved group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoundaryChannel {
+    /// The position of the node in [`GraphFile::nodes`].
+    pub node: usize,
+    pub endpoint: EdgeEndpoint,
+    /// The titles of the node and of the channel, e.g. `Gradient / phase`.
+    pub label: String,
+}
+
+/// Describes the entry, stored next to the graph in the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryInfo {
+    pub name: String,
+    pub description: String,
+    /// Identifies the contents of the entry, see [`content_hash`].
+    pub content_hash: String,
+    pub inputs: Vec<BoundaryChannel>,
+    pub outputs: Vec<BoundaryChannel>,
+}
+
+/// Identifies the entry a node was inserted from, along with the instance of the entry the node
+/// belongs to, as an entry may be inserted into the same graph several times.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LibraryOrigin {
+    /// The name of the entry.
+    pub name: String,
+    /// The [`instance_hash`] of the entry when the instance was inserted, last updated from the
+    /// entry, or last pushed into it.
+    pub content_hash: String,
+    /// Distinguishes the instances of the entry within the graph.
+    pub instance: u64,
+}
+
+impl LibraryOrigin {
+    /// Whether both nodes belong to the same instance of the same entry.
+    pub fn is_same_instance(&self, other: &Self) -> bool {
+        self.name == other.name && self.instance == other.instance
+    }
+}
+
+/// How an instance differs from the entry it was inserted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Divergence {
+    /// The nodes of the instance have been edited.
+    Edited,
+    /// The entry has been changed, such as by pushing another instance into it.
+    Outdated,
+    /// Both the nodes of the instance and the entry have been changed.
+    Conflicting,
+    /// The entry has been removed or renamed.
+    Missing,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Divergence::*;
+        match self {
+            Edited => write!(f, "The nodes have been edited since they were inserted from the library."),
+            Outdated => write!(f, "The library entry has been changed since the nodes were inserted."),
+            Conflicting => write!(f, "Both the nodes and the library entry have been changed."),
+            Missing => write!(f, "The library entry does not exist anymore."),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LibraryEntry {
+    pub info: LibraryInfo,
+    pub graph: GraphFile,
+}
+
+impl LibraryEntry {
+    /// Captures the nodes of the graph for which `include` returns `true`, along with the
+    /// connections among them. The channels connected to the other nodes become the boundary
+    /// channels of the entry.
+    pub fn from_nodes(
+        name: &str,
+        graph: &ExecutionGraph,
+        metadata: &GraphMetadata,
+        include: impl Fn(NodeIndex) -> bool,
+    ) -> Result<Self, LibraryError> {
+        let mut file = GraphFile::from_nodes(graph, metadata, &include);
+        // The positions of the nodes in the file follow the order of the node indices.
+        let positions: HashMap<NodeIndex, usize> = graph
+            .node_indices()
+            .filter(|node| include(*node))
+            .enumerate()
+            .map(|(position, node)| (node, position))
+            .collect();
+
+        if positions.is_empty() {
+            return Err(LibraryError::EmptySelection);
+        }
+
+        let label = |node: NodeIndex, endpoint: EdgeEndpoint, direction: ChannelDirection| {
+            let channel = endpoint.into_undirected_identifier(node).into_directed(direction);
+
+            graph.channel_label(channel).unwrap_or_default()
+        };
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::<BoundaryChannel>::new();
+
+        for edge in graph.edge_indices() {
+            let (from, to) = graph.edge_endpoints(edge).unwrap();
+            let edge = &graph[edge];
+
+            match (positions.get(&from), positions.get(&to)) {
+                (None, Some(&node)) => inputs.push(BoundaryChannel {
+                    node,
+                    endpoint: edge.endpoint_to,
+                    label: label(to, edge.endpoint_to, ChannelDirection::In),
+                }),
+                (Some(&node), None) => {
+                    // An output may be connected to several nodes outside of the group.
+                    if !outputs
+                        .iter()
+                        .any(|output| output.node == node && output.endpoint == edge.endpoint_from)
+                    {
+                        outputs.push(BoundaryChannel {
+                            node,
+                            endpoint: edge.endpoint_from,
+                            label: label(from, edge.endpoint_from, ChannelDirection::Out),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // The entry is not an instance of itself, its instances are assigned their origins when
+        // they are inserted.
+        for node in &mut file.nodes {
+            node.library_origin = None;
+        }
+
+        file.metadata = GraphMetadata { title: name.trim().to_string(), ..file.metadata };
+
+        let mut info = LibraryInfo {
+            name: validate_name(name)?,
+            description: String::new(),
+            content_hash: String::new(),
+            inputs,
+            outputs,
+        };
+
+        info.content_hash = content_hash(&file, &info);
+        Ok(Self { info, graph: file })
+    }
+
+    /// Summarizes the size and the boundary of the entry, e.g. `3 nodes, 1 input, 2 outputs`,
+    /// followed by the description, if there is one.
+    pub fn summary(&self) -> String {
+        let count =
+            |count: usize, noun: &str| format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" });
+        let mut summary = format!(
+            "{}, {}, {}",
+            count(self.graph.nodes.len(), "node"),
+            count(self.info.inputs.len(), "input"),
+            count(self.info.outputs.len(), "output")
+        );
+
+        if !self.info.description.is_empty() {
+            summary += &format!(" · {}", self.info.description);
+        }
+
+        summary
+    }
+
+    /// Reads the entry, upgrading its graph to the current format version if necessary.
+    fn load(path: &Path) -> Result<Self, LibraryError> {
+        let source = fs::read(path)?;
+        let mut document: serde_json::Value = serde_json::from_slice(&source)?;
+        let info = document
+            .as_object_mut()
+            .and_then(|document| document.remove(LIBRARY_KEY))
+            .ok_or(LibraryError::MissingLibraryInfo)?;
+        let info = serde_json::from_value(info)?;
+        let (graph, _) = GraphFile::from_document(document)?;
+
+        Ok(Self { info, graph })
+    }
+
+    fn save(&self, path: &Path) -> Result<(), LibraryError> {
+        let mut document = serde_json::to_value(&self.graph)?;
+
+        if let Some(document) = document.as_object_mut() {
+            document.insert(LIBRARY_KEY.to_string(), serde_json::to_value(&self.info)?);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_vec_pretty(&document)?)?;
+        Ok(())
+    }
+}
+
+/// Hashes the nodes, the connections and the boundary channels of the entry, leaving out the
+/// metadata, so that saving the same nodes again results in the same hash.
+fn content_hash(graph: &GraphFile, info: &LibraryInfo) -> String {
+    let content = serde_json::json!({
+        "nodes": graph.nodes,
+        "edges": graph.edges,
+        "inputs": info.inputs,
+        "outputs": info.outputs,
+    });
+
+    fnv1a(&content)
+}
+
+/// Hashes the titles, the kinds and the settings of the nodes, and the connections among them.
+/// Unlike [`content_hash`], the hash depends neither on the layout of the nodes nor on their order,
+/// which changes as the indices of removed nodes are reused, so that an instance hashes like the
+/// entry it was inserted from, until either of them is edited.
+pub fn instance_hash(graph: &GraphFile) -> String {
+    let keys = graph
+        .nodes
+        .iter()
+        .map(|node| serde_json::json!([node.title, node.behaviour, node.settings]).to_string())
+        .collect::<Vec<_>>();
+    let mut order = (0..keys.len()).collect::<Vec<_>>();
+
+    order.sort_by(|a, b| keys[*a].cmp(&keys[*b]));
+
+    let mut ranks = vec![0; keys.len()];
+
+    for (rank, position) in order.iter().enumerate() {
+        ranks[*position] = rank;
+    }
+
+    let mut edges = graph
+        .edges
+        .iter()
+        .filter_map(|edge| {
+            let edge = serde_json::json!([
+                ranks.get(edge.from)?,
+                ranks.get(edge.to)?,
+                edge.endpoint_from,
+                edge.endpoint_to,
+                edge.primary,
+            ]);
+
+            Some(edge.to_string())
+        })
+        .collect::<Vec<_>>();
+
+    edges.sort();
+
+    let nodes = order.iter().map(|position| &keys[*position]).collect::<Vec<_>>();
+
+    fnv1a(&serde_json::json!({ "nodes": nodes, "edges": edges }))
+}
+
+/// Hashes the serialized value with FNV-1a.
+fn fnv1a(value: &serde_json::Value) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let hash =
+        value.to_string().bytes().fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME));
+
+    format!("{:016x}", hash)
+}
+
+#[derive(Debug)]
+pub enum LibraryError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Graph(GraphFileError),
+    /// The file is a graph file, but not a library entry.
+    MissingLibraryInfo,
+    EmptySelection,
+    EmptyName,
+    /// Another entry already has the name.
+    DuplicateName(String),
+    NoSettingsDirectory,
+}
+
+impl From<io::Error> for LibraryError {
+    fn from(error: io::Error) -> Self {
+        LibraryError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for LibraryError {
+    fn from(error: serde_json::Error) -> Self {
+        LibraryError::Json(error)
+    }
+}
+
+impl From<GraphFileError> for LibraryError {
+    fn from(error: GraphFileError) -> Self {
+        LibraryError::Graph(error)
+    }
+}
+
+impl Display for LibraryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use LibraryError::*;
+        match self {
+            Io(error) => write!(f, "I/O error: {}", error),
+            Json(error) => write!(f, "The library entry is not valid: {}", error),
+            Graph(error) => write!(f, "{}", error),
+            MissingLibraryInfo => write!(f, "The file is a graph, but not a library entry."),
+            EmptySelection => write!(f, "No nodes are selected."),
+            EmptyName => write!(f, "The name of a library entry must not be empty."),
+            DuplicateName(name) => write!(f, "A library entry named `{}` already exists.", name),
+            NoSettingsDirectory => write!(f, "The settings directory could not be determined."),
+        }
+    }
+}
+
+/// The entries of the library directory, ordered by their names. Every change is written to the
+/// files immediately.
+#[derive(Debug, Default)]
+pub struct Library {
+    entries: Vec<LibraryEntry>,
+}
+
+impl Library {
+    const DIRECTORY_NAME: &'static str = "library";
+
+    pub fn directory() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("dvsynth").join(Self::DIRECTORY_NAME))
+    }
+
+    /// The file of the entry with the name, within the library directory. Characters that may not
+    /// be valid in file names are replaced.
+    fn entry_path(name: &str) -> Option<PathBuf> {
+        let file_name = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == ' ' { c } else { '_' })
+            .collect::<String>();
+
+        Self::directory().map(|directory| directory.join(file_name).with_extension("json"))
+    }
+
+    /// Reads all entries of the library directory. Files that cannot be read are skipped and left
+    /// untouched.
+    pub fn load() -> Self {
+        let directory =
+            if let Some(directory) = Self::directory() { directory } else { return Default::default() };
+        let files = match fs::read_dir(&directory) {
+            Ok(files) => files,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Default::default(),
+            Err(error) => {
+                error!(Category::Ui, "Could not read the library `{}`: {}", directory.display(), error);
+                return Default::default();
+            }
+        };
+        let mut library = Self::default();
+
+        for path in files.filter_map(Result::ok).map(|file| file.path()) {
+            if path.extension().map_or(true, |extension| extension != "json") {
+                continue;
+            }
+
+            match LibraryEntry::load(&path) {
+                Ok(entry) => library.entries.push(entry),
+                Err(error) => {
+                    warn!(Category::Ui, "Could not load the library entry `{}`: {}", path.display(), error)
+                }
+            }
+        }
+
+        library.sort();
+        library
+    }
+
+    fn sort(&mut self) {
+        self.entries.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+    }
+
+    pub fn entries(&self) -> &[LibraryEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LibraryEntry> {
+        self.entries.iter().find(|entry| entry.info.name == name)
+    }
+
+    /// How the instance, captured by [`GraphFile::from_nodes`], differs from the entry it was
+    /// inserted from, `None` if it does not.
+    pub fn divergence(&self, origin: &LibraryOrigin, instance: &GraphFile) -> Option<Divergence> {
+        let entry =
+            if let Some(entry) = self.get(&origin.name) { entry } else { return Some(Divergence::Missing) };
+        let instance = instance_hash(instance);
+        let entry = instance_hash(&entry.graph);
+
+        if instance == entry {
+            return None;
+        }
+
+        Some(match (instance != origin.content_hash, entry != origin.content_hash) {
+            (true, false) => Divergence::Edited,
+            (false, true) => Divergence::Outdated,
+            _ => Divergence::Conflicting,
+        })
+    }
+
+    /// Whether the name or the file of the entry would be taken by another entry.
+    fn is_taken(&self, name: &str) -> bool {
+        let path = Self::entry_path(name);
+
+        self.entries.iter().any(|entry| entry.info.name == name || Self::entry_path(&entry.info.name) == path)
+    }
+
+    /// The name, followed by the lowest number that makes it unique, if it is taken.
+    pub fn unique_name(&self, name: &str) -> String {
+        if !self.is_taken(name) {
+            return name.to_string();
+        }
+
+        (2..)
+            .map(|number| format!("{} ({})", name, number))
+            .find(|candidate| !self.is_taken(candidate))
+            .unwrap()
+    }
+
+    /// Adds the entry, replacing the entry with the same name, if `overwrite` is set.
+    pub fn insert(&mut self, mut entry: LibraryEntry, overwrite: bool) -> Result<(), LibraryError> {
+        entry.info.name = validate_name(&entry.info.name)?;
+
+        let existing = self.entries.iter().position(|existing| existing.info.name == entry.info.name);
+
+        if (existing.is_some() && !overwrite) || (existing.is_none() && self.is_taken(&entry.info.name)) {
+            return Err(LibraryError::DuplicateName(entry.info.name));
+        }
+
+        let path = Self::entry_path(&entry.info.name).ok_or(LibraryError::NoSettingsDirectory)?;
+
+        entry.save(&path)?;
+
+        if let Some(index) = existing {
+            self.entries.remove(index);
+        }
+
+        self.entries.push(entry);
+        self.sort();
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, LibraryError> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        Err(LibraryError::EmptyName)
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{EdgeData, Graph, NodeData};
+    use crate::node::behaviour::create_behaviour;
+    use crate::node::ChannelPassBy;
+
+    /// A gradient connected to a debug node, placed at the offset and added to the graph in the
+    /// reverse order, if `reversed`.
+    fn instance(offset: [f32; 2], reversed: bool) -> GraphFile {
+        let mut graph = Graph::new();
+        let mut add = |title: &str, x: f32| {
+            let position = [offset[0] + x, offset[1]];
+
+            graph.add_node(NodeData::new(title, position, create_behaviour(title).unwrap()))
+        };
+        let (gradient, debug) = if reversed {
+            let debug = add("Debug", 200.0);
+
+            (add("Gradient", 0.0), debug)
+        } else {
+            let gradient = add("Gradient", 0.0);
+
+            (gradient, add("Debug", 200.0))
+        };
+        let endpoint = EdgeEndpoint { channel_index: 0, pass_by: ChannelPassBy::SharedReference };
+
+        graph.add_edge(
+            gradient,
+            debug,
+            EdgeData { endpoint_from: endpoint, endpoint_to: endpoint, primary: false },
+        );
+        GraphFile::from_graph(&graph, &GraphMetadata::default())
+    }
+
+    fn renamed(mut file: GraphFile, title: &str) -> GraphFile {
+        file.nodes[0].title = title.to_string();
+        file
+    }
+
+    fn library(graph: GraphFile) -> Library {
+        let info = LibraryInfo {
+            name: "Group".to_string(),
+            description: String::new(),
+            content_hash: String::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        };
+
+        Library { entries: vec![LibraryEntry { info, graph }] }
+    }
+
+    #[test]
+    fn instance_hashes_ignore_the_layout_and_the_order_of_the_nodes() {
+        let entry = instance([0.0, 0.0], false);
+
+        assert_eq!(instance_hash(&instance([300.0, -50.0], true)), instance_hash(&entry));
+        assert_ne!(instance_hash(&renamed(instance([0.0, 0.0], false), "Renamed")), instance_hash(&entry));
+
+        let mut disconnected = instance([0.0, 0.0], false);
+
+        disconnected.edges.clear();
+        assert_ne!(instance_hash(&disconnected), instance_hash(&entry));
+    }
+
+    #[test]
+    fn divergence_tells_edited_instances_from_outdated_ones() {
+        let entry = instance([0.0, 0.0], false);
+        let origin =
+            LibraryOrigin { name: "Group".to_string(), content_hash: instance_hash(&entry), instance: 0 };
+        let moved = instance([300.0, -50.0], true);
+        let edited = renamed(instance([0.0, 0.0], false), "Edited");
+        let current = library(entry);
+
+        assert_eq!(current.divergence(&origin, &moved), None);
+        assert_eq!(current.divergence(&origin, &edited), Some(Divergence::Edited));
+        assert_eq!(Library::default().divergence(&origin, &moved), Some(Divergence::Missing));
+
+        let pushed = library(renamed(instance([0.0, 0.0], false), "Pushed"));
+
+        assert_eq!(pushed.divergence(&origin, &moved), Some(Divergence::Outdated));
+        assert_eq!(pushed.divergence(&origin, &edited), Some(Divergence::Conflicting));
+        // Edited the same way as the entry.
+        assert_eq!(pushed.divergence(&origin, &renamed(instance([300.0, -50.0], false), "Pushed")), None);
+    }
+}
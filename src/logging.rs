@@ -0,0 +1,338 @@
+//! Diagnostic messages of the subsystems, filterable per category at runtime.
+//!
+//! Messages are written to the standard error output and kept in a ring buffer of the most recent
+//! records, to be displayed within the application. The format arguments of a message are only
+//! evaluated if its category is enabled at its level, so that disabled messages in hot paths cost
+//! no more than the check of an atomic.
+//!
+//! Trace messages, such as the refcount changes of the allocator, are compiled out entirely in
+//! release builds, unless the `trace` feature is enabled.
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    Allocator,
+    Scheduler,
+    Executor,
+    Ui,
+    Plugin,
+}
+
+impl Category {
+    pub const VALUES: [Category; 5] =
+        [Category::Allocator, Category::Scheduler, Category::Executor, Category::Ui, Category::Plugin];
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Category::Allocator => write!(f, "Allocator"),
+            Category::Scheduler => write!(f, "Scheduler"),
+            Category::Executor => write!(f, "Executor"),
+            Category::Ui => write!(f, "UI"),
+            Category::Plugin => write!(f, "Plugin"),
+        }
+    }
+}
+
+/// The severity of a message, the more verbose the higher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Error => write!(f, "ERROR"),
+            Level::Warn => write!(f, "WARN"),
+            Level::Info => write!(f, "INFO"),
+            Level::Debug => write!(f, "DEBUG"),
+            Level::Trace => write!(f, "TRACE"),
+        }
+    }
+}
+
+/// The most verbose level of messages of a category to record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum LevelFilter {
+    Off = 0,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    /// Only available in debug builds, or with the `trace` feature enabled.
+    Trace,
+}
+
+impl LevelFilter {
+    pub const VALUES: [LevelFilter; 6] = [
+        LevelFilter::Off,
+        LevelFilter::Error,
+        LevelFilter::Warn,
+        LevelFilter::Info,
+        LevelFilter::Debug,
+        LevelFilter::Trace,
+    ];
+}
+
+impl Display for LevelFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelFilter::Off => write!(f, "Off"),
+            LevelFilter::Error => write!(f, "Error"),
+            LevelFilter::Warn => write!(f, "Warning"),
+            LevelFilter::Info => write!(f, "Info"),
+            LevelFilter::Debug => write!(f, "Debug"),
+            LevelFilter::Trace => write!(f, "Trace"),
+        }
+    }
+}
+
+/// The filters of all categories, as stored in the settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelFilters {
+    pub allocator: LevelFilter,
+    pub scheduler: LevelFilter,
+    pub executor: LevelFilter,
+    pub ui: LevelFilter,
+    pub plugin: LevelFilter,
+}
+
+impl LevelFilters {
+    pub fn get(&self, category: Category) -> LevelFilter {
+        match category {
+            Category::Allocator => self.allocator,
+            Category::Scheduler => self.scheduler,
+            Category::Executor => self.executor,
+            Category::Ui => self.ui,
+            Category::Plugin => self.plugin,
+        }
+    }
+
+    pub fn set(&mut self, category: Category, filter: LevelFilter) {
+        match category {
+            Category::Allocator => self.allocator = filter,
+            Category::Scheduler => self.scheduler = filter,
+            Category::Executor => self.executor = filter,
+            Category::Ui => self.ui = filter,
+            Category::Plugin => self.plugin = filter,
+        }
+    }
+}
+
+impl Default for LevelFilters {
+    fn default() -> Self {
+        Self {
+            allocator: LevelFilter::Info,
+            scheduler: LevelFilter::Info,
+            executor: LevelFilter::Info,
+            ui: LevelFilter::Info,
+            plugin: LevelFilter::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub category: Category,
+    pub level: Level,
+    pub message: String,
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] {}", self.level, self.category, self.message)
+    }
+}
+
+lazy_static! {
+    static ref LOGGER: Logger = Logger::new(&LevelFilters::default());
+}
+
+#[derive(Debug)]
+pub struct Logger {
+    /// The [`LevelFilter`] of each category, indexed by the category.
+    filters: [AtomicU8; Category::VALUES.len()],
+    /// The most recent records, the oldest first.
+    records: Mutex<VecDeque<Record>>,
+}
+
+impl Logger {
+    /// The number of records kept in the ring buffer.
+    pub const CAPACITY: usize = 1000;
+
+    fn new(filters: &LevelFilters) -> Self {
+        let logger = Self {
+            filters: Default::default(),
+            records: Mutex::new(VecDeque::with_capacity(Self::CAPACITY)),
+        };
+
+        logger.set_filters(filters);
+        logger
+    }
+
+    pub fn get() -> &'static Logger {
+        &LOGGER
+    }
+
+    pub fn set_filter(&self, category: Category, filter: LevelFilter) {
+        self.filters[category as usize].store(filter as u8, Ordering::Relaxed);
+    }
+
+    pub fn set_filters(&self, filters: &LevelFilters) {
+        for category in &Category::VALUES {
+            self.set_filter(*category, filters.get(*category));
+        }
+    }
+
+    /// Whether messages of the category at the level are recorded. Checked by the logging macros
+    /// before the message is formatted.
+    #[inline]
+    pub fn enabled(&self, category: Category, level: Level) -> bool {
+        level as u8 <= self.filters[category as usize].load(Ordering::Relaxed)
+    }
+
+    /// Records the message regardless of the filters, use the logging macros instead.
+    pub fn log(&self, category: Category, level: Level, arguments: fmt::Arguments) {
+        let record = Record { category, level, message: arguments.to_string() };
+
+        eprintln!("{}", record);
+
+        let mut records = self.records.lock().unwrap_or_else(|error| error.into_inner());
+
+        if records.len() >= Self::CAPACITY {
+            records.pop_front();
+        }
+
+        records.push_back(record);
+    }
+
+    /// The `count` most recent records, the oldest first.
+    pub fn recent_records(&self, count: usize) -> Vec<Record> {
+        let records = self.records.lock().unwrap_or_else(|error| error.into_inner());
+
+        records.iter().skip(records.len().saturating_sub(count)).cloned().collect()
+    }
+}
+
+/// Records a message of the category at the level, formatting it only if it is enabled.
+macro_rules! log {
+    ($category:expr, $level:expr, $($arg:tt)+) => {{
+        let (category, level) = ($category, $level);
+        let logger = $crate::logging::Logger::get();
+
+        if logger.enabled(category, level) {
+            logger.log(category, level, format_args!($($arg)+));
+        }
+    }};
+}
+
+#[allow(unused_macros)]
+macro_rules! error {
+    ($category:expr, $($arg:tt)+) => {
+        log!($category, $crate::logging::Level::Error, $($arg)+)
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! warn {
+    ($category:expr, $($arg:tt)+) => {
+        log!($category, $crate::logging::Level::Warn, $($arg)+)
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! info {
+    ($category:expr, $($arg:tt)+) => {
+        log!($category, $crate::logging::Level::Info, $($arg)+)
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! debug {
+    ($category:expr, $($arg:tt)+) => {
+        log!($category, $crate::logging::Level::Debug, $($arg)+)
+    };
+}
+
+#[allow(unused_macros)]
+#[cfg(not(any(debug_assertions, feature = "trace")))]
+macro_rules! trace {
+    ($($tt:tt)*) => {};
+}
+
+#[allow(unused_macros)]
+#[cfg(any(debug_assertions, feature = "trace"))]
+macro_rules! trace {
+    ($category:expr, $($arg:tt)+) => {
+        log!($category, $crate::logging::Level::Trace, $($arg)+)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Counts how many times it has been formatted.
+    struct Counted<'a>(&'a Cell<usize>);
+
+    impl Display for Counted<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.set(self.0.get() + 1);
+            write!(f, "counted")
+        }
+    }
+
+    #[test]
+    fn disabled_messages_are_not_formatted() {
+        let logger = Logger::get();
+        let formatted = Cell::new(0);
+        let evaluated = Cell::new(0);
+        let argument = || {
+            evaluated.set(evaluated.get() + 1);
+            Counted(&formatted)
+        };
+
+        logger.set_filter(Category::Plugin, LevelFilter::Off);
+        error!(Category::Plugin, "{}", argument());
+
+        assert_eq!((evaluated.get(), formatted.get()), (0, 0));
+
+        logger.set_filter(Category::Plugin, LevelFilter::Warn);
+        info!(Category::Plugin, "{}", argument());
+        debug!(Category::Plugin, "{}", argument());
+
+        assert_eq!((evaluated.get(), formatted.get()), (0, 0));
+
+        // Other categories are filtered independently.
+        logger.set_filter(Category::Ui, LevelFilter::Off);
+        warn!(Category::Plugin, "{}", argument());
+        warn!(Category::Ui, "{}", argument());
+
+        assert_eq!((evaluated.get(), formatted.get()), (1, 1));
+
+        let records = logger.recent_records(Logger::CAPACITY);
+
+        assert!(records.iter().any(|record| {
+            record.category == Category::Plugin && record.level == Level::Warn && record.message == "counted"
+        }));
+
+        logger.set_filters(&LevelFilters::default());
+    }
+}
@@ -10,6 +10,8 @@
 #![feature(trivial_bounds)]
 #![feature(associated_type_defaults)]
 #![feature(trait_alias)]
+#![feature(backtrace)]
+#![cfg_attr(test, feature(test))]
 //!
 //! Task list:
 //! * Finish adding generic params to channel types
@@ -24,28 +26,96 @@
 //! * Custom UI rendering:
 //!     * CPU Canvas (WASM) https://github.com/embedded-graphics/embedded-graphics
 //!     * Node Definitions (displaying GPU-rendered texture)
-//! * Display type tooltips when hovering over channels
+//! * Plugin loading, its settings are already persisted in `config::Config`
 //!
 
+use arc_swap::ArcSwapOption;
+use autosave::Autosave;
+use config::{Config, ReconnectOnRemoval, SharedConfig};
+use connection_history::ReconnectCandidate;
+use crash::{CrashReport, CrashReporter};
+use document::{Document, DocumentId};
+use graph::background::{BackgroundTaskStatus, BackgroundTasks};
+use graph::controller::{EditConsequences, GraphCommand, GraphCommandResult};
+use graph::diff::GraphDiff;
+use graph::file::{GraphFile, GraphFileError, GraphMetadata};
+use graph::recording::{Recorder, RecordingControl, RecordingMode, RecordingStatus, Replayer};
 use graph::{
-    ApplicationContext, ChannelIdentifier, Connection, EdgeData, ExecutionGraph, Graph, GraphExecutor,
-    GraphValidationErrors, NodeData,
+    ApplicationContext, ChannelIdentifier, Connection, EdgeEndpoint, ExecutingGeneration, ExecutionGraph,
+    ExecutionWatchdog, Graph, GraphExecutor, GraphValidationErrors, NodeErrors, NodeId, PinnedSchedules,
+    Renderer, Schedule, StalledTask, ThroughputRates, ValueSnapshot, ValueTaps,
 };
-use iced::{window, Application, Command, Settings};
+use iced::{keyboard, window, Application, Command, Settings, Subscription};
+use iced_futures::futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use iced_futures::futures::stream::{self, BoxStream, StreamExt};
+use iced_futures::subscription::Recipe;
+use iced_native::Event;
 use iced_winit::winit;
+use idle::{FrameRate, IdleTracker, MainThreadWaker};
+use keymap::{Action, Shortcut};
+use library::{instance_hash, Library, LibraryEntry, LibraryOrigin};
+use logging::Category;
+use modal::{Modal, ModalAction, ModalMessage};
 use node::behaviour::*;
 use node::*;
+use palette::{NodePalette, NodePaletteMessage};
 use petgraph::graph::NodeIndex;
+use petgraph::Direction;
+use preferences::{Preferences, PreferencesMessage};
+use preset::{Preset, Presets};
+use safe_mode::{SafeModeBanner, SafeModeMessage, StartupMarker, Substitutions};
+use serde::{Deserialize, Serialize};
+use session::{SessionRecorder, SessionReplay};
+use start_screen::{StartScreen, StartScreenMessage};
+use startup::Milestone;
+use statistics::ExecutionStatistics;
+use std::any::TypeId;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use style::Themeable;
 use style::*;
+use template::Template;
+use threading::AppliedThreadSettings;
+use vek::Vec2;
 use widgets::*;
 
+#[cfg(test)]
+extern crate test;
+
+#[macro_use]
+pub mod logging;
 #[macro_use]
 pub mod util;
 
+pub mod autosave;
+pub mod config;
+pub mod connection_history;
+pub mod crash;
+pub mod document;
+pub mod export;
 pub mod graph;
+pub mod idle;
+pub mod keymap;
+pub mod library;
+pub mod modal;
 pub mod node;
+pub mod palette;
+pub mod preferences;
+pub mod preset;
+pub mod safe_mode;
+pub mod self_check;
+pub mod session;
+pub mod start_screen;
+pub mod startup;
+pub mod statistics;
 pub mod style;
+pub mod template;
+pub mod threading;
 pub mod widgets;
 
 #[derive(Debug, Clone)]
@@ -53,8 +123,11 @@ pub enum NodeMessage {
     NodeBehaviourMessage(Box<dyn NodeBehaviourMessage>),
 }
 
-#[derive(Debug, Clone)]
+/// Recorded into session logs, see [`session`]. The variants whose payload cannot be serialized are
+/// recorded as opaque markers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Message {
+    #[serde(skip)]
     NodeMessage {
         node: NodeIndex<u32>,
         message: NodeMessage,
@@ -65,19 +138,345 @@ pub enum Message {
     InsertConnection {
         connection: Connection,
     },
+    /// Creates the connections between two nodes, as a single edit.
+    InsertConnections {
+        connections: Vec<Connection>,
+    },
+    /// Replaces the connection by the pair of connections through a node, the one leading into the
+    /// node first.
+    SpliceConnection {
+        connection: Connection,
+        through: [Connection; 2],
+    },
+    /// Recreates a previously removed connection, replacing the current connection of the input.
+    Reconnect {
+        connection: Connection,
+    },
+    /// Recreates the most recently removed connection which is still valid.
+    ReconnectLast,
+    /// Moves the value of the output into the connection, rather than into its other connections.
+    SetPrimaryConnection {
+        connection: Connection,
+    },
+    /// Creates a node connected to the channel, a `Constant` for inputs and a `Debug` node for
+    /// outputs.
+    CreateConnectedNode {
+        channel: ChannelIdentifier,
+        position: Vec2<f32>,
+    },
+    /// Requests a snapshot of the value of the output channel, to be frozen into a `Constant` node.
+    FreezeValue {
+        channel: ChannelIdentifier,
+    },
+    #[serde(skip)]
+    ValueSnapshotTaken(ValueSnapshot),
+    /// The executor has picked up another schedule, or stopped executing, which only changes the
+    /// displayed schedule status.
+    ExecutingGenerationChanged,
+    /// Wakes the event loop to run the tasks submitted by the executor, see [`idle`].
+    MainThreadTasksSubmitted,
+    /// Sent periodically to sample the execution statistics and, while it is displayed, the
+    /// throughput of connections.
+    SampleStatistics,
+    /// Reported by the watchdog while a node blocks the executor, and with `None` once it has
+    /// finished.
+    #[serde(skip)]
+    TaskStalled(Option<StalledTask>),
+    SetNodeColor {
+        node: NodeIndex<u32>,
+        color: Option<NodeColor>,
+    },
+    SetNodeMuted {
+        node: NodeIndex<u32>,
+        muted: bool,
+    },
+    SetNodeSoloed {
+        node: NodeIndex<u32>,
+        soloed: bool,
+    },
+    /// Displays only the title bar and the connection points of the node, or its full content.
+    SetNodeCollapsed {
+        node: NodeIndex<u32>,
+        collapsed: bool,
+    },
+    /// Collapses all nodes, or expands them, if all of them are collapsed already.
+    ToggleCollapseAll,
+    /// Displays the full content of the collapsed node, once the cursor has been resting on its
+    /// pane for [`COLLAPSED_PANE_PREVIEW_DELAY`].
+    #[serde(skip)]
+    PreviewCollapsedPane {
+        node: NodeIndex<u32>,
+    },
+    /// Draws the pane of the node above all panes that are not pinned, or among them.
+    SetNodePinned {
+        node: NodeIndex<u32>,
+        pinned: bool,
+    },
+    /// Sets the opacity the pane of the node is drawn with, from 0 to 1.
+    SetNodeOpacity {
+        node: NodeIndex<u32>,
+        opacity: f32,
+    },
+    /// Lets the cursor through the content of the pane of the node, to whatever is below it.
+    SetNodeClickThrough {
+        node: NodeIndex<u32>,
+        click_through: bool,
+    },
+    /// Passes the message to the behaviours of all nodes of the kind identified by its
+    /// [`NodeBehaviour::name`](node::behaviour::NodeBehaviour::name), as a single edit.
+    #[serde(skip)]
+    BroadcastToBehaviour {
+        behaviour_id: String,
+        message: Box<dyn NodeBehaviourMessage>,
+    },
+    /// Asks for the name to save the selected nodes under, as a library entry.
+    SaveSelectionToLibrary,
+    RemoveNode {
+        node: NodeIndex<u32>,
+    },
+    /// Asks for the name to save the kind and the settings of the node under, as a preset.
+    SavePreset {
+        node: NodeIndex<u32>,
+    },
+    /// Replaces the nodes of the instance of the library entry the node belongs to with a copy of
+    /// the current entry.
+    UpdateFromLibrary {
+        node: NodeIndex<u32>,
+    },
+    /// Replaces the library entry the node was inserted from with the nodes of its instance.
+    PushToLibrary {
+        node: NodeIndex<u32>,
+    },
+    ToggleChannelGroup {
+        node: NodeIndex<u32>,
+        direction: ChannelDirection,
+        group: String,
+    },
+    /// Enters or leaves the mode in which the channel rows of the node are dragged to reorder them.
+    SetArrangingChannels {
+        node: NodeIndex<u32>,
+        arranging: bool,
+    },
+    /// Displays the channel at the position of the target channel, both specified by their global
+    /// indices.
+    MoveChannel {
+        node: NodeIndex<u32>,
+        direction: ChannelDirection,
+        channel: usize,
+        target: usize,
+    },
+    /// Displays the channels of the node in the order of their indices.
+    ResetChannelOrder {
+        node: NodeIndex<u32>,
+    },
+    SetNodeSelected {
+        node: NodeIndex<u32>,
+        selected: bool,
+    },
+    /// Moves the selected nodes, as a single edit.
+    AlignSelectedNodes(PaneAlignment),
+    ScrollNodeContent {
+        node: NodeIndex<u32>,
+        offset: f32,
+    },
+    /// A key press, to be looked up in the keymap. Plain shortcuts are ignored if the key press
+    /// has been captured by a widget.
+    #[serde(skip)]
+    KeyPressed {
+        shortcut: Shortcut,
+        captured: bool,
+    },
+    TogglePreferences,
+    ToggleNodePalette,
+    ExportGraphImage,
+    /// Writes the last sampled execution statistics into a CSV or JSON file.
+    ExportStatistics,
+    /// Allows or disallows connections to inputs of wider primitive types in the focused graph.
+    ToggleImplicitPromotion,
+    /// Replaces the non-finite floating-point values output by the nodes, or stops replacing them.
+    ToggleSanitizeNumerics,
+    /// Starts recording the outputs of the source nodes of the focused graph into a file, or stops
+    /// the recording.
+    ToggleRecording,
+    /// Starts replaying a recording into the focused graph, or stops the replay.
+    ToggleReplay,
+    ToggleExecutionOrder,
+    SaveGraph,
+    /// Lists the changes since the graph was last saved.
+    CompareWithSaved,
+    OpenGraph,
+    /// Opens an empty graph in a new tab.
+    NewDocument,
+    /// Focuses the tab at the position, executing its graph instead of the focused one.
+    SelectDocument {
+        tab: usize,
+    },
+    /// Closes the tab at the position, once its unsaved changes are saved or discarded.
+    CloseDocument {
+        tab: usize,
+    },
+    /// Keeps executing the document of the tab at the position while other tabs are focused, or
+    /// stops doing so.
+    SetDocumentPinned {
+        tab: usize,
+        pinned: bool,
+    },
+    ToggleStartScreen,
+    #[serde(skip)]
+    StartScreenMessage(StartScreenMessage),
+    #[serde(skip)]
+    PreferencesMessage(PreferencesMessage),
+    #[serde(skip)]
+    NodePaletteMessage(NodePaletteMessage),
+    #[serde(skip)]
+    ModalMessage(ModalMessage),
+    #[serde(skip)]
+    SafeModeMessage(SafeModeMessage),
+    /// Closes the window, once the unsaved changes are saved or discarded.
+    CloseRequested,
     /// Workaround for layouts not being updated when we only change its mutable state
-    RecomputeLayout,
+    #[serde(skip)]
+    LayoutChange(LayoutChange<NodeIndex>),
+    /// Replays the next message of the session passed with `--replay-session`.
+    #[serde(skip)]
+    ReplaySessionStep,
+    /// A message of the graph view of the document, see [`Message::in_document`]. Unwrapped before
+    /// it is recorded.
+    #[serde(skip)]
+    InDocument {
+        document: DocumentId,
+        message: Box<Message>,
+    },
+}
+
+impl Message {
+    /// Tags the messages referring to the nodes and connections of the graph with the document they
+    /// have been produced for. Node indices are only meaningful within their graph, so such
+    /// messages are dropped if another document has been focused before they are handled.
+    pub fn in_document(self, document: DocumentId) -> Message {
+        let refers_to_graph = matches!(
+            self,
+            Message::NodeMessage { .. }
+                | Message::DisconnectChannel { .. }
+                | Message::InsertConnection { .. }
+                | Message::InsertConnections { .. }
+                | Message::SpliceConnection { .. }
+                | Message::Reconnect { .. }
+                | Message::SetPrimaryConnection { .. }
+                | Message::CreateConnectedNode { .. }
+                | Message::FreezeValue { .. }
+                | Message::SetNodeColor { .. }
+                | Message::SetNodeMuted { .. }
+                | Message::SetNodeSoloed { .. }
+                | Message::SetNodeCollapsed { .. }
+                | Message::ToggleCollapseAll
+                | Message::PreviewCollapsedPane { .. }
+                | Message::SetNodePinned { .. }
+                | Message::SetNodeOpacity { .. }
+                | Message::SetNodeClickThrough { .. }
+                | Message::BroadcastToBehaviour { .. }
+                | Message::SaveSelectionToLibrary
+                | Message::RemoveNode { .. }
+                | Message::SavePreset { .. }
+                | Message::UpdateFromLibrary { .. }
+                | Message::PushToLibrary { .. }
+                | Message::ToggleChannelGroup { .. }
+                | Message::SetArrangingChannels { .. }
+                | Message::MoveChannel { .. }
+                | Message::ResetChannelOrder { .. }
+                | Message::SetNodeSelected { .. }
+                | Message::AlignSelectedNodes(_)
+                | Message::ScrollNodeContent { .. }
+                | Message::LayoutChange(_)
+        );
+
+        if refers_to_graph {
+            Message::InDocument { document, message: Box::new(self) }
+        } else {
+            self
+        }
+    }
 }
 
 pub struct ApplicationFlags {
     graph: ExecutionGraph,
+    config: Arc<SharedConfig>,
+    renderer: Renderer,
+    node_errors: Arc<NodeErrors>,
+    value_taps: Arc<ValueTaps>,
+    watchdog: Arc<ExecutionWatchdog>,
+    executor_thread: Arc<ArcSwapOption<AppliedThreadSettings>>,
+    background_tasks: Arc<BackgroundTasks>,
+    pinned_schedules: Arc<PinnedSchedules>,
+    recording_control: Arc<RecordingControl>,
+    autosave: Autosave,
+    /// An autosave newer than the last explicitly saved graph, offered to be restored.
+    restorable_autosave: Option<PathBuf>,
+    crash_reporter: CrashReporter,
+    /// The report of a crash of the previous session, offered instead of the autosave.
+    crash_report: Option<CrashReport>,
+    /// The graph passed on the command line. The start screen is shown instead, if there is none.
+    graph_path: Option<PathBuf>,
+    /// Set if the previous launches died during startup, see [`safe_mode`].
+    safe_mode: Option<SafeModeBanner>,
+    close_requests: CloseRequests,
+    exit_requested: Arc<AtomicBool>,
+    frame_rate: FrameRate,
+    main_thread_waker: Arc<MainThreadWaker>,
+    session_recorder: Arc<SessionRecorder>,
+    /// The session passed with `--replay-session`, replayed once the graph is opened.
+    session_replay: Option<SessionReplay>,
 }
 
 pub struct ApplicationState {
-    graph: ExecutionGraph,
-    floating_panes_state: FloatingPanesState,
-    floating_panes_content_state: FloatingPanesBehaviourState,
-    graph_validation_errors: GraphValidationErrors,
+    /// The document of the focused tab, which is the only one displayed.
+    document: Document,
+    /// The documents of the other tabs, in the order of the tabs. Only executed while they are
+    /// pinned to keep running in the background.
+    background_documents: Vec<Document>,
+    /// The position of the focused tab among all tabs.
+    focused_tab: usize,
+    config: Arc<SharedConfig>,
+    renderer: Renderer,
+    node_errors: Arc<NodeErrors>,
+    value_taps: Arc<ValueTaps>,
+    watchdog: Arc<ExecutionWatchdog>,
+    executor_thread: Arc<ArcSwapOption<AppliedThreadSettings>>,
+    background_tasks: Arc<BackgroundTasks>,
+    /// The schedules of the pinned background documents, executed along with the focused one.
+    pinned_schedules: Arc<PinnedSchedules>,
+    /// Starts and stops recording or replaying the outputs of source nodes, see [`graph::recording`].
+    recording_control: Arc<RecordingControl>,
+    autosave: Autosave,
+    crash_reporter: CrashReporter,
+    close_requests: CloseRequests,
+    /// Read by the event loop, which exits once it is set.
+    exit_requested: Arc<AtomicBool>,
+    /// Measured by the event loop, see [`idle`].
+    frame_rate: FrameRate,
+    /// Wakes the event loop once the executor submits tasks to the main thread, see [`idle`].
+    main_thread_waker: Arc<MainThreadWaker>,
+    modal: Option<Modal>,
+    preferences: Preferences,
+    presets: Presets,
+    library: Library,
+    palette: NodePalette,
+    start_screen: StartScreen,
+    /// The banner displayed while in safe mode, `None` outside of it.
+    safe_mode: Option<SafeModeBanner>,
+    /// The node blocking the executor, as last reported by the watchdog.
+    stalled_task: Option<StalledTask>,
+    throughput: ThroughputRates,
+    /// The execution statistics of the focused graph, as last sampled.
+    statistics: Option<ExecutionStatistics>,
+    /// The nodes whose non-finite output values were replaced within the last sampled window.
+    sanitizing_nodes: HashSet<NodeId>,
+    /// Shared with the event loop, which records the input events, see [`session`].
+    session_recorder: Arc<SessionRecorder>,
+    /// The last applied value of [`Config::record_sessions`](config::Config::record_sessions).
+    session_recording_enabled: bool,
+    /// The session being replayed, `None` once all of its messages have been replayed.
+    session_replay: Option<SessionReplay>,
 }
 
 impl Application for ApplicationState {
@@ -86,191 +485,2691 @@ impl Application for ApplicationState {
     type Flags = ApplicationFlags; // The data needed to initialize your Application.
 
     fn new(flags: ApplicationFlags) -> (Self, Command<Self::Message>) {
-        (
-            Self {
-                graph: flags.graph,
-                floating_panes_state: Default::default(),
-                floating_panes_content_state: FloatingPanesBehaviourState::default(),
-                graph_validation_errors: Default::default(),
-            },
-            Command::none(),
-        )
+        let mut state = Self {
+            document: Document::new(flags.graph),
+            background_documents: Vec::new(),
+            focused_tab: 0,
+            config: flags.config,
+            renderer: flags.renderer,
+            node_errors: flags.node_errors,
+            value_taps: flags.value_taps,
+            watchdog: flags.watchdog,
+            executor_thread: flags.executor_thread,
+            background_tasks: flags.background_tasks,
+            pinned_schedules: flags.pinned_schedules,
+            recording_control: flags.recording_control,
+            stalled_task: None,
+            autosave: flags.autosave,
+            crash_reporter: flags.crash_reporter,
+            close_requests: flags.close_requests,
+            exit_requested: flags.exit_requested,
+            frame_rate: flags.frame_rate,
+            main_thread_waker: flags.main_thread_waker,
+            modal: flags.crash_report.map(crash_report_modal).or_else(|| {
+                flags.restorable_autosave.map(|path| {
+                    Modal::new(
+                        ModalAction::RestoreAutosave(path.clone()),
+                        "Restore autosave?",
+                        format!(
+                            "An autosave newer than the last saved graph was found at `{}`. It may \
+                             contain unsaved changes from a previous session.",
+                            path.display()
+                        ),
+                        "Restore",
+                    )
+                })
+            }),
+            preferences: Default::default(),
+            presets: Presets::load(),
+            library: Library::load(),
+            palette: Default::default(),
+            start_screen: Default::default(),
+            safe_mode: flags.safe_mode,
+            throughput: ThroughputRates::default(),
+            statistics: None,
+            sanitizing_nodes: HashSet::new(),
+            session_recorder: flags.session_recorder,
+            session_recording_enabled: false,
+            session_replay: flags.session_replay,
+        };
+
+        match flags.graph_path {
+            Some(path) if state.open_graph(path) => {
+                state.document.graph.flush();
+            }
+            _ => state.start_screen.open = true,
+        }
+
+        // The startup is not considered complete before the opened graph has been executed, see
+        // `safe_mode`. Without a graph, nothing is executed until one is opened.
+        if state.document.graph.scheduled_generation().is_none() {
+            startup::reach(Milestone::FirstExecution);
+        }
+
+        if let Some(replay) = state.session_replay.as_mut() {
+            state.start_screen.open = false;
+            replay.start(&state.document.graph);
+        }
+
+        state.update_session_recording();
+
+        (state, Command::none())
     }
 
     fn title(&self) -> String {
-        String::from("DVSynth")
+        let document_name = self.document.name();
+        let unsaved_marker = if self.document.unsaved_changes { " *" } else { "" };
+
+        match document_name {
+            Some(document_name) => format!("DVSynth — {}{}", document_name, unsaved_marker),
+            None => format!("DVSynth{}", unsaved_marker),
+        }
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
-        let mut update_schedule = false;
+        let message = match message {
+            Message::InDocument { document, message } => {
+                if document != self.document.id {
+                    debug!(
+                        Category::Ui,
+                        "Dropping a message of a document that is no longer focused: {:?}", message
+                    );
+                    return Command::none();
+                }
+
+                *message
+            }
+            message => message,
+        };
+
+        self.session_recorder.record_message(&message);
 
         match message {
-            Message::NodeMessage { node, message } => {
-                match message {
-                    NodeMessage::NodeBehaviourMessage(message) => {
-                        if let Some(node_data) = self.graph.node_weight_mut(node) {
-                            node_data.update(NodeEvent::Message(message));
+            Message::NodeMessage { node, message } => match message {
+                NodeMessage::NodeBehaviourMessage(message) => {
+                    let command = GraphCommand::SetNodeParameter { node, message };
+
+                    // Changing the settings of a node without connections cannot break anything.
+                    if self.document.graph.neighbors_undirected(node).next().is_some() {
+                        self.apply_or_confirm(command, "Change the node settings?");
+                    } else {
+                        self.apply(command);
+                    }
+                }
+            },
+            Message::DisconnectChannel { channel } => self.disconnect_channel(channel),
+            Message::InsertConnection { connection } => {
+                self.apply(GraphCommand::InsertEdge { connection, primary: false });
+            }
+            Message::InsertConnections { connections } => {
+                self.apply(GraphCommand::InsertEdges { connections });
+            }
+            Message::SpliceConnection { connection, through } => {
+                if self.apply(GraphCommand::RemoveEdge { connection }).is_some() {
+                    for connection in through.iter().cloned() {
+                        self.apply(GraphCommand::InsertEdge { connection, primary: false });
+                    }
+                }
+
+                self.document.floating_panes_content_state.context_menu = None;
+            }
+            Message::Reconnect { connection } => {
+                // Replaces the current connection of the input.
+                if self.document.graph.can_connect(&connection) {
+                    self.apply(GraphCommand::InsertEdge { connection, primary: false });
+                }
+
+                self.document.floating_panes_content_state.context_menu = None;
+            }
+            Message::ReconnectLast => {
+                if let Some(connection) = self.document.connection_history.most_recent(&self.document.graph) {
+                    self.apply(GraphCommand::InsertEdge { connection, primary: false });
+                }
+            }
+            Message::SetPrimaryConnection { connection } => {
+                self.apply(GraphCommand::SetPrimaryEdge { connection });
+                self.document.floating_panes_content_state.context_menu = None;
+            }
+            Message::CreateConnectedNode { channel, position } => {
+                self.create_connected_node(channel, position)
+            }
+            Message::FreezeValue { channel } => {
+                self.value_taps.request(channel);
+                self.document.floating_panes_content_state.context_menu = None;
+            }
+            Message::ValueSnapshotTaken(ValueSnapshot { channel, value }) => match value {
+                // Snapshots arriving while another dialog is open are dropped.
+                Ok(value) if self.modal.is_none() => {
+                    let label = self.document.graph.channel_label(channel).unwrap_or_default();
+
+                    self.modal = Some(
+                        Modal::new(
+                            ModalAction::FreezeValue { channel, value },
+                            "Freeze value?",
+                            format!(
+                                "A Constant node with the current value `{}` of `{}` will be created.",
+                                ValueFormatter::get().format_primitive(value),
+                                label
+                            ),
+                            "Create",
+                        )
+                        .with_option("Replace the connections of the channel"),
+                    );
+                }
+                Ok(_) => (),
+                Err(error) => error!(Category::Ui, "Could not freeze the value of the channel: {}", error),
+            },
+            Message::ExecutingGenerationChanged => (),
+            // The event loop runs the tasks once the message has woken it up.
+            Message::MainThreadTasksSubmitted => (),
+            Message::SampleStatistics => {
+                let schedule = self.document.graph.active_schedule.load_full();
+                let show_throughput = self.config.get().show_throughput;
+
+                if show_throughput {
+                    self.throughput.sample(schedule.as_deref());
+                }
+
+                // The throughput is sampled along with the statistics, so that they cover the same
+                // window.
+                self.statistics = schedule.as_deref().map(|schedule| {
+                    ExecutionStatistics::sample(
+                        schedule,
+                        &self.document.graph,
+                        Some(self.throughput.rates()).filter(|_| show_throughput),
+                    )
+                });
+
+                // Only log the nodes which have started outputting non-finite values, rather than with
+                // every sample.
+                let previous = std::mem::take(&mut self.sanitizing_nodes);
+
+                for node in self.statistics.iter().flat_map(|statistics| &statistics.nodes) {
+                    if node.sanitized > 0 {
+                        if !previous.contains(&node.id) {
+                            warn!(
+                                Category::Executor,
+                                "Node '{}' outputs non-finite values, {} of them were replaced within the last \
+                                 second.",
+                                node.title,
+                                node.sanitized
+                            );
                         }
+
+                        self.sanitizing_nodes.insert(node.id);
+                    }
+                }
+            }
+            Message::TaskStalled(task) => {
+                if let Some(task) = task.filter(|task| self.document.graph.contains_node(task.node)) {
+                    // Only log the stall once, rather than with every report.
+                    if self.stalled_task.map(|stalled_task| stalled_task.node) != Some(task.node) {
+                        warn!(
+                            Category::Executor,
+                            "Node '{}' has been executing for {:.1} s, it may be stuck.",
+                            self.document.graph[task.node].title,
+                            task.elapsed.as_secs_f32()
+                        );
+                    }
+
+                    self.stalled_task = Some(task);
+                } else {
+                    self.stalled_task = None;
+                }
+            }
+            Message::SetNodeColor { node, color } => {
+                self.document.floating_panes_content_state.context_menu = None;
+                self.apply(GraphCommand::SetNodeColor { node, color });
+            }
+            Message::SetNodeMuted { node, muted } => {
+                self.apply(GraphCommand::SetNodeMuted { node, muted });
+            }
+            Message::SetNodeSoloed { node, soloed } => {
+                self.apply(GraphCommand::SetNodeSoloed { node, soloed });
+            }
+            Message::SetNodeCollapsed { node, collapsed } => {
+                self.apply(GraphCommand::SetNodeCollapsed { node, collapsed });
+            }
+            Message::ToggleCollapseAll => {
+                let graph = &self.document.graph;
+                let collapsed =
+                    graph.node_weights().any(|node_data| !node_data.floating_pane_state.collapsed);
+                let nodes = graph.node_indices().map(|node| (node, collapsed)).collect();
+
+                self.apply(GraphCommand::SetNodesCollapsed { nodes });
+            }
+            Message::PreviewCollapsedPane { node } => {
+                // The cursor may have left the pane in the meantime.
+                if self.document.hovered_collapsed_node == Some(node) {
+                    if let Some(node_data) = self.document.graph.graph_mut().node_weight_mut(node) {
+                        node_data.floating_pane_state.previewed = node_data.floating_pane_state.collapsed;
                     }
                 }
+            }
+            Message::SetNodePinned { node, pinned } => {
+                self.apply(GraphCommand::SetNodePinned { node, pinned });
+            }
+            Message::SetNodeOpacity { node, opacity } => {
+                self.apply(GraphCommand::SetNodeOpacity { node, opacity });
+            }
+            Message::SetNodeClickThrough { node, click_through } => {
+                self.apply(GraphCommand::SetNodeClickThrough { node, click_through });
+            }
+            Message::BroadcastToBehaviour { behaviour_id, message } => {
+                let nodes = self
+                    .document
+                    .graph
+                    .node_indices()
+                    .filter(|node| self.document.graph[*node].behaviour.name() == behaviour_id)
+                    .collect::<Vec<_>>();
+
+                for node in nodes {
+                    self.apply(GraphCommand::SetNodeParameter { node, message: message.clone() });
+                }
+            }
+            Message::SaveSelectionToLibrary => {
+                let selected =
+                    self.document.graph.node_weights().filter(|node_data| node_data.selected).count();
+
+                if selected == 0 {
+                    warn!(Category::Ui, "Select the nodes to save into the library first.");
+                } else {
+                    self.modal = Some(
+                        Modal::new(
+                            ModalAction::SaveToLibrary,
+                            "Save to library",
+                            format!(
+                                "The {} selected nodes and the connections among them will be saved under the \
+                                 name, to be inserted into any graph from the node palette.",
+                                selected
+                            ),
+                            "Save",
+                        )
+                        .with_text("Name", ""),
+                    );
+                }
+            }
+            Message::SavePreset { node } => {
+                self.document.floating_panes_content_state.context_menu = None;
 
-                update_schedule = true;
+                if let Some(node_data) = self.document.graph.node_weight(node) {
+                    self.modal = Some(
+                        Modal::new(
+                            ModalAction::SavePreset(node),
+                            "Save as preset",
+                            format!(
+                                "The settings of the {} node `{}` will be saved under the name, to be \
+                                 inserted into any graph from the preferences.",
+                                node_data.behaviour.name(),
+                                node_data.title
+                            ),
+                            "Save",
+                        )
+                        .with_text("Name", &node_data.title),
+                    );
+                }
             }
-            Message::DisconnectChannel { channel } => {
-                self.graph.retain_edges(|frozen, edge| {
-                    let (from, to) = frozen.edge_endpoints(edge).unwrap();
-                    let node_index = match channel.channel_direction {
-                        ChannelDirection::In => to,
-                        ChannelDirection::Out => from,
+            Message::UpdateFromLibrary { node } => {
+                self.document.floating_panes_content_state.context_menu = None;
+                self.update_from_library(node);
+            }
+            Message::PushToLibrary { node } => {
+                self.document.floating_panes_content_state.context_menu = None;
+                self.push_to_library(node);
+            }
+            Message::RemoveNode { node } => {
+                let incoming_count = self.document.graph.edges_directed(node, Direction::Incoming).count();
+                let outgoing_count = self.document.graph.edges_directed(node, Direction::Outgoing).count();
+                let connection_count = incoming_count + outgoing_count;
+                let config = self.config.get();
+                let candidates = if config.reconnect_on_removal != ReconnectOnRemoval::Never {
+                    self.document.graph.reconnection_candidates(node)
+                } else {
+                    Vec::new()
+                };
+                let simple_chain = incoming_count == 1 && outgoing_count == 1 && candidates.len() == 1;
+
+                if !candidates.is_empty()
+                    && !(simple_chain && config.reconnect_on_removal == ReconnectOnRemoval::Silently)
+                {
+                    let graph = &self.document.graph;
+                    let title = graph.node_weight(node).map(|node_data| node_data.title.clone());
+                    let mut connected_inputs = Vec::new();
+                    // Each input is preselected to be connected to the first of its candidates.
+                    let choices = candidates
+                        .iter()
+                        .map(|candidate| {
+                            let checked = !connected_inputs.contains(&candidate.to());
+
+                            connected_inputs.push(candidate.to());
+                            (
+                                format!(
+                                    "{} \u{2192} {}",
+                                    graph.channel_label(candidate.from()).unwrap_or_default(),
+                                    graph.channel_label(candidate.to()).unwrap_or_default(),
+                                ),
+                                checked,
+                            )
+                        })
+                        .collect::<Vec<_>>();
+
+                    self.modal = Some(
+                        Modal::new(
+                            ModalAction::DissolveNode { node, candidates },
+                            "Remove node?",
+                            format!(
+                                "The node `{}` is connected between other nodes. The checked connections \
+                                 will be created in its place.",
+                                title.unwrap_or_default(),
+                            ),
+                            "Remove",
+                        )
+                        .with_choices(choices),
+                    );
+                } else {
+                    let reconnections = if simple_chain { candidates } else { Vec::new() };
+                    let consequences = if connection_count > 0 {
+                        self.breaking_consequences(&GraphCommand::DissolveNode {
+                            node,
+                            reconnections: reconnections.clone(),
+                        })
+                    } else {
+                        None
                     };
+                    let confirm = (consequences.is_some() && config.confirm_breaking_edits)
+                        || config
+                            .node_removal_confirmation_threshold()
+                            .map_or(false, |threshold| connection_count >= threshold);
 
-                    if node_index == channel.node_index {
-                        let edge_data = frozen.edge_weight(edge).unwrap();
+                    if confirm {
+                        let title =
+                            self.document.graph.node_weight(node).map(|node_data| node_data.title.clone());
+                        let description = match consequences {
+                            Some(consequences) => consequences.describe(&self.document.graph, false),
+                            None => format!(
+                                "The node `{}` has {} connections, which will be removed along with it.",
+                                title.unwrap_or_default(),
+                                connection_count,
+                            ),
+                        };
 
-                        if edge_data.get_endpoint(channel.channel_direction.inverse()) == channel.into() {
-                            return false;
+                        self.modal = Some(Modal::new(
+                            ModalAction::RemoveNode { node, reconnections },
+                            "Remove node?",
+                            description,
+                            "Remove",
+                        ));
+                    } else {
+                        if let Some(consequences) = consequences {
+                            warn!(Category::Ui, "{}", consequences.describe(&self.document.graph, true));
                         }
+
+                        self.remove_node(node, reconnections);
                     }
+                }
+            }
+            Message::ToggleChannelGroup { node, direction, group } => {
+                if let Some(node_data) = self.document.graph.graph_mut().node_weight_mut(node) {
+                    node_data.element_state.toggle_channel_group(direction, &group);
+                    self.mark_modified();
+                }
+            }
+            Message::SetArrangingChannels { node, arranging } => {
+                self.document.floating_panes_content_state.context_menu = None;
 
-                    true
-                });
+                if let Some(node_data) = self.document.graph.graph_mut().node_weight_mut(node) {
+                    node_data.arranging_channels = arranging;
+                }
+            }
+            Message::MoveChannel { node, direction, channel, target } => {
+                self.apply(GraphCommand::MoveChannel { node, direction, channel, target });
+            }
+            Message::ResetChannelOrder { node } => {
+                self.document.floating_panes_content_state.context_menu = None;
+                self.apply(GraphCommand::SetChannelOrder { node, order: Default::default() });
+            }
+            Message::SetNodeSelected { node, selected } => {
+                if let Some(node_data) = self.document.graph.graph_mut().node_weight_mut(node) {
+                    node_data.selected = selected;
+                }
+            }
+            Message::AlignSelectedNodes(alignment) => self.align_selected_nodes(alignment),
+            Message::ScrollNodeContent { node, offset } => {
+                if let Some(node_data) = self.document.graph.graph_mut().node_weight_mut(node) {
+                    node_data.element_state.set_scroll_offset(offset);
+                }
+            }
+            Message::KeyPressed { shortcut, captured } => {
+                if self.preferences.is_capturing() {
+                    self.preferences.capture(shortcut, &self.config);
+                } else if let Some(action) = self.config.get().shortcuts.action(shortcut, captured) {
+                    if let Some(message) = self.action_message(action) {
+                        return self.update(message);
+                    }
+                }
+            }
+            Message::TogglePreferences => self.preferences.toggle(&self.config.get(), &self.presets),
+            Message::PreferencesMessage(PreferencesMessage::InsertPreset(index)) => self.insert_preset(index),
+            Message::PreferencesMessage(message) => {
+                self.preferences.update(message, &self.config, &mut self.presets)
+            }
+            Message::ToggleNodePalette => self.palette.toggle(&self.library),
+            Message::NodePaletteMessage(NodePaletteMessage::Insert(name)) => self.insert_node(&name),
+            Message::NodePaletteMessage(NodePaletteMessage::InsertFromLibrary(name)) => {
+                self.insert_library_entry(&name)
+            }
+            Message::NodePaletteMessage(message) => self.palette.update(message, &self.config),
+            Message::ModalMessage(ModalMessage::ToggleOption(checked)) => {
+                if let Some(modal) = self.modal.as_mut() {
+                    modal.set_option(checked);
+                }
+            }
+            Message::ModalMessage(ModalMessage::ToggleChoice(index, checked)) => {
+                if let Some(modal) = self.modal.as_mut() {
+                    // An input can only be connected once, checking a candidate unchecks the other
+                    // candidates of its input.
+                    if let (true, ModalAction::DissolveNode { candidates, .. }) = (checked, &modal.action) {
+                        let input = candidates[index].to();
+                        let others = (0..candidates.len())
+                            .filter(|other| *other != index && candidates[*other].to() == input)
+                            .collect::<Vec<_>>();
+
+                        for other in others {
+                            modal.set_choice(other, false);
+                        }
+                    }
 
-                update_schedule = true;
+                    modal.set_choice(index, checked);
+                }
             }
-            Message::InsertConnection { connection } => {
-                let from = connection.from();
-                let to = connection.to();
+            Message::ModalMessage(ModalMessage::UpdateText(text)) => {
+                if let Some(modal) = self.modal.as_mut() {
+                    modal.set_text(text);
+                }
+            }
+            Message::ModalMessage(message) => {
+                if let Some(modal) = self.modal.take() {
+                    let option_checked = modal.is_option_checked();
+                    let checked_choices = modal.checked_choices();
+                    let text = modal.text().to_string();
 
-                self.graph.add_edge(
-                    from.node_index,
-                    to.node_index,
-                    EdgeData { endpoint_from: from.into(), endpoint_to: to.into() },
-                );
+                    match (message, modal.action) {
+                        (ModalMessage::Confirm, ModalAction::RestoreAutosave(path)) => {
+                            match self.load_graph(&path) {
+                                Ok(()) => {
+                                    // The restored graph is not saved anywhere but in the autosave.
+                                    self.mark_modified();
+                                    self.start_screen.open = false;
+                                }
+                                Err(error) => error!(
+                                    Category::Ui,
+                                    "Could not restore the autosave `{}`: {}",
+                                    path.display(),
+                                    error
+                                ),
+                            }
+                        }
+                        (ModalMessage::Confirm, ModalAction::RemoveNode { node, reconnections }) => {
+                            self.remove_node(node, reconnections)
+                        }
+                        (ModalMessage::Confirm, ModalAction::DissolveNode { node, candidates }) => {
+                            let reconnections =
+                                checked_choices.into_iter().map(|index| candidates[index].clone()).collect();
+
+                            self.remove_node(node, reconnections);
+                        }
+                        (ModalMessage::Confirm, ModalAction::ApplyEdit(command)) => {
+                            self.apply(command);
+                        }
+                        (ModalMessage::Confirm, ModalAction::FreezeValue { channel, value }) => {
+                            self.freeze_value(channel, value, option_checked);
+                        }
+                        (ModalMessage::Confirm, ModalAction::SaveGraph) => {
+                            if option_checked {
+                                self.config.modify(|config| config.review_changes_before_saving = false);
+                            }
+
+                            self.save_graph();
+                        }
+                        (ModalMessage::Confirm, ModalAction::Quit) => {
+                            if self.save_all_documents() {
+                                self.request_exit();
+                            }
+                        }
+                        (ModalMessage::Alternative, ModalAction::Quit) => self.request_exit(),
+                        (ModalMessage::Confirm, ModalAction::OpenTemplate(template)) => {
+                            if self.save_graph() {
+                                self.open_template(template);
+                            }
+                        }
+                        (ModalMessage::Alternative, ModalAction::OpenTemplate(template)) => {
+                            self.open_template(template);
+                        }
+                        (ModalMessage::Confirm, ModalAction::CloseDocument(tab)) => {
+                            if self.save_graph() {
+                                self.close_document(tab);
+                            }
+                        }
+                        (ModalMessage::Alternative, ModalAction::CloseDocument(tab)) => {
+                            self.close_document(tab)
+                        }
+                        (ModalMessage::Confirm, ModalAction::ReportCrash(report)) => {
+                            match report.recovery_path.as_ref() {
+                                Some(recovery_path) => match self.load_graph(recovery_path) {
+                                    Ok(()) => {
+                                        // The recovered graph is not saved anywhere but in the
+                                        // recovery file.
+                                        self.mark_modified();
+                                        self.start_screen.open = false;
+                                    }
+                                    Err(error) => error!(
+                                        Category::Ui,
+                                        "Could not load the recovery snapshot `{}`: {}",
+                                        recovery_path.display(),
+                                        error
+                                    ),
+                                },
+                                None => self.open_crash_report_location(&report),
+                            }
+                        }
+                        (ModalMessage::Alternative, ModalAction::ReportCrash(report)) => {
+                            self.open_crash_report_location(&report);
+                            // Keep offering the recovery snapshot.
+                            self.modal = Some(crash_report_modal(report));
+                        }
+                        (ModalMessage::Confirm, ModalAction::SavePreset(node)) => {
+                            self.save_preset(node, &text);
+                        }
+                        (ModalMessage::Confirm, ModalAction::OverwritePreset(preset)) => {
+                            self.store_preset(preset, true);
+                        }
+                        (ModalMessage::Alternative, ModalAction::OverwritePreset(preset)) => {
+                            let name = self.presets.unique_name(&preset.name);
+
+                            self.store_preset(Preset { name, ..preset }, false);
+                        }
+                        (ModalMessage::Confirm, ModalAction::SaveToLibrary) => self.save_to_library(&text),
+                        (ModalMessage::Confirm, ModalAction::SanitizeNumerics) => {
+                            match text.trim().parse::<f64>() {
+                                Ok(substitute) if substitute.is_finite() => {
+                                    self.set_sanitize_numerics(Some(substitute))
+                                }
+                                _ => {
+                                    warn!(Category::Ui, "`{}` is not a finite number.", text);
+                                    self.modal = Some(sanitize_numerics_modal(text));
+                                }
+                            }
+                        }
+                        (ModalMessage::Confirm, ModalAction::OverwriteLibraryEntry(entry)) => {
+                            self.store_library_entry(entry, true);
+                        }
+                        (ModalMessage::Alternative, ModalAction::OverwriteLibraryEntry(mut entry)) => {
+                            entry.info.name = self.library.unique_name(&entry.info.name);
 
-                update_schedule = true;
+                            self.store_library_entry(entry, false);
+                        }
+                        _ => (),
+                    }
+                }
             }
-            Message::RecomputeLayout => (),
-        }
+            Message::ExportGraphImage => {
+                // `update` is executed on the main thread, which is required by the file dialog.
+                let path = rfd::FileDialog::new()
+                    .add_filter("PNG image", &["png"])
+                    .set_file_name("graph.png")
+                    .save_file();
+
+                if let Some(path) = path {
+                    let renderer = self.renderer.clone();
 
-        if update_schedule {
-            if let Err(vec) = self.graph.update_schedule() {
-                eprintln!("Could not construct the graph schedule:\n{:?}", vec);
-                self.graph_validation_errors = vec.into();
-            } else {
-                self.graph_validation_errors = Default::default();
+                    // Only the panes are drawn on the UI thread, rendering and encoding the image
+                    // may take seconds.
+                    match export::prepare_graph_image(self, &renderer) {
+                        Ok(image) => image.spawn_save(renderer, path),
+                        Err(error) => error!(Category::Ui, "Could not export the graph: {}", error),
+                    }
+                }
             }
-        }
+            Message::ExportStatistics => {
+                let path = rfd::FileDialog::new()
+                    .add_filter("CSV, one row per node", &["csv"])
+                    .add_filter("JSON, including the channels", &["json"])
+                    .set_file_name("statistics.csv")
+                    .save_file();
 
-        Command::none()
-    }
+                if let Some(path) = path {
+                    // The last sampled window is exported, rather than the live measurements.
+                    match statistics::export_statistics(
+                        self.statistics.as_ref(),
+                        &self.document.metadata.title,
+                        &path,
+                    ) {
+                        Ok(()) => {
+                            info!(Category::Ui, "Exported the execution statistics to `{}`.", path.display())
+                        }
+                        Err(error) => {
+                            error!(Category::Ui, "Could not export the execution statistics: {}", error)
+                        }
+                    }
+                }
+            }
+            Message::ToggleExecutionOrder => {
+                self.config.modify(|config| config.show_execution_order = !config.show_execution_order);
+            }
+            Message::ToggleImplicitPromotion => {
+                let implicit_promotion = !self.document.metadata.implicit_promotion;
 
-    fn view(&mut self) -> iced::Element<Message> {
-        let theme: Box<dyn Theme> = Box::new(style::Dark);
-        let node_indices = self.graph.node_indices().collect::<Vec<_>>();
-        let connections = self.graph.get_connections();
+                self.document.metadata.implicit_promotion = implicit_promotion;
+                self.document.graph.set_implicit_promotion(implicit_promotion);
+                self.mark_modified();
+                info!(
+                    Category::Ui,
+                    "Implicit type promotion {}.",
+                    if implicit_promotion { "enabled" } else { "disabled" }
+                );
+            }
+            Message::ToggleSanitizeNumerics => {
+                if self.document.metadata.sanitize_numerics {
+                    self.set_sanitize_numerics(None);
+                } else {
+                    // Let the user pick the substitute, starting with the previous one.
+                    self.modal = Some(sanitize_numerics_modal(self.document.metadata.sanitize_substitute));
+                }
+            }
+            Message::ToggleRecording => {
+                if self.recording_control.status() == RecordingStatus::Recording {
+                    self.recording_control.request(None);
+                    info!(Category::Ui, "Stopped recording the outputs of source nodes.");
+                } else {
+                    let path = rfd::FileDialog::new()
+                        .add_filter("DVSynth recording", &["dvsr"])
+                        .set_file_name("recording.dvsr")
+                        .save_file();
 
-        let mut panes = FloatingPanes::new(
-            &mut self.floating_panes_state,
-            &mut self.floating_panes_content_state,
-            crate::widgets::node::FloatingPanesBehaviour {
-                on_channel_disconnect: |channel| Message::DisconnectChannel { channel },
-                on_connection_create: |connection| Message::InsertConnection { connection },
-                connections,
-                graph_validation_errors: self.graph_validation_errors.clone(),
-                tooltip_style: Some(theme.tooltip()),
-            },
-            Box::new(|| Message::RecomputeLayout),
-        )
-        .theme(&*theme);
+                    if let Some(path) = path {
+                        match Recorder::create(&path) {
+                            Ok(recorder) => {
+                                self.recording_control.request(Some(RecordingMode::Record(recorder)));
+                                info!(
+                                    Category::Ui,
+                                    "Recording the outputs of source nodes to `{}`.",
+                                    path.display()
+                                );
+                            }
+                            Err(error) => error!(Category::Ui, "Could not start the recording: {}", error),
+                        }
+                    }
+                }
+            }
+            Message::ToggleReplay => {
+                if self.recording_control.status() == RecordingStatus::Replaying {
+                    self.recording_control.request(None);
+                    info!(Category::Ui, "Stopped the replay, continuing with live execution.");
+                } else {
+                    let path = rfd::FileDialog::new().add_filter("DVSynth recording", &["dvsr"]).pick_file();
 
-        for (node_index, node_data) in node_indices.iter().zip(self.graph.node_weights_mut()) {
-            panes = panes.insert(*node_index, node_data.view(*node_index, theme.as_ref()));
-        }
+                    if let Some(path) = path {
+                        match Replayer::open(&path) {
+                            Ok(replayer) => {
+                                self.recording_control.request(Some(RecordingMode::Replay(replayer)));
+                                info!(Category::Ui, "Replaying the recording `{}`.", path.display());
+                            }
+                            Err(error) => error!(Category::Ui, "Could not open the recording: {}", error),
+                        }
+                    }
+                }
+            }
+            Message::SaveGraph => {
+                let changes = if self.config.get().review_changes_before_saving {
+                    self.changes_since_saved()
+                } else {
+                    None
+                };
 
-        panes.into()
-    }
-}
+                match changes {
+                    Some(changes) if !changes.is_empty() => {
+                        self.modal = Some(
+                            Modal::new(
+                                ModalAction::SaveGraph,
+                                "Overwrite saved graph?",
+                                changes.describe(MAX_LISTED_CHANGES),
+                                "Save",
+                            )
+                            .with_option("Don't ask again"),
+                        );
+                    }
+                    _ => {
+                        self.save_graph();
+                    }
+                }
+            }
+            Message::CompareWithSaved => {
+                if self.document.path.is_none() {
+                    info!(
+                        Category::Ui,
+                        "The graph has not been saved yet, there is nothing to compare it with."
+                    );
+                } else if let Some(changes) = self.changes_since_saved() {
+                    self.modal = Some(Modal::new(
+                        ModalAction::SaveGraph,
+                        "Changes since saved",
+                        changes.describe(MAX_LISTED_CHANGES),
+                        "Save",
+                    ));
+                }
+            }
+            Message::CloseRequested => {
+                let unsaved_count = self.documents().filter(|document| document.unsaved_changes).count();
 
-fn main() {
-    let graph: ExecutionGraph = {
-        let mut graph = Graph::new();
-
-        graph.add_node(NodeData::new(
-            "My Constant Node #1",
-            [210.0, 10.0],
-            Box::new(ConstantNodeBehaviour::new(42.0_f32)),
-        ));
-
-        graph.add_node(NodeData::new(
-            "My Constant Node #2",
-            [10.0, 10.0],
-            Box::new(ConstantNodeBehaviour::new(84.0_f32)),
-        ));
-
-        graph.add_node(NodeData::new(
-            "My Bin Op #1",
-            [410.0, 10.0],
-            Box::new(BinaryOpNodeBehaviour::default()),
-        ));
-
-        graph.add_node(NodeData::new(
-            "My Window #1",
-            [610.0, 10.0],
-            Box::new(WindowNodeBehaviour::default()),
-        ));
-
-        graph.add_node(NodeData::new(
-            "My Array Constructor",
-            [10.0, 310.0],
-            Box::new(ArrayConstructorNodeBehaviour::default()),
-        ));
-
-        graph.add_node(NodeData::new(
-            "My List Constructor",
-            [10.0, 510.0],
-            Box::new(ListConstructorNodeBehaviour::default()),
-        ));
-
-        graph.add_node(NodeData::new("My Debug", [210.0, 510.0], Box::new(DebugNodeBehaviour::default())));
-        graph.add_node(NodeData::new("My Debug 2", [410.0, 510.0], Box::new(DebugNodeBehaviour::default())));
-
-        graph.add_node(NodeData::new("My Counter", [810.0, 10.0], Box::new(CounterNodeBehaviour::default())));
-
-        graph.into()
-    };
+                if unsaved_count > 0 {
+                    let description = if unsaved_count == 1 {
+                        "The graph has unsaved changes, which will be lost unless saved.".to_string()
+                    } else {
+                        format!(
+                            "{} graphs have unsaved changes, which will be lost unless saved.",
+                            unsaved_count
+                        )
+                    };
 
-    let active_schedule = graph.active_schedule.clone();
-    let settings = Settings {
-        window: window::Settings {
-            icon: None, // TODO
-            ..window::Settings::default()
-        },
-        antialiasing: true,
-        ..Settings::with_flags(ApplicationFlags { graph })
-    };
-    let (execution_context, main_thread_task_receiver) = ApplicationContext::from_settings(&settings);
-    let renderer_settings = iced_wgpu::Settings {
-        default_font: settings.default_font,
-        default_text_size: settings.default_text_size,
-        // because anti-aliasing is enabled in the settings
-        antialiasing: Some(iced_wgpu::Antialiasing::MSAAx4),
-        instance: Some(execution_context.renderer.instance.clone()),
-        device_queue: Some((
-            execution_context.renderer.device.clone(),
-            execution_context.renderer.queue.clone(),
-        )),
-        ..iced_wgpu::Settings::default()
-    };
-    let _join_handle = GraphExecutor::spawn(execution_context, active_schedule);
+                    self.modal = Some(
+                        Modal::new(ModalAction::Quit, "Save changes?", description, "Save")
+                            .with_alternative("Discard"),
+                    );
+                } else {
+                    self.request_exit();
+                }
+            }
+            Message::OpenGraph | Message::StartScreenMessage(StartScreenMessage::Browse) => {
+                let path = rfd::FileDialog::new().add_filter("DVSynth graph", &["json"]).pick_file();
+
+                if let Some(path) = path {
+                    // Replacing the graph discards it, make sure it can be recovered.
+                    self.autosave.snapshot_urgent(&self.document.graph, &self.document.metadata);
+
+                    if self.open_graph(path) {
+                        self.start_screen.open = false;
+                    }
+                }
+            }
+            Message::NewDocument => {
+                self.background_documents.push(Document::empty());
+                self.focus_document(self.background_documents.len());
+                self.start_screen.open = false;
+            }
+            Message::SelectDocument { tab } => self.focus_document(tab),
+            Message::SetDocumentPinned { tab, pinned } => {
+                if tab == self.focused_tab {
+                    self.document.run_in_background = pinned;
+                } else if tab <= self.background_documents.len() {
+                    let index = self.background_index(tab);
+
+                    self.background_documents[index].run_in_background = pinned;
+                }
+
+                self.update_pinned_schedules();
+            }
+            Message::CloseDocument { tab } => {
+                let unsaved_changes =
+                    self.document_at(tab).map_or(false, |document| document.unsaved_changes);
+
+                if unsaved_changes {
+                    // Only the focused document can be saved, show which one is about to be closed.
+                    self.focus_document(tab);
+                    self.modal = Some(
+                        Modal::new(
+                            ModalAction::CloseDocument(tab),
+                            "Save changes?",
+                            "The graph has unsaved changes, which will be lost unless saved.",
+                            "Save",
+                        )
+                        .with_alternative("Discard"),
+                    );
+                } else {
+                    self.close_document(tab);
+                }
+            }
+            Message::ToggleStartScreen => self.start_screen.toggle(),
+            Message::StartScreenMessage(StartScreenMessage::OpenRecent(path)) => {
+                self.autosave.snapshot_urgent(&self.document.graph, &self.document.metadata);
+
+                // Otherwise the start screen stays open, so that another graph can be picked.
+                if self.open_graph(path) {
+                    self.start_screen.open = false;
+                }
+            }
+            Message::StartScreenMessage(StartScreenMessage::OpenTemplate(template)) => {
+                if self.document.unsaved_changes {
+                    self.modal = Some(
+                        Modal::new(
+                            ModalAction::OpenTemplate(template),
+                            "Save changes?",
+                            "The graph has unsaved changes, which will be lost unless saved.",
+                            "Save",
+                        )
+                        .with_alternative("Discard"),
+                    );
+                } else {
+                    self.open_template(template);
+                }
+            }
+            Message::StartScreenMessage(StartScreenMessage::Close) => self.start_screen.open = false,
+            Message::SafeModeMessage(SafeModeMessage::RetryNormalMode) => {
+                self.safe_mode = None;
+
+                let substitutions = self.substitutions();
+
+                for document in std::iter::once(&mut self.document).chain(&mut self.background_documents) {
+                    if let Err(error) = document.restore_placeholders(&substitutions) {
+                        error!(Category::Ui, "Could not restore the nodes disabled in safe mode: {}", error);
+                    }
+                }
+
+                self.clear_execution_state();
+                self.crash_reporter.mark_dirty();
+            }
+            Message::SafeModeMessage(SafeModeMessage::DisableBehaviour(name)) => {
+                info!(Category::Ui, "Disabling the nodes of `{}` permanently.", name);
+                self.config.modify(|config| {
+                    config.disabled_behaviours.insert(name);
+                });
+            }
+            // Panes have been moved or resized.
+            Message::LayoutChange(change) => match change {
+                // Only finished gestures are recorded, the intermediate changes merely cause the
+                // layout to be recomputed.
+                LayoutChange::GestureEnd(Some(_)) => self.mark_modified(),
+                LayoutChange::CollapsedPaneHovered(node) => self.document.hovered_collapsed_node = node,
+                _ => (),
+            },
+            Message::ReplaySessionStep => {
+                let step = match self.session_replay.as_mut().and_then(SessionReplay::next_step) {
+                    Some(step) => step,
+                    None => return Command::none(),
+                };
+                let command = match step.message.clone() {
+                    Some(message) => self.update(message),
+                    None => Command::none(),
+                };
+
+                if let Some(replay) = self.session_replay.as_mut() {
+                    replay.check_step(&step);
+
+                    if replay.is_finished() {
+                        replay.finish(&self.document.graph);
+                        self.session_replay = None;
+                    }
+                }
+
+                return command;
+            }
+        }
+
+        // Rebuild the schedule once for all edits caused by the message.
+        if self.document.graph.flush() {
+            self.mark_modified();
+        }
+
+        self.update_session_recording();
+
+        self.autosave.snapshot(&self.document.graph, &self.document.metadata);
+        self.crash_reporter.snapshot(
+            &self.document.graph,
+            &self.document.metadata,
+            self.document.path.as_deref(),
+        );
+
+        Command::none()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = vec![
+            Self::shortcuts(),
+            Subscription::from_recipe(self.close_requests.clone()),
+            Subscription::from_recipe(ValueSnapshots(self.value_taps.clone())),
+            Subscription::from_recipe(StalledTasks(self.watchdog.clone())),
+            Subscription::from_recipe(ExecutingGenerations(self.document.graph.executing_generation.clone())),
+            Subscription::from_recipe(MainThreadWakeups(self.main_thread_waker.clone())),
+            Subscription::from_recipe(StatisticsSamples(self.document.graph.active_schedule.clone())),
+        ];
+
+        if let Some(replay) = &self.session_replay {
+            subscriptions.push(Subscription::from_recipe(SessionReplaySteps(replay.delays())));
+        }
+
+        if let Some(node) = self.document.hovered_collapsed_node {
+            subscriptions.push(Subscription::from_recipe(CollapsedPanePreview(node)));
+        }
+
+        Subscription::batch(subscriptions)
+    }
+
+    fn view(&mut self) -> iced::Element<Message> {
+        startup::reach(Milestone::FirstLayout);
+
+        let config = self.config.get();
+        let theme: Box<dyn Theme> = config.theme.theme();
+
+        if let Some(modal) = self.modal.as_mut() {
+            return modal.view(&config.shortcuts, theme.as_ref()).map(Message::ModalMessage);
+        }
+
+        if self.preferences.open {
+            return self
+                .preferences
+                .view(&config, &self.presets, self.executor_thread.load().as_deref().copied(), theme.as_ref())
+                .map(Message::PreferencesMessage);
+        }
+
+        if self.palette.open {
+            return self
+                .palette
+                .view(&config.node_usage, &config.favorite_nodes, &config.shortcuts, theme.as_ref())
+                .map(Message::NodePaletteMessage);
+        }
+
+        if self.start_screen.open {
+            return self
+                .start_screen
+                .view(&config.recent_graphs, &config.shortcuts, theme.as_ref())
+                .map(Message::StartScreenMessage);
+        }
+
+        let mut graph_validation_errors =
+            self.document.graph.validation_errors().clone().with_node_errors(&self.node_errors);
+
+        if let Some(task) = self.stalled_task.filter(|task| self.document.graph.contains_node(task.node)) {
+            graph_validation_errors =
+                graph_validation_errors.with_stalled_task(task, &self.document.graph[task.node].title);
+        }
+
+        for (origin, nodes) in self.library_instances() {
+            let instance = GraphFile::from_nodes(&self.document.graph, &self.document.metadata, |node| {
+                nodes.contains(&node)
+            });
+
+            if let Some(divergence) = self.library.divergence(&origin, &instance) {
+                graph_validation_errors =
+                    graph_validation_errors.with_library_divergence(nodes, &origin.name, divergence);
+            }
+        }
+
+        let reconnect_candidates = self.document.connection_history.candidates(&self.document.graph);
+        let document_tabs = self.document_tabs();
+        let background_tasks = self.background_tasks.statuses();
+        let schedule_status = ScheduleStatus {
+            edit_generation: self.document.graph.edit_generation(),
+            scheduled_generation: self.document.graph.scheduled_generation(),
+            executing_generation: self.document.graph.executing_generation.get(),
+            failed: self.document.graph.schedule_failed(),
+            ui_frame_rate: Some(self.frame_rate.get().round() as u32).filter(|_| config.show_frame_rate),
+            background_tasks: background_tasks.len(),
+            unresponsive_background_tasks: background_tasks
+                .iter()
+                .filter(|status| **status == BackgroundTaskStatus::Unresponsive)
+                .count(),
+            recording: self.recording_control.status(),
+        };
+        // The kinds of nodes offered to be disabled by the banner of safe mode.
+        let replaced_behaviours = if self.safe_mode.is_some() {
+            self.documents()
+                .flat_map(|document| document.graph.node_weights())
+                .filter(|node_data| node_data.behaviour.is_placeholder())
+                .map(|node_data| node_data.behaviour.name().to_string())
+                .collect()
+        } else {
+            BTreeSet::new()
+        };
+        let document = self.document.id;
+        let mut panes = Self::view_graph(
+            self.document.graph.graph_mut(),
+            &mut self.document.floating_panes_state,
+            &mut self.document.floating_panes_content_state,
+            &graph_validation_errors,
+            reconnect_candidates,
+            &self.library,
+            &config,
+            theme.as_ref(),
+        );
+
+        panes.behaviour.schedule_status = Some(schedule_status);
+        panes.behaviour.document_tabs = Some(document_tabs);
+        panes.behaviour.value_taps = Some(self.value_taps.clone());
+
+        if config.show_throughput {
+            panes.behaviour.throughput = Some(self.throughput.rates().clone());
+        }
+
+        // The failure is indicated by the schedule status instead of the order of the stale schedule.
+        if config.show_execution_order && !self.document.graph.schedule_failed() {
+            let schedule = self.document.graph.active_schedule.load();
+
+            panes.behaviour.execution_order = schedule.as_ref().map(|schedule| schedule.execution_order());
+        }
+
+        let panes = iced::Element::from(panes).map(move |message: Message| message.in_document(document));
+
+        match self.safe_mode.as_mut() {
+            Some(banner) => iced::Column::new()
+                .push(
+                    banner
+                        .view(&replaced_behaviours, &config.disabled_behaviours, theme.as_ref())
+                        .map(Message::SafeModeMessage),
+                )
+                .push(panes)
+                .into(),
+            None => panes,
+        }
+    }
+}
+
+impl ApplicationState {
+    /// Forwards the key presses to be looked up in the keymap.
+    fn shortcuts() -> Subscription<Message> {
+        iced_native::subscription::events_with(|event, status| match event {
+            Event::Keyboard(keyboard::Event::KeyPressed { key_code, modifiers }) => {
+                Shortcut::new(key_code, modifiers).map(|shortcut| Message::KeyPressed {
+                    shortcut,
+                    captured: status == iced_native::event::Status::Captured,
+                })
+            }
+            _ => None,
+        })
+    }
+
+    /// The message triggered by the action. Actions handled by the widgets themselves have no
+    /// message.
+    fn action_message(&self, action: Action) -> Option<Message> {
+        Some(match action {
+            Action::TogglePreferences => Message::TogglePreferences,
+            Action::SaveGraph => Message::SaveGraph,
+            Action::CompareWithSaved => Message::CompareWithSaved,
+            Action::OpenGraph => Message::OpenGraph,
+            Action::ToggleStartScreen => Message::ToggleStartScreen,
+            Action::ToggleNodePalette => Message::ToggleNodePalette,
+            Action::NewDocument => Message::NewDocument,
+            Action::CloseDocument => Message::CloseDocument { tab: self.focused_tab },
+            Action::ExportGraphImage => Message::ExportGraphImage,
+            Action::ExportStatistics => Message::ExportStatistics,
+            Action::ToggleImplicitPromotion => Message::ToggleImplicitPromotion,
+            Action::ToggleSanitizeNumerics => Message::ToggleSanitizeNumerics,
+            Action::ToggleRecording => Message::ToggleRecording,
+            Action::ToggleReplay => Message::ToggleReplay,
+            Action::ToggleExecutionOrder => Message::ToggleExecutionOrder,
+            Action::ReconnectLast => Message::ReconnectLast,
+            Action::ToggleCollapseAll => Message::ToggleCollapseAll,
+            Action::ToggleABSwitches => Message::BroadcastToBehaviour {
+                behaviour_id: ABSwitchNodeBehaviour::NAME.to_string(),
+                message: Box::new(ABSwitchNodeMessage::Toggle),
+            },
+            Action::SaveSelectionToLibrary => Message::SaveSelectionToLibrary,
+            Action::AlignLeft => Message::AlignSelectedNodes(PaneAlignment::Left),
+            Action::AlignRight => Message::AlignSelectedNodes(PaneAlignment::Right),
+            Action::AlignTop => Message::AlignSelectedNodes(PaneAlignment::Top),
+            Action::AlignBottom => Message::AlignSelectedNodes(PaneAlignment::Bottom),
+            Action::AlignHorizontalCenters => Message::AlignSelectedNodes(PaneAlignment::HorizontalCenters),
+            Action::AlignVerticalCenters => Message::AlignSelectedNodes(PaneAlignment::VerticalCenters),
+            Action::DistributeHorizontally => {
+                Message::AlignSelectedNodes(PaneAlignment::DistributeHorizontally)
+            }
+            Action::DistributeVertically => Message::AlignSelectedNodes(PaneAlignment::DistributeVertically),
+            Action::Confirm => Message::ModalMessage(ModalMessage::Confirm),
+            Action::Cancel => Message::ModalMessage(ModalMessage::Cancel),
+            Action::Quit => Message::CloseRequested,
+            Action::RemoveNode => return None,
+        })
+    }
+
+    /// Lets the event loop exit, ending the recording of the session and writing the settings
+    /// first.
+    fn request_exit(&mut self) {
+        self.session_recorder.stop(&self.document.graph);
+        self.config.flush();
+        self.exit_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Starts or stops recording the session, once
+    /// [`Config::record_sessions`](config::Config::record_sessions) has been toggled.
+    fn update_session_recording(&mut self) {
+        let record_sessions = self.config.get().record_sessions;
+
+        if record_sessions == self.session_recording_enabled {
+            return;
+        }
+
+        self.session_recording_enabled = record_sessions;
+
+        if record_sessions {
+            match self.session_recorder.start(&self.document.graph, self.document.path.as_deref()) {
+                Ok(path) => info!(Category::Ui, "Recording the session into `{}`.", path.display()),
+                Err(error) => error!(Category::Ui, "Could not record the session: {}", error),
+            }
+        } else if self.session_recorder.is_recording() {
+            self.session_recorder.stop(&self.document.graph);
+            info!(Category::Ui, "Stopped recording the session.");
+        }
+    }
+
+    /// Applies the command to the graph, recording the removed connections in the connection
+    /// history and discarding the errors of the removed nodes. Returns `None` if the command could not be applied, reporting the error.
+    fn apply(&mut self, command: GraphCommand) -> Option<GraphCommandResult> {
+        let result = self.document.graph.apply(command);
+        let error = result.as_ref().err().map(|error| error as &dyn Display);
+
+        self.session_recorder.record_command(error);
+
+        if let Some(replay) = self.session_replay.as_mut() {
+            replay.record_command(error);
+        }
+
+        match result {
+            Ok(result) => {
+                for edge in result.removed_edges() {
+                    self.document.connection_history.record(edge.connection.clone());
+                }
+
+                if let GraphCommandResult::NodeRemoved { node, .. }
+                | GraphCommandResult::NodeDissolved { node, .. } = &result
+                {
+                    self.node_errors.remove(*node);
+                }
+
+                Some(result)
+            }
+            Err(error) => {
+                warn!(Category::Ui, "Could not edit the graph: {}", error);
+                None
+            }
+        }
+    }
+
+    /// What the command would break, if it leaves other nodes without inputs or invalidates the
+    /// graph.
+    fn breaking_consequences(&self, command: &GraphCommand) -> Option<EditConsequences> {
+        self.document.graph.preview(command).ok().filter(EditConsequences::is_breaking)
+    }
+
+    /// Applies the command, unless it breaks anything and breaking edits are to be confirmed, in
+    /// which case what it breaks is listed in a modal with the title first. Otherwise, the
+    /// consequences are logged.
+    fn apply_or_confirm(&mut self, command: GraphCommand, title: &str) {
+        match self.breaking_consequences(&command) {
+            Some(consequences) if self.config.get().confirm_breaking_edits => {
+                let description = consequences.describe(&self.document.graph, false);
+
+                self.modal = Some(Modal::new(ModalAction::ApplyEdit(command), title, description, "Apply"));
+            }
+            consequences => {
+                let description =
+                    consequences.map(|consequences| consequences.describe(&self.document.graph, true));
+
+                if self.apply(command).is_some() {
+                    if let Some(description) = description {
+                        warn!(Category::Ui, "{}", description);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves the selected nodes according to the alignment, using the sizes of their panes from the
+    /// last layout. All nodes are moved by a single command.
+    fn align_selected_nodes(&mut self, alignment: PaneAlignment) {
+        let graph = &self.document.graph;
+        let selected = graph
+            .node_indices()
+            .filter(|node| graph[*node].selected)
+            .map(|node| {
+                let pane_state = &graph[node].floating_pane_state;
+
+                (node, (pane_state.position, pane_state.laid_out_size))
+            })
+            .collect::<Vec<_>>();
+
+        if selected.len() < 2 {
+            return;
+        }
+
+        let geometries = selected.iter().map(|(_, geometry)| *geometry).collect::<Vec<_>>();
+        let positions = alignment
+            .positions(&geometries)
+            .into_iter()
+            .zip(&selected)
+            .filter(|(position, (_, (previous, _)))| position != previous)
+            .map(|(position, (node, _))| (*node, position))
+            .collect::<Vec<_>>();
+
+        if !positions.is_empty() {
+            self.apply(GraphCommand::MoveNodes { positions });
+        }
+    }
+
+    /// Removes all connections of the channel, recording them in the connection history.
+    fn disconnect_channel(&mut self, channel: ChannelIdentifier) {
+        let connection_count = self
+            .graph
+            .get_connections()
+            .into_iter()
+            .filter(|connection| connection.contains_channel(channel))
+            .count();
+
+        // Removing multiple connections at once is not easily reverted.
+        if connection_count > 1 {
+            self.autosave.snapshot_urgent(&self.document.graph, &self.document.metadata);
+        }
+
+        self.apply(GraphCommand::DisconnectChannel { channel });
+    }
+
+    /// Creates a node at the position and connects it to the channel. Only channels of primitive
+    /// types are supported.
+    fn create_connected_node(&mut self, channel: ChannelIdentifier, position: Vec2<f32>) {
+        let ty = self.document.graph.node_weight(channel.node_index).and_then(|node_data| {
+            node_data
+                .configuration
+                .channels(channel.channel_direction)
+                .find(|channel_ref| channel_ref.edge_endpoint == channel.into())
+                .and_then(|channel_ref| channel_ref.ty.as_primitive_type_enum())
+        });
+        let ty = if let Some(ty) = ty {
+            ty
+        } else {
+            return;
+        };
+        let behaviour: Box<dyn NodeBehaviourContainer> = match channel.channel_direction {
+            ChannelDirection::In => Box::new(ConstantNodeBehaviour::new(ty.default_value())),
+            ChannelDirection::Out => Box::new(DebugNodeBehaviour::new(ty)),
+        };
+        let title = behaviour.name().to_string();
+        let node = match self.apply(GraphCommand::AddNode { title, position, behaviour }) {
+            Some(result) => result.added_node().unwrap(),
+            None => return,
+        };
+        let created_channel = self.document.graph[node]
+            .configuration
+            .channels(channel.channel_direction.inverse())
+            .next()
+            .map(|channel_ref| channel_ref.into_identifier(node));
+
+        if let Some(connection) = created_channel
+            .and_then(|created_channel| Connection::try_from_identifiers([channel, created_channel]))
+        {
+            // Replaces the current connection of an input.
+            self.apply(GraphCommand::InsertEdge { connection, primary: false });
+        }
+    }
+
+    /// Saves the node as a preset, asking whether to overwrite the preset with the same name, if
+    /// there is one.
+    fn save_preset(&mut self, node: NodeIndex, name: &str) {
+        let preset = match self.document.graph.node_weight(node) {
+            Some(node_data) => Preset::from_behaviour(name.trim(), &*node_data.behaviour),
+            None => return,
+        };
+
+        if self.presets.position(&preset.name).is_some() {
+            self.modal = Some(
+                Modal::new(
+                    ModalAction::OverwritePreset(preset.clone()),
+                    "Overwrite preset?",
+                    format!(
+                        "A preset named `{}` already exists. It can be overwritten with the settings of \
+                         the node, or both can be kept by numbering the name of the new one.",
+                        preset.name
+                    ),
+                    "Overwrite",
+                )
+                .with_alternative("Keep both"),
+            );
+        } else {
+            self.store_preset(preset, false);
+        }
+    }
+
+    fn store_preset(&mut self, preset: Preset, overwrite: bool) {
+        let label = preset.label();
+
+        match self.presets.insert(preset, overwrite) {
+            Ok(()) => info!(Category::Ui, "Saved the preset `{}`.", label),
+            Err(error) => error!(Category::Ui, "Could not save the preset `{}`: {}", label, error),
+        }
+    }
+
+    /// Saves the selected nodes as a library entry. If an entry with the same name exists and its
+    /// contents differ, asks whether to overwrite it.
+    fn save_to_library(&mut self, name: &str) {
+        let graph = &self.document.graph;
+        let selected = |node: NodeIndex| graph[node].selected;
+        let entry = match LibraryEntry::from_nodes(name, graph, &self.document.metadata, selected) {
+            Ok(entry) => entry,
+            Err(error) => {
+                error!(Category::Ui, "Could not save the library entry `{}`: {}", name.trim(), error);
+                return;
+            }
+        };
+
+        match self.library.get(&entry.info.name) {
+            Some(existing) if existing.info.content_hash == entry.info.content_hash => {
+                info!(Category::Ui, "The library entry `{}` is up to date already.", entry.info.name);
+            }
+            Some(_) => {
+                self.modal = Some(
+                    Modal::new(
+                        ModalAction::OverwriteLibraryEntry(entry.clone()),
+                        "Overwrite library entry?",
+                        format!(
+                            "A library entry named `{}` already exists with different contents. It can be \
+                             overwritten with the selected nodes, or both can be kept by numbering the name \
+                             of the new one.",
+                            entry.info.name
+                        ),
+                        "Overwrite",
+                    )
+                    .with_alternative("Keep both"),
+                );
+            }
+            None => self.store_library_entry(entry, false),
+        }
+    }
+
+    fn store_library_entry(&mut self, entry: LibraryEntry, overwrite: bool) {
+        let name = entry.info.name.clone();
+
+        match self.library.insert(entry, overwrite) {
+            Ok(()) => info!(Category::Ui, "Saved the library entry `{}`.", name),
+            Err(error) => error!(Category::Ui, "Could not save the library entry `{}`: {}", name, error),
+        }
+    }
+
+    /// Copies the nodes and the connections of the library entry into the top left corner of the
+    /// view, selecting only the copies, and closes the node palette.
+    fn insert_library_entry(&mut self, name: &str) {
+        /// The offset of the copied nodes from the top left corner of the view.
+        const OFFSET: [f32; 2] = [24.0, 24.0];
+
+        let entry = if let Some(entry) = self.library.get(name) { entry.clone() } else { return };
+        let corner = -self.document.floating_panes_state.panes_offset + Vec2::from(OFFSET);
+        let instance = self
+            .library_instances()
+            .into_iter()
+            .filter(|(origin, _)| origin.name == entry.info.name)
+            .map(|(origin, _)| origin.instance + 1)
+            .max()
+            .unwrap_or(0);
+        let origin = LibraryOrigin {
+            name: entry.info.name.clone(),
+            content_hash: instance_hash(&entry.graph),
+            instance,
+        };
+
+        if self.copy_library_entry(&entry, corner, origin).is_some() {
+            info!(Category::Ui, "Inserted a copy of the library entry `{}`.", name);
+            self.palette.close();
+        }
+    }
+
+    /// Copies the nodes and the connections of the library entry, so that the top left corner of
+    /// the copies is at `corner`, selecting only the copies, which become the instance `origin`.
+    /// Returns the copies in the order of the nodes of the entry, or `None` if the entry could not
+    /// be instantiated. Entries with nodes of unknown kinds are rejected, as graphs are when they
+    /// are opened.
+    fn copy_library_entry(
+        &mut self,
+        entry: &LibraryEntry,
+        corner: Vec2<f32>,
+        origin: LibraryOrigin,
+    ) -> Option<Vec<NodeIndex>> {
+        let mut graph = match entry.graph.clone().into_graph(&self.substitutions()) {
+            Ok(graph) => graph,
+            Err(error) => {
+                warn!(Category::Ui, "Could not insert the library entry `{}`: {}", entry.info.name, error);
+                return None;
+            }
+        };
+        let top_left = graph
+            .node_weights()
+            .map(|node_data| node_data.floating_pane_state.position)
+            .reduce(|top_left, position| Vec2::new(top_left.x.min(position.x), top_left.y.min(position.y)))
+            .unwrap_or_default();
+        let offset = corner - top_left;
+        let edges = graph
+            .edge_indices()
+            .map(|edge| {
+                let (from, to) = graph.edge_endpoints(edge).unwrap();
+
+                (from, to, graph[edge].endpoint_from, graph[edge].endpoint_to, graph[edge].primary)
+            })
+            .collect::<Vec<_>>();
+
+        for node_data in self.document.graph.graph_mut().node_weights_mut() {
+            node_data.selected = false;
+        }
+
+        // The nodes of the instantiated graph are indexed from zero, in the order of the entry.
+        let mut copies = Vec::new();
+
+        for node in graph.node_indices().collect::<Vec<_>>() {
+            let node_data = graph.remove_node(node).unwrap();
+            let position = node_data.floating_pane_state.position + offset;
+            let command =
+                GraphCommand::AddNode { title: node_data.title, position, behaviour: node_data.behaviour };
+            let copy = self.apply(command)?.added_node().unwrap();
+
+            if node_data.color.is_some() {
+                self.apply(GraphCommand::SetNodeColor { node: copy, color: node_data.color });
+            }
+
+            if node_data.muted {
+                self.apply(GraphCommand::SetNodeMuted { node: copy, muted: true });
+            }
+
+            if node_data.soloed {
+                self.apply(GraphCommand::SetNodeSoloed { node: copy, soloed: true });
+            }
+
+            let pane_state = &node_data.floating_pane_state;
+
+            self.apply(GraphCommand::SetNodeCollapsed { node: copy, collapsed: pane_state.collapsed });
+            self.apply(GraphCommand::SetNodePinned { node: copy, pinned: pane_state.pinned });
+            self.apply(GraphCommand::SetNodeOpacity { node: copy, opacity: pane_state.opacity });
+            self.apply(GraphCommand::SetNodeClickThrough {
+                node: copy,
+                click_through: pane_state.click_through,
+            });
+            self.apply(GraphCommand::SetChannelOrder { node: copy, order: node_data.channel_order });
+
+            let copied = &mut self.document.graph.graph_mut()[copy];
+
+            copied.floating_pane_state.size = node_data.floating_pane_state.size;
+            copied.selected = true;
+            copied.library_origin = Some(origin.clone());
+            copies.push(copy);
+        }
+
+        let connections = edges
+            .iter()
+            .map(|(from, to, endpoint_from, endpoint_to, _)| {
+                Connection([
+                    endpoint_from.into_undirected_identifier(copies[from.index()]),
+                    endpoint_to.into_undirected_identifier(copies[to.index()]),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        if !connections.is_empty() {
+            self.apply(GraphCommand::InsertEdges { connections: connections.clone() });
+        }
+
+        for (connection, _) in connections.into_iter().zip(&edges).filter(|(_, edge)| edge.4) {
+            self.apply(GraphCommand::SetPrimaryEdge { connection });
+        }
+
+        Some(copies)
+    }
+
+    /// The nodes inserted from the library, grouped by the instances they belong to.
+    fn library_instances(&self) -> Vec<(LibraryOrigin, Vec<NodeIndex>)> {
+        let graph = &self.document.graph;
+        let mut instances = Vec::<(LibraryOrigin, Vec<NodeIndex>)>::new();
+
+        for node in graph.node_indices() {
+            let origin =
+                if let Some(origin) = graph[node].library_origin.as_ref() { origin } else { continue };
+
+            match instances.iter_mut().find(|(instance, _)| instance.is_same_instance(origin)) {
+                Some((_, nodes)) => nodes.push(node),
+                None => instances.push((origin.clone(), vec![node])),
+            }
+        }
+
+        instances
+    }
+
+    /// The instance of the library entry the node belongs to, if it was inserted from the library.
+    fn library_instance(&self, node: NodeIndex) -> Option<(LibraryOrigin, Vec<NodeIndex>)> {
+        let origin = self.document.graph.node_weight(node)?.library_origin.as_ref()?;
+
+        self.library_instances().into_iter().find(|(instance, _)| instance.is_same_instance(origin))
+    }
+
+    /// Replaces the nodes of the instance the node belongs to with a copy of the current library
+    /// entry, placed at the top left corner of the instance. The connections to the other nodes are
+    /// recreated with the channels of the copies of the same labels, see
+    /// [`ExecutionGraph::channel_label`].
+    fn update_from_library(&mut self, node: NodeIndex) {
+        let (origin, nodes) =
+            if let Some(instance) = self.library_instance(node) { instance } else { return };
+        let entry = match self.library.get(&origin.name) {
+            Some(entry) => entry.clone(),
+            None => {
+                warn!(Category::Ui, "The library entry `{}` does not exist anymore.", origin.name);
+                return;
+            }
+        };
+        let graph = &self.document.graph;
+        let corner = nodes
+            .iter()
+            .map(|node| graph[*node].floating_pane_state.position)
+            .reduce(|corner, position| Vec2::new(corner.x.min(position.x), corner.y.min(position.y)))
+            .unwrap_or_default();
+        let label = |node: NodeIndex, endpoint: EdgeEndpoint, direction: ChannelDirection| {
+            graph.channel_label(endpoint.into_undirected_identifier(node).into_directed(direction))
+        };
+        // The channels of the other nodes, along with the labels of the channels of the instance
+        // they are connected to.
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+
+        for edge in graph.edge_indices() {
+            let (from, to) = graph.edge_endpoints(edge).unwrap();
+            let edge = &graph[edge];
+
+            match (nodes.contains(&from), nodes.contains(&to)) {
+                (false, true) => inputs.push((
+                    edge.endpoint_from.into_undirected_identifier(from),
+                    label(to, edge.endpoint_to, ChannelDirection::In),
+                )),
+                (true, false) => outputs.push((
+                    label(from, edge.endpoint_from, ChannelDirection::Out),
+                    edge.endpoint_to.into_undirected_identifier(to),
+                )),
+                _ => (),
+            }
+        }
+
+        for node in nodes {
+            self.apply(GraphCommand::RemoveNode { node });
+        }
+
+        let origin = LibraryOrigin { content_hash: instance_hash(&entry.graph), ..origin };
+        let copies =
+            if let Some(copies) = self.copy_library_entry(&entry, corner, origin) { copies } else { return };
+        let graph = &self.document.graph;
+        let find = |label: &Option<String>, direction: ChannelDirection| {
+            copies.iter().find_map(|copy| {
+                graph[*copy]
+                    .configuration
+                    .channels(direction)
+                    .map(|channel_ref| channel_ref.edge_endpoint.into_undirected_identifier(*copy))
+                    .find(|channel| {
+                        label.is_some() && graph.channel_label(channel.into_directed(direction)) == *label
+                    })
+            })
+        };
+        let connections = inputs
+            .iter()
+            .filter_map(|(from, label)| Some(Connection([*from, find(label, ChannelDirection::In)?])))
+            .chain(
+                outputs
+                    .iter()
+                    .filter_map(|(label, to)| Some(Connection([find(label, ChannelDirection::Out)?, *to]))),
+            )
+            .collect::<Vec<_>>();
+        let lost = inputs.len() + outputs.len() - connections.len();
+
+        if !connections.is_empty() {
+            self.apply(GraphCommand::InsertEdges { connections });
+        }
+
+        if lost > 0 {
+            warn!(
+                Category::Ui,
+                "Updated the nodes from the library entry `{}`, but {} of their connections could not be \
+                 recreated, as the entry has no channels of the same labels.",
+                entry.info.name,
+                lost
+            );
+        } else {
+            info!(Category::Ui, "Updated the nodes from the library entry `{}`.", entry.info.name);
+        }
+    }
+
+    /// Replaces the library entry the node was inserted from with the nodes of its instance,
+    /// keeping the description of the entry. Other instances of the entry become outdated.
+    fn push_to_library(&mut self, node: NodeIndex) {
+        let (origin, nodes) =
+            if let Some(instance) = self.library_instance(node) { instance } else { return };
+        let in_instance = |node: NodeIndex| nodes.contains(&node);
+        let mut entry = match LibraryEntry::from_nodes(
+            &origin.name,
+            &self.document.graph,
+            &self.document.metadata,
+            in_instance,
+        ) {
+            Ok(entry) => entry,
+            Err(error) => {
+                error!(Category::Ui, "Could not save the library entry `{}`: {}", origin.name, error);
+                return;
+            }
+        };
+
+        if let Some(existing) = self.library.get(&origin.name) {
+            entry.info.description = existing.info.description.clone();
+        }
+
+        let content_hash = instance_hash(&entry.graph);
+
+        if let Err(error) = self.library.insert(entry, true) {
+            error!(Category::Ui, "Could not save the library entry `{}`: {}", origin.name, error);
+            return;
+        }
+
+        for node in nodes {
+            if let Some(origin) = self.document.graph.graph_mut()[node].library_origin.as_mut() {
+                origin.content_hash = content_hash.clone();
+            }
+        }
+
+        self.mark_modified();
+        info!(Category::Ui, "Pushed the changes into the library entry `{}`.", origin.name);
+    }
+
+    /// Creates a node from the preset in the top left corner of the view, closing the preferences.
+    fn insert_preset(&mut self, index: usize) {
+        /// The offset of the created node from the top left corner of the view.
+        const OFFSET: [f32; 2] = [24.0, 24.0];
+
+        let preset = if let Some(entry) = self.presets.entries().get(index) {
+            entry.preset.clone()
+        } else {
+            return;
+        };
+        let behaviour = match preset.instantiate() {
+            Ok(behaviour) => behaviour,
+            Err(error) => {
+                warn!(Category::Ui, "Could not insert the preset `{}`: {}", preset.label(), error);
+                return;
+            }
+        };
+        let position = -self.document.floating_panes_state.panes_offset + Vec2::from(OFFSET);
+
+        if self.apply(GraphCommand::AddNode { title: preset.name, position, behaviour }).is_some() {
+            self.config.modify(|config| config.node_usage.record(&preset.behaviour));
+            self.preferences.close();
+        }
+    }
+
+    /// Creates a node of the kind in the top left corner of the view, closing the node palette.
+    fn insert_node(&mut self, name: &str) {
+        /// The offset of the created node from the top left corner of the view.
+        const OFFSET: [f32; 2] = [24.0, 24.0];
+
+        let behaviour = match create_behaviour(name) {
+            Some(behaviour) => behaviour,
+            None => {
+                warn!(Category::Ui, "Could not insert a node: The kind of node `{}` is not available.", name);
+                return;
+            }
+        };
+        let title = behaviour.name().to_string();
+        let position = -self.document.floating_panes_state.panes_offset + Vec2::from(OFFSET);
+
+        if self.apply(GraphCommand::AddNode { title, position, behaviour }).is_some() {
+            self.config.modify(|config| config.node_usage.record(name));
+            self.palette.close();
+        }
+    }
+
+    /// Creates a `Constant` node with the value next to the node of the output channel. If
+    /// `replace` is set, the connections of the channel are moved to the `Constant` node.
+    fn freeze_value(&mut self, channel: ChannelIdentifier, value: PrimitiveChannelValue, replace: bool) {
+        /// The offset of the created node from the node of the channel.
+        const OFFSET: [f32; 2] = [24.0, 24.0];
+
+        let source = if let Some(node_data) = self.document.graph.node_weight(channel.node_index) {
+            node_data
+        } else {
+            return;
+        };
+        let title = format!("Frozen {}", self.document.graph.channel_label(channel).unwrap_or_default());
+        let position = source.floating_pane_state.position + Vec2::from(OFFSET);
+        let behaviour = Box::new(ConstantNodeBehaviour::new(value));
+        let node = match self.apply(GraphCommand::AddNode { title, position, behaviour }) {
+            Some(result) => result.added_node().unwrap(),
+            None => return,
+        };
+
+        if replace {
+            let created_channel = self.document.graph[node]
+                .configuration
+                .channels(ChannelDirection::Out)
+                .next()
+                .map(|channel_ref| channel_ref.into_identifier(node));
+            let targets = self
+                .graph
+                .get_connections()
+                .into_iter()
+                .filter(|connection| connection.from() == channel)
+                .map(|connection| connection.to())
+                .collect::<Vec<_>>();
+
+            if let Some(created_channel) = created_channel {
+                for target in targets {
+                    if let Some(connection) = Connection::try_from_identifiers([created_channel, target]) {
+                        // Replaces the connection from the frozen channel.
+                        self.apply(GraphCommand::InsertEdge { connection, primary: false });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes the node along with its connections, then creates the reconnections, autosaving the
+    /// graph beforehand.
+    fn remove_node(&mut self, node: NodeIndex<u32>, reconnections: Vec<Connection>) {
+        if !self.document.graph.contains_node(node) {
+            return;
+        }
+
+        self.autosave.snapshot_urgent(&self.document.graph, &self.document.metadata);
+        // A node blocking the executor is not executed again once it returns. Removing it does not
+        // wait for the executor.
+        self.watchdog.request_cancellation(node);
+        let command = if reconnections.is_empty() {
+            GraphCommand::RemoveNode { node }
+        } else {
+            GraphCommand::DissolveNode { node, reconnections }
+        };
+
+        if self.apply(command).is_none() {
+            return;
+        }
+
+        // The highlighted and selected channels may belong to the removed node.
+        self.document.floating_panes_content_state = Default::default();
+    }
+
+    /// Saves the graph to the file it was opened from, or to a file picked by the user, and
+    /// returns whether it was saved.
+    fn save_graph(&mut self) -> bool {
+        // `update` is executed on the main thread, which is required by the file dialog.
+        let path = self.document.path.clone().or_else(|| {
+            rfd::FileDialog::new()
+                .add_filter("DVSynth graph", &["json"])
+                .set_file_name("graph.json")
+                .save_file()
+        });
+        let path = if let Some(path) = path {
+            path
+        } else {
+            return false;
+        };
+        let file = GraphFile::from_graph(&self.document.graph, &self.document.metadata);
+
+        match file.save(&path) {
+            Ok(()) => {
+                info!(Category::Ui, "Saved the graph to `{}`.", path.display());
+                self.autosave.record_explicit_save(&path);
+                self.record_recent_graph(&path);
+                self.document.path = Some(path);
+                self.document.metadata = file.metadata;
+                self.document.unsaved_changes = false;
+                true
+            }
+            Err(error) => {
+                error!(Category::Ui, "Could not save the graph to `{}`: {}", path.display(), error);
+                false
+            }
+        }
+    }
+
+    /// Compares the graph with the file it was last saved to or opened from. Returns `None` if
+    /// there is no such file or if it cannot be read.
+    fn changes_since_saved(&self) -> Option<GraphDiff> {
+        let path = self.document.path.as_ref()?;
+
+        match GraphFile::load(path) {
+            Ok((saved, _)) => {
+                let current = GraphFile::from_graph(&self.document.graph, &self.document.metadata);
+
+                Some(GraphDiff::new(&saved, &current))
+            }
+            Err(error) => {
+                warn!(
+                    Category::Ui,
+                    "Could not read the saved graph `{}` to compare it: {}",
+                    path.display(),
+                    error
+                );
+                None
+            }
+        }
+    }
+
+    /// Replaces the non-finite values output by the nodes with the substitute, or stops replacing
+    /// them, if it is `None`. The substitute is kept in the metadata either way, to be offered the
+    /// next time.
+    fn set_sanitize_numerics(&mut self, substitute: Option<f64>) {
+        let metadata = &mut self.document.metadata;
+
+        metadata.sanitize_numerics = substitute.is_some();
+        metadata.sanitize_substitute = substitute.unwrap_or(metadata.sanitize_substitute);
+        self.document.graph.set_sanitize_numerics(substitute);
+
+        match substitute {
+            Some(substitute) => {
+                info!(Category::Ui, "Non-finite values output by nodes are replaced with {}.", substitute)
+            }
+            None => info!(Category::Ui, "Non-finite values output by nodes are no longer replaced."),
+        }
+
+        self.mark_modified();
+    }
+
+    fn mark_modified(&mut self) {
+        self.document.unsaved_changes = true;
+        self.autosave.mark_dirty();
+        self.crash_reporter.mark_dirty();
+    }
+
+    fn open_crash_report_location(&self, report: &CrashReport) {
+        if let Err(error) = report.open_location() {
+            error!(
+                Category::Ui,
+                "Could not open the location of the crash report `{}`: {}",
+                report.path.display(),
+                error
+            );
+        }
+    }
+
+    /// Moves the graph to the front of the recently opened graphs stored in the settings.
+    fn record_recent_graph(&self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        self.config.modify(|config| config.record_recent_graph(&path));
+    }
+
+    /// Replaces the current graph with the one stored in the file and returns whether it was
+    /// opened, reporting the errors otherwise.
+    fn open_graph(&mut self, path: PathBuf) -> bool {
+        match self.load_graph(&path) {
+            Ok(()) => {
+                self.record_recent_graph(&path);
+                self.document.path = Some(path);
+                true
+            }
+            Err(error) => {
+                error!(Category::Ui, "Could not open the graph `{}`: {}", path.display(), error);
+                false
+            }
+        }
+    }
+
+    /// Replaces the current graph with a new, unsaved graph created from the template.
+    fn open_template(&mut self, template: Template) {
+        // Replacing the graph discards it, make sure it can be recovered.
+        self.autosave.snapshot_urgent(&self.document.graph, &self.document.metadata);
+        self.replace_graph(template.graph(), Default::default());
+        self.document.path = None;
+        self.document.unsaved_changes = false;
+        self.start_screen.open = false;
+    }
+
+    /// Replaces the nodes and connections of the graph, discarding the state referring to them.
+    fn replace_graph(&mut self, graph: Graph, metadata: GraphMetadata) {
+        // Keep the schedule shared with the executor, only replace the nodes and connections.
+        self.document.graph.replace(graph);
+        self.document.graph.set_implicit_promotion(metadata.implicit_promotion);
+        self.document.graph.set_sanitize_numerics(metadata.sanitize_substitute());
+        self.document.metadata = metadata;
+        self.document.floating_panes_content_state = Default::default();
+        // The node indices of the history refer to the replaced graph.
+        self.document.connection_history.clear();
+        self.clear_execution_state();
+        self.crash_reporter.mark_dirty();
+    }
+
+    /// Discards the state reported by the executor, which refers to the nodes and channels of a
+    /// graph that is no longer executed.
+    fn clear_execution_state(&mut self) {
+        self.node_errors.remove_all();
+        self.value_taps.clear();
+        self.stalled_task = None;
+        self.sanitizing_nodes.clear();
+    }
+
+    /// The nodes replaced by placeholders when graphs are opened, see [`safe_mode`].
+    fn substitutions(&self) -> Substitutions {
+        Substitutions {
+            safe_mode: self.safe_mode.is_some(),
+            disabled_behaviours: self.config.get().disabled_behaviours.clone(),
+        }
+    }
+
+    /// All open documents, in the order of their tabs.
+    fn documents(&self) -> impl Iterator<Item = &Document> {
+        let (before, after) = self.background_documents.split_at(self.focused_tab);
+
+        before.iter().chain(std::iter::once(&self.document)).chain(after.iter())
+    }
+
+    fn document_at(&self, tab: usize) -> Option<&Document> {
+        self.documents().nth(tab)
+    }
+
+    fn document_tabs(&self) -> DocumentTabs {
+        DocumentTabs {
+            titles: self.documents().map(Document::tab_title).collect(),
+            pinned: self.documents().map(|document| document.run_in_background).collect(),
+            focused: self.focused_tab,
+        }
+    }
+
+    /// Hands the schedules of the pinned background documents over to the executor, which keeps
+    /// executing them along with the focused document.
+    fn update_pinned_schedules(&self) {
+        self.pinned_schedules.set(
+            self.background_documents
+                .iter()
+                .filter(|document| document.run_in_background)
+                .map(|document| document.graph.active_schedule.clone())
+                .collect(),
+        );
+    }
+
+    /// The position of the document among the background documents, which skip the focused tab.
+    fn background_index(&self, tab: usize) -> usize {
+        if tab < self.focused_tab {
+            tab
+        } else {
+            tab - 1
+        }
+    }
+
+    /// Focuses the tab at the position, handing the execution over to its document.
+    fn focus_document(&mut self, tab: usize) {
+        if tab == self.focused_tab || tab > self.background_documents.len() {
+            return;
+        }
+
+        let document = self.background_documents.remove(self.background_index(tab));
+        let previous = self.replace_focused_document(document);
+        // The previous document takes the place of the focused one among the background
+        // documents, which no longer skip it.
+        let previous_index = if self.focused_tab < tab { self.focused_tab } else { self.focused_tab - 1 };
+
+        self.background_documents.insert(previous_index, previous);
+        self.focused_tab = tab;
+        self.update_pinned_schedules();
+    }
+
+    /// Closes the tab at the position. If it is focused, the following tab is focused instead, or
+    /// an empty document, if it is the last tab.
+    fn close_document(&mut self, tab: usize) {
+        if tab != self.focused_tab {
+            if tab <= self.background_documents.len() {
+                self.background_documents.remove(self.background_index(tab));
+
+                if tab < self.focused_tab {
+                    self.focused_tab -= 1;
+                }
+
+                self.update_pinned_schedules();
+            }
+
+            return;
+        }
+
+        let next = if self.background_documents.is_empty() {
+            Document::empty()
+        } else {
+            self.focused_tab = self.focused_tab.min(self.background_documents.len() - 1);
+            self.background_documents.remove(self.focused_tab)
+        };
+
+        self.replace_focused_document(next);
+        self.update_pinned_schedules();
+    }
+
+    /// Focuses the document, making the executor execute it instead of the previously focused
+    /// document, which is returned.
+    fn replace_focused_document(&mut self, mut document: Document) -> Document {
+        // The autosave only follows the focused document, make sure the changes of the previous
+        // one can be recovered.
+        if self.document.unsaved_changes {
+            self.autosave.snapshot_urgent(&self.document.graph, &self.document.metadata);
+        }
+
+        document.graph.take_over_execution(&mut self.document.graph);
+
+        let previous = std::mem::replace(&mut self.document, document);
+
+        self.clear_execution_state();
+        self.throughput = ThroughputRates::default();
+        self.statistics = None;
+        self.crash_reporter.mark_dirty();
+        previous
+    }
+
+    /// Saves the documents with unsaved changes one after another, focusing each of them. Returns
+    /// whether all of them were saved.
+    fn save_all_documents(&mut self) -> bool {
+        for tab in 0..=self.background_documents.len() {
+            if self.document_at(tab).map_or(false, |document| document.unsaved_changes) {
+                self.focus_document(tab);
+
+                if !self.save_graph() {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Replaces the current graph with the one stored in the file.
+    fn load_graph(&mut self, path: &Path) -> Result<(), GraphFileError> {
+        let (file, migrated) = GraphFile::load(path)?;
+        let metadata = file.metadata.clone();
+        let graph = file.into_graph(&self.substitutions())?;
+
+        self.replace_graph(graph, metadata);
+
+        if migrated {
+            warn!(
+                Category::Ui,
+                "`{}` was stored in an older format, save the graph to upgrade the file.",
+                path.display()
+            );
+        }
+
+        // Opening a file does not modify it, unless it had to be upgraded.
+        self.document.unsaved_changes = migrated;
+
+        Ok(())
+    }
+
+    /// Creates the floating panes of all nodes, positioned according to `floating_panes_state`.
+    fn view_graph<'a>(
+        graph: &'a mut ExecutionGraph,
+        floating_panes_state: &'a mut FloatingPanesState,
+        floating_panes_content_state: &'a mut FloatingPanesBehaviourState,
+        graph_validation_errors: &GraphValidationErrors,
+        reconnect_candidates: Vec<ReconnectCandidate>,
+        library: &Library,
+        config: &Config,
+        theme: &dyn Theme,
+    ) -> FloatingPanes<
+        'a,
+        Message,
+        iced_wgpu::Renderer,
+        widgets::node::FloatingPanesBehaviour<Message, iced_wgpu::Renderer>,
+    > {
+        let node_indices = graph.node_indices().collect::<Vec<_>>();
+        let connections = graph.get_connections();
+        // Rebuilt on every view, so that the items reflect the current state of the behaviour.
+        let node_context_menu_items = match floating_panes_content_state.context_menu.as_ref() {
+            Some(ContextMenu { target: ContextMenuTarget::Node(node), .. }) => graph
+                .node_weight(*node)
+                .map(|node_data| {
+                    let mut items = node_data.context_menu_items(*node);
+
+                    if let Some(origin) = node_data.library_origin.as_ref() {
+                        items.push(
+                            ContextMenuItem::new(
+                                "Update from library",
+                                Message::UpdateFromLibrary { node: *node },
+                            )
+                            .enabled(library.get(&origin.name).is_some()),
+                        );
+                        items.push(ContextMenuItem::new(
+                            "Push changes to library",
+                            Message::PushToLibrary { node: *node },
+                        ));
+                    }
+
+                    items
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        let mut panes = FloatingPanes::new(
+            floating_panes_state,
+            floating_panes_content_state,
+            crate::widgets::node::FloatingPanesBehaviour {
+                on_channel_disconnect: |channel| Message::DisconnectChannel { channel },
+                on_connection_create: |connection| Message::InsertConnection { connection },
+                on_connections_create: |connections| Message::InsertConnections { connections },
+                on_reconnect: |connection| Message::Reconnect { connection },
+                on_connection_splice: |connection, through| Message::SpliceConnection { connection, through },
+                on_value_freeze: |channel| Message::FreezeValue { channel },
+                on_node_create: |channel, position| Message::CreateConnectedNode { channel, position },
+                on_node_color: |node, color| Message::SetNodeColor { node, color },
+                on_node_remove: |node| Message::RemoveNode { node },
+                on_node_mute: |node, muted| Message::SetNodeMuted { node, muted },
+                on_node_pin: |node, pinned| Message::SetNodePinned { node, pinned },
+                on_node_opacity: |node, opacity| Message::SetNodeOpacity { node, opacity },
+                on_node_click_through: |node, click_through| Message::SetNodeClickThrough {
+                    node,
+                    click_through,
+                },
+                on_preset_save: |node| Message::SavePreset { node },
+                on_channel_group_toggle: |node, direction, group| Message::ToggleChannelGroup {
+                    node,
+                    direction,
+                    group,
+                },
+                on_channels_arrange: |node, arranging| Message::SetArrangingChannels { node, arranging },
+                on_channel_move: |node, direction, channel, target| Message::MoveChannel {
+                    node,
+                    direction,
+                    channel,
+                    target,
+                },
+                on_channel_order_reset: |node| Message::ResetChannelOrder { node },
+                on_node_select: |node, selected| Message::SetNodeSelected { node, selected },
+                on_connection_primary: |connection| Message::SetPrimaryConnection { connection },
+                on_document_select: |tab| Message::SelectDocument { tab },
+                on_document_close: |tab| Message::CloseDocument { tab },
+                on_document_pin: |tab, pinned| Message::SetDocumentPinned { tab, pinned },
+                on_document_create: || Message::NewDocument,
+                node_context_menu_items,
+                connections,
+                move_recipients: graph.move_recipients(),
+                implicit_promotion: graph.implicit_promotion,
+                graph_validation_errors: graph_validation_errors.clone(),
+                reconnect_candidates,
+                create_nodes_on_drop: config.create_nodes_on_drop,
+                dim_crossing_connections: config.dim_crossing_connections,
+                keymap: config.shortcuts.clone(),
+                status_colors: theme.status_colors(),
+                tooltip_style: Some(theme.tooltip()),
+                schedule_status: None,
+                document_tabs: None,
+                value_taps: None,
+                throughput: None,
+                execution_order: None,
+            },
+            Box::new(Message::LayoutChange),
+        )
+        .grid_size(config.grid_size())
+        .theme(theme);
+
+        let muted_nodes = graph.muted_nodes();
+
+        for (node_index, node_data) in node_indices.iter().zip(graph.node_weights_mut()) {
+            let muted = muted_nodes.contains(node_index);
+
+            panes = panes.insert(*node_index, node_data.view(*node_index, muted, theme));
+        }
+
+        panes
+    }
+}
+
+/// Forwards the close requests of the window from the event loop to the application.
+#[derive(Clone)]
+pub struct CloseRequests(Arc<Mutex<Option<UnboundedReceiver<()>>>>);
+
+impl CloseRequests {
+    pub fn new() -> (UnboundedSender<()>, Self) {
+        let (sender, receiver) = mpsc::unbounded();
+
+        (sender, Self(Arc::new(Mutex::new(Some(receiver)))))
+    }
+}
+
+impl<H: Hasher, E> Recipe<H, E> for CloseRequests {
+    type Output = Message;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Message> {
+        // The subscription is recreated on every update, but its stream is only created once.
+        match self.0.lock().unwrap().take() {
+            Some(receiver) => receiver.map(|()| Message::CloseRequested).boxed(),
+            None => stream::empty().boxed(),
+        }
+    }
+}
+
+/// Forwards the snapshots of channel values from the executor to the application.
+#[derive(Clone)]
+pub struct ValueSnapshots(Arc<ValueTaps>);
+
+impl<H: Hasher, E> Recipe<H, E> for ValueSnapshots {
+    type Output = Message;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Message> {
+        // The subscription is recreated on every update, but its stream is only created once.
+        match self.0.take_receiver() {
+            Some(receiver) => receiver.map(Message::ValueSnapshotTaken).boxed(),
+            None => stream::empty().boxed(),
+        }
+    }
+}
+
+/// Forwards the reports of nodes blocking the executor from the watchdog to the application.
+#[derive(Clone)]
+pub struct StalledTasks(Arc<ExecutionWatchdog>);
+
+impl<H: Hasher, E> Recipe<H, E> for StalledTasks {
+    type Output = Message;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Message> {
+        // The subscription is recreated on every update, but its stream is only created once.
+        match self.0.take_receiver() {
+            Some(receiver) => receiver.map(Message::TaskStalled).boxed(),
+            None => stream::empty().boxed(),
+        }
+    }
+}
+
+/// Notifies the application whenever the executor picks up another schedule.
+#[derive(Clone)]
+pub struct ExecutingGenerations(Arc<ExecutingGeneration>);
+
+impl<H: Hasher, E> Recipe<H, E> for ExecutingGenerations {
+    type Output = Message;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Message> {
+        // The subscription is recreated on every update, but its stream is only created once.
+        match self.0.take_receiver() {
+            Some(receiver) => receiver.map(|()| Message::ExecutingGenerationChanged).boxed(),
+            None => stream::empty().boxed(),
+        }
+    }
+}
+
+/// Notifies the application once the executor has submitted tasks to the main thread, which wakes
+/// the event loop up, see [`idle`].
+#[derive(Clone)]
+pub struct MainThreadWakeups(Arc<MainThreadWaker>);
+
+impl<H: Hasher, E> Recipe<H, E> for MainThreadWakeups {
+    type Output = Message;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Message> {
+        // The subscription is recreated on every update, but its stream is only created once.
+        match self.0.take_receiver() {
+            Some(receiver) => receiver.map(|()| Message::MainThreadTasksSubmitted).boxed(),
+            None => stream::empty().boxed(),
+        }
+    }
+}
+
+/// Requests the execution statistics and the throughput of the connections of the active schedule
+/// to be sampled periodically. The thread sending the requests stops once the subscription is
+/// dropped.
+///
+/// No requests are sent while the executor does not execute the schedule, so that the idle editor
+/// is not redrawn, except for the one sample that drops the statistics to zero.
+pub struct StatisticsSamples(Arc<ArcSwapOption<Schedule>>);
+
+impl<H: Hasher, E> Recipe<H, E> for StatisticsSamples {
+    type Output = Message;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+        // Restarted with the focused document.
+        Arc::as_ptr(&self.0).hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Message> {
+        let (sender, receiver) = mpsc::unbounded();
+        let active_schedule = self.0;
+
+        std::thread::spawn(move || {
+            let mut previously_executed = true;
+            let mut previous_schedule = None;
+
+            loop {
+                std::thread::sleep(statistics::SAMPLE_INTERVAL);
+
+                let schedule = active_schedule.load_full();
+                let schedule_pointer = schedule.as_ref().map(Arc::as_ptr);
+                let executed = schedule.map_or(false, |schedule| schedule.execution_times.passes() > 0);
+
+                if !executed && !previously_executed && schedule_pointer == previous_schedule {
+                    continue;
+                }
+
+                previously_executed = executed;
+                previous_schedule = schedule_pointer;
+
+                if sender.unbounded_send(()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        receiver.map(|()| Message::SampleStatistics).boxed()
+    }
+}
+
+/// Paces the replay of a session, sending a step after each of the delays.
+pub struct SessionReplaySteps(Arc<[Duration]>);
+
+impl<H: Hasher, E> Recipe<H, E> for SessionReplaySteps {
+    type Output = Message;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+        Arc::as_ptr(&self.0).hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Message> {
+        let (sender, receiver) = mpsc::unbounded();
+        let delays = self.0;
+
+        std::thread::spawn(move || {
+            for delay in delays.iter() {
+                std::thread::sleep(*delay);
+
+                if sender.unbounded_send(()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        receiver.map(|()| Message::ReplaySessionStep).boxed()
+    }
+}
+
+/// Emits [`Message::PreviewCollapsedPane`] once [`COLLAPSED_PANE_PREVIEW_DELAY`] has passed. Dropped
+/// as soon as the cursor leaves the pane of the node.
+pub struct CollapsedPanePreview(NodeIndex);
+
+impl<H: Hasher, E> Recipe<H, E> for CollapsedPanePreview {
+    type Output = Message;
+
+    fn hash(&self, state: &mut H) {
+        TypeId::of::<Self>().hash(state);
+        self.0.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Message> {
+        let (sender, receiver) = mpsc::unbounded();
+        let node = self.0;
+
+        std::thread::spawn(move || {
+            std::thread::sleep(COLLAPSED_PANE_PREVIEW_DELAY);
+            let _ = sender.unbounded_send(());
+        });
+
+        receiver.map(move |()| Message::PreviewCollapsedPane { node }).boxed()
+    }
+}
+
+/// The graph file passed on the command line, the first argument which is neither an option nor
+/// the value of one.
+fn graph_path_from_args(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record" | "--replay" | "--replay-session" => {
+                args.next();
+            }
+            _ if arg.starts_with("--") => (),
+            _ => return Some(PathBuf::from(arg)),
+        }
+    }
+
+    None
+}
+
+/// The session log passed with `--replay-session`, see [`session`].
+fn session_replay_from_args(mut args: impl Iterator<Item = String>) -> Option<SessionReplay> {
+    args.position(|arg| arg == "--replay-session")?;
+
+    let path = match args.next() {
+        Some(path) => path,
+        None => {
+            error!(Category::Ui, "`--replay-session` expects the path of a session log.");
+            return None;
+        }
+    };
+
+    SessionReplay::open(&path)
+        .map_err(|error| error!(Category::Ui, "Could not open the session `{}`: {}", path, error))
+        .ok()
+}
+
+/// The number of changes listed by the dialogs comparing the graph with the saved file.
+const MAX_LISTED_CHANGES: usize = 12;
+
+/// The maximum number of main thread tasks processed per frame, the rest is deferred to later frames.
+const MAX_MAIN_THREAD_TASKS_PER_FRAME: usize = 16;
+
+/// How long the cursor has to rest on a collapsed pane, before its full content is displayed.
+const COLLAPSED_PANE_PREVIEW_DELAY: Duration = Duration::from_millis(600);
+
+/// Summarizes the crash of the previous session and offers to load the recovery snapshot.
+fn crash_report_modal(report: CrashReport) -> Modal {
+    let description = format!(
+        "DVSynth crashed during the previous session. The report was saved to `{}`.\n\n{}",
+        report.path.display(),
+        report.summary
+    );
+
+    if report.recovery_path.is_some() {
+        Modal::new(ModalAction::ReportCrash(report), "DVSynth crashed", description, "Load recovery snapshot")
+            .with_alternative("Open report location")
+    } else {
+        Modal::new(ModalAction::ReportCrash(report), "DVSynth crashed", description, "Open report location")
+    }
+}
+
+/// Asks for the value to replace the non-finite values output by the nodes with.
+fn sanitize_numerics_modal(substitute: impl ToString) -> Modal {
+    Modal::new(
+        ModalAction::SanitizeNumerics,
+        "Replace non-finite values?",
+        "NaN and infinite values output by nodes are replaced with the value below, so that they do not \
+         spread to the nodes downstream.",
+        "Replace",
+    )
+    .with_text("Substitute", substitute)
+}
+
+fn main() {
+    // Checked before anything is set up, so that the check does not interfere with the application.
+    if std::env::args().skip(1).any(|arg| arg == "--check") {
+        std::process::exit(self_check::run_from_command_line());
+    }
+
+    startup::begin();
+
+    // Written before anything else is set up, so that crashes during the setup are counted.
+    let startup_marker = StartupMarker::begin();
+
+    let graph: ExecutionGraph = Graph::new().into();
+    let graph_path = graph_path_from_args(std::env::args().skip(1));
+
+    let active_schedule = graph.active_schedule.clone();
+    let executing_generation = graph.executing_generation.clone();
+    let pinned_schedules = Arc::new(PinnedSchedules::default());
+    let recording_control = Arc::new(RecordingControl::default());
+
+    match RecordingMode::from_args(std::env::args().skip(1)) {
+        Ok(recording) => recording_control.request(recording),
+        Err(error) => error!(Category::Executor, "Could not set up the recording: {}", error),
+    }
+
+    let config = Arc::new(SharedConfig::persisted(Config::load()));
+    logging::Logger::get().set_filters(&config.get().log_levels);
+    let session_replay = session_replay_from_args(std::env::args().skip(1));
+    // Replays start from the graph the session was recorded with, unless another one is passed.
+    let graph_path =
+        graph_path.or_else(|| session_replay.as_ref().and_then(|replay| replay.header.graph_path.clone()));
+    let session_recorder = Arc::new(SessionRecorder::default());
+    let (execution_context, main_thread_tasks) = ApplicationContext::new(config.clone());
+    // Acquiring the GPU may take a while, so it is done in the background, while the executor
+    // already executes the nodes which do not use the GPU. The device is shared with the renderer
+    // of the editor, which needs it before the window is created.
+    let renderer = {
+        let config = config.get();
+        let node_errors = execution_context.node_errors.clone();
+        let shared_renderer = execution_context.shared_renderer.clone();
+
+        std::thread::Builder::new()
+            .name("gpu-init".to_string())
+            .spawn(move || {
+                let renderer = Renderer::new(&config);
+
+                renderer.install_error_handler(node_errors);
+                shared_renderer.store(Some(Arc::new(renderer.clone())));
+                renderer
+            })
+            .unwrap()
+    };
+    let autosave = Autosave::spawn(config.clone());
+    let restorable_autosave = autosave::find_restorable();
+    let (close_request_sender, close_requests) = CloseRequests::new();
+    let exit_requested = Arc::new(AtomicBool::new(false));
+    let frame_rate = FrameRate::default();
+    let crash_report = crash::find_report();
+    let _watchdog_join_handle = execution_context.watchdog.clone().spawn(config.clone());
+    let crash_reporter = crash::install(execution_context.watchdog.clone(), executing_generation.clone());
+    let safe_mode = if startup_marker.is_safe_mode() {
+        warn!(
+            Category::Ui,
+            "The previous {} launches did not finish starting up, starting in safe mode.",
+            startup_marker.failed_startups()
+        );
+        Some(SafeModeBanner::new(startup_marker.failed_startups()))
+    } else {
+        None
+    };
+    let node_errors = execution_context.node_errors.clone();
+    let value_taps = execution_context.value_taps.clone();
+    let watchdog = execution_context.watchdog.clone();
+    let executor_thread = execution_context.executor_thread.clone();
+    let window_sizes = execution_context.window_sizes.clone();
+    let background_tasks = execution_context.background_tasks.clone();
+    let main_thread_waker = execution_context.main_thread_waker.clone();
+    let _join_handle = GraphExecutor::spawn(
+        execution_context,
+        active_schedule,
+        executing_generation,
+        pinned_schedules.clone(),
+        recording_control.clone(),
+    );
+    let renderer = renderer.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+    let settings = Settings {
+        window: window::Settings {
+            icon: None, // TODO
+            ..window::Settings::default()
+        },
+        antialiasing: true,
+        // Closing the window is handled by `Message::CloseRequested`, which asks to save the unsaved
+        // changes first and exits through `exit_requested`.
+        exit_on_close_request: false,
+        ..Settings::with_flags(ApplicationFlags {
+            graph,
+            config,
+            renderer: renderer.clone(),
+            node_errors,
+            value_taps,
+            watchdog,
+            executor_thread,
+            background_tasks: background_tasks.clone(),
+            pinned_schedules,
+            recording_control,
+            autosave,
+            restorable_autosave,
+            crash_reporter,
+            crash_report,
+            graph_path,
+            safe_mode,
+            close_requests,
+            exit_requested: exit_requested.clone(),
+            frame_rate: frame_rate.clone(),
+            main_thread_waker: main_thread_waker.clone(),
+            session_recorder: session_recorder.clone(),
+            session_replay,
+        })
+    };
+    let renderer_settings = iced_wgpu::Settings {
+        default_font: settings.default_font,
+        default_text_size: settings.default_text_size,
+        // because anti-aliasing is enabled in the settings
+        antialiasing: Some(iced_wgpu::Antialiasing::MSAAx4),
+        instance: Some(renderer.instance.clone()),
+        device_queue: Some((renderer.device.clone(), renderer.queue.clone())),
+        ..iced_wgpu::Settings::default()
+    };
+    let mut idle_tracker = IdleTracker::new(frame_rate);
+
+    ApplicationState::run_with_event_handler_and_renderer_settings(
+        settings,
+        renderer_settings,
+        Some(Box::new(move |event, window_target, control_flow| {
+            idle_tracker.observe(&event);
+
+            // Only the window of the editor is closed by the user, the windows opened by nodes are
+            // closed by removing their nodes.
+            if let winit::event::Event::WindowEvent {
+                window_id,
+                event: winit::event::WindowEvent::CloseRequested,
+            } = &event
+            {
+                if !window_sizes.is_tracked(*window_id) {
+                    close_request_sender.unbounded_send(()).ok();
+                }
+            }
+
+            // Forward the sizes of the windows of nodes, see `WindowSizes`, and record the input
+            // events, see `session`.
+            if let winit::event::Event::WindowEvent { window_id, event } = &event {
+                session_recorder.record_input(event);
+
+                match event {
+                    winit::event::WindowEvent::Resized(size) => {
+                        window_sizes.resize(*window_id, Vec2::new(size.width, size.height));
+                    }
+                    winit::event::WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        window_sizes
+                            .resize(*window_id, Vec2::new(new_inner_size.width, new_inner_size.height));
+                    }
+                    _ => (),
+                }
+            }
+
+            if event == winit::event::Event::LoopDestroyed {
+                background_tasks.shutdown();
+            }
+
+            if event == winit::event::Event::RedrawEventsCleared {
+                startup::reach(Milestone::FirstFrame);
+            }
+
+            // The first frame has been drawn and the graph has been executed, see `safe_mode`.
+            if startup::is_reached(Milestone::FirstFrame) && startup::is_reached(Milestone::FirstExecution) {
+                startup_marker.complete();
+            }
 
-    ApplicationState::run_with_event_handler_and_renderer_settings(
-        settings,
-        renderer_settings,
-        Some(Box::new(move |event, window_target, _control_flow| {
             if event == winit::event::Event::MainEventsCleared {
-                for main_thread_task in main_thread_task_receiver.try_iter() {
+                main_thread_waker.clear();
+
+                for main_thread_task in main_thread_tasks.take(MAX_MAIN_THREAD_TASKS_PER_FRAME) {
                     (main_thread_task)(window_target);
+                    idle_tracker.wake();
                 }
+
+                *control_flow = if exit_requested.load(Ordering::SeqCst) {
+                    winit::event_loop::ControlFlow::Exit
+                } else {
+                    idle_tracker.control_flow()
+                };
             }
         })),
     )
@@ -0,0 +1,239 @@
+use crate::crash::CrashReport;
+use crate::graph::controller::GraphCommand;
+use crate::graph::{ChannelIdentifier, Connection, NodeIndex};
+use crate::keymap::{Action, Keymap};
+use crate::library::LibraryEntry;
+use crate::node::PrimitiveChannelValue;
+use crate::preset::Preset;
+use crate::style::{consts, Theme, Themeable};
+use crate::template::Template;
+use iced::widget::checkbox::Checkbox;
+use iced::{button, text_input, Align, Button, Column, Container, Element, Length, Row, Text, TextInput};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub enum ModalMessage {
+    Confirm,
+    /// The secondary option, if the modal has one.
+    Alternative,
+    Cancel,
+    /// Toggles the checkbox, if the modal has one.
+    ToggleOption(bool),
+    /// Toggles the choice at the position.
+    ToggleChoice(usize, bool),
+    /// Edits the text field, if the modal has one.
+    UpdateText(String),
+}
+
+/// The operation performed when a [`Modal`] is confirmed.
+#[derive(Debug, Clone)]
+pub enum ModalAction {
+    RestoreAutosave(PathBuf),
+    /// Removes the node, then creates the reconnections.
+    RemoveNode {
+        node: NodeIndex,
+        reconnections: Vec<Connection>,
+    },
+    /// Removes the node, then creates the candidates checked in the choices, one per candidate.
+    DissolveNode {
+        node: NodeIndex,
+        candidates: Vec<Connection>,
+    },
+    /// Applies the edit, once what it breaks has been listed.
+    ApplyEdit(GraphCommand),
+    /// Creates a `Constant` node with the value of the output channel. If the option is checked,
+    /// the connections of the channel are moved to the `Constant` node.
+    FreezeValue {
+        channel: ChannelIdentifier,
+        value: PrimitiveChannelValue,
+    },
+    /// Overwrites the saved graph after its changes have been listed. If the option is checked,
+    /// the changes are not listed before saving anymore.
+    SaveGraph,
+    /// Confirmed by saving the graph, the alternative discards the unsaved changes.
+    Quit,
+    /// Replaces the graph with a new one created from the template, once the unsaved changes are
+    /// saved or discarded.
+    OpenTemplate(Template),
+    /// Closes the tab at the position, once its unsaved changes are saved or discarded.
+    CloseDocument(usize),
+    /// Confirmed by loading the recovery snapshot of the crash, if there is one. Otherwise, and
+    /// for the alternative, the location of the report is opened.
+    ReportCrash(CrashReport),
+    /// Saves the kind and the settings of the node as a preset, named by the text field.
+    SavePreset(NodeIndex),
+    /// Replaces the preset with the same name. The alternative keeps both, renaming the new one.
+    OverwritePreset(Preset),
+    /// Saves the selected nodes into the library, named by the text field.
+    SaveToLibrary,
+    /// Replaces the library entry with the same name. The alternative keeps both, renaming the new
+    /// one.
+    OverwriteLibraryEntry(LibraryEntry),
+    /// Replaces the non-finite values output by the nodes with the value of the text field.
+    SanitizeNumerics,
+}
+
+/// A dialog displayed instead of the graph, confirmed with Enter and dismissed with Escape.
+#[derive(Debug)]
+pub struct Modal {
+    pub action: ModalAction,
+    title: String,
+    description: String,
+    confirm_label: &'static str,
+    confirm_state: button::State,
+    alternative: Option<(&'static str, button::State)>,
+    option: Option<(&'static str, bool)>,
+    /// The labels of the checkboxes listed below the description, and whether they are checked.
+    choices: Vec<(String, bool)>,
+    /// The placeholder, the value and the state of the text field.
+    text: Option<(&'static str, String, text_input::State)>,
+    cancel_state: button::State,
+}
+
+impl Modal {
+    pub fn new(
+        action: ModalAction,
+        title: impl ToString,
+        description: impl ToString,
+        confirm_label: &'static str,
+    ) -> Self {
+        Self {
+            action,
+            title: title.to_string(),
+            description: description.to_string(),
+            confirm_label,
+            confirm_state: Default::default(),
+            alternative: None,
+            option: None,
+            choices: Vec::new(),
+            text: None,
+            cancel_state: Default::default(),
+        }
+    }
+
+    /// Adds a button between the confirm and cancel buttons, which sends
+    /// [`ModalMessage::Alternative`].
+    pub fn with_alternative(mut self, label: &'static str) -> Self {
+        self.alternative = Some((label, Default::default()));
+        self
+    }
+
+    /// Adds an unchecked checkbox above the buttons, which sends [`ModalMessage::ToggleOption`].
+    pub fn with_option(mut self, label: &'static str) -> Self {
+        self.option = Some((label, false));
+        self
+    }
+
+    pub fn set_option(&mut self, checked: bool) {
+        if let Some((_, option)) = self.option.as_mut() {
+            *option = checked;
+        }
+    }
+
+    /// Whether the checkbox is checked, `false` if the modal does not have one.
+    pub fn is_option_checked(&self) -> bool {
+        self.option.map_or(false, |(_, checked)| checked)
+    }
+
+    /// Lists checkboxes below the description, which send [`ModalMessage::ToggleChoice`].
+    pub fn with_choices(mut self, choices: impl IntoIterator<Item = (String, bool)>) -> Self {
+        self.choices = choices.into_iter().collect();
+        self
+    }
+
+    pub fn set_choice(&mut self, index: usize, checked: bool) {
+        if let Some((_, choice)) = self.choices.get_mut(index) {
+            *choice = checked;
+        }
+    }
+
+    /// The positions of the checked choices.
+    pub fn checked_choices(&self) -> Vec<usize> {
+        self.choices.iter().enumerate().filter(|(_, (_, checked))| *checked).map(|(index, _)| index).collect()
+    }
+
+    /// Adds a focused text field above the buttons, which sends [`ModalMessage::UpdateText`].
+    pub fn with_text(mut self, placeholder: &'static str, value: impl ToString) -> Self {
+        self.text = Some((placeholder, value.to_string(), text_input::State::focused()));
+        self
+    }
+
+    pub fn set_text(&mut self, value: String) {
+        if let Some((_, text, _)) = self.text.as_mut() {
+            *text = value;
+        }
+    }
+
+    /// The value of the text field, empty if the modal does not have one.
+    pub fn text(&self) -> &str {
+        self.text.as_ref().map_or("", |(_, text, _)| text)
+    }
+
+    pub fn view(&mut self, keymap: &Keymap, theme: &dyn Theme) -> Element<ModalMessage> {
+        let hint = match (keymap.shortcut(Action::Confirm), keymap.shortcut(Action::Cancel)) {
+            (Some(confirm), Some(cancel)) => format!("Press {} to confirm or {} to cancel.", confirm, cancel),
+            (Some(confirm), None) => format!("Press {} to confirm.", confirm),
+            (None, Some(cancel)) => format!("Press {} to cancel.", cancel),
+            (None, None) => String::new(),
+        };
+        let mut buttons = Row::new().theme(theme).push(
+            Button::new(&mut self.confirm_state, Text::new(self.confirm_label))
+                .width(Length::Fill)
+                .on_press(ModalMessage::Confirm),
+        );
+
+        if let Some((label, state)) = self.alternative.as_mut() {
+            buttons = buttons.push(
+                Button::new(state, Text::new(*label)).width(Length::Fill).on_press(ModalMessage::Alternative),
+            );
+        }
+
+        let mut column = Column::new()
+            .theme(theme)
+            .push(Text::new(&self.title).size(consts::TEXT_SIZE_TITLE))
+            .push(Text::new(&self.description).size(consts::TEXT_SIZE_REGULAR));
+
+        for (index, (label, checked)) in self.choices.iter().enumerate() {
+            column = column.push(
+                Checkbox::new(*checked, label.as_str(), move |checked| {
+                    ModalMessage::ToggleChoice(index, checked)
+                })
+                .theme(theme),
+            );
+        }
+
+        if let Some((label, checked)) = self.option {
+            column = column.push(Checkbox::new(checked, label, ModalMessage::ToggleOption).theme(theme));
+        }
+
+        if let Some((placeholder, text, state)) = self.text.as_mut() {
+            column = column.push(
+                TextInput::new(state, placeholder, text, ModalMessage::UpdateText)
+                    .on_submit(ModalMessage::Confirm)
+                    .theme(theme),
+            );
+        }
+
+        let column = column
+            .push(
+                buttons
+                    .push(
+                        Button::new(&mut self.cancel_state, Text::new("Cancel"))
+                            .width(Length::Fill)
+                            .on_press(ModalMessage::Cancel),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            )
+            .push(Text::new(hint).size(consts::TEXT_SIZE_REGULAR))
+            .width(Length::Units(480));
+
+        Container::new(column)
+            .theme(theme)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+}
@@ -1,21 +1,64 @@
-use crate::graph::{ApplicationContext, NodeIndex};
+use crate::graph::background::{BackgroundHandle, BackgroundTask};
+use crate::graph::{
+    ApplicationContext, EdgeEndpoint, MainThreadTaskSubmitError, NodeIndex, Renderer, WindowSurfaceHandle,
+    GPU_INITIALIZING,
+};
 use crate::node::{
-    BorrowedRef, BorrowedRefMut, ChannelValueRefs, ChannelValues, DynTypeTrait, NodeConfiguration, OptionType,
+    AssignError, AssignRefMutExt, BorrowedRef, BorrowedRefMut, ChannelDirection, ChannelValueRefs,
+    ChannelValues, DynTypeTrait, NodeConfiguration, OptionType,
 };
 use crate::style::Theme;
 use downcast_rs::{impl_downcast, Downcast};
 use dyn_clone::DynClone;
 use iced::Element;
+use iced_wgpu::wgpu;
 use iced_winit::winit::event_loop::EventLoopWindowTarget;
+use std::any::TypeId;
+use std::borrow::Cow;
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use vek::Vec2;
 
+pub use ab_switch::*;
 pub use array_constructor::*;
 pub use binary_op::*;
+pub use bit_op::*;
+pub use buffer_upload::*;
+pub use clock::*;
+pub use color::*;
+pub use color_picker::*;
 pub use constant::*;
 pub use counter::*;
 pub use debug::*;
+pub use delay::*;
+pub use envelope::*;
+pub use file_watcher::*;
+pub use format::*;
+pub use gradient::*;
+pub use histogram::*;
+pub use is_finite::*;
 pub use list_constructor::*;
+pub use list_reduce::*;
+pub use midi_output::*;
+pub use osc_receive::*;
+pub use pack::*;
+pub use placeholder::*;
+pub use pointer_info::*;
+pub use probability_gate::*;
+pub use quantize::*;
+pub use random::*;
+pub use range::*;
+pub use range_slider::*;
+pub use running_stats::*;
+pub use schmitt_trigger::*;
+pub use simple_map::*;
+pub use table_view::*;
+pub use text_render::*;
+pub use texture_history::*;
+pub use texture_readback::*;
+pub use texture_transform::*;
+pub use tween::*;
+pub use weighted_choice::*;
 pub use window::*;
 
 use super::{OwnedRefMut, SizedTypeExt, TypeEnum, TypeTrait, Unique};
@@ -34,6 +77,32 @@ impl<T> NodeBehaviourMessage for T where T: DynClone + Downcast + Debug + Send {
 impl_downcast!(NodeBehaviourMessage);
 dyn_clone::clone_trait_object!(NodeBehaviourMessage);
 
+/// An action contributed by a behaviour to the context menu of its node, for actions that do not
+/// deserve a permanent control in the node view.
+#[derive(Debug, Clone)]
+pub struct ContextMenuItem<M> {
+    pub label: String,
+    /// Disabled items are listed, but cannot be activated.
+    pub enabled: bool,
+    /// Sent to the behaviour when the item is activated.
+    pub message: M,
+}
+
+impl<M> ContextMenuItem<M> {
+    pub fn new(label: impl Into<String>, message: M) -> Self {
+        Self { label: label.into(), enabled: true, message }
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn map<R>(self, map: impl FnOnce(M) -> R) -> ContextMenuItem<R> {
+        ContextMenuItem { label: self.label, enabled: self.enabled, message: (map)(self.message) }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum NodeEvent<M> {
     Update,
@@ -68,11 +137,16 @@ impl<M: NodeBehaviourMessage> NodeEvent<M> {
 // FIXME: Maybe just store `Box<dyn NodeExecutor<'static>>` instead?
 pub struct NodeStateContainer<'state> {
     ptr: Box<dyn NodeExecutor<'state> + 'state>,
+    /// The type of the behaviour the state was created by.
+    behaviour_type_id: TypeId,
 }
 
 impl<'state> NodeStateContainer<'state> {
     pub fn from<T: NodeBehaviour>(state: T::State<'state>) -> Self {
-        Self { ptr: Box::new(state) as Box<dyn NodeExecutor<'state> + 'state> }
+        Self {
+            ptr: Box::new(state) as Box<dyn NodeExecutor<'state> + 'state>,
+            behaviour_type_id: TypeId::of::<T>(),
+        }
     }
 
     /// Safety: The returned value must not outlive self.
@@ -87,16 +161,26 @@ impl<'state> NodeStateContainer<'state> {
         &mut *(trait_object.data as *mut T::State<'state>)
     }
 
+    /// Updates the state with the new settings of the behaviour. Returns `false` without
+    /// updating the state, if the state was created by a different kind of behaviour, e.g. after
+    /// the node it belonged to was replaced.
     pub fn update<'invocation, T: NodeBehaviour>(
         &'invocation mut self,
         context: &'invocation ApplicationContext,
         behaviour: &T,
-    ) where
+    ) -> bool
+    where
         'state: 'invocation,
     {
+        if self.behaviour_type_id != TypeId::of::<T>() {
+            return false;
+        }
+
         let state = unsafe { self.downcast_mut::<T>() };
 
-        state.update(context, behaviour)
+        state.update(context, behaviour);
+
+        true
     }
 
     pub fn execute<'invocation>(&'invocation mut self, context: ExecutionContext<'invocation, 'state>)
@@ -266,14 +350,221 @@ pub struct ExecutionContext<'invocation, 'state: 'invocation> {
     pub outputs: &'invocation mut [BorrowedRefMut<'invocation, OptionType>], // [&mut Option<T>]
 }
 
+impl<'invocation, 'state: 'invocation> ExecutionContext<'invocation, 'state> {
+    /// Submits a task to be executed on the main thread during the next frame.
+    /// Fails without blocking if too many tasks are pending, in which case the node should retry
+    /// the submission during a later execution.
+    pub fn submit_main_thread_task(
+        &self,
+        task: Box<MainThreadTask>,
+    ) -> Result<(), MainThreadTaskSubmitError> {
+        self.application_context.submit_main_thread_task(self.allocator_handle.node, task)
+    }
+
+    /// Displays an error at the node until it is cleared with [`ExecutionContext::clear_error`].
+    pub fn report_error(&self, message: impl ToString) {
+        self.application_context.node_errors.set(self.allocator_handle.node, message);
+    }
+
+    pub fn clear_error(&self) {
+        self.application_context.node_errors.clear(self.allocator_handle.node);
+    }
+
+    /// The renderer, or `None` while the GPU is being acquired, in which case [`GPU_INITIALIZING`]
+    /// is displayed at the node, until it is cleared by a later execution.
+    pub fn renderer(&self) -> Option<&'invocation Renderer> {
+        let renderer = self.application_context.renderer();
+
+        if renderer.is_none() {
+            self.report_error(GPU_INITIALIZING);
+        }
+
+        renderer
+    }
+
+    /// Moves the value of the by-value input `input` to the by-value output `output`, without
+    /// copying it. Failures are also displayed at the node.
+    pub fn forward(&mut self, input: usize, output: usize) -> Result<(), AssignError> {
+        let result = self.inputs[input].swap(&mut self.outputs[output]);
+
+        if let Err(error) = &result {
+            self.report_error(format!("Could not forward input #{} to output #{}: {}", input, output, error));
+        }
+
+        result
+    }
+
+    /// Whether this node has been executing for too long or is being removed. Nodes that may
+    /// block, for example while waiting for data, should poll this and return as soon as possible
+    /// once it is set.
+    pub fn is_cancellation_requested(&self) -> bool {
+        self.application_context.watchdog.is_cancellation_requested()
+    }
+
+    /// Makes the size of the surface of a window presented to by this node available to other
+    /// nodes through [`ApplicationContext::window_surfaces`], as long as the handle is kept.
+    pub fn register_window_surface(&self, size: Vec2<u32>) -> WindowSurfaceHandle {
+        self.application_context.window_surfaces.register(self.allocator_handle.node, size)
+    }
+
+    /// Spawns a task on its own thread, for work that outlives a single execution of this node, such
+    /// as waiting for network packets. The returned task should be kept in the state of the node, so
+    /// that the task is cancelled once the node is removed. See [`crate::graph::background`].
+    pub fn spawn_background<T: Send + 'static>(
+        &self,
+        name: impl Into<String>,
+        capacity: usize,
+        task: impl FnOnce(BackgroundHandle<T>) + Send + 'static,
+    ) -> std::io::Result<BackgroundTask<T>> {
+        self.application_context.background_tasks.spawn(
+            Some(self.allocator_handle.node),
+            name,
+            capacity,
+            task,
+        )
+    }
+
+    /// Creates a command encoder labelled with this node, so that GPU errors can be traced back to it.
+    /// Panics if the GPU has not been acquired yet, see [`ExecutionContext::renderer`].
+    pub fn create_command_encoder(&self) -> wgpu::CommandEncoder {
+        let renderer = self.application_context.renderer().expect("The GPU has not been acquired yet.");
+
+        renderer.create_node_command_encoder(self.allocator_handle.node)
+    }
+}
+
+/// A glyph displayed in the title of a node, so that node kinds are recognizable at a glance.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum NodeIcon {
+    Value,
+    Operation,
+    Collection,
+    Output,
+    Inspect,
+    Custom(char),
+}
+
+impl NodeIcon {
+    pub fn as_char(self) -> char {
+        use NodeIcon::*;
+        match self {
+            Value => '◆',
+            Operation => 'ƒ',
+            Collection => '☰',
+            Output => '▣',
+            Inspect => '◉',
+            Custom(glyph) => glyph,
+        }
+    }
+}
+
 pub type MainThreadTask = dyn Send + FnOnce(&EventLoopWindowTarget<crate::Message>);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Displayed, but does not prevent the graph from being executed.
+    Warning,
+    /// Prevents the graph from being executed.
+    Error,
+}
+
+/// A constraint of a node that cannot be expressed by the types of its channels, reported by
+/// [`NodeBehaviour::validate`].
+#[derive(Debug, Clone)]
+pub struct NodeValidationIssue {
+    pub severity: Severity,
+    /// The input channel the issue is caused by, if any.
+    pub channel: Option<EdgeEndpoint>,
+    pub message: Cow<'static, str>,
+}
+
+impl NodeValidationIssue {
+    pub fn warning(message: impl Into<Cow<'static, str>>) -> Self {
+        Self { severity: Severity::Warning, channel: None, message: message.into() }
+    }
+
+    pub fn error(message: impl Into<Cow<'static, str>>) -> Self {
+        Self { severity: Severity::Error, channel: None, message: message.into() }
+    }
+
+    pub fn with_channel(mut self, channel: EdgeEndpoint) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+}
+
+/// The types of the output channels connected to the input channels of a node.
+#[derive(Clone, Copy)]
+pub struct ConnectedTypeInfo<'a> {
+    configuration: &'a NodeConfiguration,
+    /// Indexed by [`NodeConfiguration::get_global_channel_index`].
+    types: &'a [Option<&'a TypeEnum>],
+}
+
+impl<'a> ConnectedTypeInfo<'a> {
+    pub fn new(configuration: &'a NodeConfiguration, types: &'a [Option<&'a TypeEnum>]) -> Self {
+        Self { configuration, types }
+    }
+
+    /// The type of the output channel connected to the input channel, if it is connected.
+    pub fn get(&self, channel: EdgeEndpoint) -> Option<&'a TypeEnum> {
+        self.types.get(self.configuration.get_global_channel_index(channel)).copied().flatten()
+    }
+
+    /// Warns about the input channels connected to outputs of a different, but ABI-compatible type,
+    /// such as integers of a different signedness, whose values are reinterpreted.
+    pub fn reinterpretation_warnings(self) -> impl Iterator<Item = NodeValidationIssue> + 'a {
+        // Colors are laid out as arrays of floats, connecting one to the other keeps the values.
+        let is_color_array = |a: &TypeEnum, b: &TypeEnum| match (a, b) {
+            (TypeEnum::Color(color), TypeEnum::Array(array)) => *array == color.as_array(),
+            _ => false,
+        };
+
+        self.configuration.channels(ChannelDirection::In).filter_map(move |channel| {
+            let connected_ty = self.get(channel.edge_endpoint)?;
+
+            if connected_ty == channel.ty
+                || is_color_array(connected_ty, channel.ty)
+                || is_color_array(channel.ty, connected_ty)
+            {
+                return None;
+            }
+
+            let message = format!(
+                "The {} values connected to `{}` are reinterpreted as {}.",
+                connected_ty, channel.title, channel.ty
+            );
+
+            Some(NodeValidationIssue::warning(message).with_channel(channel.edge_endpoint))
+        })
+    }
+}
+
 pub trait NodeBehaviourContainer: DynClone + std::fmt::Debug + Send + Sync + 'static {
     fn name(&self) -> &str;
+    fn is_source(&self) -> bool;
+    fn is_sink(&self) -> bool;
+    fn is_external(&self) -> bool;
+    fn is_placeholder(&self) -> bool;
+    fn icon(&self) -> Option<NodeIcon>;
     fn update(&mut self, event: NodeEventContainer) -> Vec<NodeCommand>;
     fn view(&mut self, theme: &dyn Theme) -> Option<Element<Box<dyn NodeBehaviourMessage>>>;
+    fn context_menu_items(&self) -> Vec<ContextMenuItem<Box<dyn NodeBehaviourMessage>>>;
+    fn save(&self) -> serde_json::Value;
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error>;
+    fn update_input_sources(&mut self, sources: &[Option<String>]);
+    fn ignored_inputs(&self) -> Vec<usize>;
+    fn validate(
+        &self,
+        configuration: &NodeConfiguration,
+        connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue>;
     fn create_state<'state>(&self, context: &ApplicationContext) -> NodeStateContainer<'state>;
-    fn update_state<'state>(&self, context: &ApplicationContext, state: &mut NodeStateContainer<'state>);
+    fn update_state<'state>(
+        &self,
+        context: &ApplicationContext,
+        state: &mut NodeStateContainer<'state>,
+    ) -> bool;
 }
 
 dyn_clone::clone_trait_object!(NodeBehaviourContainer);
@@ -283,8 +574,81 @@ pub trait NodeBehaviour: std::fmt::Debug + Clone + Send + Sync + 'static {
     type State<'state>: NodeState<'state, Behaviour = Self> = NodeStateClosure<'state, Self>;
 
     fn name(&self) -> &str;
+
+    /// Whether the outputs of the node are impure, i.e. not fully determined by its inputs and
+    /// configuration. The outputs of source nodes are what gets recorded and replayed by the
+    /// executor.
+    fn is_source(&self) -> bool {
+        false
+    }
+
+    /// Whether the node has side effects outside of the graph, such as displaying a window. Sink
+    /// nodes can be muted and soloed, which excludes them, along with the nodes only they depend
+    /// on, from execution.
+    fn is_sink(&self) -> bool {
+        false
+    }
+
+    /// Whether the node depends on resources outside of the graph, such as the GPU, which may
+    /// crash the application. The nodes of external behaviours are replaced by a
+    /// [`PlaceholderNodeBehaviour`] in safe mode.
+    fn is_external(&self) -> bool {
+        false
+    }
+
+    /// Whether the node stands in for a node of the behaviour with its name, see
+    /// [`PlaceholderNodeBehaviour`].
+    fn is_placeholder(&self) -> bool {
+        false
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        None
+    }
+
     fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand>;
     fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>>;
+
+    /// The node-specific actions listed in the context menu of the node below the standard ones.
+    /// Queried whenever the menu is displayed, so the items may reflect the current state.
+    fn context_menu_items(&self) -> Vec<ContextMenuItem<Self::Message>> {
+        Vec::new()
+    }
+
+    /// Serializes the settings of the behaviour to be stored in saved graphs.
+    /// Behaviours without any settings do not need to override this.
+    fn save(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Restores the settings serialized by [`NodeBehaviour::save`].
+    /// The node is reconfigured with [`NodeEvent::Update`] afterwards.
+    fn load(&mut self, _settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    /// Invoked with the titles of the output channels connected to the input channels of the node,
+    /// indexed by [`NodeConfiguration::get_global_channel_index`], whenever the schedule is rebuilt.
+    fn update_input_sources(&mut self, _sources: &[Option<String>]) {}
+
+    /// The indices of the input channels passed by value which are not read under the current
+    /// settings. The nodes only these inputs depend on are excluded from execution, like the
+    /// nodes only muted sinks depend on, and the inputs are left empty. Queried whenever the
+    /// schedule is rebuilt.
+    fn ignored_inputs(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// Checks constraints that cannot be expressed by the types of the channels, before the graph
+    /// is scheduled. Invoked on every edit of the graph, so it must be cheap.
+    fn validate(
+        &self,
+        _configuration: &NodeConfiguration,
+        _connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        Vec::new()
+    }
+
     fn create_state<'state>(&self, context: &ApplicationContext) -> Self::State<'state>;
 }
 
@@ -293,6 +657,26 @@ impl<T: NodeBehaviour> NodeBehaviourContainer for T {
         NodeBehaviour::name(self)
     }
 
+    fn is_source(&self) -> bool {
+        NodeBehaviour::is_source(self)
+    }
+
+    fn is_sink(&self) -> bool {
+        NodeBehaviour::is_sink(self)
+    }
+
+    fn is_external(&self) -> bool {
+        NodeBehaviour::is_external(self)
+    }
+
+    fn is_placeholder(&self) -> bool {
+        NodeBehaviour::is_placeholder(self)
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        NodeBehaviour::icon(self)
+    }
+
     fn update(&mut self, event: NodeEventContainer) -> Vec<NodeCommand> {
         NodeBehaviour::update(self, NodeEvent::from_container(event).unwrap())
     }
@@ -302,21 +686,149 @@ impl<T: NodeBehaviour> NodeBehaviourContainer for T {
             .map(|element| element.map(|message| Box::new(message) as Box<dyn NodeBehaviourMessage>))
     }
 
+    fn context_menu_items(&self) -> Vec<ContextMenuItem<Box<dyn NodeBehaviourMessage>>> {
+        NodeBehaviour::context_menu_items(self)
+            .into_iter()
+            .map(|item| item.map(|message| Box::new(message) as Box<dyn NodeBehaviourMessage>))
+            .collect()
+    }
+
+    fn save(&self) -> serde_json::Value {
+        NodeBehaviour::save(self)
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        NodeBehaviour::load(self, settings)
+    }
+
+    fn update_input_sources(&mut self, sources: &[Option<String>]) {
+        NodeBehaviour::update_input_sources(self, sources)
+    }
+
+    fn ignored_inputs(&self) -> Vec<usize> {
+        NodeBehaviour::ignored_inputs(self)
+    }
+
+    fn validate(
+        &self,
+        configuration: &NodeConfiguration,
+        connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        NodeBehaviour::validate(self, configuration, connected)
+    }
+
     fn create_state<'state>(&self, context: &ApplicationContext) -> NodeStateContainer<'state> {
         let state = <Self as NodeBehaviour>::create_state(self, context);
 
         NodeStateContainer::from::<Self>(state)
     }
 
-    fn update_state<'state>(&self, context: &ApplicationContext, state: &mut NodeStateContainer<'state>) {
+    fn update_state<'state>(
+        &self,
+        context: &ApplicationContext,
+        state: &mut NodeStateContainer<'state>,
+    ) -> bool {
         state.update::<Self>(context, self)
     }
 }
 
+/// Creates the default behaviours of all kinds of nodes, used to instantiate nodes by name.
+pub fn all_behaviours() -> Vec<Box<dyn NodeBehaviourContainer>> {
+    let mut behaviours: Vec<Box<dyn NodeBehaviourContainer>> = vec![
+        Box::new(ABSwitchNodeBehaviour::default()),
+        Box::new(ArrayConstructorNodeBehaviour::default()),
+        Box::new(BinaryOpNodeBehaviour::default()),
+        Box::new(BitOpNodeBehaviour::default()),
+        Box::new(BufferUploadNodeBehaviour::default()),
+        Box::new(ClockNodeBehaviour::default()),
+        Box::new(ColorFromHsvNodeBehaviour::default()),
+        Box::new(ColorPickerNodeBehaviour::default()),
+        Box::new(ColorSpaceConversionNodeBehaviour::default()),
+        Box::new(ColorToComponentsNodeBehaviour::default()),
+        Box::new(ConstantNodeBehaviour::default()),
+        Box::new(CounterNodeBehaviour::default()),
+        Box::new(DebugNodeBehaviour::default()),
+        Box::new(DelayNodeBehaviour::default()),
+        Box::new(EnvelopeNodeBehaviour::default()),
+        Box::new(FileWatcherNodeBehaviour::default()),
+        Box::new(FormatNodeBehaviour::default()),
+        Box::new(GradientNodeBehaviour::default()),
+        Box::new(HistogramNodeBehaviour::default()),
+        Box::new(IsFiniteNodeBehaviour::default()),
+        Box::new(ListConstructorNodeBehaviour::default()),
+        Box::new(ListReduceNodeBehaviour::default()),
+        Box::new(MidiOutputNodeBehaviour::default()),
+        Box::new(OscReceiveNodeBehaviour::default()),
+        Box::new(PackNodeBehaviour::default()),
+        Box::new(PointerInfoNodeBehaviour::default()),
+        Box::new(ProbabilityGateNodeBehaviour::default()),
+        Box::new(QuantizeNodeBehaviour::default()),
+        Box::new(RangeNodeBehaviour::default()),
+        Box::new(RangeSliderNodeBehaviour::default()),
+        Box::new(RunningStatsNodeBehaviour::default()),
+        Box::new(SchmittTriggerNodeBehaviour::default()),
+        Box::new(TableViewNodeBehaviour::default()),
+        Box::new(TextRenderNodeBehaviour::default()),
+        Box::new(TextureHistoryNodeBehaviour::default()),
+        Box::new(TextureReadbackNodeBehaviour::default()),
+        Box::new(TextureTransformNodeBehaviour::default()),
+        Box::new(TweenNodeBehaviour::default()),
+        Box::new(UnpackNodeBehaviour::default()),
+        Box::new(WeightedChoiceNodeBehaviour::default()),
+        Box::new(WindowNodeBehaviour::default()),
+    ];
+
+    behaviours.extend(
+        simple_map_behaviours()
+            .into_iter()
+            .map(|behaviour| Box::new(behaviour) as Box<dyn NodeBehaviourContainer>),
+    );
+    behaviours
+}
+
+/// Creates the default behaviour with the given [`NodeBehaviour::name`].
+pub fn create_behaviour(name: &str) -> Option<Box<dyn NodeBehaviourContainer>> {
+    all_behaviours().into_iter().find(|behaviour| behaviour.name() == name)
+}
+
+pub mod ab_switch;
 pub mod array_constructor;
 pub mod binary_op;
+pub mod bit_op;
+pub mod buffer_upload;
+pub mod clock;
+pub mod color;
+pub mod color_picker;
 pub mod constant;
 pub mod counter;
 pub mod debug;
+pub mod delay;
+pub mod envelope;
+pub mod file_watcher;
+pub mod format;
+pub mod gradient;
+pub mod histogram;
+pub mod is_finite;
 pub mod list_constructor;
+pub mod list_reduce;
+pub mod midi_output;
+pub mod osc_receive;
+pub mod pack;
+pub mod placeholder;
+pub mod pointer_info;
+pub mod probability_gate;
+pub mod quantize;
+pub mod random;
+pub mod range;
+pub mod range_slider;
+pub mod running_stats;
+pub mod schmitt_trigger;
+pub mod simple_map;
+pub mod table_view;
+pub mod text_render;
+pub mod texture_history;
+pub mod texture_readback;
+pub mod texture_transform;
+pub mod tween;
+pub mod weighted_choice;
 pub mod window;
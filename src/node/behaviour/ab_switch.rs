@@ -0,0 +1,409 @@
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        ArrayType, BufferType, Channel, ColorType, ListType, NodeConfiguration, PrimitiveTypeEnum, Shared,
+        TextureType, TypeEnum,
+    },
+    style::{consts, Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use iced::{
+    button::{self, Button},
+    Align, Column, Element, HorizontalAlignment, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ABSide {
+    A,
+    B,
+}
+
+impl ABSide {
+    pub fn other(self) -> Self {
+        match self {
+            ABSide::A => ABSide::B,
+            ABSide::B => ABSide::A,
+        }
+    }
+
+    /// The index of the input channel of the side.
+    pub fn channel_index(self) -> usize {
+        match self {
+            ABSide::A => 0,
+            ABSide::B => 1,
+        }
+    }
+}
+
+impl Display for ABSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ABSide::A => write!(f, "A"),
+            ABSide::B => write!(f, "B"),
+        }
+    }
+}
+
+/// The shapes of the values switched between, all of which are cloneable: scalars, arrays and
+/// colors are copied, while textures, buffers and lists are passed through shared, refcounted
+/// pointers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ABSwitchShape {
+    Scalar,
+    Array,
+    Color,
+    Texture,
+    Buffer,
+    List,
+}
+
+impl ABSwitchShape {
+    pub const VALUES: [ABSwitchShape; 6] = [
+        ABSwitchShape::Scalar,
+        ABSwitchShape::Array,
+        ABSwitchShape::Color,
+        ABSwitchShape::Texture,
+        ABSwitchShape::Buffer,
+        ABSwitchShape::List,
+    ];
+
+    /// Whether the values are composed of the selected primitive type.
+    pub fn has_primitive_type(self) -> bool {
+        matches!(self, ABSwitchShape::Scalar | ABSwitchShape::Array | ABSwitchShape::List)
+    }
+}
+
+impl Display for ABSwitchShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ABSwitchShape::Scalar => write!(f, "Scalar"),
+            ABSwitchShape::Array => write!(f, "Array"),
+            ABSwitchShape::Color => write!(f, "Color"),
+            ABSwitchShape::Texture => write!(f, "Texture"),
+            ABSwitchShape::Buffer => write!(f, "Buffer"),
+            ABSwitchShape::List => write!(f, "List"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ABSwitchSettings {
+    /// The type of scalars and of the items of arrays and lists, ignored for other shapes.
+    pub ty: PrimitiveTypeEnum,
+    pub shape: ABSwitchShape,
+    /// The number of items of arrays, ignored for other shapes.
+    pub length: usize,
+    pub selected: ABSide,
+}
+
+impl Default for ABSwitchSettings {
+    fn default() -> Self {
+        Self { ty: PrimitiveTypeEnum::F32, shape: ABSwitchShape::Scalar, length: 2, selected: ABSide::A }
+    }
+}
+
+impl ABSwitchSettings {
+    /// The selectable numbers of items of arrays.
+    const LENGTHS: [usize; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    /// The type of the inputs and of the output. Textures of any format are accepted.
+    pub fn value_type(&self) -> TypeEnum {
+        match self.shape {
+            ABSwitchShape::Scalar => self.ty.into(),
+            ABSwitchShape::Array => ArrayType::new_if_sized(self.ty, self.length).unwrap().into(),
+            ABSwitchShape::Color => ColorType::new().into(),
+            ABSwitchShape::Texture => Shared::from_enum(TextureType::new()).into(),
+            ABSwitchShape::Buffer => Shared::from_enum(BufferType::new()).into(),
+            ABSwitchShape::List => Shared::from_enum(ListType::new_if_sized(self.ty).unwrap()).into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ABSwitchNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    UpdateShape(ABSwitchShape),
+    UpdateLength(usize),
+    /// Selects the other input, also broadcast to all A/B switches by
+    /// [`Action::ToggleABSwitches`](crate::keymap::Action::ToggleABSwitches).
+    Toggle,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ABSwitchUiState {
+    type_state: enum_dropdown::State,
+    shape_state: enum_dropdown::State,
+    length_state: enum_dropdown::State,
+    toggle_state: button::State,
+}
+
+/// Forwards the value of one of its two inputs, so that two alternative chains of nodes can be
+/// compared by flipping between them. The nodes only the other input depends on are not executed.
+/// The inputs are passed by value, which is either copied or a shared pointer, see
+/// [`ABSwitchShape`].
+#[derive(Debug, Clone)]
+pub struct ABSwitchNodeBehaviour {
+    settings: ABSwitchSettings,
+    ui_state: ABSwitchUiState,
+}
+
+impl Default for ABSwitchNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl ABSwitchNodeBehaviour {
+    /// The name of the behaviour, which the messages of the global toggle are broadcast to.
+    pub const NAME: &'static str = "A/B Switch";
+
+    pub fn with_settings(settings: ABSwitchSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let ty = self.settings.value_type();
+
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_input_value(Channel::new("a", ty.clone()))
+                .with_input_value(Channel::new("b", ty.clone()))
+                .with_output_value(Channel::new("value", ty)),
+        )
+    }
+}
+
+impl NodeBehaviour for ABSwitchNodeBehaviour {
+    type Message = ABSwitchNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self>;
+
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => match message {
+                ABSwitchNodeMessage::UpdateType(ty) => {
+                    self.settings.ty = ty;
+                    vec![self.get_configure_command()]
+                }
+                ABSwitchNodeMessage::UpdateShape(shape) => {
+                    self.settings.shape = shape;
+                    vec![self.get_configure_command()]
+                }
+                ABSwitchNodeMessage::UpdateLength(length) => {
+                    self.settings.length = length;
+                    vec![self.get_configure_command()]
+                }
+                ABSwitchNodeMessage::Toggle => {
+                    self.settings.selected = self.settings.selected.other();
+                    vec![]
+                }
+            },
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+        let mut row = Row::new()
+            .theme(theme)
+            .push(
+                EnumDropdown::new(
+                    &mut ui_state.shape_state,
+                    &ABSwitchShape::VALUES[..],
+                    settings.shape,
+                    ABSwitchNodeMessage::UpdateShape,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+            )
+            .align_items(Align::Center)
+            .width(Length::Fill);
+
+        if settings.shape.has_primitive_type() {
+            row = row.push(
+                EnumDropdown::new(
+                    &mut ui_state.type_state,
+                    &PrimitiveTypeEnum::VALUES[..],
+                    settings.ty,
+                    ABSwitchNodeMessage::UpdateType,
+                )
+                .theme(theme)
+                .width(Length::Units(64)),
+            );
+        }
+
+        if settings.shape == ABSwitchShape::Array {
+            row = row.push(
+                EnumDropdown::new(
+                    &mut ui_state.length_state,
+                    &ABSwitchSettings::LENGTHS[..],
+                    settings.length,
+                    ABSwitchNodeMessage::UpdateLength,
+                )
+                .theme(theme)
+                .width(Length::Units(48)),
+            );
+        }
+
+        Some(
+            Column::new()
+                .theme(theme)
+                .push(row)
+                .push(
+                    Button::new(
+                        &mut ui_state.toggle_state,
+                        Text::new(settings.selected.to_string())
+                            .size(consts::TEXT_SIZE_TITLE * 2)
+                            .horizontal_alignment(HorizontalAlignment::Center)
+                            .width(Length::Fill),
+                    )
+                    .on_press(ABSwitchNodeMessage::Toggle)
+                    .width(Length::Fill),
+                )
+                .align_items(Align::Center)
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        *self = Self::with_settings(serde_json::from_value(settings)?);
+        Ok(())
+    }
+
+    fn ignored_inputs(&self) -> Vec<usize> {
+        vec![self.settings.selected.other().channel_index()]
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                Box::new(move |mut context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    if context.forward(settings.selected.channel_index(), 0).is_ok() {
+                        context.clear_error();
+                    }
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::testing::{cpu_context, executor_lock, schedule};
+    use crate::graph::{ExecutionGraph, PreparedExecution};
+    use crate::node::behaviour::ConstantNodeBehaviour;
+    use crate::node::{ChannelPassBy, NodeIndex, TypeExt};
+    use crate::template::GraphBuilder;
+
+    /// A constant array of two `f32` items.
+    fn array_constant(items: [f32; 2]) -> ConstantNodeBehaviour {
+        let mut behaviour = ConstantNodeBehaviour::default();
+        let bytes = items.iter().flat_map(|item| item.to_le_bytes().to_vec()).collect::<Vec<_>>();
+        let settings =
+            serde_json::json!({ "ty": "F32", "shape": "Array", "array_length": 2, "bytes": bytes });
+
+        behaviour.load(settings).unwrap();
+        behaviour
+    }
+
+    /// Two constant arrays switched between by a switch with the given side selected.
+    fn switched_arrays(selected: ABSide) -> (ExecutionGraph, NodeIndex) {
+        let mut builder = GraphBuilder::default();
+        let settings =
+            ABSwitchSettings { shape: ABSwitchShape::Array, length: 2, selected, ..Default::default() };
+        let a = builder.node("A", [0.0, 0.0], array_constant([1.0, 2.0]));
+        let b = builder.node("B", [0.0, 200.0], array_constant([3.0, 4.0]));
+        let switch = builder.node("Switch", [200.0, 0.0], ABSwitchNodeBehaviour::with_settings(settings));
+
+        builder.connect((a, 0), (switch, ChannelPassBy::Value, 0));
+        builder.connect((b, 0), (switch, ChannelPassBy::Value, 1));
+
+        (builder.build().into(), switch)
+    }
+
+    #[test]
+    fn values_of_every_shape_are_cloneable() {
+        for shape in ABSwitchShape::VALUES.iter().copied() {
+            let settings = ABSwitchSettings { shape, ..Default::default() };
+
+            assert!(settings.value_type().is_cloneable(), "{}", shape);
+        }
+    }
+
+    #[test]
+    fn pointers_are_shared() {
+        for shape in [ABSwitchShape::Texture, ABSwitchShape::Buffer, ABSwitchShape::List].iter().copied() {
+            let settings = ABSwitchSettings { shape, ..Default::default() };
+
+            assert!(matches!(settings.value_type(), TypeEnum::Shared(_)), "{}", shape);
+        }
+    }
+
+    /// The array of the selected input is forwarded to the output, the other one is pruned.
+    #[test]
+    fn the_selected_array_is_forwarded() {
+        let _lock = executor_lock();
+        let mut context = cpu_context();
+
+        for (selected, expected) in [(ABSide::A, [1.0_f32, 2.0]), (ABSide::B, [3.0, 4.0])].iter() {
+            let (mut graph, switch) = switched_arrays(*selected);
+            let schedule = schedule(&mut graph);
+            let mut execution = PreparedExecution::from(&schedule, &mut context, None);
+
+            execution.execute(&schedule, &mut context, &mut None);
+
+            {
+                let mut task = execution
+                    .tasks
+                    .iter()
+                    .flatten()
+                    .map(|task| task.write().unwrap())
+                    .find(|task| task.node_index == switch)
+                    .unwrap();
+                let (ty, bytes) = task.output_values.get_mut(0).unwrap();
+                let some = match ty {
+                    TypeEnum::Option(option_ty) => option_ty.is_some_in(bytes),
+                    ty => panic!("Unexpected type: {}", ty),
+                };
+                let items = bytes[..8]
+                    .chunks(4)
+                    .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect::<Vec<_>>();
+
+                assert!(some, "{}", selected);
+                assert_eq!(items, expected, "{}", selected);
+            }
+
+            execution.release();
+        }
+    }
+}
@@ -2,7 +2,7 @@ use crate::{
     node::{
         behaviour::{
             ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
-            NodeStateClosure,
+            NodeIcon, NodeStateClosure,
         },
         ArrayType, BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType,
         PrimitiveTypeEnum,
@@ -15,6 +15,7 @@ use iced::{
     Element,
 };
 use iced::{Align, Length, Row, Text};
+use serde::{Deserialize, Serialize};
 use std::io::{Cursor, Write};
 use std::num::NonZeroUsize;
 use style::Theme;
@@ -26,6 +27,12 @@ pub enum ArrayConstructorNodeMessage {
     RemoveChannel,
 }
 
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    ty: PrimitiveTypeEnum,
+    channel_count: NonZeroUsize,
+}
+
 #[derive(Clone, Debug)]
 pub struct ArrayConstructorNodeBehaviour {
     ty: PrimitiveTypeEnum,
@@ -70,6 +77,10 @@ impl NodeBehaviour for ArrayConstructorNodeBehaviour {
         "ArrayConstructor"
     }
 
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Collection)
+    }
+
     fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
         match event {
             NodeEvent::Update => vec![self.get_configure_command()],
@@ -128,6 +139,17 @@ impl NodeBehaviour for ArrayConstructorNodeBehaviour {
         )
     }
 
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(SavedSettings { ty: self.ty, channel_count: self.channel_count }).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let SavedSettings { ty, channel_count } = serde_json::from_value(settings)?;
+        self.ty = ty;
+        self.channel_count = channel_count;
+        Ok(())
+    }
+
     fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
         NodeStateClosure::new(
             self,
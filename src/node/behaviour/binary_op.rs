@@ -2,19 +2,19 @@ use crate::node::PrimitiveChannelValue;
 use crate::{
     node::{
         behaviour::{
-            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
-            NodeStateClosure,
+            ApplicationContext, ConnectedTypeInfo, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure, NodeValidationIssue,
         },
         BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
     },
     style::{Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
 };
 use byteorder::LittleEndian;
-use iced::{
-    pick_list::{self, PickList},
-    Element,
-};
-use iced::{Align, Container, Length, Row};
+use iced::Element;
+use iced::{Align, Length, Row};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 use std::io::Cursor;
 use std::ops::{Add, Div, Mul, Sub};
 
@@ -24,11 +24,17 @@ pub enum BinaryOpMessage {
     UpdateOp(BinaryOp),
 }
 
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    ty: PrimitiveTypeEnum,
+    op: BinaryOp,
+}
+
 #[derive(Clone, Debug)]
 pub struct BinaryOpNodeBehaviour {
-    pub pick_list_ty_state: pick_list::State<PrimitiveTypeEnum>,
+    pub pick_list_ty_state: enum_dropdown::State,
     pub pick_list_ty_value: PrimitiveTypeEnum,
-    pub pick_list_op_state: pick_list::State<BinaryOp>,
+    pub pick_list_op_state: enum_dropdown::State,
     pub op: BinaryOp,
 }
 
@@ -61,6 +67,10 @@ impl NodeBehaviour for BinaryOpNodeBehaviour {
         "Binary Operation"
     }
 
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
     fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
         match event {
             NodeEvent::Update => vec![self.get_configure_command()],
@@ -85,31 +95,23 @@ impl NodeBehaviour for BinaryOpNodeBehaviour {
             Row::new()
                 .theme(theme)
                 .push(
-                    // Wrap PickList in a container because PickList's width resolution is buggy
-                    Container::new(
-                        PickList::new(
-                            &mut self.pick_list_ty_state,
-                            &PrimitiveTypeEnum::VALUES[..],
-                            Some(self.pick_list_ty_value),
-                            |new_value| BinaryOpMessage::UpdateType(new_value),
-                        )
-                        .theme(theme)
-                        .width(Length::Fill),
+                    EnumDropdown::new(
+                        &mut self.pick_list_ty_state,
+                        &PrimitiveTypeEnum::VALUES[..],
+                        self.pick_list_ty_value,
+                        BinaryOpMessage::UpdateType,
                     )
+                    .theme(theme)
                     .width(Length::Fill),
                 )
                 .push(
-                    // Wrap PickList in a container because PickList's width resolution is buggy
-                    Container::new(
-                        PickList::new(
-                            &mut self.pick_list_op_state,
-                            &BinaryOp::VALUES[..],
-                            Some(self.op),
-                            |value| BinaryOpMessage::UpdateOp(value),
-                        )
-                        .theme(theme)
-                        .width(Length::Fill),
+                    EnumDropdown::new(
+                        &mut self.pick_list_op_state,
+                        &BinaryOp::VALUES[..],
+                        self.op,
+                        BinaryOpMessage::UpdateOp,
                     )
+                    .theme(theme)
                     .width(Length::Units(48)),
                 )
                 .align_items(Align::Center)
@@ -118,6 +120,26 @@ impl NodeBehaviour for BinaryOpNodeBehaviour {
         )
     }
 
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(SavedSettings { ty: self.pick_list_ty_value, op: self.op }).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let SavedSettings { ty, op } = serde_json::from_value(settings)?;
+        self.pick_list_ty_value = ty;
+        self.op = op;
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _configuration: &NodeConfiguration,
+        connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        // Arithmetic on integers of the other signedness yields unexpected results.
+        connected.reinterpretation_warnings().collect()
+    }
+
     fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
         NodeStateClosure::new(
             self,
@@ -153,7 +175,7 @@ impl NodeBehaviour for BinaryOpNodeBehaviour {
     }
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum BinaryOp {
     Add,
     Sub,
@@ -164,16 +186,15 @@ pub enum BinaryOp {
     // Xor,
 }
 
-impl ToString for BinaryOp {
-    fn to_string(&self) -> String {
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use BinaryOp::*;
         match self {
-            Add => "+",
-            Sub => "-",
-            Mul => "*",
-            Div => "/",
+            Add => write!(f, "+"),
+            Sub => write!(f, "-"),
+            Mul => write!(f, "*"),
+            Div => write!(f, "/"),
         }
-        .to_string()
     }
 }
 
@@ -0,0 +1,436 @@
+use crate::node::PrimitiveChannelValue;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveKind, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::LittleEndian;
+use iced::{Align, Element, Length, Row};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Cursor;
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+/// The types the bitwise operations can be applied to.
+pub const INTEGER_TYPES: [PrimitiveTypeEnum; 10] = [
+    PrimitiveTypeEnum::U8,
+    PrimitiveTypeEnum::U16,
+    PrimitiveTypeEnum::U32,
+    PrimitiveTypeEnum::U64,
+    PrimitiveTypeEnum::U128,
+    PrimitiveTypeEnum::I8,
+    PrimitiveTypeEnum::I16,
+    PrimitiveTypeEnum::I32,
+    PrimitiveTypeEnum::I64,
+    PrimitiveTypeEnum::I128,
+];
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum BitOp {
+    And,
+    Or,
+    Xor,
+    Not,
+    ShiftLeft,
+    /// Shifts in zeros, regardless of the signedness of the type.
+    ShiftRightLogical,
+    /// Shifts in copies of the most significant bit, regardless of the signedness of the type.
+    ShiftRightArithmetic,
+}
+
+impl Display for BitOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use BitOp::*;
+        match self {
+            And => write!(f, "&"),
+            Or => write!(f, "|"),
+            Xor => write!(f, "^"),
+            Not => write!(f, "!"),
+            ShiftLeft => write!(f, "<<"),
+            ShiftRightLogical => write!(f, ">>>"),
+            ShiftRightArithmetic => write!(f, ">>"),
+        }
+    }
+}
+
+/// The operations on the bits of an integer type.
+pub trait Bits:
+    Copy + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> + Not<Output = Self>
+{
+    /// The shift amount represented by the value, before it is masked.
+    fn shift_amount(self) -> u32;
+    fn shift_left(self, amount: u32) -> Self;
+    fn shift_right_logical(self, amount: u32) -> Self;
+    fn shift_right_arithmetic(self, amount: u32) -> Self;
+}
+
+macro_rules! impl_bits {
+    ($($ty:ty => $unsigned:ty, $signed:ty);*$(;)?) => {
+        $(
+            impl Bits for $ty {
+                fn shift_amount(self) -> u32 {
+                    // Truncation keeps the low bits, which are the only ones kept by the masking.
+                    self as u32
+                }
+
+                // The `wrapping_*` shifts mask the amount by the bit width of the type.
+                fn shift_left(self, amount: u32) -> Self {
+                    self.wrapping_shl(amount)
+                }
+
+                fn shift_right_logical(self, amount: u32) -> Self {
+                    (self as $unsigned).wrapping_shr(amount) as $ty
+                }
+
+                fn shift_right_arithmetic(self, amount: u32) -> Self {
+                    (self as $signed).wrapping_shr(amount) as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_bits! {
+    u8 => u8, i8;
+    u16 => u16, i16;
+    u32 => u32, i32;
+    u64 => u64, i64;
+    u128 => u128, i128;
+    i8 => u8, i8;
+    i16 => u16, i16;
+    i32 => u32, i32;
+    i64 => u64, i64;
+    i128 => u128, i128;
+}
+
+impl BitOp {
+    pub const VALUES: [BitOp; 7] = [
+        BitOp::And,
+        BitOp::Or,
+        BitOp::Xor,
+        BitOp::Not,
+        BitOp::ShiftLeft,
+        BitOp::ShiftRightLogical,
+        BitOp::ShiftRightArithmetic,
+    ];
+
+    pub fn is_unary(self) -> bool {
+        self == BitOp::Not
+    }
+
+    pub fn is_shift(self) -> bool {
+        matches!(self, BitOp::ShiftLeft | BitOp::ShiftRightLogical | BitOp::ShiftRightArithmetic)
+    }
+
+    /// Applies the operation, `rhs` is ignored by unary operations and is the shift amount of
+    /// shifts. Shift amounts are masked by the bit width of the type.
+    pub fn apply<T: Bits>(self, lhs: T, rhs: T) -> T {
+        match self {
+            BitOp::And => lhs & rhs,
+            BitOp::Or => lhs | rhs,
+            BitOp::Xor => lhs ^ rhs,
+            BitOp::Not => !lhs,
+            BitOp::ShiftLeft => lhs.shift_left(rhs.shift_amount()),
+            BitOp::ShiftRightLogical => lhs.shift_right_logical(rhs.shift_amount()),
+            BitOp::ShiftRightArithmetic => lhs.shift_right_arithmetic(rhs.shift_amount()),
+        }
+    }
+
+    pub fn apply_dyn(self, lhs: PrimitiveChannelValue, rhs: PrimitiveChannelValue) -> PrimitiveChannelValue {
+        use PrimitiveChannelValue::*;
+        match (lhs, rhs) {
+            (U8(lhs), U8(rhs)) => U8(self.apply(lhs, rhs)),
+            (U16(lhs), U16(rhs)) => U16(self.apply(lhs, rhs)),
+            (U32(lhs), U32(rhs)) => U32(self.apply(lhs, rhs)),
+            (U64(lhs), U64(rhs)) => U64(self.apply(lhs, rhs)),
+            (U128(lhs), U128(rhs)) => U128(self.apply(lhs, rhs)),
+            (I8(lhs), I8(rhs)) => I8(self.apply(lhs, rhs)),
+            (I16(lhs), I16(rhs)) => I16(self.apply(lhs, rhs)),
+            (I32(lhs), I32(rhs)) => I32(self.apply(lhs, rhs)),
+            (I64(lhs), I64(rhs)) => I64(self.apply(lhs, rhs)),
+            (I128(lhs), I128(rhs)) => I128(self.apply(lhs, rhs)),
+            _ => panic!("Incompatible dynamic primitive types when trying to apply a bitwise operation."),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BitOpNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    UpdateOp(BitOp),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BitOpSettings {
+    /// Always an integer type.
+    pub ty: PrimitiveTypeEnum,
+    pub op: BitOp,
+}
+
+impl Default for BitOpSettings {
+    fn default() -> Self {
+        Self { ty: PrimitiveTypeEnum::U32, op: BitOp::And }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct BitOpUiState {
+    ty_state: enum_dropdown::State,
+    op_state: enum_dropdown::State,
+}
+
+/// Applies bitwise operations and shifts to integers.
+#[derive(Debug, Clone)]
+pub struct BitOpNodeBehaviour {
+    settings: BitOpSettings,
+    ui_state: BitOpUiState,
+}
+
+impl Default for BitOpNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl BitOpNodeBehaviour {
+    pub fn with_settings(settings: BitOpSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let ty = self.settings.ty;
+        let mut configuration = NodeConfiguration::default();
+
+        if self.settings.op.is_unary() {
+            configuration = configuration.with_input_value(Channel::new("value", ty));
+        } else if self.settings.op.is_shift() {
+            let amount = Channel::new("amount", ty).with_description("Masked by the bit width of the type");
+
+            configuration =
+                configuration.with_input_value(Channel::new("value", ty)).with_input_value(amount);
+        } else {
+            configuration = configuration
+                .with_input_value(Channel::new("lhs", ty))
+                .with_input_value(Channel::new("rhs", ty));
+        }
+
+        NodeCommand::Configure(configuration.with_output_value(Channel::new("result", ty)))
+    }
+}
+
+impl NodeBehaviour for BitOpNodeBehaviour {
+    type Message = BitOpNodeMessage;
+
+    fn name(&self) -> &str {
+        "Bit Operation"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                let mut commands = Vec::new();
+
+                match message {
+                    BitOpNodeMessage::UpdateType(ty) => {
+                        self.settings.ty = ty;
+                        commands.push(self.get_configure_command());
+                    }
+                    BitOpNodeMessage::UpdateOp(op) => {
+                        let reconfigure = op.is_unary() != self.settings.op.is_unary()
+                            || op.is_shift() != self.settings.op.is_shift();
+
+                        self.settings.op = op;
+
+                        if reconfigure {
+                            commands.push(self.get_configure_command());
+                        }
+                    }
+                }
+
+                commands
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            Row::new()
+                .theme(theme)
+                .push(
+                    EnumDropdown::new(
+                        &mut self.ui_state.ty_state,
+                        &INTEGER_TYPES[..],
+                        self.settings.ty,
+                        BitOpNodeMessage::UpdateType,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(
+                    EnumDropdown::new(
+                        &mut self.ui_state.op_state,
+                        &BitOp::VALUES[..],
+                        self.settings.op,
+                        BitOpNodeMessage::UpdateOp,
+                    )
+                    .theme(theme)
+                    .width(Length::Units(56)),
+                )
+                .align_items(Align::Center)
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: BitOpSettings = serde_json::from_value(settings)?;
+
+        if settings.ty.kind() == PrimitiveKind::Float {
+            return Err(<serde_json::Error as serde::de::Error>::custom(
+                "bitwise operations cannot be applied to floating point types",
+            ));
+        }
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let BitOpSettings { ty, op } = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let lhs = ty.read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap()).unwrap();
+                    let rhs = if op.is_unary() {
+                        ty.default_value()
+                    } else {
+                        ty.read::<LittleEndian, _>(&context.inputs[1].as_bytes().unwrap()).unwrap()
+                    };
+                    let result = op.apply_dyn(lhs, rhs);
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            result.write::<LittleEndian>(&mut Cursor::new(bytes)).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::{BigEndian, ByteOrder};
+
+    /// Bit patterns truncated to the width of each type, including ones with the most significant
+    /// bit set for every width.
+    const SAMPLES: [u128; 7] = [
+        0,
+        1,
+        u128::MAX,
+        0x8080_8080_8080_8080_8080_8080_8080_8080,
+        0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210,
+        0x5555_5555_5555_5555_5555_5555_5555_5555,
+        0x0000_0000_0000_0000_0000_0000_0000_0107,
+    ];
+
+    /// The result of the operation on bit patterns of the given width.
+    fn reference(op: BitOp, width: u32, lhs: u128, rhs: u128) -> u128 {
+        let mask = u128::MAX >> (128 - width);
+        let (lhs, rhs) = (lhs & mask, rhs & mask);
+        let amount = (rhs % width as u128) as u32;
+        let result = match op {
+            BitOp::And => lhs & rhs,
+            BitOp::Or => lhs | rhs,
+            BitOp::Xor => lhs ^ rhs,
+            BitOp::Not => !lhs,
+            BitOp::ShiftLeft => lhs << amount,
+            BitOp::ShiftRightLogical => lhs >> amount,
+            BitOp::ShiftRightArithmetic => {
+                // Sign-extend the most significant bit of the width.
+                ((((lhs << (128 - width)) as i128) >> (128 - width)) >> amount) as u128
+            }
+        };
+
+        result & mask
+    }
+
+    /// Applies every operation to every pair of samples of every type, read and written in the byte
+    /// order `E`.
+    fn check_all_types<E: ByteOrder>() {
+        for ty in INTEGER_TYPES.iter().copied() {
+            let size = ty.value_size();
+            let width = size as u32 * 8;
+            let mask = u128::MAX >> (128 - width);
+            let value = |bits: u128| {
+                let mut bytes = vec![0; size];
+
+                E::write_uint128(&mut bytes, bits & mask, size);
+                ty.read::<E, _>(&bytes[..]).unwrap()
+            };
+
+            for op in BitOp::VALUES.iter().copied() {
+                for lhs in SAMPLES.iter().copied() {
+                    for rhs in SAMPLES.iter().copied().chain(0..=width as u128 + 1) {
+                        let mut bytes = Vec::new();
+
+                        op.apply_dyn(value(lhs), value(rhs)).write::<E>(&mut bytes).unwrap();
+
+                        assert_eq!(
+                            E::read_uint128(&bytes, size),
+                            reference(op, width, lhs, rhs),
+                            "{:?} {} {:#x} {:#x}",
+                            ty,
+                            op,
+                            lhs & mask,
+                            rhs & mask,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn operations_match_the_reference_in_little_endian() {
+        check_all_types::<LittleEndian>();
+    }
+
+    #[test]
+    fn operations_match_the_reference_in_big_endian() {
+        check_all_types::<BigEndian>();
+    }
+
+    #[test]
+    fn shift_amounts_are_masked_by_the_bit_width() {
+        assert_eq!(BitOp::ShiftLeft.apply(1u8, 9), 2);
+        assert_eq!(BitOp::ShiftRightLogical.apply(-128i8, 7), 1);
+        assert_eq!(BitOp::ShiftRightArithmetic.apply(0x80u8, 15), 0xFF);
+        assert_eq!(BitOp::ShiftRightArithmetic.apply(i128::MIN, 255), -1);
+        assert_eq!(BitOp::ShiftLeft.apply(1u128, u128::MAX), 1 << 127);
+    }
+}
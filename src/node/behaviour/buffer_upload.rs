@@ -0,0 +1,288 @@
+use crate::node::prelude::*;
+use crate::node::{
+    ArrayType, BufferAllocation, BufferDescriptor, BufferType, LayoutRules, ListOfRecordsRef, ListType,
+    OwnedRefMut, TypeEnum, Unique,
+};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        Channel, NodeConfiguration, OptionRefMutExt, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use iced::{Align, Element, Length, Row};
+use iced_wgpu::wgpu;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub enum BufferUploadNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    UpdateComponents(usize),
+    UpdateRules(LayoutRules),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BufferUploadSettings {
+    /// The type of the components of the items of the list.
+    pub ty: PrimitiveTypeEnum,
+    /// The number of components of each item, items of more than one component are arrays.
+    pub components: usize,
+    pub rules: LayoutRules,
+}
+
+impl Default for BufferUploadSettings {
+    fn default() -> Self {
+        Self { ty: PrimitiveTypeEnum::F32, components: 1, rules: LayoutRules::Std430 }
+    }
+}
+
+impl BufferUploadSettings {
+    /// The selectable numbers of components, as in the vectors of shaders.
+    const COMPONENTS: [usize; 4] = [1, 2, 3, 4];
+
+    fn item_type(&self) -> TypeEnum {
+        if self.components == 1 {
+            self.ty.into()
+        } else {
+            ArrayType::new_if_sized(self.ty, self.components).unwrap().into()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct BufferUploadUiState {
+    ty_state: enum_dropdown::State,
+    components_state: enum_dropdown::State,
+    rules_state: enum_dropdown::State,
+}
+
+/// Uploads the items of a list to a GPU buffer, to be drawn by other nodes.
+#[derive(Debug, Clone)]
+pub struct BufferUploadNodeBehaviour {
+    settings: BufferUploadSettings,
+    ui_state: BufferUploadUiState,
+}
+
+impl Default for BufferUploadNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl BufferUploadNodeBehaviour {
+    pub fn with_settings(settings: BufferUploadSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_borrow(Channel::new("list", ListType::new_if_sized(self.settings.item_type()).unwrap()))
+                .with_output_value(Channel::new("buffer", Unique::new(BufferType::new()))),
+        )
+    }
+}
+
+impl NodeBehaviour for BufferUploadNodeBehaviour {
+    type Message = BufferUploadNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Buffer Upload"
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                match message {
+                    BufferUploadNodeMessage::UpdateType(ty) => self.settings.ty = ty,
+                    BufferUploadNodeMessage::UpdateComponents(components) => {
+                        self.settings.components = components
+                    }
+                    BufferUploadNodeMessage::UpdateRules(rules) => self.settings.rules = rules,
+                }
+
+                vec![self.get_configure_command()]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            Row::new()
+                .theme(theme)
+                .push(
+                    EnumDropdown::new(
+                        &mut self.ui_state.ty_state,
+                        &PrimitiveTypeEnum::VALUES[..],
+                        self.settings.ty,
+                        BufferUploadNodeMessage::UpdateType,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(
+                    EnumDropdown::new(
+                        &mut self.ui_state.components_state,
+                        &BufferUploadSettings::COMPONENTS[..],
+                        self.settings.components,
+                        BufferUploadNodeMessage::UpdateComponents,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(
+                    EnumDropdown::new(
+                        &mut self.ui_state.rules_state,
+                        &LayoutRules::VALUES[..],
+                        self.settings.rules,
+                        BufferUploadNodeMessage::UpdateRules,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .align_items(Align::Center)
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        *self = Self::with_settings(serde_json::from_value(settings)?);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let item_ty = behaviour.settings.item_type();
+                let rules = behaviour.settings.rules;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let list = context.borrows[0].clone().downcast_ref::<ListType>().unwrap();
+                    // Items of primitives always have a safe binary representation.
+                    let records =
+                        ListOfRecordsRef::new(&item_ty, list.items_bytes_if_safe().unwrap()).unwrap();
+                    let (layout, bytes) = records.to_layout(rules);
+                    // Copies to buffers have to be a multiple of the alignment large, and empty
+                    // buffers are not valid bindings.
+                    let aligned_len =
+                        align(bytes.len() as wgpu::BufferAddress, wgpu::COPY_BUFFER_ALIGNMENT) as usize;
+                    let bytes = if aligned_len == 0 {
+                        Cow::Owned(vec![0; wgpu::COPY_BUFFER_ALIGNMENT as usize])
+                    } else if aligned_len != bytes.len() {
+                        let mut padded = bytes.into_owned();
+
+                        padded.resize(aligned_len, 0);
+                        Cow::Owned(padded)
+                    } else {
+                        bytes
+                    };
+                    let size = bytes.len() as wgpu::BufferAddress;
+                    let hash = {
+                        let mut hasher = DefaultHasher::new();
+
+                        bytes.hash(&mut hasher);
+                        hasher.finish()
+                    };
+                    let renderer = if let Some(renderer) = context.renderer() { renderer } else { return };
+
+                    let uploaded = match &persistent.uploaded {
+                        Some(uploaded) if uploaded.allocation.size == size => {
+                            if uploaded.hash != hash {
+                                renderer.queue.write_buffer(&uploaded.allocation.buffer, 0, &bytes);
+                            }
+
+                            UploadedBuffer {
+                                allocation: BufferAllocation {
+                                    layout,
+                                    len: records.len(),
+                                    ..uploaded.allocation.clone()
+                                },
+                                hash,
+                            }
+                        }
+                        // The size has changed, recreate the buffer.
+                        _ => {
+                            let buffer = renderer.device.create_buffer(&wgpu::BufferDescriptor {
+                                label: Some(&format!("node #{}", context.allocator_handle.node.index())),
+                                size,
+                                usage: wgpu::BufferUsage::VERTEX
+                                    | wgpu::BufferUsage::STORAGE
+                                    | wgpu::BufferUsage::COPY_DST,
+                                mapped_at_creation: false,
+                            });
+
+                            renderer.queue.write_buffer(&buffer, 0, &bytes);
+
+                            UploadedBuffer {
+                                allocation: BufferAllocation {
+                                    buffer: Arc::new(buffer),
+                                    size,
+                                    layout,
+                                    len: records.len(),
+                                },
+                                hash,
+                            }
+                        }
+                    };
+
+                    let output: OwnedRefMut<Unique<BufferType>> = context
+                        .allocator_handle
+                        .allocate_object::<BufferType>(BufferDescriptor::new(uploaded.allocation.clone()));
+
+                    context.outputs[0].replace(output.upcast(), context.allocator_handle).unwrap();
+                    persistent.uploaded = Some(uploaded);
+                    context.clear_error();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Rounds the size up to the nearest multiple of the alignment.
+fn align(size: wgpu::BufferAddress, alignment: wgpu::BufferAddress) -> wgpu::BufferAddress {
+    (size + alignment - 1) / alignment * alignment
+}
+
+#[derive(Debug)]
+struct UploadedBuffer {
+    /// Shared with the outputs of the node.
+    allocation: BufferAllocation,
+    /// The hash of the uploaded bytes, to only upload lists that have changed.
+    hash: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Persistent {
+    uploaded: Option<UploadedBuffer>,
+}
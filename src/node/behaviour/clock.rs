@@ -0,0 +1,491 @@
+use crate::node::PrimitiveChannelValue;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ContextMenuItem, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
+    },
+    style::{consts, Theme, Themeable},
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use iced::widget::checkbox::Checkbox;
+use iced::{
+    button::{self, Button},
+    pick_list::{self, PickList},
+    text_input::{self, TextInput},
+    Align, Column, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Cursor;
+use std::time::Instant;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Subdivision {
+    Quarter,
+    Eighth,
+    Sixteenth,
+    /// Eighth note triplets.
+    Triplet,
+}
+
+impl Subdivision {
+    pub const VALUES: [Subdivision; 4] =
+        [Subdivision::Quarter, Subdivision::Eighth, Subdivision::Sixteenth, Subdivision::Triplet];
+
+    pub fn ticks_per_beat(self) -> u32 {
+        match self {
+            Subdivision::Quarter => 1,
+            Subdivision::Eighth => 2,
+            Subdivision::Sixteenth => 4,
+            Subdivision::Triplet => 3,
+        }
+    }
+}
+
+impl Display for Subdivision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Subdivision::Quarter => write!(f, "1/4"),
+            Subdivision::Eighth => write!(f, "1/8"),
+            Subdivision::Sixteenth => write!(f, "1/16"),
+            Subdivision::Triplet => write!(f, "1/8T"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClockSettings {
+    /// Beats per minute, used unless `bpm_input` is set.
+    pub bpm: f32,
+    /// Whether the tempo is read from an input channel.
+    pub bpm_input: bool,
+    pub subdivision: Subdivision,
+    pub running: bool,
+}
+
+impl Default for ClockSettings {
+    fn default() -> Self {
+        Self { bpm: 120.0, bpm_input: false, subdivision: Subdivision::Quarter, running: true }
+    }
+}
+
+/// The position of the clock, advanced by elapsed time rather than by executions.
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    /// The number of beats elapsed since the last reset, including the phase within the current
+    /// beat. Accumulating a continuous position keeps the remainder of every tick, so that
+    /// rounding errors do not cause drift.
+    position: f64,
+    /// The index of the last tick a pulse was emitted for, `None` after a reset.
+    last_tick: Option<u64>,
+}
+
+impl Clock {
+    /// Advances the clock by `delta` seconds and returns whether a tick has occurred.
+    pub fn advance(&mut self, bpm: f32, subdivision: Subdivision, delta: f64) -> bool {
+        self.position += delta * bpm.max(0.0) as f64 / 60.0;
+
+        let tick = (self.position * subdivision.ticks_per_beat() as f64).floor() as u64;
+        let ticked = self.last_tick.map_or(true, |last_tick| tick > last_tick);
+
+        self.last_tick = Some(tick);
+
+        ticked
+    }
+
+    pub fn beat(&self) -> u64 {
+        self.position.floor() as u64
+    }
+
+    /// From 0 to 1 within the current beat.
+    pub fn phase(&self) -> f32 {
+        self.position.fract() as f32
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ClockNodeMessage {
+    UpdateBpm(String),
+    UpdateBpmInput(bool),
+    UpdateSubdivision(Subdivision),
+    ToggleRunning,
+    Reset,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ClockUiState {
+    bpm_state: text_input::State,
+    bpm_string: String,
+    subdivision_state: pick_list::State<Subdivision>,
+    running_state: button::State,
+    reset_state: button::State,
+    /// Describes why the last entered value was rejected.
+    error: Option<&'static str>,
+}
+
+/// Emits pulses at a tempo, along with the current beat and the phase within it.
+#[derive(Debug, Clone)]
+pub struct ClockNodeBehaviour {
+    settings: ClockSettings,
+    /// Incremented to make the executor reset the clock.
+    reset_generation: u64,
+    ui_state: ClockUiState,
+}
+
+impl Default for ClockNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl ClockNodeBehaviour {
+    pub fn with_settings(settings: ClockSettings) -> Self {
+        Self {
+            settings,
+            reset_generation: 0,
+            ui_state: ClockUiState { bpm_string: settings.bpm.to_string(), ..Default::default() },
+        }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let mut configuration = NodeConfiguration::default();
+
+        if self.settings.bpm_input {
+            configuration =
+                configuration.with_input_value(Channel::new("bpm", PrimitiveType::<f32>::default()));
+        }
+
+        NodeCommand::Configure(
+            configuration
+                .with_output_value(
+                    Channel::new("pulse", PrimitiveType::<u8>::default())
+                        .with_description("1 during the execution in which a tick occurs, 0 otherwise"),
+                )
+                .with_output_value(Channel::new("beat", PrimitiveType::<u64>::default()))
+                .with_output_value(Channel::new("phase", PrimitiveType::<f32>::default())),
+        )
+    }
+}
+
+impl NodeBehaviour for ClockNodeBehaviour {
+    type Message = ClockNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Clock"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Value)
+    }
+
+    fn is_source(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use ClockNodeMessage::*;
+                let mut commands = Vec::new();
+
+                match message {
+                    UpdateBpm(string) => {
+                        self.ui_state.error = match string.parse::<f32>() {
+                            Ok(bpm) if bpm > 0.0 && bpm.is_finite() => {
+                                self.settings.bpm = bpm;
+                                None
+                            }
+                            Ok(_) => Some("The tempo must be greater than zero."),
+                            Err(_) => Some("The tempo must be a number."),
+                        };
+                        self.ui_state.bpm_string = string;
+                    }
+                    UpdateBpmInput(bpm_input) => {
+                        self.settings.bpm_input = bpm_input;
+                        commands.push(self.get_configure_command());
+                    }
+                    UpdateSubdivision(subdivision) => {
+                        self.settings.subdivision = subdivision;
+                    }
+                    ToggleRunning => {
+                        self.settings.running = !self.settings.running;
+                    }
+                    Reset => {
+                        self.reset_generation += 1;
+                    }
+                }
+
+                commands
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+        let mut tempo_row = Row::new().theme(theme);
+
+        if !settings.bpm_input {
+            tempo_row = tempo_row.push(
+                TextInput::new(
+                    &mut ui_state.bpm_state,
+                    "BPM",
+                    &ui_state.bpm_string,
+                    ClockNodeMessage::UpdateBpm,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+            );
+        }
+
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                tempo_row
+                    .push(
+                        PickList::new(
+                            &mut ui_state.subdivision_state,
+                            &Subdivision::VALUES[..],
+                            Some(settings.subdivision),
+                            ClockNodeMessage::UpdateSubdivision,
+                        )
+                        .theme(theme)
+                        .width(Length::Units(64)),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            )
+            .push(
+                Checkbox::new(settings.bpm_input, "Tempo input", ClockNodeMessage::UpdateBpmInput)
+                    .theme(theme),
+            )
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        Button::new(
+                            &mut ui_state.running_state,
+                            Text::new(if settings.running { "Stop" } else { "Run" }),
+                        )
+                        .width(Length::Fill)
+                        .on_press(ClockNodeMessage::ToggleRunning),
+                    )
+                    .push(
+                        Button::new(&mut ui_state.reset_state, Text::new("Reset"))
+                            .width(Length::Fill)
+                            .on_press(ClockNodeMessage::Reset),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+
+        if let Some(error) = ui_state.error {
+            column = column.push(Text::new(error).size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn context_menu_items(&self) -> Vec<ContextMenuItem<Self::Message>> {
+        let label = if self.settings.running { "Stop clock" } else { "Run clock" };
+
+        vec![
+            ContextMenuItem::new(label, ClockNodeMessage::ToggleRunning),
+            ContextMenuItem::new("Reset clock", ClockNodeMessage::Reset),
+        ]
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: ClockSettings = serde_json::from_value(settings)?;
+
+        if !(settings.bpm > 0.0) {
+            return Err(<serde_json::Error as serde::de::Error>::custom(
+                "the tempo must be greater than zero",
+            ));
+        }
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                if persistent.reset_generation != behaviour.reset_generation {
+                    persistent.reset_generation = behaviour.reset_generation;
+                    persistent.clock = Default::default();
+                }
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let bpm = if settings.bpm_input {
+                        match PrimitiveTypeEnum::F32
+                            .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                            .unwrap()
+                        {
+                            PrimitiveChannelValue::F32(bpm) => bpm,
+                            _ => unreachable!(),
+                        }
+                    } else {
+                        settings.bpm
+                    };
+                    let now = context.application_context.frame_info.pass_start;
+                    let delta = persistent
+                        .last_execution
+                        .map(|last_execution| (now - last_execution).as_secs_f64())
+                        .unwrap_or(0.0);
+
+                    // The time is measured even while stopped, so that resuming does not skip ahead.
+                    persistent.last_execution = Some(now);
+
+                    let pulse =
+                        settings.running && persistent.clock.advance(bpm, settings.subdivision, delta);
+                    let beat = persistent.clock.beat();
+                    let phase = persistent.clock.phase();
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes).write_u8(pulse as u8).unwrap();
+                        })
+                        .unwrap();
+                    context.outputs[1]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes).write_u64::<LittleEndian>(beat).unwrap();
+                        })
+                        .unwrap();
+                    context.outputs[2]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes).write_f32::<LittleEndian>(phase).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated, so that the clock keeps its position.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    clock: Clock,
+    last_execution: Option<Instant>,
+    /// The [`ClockNodeBehaviour::reset_generation`] the clock was last reset at.
+    reset_generation: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::behaviour::Prng;
+
+    /// Advances the clock by the deltas, returning the number of ticks.
+    fn count_ticks(clock: &mut Clock, bpm: f32, subdivision: Subdivision, deltas: &[f64]) -> u64 {
+        deltas.iter().filter(|delta| clock.advance(bpm, subdivision, **delta)).count() as u64
+    }
+
+    /// Deltas of executions at irregular intervals of 1 to 50 ms, summing up to `duration`.
+    fn irregular_deltas(seed: u64, duration: f64) -> Vec<f64> {
+        let mut prng = Prng::new(seed);
+        let mut deltas = Vec::new();
+        let mut elapsed = 0.0;
+
+        while elapsed < duration {
+            let delta = (0.001 + prng.next_f64() * 0.049).min(duration - elapsed);
+
+            deltas.push(delta);
+            elapsed += delta;
+        }
+
+        deltas
+    }
+
+    #[test]
+    fn ticks_on_the_first_execution() {
+        let mut clock = Clock::default();
+
+        assert!(clock.advance(120.0, Subdivision::Quarter, 0.0));
+        assert!(!clock.advance(120.0, Subdivision::Quarter, 0.1));
+    }
+
+    #[test]
+    fn ticks_at_every_subdivision() {
+        for subdivision in Subdivision::VALUES.iter().copied() {
+            let mut clock = Clock::default();
+            // Executions at 100 Hz for almost 10 seconds, out of phase with the ticks.
+            let deltas = std::iter::once(0.005).chain(std::iter::repeat(0.01).take(999)).collect::<Vec<_>>();
+            let ticks = count_ticks(&mut clock, 60.0, subdivision, &deltas);
+
+            // 10 beats at 60 BPM, the first tick is emitted at the start, the last one is not reached.
+            assert_eq!(ticks, 10 * subdivision.ticks_per_beat() as u64, "{}", subdivision);
+        }
+    }
+
+    /// Irregular intervals between executions must not make the tempo drift.
+    #[test]
+    fn keeps_the_tempo_over_irregular_deltas() {
+        const BPM: f32 = 137.0;
+        // Ten minutes, ending between two ticks.
+        const DURATION: f64 = 600.1;
+
+        for seed in 0..10 {
+            let mut clock = Clock::default();
+            let deltas = irregular_deltas(seed, DURATION);
+            let ticks = count_ticks(&mut clock, BPM, Subdivision::Eighth, &deltas);
+            let expected_position = DURATION * BPM as f64 / 60.0;
+            let position = clock.beat() as f64 + clock.phase() as f64;
+
+            assert!((position - expected_position).abs() < 1e-3, "seed {}: position {}", seed, position);
+
+            // Every tick is longer than the longest delta, so none are skipped.
+            assert_eq!(ticks, (expected_position * 2.0).floor() as u64 + 1, "seed {}", seed);
+
+            // The tick at the start is not part of the tempo. Counting whole ticks is accurate to a
+            // tick, 0.05 BPM over ten minutes of eighth notes.
+            let measured_bpm = (ticks - 1) as f64 / 2.0 / DURATION * 60.0;
+
+            assert!((measured_bpm - BPM as f64).abs() < 0.05, "seed {}: {} BPM", seed, measured_bpm);
+        }
+    }
+
+    /// A delta longer than a tick emits a single pulse rather than a burst of them.
+    #[test]
+    fn long_deltas_tick_once() {
+        let mut clock = Clock::default();
+
+        clock.advance(120.0, Subdivision::Sixteenth, 0.0);
+
+        assert!(clock.advance(120.0, Subdivision::Sixteenth, 5.0));
+        assert_eq!(clock.beat(), 10);
+        assert!(!clock.advance(120.0, Subdivision::Sixteenth, 0.01));
+    }
+
+    #[test]
+    fn does_not_advance_without_a_positive_tempo() {
+        let mut clock = Clock::default();
+
+        clock.advance(120.0, Subdivision::Quarter, 0.25);
+
+        assert!(!clock.advance(0.0, Subdivision::Quarter, 10.0));
+        assert!(!clock.advance(-120.0, Subdivision::Quarter, 10.0));
+        assert_eq!(clock.beat(), 0);
+        assert!((clock.phase() - 0.5).abs() < 1e-6);
+    }
+}
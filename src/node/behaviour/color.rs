@@ -0,0 +1,264 @@
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        hsv_to_rgb, linear_to_srgb, read_color, srgb_to_linear, write_color, BytesRefExt, Channel, ColorType,
+        NodeConfiguration, OptionRefMutExt, PrimitiveType,
+    },
+    style::{Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::{ByteOrder, LittleEndian};
+use iced::{Element, Length};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+const COMPONENT_NAMES: [&str; 4] = ["red", "green", "blue", "alpha"];
+
+/// Creates a color from its hue, saturation, value and alpha. The components are not converted
+/// to the linear color space, use the Color Space Conversion node for HSV values of sRGB colors.
+#[derive(Clone, Debug, Default)]
+pub struct ColorFromHsvNodeBehaviour;
+
+impl NodeBehaviour for ColorFromHsvNodeBehaviour {
+    fn name(&self) -> &str {
+        "Color from HSV"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![NodeCommand::Configure(
+                NodeConfiguration::default()
+                    .with_input_value(
+                        Channel::new("hue", PrimitiveType::<f32>::default())
+                            .with_description("In turns, wraps around"),
+                    )
+                    .with_input_value(Channel::new("saturation", PrimitiveType::<f32>::default()))
+                    .with_input_value(Channel::new("value", PrimitiveType::<f32>::default()))
+                    .with_input_value(Channel::new("alpha", PrimitiveType::<f32>::default()))
+                    .with_output_value(Channel::new("color", ColorType::new())),
+            )],
+            NodeEvent::Message(_) => vec![],
+        }
+    }
+
+    fn view(&mut self, _theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        None
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |_behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let mut components = [0.0; 4];
+
+                    for (index, component) in components.iter_mut().enumerate() {
+                        *component = LittleEndian::read_f32(context.inputs[index].as_bytes().unwrap());
+                    }
+
+                    let [hue, saturation, value, alpha] = components;
+                    let [red, green, blue] = hsv_to_rgb(hue, saturation, value);
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            write_color(bytes, [red, green, blue, alpha])
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
+
+/// Splits a color into its red, green, blue and alpha components.
+#[derive(Clone, Debug, Default)]
+pub struct ColorToComponentsNodeBehaviour;
+
+impl NodeBehaviour for ColorToComponentsNodeBehaviour {
+    fn name(&self) -> &str {
+        "Color to Components"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => {
+                let configuration = COMPONENT_NAMES.iter().fold(
+                    NodeConfiguration::default().with_input_value(Channel::new("color", ColorType::new())),
+                    |configuration, name| {
+                        configuration.with_output_value(Channel::new(*name, PrimitiveType::<f32>::default()))
+                    },
+                );
+
+                vec![NodeCommand::Configure(configuration)]
+            }
+            NodeEvent::Message(_) => vec![],
+        }
+    }
+
+    fn view(&mut self, _theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        None
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |_behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let color = read_color(context.inputs[0].as_bytes().unwrap());
+
+                    for (output, component) in context.outputs.iter_mut().zip(&color) {
+                        output
+                            .replace_with_bytes(context.allocator_handle, |bytes| {
+                                LittleEndian::write_f32(bytes, *component)
+                            })
+                            .unwrap();
+                    }
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpaceConversion {
+    /// Decodes colors encoded with the sRGB transfer function, such as colors picked by the user.
+    SrgbToLinear,
+    /// Encodes colors with the sRGB transfer function, such as colors written into textures of
+    /// formats without the `Srgb` suffix.
+    LinearToSrgb,
+}
+
+impl ColorSpaceConversion {
+    pub const VALUES: [ColorSpaceConversion; 2] =
+        [ColorSpaceConversion::SrgbToLinear, ColorSpaceConversion::LinearToSrgb];
+
+    /// Converts the color components, the alpha is kept as it is.
+    pub fn apply(self, [red, green, blue, alpha]: [f32; 4]) -> [f32; 4] {
+        let convert = match self {
+            ColorSpaceConversion::SrgbToLinear => srgb_to_linear,
+            ColorSpaceConversion::LinearToSrgb => linear_to_srgb,
+        };
+
+        [convert(red), convert(green), convert(blue), alpha]
+    }
+}
+
+impl Display for ColorSpaceConversion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorSpaceConversion::SrgbToLinear => write!(f, "sRGB to linear"),
+            ColorSpaceConversion::LinearToSrgb => write!(f, "Linear to sRGB"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ColorSpaceConversionNodeMessage {
+    UpdateConversion(ColorSpaceConversion),
+}
+
+/// Converts colors between the linear color space and the sRGB encoding.
+#[derive(Debug, Clone)]
+pub struct ColorSpaceConversionNodeBehaviour {
+    conversion: ColorSpaceConversion,
+    conversion_state: enum_dropdown::State,
+}
+
+impl Default for ColorSpaceConversionNodeBehaviour {
+    fn default() -> Self {
+        Self { conversion: ColorSpaceConversion::SrgbToLinear, conversion_state: Default::default() }
+    }
+}
+
+impl NodeBehaviour for ColorSpaceConversionNodeBehaviour {
+    type Message = ColorSpaceConversionNodeMessage;
+
+    fn name(&self) -> &str {
+        "Color Space Conversion"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![NodeCommand::Configure(
+                NodeConfiguration::default()
+                    .with_input_value(Channel::new("color", ColorType::new()))
+                    .with_output_value(Channel::new("color", ColorType::new())),
+            )],
+            NodeEvent::Message(ColorSpaceConversionNodeMessage::UpdateConversion(conversion)) => {
+                self.conversion = conversion;
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            EnumDropdown::new(
+                &mut self.conversion_state,
+                &ColorSpaceConversion::VALUES[..],
+                self.conversion,
+                ColorSpaceConversionNodeMessage::UpdateConversion,
+            )
+            .theme(theme)
+            .width(Length::Fill)
+            .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.conversion).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.conversion = serde_json::from_value(settings)?;
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let conversion = behaviour.conversion;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let color = conversion.apply(read_color(context.inputs[0].as_bytes().unwrap()));
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| write_color(bytes, color))
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        hsv_to_rgb, srgb_to_linear, write_color, Channel, ColorType, NodeConfiguration, OptionRefMutExt,
+    },
+    style::{consts, Theme, Themeable},
+    widgets::{color_picker, ColorPicker},
+};
+use iced::{
+    text_input::{self, TextInput},
+    Align, Column, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone)]
+pub enum ColorPickerNodeMessage {
+    PickSaturationValue(f32, f32),
+    PickHue(f32),
+    UpdateAlpha(String),
+}
+
+/// The picked color, in the sRGB color space.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PickedColor {
+    /// In turns, from 0 to 1.
+    pub hue: f32,
+    pub saturation: f32,
+    pub value: f32,
+    pub alpha: f32,
+}
+
+impl Default for PickedColor {
+    fn default() -> Self {
+        Self { hue: 0.0, saturation: 0.0, value: 1.0, alpha: 1.0 }
+    }
+}
+
+impl PickedColor {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let in_unit_range = |component: f32| (0.0..=1.0).contains(&component);
+
+        if [self.hue, self.saturation, self.value, self.alpha]
+            .iter()
+            .all(|component| in_unit_range(*component))
+        {
+            Ok(())
+        } else {
+            Err("The components of the picked color must be between 0 and 1.")
+        }
+    }
+
+    /// The color encoded with the sRGB transfer function, as displayed by the picker.
+    pub fn srgb(&self) -> [f32; 4] {
+        let [red, green, blue] = hsv_to_rgb(self.hue, self.saturation, self.value);
+
+        [red, green, blue, self.alpha]
+    }
+
+    /// The color in the linear color space, as output by the node.
+    pub fn linear(&self) -> [f32; 4] {
+        let [red, green, blue, alpha] = self.srgb();
+
+        [srgb_to_linear(red), srgb_to_linear(green), srgb_to_linear(blue), alpha]
+    }
+
+    /// The sRGB color formatted as `#RRGGBB`.
+    fn hex(&self) -> String {
+        let [red, green, blue, _] = self.srgb();
+        let byte = |component: f32| (component * 255.0).round() as u8;
+
+        format!("#{:02X}{:02X}{:02X}", byte(red), byte(green), byte(blue))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ColorPickerUiState {
+    picker_state: color_picker::State,
+    alpha_state: text_input::State,
+    alpha_string: String,
+    /// Set when the entered alpha is invalid.
+    error: Option<&'static str>,
+}
+
+/// Outputs a color picked by its hue, saturation and value. The picked color is treated as an sRGB
+/// color, like colors picked in other applications, and output in the linear color space.
+#[derive(Debug, Clone)]
+pub struct ColorPickerNodeBehaviour {
+    color: PickedColor,
+    ui_state: ColorPickerUiState,
+}
+
+impl Default for ColorPickerNodeBehaviour {
+    fn default() -> Self {
+        Self::with_color(Default::default())
+    }
+}
+
+impl ColorPickerNodeBehaviour {
+    pub fn with_color(color: PickedColor) -> Self {
+        Self {
+            color,
+            ui_state: ColorPickerUiState { alpha_string: color.alpha.to_string(), ..Default::default() },
+        }
+    }
+}
+
+impl NodeBehaviour for ColorPickerNodeBehaviour {
+    type Message = ColorPickerNodeMessage;
+
+    fn name(&self) -> &str {
+        "Color Picker"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Value)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![NodeCommand::Configure(
+                NodeConfiguration::default().with_output_value(Channel::new("color", ColorType::new())),
+            )],
+            NodeEvent::Message(message) => {
+                match message {
+                    ColorPickerNodeMessage::PickSaturationValue(saturation, value) => {
+                        self.color.saturation = saturation;
+                        self.color.value = value;
+                    }
+                    ColorPickerNodeMessage::PickHue(hue) => self.color.hue = hue,
+                    ColorPickerNodeMessage::UpdateAlpha(string) => {
+                        match string.trim().parse::<f32>() {
+                            Ok(alpha) if (0.0..=1.0).contains(&alpha) => {
+                                self.color.alpha = alpha;
+                                self.ui_state.error = None;
+                            }
+                            _ => self.ui_state.error = Some("The alpha must be a number between 0 and 1."),
+                        }
+
+                        self.ui_state.alpha_string = string;
+                    }
+                }
+
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let hex = self.color.hex();
+        let ui_state = &mut self.ui_state;
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                ColorPicker::new(
+                    &mut ui_state.picker_state,
+                    [self.color.hue, self.color.saturation, self.color.value],
+                    ColorPickerNodeMessage::PickSaturationValue,
+                    ColorPickerNodeMessage::PickHue,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+            )
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(Text::new(hex).size(consts::TEXT_SIZE_REGULAR).width(Length::Fill))
+                    .push(Text::new("Alpha").size(consts::TEXT_SIZE_REGULAR))
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.alpha_state,
+                            "1.0",
+                            &ui_state.alpha_string,
+                            ColorPickerNodeMessage::UpdateAlpha,
+                        )
+                        .theme(theme)
+                        .width(Length::Units(56)),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+
+        if let Some(error) = ui_state.error {
+            column = column.push(Text::new(error).size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.color).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let color: PickedColor = serde_json::from_value(settings)?;
+
+        color.validate().map_err(<serde_json::Error as serde::de::Error>::custom)?;
+
+        *self = Self::with_color(color);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let color = behaviour.color.linear();
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| write_color(bytes, color))
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
@@ -1,66 +1,130 @@
 use crate::node::PrimitiveChannelValue;
+use crate::widgets::{ValueEditor, ValueEditorMessage};
 use crate::{
     graph::ApplicationContext,
     node::{
         behaviour::{
-            ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent, NodeStateClosure,
+            ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent, NodeIcon,
+            NodeStateClosure,
         },
-        Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
+        ArrayType, Channel, NodeConfiguration, OptionRefMutExt, OptionType, PrimitiveTypeEnum, TypeEnum,
     },
     style::{Theme, Themeable},
 };
 use byteorder::LittleEndian;
 use iced::{
+    button::{self, Button},
     pick_list::{self, PickList},
-    text_input::{self, TextInput},
     Element,
 };
-use iced::{Align, Length, Row};
-use std::io::Cursor;
+use iced::{Align, Column, Length, Row, Text};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::num::NonZeroUsize;
 
 #[derive(Debug, Clone)]
 pub enum ConstantNodeMessage {
     UpdateType(PrimitiveTypeEnum),
-    UpdateValue(String),
+    UpdateShape(ConstantShape),
+    AddItem,
+    RemoveItem,
+    Edit(ValueEditorMessage),
+}
+
+/// How the values of the selected primitive type are composed into the constant value.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum ConstantShape {
+    Scalar,
+    Array,
+    Option,
+}
+
+impl Display for ConstantShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstantShape::Scalar => write!(f, "Scalar"),
+            ConstantShape::Array => write!(f, "Array"),
+            ConstantShape::Option => write!(f, "Option"),
+        }
+    }
+}
+
+impl ConstantShape {
+    pub const VALUES: [ConstantShape; 3] =
+        [ConstantShape::Scalar, ConstantShape::Array, ConstantShape::Option];
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    ty: PrimitiveTypeEnum,
+    shape: ConstantShape,
+    array_length: NonZeroUsize,
+    bytes: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedValue {
+    Settings(SavedSettings),
+    /// Saved before constants could be composite.
+    Scalar(PrimitiveChannelValue),
 }
 
 #[derive(Clone, Debug)]
 pub struct ConstantNodeBehaviour {
-    value: PrimitiveChannelValue,
+    ty: PrimitiveTypeEnum,
+    shape: ConstantShape,
+    array_length: NonZeroUsize,
+    editor: ValueEditor,
     pick_list_state: pick_list::State<PrimitiveTypeEnum>,
-    text_input_state: text_input::State,
-    text_input_value: String,
-    text_input_placeholder: String,
+    shape_pick_list_state: pick_list::State<ConstantShape>,
+    button_add_state: button::State,
+    button_remove_state: button::State,
 }
 
 impl Default for ConstantNodeBehaviour {
     fn default() -> Self {
-        Self {
-            value: PrimitiveTypeEnum::F32.default_value(),
-            pick_list_state: Default::default(),
-            text_input_state: Default::default(),
-            text_input_value: Default::default(),
-            text_input_placeholder: PrimitiveTypeEnum::F32.default_value().value_to_string(),
-        }
+        Self::new(PrimitiveTypeEnum::F32.default_value())
     }
 }
 
 impl ConstantNodeBehaviour {
     pub fn new(value: impl Into<PrimitiveChannelValue>) -> Self {
-        let mut result = Self::default();
-        result.set_value(value.into());
-        result.text_input_value = result.value.value_to_string();
-        result
+        let value = value.into();
+        let mut bytes = Vec::new();
+
+        value.write::<LittleEndian>(&mut bytes).unwrap();
+
+        Self {
+            ty: value.ty(),
+            shape: ConstantShape::Scalar,
+            array_length: NonZeroUsize::new(1).unwrap(),
+            editor: ValueEditor::new(value.ty().into(), bytes).unwrap(),
+            pick_list_state: Default::default(),
+            shape_pick_list_state: Default::default(),
+            button_add_state: Default::default(),
+            button_remove_state: Default::default(),
+        }
+    }
+
+    fn value_type(ty: PrimitiveTypeEnum, shape: ConstantShape, array_length: NonZeroUsize) -> TypeEnum {
+        match shape {
+            ConstantShape::Scalar => ty.into(),
+            ConstantShape::Array => ArrayType::new_if_sized(ty, array_length.get()).unwrap().into(),
+            ConstantShape::Option => OptionType::from_enum_if_sized(ty).unwrap().into(),
+        }
     }
 
-    pub fn set_value(&mut self, value: PrimitiveChannelValue) {
-        self.value = value;
-        self.text_input_placeholder = value.ty().default_value().value_to_string();
+    /// Replaces the editor with one of the currently selected type, keeping the edited text.
+    fn retype(&mut self) {
+        let ty = Self::value_type(self.ty, self.shape, self.array_length);
+
+        self.editor = self.editor.retype(ty).unwrap();
     }
 
     pub fn get_configure_command(&self) -> NodeCommand {
         NodeCommand::Configure(
-            NodeConfiguration::default().with_output_value(Channel::new("value", self.value.ty())),
+            NodeConfiguration::default().with_output_value(Channel::new("value", self.editor.ty().clone())),
         )
     }
 }
@@ -72,6 +136,10 @@ impl NodeBehaviour for ConstantNodeBehaviour {
         "Constant"
     }
 
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Value)
+    }
+
     fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
         match event {
             NodeEvent::Update => vec![self.get_configure_command()],
@@ -80,19 +148,29 @@ impl NodeBehaviour for ConstantNodeBehaviour {
 
                 match message {
                     ConstantNodeMessage::UpdateType(ty) => {
-                        let new_value =
-                            ty.parse(&self.text_input_value).unwrap_or_else(|| ty.default_value());
-
-                        self.set_value(new_value);
+                        self.ty = ty;
+                        self.retype();
                         commands.push(self.get_configure_command());
                     }
-                    ConstantNodeMessage::UpdateValue(new_raw_value) => {
-                        self.text_input_value = new_raw_value;
-                        let ty = self.value.ty();
-                        let new_value =
-                            ty.parse(&self.text_input_value).unwrap_or_else(|| ty.default_value());
-
-                        self.set_value(new_value);
+                    ConstantNodeMessage::UpdateShape(shape) => {
+                        self.shape = shape;
+                        self.retype();
+                        commands.push(self.get_configure_command());
+                    }
+                    ConstantNodeMessage::AddItem => {
+                        self.array_length = NonZeroUsize::new(self.array_length.get() + 1).unwrap();
+                        self.retype();
+                        commands.push(self.get_configure_command());
+                    }
+                    ConstantNodeMessage::RemoveItem => {
+                        if let Some(new_value) = NonZeroUsize::new(self.array_length.get() - 1) {
+                            self.array_length = new_value;
+                            self.retype();
+                            commands.push(self.get_configure_command());
+                        }
+                    }
+                    ConstantNodeMessage::Edit(message) => {
+                        self.editor.update(message);
                     }
                 }
 
@@ -102,35 +180,79 @@ impl NodeBehaviour for ConstantNodeBehaviour {
     }
 
     fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
-        Some(
-            Row::new()
+        let mut row = Row::new()
+            .theme(theme)
+            .push(
+                PickList::new(
+                    &mut self.pick_list_state,
+                    &PrimitiveTypeEnum::VALUES[..],
+                    Some(self.ty),
+                    |new_value| ConstantNodeMessage::UpdateType(new_value),
+                )
                 .theme(theme)
-                .push(
-                    PickList::new(
-                        &mut self.pick_list_state,
-                        &PrimitiveTypeEnum::VALUES[..],
-                        Some(self.value.ty()),
-                        |new_value| ConstantNodeMessage::UpdateType(new_value),
-                    )
-                    .theme(theme)
-                    .width(Length::Units(64)),
+                .width(Length::Units(64)),
+            )
+            .push(
+                PickList::new(
+                    &mut self.shape_pick_list_state,
+                    &ConstantShape::VALUES[..],
+                    Some(self.shape),
+                    |new_value| ConstantNodeMessage::UpdateShape(new_value),
                 )
+                .theme(theme)
+                .width(Length::Fill),
+            );
+
+        if self.shape == ConstantShape::Array {
+            row = row
                 .push(
-                    TextInput::new(
-                        &mut self.text_input_state,
-                        &self.text_input_placeholder,
-                        &self.text_input_value,
-                        |new_raw_value| ConstantNodeMessage::UpdateValue(new_raw_value),
-                    )
-                    .theme(theme)
-                    .width(Length::Fill),
+                    Button::new(&mut self.button_add_state, Text::new("+"))
+                        .on_press(ConstantNodeMessage::AddItem),
                 )
-                .align_items(Align::Center)
+                .push(
+                    Button::new(&mut self.button_remove_state, Text::new("-"))
+                        .on_press(ConstantNodeMessage::RemoveItem),
+                );
+        }
+
+        Some(
+            Column::new()
+                .theme(theme)
+                .push(row.align_items(Align::Center).width(Length::Fill))
+                .push(self.editor.view(theme).map(ConstantNodeMessage::Edit))
                 .width(Length::Fill)
                 .into(),
         )
     }
 
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(SavedSettings {
+            ty: self.ty,
+            shape: self.shape,
+            array_length: self.array_length,
+            bytes: self.editor.bytes().to_vec(),
+        })
+        .unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let SavedSettings { ty, shape, array_length, bytes } = match serde_json::from_value(settings)? {
+            SavedValue::Settings(settings) => settings,
+            SavedValue::Scalar(value) => {
+                *self = Self::new(value);
+                return Ok(());
+            }
+        };
+        let editor = ValueEditor::new(Self::value_type(ty, shape, array_length), bytes)
+            .ok_or_else(|| serde::de::Error::custom("the bytes of the constant do not match its type"))?;
+
+        self.ty = ty;
+        self.shape = shape;
+        self.array_length = array_length;
+        self.editor = editor;
+        Ok(())
+    }
+
     fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
         NodeStateClosure::new(
             self,
@@ -141,16 +263,13 @@ impl NodeBehaviour for ConstantNodeBehaviour {
                 // executor closure.
 
                 // Copy the constant value from the GUI settings.
-                let value = behaviour.value;
+                let value = behaviour.editor.bytes().to_vec();
 
                 Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
                     // Executed once per graph execution.
                     context.outputs[0]
                         .replace_with_bytes(context.allocator_handle, |bytes| {
-                            let mut cursor = Cursor::new(bytes);
-
-                            value.write::<LittleEndian>(&mut cursor).unwrap();
-                            dbg!(value);
+                            bytes.copy_from_slice(&value);
                         })
                         .unwrap();
                 }) as Box<dyn ExecutorClosure<'state> + 'state>
@@ -2,7 +2,7 @@ use crate::{
     node::{
         behaviour::{
             ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
-            NodeStateClosure,
+            NodeIcon, NodeStateClosure,
         },
         Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
     },
@@ -22,6 +22,14 @@ impl NodeBehaviour for CounterNodeBehaviour {
         "Counter"
     }
 
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Value)
+    }
+
+    fn is_source(&self) -> bool {
+        true
+    }
+
     fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
         match event {
             NodeEvent::Update => vec![NodeCommand::Configure(
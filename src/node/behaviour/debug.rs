@@ -1,38 +1,89 @@
 use crate::{
+    logging::Category,
     node::{
         behaviour::{
-            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
-            NodeStateClosure,
+            ApplicationContext, ContextMenuItem, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure,
         },
-        BytesRefExt, Channel, NodeConfiguration, PrimitiveType, PrimitiveTypeEnum,
+        BytesRefExt, Channel, NodeConfiguration, PrimitiveChannelValue, PrimitiveType, PrimitiveTypeEnum,
+        ValueFormatter,
     },
-    style::{Theme, Themeable},
+    style::{consts, Theme, Themeable},
 };
 use byteorder::LittleEndian;
 use iced::{
+    button::{self, Button},
     pick_list::{self, PickList},
+    slider::{self, Slider},
     Element,
 };
-use iced::{Align, Length, Row};
+use iced::{Align, Column, Length, Row, Text};
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone)]
 pub enum DebugNodeMessage {
     UpdateType(PrimitiveTypeEnum),
+    /// Selects the recorded value at the position within the history.
+    Scrub(f64),
+    ReturnToLatest,
+    ClearHistory,
+}
+
+/// The values of the past execution passes, recorded by the executor for the UI to display.
+/// Cleared whenever the schedule is regenerated.
+#[derive(Debug, Default)]
+struct ValueHistory {
+    /// The values along with the indices of the passes they were recorded during, oldest first.
+    values: VecDeque<(u64, PrimitiveChannelValue)>,
+}
+
+impl ValueHistory {
+    fn push(&mut self, pass_index: u64, value: PrimitiveChannelValue, capacity: usize) {
+        self.values.push_back((pass_index, value));
+
+        while self.values.len() > capacity {
+            self.values.pop_front();
+        }
+    }
+
+    /// The position of the value recorded during the pass.
+    fn position(&self, pass_index: u64) -> Option<usize> {
+        self.values.iter().position(|(recorded_pass_index, _)| *recorded_pass_index == pass_index)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct DebugNodeBehaviour {
     ty: PrimitiveTypeEnum,
+    /// Shared with the executor, as the behaviour is cloned into the schedule.
+    history: Arc<Mutex<ValueHistory>>,
+    /// The index of the pass whose value is displayed, or `None` to display the latest value.
+    cursor: Option<u64>,
     pick_list_state: pick_list::State<PrimitiveTypeEnum>,
+    slider_state: slider::State,
+    button_latest_state: button::State,
 }
 
 impl Default for DebugNodeBehaviour {
     fn default() -> Self {
-        Self { ty: PrimitiveTypeEnum::F32, pick_list_state: Default::default() }
+        Self {
+            ty: PrimitiveTypeEnum::F32,
+            history: Default::default(),
+            cursor: None,
+            pick_list_state: Default::default(),
+            slider_state: Default::default(),
+            button_latest_state: Default::default(),
+        }
     }
 }
 
 impl DebugNodeBehaviour {
+    pub fn new(ty: PrimitiveTypeEnum) -> Self {
+        Self { ty, ..Default::default() }
+    }
+
     pub fn get_configure_command(&self) -> NodeCommand {
         NodeCommand::Configure(NodeConfiguration::default().with_borrow(Channel::new("value", self.ty)))
     }
@@ -45,6 +96,14 @@ impl NodeBehaviour for DebugNodeBehaviour {
         "Debug"
     }
 
+    fn is_sink(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Inspect)
+    }
+
     fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
         match event {
             NodeEvent::Update => vec![self.get_configure_command()],
@@ -56,6 +115,21 @@ impl NodeBehaviour for DebugNodeBehaviour {
                         self.ty = ty;
                         commands.push(self.get_configure_command());
                     }
+                    DebugNodeMessage::Scrub(position) => {
+                        let history = self.history.lock().unwrap();
+
+                        // Keep following the latest value when scrubbed to the end.
+                        self.cursor = if position as usize + 1 >= history.values.len() {
+                            None
+                        } else {
+                            history.values.get(position as usize).map(|(pass_index, _)| *pass_index)
+                        };
+                    }
+                    DebugNodeMessage::ReturnToLatest => self.cursor = None,
+                    DebugNodeMessage::ClearHistory => {
+                        self.history.lock().unwrap().values.clear();
+                        self.cursor = None;
+                    }
                 }
 
                 commands
@@ -64,23 +138,87 @@ impl NodeBehaviour for DebugNodeBehaviour {
     }
 
     fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
-        Some(
-            Row::new()
+        let row = Row::new()
+            .theme(theme)
+            .push(
+                PickList::new(
+                    &mut self.pick_list_state,
+                    &PrimitiveTypeEnum::VALUES[..],
+                    Some(self.ty),
+                    |new_value| DebugNodeMessage::UpdateType(new_value),
+                )
                 .theme(theme)
-                .push(
-                    PickList::new(
-                        &mut self.pick_list_state,
-                        &PrimitiveTypeEnum::VALUES[..],
-                        Some(self.ty),
-                        |new_value| DebugNodeMessage::UpdateType(new_value),
-                    )
-                    .theme(theme)
-                    .width(Length::Fill),
+                .width(Length::Fill),
+            )
+            .align_items(Align::Center)
+            .width(Length::Fill);
+        let column = Column::new().theme(theme).push(row).width(Length::Fill);
+        let history = self.history.lock().unwrap();
+        let latest_pass_index = match history.values.back() {
+            Some((latest_pass_index, _)) => *latest_pass_index,
+            None => {
+                return Some(
+                    column.push(Text::new("No values recorded yet.").size(consts::TEXT_SIZE_REGULAR)).into(),
+                );
+            }
+        };
+        let pass_index = self.cursor.unwrap_or(latest_pass_index);
+        let position = history.position(pass_index);
+        let formatter = ValueFormatter::get();
+        let label = match position.map(|position| history.values[position].1) {
+            Some(value) if pass_index == latest_pass_index => {
+                format!("Pass {} (latest): {}", pass_index, formatter.format_primitive(value))
+            }
+            Some(value) => format!(
+                "Pass {} ({} passes ago): {}",
+                pass_index,
+                latest_pass_index - pass_index,
+                formatter.format_primitive(value),
+            ),
+            None => format!("Pass {} is no longer recorded.", pass_index),
+        };
+        let mut scrub_row = Row::new()
+            .theme(theme)
+            .push(
+                Slider::new(
+                    &mut self.slider_state,
+                    0.0..=(history.values.len() - 1) as f64,
+                    position.unwrap_or(0) as f64,
+                    DebugNodeMessage::Scrub,
                 )
-                .align_items(Align::Center)
-                .width(Length::Fill)
-                .into(),
-        )
+                .step(1.0)
+                .width(Length::Fill),
+            )
+            .align_items(Align::Center)
+            .width(Length::Fill);
+
+        if self.cursor.is_some() {
+            scrub_row = scrub_row.push(
+                Button::new(&mut self.button_latest_state, Text::new("Latest"))
+                    .on_press(DebugNodeMessage::ReturnToLatest),
+            );
+        }
+
+        Some(column.push(Text::new(label).size(consts::TEXT_SIZE_REGULAR)).push(scrub_row).into())
+    }
+
+    fn context_menu_items(&self) -> Vec<ContextMenuItem<Self::Message>> {
+        let recorded = !self.history.lock().unwrap().values.is_empty();
+
+        vec![
+            ContextMenuItem::new("Return to the latest value", DebugNodeMessage::ReturnToLatest)
+                .enabled(self.cursor.is_some()),
+            ContextMenuItem::new("Clear history", DebugNodeMessage::ClearHistory).enabled(recorded),
+        ]
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.ty).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.ty = serde_json::from_value(settings)?;
+        Ok(())
     }
 
     fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
@@ -88,15 +226,33 @@ impl NodeBehaviour for DebugNodeBehaviour {
             self,
             application_context,
             (),
-            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+            move |behaviour: &Self, application_context: &ApplicationContext, _persistent: &mut ()| {
                 // Executed when the node settings have been changed to create the following
                 // executor closure.
                 let ty = behaviour.ty;
+                let history = behaviour.history.clone();
+                let capacity = application_context
+                    .config
+                    .get()
+                    .history_capacity(mem::size_of::<(u64, PrimitiveChannelValue)>());
+
+                // Values recorded by the previous schedule may be of a different type.
+                history.lock().unwrap().values.clear();
 
                 Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
                     // Executed once per graph execution.
                     let value = ty.read::<LittleEndian, _>(&context.borrows[0].as_bytes().unwrap()).unwrap();
-                    println!("Debug node: {:?}", value);
+
+                    debug!(
+                        Category::Executor,
+                        "Debug node: {}",
+                        ValueFormatter::get().format_primitive(value)
+                    );
+                    history.lock().unwrap().push(
+                        context.application_context.frame_info.pass_index,
+                        value,
+                        capacity,
+                    );
                 }) as Box<dyn ExecutorClosure<'state> + 'state>
             },
         )
@@ -0,0 +1,552 @@
+use crate::node::PrimitiveChannelValue;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ConnectedTypeInfo, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure, NodeValidationIssue,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveTypeEnum,
+    },
+    style::{consts, Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::LittleEndian;
+use iced::{
+    text_input::{self, TextInput},
+    Align, Column, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::Cursor;
+use std::time::Instant;
+
+/// The longest delay, in passes, so that the history of a node cannot grow without bounds.
+pub const MAX_DELAY_PASSES: usize = 1 << 16;
+
+/// How quickly the measured pass duration follows changes of the pass rate, from 0 to 1.
+const PASS_DURATION_SMOOTHING: f64 = 0.1;
+
+/// The types of values that can be interpolated between two passes.
+pub trait DelayItem: Copy {
+    /// Integers are rounded to the nearest value.
+    fn lerp(from: Self, to: Self, t: f64) -> Self;
+}
+
+macro_rules! impl_delay_item {
+    (integers: $($integer:ty),*; floats: $($float:ty),*$(;)?) => {
+        $(
+            impl DelayItem for $integer {
+                fn lerp(from: Self, to: Self, t: f64) -> Self {
+                    (from as f64 + (to as f64 - from as f64) * t).round() as $integer
+                }
+            }
+        )*
+
+        $(
+            impl DelayItem for $float {
+                fn lerp(from: Self, to: Self, t: f64) -> Self {
+                    from + (to - from) * t as $float
+                }
+            }
+        )*
+    };
+}
+
+impl_delay_item! {
+    integers: u8, u16, u32, u64, u128, i8, i16, i32, i64, i128;
+    floats: f32, f64;
+}
+
+fn lerp(from: PrimitiveChannelValue, to: PrimitiveChannelValue, t: f64) -> PrimitiveChannelValue {
+    use PrimitiveChannelValue::*;
+
+    // Avoids the loss of precision of the conversion of large integers to floats.
+    if t == 0.0 {
+        return from;
+    }
+
+    match (from, to) {
+        (U8(from), U8(to)) => U8(DelayItem::lerp(from, to, t)),
+        (U16(from), U16(to)) => U16(DelayItem::lerp(from, to, t)),
+        (U32(from), U32(to)) => U32(DelayItem::lerp(from, to, t)),
+        (U64(from), U64(to)) => U64(DelayItem::lerp(from, to, t)),
+        (U128(from), U128(to)) => U128(DelayItem::lerp(from, to, t)),
+        (I8(from), I8(to)) => I8(DelayItem::lerp(from, to, t)),
+        (I16(from), I16(to)) => I16(DelayItem::lerp(from, to, t)),
+        (I32(from), I32(to)) => I32(DelayItem::lerp(from, to, t)),
+        (I64(from), I64(to)) => I64(DelayItem::lerp(from, to, t)),
+        (I128(from), I128(to)) => I128(DelayItem::lerp(from, to, t)),
+        (F32(from), F32(to)) => F32(DelayItem::lerp(from, to, t)),
+        (F64(from), F64(to)) => F64(DelayItem::lerp(from, to, t)),
+        _ => panic!("Only values of the same type can be interpolated."),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DelayMode {
+    /// The length is a whole number of passes.
+    Passes,
+    /// The length is converted to passes using the measured pass duration.
+    Seconds,
+}
+
+impl DelayMode {
+    pub const VALUES: [DelayMode; 2] = [DelayMode::Passes, DelayMode::Seconds];
+}
+
+impl Display for DelayMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DelayMode::Passes => write!(f, "Passes"),
+            DelayMode::Seconds => write!(f, "Seconds"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DelaySettings {
+    pub mode: DelayMode,
+    /// In passes or in seconds, depending on the mode. Whole in the passes mode.
+    pub length: f64,
+    /// Output until enough values have been delayed, determines the type of the values.
+    pub initial: PrimitiveChannelValue,
+}
+
+impl Default for DelaySettings {
+    fn default() -> Self {
+        Self { mode: DelayMode::Passes, length: 1.0, initial: PrimitiveChannelValue::F32(0.0) }
+    }
+}
+
+impl DelaySettings {
+    pub fn ty(&self) -> PrimitiveTypeEnum {
+        self.initial.ty()
+    }
+
+    /// Describes why the settings are not valid, if they are not.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match self.mode {
+            DelayMode::Passes
+                if self.length < 0.0
+                    || self.length.fract() != 0.0
+                    || self.length > MAX_DELAY_PASSES as f64 =>
+            {
+                Err("The number of passes must be a non-negative integer of at most 65536.")
+            }
+            DelayMode::Seconds if !(self.length >= 0.0 && self.length.is_finite()) => {
+                Err("The number of seconds must be a non-negative number.")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DelayNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    UpdateMode(DelayMode),
+    UpdateLength(String),
+    UpdateInitial(String),
+}
+
+#[derive(Debug, Clone, Default)]
+struct DelayUiState {
+    ty_state: enum_dropdown::State,
+    mode_state: enum_dropdown::State,
+    length_state: text_input::State,
+    length_string: String,
+    initial_state: text_input::State,
+    initial_string: String,
+    /// Describes why the last entered parameters were rejected.
+    error: Option<&'static str>,
+}
+
+/// Outputs the input value of a number of passes ago.
+#[derive(Debug, Clone)]
+pub struct DelayNodeBehaviour {
+    settings: DelaySettings,
+    ui_state: DelayUiState,
+}
+
+impl Default for DelayNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl DelayNodeBehaviour {
+    pub fn with_settings(settings: DelaySettings) -> Self {
+        Self {
+            settings,
+            ui_state: DelayUiState {
+                length_string: settings.length.to_string(),
+                initial_string: settings.initial.value_to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_input_value(Channel::new("value", self.settings.ty()))
+                .with_output_value(Channel::new("delayed", self.settings.ty())),
+        )
+    }
+
+    /// Applies the parameters entered into the text fields, if they are valid.
+    fn update_parameters(&mut self) {
+        let ty = self.settings.ty();
+        let length = self.ui_state.length_string.parse::<f64>();
+        let initial = ty.parse(&self.ui_state.initial_string);
+
+        self.ui_state.error = match (length, initial) {
+            (Ok(length), Some(initial)) => {
+                let settings = DelaySettings { length, initial, ..self.settings };
+
+                match settings.validate() {
+                    Ok(()) => {
+                        self.settings = settings;
+                        None
+                    }
+                    Err(error) => Some(error),
+                }
+            }
+            (Err(_), _) => Some("The length must be a number."),
+            (_, None) => Some("The initial value must be a value of the selected type."),
+        };
+    }
+}
+
+impl NodeBehaviour for DelayNodeBehaviour {
+    type Message = DelayNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Delay"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Value)
+    }
+
+    fn is_source(&self) -> bool {
+        // The delay in passes depends on the time the passes take to execute.
+        self.settings.mode == DelayMode::Seconds
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use DelayNodeMessage::*;
+                let mut commands = Vec::new();
+
+                match message {
+                    UpdateType(ty) => {
+                        // Fall back to the default value, so that the type changes even if the
+                        // entered value is not valid for it.
+                        self.settings.initial =
+                            ty.parse(&self.ui_state.initial_string).unwrap_or_else(|| ty.default_value());
+                        self.update_parameters();
+                        commands.push(self.get_configure_command());
+                    }
+                    UpdateMode(mode) => {
+                        let previous_settings = self.settings;
+                        self.settings.mode = mode;
+                        self.update_parameters();
+
+                        // Keep the mode even if the entered length is not valid for it.
+                        if self.ui_state.error.is_some() {
+                            self.settings = DelaySettings { mode, length: 0.0, ..previous_settings };
+                        }
+                    }
+                    UpdateLength(string) => {
+                        self.ui_state.length_string = string;
+                        self.update_parameters();
+                    }
+                    UpdateInitial(string) => {
+                        self.ui_state.initial_string = string;
+                        self.update_parameters();
+                    }
+                }
+
+                commands
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let ui_state = &mut self.ui_state;
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        EnumDropdown::new(
+                            &mut ui_state.ty_state,
+                            &PrimitiveTypeEnum::VALUES[..],
+                            self.settings.ty(),
+                            DelayNodeMessage::UpdateType,
+                        )
+                        .theme(theme)
+                        .width(Length::Units(64)),
+                    )
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.initial_state,
+                            "Initial value",
+                            &ui_state.initial_string,
+                            DelayNodeMessage::UpdateInitial,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            )
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.length_state,
+                            "Length",
+                            &ui_state.length_string,
+                            DelayNodeMessage::UpdateLength,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        EnumDropdown::new(
+                            &mut ui_state.mode_state,
+                            &DelayMode::VALUES[..],
+                            self.settings.mode,
+                            DelayNodeMessage::UpdateMode,
+                        )
+                        .theme(theme)
+                        .width(Length::Units(80)),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+
+        if let Some(error) = ui_state.error {
+            column = column.push(Text::new(error).size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: DelaySettings = serde_json::from_value(settings)?;
+
+        settings.validate().map_err(<serde_json::Error as serde::de::Error>::custom)?;
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _configuration: &NodeConfiguration,
+        _connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        let mut issues = Vec::new();
+
+        if self.settings.length == 0.0 {
+            issues.push(NodeValidationIssue::warning("The length is zero, the values are not delayed."));
+        }
+
+        issues
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+                let ty = settings.ty();
+
+                // The delayed values of another type cannot be output anymore.
+                if persistent.history.front().map(|value| value.ty()) != Some(ty) {
+                    persistent.history.clear();
+                }
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let frame_info = &context.application_context.frame_info;
+                    let value = ty.read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap()).unwrap();
+
+                    if let Some(last_pass_start) = persistent.last_pass_start {
+                        let pass_duration = (frame_info.pass_start - last_pass_start).as_secs_f64();
+
+                        persistent.pass_duration = Some(match persistent.pass_duration {
+                            Some(previous) => previous + (pass_duration - previous) * PASS_DURATION_SMOOTHING,
+                            None => pass_duration,
+                        });
+                    }
+
+                    persistent.last_pass_start = Some(frame_info.pass_start);
+
+                    // The delay in passes, possibly between two passes in the time mode.
+                    let delay = match settings.mode {
+                        DelayMode::Passes => Some(settings.length),
+                        DelayMode::Seconds => persistent
+                            .pass_duration
+                            .or_else(|| frame_info.target_period.map(|period| period.as_secs_f64()))
+                            .filter(|pass_duration| *pass_duration > 0.0)
+                            .map(|pass_duration| settings.length / pass_duration),
+                    };
+                    let delay = delay.map(|delay| {
+                        if delay > MAX_DELAY_PASSES as f64 {
+                            context.report_error(format!(
+                                "The delay exceeds the maximum of {} passes at the current pass rate.",
+                                MAX_DELAY_PASSES,
+                            ));
+                            MAX_DELAY_PASSES as f64
+                        } else {
+                            context.clear_error();
+                            delay
+                        }
+                    });
+
+                    // Wait for the pass duration to be measured, if the delay is not known yet.
+                    let output = persistent.advance(value, delay).unwrap_or(settings.initial);
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            output.write::<LittleEndian>(&mut Cursor::new(bytes)).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated, so that changing the length keeps the delayed values.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    /// The input values of the most recent passes, the newest last.
+    history: VecDeque<PrimitiveChannelValue>,
+    last_pass_start: Option<Instant>,
+    /// The smoothed time between the starts of consecutive passes, in seconds.
+    pass_duration: Option<f64>,
+}
+
+impl Persistent {
+    /// Keeps the input value of the current pass and returns the one of `delay` passes ago,
+    /// interpolated between two passes if the delay is not whole. `None` until enough values have
+    /// been kept.
+    fn advance(&mut self, value: PrimitiveChannelValue, delay: Option<f64>) -> Option<PrimitiveChannelValue> {
+        // The current value is the newest one, delayed by zero passes.
+        self.history.push_back(value);
+
+        let delay = delay?;
+        let (newer, older) = (delay.floor() as usize, delay.ceil() as usize);
+
+        // Only the values that may still be output are kept, the oldest are dropped first.
+        while self.history.len() > older + 1 {
+            self.history.pop_front();
+        }
+
+        self.delayed(older).map(|older_value| {
+            let newer_value = self.delayed(newer).unwrap();
+
+            lerp(newer_value, older_value, delay - newer as f64)
+        })
+    }
+
+    /// The input value of `passes` passes ago, if it has been kept.
+    fn delayed(&self, passes: usize) -> Option<PrimitiveChannelValue> {
+        self.history.len().checked_sub(passes + 1).map(|index| self.history[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds a ramp of the indices of the passes, delayed by `delay`, and returns the outputs.
+    fn ramp(persistent: &mut Persistent, passes: std::ops::Range<usize>, delay: f64) -> Vec<Option<f64>> {
+        passes
+            .map(|pass| {
+                let output = persistent.advance(PrimitiveChannelValue::F64(pass as f64), Some(delay));
+
+                output.map(|output| match output {
+                    PrimitiveChannelValue::F64(output) => output,
+                    _ => unreachable!(),
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn outputs_the_ramp_once_the_buffer_is_full() {
+        for delay in vec![0, 1, 2, 7, MAX_DELAY_PASSES] {
+            let mut persistent = Persistent::default();
+            let outputs = ramp(&mut persistent, 0..delay + 3, delay as f64);
+
+            for (pass, output) in outputs.into_iter().enumerate() {
+                // The buffer holds the current value and the delayed ones.
+                assert!(persistent.history.len() <= delay + 1);
+                assert_eq!(
+                    output,
+                    pass.checked_sub(delay).map(|delayed| delayed as f64),
+                    "delay {}, pass {}",
+                    delay,
+                    pass
+                );
+            }
+
+            assert_eq!(persistent.history.len(), delay + 1);
+        }
+    }
+
+    #[test]
+    fn interpolates_fractional_delays() {
+        let mut persistent = Persistent::default();
+        let outputs = ramp(&mut persistent, 0..6, 2.25);
+
+        assert_eq!(outputs, vec![None, None, None, Some(0.75), Some(1.75), Some(2.75)]);
+    }
+
+    #[test]
+    fn changing_the_delay_keeps_the_most_recent_values() {
+        let mut persistent = Persistent::default();
+
+        ramp(&mut persistent, 0..10, 4.0);
+
+        // Shortening the delay outputs the kept values right away.
+        assert_eq!(ramp(&mut persistent, 10..12, 2.0), vec![Some(8.0), Some(9.0)]);
+
+        // Lengthening it waits until enough values have been kept again.
+        assert_eq!(ramp(&mut persistent, 12..17, 5.0), vec![None, None, Some(9.0), Some(10.0), Some(11.0)]);
+    }
+
+    #[test]
+    fn unknown_delays_keep_the_values() {
+        let mut persistent = Persistent::default();
+
+        for pass in 0..3 {
+            assert_eq!(persistent.advance(PrimitiveChannelValue::F64(pass as f64), None), None);
+        }
+
+        assert_eq!(ramp(&mut persistent, 3..4, 3.0), vec![Some(0.0)]);
+    }
+}
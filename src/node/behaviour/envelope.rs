@@ -0,0 +1,502 @@
+use crate::node::PrimitiveChannelValue;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{drag_value, DragValue},
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use iced::{Align, Column, Element, Length, Row};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::time::Instant;
+
+/// The gate is open while the input is above this value.
+const GATE_THRESHOLD: f32 = 0.5;
+
+/// The durations of the stages in seconds, and the level held while the gate is open.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnvelopeSettings {
+    pub attack: f32,
+    pub decay: f32,
+    /// From 0 to 1.
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Default for EnvelopeSettings {
+    fn default() -> Self {
+        Self { attack: 0.1, decay: 0.2, sustain: 0.7, release: 0.5 }
+    }
+}
+
+impl EnvelopeSettings {
+    fn validate(&self) -> Result<(), &'static str> {
+        let durations = [self.attack, self.decay, self.release];
+
+        if !durations.iter().all(|duration| *duration >= 0.0 && duration.is_finite()) {
+            Err("The durations must not be negative.")
+        } else if !(0.0..=1.0).contains(&self.sustain) {
+            Err("The sustain level must be between 0 and 1.")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeStage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+impl Default for EnvelopeStage {
+    fn default() -> Self {
+        EnvelopeStage::Idle
+    }
+}
+
+/// The ADSR state machine. Each stage changes the value linearly.
+#[derive(Debug, Clone, Default)]
+pub struct Envelope {
+    pub stage: EnvelopeStage,
+    pub value: f32,
+    gate: bool,
+    /// The value the release stage started from.
+    release_start: f32,
+}
+
+impl Envelope {
+    /// Advances the envelope by `delta` seconds and returns the new value.
+    pub fn advance(&mut self, settings: &EnvelopeSettings, gate: bool, mut delta: f32) -> f32 {
+        if gate && !self.gate {
+            // Start from the current value rather than from zero, to avoid discontinuities.
+            self.stage = EnvelopeStage::Attack;
+        } else if !gate && self.gate {
+            self.stage = EnvelopeStage::Release;
+            self.release_start = self.value;
+        }
+
+        self.gate = gate;
+
+        // A single step may pass through multiple stages.
+        loop {
+            match self.stage {
+                EnvelopeStage::Idle => {
+                    self.value = 0.0;
+                    break;
+                }
+                EnvelopeStage::Attack => {
+                    let remaining = (1.0 - self.value).max(0.0) * settings.attack;
+
+                    if delta >= remaining {
+                        self.value = 1.0;
+                        self.stage = EnvelopeStage::Decay;
+                        delta -= remaining;
+                    } else {
+                        self.value += delta / settings.attack;
+                        break;
+                    }
+                }
+                EnvelopeStage::Decay => {
+                    let span = 1.0 - settings.sustain;
+                    let remaining = if span > 0.0 {
+                        (self.value - settings.sustain).max(0.0) / span * settings.decay
+                    } else {
+                        0.0
+                    };
+
+                    if delta >= remaining {
+                        self.stage = EnvelopeStage::Sustain;
+                        delta -= remaining;
+                    } else {
+                        self.value -= delta * span / settings.decay;
+                        break;
+                    }
+                }
+                EnvelopeStage::Sustain => {
+                    // Follows changes of the sustain level.
+                    self.value = settings.sustain;
+                    break;
+                }
+                EnvelopeStage::Release => {
+                    let remaining = if self.release_start > 0.0 {
+                        self.value.max(0.0) / self.release_start * settings.release
+                    } else {
+                        0.0
+                    };
+
+                    if delta >= remaining {
+                        self.stage = EnvelopeStage::Idle;
+                        delta -= remaining;
+                    } else {
+                        self.value -= delta * self.release_start / settings.release;
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.value
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum EnvelopeNodeMessage {
+    UpdateAttack(f32),
+    UpdateDecay(f32),
+    UpdateSustain(f32),
+    UpdateRelease(f32),
+}
+
+#[derive(Debug, Clone, Default)]
+struct EnvelopeUiState {
+    attack_state: drag_value::State,
+    decay_state: drag_value::State,
+    sustain_state: drag_value::State,
+    release_state: drag_value::State,
+}
+
+/// Outputs a value from 0 to 1 following an attack-decay-sustain-release envelope, triggered by
+/// the gate input.
+#[derive(Debug, Clone)]
+pub struct EnvelopeNodeBehaviour {
+    settings: EnvelopeSettings,
+    ui_state: EnvelopeUiState,
+}
+
+impl Default for EnvelopeNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl EnvelopeNodeBehaviour {
+    pub fn with_settings(settings: EnvelopeSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_input_value(Channel::new("gate", PrimitiveType::<f32>::default()))
+                .with_output_value(Channel::new("envelope", PrimitiveType::<f32>::default())),
+        )
+    }
+
+    /// A field for a duration in seconds.
+    fn duration_field<'a>(
+        state: &'a mut drag_value::State,
+        label: &'a str,
+        duration: f32,
+        on_change: fn(f32) -> EnvelopeNodeMessage,
+        theme: &dyn Theme,
+    ) -> Element<'a, EnvelopeNodeMessage> {
+        DragValue::new(state, duration, move |duration| on_change(duration as f32))
+            .label(label)
+            .speed(0.01)
+            .range(0.0, f64::INFINITY)
+            .theme(theme)
+            .width(Length::Fill)
+            .into()
+    }
+}
+
+impl NodeBehaviour for EnvelopeNodeBehaviour {
+    type Message = EnvelopeNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Envelope"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn is_source(&self) -> bool {
+        // The output depends on the time elapsed between executions.
+        true
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use EnvelopeNodeMessage::*;
+
+                // The fields keep the values within the valid ranges.
+                match message {
+                    UpdateAttack(attack) => self.settings.attack = attack,
+                    UpdateDecay(decay) => self.settings.decay = decay,
+                    UpdateSustain(sustain) => self.settings.sustain = sustain,
+                    UpdateRelease(release) => self.settings.release = release,
+                }
+
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+
+        Some(
+            Column::new()
+                .theme(theme)
+                .push(
+                    Row::new()
+                        .theme(theme)
+                        .push(Self::duration_field(
+                            &mut ui_state.attack_state,
+                            "Attack",
+                            settings.attack,
+                            EnvelopeNodeMessage::UpdateAttack,
+                            theme,
+                        ))
+                        .push(Self::duration_field(
+                            &mut ui_state.decay_state,
+                            "Decay",
+                            settings.decay,
+                            EnvelopeNodeMessage::UpdateDecay,
+                            theme,
+                        ))
+                        .align_items(Align::Center)
+                        .width(Length::Fill),
+                )
+                .push(
+                    Row::new()
+                        .theme(theme)
+                        .push(
+                            DragValue::new(&mut ui_state.sustain_state, settings.sustain, |sustain| {
+                                EnvelopeNodeMessage::UpdateSustain(sustain as f32)
+                            })
+                            .label("Sustain")
+                            .speed(0.005)
+                            .range(0.0, 1.0)
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .push(Self::duration_field(
+                            &mut ui_state.release_state,
+                            "Release",
+                            settings.release,
+                            EnvelopeNodeMessage::UpdateRelease,
+                            theme,
+                        ))
+                        .align_items(Align::Center)
+                        .width(Length::Fill),
+                )
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: EnvelopeSettings = serde_json::from_value(settings)?;
+
+        settings.validate().map_err(<serde_json::Error as serde::de::Error>::custom)?;
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let gate = match PrimitiveTypeEnum::F32
+                        .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                        .unwrap()
+                    {
+                        PrimitiveChannelValue::F32(gate) => gate > GATE_THRESHOLD,
+                        _ => unreachable!(),
+                    };
+                    let now = context.application_context.frame_info.pass_start;
+                    let delta = persistent
+                        .last_execution
+                        .map(|last_execution| (now - last_execution).as_secs_f32())
+                        .unwrap_or(0.0);
+                    let value = persistent.envelope.advance(&settings, gate, delta);
+
+                    persistent.last_execution = Some(now);
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            let mut cursor = Cursor::new(bytes);
+
+                            cursor.write_f32::<LittleEndian>(value).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated, so that the envelope continues where it was.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    envelope: Envelope,
+    last_execution: Option<Instant>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: EnvelopeSettings =
+        EnvelopeSettings { attack: 1.0, decay: 0.5, sustain: 0.5, release: 2.0 };
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-5, "expected {}, got {}", expected, actual);
+    }
+
+    /// Advances the envelope in steps of `delta` seconds, returning the values.
+    fn advance_by(envelope: &mut Envelope, gate: bool, delta: f32, steps: usize) -> Vec<f32> {
+        (0..steps).map(|_| envelope.advance(&SETTINGS, gate, delta)).collect()
+    }
+
+    #[test]
+    fn stays_idle_while_the_gate_is_closed() {
+        let mut envelope = Envelope::default();
+
+        assert_eq!(advance_by(&mut envelope, false, 0.1, 3), vec![0.0; 3]);
+        assert_eq!(envelope.stage, EnvelopeStage::Idle);
+    }
+
+    #[test]
+    fn passes_through_attack_and_decay_to_sustain() {
+        let mut envelope = Envelope::default();
+
+        assert_close(envelope.advance(&SETTINGS, true, 0.5), 0.5);
+        assert_eq!(envelope.stage, EnvelopeStage::Attack);
+        assert_close(envelope.advance(&SETTINGS, true, 0.5), 1.0);
+        assert_eq!(envelope.stage, EnvelopeStage::Decay);
+        assert_close(envelope.advance(&SETTINGS, true, 0.25), 0.75);
+        assert_eq!(envelope.stage, EnvelopeStage::Decay);
+        assert_close(envelope.advance(&SETTINGS, true, 0.25), 0.5);
+        assert_close(envelope.advance(&SETTINGS, true, 10.0), 0.5);
+        assert_eq!(envelope.stage, EnvelopeStage::Sustain);
+    }
+
+    /// The time left over by a stage is spent in the following ones.
+    #[test]
+    fn single_step_spans_multiple_stages() {
+        let mut envelope = Envelope::default();
+
+        assert_close(envelope.advance(&SETTINGS, true, 1.25), 0.75);
+        assert_eq!(envelope.stage, EnvelopeStage::Decay);
+
+        let mut envelope = Envelope::default();
+
+        assert_close(envelope.advance(&SETTINGS, true, 100.0), 0.5);
+        assert_eq!(envelope.stage, EnvelopeStage::Sustain);
+    }
+
+    #[test]
+    fn releases_from_sustain_to_idle() {
+        let mut envelope = Envelope::default();
+
+        envelope.advance(&SETTINGS, true, 10.0);
+
+        assert_close(envelope.advance(&SETTINGS, false, 1.0), 0.25);
+        assert_eq!(envelope.stage, EnvelopeStage::Release);
+        assert_close(envelope.advance(&SETTINGS, false, 1.0), 0.0);
+        assert_eq!(envelope.stage, EnvelopeStage::Idle);
+    }
+
+    /// Releasing during the attack starts from the current value and still takes the whole
+    /// release duration.
+    #[test]
+    fn release_during_attack_starts_from_current_value() {
+        let mut envelope = Envelope::default();
+
+        envelope.advance(&SETTINGS, true, 0.4);
+
+        assert_close(envelope.advance(&SETTINGS, false, 1.0), 0.2);
+        assert_close(envelope.advance(&SETTINGS, false, 0.5), 0.1);
+        assert_close(envelope.advance(&SETTINGS, false, 0.6), 0.0);
+        assert_eq!(envelope.stage, EnvelopeStage::Idle);
+    }
+
+    /// Reopening the gate during the release continues the attack from the current value,
+    /// without jumping back to zero.
+    #[test]
+    fn retrigger_during_release_continues_from_current_value() {
+        let mut envelope = Envelope::default();
+
+        envelope.advance(&SETTINGS, true, 10.0);
+        envelope.advance(&SETTINGS, false, 1.0);
+
+        assert_close(envelope.advance(&SETTINGS, true, 0.25), 0.5);
+        assert_eq!(envelope.stage, EnvelopeStage::Attack);
+        assert_close(envelope.advance(&SETTINGS, true, 0.5), 1.0);
+        assert_eq!(envelope.stage, EnvelopeStage::Decay);
+    }
+
+    #[test]
+    fn zero_durations_jump_to_the_next_stage() {
+        let settings = EnvelopeSettings { attack: 0.0, decay: 0.0, sustain: 0.3, release: 0.0 };
+        let mut envelope = Envelope::default();
+
+        assert_close(envelope.advance(&settings, true, 0.0), 0.3);
+        assert_eq!(envelope.stage, EnvelopeStage::Sustain);
+        assert_close(envelope.advance(&settings, false, 0.0), 0.0);
+        assert_eq!(envelope.stage, EnvelopeStage::Idle);
+    }
+
+    /// Without a decay span, the envelope holds the peak.
+    #[test]
+    fn full_sustain_skips_the_decay() {
+        let settings = EnvelopeSettings { sustain: 1.0, ..SETTINGS };
+        let mut envelope = Envelope::default();
+
+        assert_close(envelope.advance(&settings, true, 1.0), 1.0);
+        assert_close(envelope.advance(&settings, true, 0.0), 1.0);
+        assert_eq!(envelope.stage, EnvelopeStage::Sustain);
+    }
+
+    #[test]
+    fn sustain_follows_changes_of_the_level() {
+        let mut envelope = Envelope::default();
+
+        envelope.advance(&SETTINGS, true, 10.0);
+
+        let settings = EnvelopeSettings { sustain: 0.8, ..SETTINGS };
+
+        assert_close(envelope.advance(&settings, true, 0.1), 0.8);
+        assert_eq!(envelope.stage, EnvelopeStage::Sustain);
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        assert!(SETTINGS.validate().is_ok());
+        assert!(EnvelopeSettings { attack: -1.0, ..SETTINGS }.validate().is_err());
+        assert!(EnvelopeSettings { release: f32::NAN, ..SETTINGS }.validate().is_err());
+        assert!(EnvelopeSettings { sustain: 1.5, ..SETTINGS }.validate().is_err());
+    }
+}
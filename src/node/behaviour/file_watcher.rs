@@ -0,0 +1,230 @@
+use crate::{
+    graph::background::{BackgroundHandle, BackgroundTask},
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType,
+    },
+    style::{Theme, Themeable},
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use iced::{
+    text_input::{self, TextInput},
+    Element, Length,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Cursor;
+use std::time::{Duration, SystemTime};
+
+/// How often the modification time of the file is checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// The maximum number of events waiting to be received by the node.
+const EVENT_CAPACITY: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileWatcherSettings {
+    pub path: String,
+}
+
+#[derive(Debug)]
+enum FileWatchEvent {
+    Modified,
+    Error(String),
+}
+
+/// Sends an event whenever the modification time of the file changes, or the file appears after
+/// not having been readable, until cancelled. Read errors are only sent once per failure.
+fn watch(handle: BackgroundHandle<FileWatchEvent>, path: String) {
+    let mut last_modified: Option<SystemTime> = None;
+    let mut failing = false;
+
+    loop {
+        let event = match fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => {
+                let changed =
+                    failing || last_modified.map_or(false, |last_modified| last_modified != modified);
+
+                last_modified = Some(modified);
+                failing = false;
+
+                if changed {
+                    Some(FileWatchEvent::Modified)
+                } else {
+                    None
+                }
+            }
+            Err(error) if !failing => {
+                failing = true;
+                Some(FileWatchEvent::Error(error.to_string()))
+            }
+            Err(_) => None,
+        };
+
+        if let Some(event) = event {
+            if !handle.send(event) {
+                return;
+            }
+        }
+
+        if !handle.sleep(POLL_INTERVAL) {
+            return;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum FileWatcherNodeMessage {
+    UpdatePath(String),
+}
+
+/// Signals when a file on disk is modified, for graphs that reload shaders, images or data as they
+/// are edited. The file is polled by a background task, so that the executor never waits for the
+/// file system.
+#[derive(Debug, Clone, Default)]
+pub struct FileWatcherNodeBehaviour {
+    settings: FileWatcherSettings,
+    path_state: text_input::State,
+}
+
+impl FileWatcherNodeBehaviour {
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_output_value(Channel::new("changed", PrimitiveType::<u8>::default()).with_description(
+                    "1 if the file has been modified since the last execution, 0 otherwise",
+                ))
+                .with_output_value(
+                    Channel::new("changes", PrimitiveType::<u32>::default())
+                        .with_description("The number of modifications so far"),
+                ),
+        )
+    }
+}
+
+impl NodeBehaviour for FileWatcherNodeBehaviour {
+    type Message = FileWatcherNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "File Watcher"
+    }
+
+    fn is_source(&self) -> bool {
+        // The output depends on the contents of the file system.
+        true
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Value)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(FileWatcherNodeMessage::UpdatePath(path)) => {
+                self.settings.path = path;
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            TextInput::new(
+                &mut self.path_state,
+                "Path",
+                &self.settings.path,
+                FileWatcherNodeMessage::UpdatePath,
+            )
+            .theme(theme)
+            .width(Length::Fill)
+            .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.settings = serde_json::from_value(settings)?;
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let path = behaviour.settings.path.clone();
+                // Replacing the closure drops the task watching the previous path.
+                let mut task: Option<BackgroundTask<FileWatchEvent>> = None;
+                let mut spawn_failed = false;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    if task.is_none() && !spawn_failed && !path.is_empty() {
+                        let watched_path = path.clone();
+
+                        context.clear_error();
+
+                        match context.spawn_background(
+                            format!("File Watcher {}", path),
+                            EVENT_CAPACITY,
+                            |handle| watch(handle, watched_path),
+                        ) {
+                            Ok(spawned) => task = Some(spawned),
+                            Err(error) => {
+                                spawn_failed = true;
+                                context.report_error(format!("Could not watch the file: {}", error));
+                            }
+                        }
+                    }
+
+                    let mut changed = false;
+
+                    if let Some(task) = task.as_mut() {
+                        for event in task.try_iter() {
+                            match event {
+                                FileWatchEvent::Modified => {
+                                    changed = true;
+                                    persistent.changes = persistent.changes.wrapping_add(1);
+                                    context.clear_error();
+                                }
+                                FileWatchEvent::Error(error) => {
+                                    context.report_error(format!("Could not read the file: {}", error));
+                                }
+                            }
+                        }
+                    }
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| bytes[0] = changed as u8)
+                        .unwrap();
+                    context.outputs[1]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes).write_u32::<LittleEndian>(persistent.changes).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    changes: u32,
+}
@@ -0,0 +1,448 @@
+use crate::node::prelude::*;
+use crate::node::{ListDescriptor, ListType, OwnedRefMut, PrimitiveFormat, Unique, ValueFormatter};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        Channel, NodeConfiguration, PrimitiveTypeEnum,
+    },
+    style::{consts, Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::LittleEndian;
+use iced::{
+    text_input::{self, TextInput},
+    Align, Column, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::mem;
+
+/// The maximum number of input channels a template may use, so that a placeholder such as
+/// `{100000}` does not create as many channels.
+pub const MAX_TEMPLATE_ARGUMENTS: usize = 64;
+
+/// A part of a [`Template`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateSegment {
+    Literal(String),
+    /// Replaced by the value of the input channel at the index.
+    Placeholder {
+        argument: usize,
+        format: PrimitiveFormat,
+    },
+}
+
+/// A template with placeholders, such as `FPS: {0:.2}`, replaced by the values of the input
+/// channels.
+///
+/// Placeholders are either positional, `{0}` or `{}` for the argument following the previous
+/// positional one, or named, `{name}`, and may be followed by a format spec, see
+/// [`PrimitiveFormat::parse`]. Literal braces are written as `{{` and `}}`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Template {
+    pub segments: Vec<TemplateSegment>,
+    /// The titles of the input channels, one per argument. The positional arguments come first,
+    /// followed by the named ones, in the order of their first occurrence.
+    pub arguments: Vec<String>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        enum Segment {
+            Literal(String),
+            Positional(usize, PrimitiveFormat),
+            Named(String, PrimitiveFormat),
+        }
+
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut next_positional = 0;
+        let mut positional_count = 0;
+        let mut named = Vec::<String>::new();
+        let mut chars = source.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' | '}' if chars.peek() == Some(&c) => {
+                    chars.next();
+                    literal.push(c);
+                }
+                '}' => return Err("Unmatched `}`, write `}}` for a literal brace.".to_string()),
+                '{' => {
+                    let mut placeholder = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => {
+                                return Err(format!("The placeholder `{{{}` is not closed.", placeholder));
+                            }
+                            Some(c) => placeholder.push(c),
+                        }
+                    }
+
+                    let (argument, spec) = match placeholder.find(':') {
+                        Some(index) => (&placeholder[..index], &placeholder[index + 1..]),
+                        None => (&placeholder[..], ""),
+                    };
+                    let argument = argument.trim();
+                    let format = PrimitiveFormat::parse(spec.trim())
+                        .map_err(|error| format!("In `{{{}}}`: {}", placeholder, error))?;
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(mem::take(&mut literal)));
+                    }
+
+                    let index = if argument.is_empty() {
+                        next_positional
+                    } else if let Ok(index) = argument.parse::<usize>() {
+                        index
+                    } else {
+                        if !named.iter().any(|name| name == argument) {
+                            named.push(argument.to_string());
+                        }
+
+                        segments.push(Segment::Named(argument.to_string(), format));
+                        continue;
+                    };
+
+                    if index >= MAX_TEMPLATE_ARGUMENTS {
+                        return Err(format!("At most {} arguments may be used.", MAX_TEMPLATE_ARGUMENTS));
+                    }
+
+                    next_positional = index + 1;
+                    positional_count = std::cmp::max(positional_count, index + 1);
+                    segments.push(Segment::Positional(index, format));
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        if positional_count + named.len() > MAX_TEMPLATE_ARGUMENTS {
+            return Err(format!("At most {} arguments may be used.", MAX_TEMPLATE_ARGUMENTS));
+        }
+
+        let segments = segments
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => TemplateSegment::Literal(literal),
+                Segment::Positional(argument, format) => TemplateSegment::Placeholder { argument, format },
+                Segment::Named(name, format) => TemplateSegment::Placeholder {
+                    argument: positional_count + named.iter().position(|named| *named == name).unwrap(),
+                    format,
+                },
+            })
+            .collect();
+        let arguments = (0..positional_count).map(|index| format!("value #{}", index)).chain(named).collect();
+
+        Ok(Self { segments, arguments })
+    }
+
+    /// The formats the argument is formatted with.
+    pub fn formats(&self, argument: usize) -> impl Iterator<Item = PrimitiveFormat> + '_ {
+        self.segments.iter().filter_map(move |segment| match segment {
+            TemplateSegment::Placeholder { argument: placeholder_argument, format }
+                if *placeholder_argument == argument =>
+            {
+                Some(*format)
+            }
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormatNodeSettings {
+    pub template: String,
+    /// The types of the arguments whose type has been selected, by their titles.
+    #[serde(default)]
+    pub types: BTreeMap<String, PrimitiveTypeEnum>,
+}
+
+impl Default for FormatNodeSettings {
+    fn default() -> Self {
+        Self { template: "FPS: {0:.2}".to_string(), types: Default::default() }
+    }
+}
+
+impl FormatNodeSettings {
+    /// Parses the template and checks that the formats of the arguments support their types.
+    pub fn validate(&self) -> Result<Template, String> {
+        let template = Template::parse(&self.template)?;
+
+        for (argument, title) in template.arguments.iter().enumerate() {
+            let ty = self.argument_type(&template, argument);
+
+            if template.formats(argument).any(|format| !format.supports(ty)) {
+                return Err(format!("The format of `{}` does not support values of type {}.", title, ty));
+            }
+        }
+
+        Ok(template)
+    }
+
+    /// The selected type of the argument, or the type its formats suggest: integers for radix
+    /// formats, floats otherwise.
+    pub fn argument_type(&self, template: &Template, argument: usize) -> PrimitiveTypeEnum {
+        if let Some(ty) = self.types.get(&template.arguments[argument]) {
+            return *ty;
+        }
+
+        if template.formats(argument).any(|format| !format.supports(PrimitiveTypeEnum::F32)) {
+            PrimitiveTypeEnum::U32
+        } else {
+            PrimitiveTypeEnum::F32
+        }
+    }
+
+    /// The types of the input channels.
+    pub fn argument_types(&self, template: &Template) -> Vec<PrimitiveTypeEnum> {
+        (0..template.arguments.len()).map(|argument| self.argument_type(template, argument)).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum FormatNodeMessage {
+    UpdateTemplate(String),
+    UpdateType(usize, PrimitiveTypeEnum),
+}
+
+#[derive(Debug, Clone, Default)]
+struct FormatUiState {
+    template_state: text_input::State,
+    template_string: String,
+    type_states: Vec<enum_dropdown::State>,
+    /// Describes why the last entered template or type was rejected.
+    error: Option<String>,
+}
+
+/// Combines the values of the input channels into text, according to a [`Template`]. The text is
+/// written as a list of UTF-8 bytes.
+#[derive(Debug, Clone)]
+pub struct FormatNodeBehaviour {
+    settings: FormatNodeSettings,
+    /// Parsed from the template of the settings.
+    template: Template,
+    ui_state: FormatUiState,
+}
+
+impl Default for FormatNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default()).unwrap()
+    }
+}
+
+impl FormatNodeBehaviour {
+    pub fn with_settings(settings: FormatNodeSettings) -> Result<Self, String> {
+        let template = settings.validate()?;
+
+        Ok(Self {
+            ui_state: FormatUiState { template_string: settings.template.clone(), ..Default::default() },
+            settings,
+            template,
+        })
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(NodeConfiguration {
+            input_channels_by_value: self
+                .template
+                .arguments
+                .iter()
+                .zip(self.settings.argument_types(&self.template))
+                .map(|(title, ty)| Channel::new(title.clone(), ty))
+                .collect(),
+            output_channels_by_value: vec![Channel::new(
+                "text",
+                Unique::new(ListType::new_if_sized(PrimitiveTypeEnum::U8).unwrap()),
+            )
+            .with_description("The formatted text, encoded as UTF-8")],
+            ..Default::default()
+        })
+    }
+
+    /// Applies the settings, if they are valid, returning whether the input channels have changed.
+    fn update_settings(&mut self, mut settings: FormatNodeSettings) -> bool {
+        match settings.validate() {
+            Ok(template) => {
+                // Forget the types of arguments which are no longer used.
+                settings.types.retain(|title, _| template.arguments.contains(title));
+
+                let channels_changed = template.arguments != self.template.arguments
+                    || settings.argument_types(&template) != self.settings.argument_types(&self.template);
+
+                self.settings = settings;
+                self.template = template;
+                self.ui_state.error = None;
+                channels_changed
+            }
+            Err(error) => {
+                self.ui_state.error = Some(error);
+                false
+            }
+        }
+    }
+}
+
+impl NodeBehaviour for FormatNodeBehaviour {
+    type Message = FormatNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Format"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use FormatNodeMessage::*;
+                let mut settings = self.settings.clone();
+
+                match message {
+                    UpdateTemplate(string) => {
+                        self.ui_state.template_string = string.clone();
+                        settings.template = string;
+                    }
+                    UpdateType(argument, ty) => {
+                        settings.types.insert(self.template.arguments[argument].clone(), ty);
+                    }
+                }
+
+                if self.update_settings(settings) {
+                    vec![self.get_configure_command()]
+                } else {
+                    vec![]
+                }
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let types = self.settings.argument_types(&self.template);
+        let ui_state = &mut self.ui_state;
+        let mut column = Column::new().theme(theme).push(
+            TextInput::new(
+                &mut ui_state.template_state,
+                "Template",
+                &ui_state.template_string,
+                FormatNodeMessage::UpdateTemplate,
+            )
+            .theme(theme)
+            .width(Length::Fill),
+        );
+
+        ui_state.type_states.resize_with(self.template.arguments.len(), Default::default);
+
+        for (argument, ((title, ty), type_state)) in
+            self.template.arguments.iter().zip(types).zip(ui_state.type_states.iter_mut()).enumerate()
+        {
+            column = column.push(
+                Row::new()
+                    .theme(theme)
+                    .push(Text::new(title.as_str()).size(consts::TEXT_SIZE_REGULAR).width(Length::Fill))
+                    .push(
+                        EnumDropdown::new(type_state, &PrimitiveTypeEnum::VALUES[..], ty, move |ty| {
+                            FormatNodeMessage::UpdateType(argument, ty)
+                        })
+                        .theme(theme)
+                        .width(Length::Units(64)),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+        }
+
+        if let Some(error) = ui_state.error.as_ref() {
+            column = column.push(Text::new(error.as_str()).size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: FormatNodeSettings = serde_json::from_value(settings)?;
+
+        *self = Self::with_settings(settings).map_err(<serde_json::Error as serde::de::Error>::custom)?;
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let template = behaviour.template.clone();
+                let types = behaviour.settings.argument_types(&template);
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let formatter = ValueFormatter::get();
+                    let mut text = String::new();
+
+                    for segment in &template.segments {
+                        match segment {
+                            TemplateSegment::Literal(literal) => text.push_str(literal),
+                            TemplateSegment::Placeholder { argument, format } => {
+                                let value = types[*argument]
+                                    .read::<LittleEndian, _>(&context.inputs[*argument].as_bytes().unwrap())
+                                    .unwrap();
+
+                                text.push_str(&formatter.format_primitive_as(value, *format));
+                            }
+                        }
+                    }
+
+                    // Keep the text of the previous execution, unless it has been taken by another
+                    // node or it has changed since.
+                    if persistent.formatted.as_ref() == Some(&text) && context.outputs[0].is_some() {
+                        return;
+                    }
+
+                    let mut list: OwnedRefMut<Unique<ListType>> =
+                        context.allocator_handle.allocate_object::<ListType>(
+                            ListDescriptor::new_if_sized(PrimitiveTypeEnum::U8).unwrap(),
+                        );
+
+                    {
+                        let mut list = list.deref_mut();
+
+                        for byte in text.bytes() {
+                            list.push_item_bytes_with(|bytes| bytes[0] = byte).unwrap();
+                        }
+                    }
+
+                    context.outputs[0].replace(list.upcast(), context.allocator_handle).unwrap();
+                    persistent.formatted = Some(text);
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    /// The text in the output.
+    formatted: Option<String>,
+}
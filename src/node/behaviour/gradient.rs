@@ -0,0 +1,458 @@
+use crate::graph::{OwnedTexture, TextureAllocation};
+use crate::node::{
+    write_color, ColorType, OwnedRefMut, TextureDescriptor, TextureFormat, TextureType, Unique,
+};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveChannelValue, PrimitiveType,
+        PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{enum_dropdown, gradient_bar, EnumDropdown, GradientBar},
+};
+use byteorder::LittleEndian;
+use iced::widget::checkbox::Checkbox;
+use iced::{
+    button::{self, Button},
+    text_input::{self, TextInput},
+    Align, Color, Column, Element, Length, Row, Text,
+};
+use iced_wgpu::wgpu;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use vek::Vec2;
+
+const BAKED_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const BAKED_WIDTH: u32 = 256;
+/// The number of colors the gradient is sampled at to be displayed in the node.
+const PREVIEW_SAMPLES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GradientInterpolation {
+    /// Interpolates the components linearly between the neighbouring stops.
+    Linear,
+    /// Holds the color of each stop until the next one.
+    Stepped,
+}
+
+impl GradientInterpolation {
+    pub const VALUES: [GradientInterpolation; 2] =
+        [GradientInterpolation::Linear, GradientInterpolation::Stepped];
+}
+
+impl Display for GradientInterpolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GradientInterpolation::Linear => write!(f, "Linear RGB"),
+            GradientInterpolation::Stepped => write!(f, "Stepped"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColorStop {
+    /// From 0 to 1.
+    pub position: f32,
+    /// RGBA, from 0 to 1.
+    pub color: [f32; 4],
+}
+
+/// The stops are kept in the order they were added in, so that their indices do not change while
+/// they are being dragged past each other.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Gradient {
+    pub stops: Vec<ColorStop>,
+    pub interpolation: GradientInterpolation,
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self {
+            stops: vec![
+                ColorStop { position: 0.0, color: [0.0, 0.0, 0.0, 1.0] },
+                ColorStop { position: 1.0, color: [1.0, 1.0, 1.0, 1.0] },
+            ],
+            interpolation: GradientInterpolation::Linear,
+        }
+    }
+}
+
+impl Gradient {
+    fn validate(&self) -> Result<(), &'static str> {
+        let in_unit_range = |value: f32| (0.0..=1.0).contains(&value);
+
+        if self.stops.is_empty() {
+            Err("The gradient must have at least one stop.")
+        } else if !self.stops.iter().all(|stop| {
+            in_unit_range(stop.position) && stop.color.iter().all(|component| in_unit_range(*component))
+        }) {
+            Err("The positions and the color components of the stops must be between 0 and 1.")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The same gradient with the stops sorted by their positions, as required by
+    /// [`Gradient::evaluate`].
+    pub fn sorted(&self) -> Gradient {
+        let mut sorted = self.clone();
+
+        // The positions are never NaN, see `Gradient::validate`.
+        sorted.stops.sort_by(|a, b| a.position.partial_cmp(&b.position).unwrap());
+        sorted
+    }
+
+    /// The color at the position, which is clamped to the range from 0 to 1. The stops must be
+    /// sorted by their positions.
+    pub fn evaluate(&self, position: f32) -> [f32; 4] {
+        // NaN is clamped to 0.
+        let position = position.max(0.0).min(1.0);
+        let next_index =
+            self.stops.iter().position(|stop| stop.position > position).unwrap_or(self.stops.len());
+
+        match (next_index.checked_sub(1).map(|index| &self.stops[index]), self.stops.get(next_index)) {
+            (Some(previous), Some(next)) if self.interpolation == GradientInterpolation::Linear => {
+                // The next stop is past the position, so the distance is never zero.
+                let t = (position - previous.position) / (next.position - previous.position);
+                let mut color = [0.0; 4];
+
+                for (index, component) in color.iter_mut().enumerate() {
+                    *component = previous.color[index] + (next.color[index] - previous.color[index]) * t;
+                }
+
+                color
+            }
+            (Some(previous), _) => previous.color,
+            (None, Some(next)) => next.color,
+            (None, None) => [0.0; 4],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GradientNodeMessage {
+    UpdateInterpolation(GradientInterpolation),
+    UpdateBake(bool),
+    SelectStop(usize),
+    MoveStop(usize, f32),
+    AddStop(f32),
+    RemoveStop,
+    /// Updates a component of the color of the selected stop.
+    UpdateComponent(usize, String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    gradient: Gradient,
+    bake: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct GradientUiState {
+    interpolation_state: enum_dropdown::State,
+    bar_state: gradient_bar::State,
+    /// The index of the stop whose color is being edited.
+    selected: Option<usize>,
+    component_states: [text_input::State; 4],
+    component_strings: [String; 4],
+    remove_state: button::State,
+}
+
+/// Maps a value from 0 to 1 to a color along a gradient defined by color stops. Optionally also
+/// outputs the whole gradient as a texture, to be sampled on the GPU.
+#[derive(Debug, Clone, Default)]
+pub struct GradientNodeBehaviour {
+    gradient: Gradient,
+    /// Whether the gradient is also output as a texture.
+    bake: bool,
+    ui_state: GradientUiState,
+}
+
+impl GradientNodeBehaviour {
+    /// The default gradient, also output as a texture.
+    pub fn baked() -> Self {
+        Self { bake: true, ..Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let mut configuration = NodeConfiguration::default()
+            .with_input_value(Channel::new("position", PrimitiveType::<f32>::default()))
+            .with_output_value(Channel::new("color", ColorType::new()));
+
+        if self.bake {
+            configuration = configuration.with_output_value(Channel::new(
+                "texture",
+                Unique::new(TextureType::owned(TextureFormat::from_wgpu(BAKED_FORMAT))),
+            ));
+        }
+
+        NodeCommand::Configure(configuration)
+    }
+
+    fn select(&mut self, index: Option<usize>) {
+        self.ui_state.selected = index;
+
+        if let Some(stop) = index.and_then(|index| self.gradient.stops.get(index)) {
+            for (string, component) in self.ui_state.component_strings.iter_mut().zip(&stop.color) {
+                *string = component.to_string();
+            }
+        }
+    }
+}
+
+impl NodeBehaviour for GradientNodeBehaviour {
+    type Message = GradientNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Gradient"
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use GradientNodeMessage::*;
+
+                match message {
+                    UpdateInterpolation(interpolation) => self.gradient.interpolation = interpolation,
+                    UpdateBake(bake) => {
+                        self.bake = bake;
+                        return vec![self.get_configure_command()];
+                    }
+                    SelectStop(index) => self.select(Some(index)),
+                    MoveStop(index, position) => {
+                        if let Some(stop) = self.gradient.stops.get_mut(index) {
+                            stop.position = position;
+                        }
+                    }
+                    AddStop(position) => {
+                        let color = self.gradient.sorted().evaluate(position);
+
+                        self.gradient.stops.push(ColorStop { position, color });
+                        self.select(Some(self.gradient.stops.len() - 1));
+                    }
+                    RemoveStop => {
+                        // The gradient must keep at least one stop.
+                        if let Some(index) = self.ui_state.selected.filter(|_| self.gradient.stops.len() > 1)
+                        {
+                            self.gradient.stops.remove(index);
+                            self.select(None);
+                        }
+                    }
+                    UpdateComponent(component_index, string) => {
+                        if let Ok(value) = string.parse::<f32>() {
+                            if let Some(stop) =
+                                self.ui_state.selected.and_then(|index| self.gradient.stops.get_mut(index))
+                            {
+                                if (0.0..=1.0).contains(&value) {
+                                    stop.color[component_index] = value;
+                                }
+                            }
+                        }
+
+                        self.ui_state.component_strings[component_index] = string;
+                    }
+                }
+
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let sorted = self.gradient.sorted();
+        let to_color = |[r, g, b, a]: [f32; 4]| Color::from_rgba(r, g, b, a);
+        let samples = (0..PREVIEW_SAMPLES)
+            .map(|index| to_color(sorted.evaluate((index as f32 + 0.5) / PREVIEW_SAMPLES as f32)))
+            .collect();
+        let handles = self
+            .gradient
+            .stops
+            .iter()
+            .map(|stop| gradient_bar::Handle { position: stop.position, color: to_color(stop.color) })
+            .collect();
+        let ui_state = &mut self.ui_state;
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        EnumDropdown::new(
+                            &mut ui_state.interpolation_state,
+                            &GradientInterpolation::VALUES[..],
+                            self.gradient.interpolation,
+                            GradientNodeMessage::UpdateInterpolation,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .push(Checkbox::new(self.bake, "Texture", GradientNodeMessage::UpdateBake).theme(theme))
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            )
+            .push(
+                GradientBar::new(
+                    &mut ui_state.bar_state,
+                    samples,
+                    handles,
+                    ui_state.selected,
+                    GradientNodeMessage::SelectStop,
+                    GradientNodeMessage::MoveStop,
+                    GradientNodeMessage::AddStop,
+                )
+                .theme(theme),
+            );
+
+        if ui_state.selected.is_some() {
+            let mut row = Row::new().theme(theme);
+
+            for (component_index, ((state, string), placeholder)) in ui_state
+                .component_states
+                .iter_mut()
+                .zip(&ui_state.component_strings)
+                .zip(&["R", "G", "B", "A"])
+                .enumerate()
+            {
+                row = row.push(
+                    TextInput::new(state, placeholder, string, move |string| {
+                        GradientNodeMessage::UpdateComponent(component_index, string)
+                    })
+                    .theme(theme)
+                    .width(Length::Fill),
+                );
+            }
+
+            let mut remove = Button::new(&mut ui_state.remove_state, Text::new("-"));
+
+            if self.gradient.stops.len() > 1 {
+                remove = remove.on_press(GradientNodeMessage::RemoveStop);
+            }
+
+            column = column.push(row.push(remove).align_items(Align::Center).width(Length::Fill));
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(SavedSettings { gradient: self.gradient.clone(), bake: self.bake }).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let SavedSettings { gradient, bake } = serde_json::from_value(settings)?;
+
+        gradient.validate().map_err(<serde_json::Error as serde::de::Error>::custom)?;
+
+        self.gradient = gradient;
+        self.bake = bake;
+        self.select(None);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let gradient = behaviour.gradient.sorted();
+                let bake = behaviour.bake;
+
+                // The texture of the previous gradient is baked again.
+                persistent.baked = None;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let position = match PrimitiveTypeEnum::F32
+                        .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                        .unwrap()
+                    {
+                        PrimitiveChannelValue::F32(position) => position,
+                        _ => unreachable!(),
+                    };
+                    let color = gradient.evaluate(position);
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| write_color(bytes, color))
+                        .unwrap();
+
+                    if !bake {
+                        context.clear_error();
+                        return;
+                    }
+
+                    let renderer = if let Some(renderer) = context.renderer() { renderer } else { return };
+
+                    if persistent.baked.is_none() {
+                        let baked =
+                            match OwnedTexture::try_new(renderer, Vec2::new(BAKED_WIDTH, 1), BAKED_FORMAT) {
+                                Ok(baked) => baked,
+                                Err(error) => {
+                                    context.report_error(error);
+                                    return;
+                                }
+                            };
+                        let data = (0..BAKED_WIDTH)
+                            .flat_map(|x| {
+                                let color = gradient.evaluate(x as f32 / (BAKED_WIDTH - 1) as f32);
+
+                                (0..4).map(move |index| (color[index] * 255.0).round() as u8)
+                            })
+                            .collect::<Vec<_>>();
+
+                        baked.write(&renderer.queue, &data);
+                        persistent.baked = Some(baked);
+                    }
+
+                    let baked = persistent.baked.as_ref().unwrap();
+                    // The output is consumed by the downstream nodes, so it cannot be the cached
+                    // texture.
+                    let output = match OwnedTexture::try_new(renderer, baked.size, BAKED_FORMAT) {
+                        Ok(output) => output,
+                        Err(error) => {
+                            context.report_error(error);
+                            return;
+                        }
+                    };
+                    let mut encoder = context.create_command_encoder();
+
+                    baked.copy_to(&output, &mut encoder);
+                    renderer.queue.submit(Some(encoder.finish()));
+
+                    let output: OwnedRefMut<Unique<TextureType>> =
+                        context.allocator_handle.allocate_object::<TextureType>(TextureDescriptor::new(
+                            TextureAllocation::Texture(output),
+                        ));
+
+                    context.outputs[1].replace(output.upcast(), context.allocator_handle).unwrap();
+                    context.clear_error();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Persistent {
+    /// The gradient baked into a texture, if it is output as one.
+    baked: Option<OwnedTexture>,
+}
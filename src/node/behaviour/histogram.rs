@@ -0,0 +1,763 @@
+use crate::node::prelude::*;
+use crate::node::{ListDescriptor, ListType, OwnedRefMut, PrimitiveChannelValue, Unique};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ConnectedTypeInfo, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure, NodeValidationIssue, Reducible,
+        },
+        Channel, NodeConfiguration, PrimitiveType, PrimitiveTypeEnum,
+    },
+    style::{consts, Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::LittleEndian;
+use iced::canvas::{self, Canvas, Cursor, Fill, FillRule, Frame, Geometry, Path};
+use iced::{
+    text_input::{self, TextInput},
+    Align, Checkbox, Color, Column, Element, Length, Point, Rectangle, Row, Size, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::Cursor as IoCursor;
+use std::sync::{Arc, Mutex};
+
+const MAX_BIN_COUNT: usize = 1024;
+const MAX_WINDOW: usize = 1_000_000;
+/// The height of the canvas the bins are drawn onto.
+const BARS_HEIGHT: u16 = 64;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum HistogramMode {
+    /// Counts the items of a list, whenever the list changes.
+    List,
+    /// Counts the values of the most recent passes.
+    Stream,
+}
+
+impl HistogramMode {
+    pub const VALUES: [HistogramMode; 2] = [HistogramMode::List, HistogramMode::Stream];
+}
+
+impl Display for HistogramMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistogramMode::List => write!(f, "List"),
+            HistogramMode::Stream => write!(f, "Stream"),
+        }
+    }
+}
+
+/// The minimum, the maximum and the mean of the counted values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl HistogramStats {
+    /// `None` if there are no values.
+    fn new(values: impl Iterator<Item = f64>) -> Option<Self> {
+        let mut count = 0_usize;
+        let mut sum = 0.0;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+
+        for value in values {
+            count += 1;
+            sum += value;
+            min = min.min(value);
+            max = max.max(value);
+        }
+
+        if count == 0 {
+            None
+        } else {
+            Some(Self { min, max, mean: sum / count as f64 })
+        }
+    }
+}
+
+/// The distribution of values over bins of equal width.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// The number of values within each bin, from the lowest bin to the highest one.
+    pub counts: Vec<u64>,
+    /// The lower edge of the first bin and the upper edge of the last bin.
+    pub range: [f64; 2],
+    /// Of all values, including the ones outside of the range.
+    pub stats: Option<HistogramStats>,
+}
+
+impl Histogram {
+    /// Counts the values within the range, or within the range of the values if `range` is `None`.
+    /// A value on the edge between two bins is counted in the upper one, the upper edge of the last
+    /// bin is included in it. Non-finite values are ignored. If the range has no width, such as the
+    /// range of a constant input, all values are counted in a single bin.
+    pub fn new(values: impl Iterator<Item = f64> + Clone, bin_count: usize, range: Option<[f64; 2]>) -> Self {
+        let values = values.filter(|value| value.is_finite());
+        let stats = HistogramStats::new(values.clone());
+        let range = range.or_else(|| stats.map(|stats| [stats.min, stats.max])).unwrap_or([0.0, 0.0]);
+        let bin_count = if range[1] > range[0] { bin_count.max(1) } else { 1 };
+        let mut histogram = Self { counts: vec![0; bin_count], range, stats };
+
+        for value in values {
+            if let Some(bin) = histogram.bin(value) {
+                histogram.counts[bin] += 1;
+            }
+        }
+
+        histogram
+    }
+
+    /// The lower edge of the bin at the index, or the upper edge of the last bin, if the index
+    /// equals the number of bins.
+    pub fn edge(&self, index: usize) -> f64 {
+        let [min, max] = self.range;
+
+        min + (max - min) * index as f64 / self.counts.len() as f64
+    }
+
+    /// The index of the bin the value is counted in, `None` if it is outside of the range.
+    fn bin(&self, value: f64) -> Option<usize> {
+        let [min, max] = self.range;
+        let bin_count = self.counts.len();
+
+        if !(min..=max).contains(&value) {
+            return None;
+        }
+
+        if bin_count == 1 {
+            return Some(0);
+        }
+
+        // The estimate may be off by one due to rounding, the edges decide.
+        let mut bin = (((value - min) / (max - min) * bin_count as f64) as usize).min(bin_count - 1);
+
+        while bin > 0 && value < self.edge(bin) {
+            bin -= 1;
+        }
+
+        while bin + 1 < bin_count && value >= self.edge(bin + 1) {
+            bin += 1;
+        }
+
+        Some(bin)
+    }
+}
+
+/// Reads the items stored one after another in `bytes` as `f64`s.
+fn read_values<T: Reducible>(bytes: &[u8]) -> impl Iterator<Item = f64> + Clone + '_ {
+    bytes.chunks_exact(std::mem::size_of::<T>()).map(|chunk| T::from_le_slice(chunk).to_f64())
+}
+
+fn read_values_dyn(ty: PrimitiveTypeEnum, bytes: &[u8]) -> Vec<f64> {
+    use PrimitiveTypeEnum::*;
+    match ty {
+        U8 => read_values::<u8>(bytes).collect(),
+        U16 => read_values::<u16>(bytes).collect(),
+        U32 => read_values::<u32>(bytes).collect(),
+        U64 => read_values::<u64>(bytes).collect(),
+        U128 => read_values::<u128>(bytes).collect(),
+        I8 => read_values::<i8>(bytes).collect(),
+        I16 => read_values::<i16>(bytes).collect(),
+        I32 => read_values::<i32>(bytes).collect(),
+        I64 => read_values::<i64>(bytes).collect(),
+        I128 => read_values::<i128>(bytes).collect(),
+        F32 => read_values::<f32>(bytes).collect(),
+        F64 => read_values::<f64>(bytes).collect(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HistogramSettings {
+    pub mode: HistogramMode,
+    /// The type of the items of the list, or of the streamed values.
+    pub ty: PrimitiveTypeEnum,
+    /// Always positive.
+    pub bin_count: usize,
+    /// The range spanned by the bins, `None` to span the range of the values.
+    pub range: Option<[f64; 2]>,
+    /// The number of passes whose values are counted in the [`HistogramMode::Stream`] mode, always
+    /// positive.
+    pub window: usize,
+}
+
+impl Default for HistogramSettings {
+    fn default() -> Self {
+        Self {
+            mode: HistogramMode::List,
+            ty: PrimitiveTypeEnum::F32,
+            bin_count: 16,
+            range: None,
+            window: 256,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum HistogramNodeMessage {
+    UpdateMode(HistogramMode),
+    UpdateType(PrimitiveTypeEnum),
+    UpdateBinCount(String),
+    UpdateWindow(String),
+    UpdateAutoRange(bool),
+    UpdateMin(String),
+    UpdateMax(String),
+}
+
+#[derive(Debug, Clone, Default)]
+struct HistogramUiState {
+    mode_state: enum_dropdown::State,
+    ty_state: enum_dropdown::State,
+    bin_count_state: text_input::State,
+    bin_count_string: String,
+    window_state: text_input::State,
+    window_string: String,
+    min_state: text_input::State,
+    min_string: String,
+    max_state: text_input::State,
+    max_string: String,
+    /// Describes why the last entered value was rejected.
+    error: Option<&'static str>,
+}
+
+/// Sorts the items of a list, or the values of the most recent passes, into bins.
+#[derive(Debug, Clone)]
+pub struct HistogramNodeBehaviour {
+    settings: HistogramSettings,
+    /// The most recently computed histogram, shared with the executor, as the behaviour is cloned
+    /// into the schedule.
+    snapshot: Arc<Mutex<Option<Histogram>>>,
+    ui_state: HistogramUiState,
+}
+
+impl Default for HistogramNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl HistogramNodeBehaviour {
+    pub fn with_settings(settings: HistogramSettings) -> Self {
+        let [min, max] = settings.range.unwrap_or([0.0, 1.0]);
+
+        Self {
+            settings,
+            snapshot: Default::default(),
+            ui_state: HistogramUiState {
+                bin_count_string: settings.bin_count.to_string(),
+                window_string: settings.window.to_string(),
+                min_string: min.to_string(),
+                max_string: max.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let HistogramSettings { mode, ty, .. } = self.settings;
+        let configuration = match mode {
+            HistogramMode::List => NodeConfiguration::default()
+                .with_borrow(Channel::new("list", ListType::new_if_sized(ty).unwrap())),
+            HistogramMode::Stream => NodeConfiguration::default().with_input_value(Channel::new("value", ty)),
+        };
+
+        NodeCommand::Configure(
+            configuration
+                .with_output_value(Channel::new(
+                    "counts",
+                    Unique::new(ListType::new_if_sized(PrimitiveTypeEnum::U64).unwrap()),
+                ))
+                .with_output_value(Channel::new("min", PrimitiveType::<f64>::default()))
+                .with_output_value(Channel::new("max", PrimitiveType::<f64>::default()))
+                .with_output_value(Channel::new("mean", PrimitiveType::<f64>::default())),
+        )
+    }
+
+    /// Applies the range entered into the text fields, if it is valid.
+    fn update_range(&mut self) {
+        let min = self.ui_state.min_string.parse::<f64>();
+        let max = self.ui_state.max_string.parse::<f64>();
+
+        self.ui_state.error = match (min, max) {
+            (Ok(min), Ok(max)) if min < max && min.is_finite() && max.is_finite() => {
+                self.settings.range = Some([min, max]);
+                None
+            }
+            (Ok(_), Ok(_)) => Some("The minimum must be less than the maximum."),
+            _ => Some("The range must consist of numbers."),
+        };
+    }
+}
+
+impl NodeBehaviour for HistogramNodeBehaviour {
+    type Message = HistogramNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Histogram"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Inspect)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use HistogramNodeMessage::*;
+                let mut commands = Vec::new();
+
+                match message {
+                    UpdateMode(mode) => {
+                        self.settings.mode = mode;
+                        commands.push(self.get_configure_command());
+                    }
+                    UpdateType(ty) => {
+                        self.settings.ty = ty;
+                        commands.push(self.get_configure_command());
+                    }
+                    UpdateBinCount(string) => {
+                        self.ui_state.error = match string.parse::<usize>() {
+                            Ok(bin_count) if bin_count > 0 && bin_count <= MAX_BIN_COUNT => {
+                                self.settings.bin_count = bin_count;
+                                None
+                            }
+                            Ok(_) => Some("The bin count must be between 1 and 1024."),
+                            Err(_) => Some("The bin count must be a whole number."),
+                        };
+                        self.ui_state.bin_count_string = string;
+                    }
+                    UpdateWindow(string) => {
+                        self.ui_state.error = match string.parse::<usize>() {
+                            Ok(window) if window > 0 && window <= MAX_WINDOW => {
+                                self.settings.window = window;
+                                None
+                            }
+                            Ok(_) => Some("The window must span between 1 and 1000000 passes."),
+                            Err(_) => Some("The window must be a whole number of passes."),
+                        };
+                        self.ui_state.window_string = string;
+                    }
+                    UpdateAutoRange(auto_range) => {
+                        if auto_range {
+                            self.settings.range = None;
+                            self.ui_state.error = None;
+                        } else {
+                            self.update_range();
+                        }
+                    }
+                    UpdateMin(string) => {
+                        self.ui_state.min_string = string;
+                        self.update_range();
+                    }
+                    UpdateMax(string) => {
+                        self.ui_state.max_string = string;
+                        self.update_range();
+                    }
+                }
+
+                commands
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        EnumDropdown::new(
+                            &mut ui_state.mode_state,
+                            &HistogramMode::VALUES[..],
+                            settings.mode,
+                            HistogramNodeMessage::UpdateMode,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        EnumDropdown::new(
+                            &mut ui_state.ty_state,
+                            &PrimitiveTypeEnum::VALUES[..],
+                            settings.ty,
+                            HistogramNodeMessage::UpdateType,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            )
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(Text::new("Bins").size(consts::TEXT_SIZE_REGULAR))
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.bin_count_state,
+                            "Bins",
+                            &ui_state.bin_count_string,
+                            HistogramNodeMessage::UpdateBinCount,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+
+        if settings.mode == HistogramMode::Stream {
+            column = column.push(
+                Row::new()
+                    .theme(theme)
+                    .push(Text::new("Passes").size(consts::TEXT_SIZE_REGULAR))
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.window_state,
+                            "Passes",
+                            &ui_state.window_string,
+                            HistogramNodeMessage::UpdateWindow,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+        }
+
+        column = column.push(
+            Checkbox::new(settings.range.is_none(), "Auto range", HistogramNodeMessage::UpdateAutoRange)
+                .theme(theme),
+        );
+
+        if settings.range.is_some() {
+            column = column.push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.min_state,
+                            "Min",
+                            &ui_state.min_string,
+                            HistogramNodeMessage::UpdateMin,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.max_state,
+                            "Max",
+                            &ui_state.max_string,
+                            HistogramNodeMessage::UpdateMax,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+        }
+
+        if let Some(error) = ui_state.error {
+            column = column.push(Text::new(error).size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        column = match self.snapshot.lock().unwrap().clone() {
+            Some(histogram) => {
+                let range = format!("{} – {}", histogram.range[0], histogram.range[1]);
+                let stats = histogram.stats.map(|stats| {
+                    format!("Min {:.3} · Max {:.3} · Mean {:.3}", stats.min, stats.max, stats.mean)
+                });
+
+                column
+                    .push(
+                        Canvas::new(HistogramBars {
+                            counts: histogram.counts,
+                            color: theme.status_colors().highlight,
+                        })
+                        .width(Length::Fill)
+                        .height(Length::Units(BARS_HEIGHT)),
+                    )
+                    .push(Text::new(range).size(consts::TEXT_SIZE_REGULAR))
+                    .push(
+                        Text::new(stats.unwrap_or_else(|| "No values counted yet.".to_string()))
+                            .size(consts::TEXT_SIZE_REGULAR),
+                    )
+            }
+            None => column.push(Text::new("No values counted yet.").size(consts::TEXT_SIZE_REGULAR)),
+        };
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: HistogramSettings = serde_json::from_value(settings)?;
+
+        if settings.bin_count == 0 || settings.bin_count > MAX_BIN_COUNT {
+            return Err(<serde_json::Error as serde::de::Error>::custom("invalid bin count"));
+        }
+
+        if settings.window == 0 || settings.window > MAX_WINDOW {
+            return Err(<serde_json::Error as serde::de::Error>::custom("invalid window"));
+        }
+
+        if settings.range.map_or(false, |[min, max]| !(min < max)) {
+            return Err(<serde_json::Error as serde::de::Error>::custom(
+                "the minimum must be less than the maximum",
+            ));
+        }
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _configuration: &NodeConfiguration,
+        connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        connected.reinterpretation_warnings().collect()
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+                let snapshot = behaviour.snapshot.clone();
+
+                // The accumulated values were of another type.
+                if persistent.samples_ty != Some(settings.ty) {
+                    persistent.samples.clear();
+                    persistent.samples_ty = Some(settings.ty);
+                }
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let histogram = match settings.mode {
+                        HistogramMode::List => {
+                            let list = context.borrows[0].clone().downcast_ref::<ListType>().unwrap();
+                            // Primitive items always have a safe binary representation.
+                            let bytes = list.items_bytes_if_safe().unwrap();
+                            let unchanged = persistent.computed.as_ref().map_or(
+                                false,
+                                |(computed_settings, computed_bytes)| {
+                                    *computed_settings == settings && computed_bytes[..] == bytes[..]
+                                },
+                            );
+
+                            // Keep the outputs of the previous execution, unless they have been taken
+                            // by another node.
+                            if unchanged && context.outputs.iter().all(|output| output.is_some()) {
+                                return;
+                            }
+
+                            persistent.computed = Some((settings, bytes.to_vec()));
+
+                            let values = read_values_dyn(settings.ty, &bytes);
+
+                            Histogram::new(values.iter().copied(), settings.bin_count, settings.range)
+                        }
+                        HistogramMode::Stream => {
+                            let bytes = context.inputs[0].as_bytes().unwrap();
+
+                            for value in read_values_dyn(settings.ty, &bytes) {
+                                persistent.push_sample(value, settings.window);
+                            }
+
+                            Histogram::new(
+                                persistent.samples.iter().copied(),
+                                settings.bin_count,
+                                settings.range,
+                            )
+                        }
+                    };
+
+                    let stats = match histogram.stats {
+                        Some(stats) => {
+                            context.clear_error();
+                            stats
+                        }
+                        None => {
+                            context.report_error(
+                                "There are no finite values to count, zeros are output instead.".to_string(),
+                            );
+                            HistogramStats { min: 0.0, max: 0.0, mean: 0.0 }
+                        }
+                    };
+                    let mut counts: OwnedRefMut<Unique<ListType>> =
+                        context.allocator_handle.allocate_object::<ListType>(
+                            ListDescriptor::new_if_sized(PrimitiveTypeEnum::U64).unwrap(),
+                        );
+
+                    {
+                        let mut counts = counts.deref_mut();
+
+                        for count in &histogram.counts {
+                            counts
+                                .push_item_bytes_with(|bytes| bytes.copy_from_slice(&count.to_le_bytes()))
+                                .unwrap();
+                        }
+                    }
+
+                    context.outputs[0].replace(counts.upcast(), context.allocator_handle).unwrap();
+
+                    for (output, value) in
+                        context.outputs[1..].iter_mut().zip(&[stats.min, stats.max, stats.mean])
+                    {
+                        output
+                            .replace_with_bytes(context.allocator_handle, |bytes| {
+                                PrimitiveChannelValue::F64(*value)
+                                    .write::<LittleEndian>(&mut IoCursor::new(bytes))
+                                    .unwrap();
+                            })
+                            .unwrap();
+                    }
+
+                    *snapshot.lock().unwrap() = Some(histogram);
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated, so that changing the parameters keeps the accumulated
+/// values.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    /// The values of the most recent passes in the [`HistogramMode::Stream`] mode, the newest last.
+    samples: VecDeque<f64>,
+    samples_ty: Option<PrimitiveTypeEnum>,
+    /// The settings and the items of the list the outputs were last computed from in the
+    /// [`HistogramMode::List`] mode.
+    computed: Option<(HistogramSettings, Vec<u8>)>,
+}
+
+impl Persistent {
+    /// Accumulates the value, evicting the oldest values beyond the window.
+    fn push_sample(&mut self, value: f64, window: usize) {
+        self.samples.push_back(value);
+
+        while self.samples.len() > window {
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// Draws the counts of the bins as bars, scaled to the highest count.
+struct HistogramBars {
+    counts: Vec<u64>,
+    color: Color,
+}
+
+impl canvas::Program<HistogramNodeMessage> for HistogramBars {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        /// The horizontal space between the bars.
+        const GAP: f32 = 1.0;
+
+        let mut frame = Frame::new(bounds.size());
+        let max_count = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let bar_width = bounds.width / self.counts.len().max(1) as f32;
+
+        for (index, count) in self.counts.iter().enumerate() {
+            let height = bounds.height * *count as f32 / max_count as f32;
+            let path = Path::rectangle(
+                Point::new(index as f32 * bar_width, bounds.height - height),
+                Size::new((bar_width - GAP).max(1.0), height),
+            );
+
+            frame.fill(&path, Fill { color: self.color, rule: FillRule::NonZero });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The greatest value less than the positive value.
+    fn below(value: f64) -> f64 {
+        f64::from_bits(value.to_bits() - 1)
+    }
+
+    #[test]
+    fn values_on_edges_are_counted_in_the_upper_bin() {
+        for bin_count in 1..=64 {
+            let histogram = Histogram::new(std::iter::empty(), bin_count, Some([0.1, 0.7]));
+
+            for bin in 0..bin_count {
+                let edge = histogram.edge(bin);
+
+                assert_eq!(histogram.bin(edge), Some(bin), "{} bins, edge {}", bin_count, bin);
+
+                if bin > 0 {
+                    assert_eq!(histogram.bin(below(edge)), Some(bin - 1), "{} bins, edge {}", bin_count, bin);
+                }
+            }
+
+            // The upper edge of the last bin is included in it.
+            assert_eq!(histogram.bin(0.7), Some(bin_count - 1));
+            assert_eq!(histogram.bin(below(0.1)), None);
+            assert_eq!(histogram.bin(0.7 + f64::EPSILON), None);
+        }
+    }
+
+    #[test]
+    fn values_outside_of_the_range_are_only_included_in_the_stats() {
+        let values = vec![-1.0, 0.0, 0.25, 0.5, 0.75, 1.0, 2.0, f64::NAN, f64::INFINITY];
+        let histogram = Histogram::new(values.into_iter(), 4, Some([0.0, 1.0]));
+
+        assert_eq!(histogram.counts, vec![1, 1, 1, 2]);
+        assert_eq!(histogram.stats, Some(HistogramStats { min: -1.0, max: 2.0, mean: 0.5 }));
+    }
+
+    #[test]
+    fn constant_values_are_counted_in_a_single_bin() {
+        let histogram = Histogram::new(std::iter::repeat(3.0).take(5), 16, None);
+
+        assert_eq!(histogram.counts, vec![5]);
+        assert_eq!(histogram.range, [3.0, 3.0]);
+    }
+
+    #[test]
+    fn stream_window_evicts_the_oldest_values() {
+        let mut persistent = Persistent::default();
+
+        for value in 0..10 {
+            persistent.push_sample(value as f64, 4);
+            assert_eq!(persistent.samples.len(), (value + 1).min(4));
+        }
+
+        assert_eq!(persistent.samples, vec![6.0, 7.0, 8.0, 9.0]);
+
+        // Shrinking the window evicts the values exceeding it at once.
+        persistent.push_sample(10.0, 2);
+        assert_eq!(persistent.samples, vec![9.0, 10.0]);
+
+        persistent.push_sample(11.0, 3);
+        assert_eq!(persistent.samples, vec![9.0, 10.0, 11.0]);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveChannelValue, PrimitiveType,
+        PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::LittleEndian;
+use iced::{Element, Length};
+
+/// The types of the values the node checks.
+const FLOAT_TYPES: [PrimitiveTypeEnum; 2] = [PrimitiveTypeEnum::F32, PrimitiveTypeEnum::F64];
+
+#[derive(Debug, Clone)]
+pub enum IsFiniteNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+}
+
+/// Checks whether a floating-point value is neither NaN nor infinite, for graphs that handle
+/// non-finite values explicitly rather than by sanitizing numerics.
+#[derive(Debug, Clone)]
+pub struct IsFiniteNodeBehaviour {
+    ty: PrimitiveTypeEnum,
+    type_state: enum_dropdown::State,
+}
+
+impl Default for IsFiniteNodeBehaviour {
+    fn default() -> Self {
+        Self { ty: PrimitiveTypeEnum::F32, type_state: Default::default() }
+    }
+}
+
+impl IsFiniteNodeBehaviour {
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default().with_borrow(Channel::new("value", self.ty)).with_output_value(
+                Channel::new("finite", PrimitiveType::<u8>::default())
+                    .with_description("1 if the value is neither NaN nor infinite, 0 otherwise"),
+            ),
+        )
+    }
+}
+
+impl NodeBehaviour for IsFiniteNodeBehaviour {
+    type Message = IsFiniteNodeMessage;
+
+    fn name(&self) -> &str {
+        "Is Finite"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(IsFiniteNodeMessage::UpdateType(ty)) => {
+                self.ty = ty;
+                vec![self.get_configure_command()]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            EnumDropdown::new(
+                &mut self.type_state,
+                &FLOAT_TYPES[..],
+                self.ty,
+                IsFiniteNodeMessage::UpdateType,
+            )
+            .theme(theme)
+            .width(Length::Fill)
+            .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.ty).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.ty = serde_json::from_value(settings)?;
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let ty = behaviour.ty;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let value = ty.read::<LittleEndian, _>(&context.borrows[0].as_bytes().unwrap()).unwrap();
+                    let finite = match value {
+                        PrimitiveChannelValue::F32(value) => value.is_finite(),
+                        PrimitiveChannelValue::F64(value) => value.is_finite(),
+                        _ => true,
+                    };
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| bytes[0] = finite as u8)
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
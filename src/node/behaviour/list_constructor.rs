@@ -4,7 +4,7 @@ use crate::{
     node::{
         behaviour::{
             ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
-            NodeStateClosure,
+            NodeIcon, NodeStateClosure,
         },
         Channel, NodeConfiguration, PrimitiveType, PrimitiveTypeEnum,
     },
@@ -16,6 +16,7 @@ use iced::{
     Element, Text,
 };
 use iced::{Align, Length, Row};
+use serde::{Deserialize, Serialize};
 use std::io::{Cursor, Write};
 use std::num::NonZeroUsize;
 
@@ -26,6 +27,12 @@ pub enum ListConstructorNodeMessage {
     RemoveChannel,
 }
 
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    ty: PrimitiveTypeEnum,
+    channel_count: NonZeroUsize,
+}
+
 #[derive(Debug, Clone)]
 pub struct ListConstructorNodeBehaviour {
     ty: PrimitiveTypeEnum,
@@ -70,6 +77,10 @@ impl NodeBehaviour for ListConstructorNodeBehaviour {
         "ListConstructor"
     }
 
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Collection)
+    }
+
     fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
         match event {
             NodeEvent::Update => vec![self.get_configure_command()],
@@ -129,6 +140,17 @@ impl NodeBehaviour for ListConstructorNodeBehaviour {
         )
     }
 
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(SavedSettings { ty: self.ty, channel_count: self.channel_count }).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let SavedSettings { ty, channel_count } = serde_json::from_value(settings)?;
+        self.ty = ty;
+        self.channel_count = channel_count;
+        Ok(())
+    }
+
     fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
         NodeStateClosure::new(
             self,
@@ -0,0 +1,456 @@
+use crate::node::prelude::*;
+use crate::node::{ListType, PrimitiveChannelValue, PrimitiveKind};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ConnectedTypeInfo, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure, NodeValidationIssue,
+        },
+        Channel, NodeConfiguration, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::LittleEndian;
+use iced::{Align, Element, Length, Row};
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+use std::fmt::Display;
+use std::io::Cursor;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum ReduceOp {
+    /// Wraps around on overflow of integers.
+    Sum,
+    /// Wraps around on overflow of integers.
+    Product,
+    Min,
+    Max,
+    /// Computed in double precision, integer lists yield an `f64`.
+    Mean,
+    /// Yields a `u64`.
+    Count,
+}
+
+impl Display for ReduceOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use ReduceOp::*;
+        match self {
+            Sum => write!(f, "Sum"),
+            Product => write!(f, "Product"),
+            Min => write!(f, "Min"),
+            Max => write!(f, "Max"),
+            Mean => write!(f, "Mean"),
+            Count => write!(f, "Count"),
+        }
+    }
+}
+
+/// The numeric types the items of lists can be reduced as.
+pub trait Reducible: Copy + PartialOrd + Into<PrimitiveChannelValue> {
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Reads the item from exactly as many bytes as the size of the type.
+    fn from_le_slice(bytes: &[u8]) -> Self;
+    fn add(self, other: Self) -> Self;
+    fn mul(self, other: Self) -> Self;
+    fn to_f64(self) -> f64;
+    /// Converts the mean computed in double precision to the output type of the mean.
+    fn mean_to_value(mean: f64) -> PrimitiveChannelValue {
+        PrimitiveChannelValue::F64(mean)
+    }
+}
+
+macro_rules! impl_reducible {
+    (integers: $($integer:ty),*; floats: $($float:ty),*$(;)?) => {
+        $(
+            impl Reducible for $integer {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn from_le_slice(bytes: &[u8]) -> Self {
+                    Self::from_le_bytes(bytes.try_into().unwrap())
+                }
+
+                fn add(self, other: Self) -> Self {
+                    self.wrapping_add(other)
+                }
+
+                fn mul(self, other: Self) -> Self {
+                    self.wrapping_mul(other)
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+
+        $(
+            impl Reducible for $float {
+                const ZERO: Self = 0.0;
+                const ONE: Self = 1.0;
+
+                fn from_le_slice(bytes: &[u8]) -> Self {
+                    Self::from_le_bytes(bytes.try_into().unwrap())
+                }
+
+                fn add(self, other: Self) -> Self {
+                    self + other
+                }
+
+                fn mul(self, other: Self) -> Self {
+                    self * other
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn mean_to_value(mean: f64) -> PrimitiveChannelValue {
+                    (mean as $float).into()
+                }
+            }
+        )*
+    };
+}
+
+impl_reducible! {
+    integers: u8, u16, u32, u64, u128, i8, i16, i32, i64, i128;
+    floats: f32, f64;
+}
+
+impl ReduceOp {
+    pub const VALUES: [ReduceOp; 6] =
+        [ReduceOp::Sum, ReduceOp::Product, ReduceOp::Min, ReduceOp::Max, ReduceOp::Mean, ReduceOp::Count];
+
+    /// The type of the result of reducing items of the type `ty`.
+    pub fn output_type(self, ty: PrimitiveTypeEnum) -> PrimitiveTypeEnum {
+        match self {
+            ReduceOp::Count => PrimitiveTypeEnum::U64,
+            ReduceOp::Mean if ty.kind() != PrimitiveKind::Float => PrimitiveTypeEnum::F64,
+            _ => ty,
+        }
+    }
+
+    /// Reduces the items stored one after another in `bytes`, or returns `None` if there are no
+    /// items and the operation has no identity.
+    pub fn reduce<T: Reducible>(self, bytes: &[u8]) -> Option<PrimitiveChannelValue> {
+        let len = bytes.len() / std::mem::size_of::<T>();
+        let mut items = bytes.chunks_exact(std::mem::size_of::<T>()).map(T::from_le_slice);
+
+        Some(match self {
+            ReduceOp::Sum => items.fold(T::ZERO, T::add).into(),
+            ReduceOp::Product => items.fold(T::ONE, T::mul).into(),
+            ReduceOp::Min => {
+                let first = items.next()?;
+                items.fold(first, |min, item| if item < min { item } else { min }).into()
+            }
+            ReduceOp::Max => {
+                let first = items.next()?;
+                items.fold(first, |max, item| if item > max { item } else { max }).into()
+            }
+            ReduceOp::Mean if len == 0 => return None,
+            ReduceOp::Mean => T::mean_to_value(items.map(T::to_f64).sum::<f64>() / len as f64),
+            ReduceOp::Count => PrimitiveChannelValue::U64(len as u64),
+        })
+    }
+
+    pub fn reduce_dyn(self, ty: PrimitiveTypeEnum, bytes: &[u8]) -> Option<PrimitiveChannelValue> {
+        use PrimitiveTypeEnum::*;
+        match ty {
+            U8 => self.reduce::<u8>(bytes),
+            U16 => self.reduce::<u16>(bytes),
+            U32 => self.reduce::<u32>(bytes),
+            U64 => self.reduce::<u64>(bytes),
+            U128 => self.reduce::<u128>(bytes),
+            I8 => self.reduce::<i8>(bytes),
+            I16 => self.reduce::<i16>(bytes),
+            I32 => self.reduce::<i32>(bytes),
+            I64 => self.reduce::<i64>(bytes),
+            I128 => self.reduce::<i128>(bytes),
+            F32 => self.reduce::<f32>(bytes),
+            F64 => self.reduce::<f64>(bytes),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ListReduceNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    UpdateOp(ReduceOp),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ListReduceSettings {
+    /// The type of the items of the list.
+    pub ty: PrimitiveTypeEnum,
+    pub op: ReduceOp,
+}
+
+impl Default for ListReduceSettings {
+    fn default() -> Self {
+        Self { ty: PrimitiveTypeEnum::F32, op: ReduceOp::Sum }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ListReduceUiState {
+    ty_state: enum_dropdown::State,
+    op_state: enum_dropdown::State,
+}
+
+/// Combines all items of a list into a single value.
+#[derive(Debug, Clone)]
+pub struct ListReduceNodeBehaviour {
+    settings: ListReduceSettings,
+    ui_state: ListReduceUiState,
+}
+
+impl Default for ListReduceNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl ListReduceNodeBehaviour {
+    pub fn with_settings(settings: ListReduceSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let ListReduceSettings { ty, op } = self.settings;
+
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_borrow(Channel::new("list", ListType::new_if_sized(ty).unwrap()))
+                .with_output_value(Channel::new("result", op.output_type(ty))),
+        )
+    }
+}
+
+impl NodeBehaviour for ListReduceNodeBehaviour {
+    type Message = ListReduceNodeMessage;
+
+    fn name(&self) -> &str {
+        "List Reduce"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Collection)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                match message {
+                    ListReduceNodeMessage::UpdateType(ty) => self.settings.ty = ty,
+                    ListReduceNodeMessage::UpdateOp(op) => self.settings.op = op,
+                }
+
+                // The output type depends on both the type and the operation.
+                vec![self.get_configure_command()]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            Row::new()
+                .theme(theme)
+                .push(
+                    EnumDropdown::new(
+                        &mut self.ui_state.ty_state,
+                        &PrimitiveTypeEnum::VALUES[..],
+                        self.settings.ty,
+                        ListReduceNodeMessage::UpdateType,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(
+                    EnumDropdown::new(
+                        &mut self.ui_state.op_state,
+                        &ReduceOp::VALUES[..],
+                        self.settings.op,
+                        ListReduceNodeMessage::UpdateOp,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .align_items(Align::Center)
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        *self = Self::with_settings(serde_json::from_value(settings)?);
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _configuration: &NodeConfiguration,
+        connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        // Counting does not depend on the values of the items.
+        if self.settings.op == ReduceOp::Count {
+            return Vec::new();
+        }
+
+        connected.reinterpretation_warnings().collect()
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let ListReduceSettings { ty, op } = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let list = context.borrows[0].clone().downcast_ref::<ListType>().unwrap();
+                    // Primitive items always have a safe binary representation.
+                    let result = op.reduce_dyn(ty, list.items_bytes_if_safe().unwrap());
+                    let result = match result {
+                        Some(result) => {
+                            context.clear_error();
+                            result
+                        }
+                        None => {
+                            context.report_error(format!(
+                                "The {} of an empty list is undefined, the default value is output instead.",
+                                op.to_string().to_lowercase(),
+                            ));
+                            op.output_type(ty).default_value()
+                        }
+                    };
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            result.write::<LittleEndian>(&mut Cursor::new(bytes)).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::Bencher;
+
+    const MILLION: usize = 1_000_000;
+
+    fn f32_bytes(items: &[f32]) -> Vec<u8> {
+        items.iter().flat_map(|item| item.to_le_bytes().to_vec()).collect()
+    }
+
+    fn i16_bytes(items: &[i16]) -> Vec<u8> {
+        items.iter().flat_map(|item| item.to_le_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn reduces_floats() {
+        let items = f32_bytes(&[2.0, -1.5, 4.0, 0.5]);
+        let cases = [
+            (ReduceOp::Sum, PrimitiveChannelValue::F32(5.0)),
+            (ReduceOp::Product, PrimitiveChannelValue::F32(-6.0)),
+            (ReduceOp::Min, PrimitiveChannelValue::F32(-1.5)),
+            (ReduceOp::Max, PrimitiveChannelValue::F32(4.0)),
+            (ReduceOp::Mean, PrimitiveChannelValue::F32(1.25)),
+            (ReduceOp::Count, PrimitiveChannelValue::U64(4)),
+        ];
+
+        for (op, expected) in cases.iter() {
+            assert_eq!(op.reduce_dyn(PrimitiveTypeEnum::F32, &items), Some(*expected), "{}", op);
+            assert_eq!(op.output_type(PrimitiveTypeEnum::F32), expected.ty(), "{}", op);
+        }
+    }
+
+    #[test]
+    fn reduces_integers_wrapping_around_with_a_double_precision_mean() {
+        let items = i16_bytes(&[i16::MAX, 3, -2]);
+        let cases = [
+            (ReduceOp::Sum, PrimitiveChannelValue::I16(i16::MAX.wrapping_add(1))),
+            (ReduceOp::Product, PrimitiveChannelValue::I16(i16::MAX.wrapping_mul(-6))),
+            (ReduceOp::Min, PrimitiveChannelValue::I16(-2)),
+            (ReduceOp::Max, PrimitiveChannelValue::I16(i16::MAX)),
+            (ReduceOp::Mean, PrimitiveChannelValue::F64((i16::MAX as f64 + 1.0) / 3.0)),
+            (ReduceOp::Count, PrimitiveChannelValue::U64(3)),
+        ];
+
+        for (op, expected) in cases.iter() {
+            assert_eq!(op.reduce_dyn(PrimitiveTypeEnum::I16, &items), Some(*expected), "{}", op);
+            assert_eq!(op.output_type(PrimitiveTypeEnum::I16), expected.ty(), "{}", op);
+        }
+    }
+
+    /// Only the operations with an identity reduce empty lists.
+    #[test]
+    fn reduces_empty_lists_to_identities() {
+        for &ty in PrimitiveTypeEnum::VALUES.iter() {
+            for &op in ReduceOp::VALUES.iter() {
+                let expected = match op {
+                    ReduceOp::Sum => Some(ty.from_f64(0.0)),
+                    ReduceOp::Product => Some(ty.from_f64(1.0)),
+                    ReduceOp::Count => Some(PrimitiveChannelValue::U64(0)),
+                    ReduceOp::Min | ReduceOp::Max | ReduceOp::Mean => None,
+                };
+
+                assert_eq!(op.reduce_dyn(ty, &[]), expected, "{} of {}", op, ty);
+            }
+        }
+    }
+
+    /// A million items are reduced in a single pass over their bytes.
+    #[test]
+    fn reduces_a_million_items() {
+        let items = (0..MILLION as u32).flat_map(|item| item.to_le_bytes().to_vec()).collect::<Vec<_>>();
+        let expected_sum = (0..MILLION as u64).sum::<u64>();
+
+        assert_eq!(
+            ReduceOp::Sum.reduce_dyn(PrimitiveTypeEnum::U32, &items),
+            Some(PrimitiveChannelValue::U32(expected_sum as u32)),
+        );
+        assert_eq!(
+            ReduceOp::Mean.reduce_dyn(PrimitiveTypeEnum::U32, &items),
+            Some(PrimitiveChannelValue::F64(expected_sum as f64 / MILLION as f64)),
+        );
+        assert_eq!(
+            ReduceOp::Max.reduce_dyn(PrimitiveTypeEnum::U32, &items),
+            Some(PrimitiveChannelValue::U32(MILLION as u32 - 1)),
+        );
+        assert_eq!(
+            ReduceOp::Count.reduce_dyn(PrimitiveTypeEnum::U32, &items),
+            Some(PrimitiveChannelValue::U64(MILLION as u64)),
+        );
+    }
+
+    #[bench]
+    fn sum_a_million_items(bencher: &mut Bencher) {
+        let items = f32_bytes(&(0..MILLION).map(|item| item as f32).collect::<Vec<_>>());
+
+        bencher.iter(|| ReduceOp::Sum.reduce_dyn(PrimitiveTypeEnum::F32, test::black_box(&items)));
+    }
+
+    #[bench]
+    fn mean_of_a_million_items(bencher: &mut Bencher) {
+        let items = f32_bytes(&(0..MILLION).map(|item| item as f32).collect::<Vec<_>>());
+
+        bencher.iter(|| ReduceOp::Mean.reduce_dyn(PrimitiveTypeEnum::F32, test::black_box(&items)));
+    }
+}
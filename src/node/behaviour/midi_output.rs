@@ -0,0 +1,657 @@
+use crate::node::{ListType, PrimitiveChannelValue};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ConnectedTypeInfo, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure, NodeValidationIssue,
+        },
+        BytesRefExt, Channel, NodeConfiguration, PrimitiveType, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{drag_value, DragValue},
+};
+use byteorder::LittleEndian;
+use iced::{
+    button::{self, Button},
+    pick_list::{self, PickList},
+    Align, Column, Container, Element, Length, Row, Text,
+};
+use midir::{MidiOutput, MidiOutputConnection};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The name DVSynth identifies itself with to the MIDI system.
+const CLIENT_NAME: &str = "DVSynth";
+/// How long to wait before connecting to the port again, after connecting or sending failed.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+/// The gate of a note mapping is open while its input is above this value.
+const GATE_THRESHOLD: f32 = 0.5;
+
+const CONTROL_CHANGE: u8 = 0xB0;
+const NOTE_ON: u8 = 0x90;
+const NOTE_OFF: u8 = 0x80;
+
+/// The names of the MIDI output ports currently available.
+fn output_port_names() -> Result<Vec<String>, String> {
+    let output = MidiOutput::new(CLIENT_NAME).map_err(|error| error.to_string())?;
+
+    Ok(output.ports().iter().filter_map(|port| output.port_name(port).ok()).collect())
+}
+
+/// Connects to the MIDI output port with the name.
+fn connect(port_name: &str) -> Result<MidiOutputConnection, String> {
+    let output = MidiOutput::new(CLIENT_NAME).map_err(|error| error.to_string())?;
+    let port = output
+        .ports()
+        .into_iter()
+        .find(|port| output.port_name(port).map_or(false, |name| name == port_name))
+        .ok_or_else(|| format!("The MIDI output port `{}` is not available.", port_name))?;
+
+    output.connect(&port, CLIENT_NAME).map_err(|error| error.to_string())
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum MidiMappingKind {
+    /// Sends the input, from 0 to 1, as the value of a controller, from 0 to 127.
+    ControlChange,
+    /// Sends a note-on message once the gate input opens and a note-off message once it closes,
+    /// for the note number of the note input.
+    Note,
+    /// Sends the bytes of a `List<U8>` input verbatim, such as SysEx messages.
+    Raw,
+}
+
+impl MidiMappingKind {
+    pub const VALUES: [MidiMappingKind; 3] =
+        [MidiMappingKind::ControlChange, MidiMappingKind::Note, MidiMappingKind::Raw];
+}
+
+impl Display for MidiMappingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MidiMappingKind::ControlChange => write!(f, "CC"),
+            MidiMappingKind::Note => write!(f, "Note"),
+            MidiMappingKind::Raw => write!(f, "Raw"),
+        }
+    }
+}
+
+/// Maps input channels of the node to MIDI messages.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MidiMapping {
+    pub kind: MidiMappingKind,
+    /// The MIDI channel, from 0 to 15, displayed from 1 to 16. Unused by raw mappings.
+    pub channel: u8,
+    /// The controller number of control change mappings, the velocity of note mappings. Unused by
+    /// raw mappings.
+    pub number: u8,
+}
+
+impl Default for MidiMapping {
+    fn default() -> Self {
+        Self { kind: MidiMappingKind::ControlChange, channel: 0, number: 1 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MidiOutputSettings {
+    /// The name of the output port, `None` until one is selected.
+    pub port: Option<String>,
+    pub mappings: Vec<MidiMapping>,
+    /// The shortest time between two messages of the same control change or raw mapping, in
+    /// milliseconds. Notes are not limited, so that none of them are left hanging.
+    pub min_interval: u32,
+}
+
+impl Default for MidiOutputSettings {
+    fn default() -> Self {
+        Self { port: None, mappings: vec![Default::default()], min_interval: 10 }
+    }
+}
+
+impl MidiOutputSettings {
+    fn validate(&self) -> Result<(), &'static str> {
+        if !self.mappings.iter().all(|mapping| mapping.channel < 16 && mapping.number < 128) {
+            Err("The MIDI channels must be below 16 and the numbers below 128.")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MidiOutputNodeMessage {
+    UpdatePort(String),
+    /// Lists the available output ports again.
+    RefreshPorts,
+    AddMapping,
+    RemoveMapping,
+    UpdateKind(usize, MidiMappingKind),
+    UpdateChannel(usize, u8),
+    UpdateNumber(usize, u8),
+    UpdateMinInterval(u32),
+}
+
+#[derive(Debug, Clone, Default)]
+struct MidiMappingUiState {
+    kind_state: pick_list::State<MidiMappingKind>,
+    channel_state: drag_value::State,
+    number_state: drag_value::State,
+}
+
+#[derive(Debug, Clone, Default)]
+struct MidiOutputUiState {
+    /// Listed once the node is displayed, and again when refreshed.
+    ports: Option<Result<Vec<String>, String>>,
+    port_state: pick_list::State<String>,
+    refresh_state: button::State,
+    mappings: Vec<MidiMappingUiState>,
+    min_interval_state: drag_value::State,
+    button_add_state: button::State,
+    button_remove_state: button::State,
+}
+
+/// Sends MIDI messages to an output port, such as to drive external synthesizers. Each mapping adds
+/// input channels, whose changes are sent as control changes, notes or raw messages.
+#[derive(Debug, Clone)]
+pub struct MidiOutputNodeBehaviour {
+    settings: MidiOutputSettings,
+    ui_state: MidiOutputUiState,
+}
+
+impl Default for MidiOutputNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl MidiOutputNodeBehaviour {
+    pub fn with_settings(settings: MidiOutputSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let configuration = self.settings.mappings.iter().enumerate().fold(
+            NodeConfiguration::default(),
+            |configuration, (index, mapping)| match mapping.kind {
+                MidiMappingKind::ControlChange => configuration.with_input_value(
+                    Channel::new(
+                        format!("cc {} #{}", mapping.number, index),
+                        PrimitiveType::<f32>::default(),
+                    )
+                    .with_description("From 0 to 1"),
+                ),
+                MidiMappingKind::Note => configuration
+                    .with_input_value(Channel::new(
+                        format!("gate #{}", index),
+                        PrimitiveType::<f32>::default(),
+                    ))
+                    .with_input_value(
+                        Channel::new(format!("note #{}", index), PrimitiveType::<f32>::default())
+                            .with_description("The MIDI note number"),
+                    ),
+                MidiMappingKind::Raw => configuration.with_borrow(Channel::new(
+                    format!("bytes #{}", index),
+                    ListType::new_if_sized(PrimitiveTypeEnum::U8).unwrap(),
+                )),
+            },
+        );
+
+        NodeCommand::Configure(configuration)
+    }
+
+    /// A field for a number displayed with an offset, such as MIDI channels displayed from 1.
+    fn number_field<'a>(
+        state: &'a mut drag_value::State,
+        label: &'static str,
+        value: u8,
+        offset: u8,
+        max: u8,
+        on_change: impl Fn(u8) -> MidiOutputNodeMessage + 'static,
+        theme: &dyn Theme,
+    ) -> Element<'a, MidiOutputNodeMessage> {
+        DragValue::new(state, value + offset, move |value| on_change(value as u8 - offset))
+            .label(label)
+            .speed(0.1)
+            .step(1.0)
+            .range(offset as f64, (max + offset) as f64)
+            .theme(theme)
+            .width(Length::Fill)
+            .into()
+    }
+}
+
+impl NodeBehaviour for MidiOutputNodeBehaviour {
+    type Message = MidiOutputNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "MIDI Output"
+    }
+
+    fn is_sink(&self) -> bool {
+        true
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Output)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use MidiOutputNodeMessage::*;
+
+                // The fields keep the values within the valid ranges.
+                match message {
+                    UpdatePort(port) => {
+                        self.settings.port = Some(port);
+                        vec![]
+                    }
+                    RefreshPorts => {
+                        self.ui_state.ports = None;
+                        vec![]
+                    }
+                    AddMapping => {
+                        self.settings.mappings.push(Default::default());
+                        vec![self.get_configure_command()]
+                    }
+                    RemoveMapping => {
+                        if self.settings.mappings.len() > 1 {
+                            self.settings.mappings.pop();
+                            vec![self.get_configure_command()]
+                        } else {
+                            vec![]
+                        }
+                    }
+                    UpdateKind(index, kind) => {
+                        if let Some(mapping) = self.settings.mappings.get_mut(index) {
+                            mapping.kind = kind;
+                        }
+
+                        vec![self.get_configure_command()]
+                    }
+                    UpdateChannel(index, channel) => {
+                        if let Some(mapping) = self.settings.mappings.get_mut(index) {
+                            mapping.channel = channel;
+                        }
+
+                        vec![]
+                    }
+                    UpdateNumber(index, number) => {
+                        if let Some(mapping) = self.settings.mappings.get_mut(index) {
+                            mapping.number = number;
+                        }
+
+                        // The controller number is part of the channel title.
+                        vec![self.get_configure_command()]
+                    }
+                    UpdateMinInterval(min_interval) => {
+                        self.settings.min_interval = min_interval;
+                        vec![]
+                    }
+                }
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+        let ports = ui_state.ports.get_or_insert_with(output_port_names);
+
+        ui_state.mappings.resize_with(settings.mappings.len(), Default::default);
+
+        let port_row = Row::new().theme(theme);
+        let port_row = match ports {
+            Ok(ports) => port_row.push(
+                // Wrap PickList in a container because PickList's width resolution is buggy
+                Container::new(
+                    PickList::new(
+                        &mut ui_state.port_state,
+                        &ports[..],
+                        settings.port.clone(),
+                        MidiOutputNodeMessage::UpdatePort,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .width(Length::Fill),
+            ),
+            Err(error) => port_row.push(Text::new(error.as_str()).width(Length::Fill)),
+        };
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                port_row
+                    .push(
+                        Button::new(&mut ui_state.refresh_state, Text::new("⟳"))
+                            .on_press(MidiOutputNodeMessage::RefreshPorts),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            )
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        DragValue::new(&mut ui_state.min_interval_state, settings.min_interval, |interval| {
+                            MidiOutputNodeMessage::UpdateMinInterval(interval as u32)
+                        })
+                        .label("Interval (ms)")
+                        .speed(0.1)
+                        .step(1.0)
+                        .range(0.0, 1000.0)
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        Button::new(&mut ui_state.button_add_state, Text::new("+"))
+                            .on_press(MidiOutputNodeMessage::AddMapping),
+                    )
+                    .push(
+                        Button::new(&mut ui_state.button_remove_state, Text::new("-"))
+                            .on_press(MidiOutputNodeMessage::RemoveMapping),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+
+        for (index, (mapping, mapping_state)) in
+            settings.mappings.iter().zip(ui_state.mappings.iter_mut()).enumerate()
+        {
+            let mut row = Row::new().theme(theme).push(
+                Container::new(
+                    PickList::new(
+                        &mut mapping_state.kind_state,
+                        &MidiMappingKind::VALUES[..],
+                        Some(mapping.kind),
+                        move |kind| MidiOutputNodeMessage::UpdateKind(index, kind),
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .width(Length::Fill),
+            );
+
+            if mapping.kind != MidiMappingKind::Raw {
+                let number_label = if mapping.kind == MidiMappingKind::Note { "Vel" } else { "CC" };
+
+                row = row
+                    .push(Self::number_field(
+                        &mut mapping_state.channel_state,
+                        "Ch",
+                        mapping.channel,
+                        1,
+                        15,
+                        move |channel| MidiOutputNodeMessage::UpdateChannel(index, channel),
+                        theme,
+                    ))
+                    .push(Self::number_field(
+                        &mut mapping_state.number_state,
+                        number_label,
+                        mapping.number,
+                        0,
+                        127,
+                        move |number| MidiOutputNodeMessage::UpdateNumber(index, number),
+                        theme,
+                    ));
+            }
+
+            column = column.push(row.align_items(Align::Center).width(Length::Fill));
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: MidiOutputSettings = serde_json::from_value(settings)?;
+
+        settings.validate().map_err(<serde_json::Error as serde::de::Error>::custom)?;
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _configuration: &NodeConfiguration,
+        _connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        if self.settings.port.is_none() {
+            vec![NodeValidationIssue::warning("No output port is selected, no messages are sent.")]
+        } else {
+            vec![]
+        }
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings.clone();
+                let min_interval = Duration::from_millis(settings.min_interval as u64);
+
+                persistent.reconfigure(&settings);
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let now = Instant::now();
+                    let mut connection = persistent.connection.lock().unwrap();
+
+                    if connection.is_none() {
+                        let port = if let Some(port) = settings.port.as_ref() { port } else { return };
+
+                        if persistent
+                            .failed_at
+                            .map_or(false, |failed_at| now - failed_at < RECONNECT_INTERVAL)
+                        {
+                            return;
+                        }
+
+                        match connect(port) {
+                            Ok(connected) => *connection = Some(connected),
+                            Err(error) => {
+                                persistent.failed_at = Some(now);
+                                context.report_error(error);
+                                return;
+                            }
+                        }
+                    }
+
+                    let sender = connection.as_mut().unwrap();
+                    let mut value_index = 0;
+                    let mut borrow_index = 0;
+                    let mut read_value = || {
+                        let value = PrimitiveTypeEnum::F32
+                            .read::<LittleEndian, _>(&context.inputs[value_index].as_bytes().unwrap())
+                            .unwrap();
+
+                        value_index += 1;
+
+                        match value {
+                            PrimitiveChannelValue::F32(value) => value,
+                            _ => unreachable!(),
+                        }
+                    };
+                    let mut result = Ok(());
+
+                    for (mapping, state) in settings.mappings.iter().zip(persistent.mappings.iter_mut()) {
+                        let status = |status: u8| status | mapping.channel;
+                        let rate_limited =
+                            state.sent_at.map_or(false, |sent_at| now - sent_at < min_interval);
+                        let message = match mapping.kind {
+                            MidiMappingKind::ControlChange => {
+                                let value = (read_value().max(0.0).min(1.0) * 127.0).round() as u8;
+
+                                if state.value != Some(value) && !rate_limited {
+                                    state.value = Some(value);
+                                    Some(vec![status(CONTROL_CHANGE), mapping.number, value])
+                                } else {
+                                    None
+                                }
+                            }
+                            MidiMappingKind::Note => {
+                                let gate = read_value() > GATE_THRESHOLD;
+                                let note = read_value().round().max(0.0).min(127.0) as u8;
+
+                                match (gate, state.note) {
+                                    (true, None) => {
+                                        state.note = Some((mapping.channel, note));
+                                        Some(vec![status(NOTE_ON), note, mapping.number])
+                                    }
+                                    (false, Some((channel, playing))) => {
+                                        state.note = None;
+                                        Some(vec![NOTE_OFF | channel, playing, 0])
+                                    }
+                                    _ => None,
+                                }
+                            }
+                            MidiMappingKind::Raw => {
+                                let list =
+                                    context.borrows[borrow_index].clone().downcast_ref::<ListType>().unwrap();
+                                let bytes = list.items_bytes_if_safe().unwrap();
+
+                                borrow_index += 1;
+
+                                if !bytes.is_empty() && state.bytes[..] != *bytes && !rate_limited {
+                                    state.bytes = bytes.to_vec();
+                                    Some(bytes.to_vec())
+                                } else {
+                                    None
+                                }
+                            }
+                        };
+
+                        if let Some(message) = message {
+                            result = sender.send(&message);
+                            state.sent_at = Some(now);
+
+                            if result.is_err() {
+                                break;
+                            }
+                        }
+                    }
+
+                    if let Err(error) = result {
+                        // Reconnect and send the current values again, as the port may have been
+                        // closed or replaced. The played notes are kept, so that they are released.
+                        persistent.failed_at = Some(now);
+                        *connection = None;
+                        context.report_error(format!("Could not send a MIDI message: {}", error));
+
+                        for state in &mut persistent.mappings {
+                            *state = MappingState { note: state.note, ..Default::default() };
+                        }
+                    } else {
+                        persistent.failed_at = None;
+                        context.clear_error();
+                    }
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// What has been sent by a mapping, so that unchanged values are not sent again.
+#[derive(Default, Debug, Clone)]
+struct MappingState {
+    /// The last control change value.
+    value: Option<u8>,
+    /// The MIDI channel and the number of the note currently played, to be released once the gate
+    /// closes.
+    note: Option<(u8, u8)>,
+    /// The last raw message.
+    bytes: Vec<u8>,
+    sent_at: Option<Instant>,
+}
+
+/// Kept when the schedule is regenerated, so that the connection to the port stays open and
+/// the played notes are released even after the settings change.
+#[derive(Default)]
+pub struct Persistent {
+    /// The port the connection is open to, if any.
+    port: Option<String>,
+    /// Locked only by the executor, wrapped so that the connection can be shared between threads.
+    connection: Mutex<Option<MidiOutputConnection>>,
+    /// When connecting or sending failed last, to delay reconnecting.
+    failed_at: Option<Instant>,
+    mappings: Vec<MappingState>,
+}
+
+impl Persistent {
+    /// Releases the played notes of mappings which are not notes anymore, and closes the
+    /// connection if another port is selected.
+    fn reconfigure(&mut self, settings: &MidiOutputSettings) {
+        let mut mappings = std::mem::take(&mut self.mappings);
+
+        for (index, state) in mappings.iter_mut().enumerate() {
+            let is_note =
+                settings.mappings.get(index).map_or(false, |mapping| mapping.kind == MidiMappingKind::Note);
+
+            if !is_note || self.port != settings.port {
+                self.release_note(state);
+            }
+
+            // The values of other mappings are sent again, as their meaning may have changed.
+            *state = MappingState { note: state.note, ..Default::default() };
+        }
+
+        if self.port != settings.port {
+            *self.connection.get_mut().unwrap() = None;
+            self.port = settings.port.clone();
+            self.failed_at = None;
+        }
+
+        mappings.resize_with(settings.mappings.len(), Default::default);
+        self.mappings = mappings;
+    }
+
+    /// Sends a note-off message for the note played by the mapping, if any. Notes are released on
+    /// a best-effort basis, as the connection may have been lost already.
+    fn release_note(&mut self, state: &mut MappingState) {
+        if let Some((channel, note)) = state.note.take() {
+            if let Some(connection) = self.connection.get_mut().unwrap().as_mut() {
+                let _ = connection.send(&[NOTE_OFF | channel, note, 0]);
+            }
+        }
+    }
+}
+
+impl Drop for Persistent {
+    fn drop(&mut self) {
+        let mut mappings = std::mem::take(&mut self.mappings);
+
+        for state in &mut mappings {
+            self.release_note(state);
+        }
+    }
+}
+
+impl Debug for Persistent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Persistent")
+            .field("port", &self.port)
+            .field("connected", &self.connection.lock().map_or(false, |connection| connection.is_some()))
+            .field("failed_at", &self.failed_at)
+            .field("mappings", &self.mappings)
+            .finish()
+    }
+}
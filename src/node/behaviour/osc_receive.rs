@@ -0,0 +1,326 @@
+use crate::{
+    graph::background::{BackgroundHandle, BackgroundTask, BackgroundTaskStatus},
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType,
+    },
+    style::{Theme, Themeable},
+    widgets::{drag_value, DragValue},
+};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
+use iced::{
+    text_input::{self, TextInput},
+    Align, Element, Length, Row,
+};
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, ErrorKind};
+use std::net::UdpSocket;
+use std::time::Duration;
+
+/// How long the socket is waited on before checking whether the task has been cancelled.
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+/// The maximum number of messages waiting to be received by the node. Further messages are dropped.
+const EVENT_CAPACITY: usize = 256;
+/// The maximum size of a UDP datagram.
+const MAX_PACKET_SIZE: usize = 65536;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OscReceiveSettings {
+    pub port: u16,
+    /// The address messages must have to be received, or any address if empty.
+    pub address: String,
+}
+
+impl Default for OscReceiveSettings {
+    fn default() -> Self {
+        Self { port: 9000, address: String::new() }
+    }
+}
+
+#[derive(Debug)]
+enum OscEvent {
+    /// A message with the address, along with its first numeric argument, if any.
+    Message(Option<f32>),
+    Error(String),
+}
+
+/// Reads the null-terminated string padded to a multiple of 4 bytes at the start of the bytes.
+fn read_string<'a>(bytes: &mut &'a [u8]) -> Option<&'a str> {
+    let length = bytes.iter().position(|byte| *byte == 0)?;
+    let string = std::str::from_utf8(&bytes[..length]).ok()?;
+
+    *bytes = bytes.get((length / 4 + 1) * 4..)?;
+    Some(string)
+}
+
+/// Invokes `on_message` with the address and the first numeric argument of every message in the
+/// packet, including the ones in bundles. Returns `None` if the packet is malformed.
+fn parse_packet(mut packet: &[u8], on_message: &mut impl FnMut(&str, Option<f32>)) -> Option<()> {
+    if packet.starts_with(b"#bundle\0") {
+        // The time tag is ignored, messages are received as soon as they arrive.
+        let mut elements = packet.get(16..)?;
+
+        while !elements.is_empty() {
+            let size = elements.read_u32::<BigEndian>().ok()? as usize;
+
+            parse_packet(elements.get(..size)?, on_message)?;
+            elements = &elements[size..];
+        }
+
+        return Some(());
+    }
+
+    let address = read_string(&mut packet)?;
+    // Messages from old implementations may lack the type tags.
+    let tags = if packet.is_empty() { "," } else { read_string(&mut packet)? };
+    let mut arguments = packet;
+    let mut value = None;
+
+    for tag in tags.strip_prefix(',')?.chars() {
+        let argument = match tag {
+            'f' => Some(arguments.read_f32::<BigEndian>().ok()?),
+            'd' => Some(arguments.read_f64::<BigEndian>().ok()? as f32),
+            'i' => Some(arguments.read_i32::<BigEndian>().ok()? as f32),
+            'h' => Some(arguments.read_i64::<BigEndian>().ok()? as f32),
+            'T' => Some(1.0),
+            'F' => Some(0.0),
+            's' | 'S' => read_string(&mut arguments).map(|_| None)?,
+            'b' => {
+                let size = arguments.read_u32::<BigEndian>().ok()? as usize;
+
+                arguments = arguments.get((size + 3) / 4 * 4..)?;
+                None
+            }
+            'c' | 'r' | 'm' => arguments.read_u32::<BigEndian>().ok().map(|_| None)?,
+            't' => arguments.read_u64::<BigEndian>().ok().map(|_| None)?,
+            'N' | 'I' => None,
+            _ => return None,
+        };
+
+        if argument.is_some() {
+            value = argument;
+            break;
+        }
+    }
+
+    on_message(address, value);
+    Some(())
+}
+
+/// Receives OSC packets on the port until cancelled, sending the messages with the address.
+fn receive(handle: BackgroundHandle<OscEvent>, port: u16, address: String) {
+    let socket = match UdpSocket::bind(("0.0.0.0", port)).and_then(|socket| {
+        socket.set_read_timeout(Some(READ_TIMEOUT))?;
+        Ok(socket)
+    }) {
+        Ok(socket) => socket,
+        Err(error) => {
+            handle.send(OscEvent::Error(format!("Could not listen on port {}: {}", port, error)));
+            return;
+        }
+    };
+    let mut buffer = vec![0; MAX_PACKET_SIZE];
+
+    while !handle.is_cancelled() {
+        let length = match socket.recv(&mut buffer) {
+            Ok(length) => length,
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(error) => {
+                handle.send(OscEvent::Error(format!("Could not receive a packet: {}", error)));
+                return;
+            }
+        };
+
+        // Malformed packets are ignored, as the network may carry other traffic.
+        parse_packet(&buffer[..length], &mut |message_address, value| {
+            if address.is_empty() || message_address == address {
+                handle.try_send(OscEvent::Message(value));
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum OscReceiveNodeMessage {
+    UpdatePort(f64),
+    UpdateAddress(String),
+}
+
+/// Receives Open Sound Control messages over UDP, for controlling graphs from other applications
+/// and controllers. The socket is read by a background task, so that the executor never waits for
+/// packets. Addresses are matched exactly, without OSC patterns.
+#[derive(Debug, Clone, Default)]
+pub struct OscReceiveNodeBehaviour {
+    settings: OscReceiveSettings,
+    port_state: drag_value::State,
+    address_state: text_input::State,
+}
+
+impl OscReceiveNodeBehaviour {
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_output_value(
+                    Channel::new("value", PrimitiveType::<f32>::default())
+                        .with_description("The first numeric argument of the last message with one"),
+                )
+                .with_output_value(
+                    Channel::new("received", PrimitiveType::<u8>::default()).with_description(
+                        "1 if a message has been received since the last execution, 0 otherwise",
+                    ),
+                ),
+        )
+    }
+}
+
+impl NodeBehaviour for OscReceiveNodeBehaviour {
+    type Message = OscReceiveNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "OSC Receive"
+    }
+
+    fn is_source(&self) -> bool {
+        // The output depends on the packets received from the network.
+        true
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Value)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                match message {
+                    OscReceiveNodeMessage::UpdatePort(port) => self.settings.port = port.round() as u16,
+                    OscReceiveNodeMessage::UpdateAddress(address) => self.settings.address = address,
+                }
+
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            Row::new()
+                .theme(theme)
+                .push(
+                    DragValue::new(
+                        &mut self.port_state,
+                        self.settings.port,
+                        OscReceiveNodeMessage::UpdatePort,
+                    )
+                    .label("Port")
+                    .range(1.0, u16::MAX as f64)
+                    .step(1.0)
+                    .theme(theme)
+                    .width(Length::Units(96)),
+                )
+                .push(
+                    TextInput::new(
+                        &mut self.address_state,
+                        "Any address",
+                        &self.settings.address,
+                        OscReceiveNodeMessage::UpdateAddress,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .align_items(Align::Center)
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.settings = serde_json::from_value(settings)?;
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings.clone();
+                // Replacing the closure drops the task listening with the previous settings.
+                let mut task: Option<BackgroundTask<OscEvent>> = None;
+                let mut spawn_failed = false;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    if task.is_none() && !spawn_failed {
+                        let OscReceiveSettings { port, address } = settings.clone();
+
+                        context.clear_error();
+
+                        match context.spawn_background(
+                            format!("OSC Receive on port {}", port),
+                            EVENT_CAPACITY,
+                            move |handle| receive(handle, port, address),
+                        ) {
+                            Ok(spawned) => task = Some(spawned),
+                            Err(error) => {
+                                spawn_failed = true;
+                                context.report_error(format!("Could not start receiving: {}", error));
+                            }
+                        }
+                    }
+
+                    let mut received = false;
+
+                    if let Some(task) = task.as_mut() {
+                        for event in task.try_iter() {
+                            match event {
+                                OscEvent::Message(value) => {
+                                    received = true;
+                                    persistent.value = value.unwrap_or(persistent.value);
+                                }
+                                OscEvent::Error(error) => context.report_error(error),
+                            }
+                        }
+
+                        if task.status() == BackgroundTaskStatus::Panicked {
+                            context.report_error("Receiving stopped unexpectedly.");
+                        }
+                    }
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes).write_f32::<LittleEndian>(persistent.value).unwrap();
+                        })
+                        .unwrap();
+                    context.outputs[1]
+                        .replace_with_bytes(context.allocator_handle, |bytes| bytes[0] = received as u8)
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    /// The last value received, kept when the settings change.
+    value: f32,
+}
@@ -0,0 +1,510 @@
+use crate::node::PrimitiveChannelValue;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+};
+use byteorder::LittleEndian;
+use iced::{
+    pick_list::{self, PickList},
+    Align, Container, Element, Length, Row,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Cursor;
+
+/// The integer type which is split into parts, along with the type of the parts.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum PackWidth {
+    /// A `u16` split into two `u8`s.
+    U16,
+    /// A `u32` split into four `u8`s.
+    U32,
+    /// A `u64` split into two `u32`s.
+    U64,
+}
+
+impl PackWidth {
+    pub const VALUES: [PackWidth; 3] = [PackWidth::U16, PackWidth::U32, PackWidth::U64];
+
+    pub fn whole_ty(self) -> PrimitiveTypeEnum {
+        match self {
+            PackWidth::U16 => PrimitiveTypeEnum::U16,
+            PackWidth::U32 => PrimitiveTypeEnum::U32,
+            PackWidth::U64 => PrimitiveTypeEnum::U64,
+        }
+    }
+
+    pub fn part_ty(self) -> PrimitiveTypeEnum {
+        match self {
+            PackWidth::U16 | PackWidth::U32 => PrimitiveTypeEnum::U8,
+            PackWidth::U64 => PrimitiveTypeEnum::U32,
+        }
+    }
+
+    pub fn part_bits(self) -> u32 {
+        match self {
+            PackWidth::U16 | PackWidth::U32 => 8,
+            PackWidth::U64 => 32,
+        }
+    }
+
+    pub fn part_count(self) -> usize {
+        match self {
+            PackWidth::U16 | PackWidth::U64 => 2,
+            PackWidth::U32 => 4,
+        }
+    }
+
+    /// The position of the least significant bit of the part at `index` within the whole.
+    fn part_shift(self, endianness: Endianness, index: usize) -> u32 {
+        let significance = match endianness {
+            Endianness::Little => index,
+            Endianness::Big => self.part_count() - 1 - index,
+        };
+
+        significance as u32 * self.part_bits()
+    }
+
+    /// Combines the parts into the whole, the parts are ordered as they would be in memory.
+    pub fn pack(self, endianness: Endianness, parts: &[u64]) -> u64 {
+        let mask = u64::MAX >> (64 - self.part_bits());
+
+        parts
+            .iter()
+            .take(self.part_count())
+            .enumerate()
+            .fold(0, |whole, (index, part)| whole | (part & mask) << self.part_shift(endianness, index))
+    }
+
+    /// Splits the whole into parts, ordered as they would be in memory.
+    pub fn unpack(self, endianness: Endianness, whole: u64) -> Vec<u64> {
+        let mask = u64::MAX >> (64 - self.part_bits());
+
+        (0..self.part_count()).map(|index| whole >> self.part_shift(endianness, index) & mask).collect()
+    }
+}
+
+impl Display for PackWidth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackWidth::U16 => write!(f, "U16 ↔ 2×U8"),
+            PackWidth::U32 => write!(f, "U32 ↔ 4×U8"),
+            PackWidth::U64 => write!(f, "U64 ↔ 2×U32"),
+        }
+    }
+}
+
+/// The order of the parts.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Endianness {
+    /// The least significant part first.
+    Little,
+    /// The most significant part first.
+    Big,
+}
+
+impl Endianness {
+    pub const VALUES: [Endianness; 2] = [Endianness::Little, Endianness::Big];
+}
+
+impl Display for Endianness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endianness::Little => write!(f, "LE"),
+            Endianness::Big => write!(f, "BE"),
+        }
+    }
+}
+
+fn value_to_u64(value: PrimitiveChannelValue) -> u64 {
+    match value {
+        PrimitiveChannelValue::U8(value) => value as u64,
+        PrimitiveChannelValue::U16(value) => value as u64,
+        PrimitiveChannelValue::U32(value) => value as u64,
+        PrimitiveChannelValue::U64(value) => value,
+        _ => unreachable!("Only unsigned integers of up to 64 bits are packed."),
+    }
+}
+
+fn u64_to_value(ty: PrimitiveTypeEnum, value: u64) -> PrimitiveChannelValue {
+    match ty {
+        PrimitiveTypeEnum::U8 => (value as u8).into(),
+        PrimitiveTypeEnum::U16 => (value as u16).into(),
+        PrimitiveTypeEnum::U32 => (value as u32).into(),
+        PrimitiveTypeEnum::U64 => value.into(),
+        _ => unreachable!("Only unsigned integers of up to 64 bits are packed."),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PackSettings {
+    pub width: PackWidth,
+    pub endianness: Endianness,
+}
+
+impl Default for PackSettings {
+    fn default() -> Self {
+        Self { width: PackWidth::U32, endianness: Endianness::Little }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PackNodeMessage {
+    UpdateWidth(PackWidth),
+    UpdateEndianness(Endianness),
+}
+
+#[derive(Debug, Clone, Default)]
+struct PackUiState {
+    width_state: pick_list::State<PackWidth>,
+    endianness_state: pick_list::State<Endianness>,
+}
+
+impl PackUiState {
+    fn view(&mut self, settings: PackSettings, theme: &dyn Theme) -> Element<PackNodeMessage> {
+        Row::new()
+            .theme(theme)
+            .push(
+                // Wrap PickList in a container because PickList's width resolution is buggy
+                Container::new(
+                    PickList::new(
+                        &mut self.width_state,
+                        &PackWidth::VALUES[..],
+                        Some(settings.width),
+                        PackNodeMessage::UpdateWidth,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .width(Length::Fill),
+            )
+            .push(
+                // Wrap PickList in a container because PickList's width resolution is buggy
+                Container::new(
+                    PickList::new(
+                        &mut self.endianness_state,
+                        &Endianness::VALUES[..],
+                        Some(settings.endianness),
+                        PackNodeMessage::UpdateEndianness,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .width(Length::Units(56)),
+            )
+            .align_items(Align::Center)
+            .width(Length::Fill)
+            .into()
+    }
+}
+
+/// Applies the message to the settings and returns whether the channels have changed.
+fn update_settings(settings: &mut PackSettings, message: PackNodeMessage) -> bool {
+    match message {
+        PackNodeMessage::UpdateWidth(width) => {
+            settings.width = width;
+            true
+        }
+        PackNodeMessage::UpdateEndianness(endianness) => {
+            settings.endianness = endianness;
+            false
+        }
+    }
+}
+
+fn part_channels(width: PackWidth) -> impl Iterator<Item = Channel> {
+    (0..width.part_count()).map(move |index| {
+        Channel::new(format!("part {}", index), width.part_ty())
+            .with_description("Ordered as in memory, according to the endianness")
+    })
+}
+
+/// Combines multiple unsigned integers into a wider one.
+#[derive(Debug, Clone, Default)]
+pub struct PackNodeBehaviour {
+    settings: PackSettings,
+    ui_state: PackUiState,
+}
+
+impl PackNodeBehaviour {
+    pub fn with_settings(settings: PackSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let configuration = part_channels(self.settings.width)
+            .fold(NodeConfiguration::default(), |configuration, channel| {
+                configuration.with_input_value(channel)
+            })
+            .with_output_value(Channel::new("whole", self.settings.width.whole_ty()));
+
+        NodeCommand::Configure(configuration)
+    }
+}
+
+impl NodeBehaviour for PackNodeBehaviour {
+    type Message = PackNodeMessage;
+
+    fn name(&self) -> &str {
+        "Pack"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                if update_settings(&mut self.settings, message) {
+                    vec![self.get_configure_command()]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(self.ui_state.view(self.settings, theme))
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        *self = Self::with_settings(serde_json::from_value(settings)?);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let PackSettings { width, endianness } = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let parts = context
+                        .inputs
+                        .iter()
+                        .map(|input| {
+                            width.part_ty().read::<LittleEndian, _>(&input.as_bytes().unwrap()).unwrap()
+                        })
+                        .map(value_to_u64)
+                        .collect::<Vec<_>>();
+                    let whole = u64_to_value(width.whole_ty(), width.pack(endianness, &parts));
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            whole.write::<LittleEndian>(&mut Cursor::new(bytes)).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
+
+/// Splits an unsigned integer into multiple narrower ones.
+#[derive(Debug, Clone, Default)]
+pub struct UnpackNodeBehaviour {
+    settings: PackSettings,
+    ui_state: PackUiState,
+}
+
+impl UnpackNodeBehaviour {
+    pub fn with_settings(settings: PackSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let configuration = part_channels(self.settings.width).fold(
+            NodeConfiguration::default()
+                .with_input_value(Channel::new("whole", self.settings.width.whole_ty())),
+            |configuration, channel| configuration.with_output_value(channel),
+        );
+
+        NodeCommand::Configure(configuration)
+    }
+}
+
+impl NodeBehaviour for UnpackNodeBehaviour {
+    type Message = PackNodeMessage;
+
+    fn name(&self) -> &str {
+        "Unpack"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                if update_settings(&mut self.settings, message) {
+                    vec![self.get_configure_command()]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(self.ui_state.view(self.settings, theme))
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        *self = Self::with_settings(serde_json::from_value(settings)?);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let PackSettings { width, endianness } = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let whole = width
+                        .whole_ty()
+                        .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                        .unwrap();
+                    let parts = width.unpack(endianness, value_to_u64(whole));
+
+                    for (output, part) in context.outputs.iter_mut().zip(parts) {
+                        let part = u64_to_value(width.part_ty(), part);
+
+                        output
+                            .replace_with_bytes(context.allocator_handle, |bytes| {
+                                part.write::<LittleEndian>(&mut Cursor::new(bytes)).unwrap();
+                            })
+                            .unwrap();
+                    }
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::behaviour::Prng;
+    use byteorder::{BigEndian, ByteOrder};
+
+    /// Wholes of every width, followed by random ones.
+    fn wholes(width: PackWidth) -> impl Iterator<Item = u64> {
+        let mask = u64::MAX >> (64 - width.whole_ty().value_size() * 8);
+        let mut prng = Prng::new(width.part_bits() as u64);
+        let samples =
+            vec![0, 1, u64::MAX, 0x0123_4567_89AB_CDEF, 0x8000_0000_8000_0001, 0xFF00_FF00_FF00_FF00];
+
+        samples.into_iter().chain((0..1000).map(move |_| prng.next_u64())).map(move |whole| whole & mask)
+    }
+
+    /// The parts of the whole as laid out in memory: the bytes of the whole in the given byte
+    /// order, read in chunks of the size of the parts.
+    fn memory_parts(width: PackWidth, endianness: Endianness, whole: u64) -> Vec<u64> {
+        let whole_size = width.whole_ty().value_size();
+        let part_size = width.part_ty().value_size();
+        let mut bytes = vec![0; whole_size];
+
+        match endianness {
+            Endianness::Little => LittleEndian::write_uint(&mut bytes, whole, whole_size),
+            Endianness::Big => BigEndian::write_uint(&mut bytes, whole, whole_size),
+        }
+
+        bytes
+            .chunks(part_size)
+            .map(|chunk| match endianness {
+                Endianness::Little => LittleEndian::read_uint(chunk, part_size),
+                Endianness::Big => BigEndian::read_uint(chunk, part_size),
+            })
+            .collect()
+    }
+
+    fn settings() -> impl Iterator<Item = (PackWidth, Endianness)> {
+        PackWidth::VALUES
+            .iter()
+            .flat_map(|width| Endianness::VALUES.iter().map(move |endianness| (*width, *endianness)))
+    }
+
+    #[test]
+    fn unpacked_parts_match_the_memory_layout() {
+        for (width, endianness) in settings() {
+            for whole in wholes(width) {
+                assert_eq!(
+                    width.unpack(endianness, whole),
+                    memory_parts(width, endianness, whole),
+                    "{} {} {:#x}",
+                    width,
+                    endianness,
+                    whole,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn packing_inverts_unpacking() {
+        for (width, endianness) in settings() {
+            for whole in wholes(width) {
+                let parts = width.unpack(endianness, whole);
+
+                assert_eq!(parts.len(), width.part_count());
+                assert_eq!(width.pack(endianness, &parts), whole, "{} {} {:#x}", width, endianness, whole);
+            }
+        }
+    }
+
+    #[test]
+    fn packing_ignores_the_bits_exceeding_the_parts() {
+        for (width, endianness) in settings() {
+            let whole_mask = u64::MAX >> (64 - width.whole_ty().value_size() * 8);
+            let parts = vec![u64::MAX; width.part_count()];
+
+            assert_eq!(width.pack(endianness, &parts), whole_mask, "{} {}", width, endianness);
+        }
+    }
+
+    #[test]
+    fn endianness_orders_the_parts() {
+        assert_eq!(PackWidth::U16.unpack(Endianness::Little, 0x1234), vec![0x34, 0x12]);
+        assert_eq!(PackWidth::U16.unpack(Endianness::Big, 0x1234), vec![0x12, 0x34]);
+        assert_eq!(PackWidth::U32.unpack(Endianness::Little, 0x1234_5678), vec![0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(PackWidth::U32.unpack(Endianness::Big, 0x1234_5678), vec![0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(
+            PackWidth::U64.unpack(Endianness::Little, 0x0123_4567_89AB_CDEF),
+            vec![0x89AB_CDEF, 0x0123_4567]
+        );
+        assert_eq!(
+            PackWidth::U64.unpack(Endianness::Big, 0x0123_4567_89AB_CDEF),
+            vec![0x0123_4567, 0x89AB_CDEF]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ConnectedTypeInfo, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeBehaviourContainer, NodeCommand, NodeEvent, NodeIcon, NodeStateClosure, NodeValidationIssue,
+        },
+        NodeConfiguration, OptionRefMutExt,
+    },
+    style::{consts, Theme, Themeable},
+};
+use iced::{Column, Element, Length, Text};
+
+/// Why a node has been replaced by a placeholder, see
+/// [`Substitutions`](crate::safe_mode::Substitutions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderReason {
+    /// The node is of an external behaviour, see [`NodeBehaviour::is_external`].
+    SafeMode,
+    /// The kind of node has been disabled permanently.
+    Disabled,
+}
+
+/// Stands in for a node of an external behaviour in safe mode, see
+/// [`NodeBehaviour::is_external`], or for a node of a disabled behaviour.
+///
+/// The placeholder keeps the name and the serialized settings of the replaced behaviour, which it
+/// is saved as, so that the replaced behaviour is restored when the graph is loaded outside of
+/// safe mode. It also keeps the channels, so that the connections of the node survive. The node
+/// does nothing when executed, other than writing zeroes into the outputs which can hold them.
+#[derive(Debug, Clone)]
+pub struct PlaceholderNodeBehaviour {
+    reason: PlaceholderReason,
+    name: String,
+    settings: serde_json::Value,
+    configuration: NodeConfiguration,
+    is_source: bool,
+    is_sink: bool,
+    icon: Option<NodeIcon>,
+}
+
+impl PlaceholderNodeBehaviour {
+    /// Preserves the behaviour, configured with the configuration.
+    pub fn replacing(
+        reason: PlaceholderReason,
+        behaviour: &dyn NodeBehaviourContainer,
+        configuration: NodeConfiguration,
+    ) -> Self {
+        Self {
+            reason,
+            name: behaviour.name().to_string(),
+            settings: behaviour.save(),
+            configuration,
+            is_source: behaviour.is_source(),
+            is_sink: behaviour.is_sink(),
+            icon: behaviour.icon(),
+        }
+    }
+}
+
+impl NodeBehaviour for PlaceholderNodeBehaviour {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_source(&self) -> bool {
+        self.is_source
+    }
+
+    fn is_sink(&self) -> bool {
+        self.is_sink
+    }
+
+    fn is_placeholder(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        self.icon
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![NodeCommand::Configure(self.configuration.clone())],
+            NodeEvent::Message(()) => vec![],
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let description = match self.reason {
+            PlaceholderReason::SafeMode => {
+                "Disabled in safe mode. The settings are kept, and the node is restored once safe mode \
+                 is left."
+            }
+            PlaceholderReason::Disabled => {
+                "Disabled permanently. The settings are kept, and the node is restored once its kind \
+                 is enabled in the preferences and the graph is opened again."
+            }
+        };
+
+        Some(
+            Column::new()
+                .theme(theme)
+                .push(Text::new(description).size(consts::TEXT_SIZE_REGULAR))
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.settings.clone()
+    }
+
+    /// The settings are not validated, as the replaced behaviour is not instantiated. Invalid
+    /// settings are reported once the graph is loaded outside of safe mode.
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.settings = settings;
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _configuration: &NodeConfiguration,
+        _connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        let issue = match self.reason {
+            PlaceholderReason::SafeMode => format!("`{}` is disabled in safe mode.", self.name),
+            PlaceholderReason::Disabled => format!("`{}` is disabled.", self.name),
+        };
+
+        vec![NodeValidationIssue::warning(issue)]
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |_behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    for output in context.outputs.iter_mut() {
+                        // Outputs of objects, such as textures, cannot be written without creating
+                        // them, and are left empty.
+                        let _ = output.replace_with_bytes(context.allocator_handle, |bytes| {
+                            bytes.iter_mut().for_each(|byte| *byte = 0)
+                        });
+                    }
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
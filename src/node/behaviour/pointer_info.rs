@@ -0,0 +1,290 @@
+use crate::graph::alloc::Allocator;
+use crate::node::prelude::*;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        refcount_label, typed_bytes_to_ptr, BorrowedRef, BufferType, Bytes, Channel, ListType,
+        NodeConfiguration, PrimitiveTypeEnum, RefAny, Shared, TextureType, TypeEnum, TypeExt, Unique,
+    },
+    style::{consts, Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use iced::{Align, Column, Element, Length, Row, Text};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointerKind {
+    Shared,
+    Unique,
+}
+
+impl PointerKind {
+    pub const VALUES: [PointerKind; 2] = [PointerKind::Shared, PointerKind::Unique];
+}
+
+impl Display for PointerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PointerKind::Shared => write!(f, "Shared"),
+            PointerKind::Unique => write!(f, "Unique"),
+        }
+    }
+}
+
+/// The kinds of values that are allocated and passed around through pointers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointeeKind {
+    Texture,
+    Buffer,
+    List,
+}
+
+impl PointeeKind {
+    pub const VALUES: [PointeeKind; 3] = [PointeeKind::Texture, PointeeKind::Buffer, PointeeKind::List];
+}
+
+impl Display for PointeeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PointeeKind::Texture => write!(f, "Texture"),
+            PointeeKind::Buffer => write!(f, "Buffer"),
+            PointeeKind::List => write!(f, "List"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PointerInfoSettings {
+    pub kind: PointerKind,
+    pub pointee: PointeeKind,
+    /// The type of the items of lists, ignored for other pointees.
+    pub item_ty: PrimitiveTypeEnum,
+}
+
+impl Default for PointerInfoSettings {
+    fn default() -> Self {
+        Self { kind: PointerKind::Unique, pointee: PointeeKind::Texture, item_ty: PrimitiveTypeEnum::F32 }
+    }
+}
+
+impl PointerInfoSettings {
+    /// The type of the inspected pointers. Textures of any format are accepted.
+    pub fn ty(&self) -> TypeEnum {
+        let pointee: TypeEnum = match self.pointee {
+            PointeeKind::Texture => TextureType::new().into(),
+            PointeeKind::Buffer => BufferType::new().into(),
+            PointeeKind::List => ListType::new_if_sized(self.item_ty).unwrap().into(),
+        };
+
+        match self.kind {
+            PointerKind::Shared => Shared::from_enum(pointee).into(),
+            PointerKind::Unique => Unique::from_enum(pointee).into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PointerInfoNodeMessage {
+    UpdateKind(PointerKind),
+    UpdatePointee(PointeeKind),
+    UpdateItemType(PrimitiveTypeEnum),
+}
+
+/// The properties of the most recently inspected pointer, recorded by the executor for the UI to
+/// display.
+#[derive(Debug, Clone)]
+struct PointerSnapshot {
+    pass_index: u64,
+    /// The type of the allocation, which may be more specific than the type of the channel.
+    pointee_ty: String,
+    /// The global refcount, `None` if the allocation has already been freed.
+    refcount: Option<usize>,
+    /// In bytes, `None` for opaque objects, such as GPU resources.
+    size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PointerInfoUiState {
+    kind_state: enum_dropdown::State,
+    pointee_state: enum_dropdown::State,
+    item_ty_state: enum_dropdown::State,
+}
+
+/// Displays the type, the refcount and the size of the allocations passed through a pointer.
+#[derive(Debug, Clone, Default)]
+pub struct PointerInfoNodeBehaviour {
+    settings: PointerInfoSettings,
+    /// Shared with the executor, as the behaviour is cloned into the schedule.
+    snapshot: Arc<Mutex<Option<PointerSnapshot>>>,
+    ui_state: PointerInfoUiState,
+}
+
+impl PointerInfoNodeBehaviour {
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default().with_borrow(Channel::new("pointer", self.settings.ty())),
+        )
+    }
+}
+
+/// Inspects the allocation the pointer resolves to.
+fn inspect_pointee(pointee: BorrowedRef<'_>) -> (String, Option<usize>) {
+    let typed_bytes = unsafe { pointee.typed_bytes() };
+    let ty = typed_bytes.borrow().ty().into_owned();
+    let size = match typed_bytes.bytes() {
+        Bytes::Bytes(bytes) => Some(bytes.len()),
+        Bytes::Object { .. } => ty.value_size_if_sized(),
+    };
+
+    (ty.to_string(), size)
+}
+
+impl NodeBehaviour for PointerInfoNodeBehaviour {
+    type Message = PointerInfoNodeMessage;
+
+    fn name(&self) -> &str {
+        "PointerInfo"
+    }
+
+    fn is_sink(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Inspect)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                match message {
+                    PointerInfoNodeMessage::UpdateKind(kind) => self.settings.kind = kind,
+                    PointerInfoNodeMessage::UpdatePointee(pointee) => self.settings.pointee = pointee,
+                    PointerInfoNodeMessage::UpdateItemType(item_ty) => self.settings.item_ty = item_ty,
+                }
+
+                vec![self.get_configure_command()]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let ui_state = &mut self.ui_state;
+        let mut row = Row::new()
+            .theme(theme)
+            .push(
+                EnumDropdown::new(
+                    &mut ui_state.kind_state,
+                    &PointerKind::VALUES[..],
+                    self.settings.kind,
+                    PointerInfoNodeMessage::UpdateKind,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+            )
+            .push(
+                EnumDropdown::new(
+                    &mut ui_state.pointee_state,
+                    &PointeeKind::VALUES[..],
+                    self.settings.pointee,
+                    PointerInfoNodeMessage::UpdatePointee,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+            )
+            .align_items(Align::Center)
+            .width(Length::Fill);
+
+        if self.settings.pointee == PointeeKind::List {
+            row = row.push(
+                EnumDropdown::new(
+                    &mut ui_state.item_ty_state,
+                    &PrimitiveTypeEnum::VALUES[..],
+                    self.settings.item_ty,
+                    PointerInfoNodeMessage::UpdateItemType,
+                )
+                .theme(theme)
+                .width(Length::Units(64)),
+            );
+        }
+
+        let column = Column::new().theme(theme).push(row).width(Length::Fill);
+        let snapshot = match self.snapshot.lock().unwrap().clone() {
+            Some(snapshot) => snapshot,
+            None => {
+                return Some(
+                    column
+                        .push(Text::new("No pointer inspected yet.").size(consts::TEXT_SIZE_REGULAR))
+                        .into(),
+                );
+            }
+        };
+        let refcount = snapshot.refcount.map(refcount_label).unwrap_or_else(|| "freed".to_string());
+        let size =
+            snapshot.size.map(|size| format!("{} bytes", size)).unwrap_or_else(|| "unknown".to_string());
+
+        Some(
+            column
+                .push(Text::new(format!("Pass {}", snapshot.pass_index)).size(consts::TEXT_SIZE_REGULAR))
+                .push(Text::new(format!("Pointee: {}", snapshot.pointee_ty)).size(consts::TEXT_SIZE_REGULAR))
+                .push(Text::new(format!("Refcount: {}", refcount)).size(consts::TEXT_SIZE_REGULAR))
+                .push(Text::new(format!("Size: {}", size)).size(consts::TEXT_SIZE_REGULAR))
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.settings = serde_json::from_value(settings)?;
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let snapshot = behaviour.snapshot.clone();
+
+                // The pointer inspected by the previous schedule may be of a different type.
+                *snapshot.lock().unwrap() = None;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let pointer = context.borrows[0].clone();
+                    let ptr = typed_bytes_to_ptr(unsafe { pointer.typed_bytes() }).unwrap();
+                    // Resolve the wildcard pointee, whichever kind of pointer the channel is of.
+                    let (pointee_ty, size) = match pointer.clone().downcast_ref::<Shared>() {
+                        Some(shared) => inspect_pointee(SharedRefExt::deref(&shared)),
+                        None => {
+                            let unique = pointer.downcast_ref::<Unique>().unwrap();
+
+                            inspect_pointee(UniqueRefExt::deref(&unique))
+                        }
+                    };
+
+                    *snapshot.lock().unwrap() = Some(PointerSnapshot {
+                        pass_index: context.application_context.frame_info.pass_index,
+                        pointee_ty,
+                        refcount: Allocator::get().refcount(ptr),
+                        size,
+                    });
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
@@ -0,0 +1,219 @@
+use crate::node::behaviour::random::{seed_field, SeededPrng, TriggerInput};
+use crate::node::PrimitiveChannelValue;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{drag_value, DragValue},
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use iced::{Align, Element, Length, Row};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProbabilityGateSettings {
+    /// From 0 to 1.
+    pub probability: f32,
+    pub seed: u32,
+}
+
+impl Default for ProbabilityGateSettings {
+    fn default() -> Self {
+        Self { probability: 0.5, seed: 0 }
+    }
+}
+
+impl ProbabilityGateSettings {
+    fn validate(&self) -> Result<(), &'static str> {
+        if !(0.0..=1.0).contains(&self.probability) {
+            Err("The probability must be between 0 and 1.")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ProbabilityGateNodeMessage {
+    UpdateProbability(f32),
+    UpdateSeed(u32),
+}
+
+#[derive(Debug, Clone, Default)]
+struct ProbabilityGateUiState {
+    probability_state: drag_value::State,
+    seed_state: drag_value::State,
+}
+
+/// Passes each trigger through with a probability, suppressing it otherwise. A trigger is passed
+/// through for as long as the input stays high.
+#[derive(Debug, Clone)]
+pub struct ProbabilityGateNodeBehaviour {
+    settings: ProbabilityGateSettings,
+    ui_state: ProbabilityGateUiState,
+}
+
+impl Default for ProbabilityGateNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl ProbabilityGateNodeBehaviour {
+    pub fn with_settings(settings: ProbabilityGateSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_input_value(Channel::new("trigger", PrimitiveType::<f32>::default()))
+                .with_output_value(
+                    Channel::new("trigger", PrimitiveType::<f32>::default())
+                        .with_description("1 while a trigger which has passed through is high, 0 otherwise"),
+                )
+                .with_output_value(
+                    Channel::new("suppressed", PrimitiveType::<u32>::default())
+                        .with_description("The number of triggers suppressed so far"),
+                ),
+        )
+    }
+}
+
+impl NodeBehaviour for ProbabilityGateNodeBehaviour {
+    type Message = ProbabilityGateNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Probability Gate"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use ProbabilityGateNodeMessage::*;
+
+                // The fields keep the values within the valid ranges.
+                match message {
+                    UpdateProbability(probability) => self.settings.probability = probability,
+                    UpdateSeed(seed) => self.settings.seed = seed,
+                }
+
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+
+        Some(
+            Row::new()
+                .theme(theme)
+                .push(
+                    DragValue::new(&mut ui_state.probability_state, settings.probability, |probability| {
+                        ProbabilityGateNodeMessage::UpdateProbability(probability as f32)
+                    })
+                    .label("Probability")
+                    .speed(0.005)
+                    .range(0.0, 1.0)
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(seed_field(
+                    &mut ui_state.seed_state,
+                    settings.seed,
+                    ProbabilityGateNodeMessage::UpdateSeed,
+                    theme,
+                ))
+                .align_items(Align::Center)
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: ProbabilityGateSettings = serde_json::from_value(settings)?;
+
+        settings.validate().map_err(<serde_json::Error as serde::de::Error>::custom)?;
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let trigger = match PrimitiveTypeEnum::F32
+                        .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                        .unwrap()
+                    {
+                        PrimitiveChannelValue::F32(trigger) => trigger,
+                        _ => unreachable!(),
+                    };
+                    let (high, rising) = persistent.trigger.advance(trigger);
+
+                    if rising {
+                        persistent.passing =
+                            persistent.prng.get(settings.seed).chance(settings.probability as f64);
+
+                        if !persistent.passing {
+                            persistent.suppressed = persistent.suppressed.wrapping_add(1);
+                        }
+                    }
+
+                    let output = if high && persistent.passing { 1.0 } else { 0.0 };
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes).write_f32::<LittleEndian>(output).unwrap();
+                        })
+                        .unwrap();
+                    context.outputs[1]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes).write_u32::<LittleEndian>(persistent.suppressed).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated, so that the sequence of random decisions continues
+/// where it was, unless the seed is changed.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    prng: SeededPrng,
+    trigger: TriggerInput,
+    /// Whether the last trigger has passed through.
+    passing: bool,
+    suppressed: u32,
+}
@@ -0,0 +1,575 @@
+use crate::node::PrimitiveChannelValue;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
+    },
+    style::{consts, Theme, Themeable},
+    widgets::{drag_value, DragValue},
+};
+use byteorder::LittleEndian;
+use iced::{
+    pick_list::{self, PickList},
+    Align, Column, Container, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Cursor;
+
+const NOTE_NAMES: [&str; 12] = ["C", "C♯", "D", "D♯", "E", "F", "F♯", "G", "G♯", "A", "A♯", "B"];
+const MAX_NOTE: i32 = 127;
+/// The note number of A4, tuned to [`A4_FREQUENCY`].
+const A4_NOTE: i32 = 69;
+const A4_FREQUENCY: f32 = 440.0;
+/// The smallest step that can be entered.
+const MIN_STEP: f64 = 0.001;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum QuantizeMode {
+    /// Rounds to multiples of the step.
+    Step,
+    /// Limits the value to a range.
+    Clamp,
+    /// Maps the value to the nearest note of a musical scale.
+    Scale,
+}
+
+impl QuantizeMode {
+    pub const VALUES: [QuantizeMode; 3] = [QuantizeMode::Step, QuantizeMode::Clamp, QuantizeMode::Scale];
+}
+
+impl Display for QuantizeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuantizeMode::Step => write!(f, "Step"),
+            QuantizeMode::Clamp => write!(f, "Clamp"),
+            QuantizeMode::Scale => write!(f, "Scale"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Rounding {
+    Floor,
+    Round,
+    Ceil,
+}
+
+impl Rounding {
+    pub const VALUES: [Rounding; 3] = [Rounding::Floor, Rounding::Round, Rounding::Ceil];
+
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Rounding::Floor => value.floor(),
+            Rounding::Round => value.round(),
+            Rounding::Ceil => value.ceil(),
+        }
+    }
+}
+
+impl Display for Rounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rounding::Floor => write!(f, "Floor"),
+            Rounding::Round => write!(f, "Round"),
+            Rounding::Ceil => write!(f, "Ceil"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Scale {
+    Chromatic,
+    Major,
+    Minor,
+    Pentatonic,
+}
+
+impl Scale {
+    pub const VALUES: [Scale; 4] = [Scale::Chromatic, Scale::Major, Scale::Minor, Scale::Pentatonic];
+
+    /// The semitones of the notes of the scale, relative to the root note.
+    pub fn intervals(self) -> &'static [i32] {
+        match self {
+            Scale::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            Scale::Pentatonic => &[0, 2, 4, 7, 9],
+        }
+    }
+
+    /// Finds the note of the scale nearest to the continuous note number.
+    pub fn nearest_note(self, root: RootNote, note: f32) -> i32 {
+        let relative = note - root.0 as f32;
+        let octave = (relative / 12.0).floor() as i32;
+
+        // The nearest note is either in the same octave or at the boundary of a neighbouring one.
+        (octave - 1..=octave + 1)
+            .flat_map(|octave| self.intervals().iter().map(move |interval| octave * 12 + interval))
+            .map(|relative_note| relative_note + root.0 as i32)
+            .filter(|note| (0..=MAX_NOTE).contains(note))
+            .min_by(|a, b| {
+                let distance_a = (*a as f32 - note).abs();
+                let distance_b = (*b as f32 - note).abs();
+                distance_a.partial_cmp(&distance_b).unwrap()
+            })
+            .unwrap_or_else(|| (note.round() as i32).max(0).min(MAX_NOTE))
+    }
+}
+
+impl Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scale::Chromatic => write!(f, "Chromatic"),
+            Scale::Major => write!(f, "Major"),
+            Scale::Minor => write!(f, "Minor"),
+            Scale::Pentatonic => write!(f, "Pentatonic"),
+        }
+    }
+}
+
+/// The pitch class of the root note of a scale, `0` being C.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct RootNote(u8);
+
+impl RootNote {
+    pub const VALUES: [RootNote; 12] = [
+        RootNote(0),
+        RootNote(1),
+        RootNote(2),
+        RootNote(3),
+        RootNote(4),
+        RootNote(5),
+        RootNote(6),
+        RootNote(7),
+        RootNote(8),
+        RootNote(9),
+        RootNote(10),
+        RootNote(11),
+    ];
+}
+
+impl Display for RootNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", NOTE_NAMES[self.0 as usize])
+    }
+}
+
+/// How the input is interpreted in the [`QuantizeMode::Scale`] mode.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum ScaleInput {
+    /// A value from 0 to 1, spanning the whole range of notes.
+    Normalized,
+    /// A continuous MIDI note number.
+    Note,
+}
+
+impl ScaleInput {
+    pub const VALUES: [ScaleInput; 2] = [ScaleInput::Normalized, ScaleInput::Note];
+}
+
+impl Display for ScaleInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScaleInput::Normalized => write!(f, "0–1"),
+            ScaleInput::Note => write!(f, "Note"),
+        }
+    }
+}
+
+/// What the output holds in the [`QuantizeMode::Scale`] mode.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum ScaleOutput {
+    /// The MIDI note number, as a `u8`.
+    Note,
+    /// The frequency of the note in Hz, as an `f32`.
+    Frequency,
+}
+
+impl ScaleOutput {
+    pub const VALUES: [ScaleOutput; 2] = [ScaleOutput::Note, ScaleOutput::Frequency];
+}
+
+impl Display for ScaleOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScaleOutput::Note => write!(f, "Note"),
+            ScaleOutput::Frequency => write!(f, "Hz"),
+        }
+    }
+}
+
+/// All parameters that affect the output.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuantizeSettings {
+    pub mode: QuantizeMode,
+    pub rounding: Rounding,
+    /// Always positive.
+    pub step: f32,
+    pub min: f32,
+    pub max: f32,
+    pub scale: Scale,
+    pub root: RootNote,
+    pub scale_input: ScaleInput,
+    pub scale_output: ScaleOutput,
+}
+
+impl Default for QuantizeSettings {
+    fn default() -> Self {
+        Self {
+            mode: QuantizeMode::Step,
+            rounding: Rounding::Round,
+            step: 1.0,
+            min: 0.0,
+            max: 1.0,
+            scale: Scale::Major,
+            root: RootNote(0),
+            scale_input: ScaleInput::Note,
+            scale_output: ScaleOutput::Note,
+        }
+    }
+}
+
+impl QuantizeSettings {
+    pub fn output_ty(&self) -> PrimitiveTypeEnum {
+        match (self.mode, self.scale_output) {
+            (QuantizeMode::Scale, ScaleOutput::Note) => PrimitiveTypeEnum::U8,
+            _ => PrimitiveTypeEnum::F32,
+        }
+    }
+
+    pub fn output_title(&self) -> &'static str {
+        match (self.mode, self.scale_output) {
+            (QuantizeMode::Scale, ScaleOutput::Note) => "note",
+            (QuantizeMode::Scale, ScaleOutput::Frequency) => "frequency",
+            _ => "result",
+        }
+    }
+
+    pub fn apply(&self, value: f32) -> PrimitiveChannelValue {
+        match self.mode {
+            QuantizeMode::Step => (self.rounding.apply(value / self.step) * self.step).into(),
+            QuantizeMode::Clamp => value.max(self.min).min(self.max).into(),
+            QuantizeMode::Scale => {
+                let note = match self.scale_input {
+                    ScaleInput::Normalized => value * MAX_NOTE as f32,
+                    ScaleInput::Note => value,
+                };
+                let note = self.scale.nearest_note(self.root, note);
+
+                match self.scale_output {
+                    ScaleOutput::Note => (note as u8).into(),
+                    ScaleOutput::Frequency => {
+                        (A4_FREQUENCY * 2_f32.powf((note - A4_NOTE) as f32 / 12.0)).into()
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum QuantizeNodeMessage {
+    UpdateMode(QuantizeMode),
+    UpdateRounding(Rounding),
+    UpdateStep(f32),
+    UpdateMin(f32),
+    UpdateMax(f32),
+    UpdateScale(Scale),
+    UpdateRoot(RootNote),
+    UpdateScaleInput(ScaleInput),
+    UpdateScaleOutput(ScaleOutput),
+}
+
+#[derive(Debug, Clone, Default)]
+struct QuantizeUiState {
+    mode_state: pick_list::State<QuantizeMode>,
+    rounding_state: pick_list::State<Rounding>,
+    step_state: drag_value::State,
+    min_state: drag_value::State,
+    max_state: drag_value::State,
+    scale_state: pick_list::State<Scale>,
+    root_state: pick_list::State<RootNote>,
+    scale_input_state: pick_list::State<ScaleInput>,
+    scale_output_state: pick_list::State<ScaleOutput>,
+}
+
+/// Rounds the input to a step, limits it to a range, or maps it to the notes of a scale.
+#[derive(Debug, Clone)]
+pub struct QuantizeNodeBehaviour {
+    settings: QuantizeSettings,
+    ui_state: QuantizeUiState,
+}
+
+impl Default for QuantizeNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl QuantizeNodeBehaviour {
+    pub fn with_settings(settings: QuantizeSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_input_value(Channel::new("value", PrimitiveType::<f32>::default()))
+                .with_output_value(Channel::new(self.settings.output_title(), self.settings.output_ty())),
+        )
+    }
+}
+
+impl NodeBehaviour for QuantizeNodeBehaviour {
+    type Message = QuantizeNodeMessage;
+
+    fn name(&self) -> &str {
+        "Quantize"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use QuantizeNodeMessage::*;
+                let mut commands = Vec::new();
+
+                match message {
+                    UpdateMode(mode) => {
+                        self.settings.mode = mode;
+                        commands.push(self.get_configure_command());
+                    }
+                    UpdateRounding(rounding) => {
+                        self.settings.rounding = rounding;
+                    }
+                    // The fields keep the step positive and the minimum below the maximum.
+                    UpdateStep(step) => {
+                        self.settings.step = step;
+                    }
+                    UpdateMin(min) => {
+                        self.settings.min = min;
+                    }
+                    UpdateMax(max) => {
+                        self.settings.max = max;
+                    }
+                    UpdateScale(scale) => {
+                        self.settings.scale = scale;
+                    }
+                    UpdateRoot(root) => {
+                        self.settings.root = root;
+                    }
+                    UpdateScaleInput(scale_input) => {
+                        self.settings.scale_input = scale_input;
+                    }
+                    UpdateScaleOutput(scale_output) => {
+                        self.settings.scale_output = scale_output;
+                        commands.push(self.get_configure_command());
+                    }
+                }
+
+                commands
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+        let mode_row = Row::new()
+            .theme(theme)
+            .push(
+                // Wrap PickList in a container because PickList's width resolution is buggy
+                Container::new(
+                    PickList::new(
+                        &mut ui_state.mode_state,
+                        &QuantizeMode::VALUES[..],
+                        Some(settings.mode),
+                        QuantizeNodeMessage::UpdateMode,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .width(Length::Fill),
+            )
+            .align_items(Align::Center)
+            .width(Length::Fill);
+        let mut column = Column::new().theme(theme).push(mode_row);
+
+        column = match settings.mode {
+            QuantizeMode::Step => column.push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        PickList::new(
+                            &mut ui_state.rounding_state,
+                            &Rounding::VALUES[..],
+                            Some(settings.rounding),
+                            QuantizeNodeMessage::UpdateRounding,
+                        )
+                        .theme(theme)
+                        .width(Length::Units(64)),
+                    )
+                    .push(
+                        DragValue::new(&mut ui_state.step_state, settings.step, |step| {
+                            QuantizeNodeMessage::UpdateStep(step as f32)
+                        })
+                        .label("Step")
+                        .speed(0.01)
+                        .range(MIN_STEP, f64::INFINITY)
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            ),
+            QuantizeMode::Clamp => column.push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        DragValue::new(&mut ui_state.min_state, settings.min, |min| {
+                            QuantizeNodeMessage::UpdateMin(min as f32)
+                        })
+                        .label("Min")
+                        .speed(0.01)
+                        .range(f64::NEG_INFINITY, settings.max as f64)
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        DragValue::new(&mut ui_state.max_state, settings.max, |max| {
+                            QuantizeNodeMessage::UpdateMax(max as f32)
+                        })
+                        .label("Max")
+                        .speed(0.01)
+                        .range(settings.min as f64, f64::INFINITY)
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            ),
+            QuantizeMode::Scale => column
+                .push(
+                    Row::new()
+                        .theme(theme)
+                        .push(
+                            PickList::new(
+                                &mut ui_state.root_state,
+                                &RootNote::VALUES[..],
+                                Some(settings.root),
+                                QuantizeNodeMessage::UpdateRoot,
+                            )
+                            .theme(theme)
+                            .width(Length::Units(48)),
+                        )
+                        .push(
+                            Container::new(
+                                PickList::new(
+                                    &mut ui_state.scale_state,
+                                    &Scale::VALUES[..],
+                                    Some(settings.scale),
+                                    QuantizeNodeMessage::UpdateScale,
+                                )
+                                .theme(theme)
+                                .width(Length::Fill),
+                            )
+                            .width(Length::Fill),
+                        )
+                        .align_items(Align::Center)
+                        .width(Length::Fill),
+                )
+                .push(
+                    Row::new()
+                        .theme(theme)
+                        .push(Text::new("From").size(consts::TEXT_SIZE_REGULAR))
+                        .push(
+                            PickList::new(
+                                &mut ui_state.scale_input_state,
+                                &ScaleInput::VALUES[..],
+                                Some(settings.scale_input),
+                                QuantizeNodeMessage::UpdateScaleInput,
+                            )
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .push(Text::new("to").size(consts::TEXT_SIZE_REGULAR))
+                        .push(
+                            PickList::new(
+                                &mut ui_state.scale_output_state,
+                                &ScaleOutput::VALUES[..],
+                                Some(settings.scale_output),
+                                QuantizeNodeMessage::UpdateScaleOutput,
+                            )
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .align_items(Align::Center)
+                        .width(Length::Fill),
+                ),
+        };
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: QuantizeSettings = serde_json::from_value(settings)?;
+
+        if !(settings.step > 0.0) {
+            return Err(<serde_json::Error as serde::de::Error>::custom(
+                "the step must be greater than zero",
+            ));
+        }
+
+        if settings.root.0 as usize >= NOTE_NAMES.len() {
+            return Err(<serde_json::Error as serde::de::Error>::custom("invalid root note"));
+        }
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let value = match PrimitiveTypeEnum::F32
+                        .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                        .unwrap()
+                    {
+                        PrimitiveChannelValue::F32(value) => value,
+                        _ => unreachable!(),
+                    };
+                    let result = settings.apply(value);
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            let mut output_cursor = Cursor::new(bytes);
+
+                            result.write::<LittleEndian>(&mut output_cursor).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{
+    style::{Theme, Themeable},
+    widgets::{drag_value, DragValue},
+};
+use iced::{Element, Length};
+
+/// A trigger is received once the input rises above this value.
+pub const TRIGGER_THRESHOLD: f32 = 0.5;
+
+/// A seeded pseudorandom number generator (SplitMix64), used by the nodes producing random
+/// values, so that their sequences are reproducible from their seeds.
+#[derive(Debug, Clone)]
+pub struct Prng {
+    state: u64,
+}
+
+impl Prng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed from 0, inclusive, to 1, exclusive.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns `true` with the probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+
+    /// Chooses an index with a probability proportional to its weight. The weights are normalized
+    /// on every call, so that they may change between calls. If the weights sum to zero, the index
+    /// is chosen uniformly. `None` if there are no weights.
+    pub fn choose_weighted(&mut self, weights: &[f32]) -> Option<usize> {
+        if weights.is_empty() {
+            return None;
+        }
+
+        let total: f64 = weights.iter().map(|weight| *weight as f64).sum();
+
+        if total <= 0.0 {
+            return Some((self.next_f64() * weights.len() as f64) as usize);
+        }
+
+        let mut remaining = self.next_f64() * total;
+
+        for (index, weight) in weights.iter().enumerate() {
+            remaining -= *weight as f64;
+
+            if remaining < 0.0 {
+                return Some(index);
+            }
+        }
+
+        // Reached only due to rounding errors, choose the last index with a non-zero weight.
+        weights.iter().rposition(|weight| *weight > 0.0)
+    }
+}
+
+/// The generator of a node, along with the seed it was created from, so that it is only recreated
+/// once the seed is changed.
+#[derive(Debug, Clone, Default)]
+pub struct SeededPrng {
+    /// `None` until the node is first executed.
+    prng: Option<(u32, Prng)>,
+}
+
+impl SeededPrng {
+    /// The generator, recreated if it was created from a different seed.
+    pub fn get(&mut self, seed: u32) -> &mut Prng {
+        if self.prng.as_ref().map(|(prng_seed, _)| *prng_seed) != Some(seed) {
+            self.prng = Some((seed, Prng::new(seed as u64)));
+        }
+
+        &mut self.prng.as_mut().unwrap().1
+    }
+}
+
+/// Tracks the trigger input of a node, to detect the executions at which it rises.
+#[derive(Debug, Clone, Default)]
+pub struct TriggerInput {
+    high: bool,
+}
+
+impl TriggerInput {
+    /// Returns whether the input is high and whether it has just risen.
+    pub fn advance(&mut self, value: f32) -> (bool, bool) {
+        let high = value > TRIGGER_THRESHOLD;
+        let rising = high && !self.high;
+
+        self.high = high;
+
+        (high, rising)
+    }
+}
+
+/// A field for the seed of the generator.
+pub fn seed_field<'a, M: 'static>(
+    state: &'a mut drag_value::State,
+    seed: u32,
+    on_change: fn(u32) -> M,
+    theme: &dyn Theme,
+) -> Element<'a, M> {
+    DragValue::new(state, seed, move |seed| on_change(seed as u32))
+        .label("Seed")
+        .speed(1.0)
+        .step(1.0)
+        .range(0.0, u32::MAX as f64)
+        .theme(theme)
+        .width(Length::Fill)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: usize = 100_000;
+
+    /// The critical values of the chi-squared distribution at the significance level of 0.001, for
+    /// 1 to 4 degrees of freedom.
+    const CRITICAL_VALUES: [f64; 4] = [10.828, 13.816, 16.266, 18.467];
+
+    /// Asserts that the observed counts follow the expected probabilities, by Pearson's
+    /// chi-squared test.
+    fn assert_distribution(observed: &[usize], probabilities: &[f64]) {
+        let total = observed.iter().sum::<usize>() as f64;
+        let mut outcomes = 0;
+        let mut chi_squared = 0.0;
+
+        for (observed, probability) in observed.iter().zip(probabilities) {
+            if *probability == 0.0 {
+                assert_eq!(*observed, 0, "an impossible outcome was observed");
+                continue;
+            }
+
+            let expected = probability * total;
+
+            chi_squared += (*observed as f64 - expected).powi(2) / expected;
+            outcomes += 1;
+        }
+
+        // The degrees of freedom are one less than the number of possible outcomes.
+        let critical_value = CRITICAL_VALUES[outcomes - 2];
+
+        assert!(
+            chi_squared < critical_value,
+            "{:?} do not follow {:?}, chi-squared {}",
+            observed,
+            probabilities,
+            chi_squared
+        );
+    }
+
+    #[test]
+    fn chances_follow_the_probability() {
+        for (seed, probability) in vec![0.01, 0.1, 0.5, 0.9].into_iter().enumerate() {
+            let mut prng = Prng::new(seed as u64);
+            let passed = (0..SAMPLES).filter(|_| prng.chance(probability)).count();
+
+            assert_distribution(&[passed, SAMPLES - passed], &[probability, 1.0 - probability]);
+        }
+
+        let mut prng = Prng::new(0);
+
+        assert!((0..SAMPLES).all(|_| !prng.chance(0.0)));
+        assert!((0..SAMPLES).all(|_| prng.chance(1.0)));
+    }
+
+    #[test]
+    fn choices_follow_the_weights() {
+        let weight_sets: Vec<Vec<f32>> = vec![
+            vec![1.0, 1.0],
+            vec![1.0, 2.0, 3.0, 4.0],
+            vec![0.0, 1.0, 0.0, 3.0],
+            vec![0.5, 0.25, 0.125, 0.125],
+        ];
+
+        for (seed, weights) in weight_sets.into_iter().enumerate() {
+            let mut prng = Prng::new(seed as u64);
+            let mut counts = vec![0; weights.len()];
+
+            for _ in 0..SAMPLES {
+                counts[prng.choose_weighted(&weights).unwrap()] += 1;
+            }
+
+            let total = weights.iter().sum::<f32>() as f64;
+            let probabilities = weights.iter().map(|weight| *weight as f64 / total).collect::<Vec<_>>();
+
+            assert_distribution(&counts, &probabilities);
+        }
+    }
+
+    #[test]
+    fn choices_without_weights_are_uniform() {
+        let mut prng = Prng::new(0);
+        let mut counts = vec![0; 3];
+
+        for _ in 0..SAMPLES {
+            counts[prng.choose_weighted(&[0.0, 0.0, 0.0]).unwrap()] += 1;
+        }
+
+        assert_distribution(&counts, &[1.0 / 3.0; 3]);
+        assert_eq!(prng.choose_weighted(&[]), None);
+    }
+
+    #[test]
+    fn sequences_are_reproducible_from_the_seed() {
+        let mut seeded = SeededPrng::default();
+        let first = (0..8).map(|_| seeded.get(7).next_u64()).collect::<Vec<_>>();
+
+        seeded.get(8);
+
+        let second = (0..8).map(|_| seeded.get(7).next_u64()).collect::<Vec<_>>();
+
+        assert_eq!(first, second);
+    }
+}
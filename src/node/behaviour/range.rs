@@ -0,0 +1,376 @@
+use crate::node::prelude::*;
+use crate::node::{ListDescriptor, ListType, OwnedRefMut, PrimitiveChannelValue, Unique};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ConnectedTypeInfo, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure, NodeValidationIssue,
+        },
+        Channel, NodeConfiguration, PrimitiveTypeEnum,
+    },
+    style::{consts, Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::LittleEndian;
+use iced::{
+    text_input::{self, TextInput},
+    Align, Column, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+/// The types the items of a range can be computed for.
+pub trait RangeItem: Copy {
+    /// The item at the index. Integers wrap around on overflow.
+    fn nth(start: Self, step: Self, index: usize) -> Self;
+}
+
+macro_rules! impl_range_item {
+    (integers: $($integer:ty),*; floats: $($float:ty),*$(;)?) => {
+        $(
+            impl RangeItem for $integer {
+                fn nth(start: Self, step: Self, index: usize) -> Self {
+                    // Truncating the index is consistent with the wrapping multiplication.
+                    start.wrapping_add(step.wrapping_mul(index as $integer))
+                }
+            }
+        )*
+
+        $(
+            impl RangeItem for $float {
+                fn nth(start: Self, step: Self, index: usize) -> Self {
+                    // Multiplied rather than accumulated, so that the rounding errors do not add up.
+                    start + step * index as $float
+                }
+            }
+        )*
+    };
+}
+
+impl_range_item! {
+    integers: u8, u16, u32, u64, u128, i8, i16, i32, i64, i128;
+    floats: f32, f64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RangeSettings {
+    /// Determines the type of the items, always of the same type as `step`.
+    pub start: PrimitiveChannelValue,
+    pub step: PrimitiveChannelValue,
+    pub count: usize,
+}
+
+impl Default for RangeSettings {
+    fn default() -> Self {
+        Self { start: PrimitiveChannelValue::F32(0.0), step: PrimitiveChannelValue::F32(1.0), count: 16 }
+    }
+}
+
+impl RangeSettings {
+    pub fn ty(&self) -> PrimitiveTypeEnum {
+        self.start.ty()
+    }
+
+    pub fn nth(&self, index: usize) -> PrimitiveChannelValue {
+        use PrimitiveChannelValue::*;
+        match (self.start, self.step) {
+            (U8(start), U8(step)) => U8(RangeItem::nth(start, step, index)),
+            (U16(start), U16(step)) => U16(RangeItem::nth(start, step, index)),
+            (U32(start), U32(step)) => U32(RangeItem::nth(start, step, index)),
+            (U64(start), U64(step)) => U64(RangeItem::nth(start, step, index)),
+            (U128(start), U128(step)) => U128(RangeItem::nth(start, step, index)),
+            (I8(start), I8(step)) => I8(RangeItem::nth(start, step, index)),
+            (I16(start), I16(step)) => I16(RangeItem::nth(start, step, index)),
+            (I32(start), I32(step)) => I32(RangeItem::nth(start, step, index)),
+            (I64(start), I64(step)) => I64(RangeItem::nth(start, step, index)),
+            (I128(start), I128(step)) => I128(RangeItem::nth(start, step, index)),
+            (F32(start), F32(step)) => F32(RangeItem::nth(start, step, index)),
+            (F64(start), F64(step)) => F64(RangeItem::nth(start, step, index)),
+            _ => panic!("The start and the step of a range must be of the same type."),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RangeNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    UpdateStart(String),
+    UpdateStep(String),
+    UpdateCount(String),
+}
+
+#[derive(Debug, Clone, Default)]
+struct RangeUiState {
+    ty_state: enum_dropdown::State,
+    start_state: text_input::State,
+    start_string: String,
+    step_state: text_input::State,
+    step_string: String,
+    count_state: text_input::State,
+    count_string: String,
+    /// Describes why the last entered parameters were rejected.
+    error: Option<&'static str>,
+}
+
+/// Generates a list of evenly spaced numbers.
+#[derive(Debug, Clone)]
+pub struct RangeNodeBehaviour {
+    settings: RangeSettings,
+    ui_state: RangeUiState,
+}
+
+impl Default for RangeNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl RangeNodeBehaviour {
+    pub fn with_settings(settings: RangeSettings) -> Self {
+        Self {
+            settings,
+            ui_state: RangeUiState {
+                start_string: settings.start.value_to_string(),
+                step_string: settings.step.value_to_string(),
+                count_string: settings.count.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(NodeConfiguration::default().with_output_value(Channel::new(
+            "list",
+            Unique::new(ListType::new_if_sized(self.settings.ty()).unwrap()),
+        )))
+    }
+
+    /// Applies the parameters entered into the text fields, if they are valid.
+    fn update_parameters(&mut self) {
+        let ty = self.settings.ty();
+        let ui_state = &self.ui_state;
+
+        self.ui_state.error = match (
+            ty.parse(&ui_state.start_string),
+            ty.parse(&ui_state.step_string),
+            ui_state.count_string.parse::<usize>(),
+        ) {
+            (Some(start), Some(step), Ok(count)) => {
+                self.settings = RangeSettings { start, step, count };
+                None
+            }
+            (_, _, Err(_)) => Some("The count must be a non-negative integer."),
+            _ => Some("The start and the step must be values of the selected type."),
+        };
+    }
+}
+
+impl NodeBehaviour for RangeNodeBehaviour {
+    type Message = RangeNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Range"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Collection)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use RangeNodeMessage::*;
+                let mut commands = Vec::new();
+
+                match message {
+                    UpdateType(ty) => {
+                        // Fall back to the default values, so that the type changes even if the
+                        // entered values are not valid for it.
+                        self.settings.start =
+                            ty.parse(&self.ui_state.start_string).unwrap_or_else(|| ty.default_value());
+                        self.settings.step =
+                            ty.parse(&self.ui_state.step_string).unwrap_or_else(|| ty.default_value());
+                        self.update_parameters();
+                        commands.push(self.get_configure_command());
+                    }
+                    UpdateStart(string) => {
+                        self.ui_state.start_string = string;
+                        self.update_parameters();
+                    }
+                    UpdateStep(string) => {
+                        self.ui_state.step_string = string;
+                        self.update_parameters();
+                    }
+                    UpdateCount(string) => {
+                        self.ui_state.count_string = string;
+                        self.update_parameters();
+                    }
+                }
+
+                commands
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let ui_state = &mut self.ui_state;
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        EnumDropdown::new(
+                            &mut ui_state.ty_state,
+                            &PrimitiveTypeEnum::VALUES[..],
+                            self.settings.ty(),
+                            RangeNodeMessage::UpdateType,
+                        )
+                        .theme(theme)
+                        .width(Length::Units(64)),
+                    )
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.count_state,
+                            "Count",
+                            &ui_state.count_string,
+                            RangeNodeMessage::UpdateCount,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            )
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.start_state,
+                            "Start",
+                            &ui_state.start_string,
+                            RangeNodeMessage::UpdateStart,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.step_state,
+                            "Step",
+                            &ui_state.step_string,
+                            RangeNodeMessage::UpdateStep,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+
+        if let Some(error) = ui_state.error {
+            column = column.push(Text::new(error).size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: RangeSettings = serde_json::from_value(settings)?;
+
+        if settings.start.ty() != settings.step.ty() {
+            return Err(<serde_json::Error as serde::de::Error>::custom(
+                "the start and the step of a range must be of the same type",
+            ));
+        }
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _configuration: &NodeConfiguration,
+        _connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        let mut issues = Vec::new();
+
+        if self.settings.count == 0 {
+            issues.push(NodeValidationIssue::warning("The count is zero, the generated list is empty."));
+        } else if self.settings.count > 1 && self.settings.step == self.settings.ty().default_value() {
+            issues.push(NodeValidationIssue::warning("The step is zero, all items of the list are equal."));
+        }
+
+        issues
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let max_list_length = context.application_context.config.get().max_list_length;
+
+                    if settings.count > max_list_length {
+                        context.report_error(format!(
+                            "The count exceeds the maximum length of generated lists, {}, which can be \
+                             changed in the preferences.",
+                            max_list_length,
+                        ));
+                        return;
+                    }
+
+                    // Keep the list of the previous execution, unless it has been taken by another
+                    // node or the settings have changed since.
+                    if persistent.generated == Some(settings) && context.outputs[0].is_some() {
+                        return;
+                    }
+
+                    let mut list: OwnedRefMut<Unique<ListType>> = context
+                        .allocator_handle
+                        .allocate_object::<ListType>(ListDescriptor::new_if_sized(settings.ty()).unwrap());
+
+                    {
+                        let mut list = list.deref_mut();
+
+                        for index in 0..settings.count {
+                            let item = settings.nth(index);
+
+                            list.push_item_bytes_with(|bytes| {
+                                item.write::<LittleEndian>(&mut Cursor::new(bytes)).unwrap();
+                            })
+                            .unwrap();
+                        }
+                    }
+
+                    context.outputs[0].replace(list.upcast(), context.allocator_handle).unwrap();
+                    persistent.generated = Some(settings);
+                    context.clear_error();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    /// The settings the list in the output was generated with.
+    generated: Option<RangeSettings>,
+}
@@ -0,0 +1,332 @@
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        Channel, NodeConfiguration, OptionRefMutExt, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{drag_value, enum_dropdown, range_slider, DragValue, EnumDropdown, RangeSlider},
+};
+use byteorder::LittleEndian;
+use iced::{Align, Column, Element, Length, Row};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Cursor;
+
+/// What happens when a handle is dragged past the other one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HandleCrossing {
+    /// The dragged handle stops at the other one.
+    Clamp,
+    /// The dragged handle pushes the other one along.
+    Push,
+}
+
+impl HandleCrossing {
+    pub const VALUES: [HandleCrossing; 2] = [HandleCrossing::Clamp, HandleCrossing::Push];
+}
+
+impl Display for HandleCrossing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandleCrossing::Clamp => write!(f, "Clamp"),
+            HandleCrossing::Push => write!(f, "Push"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RangeSliderSettings {
+    pub ty: PrimitiveTypeEnum,
+    /// The bounds of the track.
+    pub track_min: f64,
+    pub track_max: f64,
+    /// The values are rounded to the minimum of the track plus multiples of the step, or not at all
+    /// if it is `0.0`.
+    pub step: f64,
+    pub min: f64,
+    pub max: f64,
+    pub crossing: HandleCrossing,
+}
+
+impl Default for RangeSliderSettings {
+    fn default() -> Self {
+        Self {
+            ty: PrimitiveTypeEnum::F32,
+            track_min: 0.0,
+            track_max: 1.0,
+            step: 0.0,
+            min: 0.25,
+            max: 0.75,
+            crossing: HandleCrossing::Clamp,
+        }
+    }
+}
+
+impl RangeSliderSettings {
+    fn validate(&self) -> Result<(), &'static str> {
+        let track_ordered =
+            self.track_min.is_finite() && self.track_max.is_finite() && self.track_min < self.track_max;
+
+        if !track_ordered {
+            Err("The minimum of the track must be less than its maximum.")
+        } else if !(self.step.is_finite() && self.step >= 0.0) {
+            Err("The step must not be negative.")
+        } else if !(self.track_min <= self.min && self.min <= self.max && self.max <= self.track_max) {
+            Err("The range must be ordered and within the track.")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Moves the handle with the index to the value, applying the crossing behaviour.
+    fn move_handle(&mut self, handle: usize, value: f64) {
+        let value = value.max(self.track_min).min(self.track_max);
+
+        match (handle, self.crossing) {
+            (0, HandleCrossing::Clamp) => self.min = value.min(self.max),
+            (0, HandleCrossing::Push) => {
+                self.min = value;
+                self.max = self.max.max(value);
+            }
+            (_, HandleCrossing::Clamp) => self.max = value.max(self.min),
+            (_, HandleCrossing::Push) => {
+                self.max = value;
+                self.min = self.min.min(value);
+            }
+        }
+    }
+
+    /// Keeps the range within the track, once its bounds have changed.
+    fn clamp_to_track(&mut self) {
+        self.min = self.min.max(self.track_min).min(self.track_max);
+        self.max = self.max.max(self.min).min(self.track_max);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RangeSliderNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    /// Moves the handle with the index, 0 for the minimum and 1 for the maximum.
+    UpdateHandle(usize, f64),
+    UpdateTrackMin(f64),
+    UpdateTrackMax(f64),
+    UpdateStep(f64),
+    UpdateCrossing(HandleCrossing),
+}
+
+#[derive(Debug, Clone, Default)]
+struct RangeSliderUiState {
+    type_state: enum_dropdown::State,
+    crossing_state: enum_dropdown::State,
+    slider_state: range_slider::State,
+    track_min_state: drag_value::State,
+    track_max_state: drag_value::State,
+    step_state: drag_value::State,
+}
+
+/// Outputs a pair of numbers chosen by dragging the two handles of a slider, for the nodes
+/// expecting a range, along with the span between them.
+#[derive(Debug, Clone)]
+pub struct RangeSliderNodeBehaviour {
+    settings: RangeSliderSettings,
+    ui_state: RangeSliderUiState,
+}
+
+impl Default for RangeSliderNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl RangeSliderNodeBehaviour {
+    pub fn with_settings(settings: RangeSliderSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_output_value(Channel::new("min", self.settings.ty))
+                .with_output_value(Channel::new("max", self.settings.ty))
+                .with_output_value(
+                    Channel::new("span", self.settings.ty).with_description("The maximum minus the minimum"),
+                ),
+        )
+    }
+}
+
+impl NodeBehaviour for RangeSliderNodeBehaviour {
+    type Message = RangeSliderNodeMessage;
+
+    fn name(&self) -> &str {
+        "Range Slider"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Value)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use RangeSliderNodeMessage::*;
+                let settings = &mut self.settings;
+
+                match message {
+                    UpdateType(ty) => {
+                        settings.ty = ty;
+                        return vec![self.get_configure_command()];
+                    }
+                    UpdateHandle(handle, value) => settings.move_handle(handle, value),
+                    // The bounds of the track must not meet.
+                    UpdateTrackMin(track_min) if track_min < settings.track_max => {
+                        settings.track_min = track_min;
+                        settings.clamp_to_track();
+                    }
+                    UpdateTrackMax(track_max) if track_max > settings.track_min => {
+                        settings.track_max = track_max;
+                        settings.clamp_to_track();
+                    }
+                    UpdateTrackMin(_) | UpdateTrackMax(_) => (),
+                    UpdateStep(step) => settings.step = step,
+                    UpdateCrossing(crossing) => settings.crossing = crossing,
+                }
+
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+
+        Some(
+            Column::new()
+                .theme(theme)
+                .push(
+                    Row::new()
+                        .theme(theme)
+                        .push(
+                            EnumDropdown::new(
+                                &mut ui_state.type_state,
+                                &PrimitiveTypeEnum::VALUES[..],
+                                settings.ty,
+                                RangeSliderNodeMessage::UpdateType,
+                            )
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .push(
+                            EnumDropdown::new(
+                                &mut ui_state.crossing_state,
+                                &HandleCrossing::VALUES[..],
+                                settings.crossing,
+                                RangeSliderNodeMessage::UpdateCrossing,
+                            )
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .align_items(Align::Center)
+                        .width(Length::Fill),
+                )
+                .push(
+                    RangeSlider::new(
+                        &mut ui_state.slider_state,
+                        vec![settings.min, settings.max],
+                        settings.track_min,
+                        settings.track_max,
+                        RangeSliderNodeMessage::UpdateHandle,
+                    )
+                    .step(settings.step)
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(
+                    Row::new()
+                        .theme(theme)
+                        .push(
+                            DragValue::new(
+                                &mut ui_state.track_min_state,
+                                settings.track_min,
+                                RangeSliderNodeMessage::UpdateTrackMin,
+                            )
+                            .label("From")
+                            .range(f64::NEG_INFINITY, settings.track_max)
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .push(
+                            DragValue::new(
+                                &mut ui_state.track_max_state,
+                                settings.track_max,
+                                RangeSliderNodeMessage::UpdateTrackMax,
+                            )
+                            .label("To")
+                            .range(settings.track_min, f64::INFINITY)
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .push(
+                            DragValue::new(
+                                &mut ui_state.step_state,
+                                settings.step,
+                                RangeSliderNodeMessage::UpdateStep,
+                            )
+                            .label("Step")
+                            .range(0.0, f64::INFINITY)
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .align_items(Align::Center)
+                        .width(Length::Fill),
+                )
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: RangeSliderSettings = serde_json::from_value(settings)?;
+
+        settings.validate().map_err(<serde_json::Error as serde::de::Error>::custom)?;
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+                let values = [settings.min, settings.max, settings.max - settings.min];
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    for (output, value) in context.outputs.iter_mut().zip(values.iter()) {
+                        let value = settings.ty.from_f64(*value);
+
+                        output
+                            .replace_with_bytes(context.allocator_handle, |bytes| {
+                                value.write::<LittleEndian>(&mut Cursor::new(bytes)).unwrap();
+                            })
+                            .unwrap();
+                    }
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
@@ -0,0 +1,387 @@
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ContextMenuItem, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::{ByteOrder, LittleEndian};
+use iced::widget::checkbox::Checkbox;
+use iced::{
+    button::{self, Button},
+    Align, Column, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+
+/// The minimum, maximum, mean and variance of the samples since the last reset.
+///
+/// The mean and the variance are updated incrementally by Welford's algorithm, which does not
+/// lose precision to the cancellation of large sums, however many samples there are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunningStats {
+    count: u64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    /// The sum of the squared differences from the mean.
+    squared_deviations: f64,
+}
+
+impl Default for RunningStats {
+    fn default() -> Self {
+        Self { count: 0, min: 0.0, max: 0.0, mean: 0.0, squared_deviations: 0.0 }
+    }
+}
+
+impl RunningStats {
+    /// Adds the sample, which must not be NaN.
+    pub fn push(&mut self, sample: f64) {
+        if self.count == 0 {
+            self.min = sample;
+            self.max = sample;
+        } else {
+            self.min = self.min.min(sample);
+            self.max = self.max.max(sample);
+        }
+
+        self.count += 1;
+
+        let deviation = sample - self.mean;
+
+        self.mean += deviation / self.count as f64;
+        self.squared_deviations += deviation * (sample - self.mean);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Zero until the first sample, as are the maximum and the mean.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// The sample variance, with Bessel's correction, zero until the second sample.
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.squared_deviations / (self.count - 1) as f64
+        }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RunningStatsSettings {
+    /// The type of the sampled value.
+    pub ty: PrimitiveTypeEnum,
+    /// Whether the statistics can be reset through an input channel.
+    pub reset_input: bool,
+}
+
+impl Default for RunningStatsSettings {
+    fn default() -> Self {
+        Self { ty: PrimitiveTypeEnum::F32, reset_input: false }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RunningStatsNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    UpdateResetInput(bool),
+    Reset,
+}
+
+/// Accumulates the statistics of a value over the executions since the last reset.
+#[derive(Debug, Clone, Default)]
+pub struct RunningStatsNodeBehaviour {
+    settings: RunningStatsSettings,
+    /// Incremented to make the executor reset the statistics.
+    reset_generation: u64,
+    ty_state: enum_dropdown::State,
+    reset_state: button::State,
+}
+
+impl RunningStatsNodeBehaviour {
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let mut configuration =
+            NodeConfiguration::default().with_input_value(Channel::new("value", self.settings.ty));
+
+        if self.settings.reset_input {
+            configuration = configuration.with_input_value(
+                Channel::new("reset", PrimitiveType::<u8>::default())
+                    .with_description("Resets the statistics before the value is sampled, while non-zero"),
+            );
+        }
+
+        NodeCommand::Configure(
+            configuration
+                .with_output_value(Channel::new("min", PrimitiveType::<f64>::default()))
+                .with_output_value(Channel::new("max", PrimitiveType::<f64>::default()))
+                .with_output_value(Channel::new("mean", PrimitiveType::<f64>::default()))
+                .with_output_value(
+                    Channel::new("variance", PrimitiveType::<f64>::default())
+                        .with_description("The sample variance"),
+                )
+                .with_output_value(Channel::new("std_dev", PrimitiveType::<f64>::default()))
+                .with_output_value(Channel::new("count", PrimitiveType::<u64>::default()))
+                .with_output_value(
+                    Channel::new("rejected", PrimitiveType::<u64>::default())
+                        .with_description("The number of NaN values skipped since the last reset"),
+                ),
+        )
+    }
+}
+
+impl NodeBehaviour for RunningStatsNodeBehaviour {
+    type Message = RunningStatsNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Running Statistics"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Inspect)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => match message {
+                RunningStatsNodeMessage::UpdateType(ty) => {
+                    self.settings.ty = ty;
+                    vec![self.get_configure_command()]
+                }
+                RunningStatsNodeMessage::UpdateResetInput(reset_input) => {
+                    self.settings.reset_input = reset_input;
+                    vec![self.get_configure_command()]
+                }
+                RunningStatsNodeMessage::Reset => {
+                    self.reset_generation += 1;
+                    vec![]
+                }
+            },
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            Column::new()
+                .theme(theme)
+                .push(
+                    EnumDropdown::new(
+                        &mut self.ty_state,
+                        &PrimitiveTypeEnum::VALUES[..],
+                        self.settings.ty,
+                        RunningStatsNodeMessage::UpdateType,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(
+                    Row::new()
+                        .theme(theme)
+                        .push(
+                            Checkbox::new(
+                                self.settings.reset_input,
+                                "Reset input",
+                                RunningStatsNodeMessage::UpdateResetInput,
+                            )
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .push(
+                            Button::new(&mut self.reset_state, Text::new("Reset"))
+                                .on_press(RunningStatsNodeMessage::Reset),
+                        )
+                        .align_items(Align::Center)
+                        .width(Length::Fill),
+                )
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn context_menu_items(&self) -> Vec<ContextMenuItem<Self::Message>> {
+        vec![ContextMenuItem::new("Reset statistics", RunningStatsNodeMessage::Reset)]
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.settings = serde_json::from_value(settings)?;
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                if persistent.reset_generation != behaviour.reset_generation {
+                    persistent.reset_generation = behaviour.reset_generation;
+                    persistent.reset();
+                }
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let sample = settings
+                        .ty
+                        .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                        .unwrap()
+                        .to_f64();
+                    let reset = settings.reset_input && context.inputs[1].as_bytes().unwrap()[0] != 0;
+
+                    if reset {
+                        persistent.reset();
+                    }
+
+                    if sample.is_nan() {
+                        persistent.rejected += 1;
+                    } else {
+                        persistent.stats.push(sample);
+                    }
+
+                    let stats = persistent.stats;
+                    let values = [stats.min(), stats.max(), stats.mean(), stats.variance(), stats.std_dev()];
+                    let counts = [stats.count(), persistent.rejected];
+
+                    for (output, value) in context.outputs.iter_mut().zip(&values) {
+                        output
+                            .replace_with_bytes(context.allocator_handle, |bytes| {
+                                LittleEndian::write_f64(bytes, *value)
+                            })
+                            .unwrap();
+                    }
+
+                    for (output, count) in context.outputs.iter_mut().skip(values.len()).zip(&counts) {
+                        output
+                            .replace_with_bytes(context.allocator_handle, |bytes| {
+                                LittleEndian::write_u64(bytes, *count)
+                            })
+                            .unwrap();
+                    }
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated, so that the statistics keep accumulating.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    stats: RunningStats,
+    /// The number of NaN values skipped since the last reset.
+    rejected: u64,
+    /// The [`RunningStatsNodeBehaviour::reset_generation`] the statistics were last reset at.
+    reset_generation: u64,
+}
+
+impl Persistent {
+    fn reset(&mut self) {
+        self.stats = Default::default();
+        self.rejected = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::behaviour::Prng;
+
+    fn stats(samples: impl IntoIterator<Item = f64>) -> RunningStats {
+        let mut stats = RunningStats::default();
+
+        for sample in samples {
+            stats.push(sample);
+        }
+
+        stats
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance * expected.abs().max(1.0),
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn matches_known_sequences() {
+        let stats = stats(vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+
+        assert_eq!(stats.count(), 8);
+        assert_eq!((stats.min(), stats.max()), (2.0, 9.0));
+        assert_close(stats.mean(), 5.0, 1e-12);
+        assert_close(stats.variance(), 32.0 / 7.0, 1e-12);
+
+        let stats = self::stats((1..=100).map(|sample| sample as f64));
+
+        assert_eq!((stats.min(), stats.max()), (1.0, 100.0));
+        assert_close(stats.mean(), 50.5, 1e-12);
+        assert_close(stats.variance(), 100.0 * 101.0 / 12.0, 1e-12);
+        assert_close(stats.std_dev(), (100.0_f64 * 101.0 / 12.0).sqrt(), 1e-12);
+    }
+
+    #[test]
+    fn keeps_precision_with_large_offsets() {
+        let stats = stats(vec![4.0, 7.0, 13.0, 16.0].into_iter().map(|sample| 1e9 + sample));
+
+        assert_close(stats.mean(), 1e9 + 10.0, 1e-12);
+        assert_close(stats.variance(), 30.0, 1e-6);
+    }
+
+    #[test]
+    fn matches_the_two_pass_computation() {
+        let mut prng = Prng::new(0);
+        let samples = (0..10_000).map(|_| prng.next_f64() * 200.0 - 100.0).collect::<Vec<_>>();
+        let stats = stats(samples.iter().copied());
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance =
+            samples.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / (samples.len() - 1) as f64;
+
+        assert_close(stats.mean(), mean, 1e-9);
+        assert_close(stats.variance(), variance, 1e-9);
+    }
+
+    #[test]
+    fn variance_is_zero_until_the_second_sample() {
+        let mut stats = RunningStats::default();
+
+        assert_eq!((stats.count(), stats.mean(), stats.variance()), (0, 0.0, 0.0));
+
+        stats.push(3.0);
+        assert_eq!((stats.min(), stats.max(), stats.mean(), stats.variance()), (3.0, 3.0, 3.0, 0.0));
+
+        stats.push(5.0);
+        assert_eq!(stats.variance(), 2.0);
+    }
+}
@@ -0,0 +1,447 @@
+use crate::node::PrimitiveChannelValue;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
+    },
+    style::{consts, Theme, Themeable},
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use iced::widget::checkbox::Checkbox;
+use iced::{
+    pick_list::{self, PickList},
+    text_input::{self, TextInput},
+    Align, Column, Container, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Cursor;
+
+/// The state of the trigger before the input first crosses a threshold.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum InitialState {
+    Low,
+    High,
+    /// High if the first value is above the midpoint between the thresholds.
+    Input,
+}
+
+impl InitialState {
+    pub const VALUES: [InitialState; 3] = [InitialState::Low, InitialState::High, InitialState::Input];
+}
+
+impl Display for InitialState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitialState::Low => write!(f, "Start low"),
+            InitialState::High => write!(f, "Start high"),
+            InitialState::Input => write!(f, "Start from input"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SchmittTriggerSettings {
+    /// The output switches to high once the input reaches this value.
+    pub rising: f32,
+    /// The output switches to low once the input drops below this value. Never greater than
+    /// `rising`, equal thresholds make the trigger a plain comparator.
+    pub falling: f32,
+    pub inverted: bool,
+    pub initial_state: InitialState,
+}
+
+impl Default for SchmittTriggerSettings {
+    fn default() -> Self {
+        Self { rising: 0.6, falling: 0.4, inverted: false, initial_state: InitialState::Low }
+    }
+}
+
+impl SchmittTriggerSettings {
+    fn validate(&self) -> Result<(), &'static str> {
+        if !self.rising.is_finite() || !self.falling.is_finite() {
+            Err("The thresholds must be finite.")
+        } else if self.rising < self.falling {
+            Err("The rising threshold must not be lower than the falling threshold.")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SchmittTrigger {
+    /// `None` until the first value which is not NaN.
+    high: Option<bool>,
+}
+
+impl SchmittTrigger {
+    /// Advances the trigger by a single value and returns whether the output is high, before the
+    /// output is inverted. NaN values hold the previous state.
+    pub fn advance(&mut self, settings: &SchmittTriggerSettings, value: f32) -> bool {
+        if value.is_nan() {
+            return self.high.unwrap_or(settings.initial_state == InitialState::High);
+        }
+
+        let high = match self.high {
+            None if settings.initial_state == InitialState::Input => {
+                value >= (settings.rising + settings.falling) / 2.0
+            }
+            None => settings.initial_state == InitialState::High,
+            Some(high) => high,
+        };
+        let high = if high { value >= settings.falling } else { value >= settings.rising };
+
+        self.high = Some(high);
+
+        high
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SchmittTriggerNodeMessage {
+    UpdateRising(String),
+    UpdateFalling(String),
+    UpdateInverted(bool),
+    UpdateInitialState(InitialState),
+}
+
+#[derive(Debug, Clone, Default)]
+struct SchmittTriggerUiState {
+    rising_state: text_input::State,
+    rising_string: String,
+    falling_state: text_input::State,
+    falling_string: String,
+    initial_state_state: pick_list::State<InitialState>,
+    /// Describes why the last entered value was rejected.
+    error: Option<&'static str>,
+}
+
+/// Converts a noisy input into a clean on/off output, by switching on and off at different
+/// thresholds.
+#[derive(Debug, Clone)]
+pub struct SchmittTriggerNodeBehaviour {
+    settings: SchmittTriggerSettings,
+    ui_state: SchmittTriggerUiState,
+}
+
+impl Default for SchmittTriggerNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl SchmittTriggerNodeBehaviour {
+    pub fn with_settings(settings: SchmittTriggerSettings) -> Self {
+        Self {
+            settings,
+            ui_state: SchmittTriggerUiState {
+                rising_string: settings.rising.to_string(),
+                falling_string: settings.falling.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_input_value(Channel::new("value", PrimitiveType::<f32>::default()))
+                .with_output_value(
+                    Channel::new("state", PrimitiveType::<u8>::default())
+                        .with_description("1 while the trigger is high, 0 otherwise"),
+                ),
+        )
+    }
+
+    /// Applies the thresholds entered into the text fields, if they are valid.
+    fn update_thresholds(&mut self) {
+        let ui_state = &self.ui_state;
+        let parsed = (|| {
+            Some(SchmittTriggerSettings {
+                rising: ui_state.rising_string.parse().ok()?,
+                falling: ui_state.falling_string.parse().ok()?,
+                ..self.settings
+            })
+        })();
+
+        self.ui_state.error = match parsed {
+            Some(settings) => match settings.validate() {
+                Ok(()) => {
+                    self.settings = settings;
+                    None
+                }
+                Err(error) => Some(error),
+            },
+            None => Some("The thresholds must be numbers."),
+        };
+    }
+}
+
+impl NodeBehaviour for SchmittTriggerNodeBehaviour {
+    type Message = SchmittTriggerNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Schmitt Trigger"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use SchmittTriggerNodeMessage::*;
+
+                match message {
+                    UpdateRising(string) => {
+                        self.ui_state.rising_string = string;
+                        self.update_thresholds();
+                    }
+                    UpdateFalling(string) => {
+                        self.ui_state.falling_string = string;
+                        self.update_thresholds();
+                    }
+                    UpdateInverted(inverted) => self.settings.inverted = inverted,
+                    UpdateInitialState(initial_state) => self.settings.initial_state = initial_state,
+                }
+
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.rising_state,
+                            "Rising",
+                            &ui_state.rising_string,
+                            SchmittTriggerNodeMessage::UpdateRising,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        TextInput::new(
+                            &mut ui_state.falling_state,
+                            "Falling",
+                            &ui_state.falling_string,
+                            SchmittTriggerNodeMessage::UpdateFalling,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            )
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        Checkbox::new(
+                            settings.inverted,
+                            "Inverted",
+                            SchmittTriggerNodeMessage::UpdateInverted,
+                        )
+                        .theme(theme)
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        // Wrap PickList in a container because PickList's width resolution is buggy
+                        Container::new(
+                            PickList::new(
+                                &mut ui_state.initial_state_state,
+                                &InitialState::VALUES[..],
+                                Some(settings.initial_state),
+                                SchmittTriggerNodeMessage::UpdateInitialState,
+                            )
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .width(Length::Fill),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+
+        if let Some(error) = ui_state.error {
+            column = column.push(Text::new(error).size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: SchmittTriggerSettings = serde_json::from_value(settings)?;
+
+        settings.validate().map_err(<serde_json::Error as serde::de::Error>::custom)?;
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let value = match PrimitiveTypeEnum::F32
+                        .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                        .unwrap()
+                    {
+                        PrimitiveChannelValue::F32(value) => value,
+                        _ => unreachable!(),
+                    };
+                    let high = persistent.trigger.advance(&settings, value) != settings.inverted;
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes).write_u8(high as u8).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated, so that the output does not flip when the settings
+/// change.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    trigger: SchmittTrigger,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::behaviour::Prng;
+
+    /// A ramp from `from` to `to` with uniform noise of the given amplitude added.
+    fn noisy_ramp(seed: u64, from: f32, to: f32, noise: f32) -> Vec<f32> {
+        let mut prng = Prng::new(seed);
+        let steps = 1000;
+
+        (0..=steps)
+            .map(|step| {
+                let ramp = from + (to - from) * step as f32 / steps as f32;
+
+                ramp + noise * (prng.next_f64() as f32 * 2.0 - 1.0)
+            })
+            .collect()
+    }
+
+    /// The indices of the values at which the output switched, along with the new output.
+    fn events(settings: &SchmittTriggerSettings, values: &[f32]) -> Vec<(usize, bool)> {
+        let mut trigger = SchmittTrigger::default();
+        let mut previous = None;
+
+        values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| {
+                let high = trigger.advance(settings, *value);
+                let switched = previous.map_or(false, |previous| previous != high);
+
+                previous = Some(high);
+
+                if switched {
+                    Some((index, high))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn noisy_rising_ramp_produces_a_single_rising_event() {
+        let settings = SchmittTriggerSettings::default();
+
+        for seed in 0..100 {
+            // The noise is narrower than the hysteresis.
+            let values = noisy_ramp(seed, 0.0, 1.0, 0.08);
+            let events = events(&settings, &values);
+
+            assert_eq!(events.len(), 1, "seed {}: {:?}", seed, events);
+
+            let (index, high) = events[0];
+
+            assert!(high);
+            assert!(values[index] >= settings.rising);
+        }
+    }
+
+    #[test]
+    fn noisy_falling_ramp_produces_a_single_falling_event() {
+        let settings = SchmittTriggerSettings { initial_state: InitialState::High, ..Default::default() };
+
+        for seed in 0..100 {
+            let values = noisy_ramp(seed, 1.0, 0.0, 0.08);
+            let events = events(&settings, &values);
+
+            assert_eq!(events.len(), 1, "seed {}: {:?}", seed, events);
+
+            let (index, high) = events[0];
+
+            assert!(!high);
+            assert!(values[index] < settings.falling);
+        }
+    }
+
+    #[test]
+    fn comparator_without_hysteresis_chatters_on_noise() {
+        let settings = SchmittTriggerSettings { rising: 0.5, falling: 0.5, ..Default::default() };
+        let events = events(&settings, &noisy_ramp(0, 0.0, 1.0, 0.08));
+
+        assert!(events.len() > 1, "{:?}", events);
+    }
+
+    #[test]
+    fn nan_holds_the_state() {
+        let settings = SchmittTriggerSettings::default();
+        let mut trigger = SchmittTrigger::default();
+
+        assert!(!trigger.advance(&settings, f32::NAN));
+        assert!(trigger.advance(&settings, 0.7));
+        assert!(trigger.advance(&settings, f32::NAN));
+        assert!(trigger.advance(&settings, 0.5));
+        assert!(!trigger.advance(&settings, 0.3));
+        assert!(!trigger.advance(&settings, f32::NAN));
+    }
+
+    #[test]
+    fn initial_state_from_input_compares_with_the_midpoint() {
+        let settings = SchmittTriggerSettings { initial_state: InitialState::Input, ..Default::default() };
+
+        assert!(SchmittTrigger::default().advance(&settings, 0.55));
+        assert!(!SchmittTrigger::default().advance(&settings, 0.45));
+    }
+}
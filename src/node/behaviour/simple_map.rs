@@ -0,0 +1,320 @@
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveChannelValue, PrimitiveTypeEnum,
+    },
+    style::Theme,
+};
+use byteorder::LittleEndian;
+use iced::Element;
+use std::io::Cursor;
+
+/// Creates a [`SimpleMapNodeBehaviour`] from its name and a non-capturing closure, applied to `f32`
+/// values, such as `simple_node!("Abs", |x| x.abs())`, or to `i32` values if the name is prefixed
+/// with `integer`.
+#[macro_export]
+macro_rules! simple_node {
+    (integer $name:literal, $function:expr) => {
+        $crate::node::behaviour::SimpleMapNodeBehaviour::new(
+            $name,
+            $crate::node::behaviour::MapFunction::Integer($function),
+        )
+    };
+    ($name:literal, $function:expr) => {
+        $crate::node::behaviour::SimpleMapNodeBehaviour::new(
+            $name,
+            $crate::node::behaviour::MapFunction::Float($function),
+        )
+    };
+}
+
+/// The kinds of nodes built on [`SimpleMapNodeBehaviour`], listed along with all other behaviours.
+pub fn simple_map_behaviours() -> Vec<SimpleMapNodeBehaviour> {
+    vec![
+        simple_node!("Abs", |x| x.abs()),
+        simple_node!("Floor", |x| x.floor()),
+        simple_node!("Ceil", |x| x.ceil()),
+        simple_node!("Round", |x| x.round()),
+        simple_node!("Sign", |x| if x == 0.0 || x.is_nan() { x } else { x.signum() }),
+        simple_node!("Sin", |x| x.sin()),
+        simple_node!("Cos", |x| x.cos()),
+        simple_node!("Exp", |x| x.exp()),
+        simple_node!("Ln", |x| x.ln()),
+        simple_node!("Sqrt", |x| x.sqrt()),
+        simple_node!(integer "Integer Abs", |x| x.abs()),
+    ]
+}
+
+/// The function a [`SimpleMapNodeBehaviour`] applies to each value.
+#[derive(Debug, Clone, Copy)]
+pub enum MapFunction {
+    /// Maps `f32` values, computed with double precision.
+    Float(fn(f64) -> f64),
+    /// Maps `i32` values, computed with 64 bits and saturated to the range of `i32`.
+    Integer(fn(i64) -> i64),
+}
+
+impl MapFunction {
+    pub fn ty(&self) -> PrimitiveTypeEnum {
+        match self {
+            MapFunction::Float(_) => PrimitiveTypeEnum::F32,
+            MapFunction::Integer(_) => PrimitiveTypeEnum::I32,
+        }
+    }
+
+    /// Applies the function to a value of the type of the function.
+    pub fn apply(&self, value: PrimitiveChannelValue) -> PrimitiveChannelValue {
+        match (self, value) {
+            (MapFunction::Float(function), PrimitiveChannelValue::F32(value)) => {
+                PrimitiveChannelValue::F32((function)(value as f64) as f32)
+            }
+            (MapFunction::Integer(function), PrimitiveChannelValue::I32(value)) => {
+                let result = (function)(value as i64);
+
+                PrimitiveChannelValue::I32(result.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+            }
+            _ => panic!("The value does not match the type of the map function."),
+        }
+    }
+}
+
+/// A node without any settings, mapping a single input value to a single output value of the same
+/// type by a pure function. Created by the [`simple_node!`] macro, so that trivial nodes take a
+/// single line to define.
+#[derive(Debug, Clone)]
+pub struct SimpleMapNodeBehaviour {
+    name: &'static str,
+    function: MapFunction,
+}
+
+impl SimpleMapNodeBehaviour {
+    pub fn new(name: &'static str, function: MapFunction) -> Self {
+        Self { name, function }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_input_value(Channel::new("value", self.function.ty()))
+                .with_output_value(Channel::new("result", self.function.ty())),
+        )
+    }
+}
+
+impl NodeBehaviour for SimpleMapNodeBehaviour {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(_) => vec![],
+        }
+    }
+
+    fn view(&mut self, _theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        None
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let function = behaviour.function;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let value = function
+                        .ty()
+                        .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                        .unwrap();
+                    let result = function.apply(value);
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            result.write::<LittleEndian>(&mut Cursor::new(bytes)).unwrap();
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::testing::{cpu_context, executor_lock, schedule};
+    use crate::graph::{ChannelIdentifier, ExecutionGraph, PreparedExecution};
+    use crate::node::behaviour::{ConstantNodeBehaviour, DebugNodeBehaviour};
+    use crate::node::{ChannelDirection, ChannelPassBy, NodeIndex};
+    use crate::template::GraphBuilder;
+
+    const FLOAT_INPUTS: [f32; 7] = [-2.5, -0.5, -0.0, 0.0, 0.5, 1.0, 2.5];
+    const INTEGER_INPUTS: [i32; 5] = [i32::MIN, -7, 0, 7, i32::MAX];
+
+    fn behaviour(name: &str) -> SimpleMapNodeBehaviour {
+        simple_map_behaviours().into_iter().find(|behaviour| behaviour.name == name).unwrap()
+    }
+
+    fn float(name: &str, input: f32) -> f32 {
+        match behaviour(name).function.apply(PrimitiveChannelValue::F32(input)) {
+            PrimitiveChannelValue::F32(result) => result,
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    /// Compares the results bit by bit, so that signed zeros and NaNs are told apart.
+    fn assert_maps(name: &str, cases: &[(f32, f32)]) {
+        for &(input, expected) in cases {
+            let result = float(name, input);
+
+            assert!(
+                result.to_bits() == expected.to_bits() || result.is_nan() && expected.is_nan(),
+                "{}({}) = {}, expected {}",
+                name,
+                input,
+                result,
+                expected,
+            );
+        }
+    }
+
+    #[test]
+    fn all_kinds_are_listed() {
+        let names = simple_map_behaviours().iter().map(|behaviour| behaviour.name).collect::<Vec<_>>();
+        let expected =
+            ["Abs", "Floor", "Ceil", "Round", "Sign", "Sin", "Cos", "Exp", "Ln", "Sqrt", "Integer Abs"];
+
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn float_functions_map_reference_values() {
+        assert_maps("Abs", &[(-2.5, 2.5), (-0.0, 0.0), (1.0, 1.0)]);
+        assert_maps("Floor", &[(-2.5, -3.0), (-0.5, -1.0), (2.5, 2.0)]);
+        assert_maps("Ceil", &[(-2.5, -2.0), (-0.5, -0.0), (2.5, 3.0)]);
+        // Halfway cases are rounded away from zero.
+        assert_maps("Round", &[(-2.5, -3.0), (-0.5, -1.0), (0.5, 1.0), (2.5, 3.0)]);
+        // Zeros and NaNs are returned as they are.
+        assert_maps("Sign", &[(-2.5, -1.0), (-0.0, -0.0), (0.0, 0.0), (0.5, 1.0), (f32::NAN, f32::NAN)]);
+        assert_maps("Sin", &[(0.0, 0.0), (std::f32::consts::FRAC_PI_2, 1.0)]);
+        assert_maps("Cos", &[(0.0, 1.0)]);
+        assert_maps("Exp", &[(0.0, 1.0), (1.0, std::f32::consts::E)]);
+        assert_maps("Ln", &[(1.0, 0.0), (0.0, f32::NEG_INFINITY), (-1.0, f32::NAN)]);
+        assert_maps("Sqrt", &[(4.0, 2.0), (0.0, 0.0), (-1.0, f32::NAN)]);
+    }
+
+    #[test]
+    fn integer_results_saturate() {
+        let abs = behaviour("Integer Abs").function;
+
+        assert_eq!(abs.apply(PrimitiveChannelValue::I32(-7)), PrimitiveChannelValue::I32(7));
+        assert_eq!(abs.apply(PrimitiveChannelValue::I32(i32::MIN)), PrimitiveChannelValue::I32(i32::MAX));
+    }
+
+    #[test]
+    fn channels_have_the_type_of_the_function() {
+        for behaviour in simple_map_behaviours() {
+            let ty = behaviour.function.ty();
+            let expected = if behaviour.name.starts_with("Integer") {
+                PrimitiveTypeEnum::I32
+            } else {
+                PrimitiveTypeEnum::F32
+            };
+
+            assert_eq!(ty, expected, "{}", behaviour.name);
+
+            match behaviour.get_configure_command() {
+                NodeCommand::Configure(configuration) => {
+                    assert_eq!(configuration.input_channels_by_value.len(), 1, "{}", behaviour.name);
+                    assert_eq!(configuration.output_channels_by_value.len(), 1, "{}", behaviour.name);
+                    assert_eq!(configuration.input_channels_by_value[0].ty, ty.into(), "{}", behaviour.name);
+                    assert_eq!(configuration.output_channels_by_value[0].ty, ty.into(), "{}", behaviour.name);
+                }
+                _ => panic!("Unexpected command."),
+            }
+        }
+    }
+
+    /// A constant mapped by the node, whose result is displayed by a debug node, along with the
+    /// mapping node.
+    fn mapped_constant(
+        behaviour: SimpleMapNodeBehaviour,
+        input: PrimitiveChannelValue,
+    ) -> (ExecutionGraph, NodeIndex) {
+        let mut builder = GraphBuilder::default();
+        let ty = behaviour.function.ty();
+        let constant = builder.node("Constant", [0.0, 0.0], ConstantNodeBehaviour::new(input));
+        let map = builder.node(behaviour.name, [200.0, 0.0], behaviour);
+        let debug = builder.node("Debug", [400.0, 0.0], DebugNodeBehaviour::new(ty));
+
+        builder.connect((constant, 0), (map, ChannelPassBy::Value, 0));
+        builder.connect((map, 0), (debug, ChannelPassBy::SharedReference, 0));
+
+        (builder.build().into(), map)
+    }
+
+    /// Every kind of node is scheduled and outputs the result of its function, as taken by a value
+    /// tap.
+    #[test]
+    fn nodes_schedule_and_output_the_mapped_values() {
+        let _lock = executor_lock();
+        let mut context = cpu_context();
+        let mut snapshots = context.value_taps.take_receiver().unwrap();
+
+        for behaviour in simple_map_behaviours() {
+            let inputs = match behaviour.function {
+                MapFunction::Float(_) => {
+                    FLOAT_INPUTS.iter().copied().map(PrimitiveChannelValue::F32).collect::<Vec<_>>()
+                }
+                MapFunction::Integer(_) => {
+                    INTEGER_INPUTS.iter().copied().map(PrimitiveChannelValue::I32).collect()
+                }
+            };
+
+            for input in inputs {
+                let function = behaviour.function;
+                let (mut graph, map) = mapped_constant(behaviour.clone(), input);
+                let schedule = schedule(&mut graph);
+
+                assert!(schedule.node_indices.contains(&map), "{} is not scheduled", behaviour.name);
+
+                let mut execution = PreparedExecution::from(&schedule, &mut context, None);
+
+                context.value_taps.request(ChannelIdentifier {
+                    node_index: map,
+                    channel_direction: ChannelDirection::Out,
+                    channel_index: 0,
+                    pass_by: ChannelPassBy::Value,
+                });
+                execution.execute(&schedule, &mut context, &mut None);
+
+                let result = snapshots.try_next().unwrap().unwrap().value.unwrap();
+                let expected = function.apply(input);
+
+                // Compared by their formatting, so that NaNs are equal.
+                assert_eq!(
+                    format!("{:?}", result),
+                    format!("{:?}", expected),
+                    "{}({:?})",
+                    behaviour.name,
+                    input,
+                );
+                execution.release();
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::node::prelude::*;
+use crate::node::ListType;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ContextMenuItem, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure,
+        },
+        Channel, NodeConfiguration, PrimitiveTypeEnum, ValueFormatter,
+    },
+    style::{consts, Theme, Themeable},
+};
+use byteorder::LittleEndian;
+use iced::{
+    button::{self, Button},
+    pick_list::{self, PickList},
+    text_input::{self, TextInput},
+    Align, Column, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+/// The number of rows displayed at once.
+const VISIBLE_ROWS: usize = 16;
+/// Displayed in place of the items of lists shorter than the longest one.
+const MISSING_ITEM: &str = "—";
+
+#[derive(Debug, Clone)]
+pub enum TableViewNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    UpdateRowOffset(String),
+    AddChannel,
+    RemoveChannel,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    ty: PrimitiveTypeEnum,
+    channel_count: NonZeroUsize,
+    row_offset: usize,
+}
+
+/// The visible window of the lists, copied by the executor for the UI to display.
+#[derive(Debug, Default)]
+struct TableSnapshot {
+    /// The length of the longest list.
+    row_count: usize,
+    /// The formatted items of the visible rows, one `Vec` per list.
+    columns: Vec<Vec<String>>,
+}
+
+/// Displays the items of the connected lists side by side, one column per list.
+#[derive(Debug, Clone)]
+pub struct TableViewNodeBehaviour {
+    ty: PrimitiveTypeEnum,
+    channel_count: NonZeroUsize,
+    row_offset: usize,
+    row_offset_string: String,
+    /// The titles of the channels connected to the inputs, used as column headers.
+    sources: Vec<Option<String>>,
+    /// Shared with the executor, as the behaviour is cloned into the schedule.
+    snapshot: Arc<Mutex<TableSnapshot>>,
+    pick_list_state: pick_list::State<PrimitiveTypeEnum>,
+    row_offset_state: text_input::State,
+    button_add_state: button::State,
+    button_remove_state: button::State,
+}
+
+impl Default for TableViewNodeBehaviour {
+    fn default() -> Self {
+        Self {
+            ty: PrimitiveTypeEnum::F32,
+            channel_count: unsafe { NonZeroUsize::new_unchecked(1) },
+            row_offset: 0,
+            row_offset_string: 0.to_string(),
+            sources: Vec::new(),
+            snapshot: Default::default(),
+            pick_list_state: Default::default(),
+            row_offset_state: Default::default(),
+            button_add_state: Default::default(),
+            button_remove_state: Default::default(),
+        }
+    }
+}
+
+impl TableViewNodeBehaviour {
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(NodeConfiguration {
+            channels_by_shared_reference: (0..self.channel_count.get())
+                .into_iter()
+                .map(|channel_index| {
+                    Channel::new(format!("list #{}", channel_index), ListType::new_if_sized(self.ty).unwrap())
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    fn view_table<'a>(&self, theme: &dyn Theme) -> Column<'a, TableViewNodeMessage> {
+        let snapshot = self.snapshot.lock().unwrap();
+        let header = (0..self.channel_count.get()).fold(
+            Row::new()
+                .theme(theme)
+                .push(Text::new("#").size(consts::TEXT_SIZE_REGULAR).width(Length::Units(32))),
+            |row, channel_index| {
+                let title = match self.sources.get(channel_index) {
+                    Some(Some(source)) => source.clone(),
+                    _ => format!("list #{}", channel_index),
+                };
+
+                row.push(Text::new(title).size(consts::TEXT_SIZE_REGULAR).width(Length::Fill))
+            },
+        );
+        let visible_rows = snapshot.row_count.saturating_sub(self.row_offset).min(VISIBLE_ROWS);
+        let column = (0..visible_rows).fold(Column::new().theme(theme).push(header), |column, row_index| {
+            let row = (0..self.channel_count.get()).fold(
+                Row::new().theme(theme).push(
+                    Text::new((self.row_offset + row_index).to_string())
+                        .size(consts::TEXT_SIZE_REGULAR)
+                        .width(Length::Units(32)),
+                ),
+                |row, channel_index| {
+                    let item = snapshot
+                        .columns
+                        .get(channel_index)
+                        .and_then(|items| items.get(row_index))
+                        .map(String::as_str)
+                        .unwrap_or(MISSING_ITEM);
+
+                    row.push(Text::new(item).size(consts::TEXT_SIZE_REGULAR).width(Length::Fill))
+                },
+            );
+
+            column.push(row)
+        });
+
+        column.push(
+            Text::new(format!(
+                "Rows {}–{} of {}",
+                self.row_offset.min(snapshot.row_count),
+                self.row_offset + visible_rows,
+                snapshot.row_count,
+            ))
+            .size(consts::TEXT_SIZE_REGULAR),
+        )
+    }
+}
+
+impl NodeBehaviour for TableViewNodeBehaviour {
+    type Message = TableViewNodeMessage;
+
+    fn name(&self) -> &str {
+        "TableView"
+    }
+
+    fn is_sink(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Inspect)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use TableViewNodeMessage::*;
+                let mut commands = Vec::new();
+
+                match message {
+                    UpdateType(ty) => {
+                        self.ty = ty;
+                        commands.push(self.get_configure_command());
+                    }
+                    UpdateRowOffset(string) => {
+                        if let Ok(row_offset) = string.parse::<usize>() {
+                            self.row_offset = row_offset;
+                        }
+
+                        self.row_offset_string = string;
+                    }
+                    AddChannel => {
+                        self.channel_count = NonZeroUsize::new(self.channel_count.get() + 1).unwrap();
+                        commands.push(self.get_configure_command());
+                    }
+                    RemoveChannel => {
+                        if let Some(new_value) = NonZeroUsize::new(self.channel_count.get() - 1) {
+                            self.channel_count = new_value;
+                            commands.push(self.get_configure_command());
+                        }
+                    }
+                }
+
+                commands
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let table = self.view_table(theme);
+        let controls = Row::new()
+            .theme(theme)
+            .push(
+                PickList::new(
+                    &mut self.pick_list_state,
+                    &PrimitiveTypeEnum::VALUES[..],
+                    Some(self.ty),
+                    |new_value| TableViewNodeMessage::UpdateType(new_value),
+                )
+                .theme(theme)
+                .width(Length::Units(64)),
+            )
+            .push(
+                TextInput::new(
+                    &mut self.row_offset_state,
+                    "Offset",
+                    &self.row_offset_string,
+                    TableViewNodeMessage::UpdateRowOffset,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+            )
+            .push(
+                Button::new(&mut self.button_add_state, Text::new("+"))
+                    .on_press(TableViewNodeMessage::AddChannel),
+            )
+            .push(
+                Button::new(&mut self.button_remove_state, Text::new("-"))
+                    .on_press(TableViewNodeMessage::RemoveChannel),
+            )
+            .align_items(Align::Center)
+            .width(Length::Fill);
+
+        Some(Column::new().theme(theme).push(table).push(controls).width(Length::Fill).into())
+    }
+
+    fn context_menu_items(&self) -> Vec<ContextMenuItem<Self::Message>> {
+        vec![
+            ContextMenuItem::new("Add column", TableViewNodeMessage::AddChannel),
+            ContextMenuItem::new("Remove column", TableViewNodeMessage::RemoveChannel)
+                .enabled(self.channel_count.get() > 1),
+        ]
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(SavedSettings {
+            ty: self.ty,
+            channel_count: self.channel_count,
+            row_offset: self.row_offset,
+        })
+        .unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let SavedSettings { ty, channel_count, row_offset } = serde_json::from_value(settings)?;
+        self.ty = ty;
+        self.channel_count = channel_count;
+        self.row_offset = row_offset;
+        self.row_offset_string = row_offset.to_string();
+        Ok(())
+    }
+
+    fn update_input_sources(&mut self, sources: &[Option<String>]) {
+        self.sources = sources.to_vec();
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let ty = behaviour.ty;
+                let row_offset = behaviour.row_offset;
+                let snapshot = behaviour.snapshot.clone();
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    // Only the visible rows are formatted, so that long lists stay cheap to display.
+                    let formatter = ValueFormatter::get();
+                    let mut row_count = 0;
+                    let columns = context
+                        .borrows
+                        .iter()
+                        .map(|borrow| {
+                            let list = borrow.clone().downcast_ref::<ListType>().unwrap();
+                            let len = list.len();
+
+                            row_count = row_count.max(len);
+
+                            (row_offset..len.min(row_offset.saturating_add(VISIBLE_ROWS)))
+                                .map(|index| {
+                                    let item = list.get(index).unwrap();
+                                    let bytes = item.bytes_if_sized().unwrap();
+
+                                    formatter.format_primitive(ty.read::<LittleEndian, _>(bytes).unwrap())
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .collect::<Vec<_>>();
+
+                    *snapshot.lock().unwrap() = TableSnapshot { row_count, columns };
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
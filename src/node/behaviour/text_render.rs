@@ -0,0 +1,462 @@
+use crate::graph::{OwnedTexture, TextureAllocation};
+use crate::logging::Category;
+use crate::node::{OwnedRefMut, TextureDescriptor, TextureFormat, TextureType, Unique};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        Channel, NodeConfiguration, OptionRefMutExt,
+    },
+    style::{Theme, Themeable},
+};
+use fontdue::layout::{CoordinateSystem, Layout, LayoutSettings, TextStyle};
+use fontdue::Font;
+use iced::{
+    pick_list::{self, PickList},
+    text_input::{self, TextInput},
+    Column, Element, Length, Row,
+};
+use iced_wgpu::wgpu;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::Arc;
+use vek::Vec2;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const REPLACEMENT_CHARACTER: char = '\u{FFFD}';
+
+lazy_static! {
+    /// The default sans-serif font of the system.
+    static ref FONT: Option<Font> = {
+        use font_kit::{family_name::FamilyName, properties::Properties, source::SystemSource};
+
+        let font = SystemSource::new()
+            .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+            .ok()
+            .and_then(|handle| handle.load().ok())
+            .and_then(|font| font.copy_font_data())
+            .and_then(|data| Font::from_bytes(data.as_slice(), Default::default()).ok());
+
+        if font.is_none() {
+            warn!(Category::Executor, "Could not load a system font, text will not be rendered.");
+        }
+
+        font
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextSizeMode {
+    /// The texture fits the text tightly.
+    Fit,
+    /// The texture has a fixed size, lines are wrapped at its width.
+    Wrap,
+    /// The texture has a fixed size, text outside of it is clipped.
+    Clip,
+}
+
+impl TextSizeMode {
+    pub const VALUES: [TextSizeMode; 3] = [TextSizeMode::Fit, TextSizeMode::Wrap, TextSizeMode::Clip];
+}
+
+impl Display for TextSizeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextSizeMode::Fit => write!(f, "Fit"),
+            TextSizeMode::Wrap => write!(f, "Wrap"),
+            TextSizeMode::Clip => write!(f, "Clip"),
+        }
+    }
+}
+
+/// All parameters that affect the rasterized text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextRenderSettings {
+    pub text: String,
+    pub font_size: f32,
+    /// Non-premultiplied RGBA.
+    pub color: [u8; 4],
+    pub size_mode: TextSizeMode,
+    /// Used by the fixed size modes.
+    pub fixed_size: Vec2<u32>,
+}
+
+impl Default for TextRenderSettings {
+    fn default() -> Self {
+        Self {
+            text: "Text".to_string(),
+            font_size: 32.0,
+            color: [0xFF; 4],
+            size_mode: TextSizeMode::Fit,
+            fixed_size: [256, 64].into(),
+        }
+    }
+}
+
+impl TextRenderSettings {
+    /// Rasterizes the text into tightly packed RGBA texels, returning them along with the size
+    /// of the image. The image is at least 1×1 texels large.
+    pub fn rasterize(&self, font: Option<&Font>) -> (Vec2<u32>, Vec<u8>) {
+        let font = match font {
+            Some(font) if !self.text.is_empty() => font,
+            _ => return (Vec2::one(), vec![0; 4]),
+        };
+        // Replace characters the font does not contain, so that they remain visible.
+        let text = self
+            .text
+            .chars()
+            .map(
+                |c| if c.is_control() || font.lookup_glyph_index(c) != 0 { c } else { REPLACEMENT_CHARACTER },
+            )
+            .collect::<String>();
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+
+        layout.reset(&LayoutSettings {
+            max_width: if self.size_mode == TextSizeMode::Wrap {
+                Some(self.fixed_size[0] as f32)
+            } else {
+                None
+            },
+            ..LayoutSettings::default()
+        });
+        layout.append(&[font], &TextStyle::new(&text, self.font_size, 0));
+
+        let glyphs = layout.glyphs();
+        let size: Vec2<u32> = match self.size_mode {
+            TextSizeMode::Fit => glyphs
+                .iter()
+                .map(|glyph| {
+                    Vec2::new((glyph.x + glyph.width as f32).ceil(), (glyph.y + glyph.height as f32).ceil())
+                })
+                .fold(Vec2::zero(), Vec2::partial_max)
+                .map(|coord: f32| coord.max(1.0) as u32),
+            TextSizeMode::Wrap | TextSizeMode::Clip => self.fixed_size.map(|coord| coord.max(1)),
+        };
+        let mut data = vec![0; (size[0] * size[1] * 4) as usize];
+
+        for glyph in glyphs {
+            if glyph.width == 0 || glyph.height == 0 {
+                continue;
+            }
+
+            let (metrics, coverage) = font.rasterize_config(glyph.key);
+
+            for glyph_y in 0..metrics.height {
+                for glyph_x in 0..metrics.width {
+                    let x = glyph.x as i64 + glyph_x as i64;
+                    let y = glyph.y as i64 + glyph_y as i64;
+
+                    // Clip texels outside of the texture.
+                    if x < 0 || y < 0 || x >= size[0] as i64 || y >= size[1] as i64 {
+                        continue;
+                    }
+
+                    let alpha =
+                        coverage[glyph_y * metrics.width + glyph_x] as u32 * self.color[3] as u32 / 0xFF;
+                    let texel_index = ((y as u32 * size[0] + x as u32) * 4) as usize;
+                    let texel = &mut data[texel_index..texel_index + 4];
+
+                    // Overlapping glyphs keep the larger coverage.
+                    if alpha as u8 > texel[3] {
+                        texel.copy_from_slice(&[self.color[0], self.color[1], self.color[2], alpha as u8]);
+                    }
+                }
+            }
+        }
+
+        (size, data)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TextRenderNodeMessage {
+    UpdateText(String),
+    UpdateFontSize(String),
+    UpdateColor(String),
+    UpdateSizeMode(TextSizeMode),
+    UpdateWidth(String),
+    UpdateHeight(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextRenderUiState {
+    text_state: text_input::State,
+    font_size_state: text_input::State,
+    font_size_string: String,
+    color_state: text_input::State,
+    color_string: String,
+    size_mode_state: pick_list::State<TextSizeMode>,
+    width_state: text_input::State,
+    width_string: String,
+    height_state: text_input::State,
+    height_string: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TextRenderNodeBehaviour {
+    settings: TextRenderSettings,
+    ui_state: TextRenderUiState,
+}
+
+impl Default for TextRenderNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(TextRenderSettings::default())
+    }
+}
+
+impl TextRenderNodeBehaviour {
+    pub fn with_settings(settings: TextRenderSettings) -> Self {
+        let ui_state = TextRenderUiState {
+            font_size_string: settings.font_size.to_string(),
+            color_string: format_color(settings.color),
+            width_string: settings.fixed_size[0].to_string(),
+            height_string: settings.fixed_size[1].to_string(),
+            ..Default::default()
+        };
+
+        Self { settings, ui_state }
+    }
+}
+
+/// Formats the color as `RRGGBBAA`.
+fn format_color(color: [u8; 4]) -> String {
+    color.iter().map(|component| format!("{:02X}", component)).collect()
+}
+
+/// Parses a color in the `RRGGBB` or `RRGGBBAA` format, with an optional leading `#`.
+fn parse_color(string: &str) -> Option<[u8; 4]> {
+    let string = string.trim_start_matches('#');
+
+    if !string.is_ascii() || (string.len() != 6 && string.len() != 8) {
+        return None;
+    }
+
+    let mut color = [0xFF; 4];
+
+    for (component_index, component) in color.iter_mut().enumerate().take(string.len() / 2) {
+        *component = u8::from_str_radix(&string[component_index * 2..component_index * 2 + 2], 16).ok()?;
+    }
+
+    Some(color)
+}
+
+impl NodeBehaviour for TextRenderNodeBehaviour {
+    type Message = TextRenderNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "TextRender"
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Value)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => {
+                let ty = TextureType::owned(TextureFormat::from_wgpu(FORMAT));
+
+                vec![NodeCommand::Configure(
+                    NodeConfiguration::default().with_output_value(Channel::new("texture", Unique::new(ty))),
+                )]
+            }
+            NodeEvent::Message(message) => {
+                use TextRenderNodeMessage::*;
+
+                match message {
+                    UpdateText(text) => self.settings.text = text,
+                    UpdateFontSize(string) => {
+                        if let Ok(font_size) = string.parse::<f32>() {
+                            if font_size > 0.0 && font_size.is_finite() {
+                                self.settings.font_size = font_size;
+                            }
+                        }
+
+                        self.ui_state.font_size_string = string;
+                    }
+                    UpdateColor(string) => {
+                        if let Some(color) = parse_color(&string) {
+                            self.settings.color = color;
+                        }
+
+                        self.ui_state.color_string = string;
+                    }
+                    UpdateSizeMode(size_mode) => self.settings.size_mode = size_mode,
+                    UpdateWidth(string) => {
+                        if let Ok(width) = string.parse::<u32>() {
+                            self.settings.fixed_size[0] = width;
+                        }
+
+                        self.ui_state.width_string = string;
+                    }
+                    UpdateHeight(string) => {
+                        if let Ok(height) = string.parse::<u32>() {
+                            self.settings.fixed_size[1] = height;
+                        }
+
+                        self.ui_state.height_string = string;
+                    }
+                }
+
+                vec![]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                TextInput::new(
+                    &mut self.ui_state.text_state,
+                    "Text",
+                    &self.settings.text,
+                    TextRenderNodeMessage::UpdateText,
+                )
+                .theme(theme),
+            )
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        TextInput::new(
+                            &mut self.ui_state.font_size_state,
+                            "Size",
+                            &self.ui_state.font_size_string,
+                            TextRenderNodeMessage::UpdateFontSize,
+                        )
+                        .theme(theme),
+                    )
+                    .push(
+                        TextInput::new(
+                            &mut self.ui_state.color_state,
+                            "RRGGBBAA",
+                            &self.ui_state.color_string,
+                            TextRenderNodeMessage::UpdateColor,
+                        )
+                        .theme(theme),
+                    ),
+            )
+            .push(
+                PickList::new(
+                    &mut self.ui_state.size_mode_state,
+                    &TextSizeMode::VALUES[..],
+                    Some(self.settings.size_mode),
+                    TextRenderNodeMessage::UpdateSizeMode,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+            );
+
+        if self.settings.size_mode != TextSizeMode::Fit {
+            column = column.push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        TextInput::new(
+                            &mut self.ui_state.width_state,
+                            "Width",
+                            &self.ui_state.width_string,
+                            TextRenderNodeMessage::UpdateWidth,
+                        )
+                        .theme(theme),
+                    )
+                    .push(
+                        TextInput::new(
+                            &mut self.ui_state.height_state,
+                            "Height",
+                            &self.ui_state.height_string,
+                            TextRenderNodeMessage::UpdateHeight,
+                        )
+                        .theme(theme),
+                    ),
+            );
+        }
+
+        Some(column.into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        *self = Self::with_settings(serde_json::from_value(settings)?);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings.clone();
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let renderer = if let Some(renderer) = context.renderer() { renderer } else { return };
+
+                    // Only rasterize the text again, if the settings have actually changed.
+                    if persistent.rendered.as_ref().map_or(true, |rendered| rendered.settings != settings) {
+                        let (size, data) = settings.rasterize(FONT.as_ref());
+                        let texture = match persistent.rendered.take() {
+                            // Reuse the texture, if the size has not changed and no downstream node
+                            // holds on to it anymore.
+                            Some(rendered)
+                                if rendered.texture.size == size
+                                    && Arc::strong_count(&rendered.texture) == 1 =>
+                            {
+                                rendered.texture
+                            }
+                            _ => match OwnedTexture::try_new(renderer, size, FORMAT) {
+                                Ok(texture) => Arc::new(texture),
+                                Err(error) => {
+                                    context.report_error(error);
+                                    return;
+                                }
+                            },
+                        };
+
+                        texture.write(&renderer.queue, &data);
+                        persistent.rendered = Some(RenderedText { settings: settings.clone(), texture });
+                    }
+
+                    // The texture is only written to when the settings change, so it is shared with
+                    // the downstream nodes instead of being copied every execution.
+                    let rendered = persistent.rendered.as_ref().unwrap();
+                    let output: OwnedRefMut<Unique<TextureType>> =
+                        context.allocator_handle.allocate_object::<TextureType>(TextureDescriptor::new(
+                            TextureAllocation::SharedTexture(rendered.texture.clone()),
+                        ));
+
+                    context.outputs[0].replace(output.upcast(), context.allocator_handle).unwrap();
+                    context.clear_error();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct RenderedText {
+    settings: TextRenderSettings,
+    texture: Arc<OwnedTexture>,
+}
+
+#[derive(Debug, Default)]
+pub struct Persistent {
+    rendered: Option<RenderedText>,
+}
@@ -0,0 +1,194 @@
+use crate::graph::{OwnedTexture, Renderer, TextureAllocation, TextureCapabilityError};
+use crate::node::prelude::*;
+use crate::node::{OwnedRefMut, TextureDescriptor, TextureType, Unique};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        Channel, NodeConfiguration, OptionRefMutExt,
+    },
+    style::Theme,
+};
+use iced::Element;
+use iced_wgpu::wgpu;
+use std::sync::Arc;
+use vek::Vec2;
+
+/// Outputs the texture connected to its input as it was during the previous execution.
+#[derive(Clone, Debug, Default)]
+pub struct TextureHistoryNodeBehaviour;
+
+impl NodeBehaviour for TextureHistoryNodeBehaviour {
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "TextureHistory"
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![NodeCommand::Configure(
+                NodeConfiguration::default()
+                    // Swapchain frames cannot be copied from.
+                    .with_borrow(Channel::new(
+                        "texture",
+                        TextureType::new().with_usage(wgpu::TextureUsage::COPY_SRC),
+                    ))
+                    // The format is the same as the format of the input.
+                    .with_output_value(Channel::new("previous", Unique::new(TextureType::owned(None)))),
+            )],
+            NodeEvent::Message(_) => vec![],
+        }
+    }
+
+    fn view(&mut self, _theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        None
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |_behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let input = context.borrows[0].clone().downcast_ref::<TextureType>().unwrap();
+                    let input = if let Some(input) = input.texture().owned_texture() {
+                        input
+                    } else {
+                        // Swapchain frames cannot be copied from, leave the output empty.
+                        return;
+                    };
+                    let renderer = if let Some(renderer) = context.renderer() { renderer } else { return };
+                    let mut encoder = context.create_command_encoder();
+
+                    // Reallocate the textures, if the size or format of the input has changed.
+                    let reallocate = persistent
+                        .history
+                        .as_ref()
+                        .map_or(true, |history| history.size != input.size || history.format != input.format);
+
+                    if reallocate {
+                        persistent.history = Some(TextureHistory::new(input.size, input.format));
+                    }
+
+                    let history = persistent.history.as_mut().unwrap();
+                    let result = history
+                        .advance(renderer, &mut encoder, |target, encoder| input.copy_to(target, encoder));
+                    let previous = match result {
+                        Ok(previous) => previous,
+                        Err(error) => {
+                            persistent.history = None;
+                            context.report_error(error);
+                            return;
+                        }
+                    };
+
+                    renderer.queue.submit(Some(encoder.finish()));
+
+                    let output: OwnedRefMut<Unique<TextureType>> =
+                        context.allocator_handle.allocate_object::<TextureType>(TextureDescriptor::new(
+                            TextureAllocation::SharedTexture(previous),
+                        ));
+
+                    context.outputs[0].replace(output.upcast(), context.allocator_handle).unwrap();
+                    context.clear_error();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// The number of textures kept for reuse. Once all of them are held by the downstream nodes, the
+/// oldest one is left to its holders and replaced by a new one.
+const RING_CAPACITY: usize = 3;
+
+/// A ring of textures holding the inputs of the recent executions. The texture holding the input
+/// of the previous execution is output without copying it, the textures are reused once the
+/// downstream nodes have released them.
+#[derive(Debug)]
+pub struct TextureHistory {
+    textures: Vec<Arc<OwnedTexture>>,
+    /// The index of the texture holding the input of the previous execution.
+    previous: Option<usize>,
+    size: Vec2<u32>,
+    format: wgpu::TextureFormat,
+}
+
+impl TextureHistory {
+    fn new(size: Vec2<u32>, format: wgpu::TextureFormat) -> Self {
+        Self { textures: Vec::with_capacity(RING_CAPACITY), previous: None, size, format }
+    }
+
+    /// The index of a texture not held by any downstream node, other than the previous one,
+    /// allocating one if there is none.
+    fn reusable(&mut self, renderer: &Renderer) -> Result<usize, TextureCapabilityError> {
+        let previous = self.previous;
+        let reusable = (0..self.textures.len())
+            .find(|index| Some(*index) != previous && Arc::strong_count(&self.textures[*index]) == 1);
+
+        if let Some(index) = reusable {
+            return Ok(index);
+        }
+
+        let texture = Arc::new(OwnedTexture::try_new(renderer, self.size, self.format)?);
+
+        if self.textures.len() < RING_CAPACITY {
+            self.textures.push(texture);
+            Ok(self.textures.len() - 1)
+        } else {
+            // All textures are held downstream, replace the oldest one, whose holders keep it alive.
+            let index = (0..self.textures.len()).find(|index| Some(*index) != previous).unwrap();
+
+            self.textures[index] = texture;
+            Ok(index)
+        }
+    }
+
+    /// Writes the input of the current execution into a reusable texture, returning the texture
+    /// holding the input of the previous execution, which is cleared on the first execution.
+    fn advance(
+        &mut self,
+        renderer: &Renderer,
+        encoder: &mut wgpu::CommandEncoder,
+        write: impl FnOnce(&OwnedTexture, &mut wgpu::CommandEncoder),
+    ) -> Result<Arc<OwnedTexture>, TextureCapabilityError> {
+        let previous = match self.previous {
+            Some(previous) => previous,
+            None => {
+                // There is no previous execution to output the input of yet.
+                let index = self.reusable(renderer)?;
+
+                self.textures[index].clear(encoder);
+                self.previous = Some(index);
+                index
+            }
+        };
+        let current = self.reusable(renderer)?;
+
+        write(&self.textures[current], encoder);
+        self.previous = Some(current);
+
+        Ok(self.textures[previous].clone())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Persistent {
+    history: Option<TextureHistory>,
+}
@@ -0,0 +1,447 @@
+use crate::graph::{OwnedTexture, TextureReadback};
+use crate::node::prelude::*;
+use crate::node::{ArrayType, ListDescriptor, ListType, OwnedRefMut, TextureType, Unique};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, Blit, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand,
+            NodeEvent, NodeIcon, NodeStateClosure,
+        },
+        Channel, NodeConfiguration, OptionRefMutExt, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use iced::{Align, Element, Length, Row};
+use iced_wgpu::wgpu;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Cursor;
+use vek::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadbackMode {
+    /// The components of all pixels, row by row, 4 per pixel.
+    Pixels,
+    /// The average of each component over all pixels.
+    AverageColor,
+    /// The minimum and the maximum luminance of the pixels.
+    LuminanceRange,
+}
+
+impl ReadbackMode {
+    pub const VALUES: [ReadbackMode; 3] =
+        [ReadbackMode::Pixels, ReadbackMode::AverageColor, ReadbackMode::LuminanceRange];
+
+    fn output_channel(&self) -> Channel {
+        match self {
+            ReadbackMode::Pixels => {
+                Channel::new("pixels", Unique::new(ListType::new_if_sized(PrimitiveTypeEnum::F32).unwrap()))
+            }
+            ReadbackMode::AverageColor => {
+                Channel::new("color", ArrayType::new_if_sized(PrimitiveTypeEnum::F32, 4).unwrap())
+            }
+            ReadbackMode::LuminanceRange => {
+                Channel::new("luminance", ArrayType::new_if_sized(PrimitiveTypeEnum::F32, 2).unwrap())
+            }
+        }
+    }
+
+    /// Computes the output values from normalized RGBA pixels.
+    fn reduce(&self, pixels: impl ExactSizeIterator<Item = [f32; 4]>) -> Vec<f32> {
+        match self {
+            ReadbackMode::Pixels => {
+                let mut values = Vec::with_capacity(pixels.len() * 4);
+
+                pixels.for_each(|pixel| values.extend_from_slice(&pixel));
+                values
+            }
+            ReadbackMode::AverageColor => {
+                let count = pixels.len().max(1) as f32;
+                let sum = pixels.fold([0.0; 4], |mut sum, pixel| {
+                    sum.iter_mut().zip(&pixel).for_each(|(sum, component)| *sum += component);
+                    sum
+                });
+
+                sum.iter().map(|sum| sum / count).collect()
+            }
+            ReadbackMode::LuminanceRange => {
+                // Rec. 709 coefficients, applied to the stored values as they are.
+                let range = pixels.map(|[r, g, b, _]| 0.2126 * r + 0.7152 * g + 0.0722 * b).fold(
+                    None,
+                    |range: Option<(f32, f32)>, luminance| match range {
+                        Some((min, max)) => Some((min.min(luminance), max.max(luminance))),
+                        None => Some((luminance, luminance)),
+                    },
+                );
+                let (min, max) = range.unwrap_or((0.0, 0.0));
+
+                vec![min, max]
+            }
+        }
+    }
+}
+
+impl Display for ReadbackMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadbackMode::Pixels => write!(f, "Pixels"),
+            ReadbackMode::AverageColor => write!(f, "Average color"),
+            ReadbackMode::LuminanceRange => write!(f, "Luminance range"),
+        }
+    }
+}
+
+/// The factor the width and the height of the texture are divided by before the readback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Downsample(u32);
+
+impl Downsample {
+    pub const VALUES: [Downsample; 6] =
+        [Downsample(1), Downsample(2), Downsample(4), Downsample(8), Downsample(16), Downsample(32)];
+
+    fn apply(&self, size: Vec2<u32>) -> Vec2<u32> {
+        size.map(|length| (length + self.0 - 1) / self.0)
+    }
+}
+
+impl Display for Downsample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "1/{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TextureReadbackNodeMessage {
+    UpdateMode(ReadbackMode),
+    UpdateDownsample(Downsample),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    mode: ReadbackMode,
+    downsample: Downsample,
+}
+
+/// Reads the texture connected to its input back to the CPU. Readbacks take several executions to
+/// complete, the outputs contain the most recent completed one along with its age in passes.
+#[derive(Clone, Debug)]
+pub struct TextureReadbackNodeBehaviour {
+    mode: ReadbackMode,
+    downsample: Downsample,
+    mode_state: enum_dropdown::State,
+    downsample_state: enum_dropdown::State,
+}
+
+impl Default for TextureReadbackNodeBehaviour {
+    fn default() -> Self {
+        Self {
+            mode: ReadbackMode::AverageColor,
+            downsample: Downsample(4),
+            mode_state: Default::default(),
+            downsample_state: Default::default(),
+        }
+    }
+}
+
+impl TextureReadbackNodeBehaviour {
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                // Swapchain frames can be neither copied from nor sampled.
+                .with_borrow(Channel::new(
+                    "texture",
+                    TextureType::new().with_usage(wgpu::TextureUsage::COPY_SRC | wgpu::TextureUsage::SAMPLED),
+                ))
+                .with_output_value(self.mode.output_channel())
+                .with_output_value(Channel::new("staleness", PrimitiveTypeEnum::U32)),
+        )
+    }
+}
+
+impl NodeBehaviour for TextureReadbackNodeBehaviour {
+    type Message = TextureReadbackNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "TextureReadback"
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                match message {
+                    TextureReadbackNodeMessage::UpdateMode(mode) => self.mode = mode,
+                    TextureReadbackNodeMessage::UpdateDownsample(downsample) => self.downsample = downsample,
+                }
+
+                vec![self.get_configure_command()]
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            Row::new()
+                .theme(theme)
+                .push(
+                    EnumDropdown::new(
+                        &mut self.mode_state,
+                        &ReadbackMode::VALUES[..],
+                        self.mode,
+                        TextureReadbackNodeMessage::UpdateMode,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(
+                    EnumDropdown::new(
+                        &mut self.downsample_state,
+                        &Downsample::VALUES[..],
+                        self.downsample,
+                        TextureReadbackNodeMessage::UpdateDownsample,
+                    )
+                    .theme(theme)
+                    .width(Length::Units(48)),
+                )
+                .align_items(Align::Center)
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(SavedSettings { mode: self.mode, downsample: self.downsample }).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let SavedSettings { mode, downsample } = serde_json::from_value(settings)?;
+
+        if downsample.0 == 0 {
+            return Err(<serde_json::Error as serde::de::Error>::custom(
+                "the downsample factor must not be zero",
+            ));
+        }
+
+        self.mode = mode;
+        self.downsample = downsample;
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let mode = behaviour.mode;
+                let downsample = behaviour.downsample;
+
+                // Readbacks of the previous settings do not match the outputs anymore.
+                persistent.pending = None;
+                persistent.latest = None;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let renderer = if let Some(renderer) = context.renderer() { renderer } else { return };
+                    let pass_index = context.application_context.frame_info.pass_index;
+
+                    if let Some(pending) = persistent.pending.as_mut() {
+                        match pending.readback.try_read(&renderer.device, false) {
+                            Ok(Some(texels)) => {
+                                let pixels = texels.chunks_exact(4).map(|texel| pending.pixel(texel));
+
+                                persistent.latest = Some(CompletedReadback {
+                                    values: mode.reduce(pixels),
+                                    pass_index: pending.pass_index,
+                                });
+                                persistent.pending = None;
+                            }
+                            Ok(None) => (),
+                            Err(error) => {
+                                persistent.pending = None;
+                                context.report_error(format!("Could not read the texture back: {:?}", error));
+                                return;
+                            }
+                        }
+                    }
+
+                    // Start the next readback only once the previous one has completed, so that
+                    // readbacks do not pile up while the GPU is busy.
+                    if persistent.pending.is_none() {
+                        let input = context.borrows[0].clone().downcast_ref::<TextureType>().unwrap();
+                        let input = if let Some(input) = input.texture().owned_texture() {
+                            input
+                        } else {
+                            // Swapchain frames cannot be copied from, leave the outputs empty.
+                            return;
+                        };
+                        let bgra = match input.format {
+                            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+                            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+                            format => {
+                                context.report_error(format!(
+                                    "Textures of the format {:?} cannot be read back, only 8-bit RGBA \
+                                     and BGRA formats are supported.",
+                                    format,
+                                ));
+                                return;
+                            }
+                        };
+                        let mut encoder = context.create_command_encoder();
+                        let source = if downsample.0 > 1 {
+                            let size = downsample.apply(input.size);
+                            let reallocate = persistent.downsampled.as_ref().map_or(true, |downsampled| {
+                                downsampled.size != size || downsampled.format != input.format
+                            });
+
+                            if reallocate {
+                                persistent.downsampled =
+                                    match OwnedTexture::try_new(renderer, size, input.format) {
+                                        Ok(downsampled) => Some(downsampled),
+                                        Err(error) => {
+                                            context.report_error(error);
+                                            return;
+                                        }
+                                    };
+                            }
+
+                            let blit = match persistent.blit.take() {
+                                Some((format, blit)) if format == input.format => blit,
+                                _ => Blit::new(&renderer.device, input.format),
+                            };
+                            let downsampled = persistent.downsampled.as_ref().unwrap();
+
+                            blit.draw(&renderer.device, Some(input), &downsampled.view, &mut encoder);
+                            persistent.blit = Some((input.format, blit));
+                            downsampled
+                        } else {
+                            input
+                        };
+                        let mut readback = TextureReadback::record(&renderer.device, source, &mut encoder);
+
+                        renderer.queue.submit(Some(encoder.finish()));
+                        readback.start_mapping();
+                        persistent.pending = Some(PendingReadback { readback, bgra, pass_index });
+                    }
+
+                    let latest = if let Some(latest) = persistent.latest.as_ref() {
+                        latest
+                    } else {
+                        // No readback has completed yet, leave the outputs empty.
+                        return;
+                    };
+
+                    if mode == ReadbackMode::Pixels {
+                        let max_list_length = context.application_context.config.get().max_list_length;
+
+                        if latest.values.len() > max_list_length {
+                            context.report_error(format!(
+                                "The texture has more pixel components than the maximum length of \
+                                 generated lists, {}, which can be changed in the preferences. \
+                                 Increase the downsample factor instead.",
+                                max_list_length,
+                            ));
+                            return;
+                        }
+
+                        let mut list: OwnedRefMut<Unique<ListType>> =
+                            context.allocator_handle.allocate_object::<ListType>(
+                                ListDescriptor::new_if_sized(PrimitiveTypeEnum::F32).unwrap(),
+                            );
+
+                        {
+                            let mut list = list.deref_mut();
+
+                            for value in &latest.values {
+                                list.push_item_bytes_with(|bytes| {
+                                    Cursor::new(bytes).write_f32::<LittleEndian>(*value).unwrap();
+                                })
+                                .unwrap();
+                            }
+                        }
+
+                        context.outputs[0].replace(list.upcast(), context.allocator_handle).unwrap();
+                    } else {
+                        context.outputs[0]
+                            .replace_with_bytes(context.allocator_handle, |bytes| {
+                                let mut cursor = Cursor::new(bytes);
+
+                                for value in &latest.values {
+                                    cursor.write_f32::<LittleEndian>(*value).unwrap();
+                                }
+                            })
+                            .unwrap();
+                    }
+
+                    let staleness = pass_index.saturating_sub(latest.pass_index);
+
+                    context.outputs[1]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes)
+                                .write_u32::<LittleEndian>(staleness.min(u32::MAX as u64) as u32)
+                                .unwrap();
+                        })
+                        .unwrap();
+                    context.clear_error();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+#[derive(Debug)]
+struct PendingReadback {
+    readback: TextureReadback,
+    /// Whether the red and blue components of the texels are swapped.
+    bgra: bool,
+    /// The pass the readback was started during.
+    pass_index: u64,
+}
+
+impl PendingReadback {
+    /// Normalizes the components of the texel to RGBA.
+    fn pixel(&self, texel: &[u8]) -> [f32; 4] {
+        let component = |index: usize| texel[index] as f32 / 255.0;
+
+        if self.bgra {
+            [component(2), component(1), component(0), component(3)]
+        } else {
+            [component(0), component(1), component(2), component(3)]
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CompletedReadback {
+    values: Vec<f32>,
+    /// The pass the readback was started during.
+    pass_index: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Persistent {
+    /// The readback in flight, at most one at a time.
+    pending: Option<PendingReadback>,
+    latest: Option<CompletedReadback>,
+    /// The target the input is downsampled into, if downsampling.
+    downsampled: Option<OwnedTexture>,
+    /// The pipeline downsampling into targets of the format.
+    blit: Option<(wgpu::TextureFormat, Blit)>,
+}
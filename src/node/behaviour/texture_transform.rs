@@ -0,0 +1,541 @@
+use crate::graph::pipeline::{self, FullscreenPipeline};
+use crate::graph::{OwnedTexture, TextureAllocation};
+use crate::node::prelude::*;
+use crate::node::{
+    OwnedRefMut, PrimitiveChannelValue, PrimitiveTypeEnum, TextureDescriptor, TextureType, Unique,
+};
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType,
+    },
+    style::{Theme, Themeable},
+    widgets::{drag_value, enum_dropdown, DragValue, EnumDropdown},
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use iced::widget::checkbox::Checkbox;
+use iced::{Align, Column, Element, Length, Row};
+use iced_wgpu::wgpu;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use vek::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransformParameter {
+    /// The offset of the image to the right, in widths of the texture.
+    TranslateX,
+    /// The offset of the image downwards, in heights of the texture.
+    TranslateY,
+    /// The clockwise rotation of the image around its center, in degrees.
+    Rotation,
+    /// The factor the image is stretched by horizontally, around its center.
+    ScaleX,
+    /// The factor the image is stretched by vertically, around its center.
+    ScaleY,
+}
+
+impl TransformParameter {
+    pub const VALUES: [TransformParameter; 5] = [
+        TransformParameter::TranslateX,
+        TransformParameter::TranslateY,
+        TransformParameter::Rotation,
+        TransformParameter::ScaleX,
+        TransformParameter::ScaleY,
+    ];
+
+    /// The values of the parameters of the identity transform.
+    pub const IDENTITY: [f32; 5] = [0.0, 0.0, 0.0, 1.0, 1.0];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The title of the input channel of the parameter.
+    pub fn title(self) -> &'static str {
+        match self {
+            TransformParameter::TranslateX => "translate x",
+            TransformParameter::TranslateY => "translate y",
+            TransformParameter::Rotation => "rotation",
+            TransformParameter::ScaleX => "scale x",
+            TransformParameter::ScaleY => "scale y",
+        }
+    }
+
+    /// The label of the field of the parameter.
+    pub fn label(self) -> &'static str {
+        match self {
+            TransformParameter::TranslateX => "X",
+            TransformParameter::TranslateY => "Y",
+            TransformParameter::Rotation => "Rotation",
+            TransformParameter::ScaleX => "Scale X",
+            TransformParameter::ScaleY => "Scale Y",
+        }
+    }
+
+    /// How much the value of the field changes per pixel dragged.
+    fn speed(self) -> f64 {
+        match self {
+            TransformParameter::Rotation => 0.5,
+            _ => 0.005,
+        }
+    }
+}
+
+/// How the input is sampled outside of its bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeMode {
+    /// Repeats the texels at the edges.
+    Clamp,
+    /// Tiles the texture.
+    Repeat,
+    /// Tiles the texture, mirrored every other tile.
+    Mirror,
+}
+
+impl EdgeMode {
+    pub const VALUES: [EdgeMode; 3] = [EdgeMode::Clamp, EdgeMode::Repeat, EdgeMode::Mirror];
+
+    pub fn address_mode(self) -> wgpu::AddressMode {
+        match self {
+            EdgeMode::Clamp => wgpu::AddressMode::ClampToEdge,
+            EdgeMode::Repeat => wgpu::AddressMode::Repeat,
+            EdgeMode::Mirror => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+impl Display for EdgeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdgeMode::Clamp => write!(f, "Clamp"),
+            EdgeMode::Repeat => write!(f, "Repeat"),
+            EdgeMode::Mirror => write!(f, "Mirror"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filtering {
+    Nearest,
+    Linear,
+}
+
+impl Filtering {
+    pub const VALUES: [Filtering; 2] = [Filtering::Nearest, Filtering::Linear];
+
+    pub fn filter_mode(self) -> wgpu::FilterMode {
+        match self {
+            Filtering::Nearest => wgpu::FilterMode::Nearest,
+            Filtering::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+impl Display for Filtering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filtering::Nearest => write!(f, "Nearest"),
+            Filtering::Linear => write!(f, "Linear"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextureTransformSettings {
+    /// The values of the parameters, in the order of [`TransformParameter::VALUES`], used unless
+    /// the parameters are read from input channels.
+    pub values: [f32; 5],
+    /// Whether each parameter is read from an input channel.
+    pub inputs: [bool; 5],
+    pub edge_mode: EdgeMode,
+    pub filtering: Filtering,
+}
+
+impl Default for TextureTransformSettings {
+    fn default() -> Self {
+        Self {
+            values: TransformParameter::IDENTITY,
+            inputs: [false; 5],
+            edge_mode: EdgeMode::Clamp,
+            filtering: Filtering::Linear,
+        }
+    }
+}
+
+/// The affine transform from the texture coordinates of the output to the texture coordinates
+/// the input is sampled at, as the rows of a 2x3 matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvTransform {
+    rows: [[f32; 3]; 2],
+}
+
+impl UvTransform {
+    /// Inverts the transform of the image described by the parameters. The image is rotated in
+    /// pixels rather than in texture coordinates, so that it is not skewed in textures which are
+    /// not square.
+    pub fn new(values: [f32; 5], size: Vec2<u32>) -> Option<Self> {
+        let [translate_x, translate_y, rotation, scale_x, scale_y] = values;
+        let size = size.map(|length| length as f32);
+        let (sin, cos) = (-rotation.to_radians()).sin_cos();
+        let sample = |uv: Vec2<f32>| {
+            // Relative to the center of the transformed image, in pixels.
+            let position = (uv - Vec2::new(0.5 + translate_x, 0.5 + translate_y)) * size;
+            let rotated = Vec2::new(position.x * cos - position.y * sin, position.x * sin + position.y * cos);
+
+            rotated / Vec2::new(scale_x, scale_y) / size + 0.5
+        };
+        let origin = sample(Vec2::zero());
+        let x = sample(Vec2::unit_x()) - origin;
+        let y = sample(Vec2::unit_y()) - origin;
+        let rows = [[x.x, y.x, origin.x], [x.y, y.y, origin.y]];
+
+        // Scaling by zero results in a transform without an inverse.
+        if rows.iter().flatten().all(|value| value.is_finite()) {
+            Some(Self { rows })
+        } else {
+            None
+        }
+    }
+
+    /// The contents of the uniform buffer, two `vec4`s with the rows in their first components.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(UNIFORM_SIZE as usize);
+
+        for row in &self.rows {
+            for &value in row.iter().chain(&[0.0]) {
+                bytes.write_f32::<LittleEndian>(value).unwrap();
+            }
+        }
+
+        bytes
+    }
+}
+
+/// The size of the uniform buffer with the transform.
+const UNIFORM_SIZE: u64 = 2 * 4 * 4;
+
+#[derive(Debug, Clone)]
+pub enum TextureTransformNodeMessage {
+    UpdateValue(TransformParameter, f32),
+    UpdateInput(TransformParameter, bool),
+    UpdateEdgeMode(EdgeMode),
+    UpdateFiltering(Filtering),
+}
+
+#[derive(Debug, Clone, Default)]
+struct TextureTransformUiState {
+    value_states: [drag_value::State; 5],
+    edge_mode_state: enum_dropdown::State,
+    filtering_state: enum_dropdown::State,
+}
+
+/// Resamples the texture connected to its input into a texture of the same size and format,
+/// translated, rotated and scaled around its center.
+#[derive(Debug, Clone)]
+pub struct TextureTransformNodeBehaviour {
+    settings: TextureTransformSettings,
+    ui_state: TextureTransformUiState,
+}
+
+impl Default for TextureTransformNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl TextureTransformNodeBehaviour {
+    pub fn with_settings(settings: TextureTransformSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        // Swapchain frames can be neither copied from nor sampled.
+        let configuration = NodeConfiguration::default().with_borrow(Channel::new(
+            "texture",
+            TextureType::new().with_usage(wgpu::TextureUsage::COPY_SRC | wgpu::TextureUsage::SAMPLED),
+        ));
+        let configuration = TransformParameter::VALUES
+            .iter()
+            .filter(|parameter| self.settings.inputs[parameter.index()])
+            .fold(configuration, |configuration, parameter| {
+                configuration
+                    .with_input_value(Channel::new(parameter.title(), PrimitiveType::<f32>::default()))
+            });
+
+        NodeCommand::Configure(
+            // The format is the same as the format of the input.
+            configuration.with_output_value(Channel::new("texture", Unique::new(TextureType::owned(None)))),
+        )
+    }
+}
+
+impl NodeBehaviour for TextureTransformNodeBehaviour {
+    type Message = TextureTransformNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "TextureTransform"
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use TextureTransformNodeMessage::*;
+
+                match message {
+                    UpdateValue(parameter, value) => {
+                        self.settings.values[parameter.index()] = value;
+                        vec![]
+                    }
+                    UpdateInput(parameter, input) => {
+                        self.settings.inputs[parameter.index()] = input;
+                        vec![self.get_configure_command()]
+                    }
+                    UpdateEdgeMode(edge_mode) => {
+                        self.settings.edge_mode = edge_mode;
+                        vec![]
+                    }
+                    UpdateFiltering(filtering) => {
+                        self.settings.filtering = filtering;
+                        vec![]
+                    }
+                }
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+        let mut column = Column::new().theme(theme).push(
+            Row::new()
+                .theme(theme)
+                .push(
+                    EnumDropdown::new(
+                        &mut ui_state.edge_mode_state,
+                        &EdgeMode::VALUES[..],
+                        settings.edge_mode,
+                        TextureTransformNodeMessage::UpdateEdgeMode,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(
+                    EnumDropdown::new(
+                        &mut ui_state.filtering_state,
+                        &Filtering::VALUES[..],
+                        settings.filtering,
+                        TextureTransformNodeMessage::UpdateFiltering,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .align_items(Align::Center)
+                .width(Length::Fill),
+        );
+
+        for (&parameter, value_state) in
+            TransformParameter::VALUES.iter().zip(ui_state.value_states.iter_mut())
+        {
+            let input = settings.inputs[parameter.index()];
+            let mut row = Row::new().theme(theme);
+
+            // The value of the field is used while the parameter is not read from the input.
+            if !input {
+                row = row.push(
+                    DragValue::new(value_state, settings.values[parameter.index()], move |value| {
+                        TextureTransformNodeMessage::UpdateValue(parameter, value as f32)
+                    })
+                    .label(parameter.label())
+                    .speed(parameter.speed())
+                    .theme(theme)
+                    .width(Length::Fill),
+                );
+            }
+
+            column = column.push(
+                row.push(
+                    Checkbox::new(input, "Input", move |input| {
+                        TextureTransformNodeMessage::UpdateInput(parameter, input)
+                    })
+                    .theme(theme),
+                )
+                .align_items(Align::Center)
+                .width(Length::Fill),
+            );
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        *self = Self::with_settings(serde_json::from_value(settings)?);
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                // Recreated with the edge mode and the filtering of the new settings.
+                persistent.sampler = None;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let input = context.borrows[0].clone().downcast_ref::<TextureType>().unwrap();
+                    let input = if let Some(input) = input.texture().owned_texture() {
+                        input
+                    } else {
+                        // Swapchain frames can be neither copied from nor sampled, leave the output
+                        // empty.
+                        return;
+                    };
+                    let mut values = settings.values;
+                    let mut input_index = 0;
+
+                    for parameter in &TransformParameter::VALUES {
+                        if settings.inputs[parameter.index()] {
+                            let value = PrimitiveTypeEnum::F32
+                                .read::<LittleEndian, _>(&context.inputs[input_index].as_bytes().unwrap())
+                                .unwrap();
+
+                            values[parameter.index()] = match value {
+                                PrimitiveChannelValue::F32(value) => value,
+                                _ => unreachable!(),
+                            };
+                            input_index += 1;
+                        }
+                    }
+
+                    let renderer = if let Some(renderer) = context.renderer() { renderer } else { return };
+                    let output = match OwnedTexture::try_new(renderer, input.size, input.format) {
+                        Ok(output) => output,
+                        Err(error) => {
+                            context.report_error(error);
+                            return;
+                        }
+                    };
+                    let mut encoder = context.create_command_encoder();
+
+                    if values == TransformParameter::IDENTITY {
+                        // The output cannot refer to the input, as it is owned by the downstream
+                        // nodes, but copying is cheaper than resampling.
+                        input.copy_to(&output, &mut encoder);
+                    } else {
+                        let transform = match UvTransform::new(values, input.size) {
+                            Some(transform) => transform,
+                            None => {
+                                context.report_error("The scale must not be zero.");
+                                return;
+                            }
+                        };
+                        let pipeline = match persistent.pipeline.take() {
+                            Some((format, pipeline)) if format == input.format => pipeline,
+                            _ => FullscreenPipeline::new(
+                                &renderer.device,
+                                &renderer.device.create_shader_module(wgpu::include_spirv!(
+                                    "../../shaders/texture_transform.vert.spv"
+                                )),
+                                &pipeline::sample_fragment_module(&renderer.device),
+                                &[
+                                    pipeline::sampled_texture_entry(0),
+                                    pipeline::sampler_entry(1),
+                                    pipeline::uniform_buffer_entry(2, wgpu::ShaderStage::VERTEX),
+                                ],
+                                input.format,
+                            ),
+                        };
+                        let sampler = persistent.sampler.get_or_insert_with(|| {
+                            renderer.device.create_sampler(&wgpu::SamplerDescriptor {
+                                address_mode_u: settings.edge_mode.address_mode(),
+                                address_mode_v: settings.edge_mode.address_mode(),
+                                mag_filter: settings.filtering.filter_mode(),
+                                min_filter: settings.filtering.filter_mode(),
+                                ..Default::default()
+                            })
+                        });
+                        let uniform_buffer = persistent.uniform_buffer.get_or_insert_with(|| {
+                            renderer.device.create_buffer(&wgpu::BufferDescriptor {
+                                label: Some(&format!("node #{}", context.allocator_handle.node.index())),
+                                size: UNIFORM_SIZE,
+                                usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+                                mapped_at_creation: false,
+                            })
+                        });
+
+                        // Only the uniform buffer is updated while the parameters are animated.
+                        if persistent.transform != Some(transform) {
+                            renderer.queue.write_buffer(uniform_buffer, 0, &transform.to_bytes());
+                            persistent.transform = Some(transform);
+                        }
+
+                        let bind_group = pipeline.create_bind_group(
+                            &renderer.device,
+                            vec![
+                                wgpu::BindingResource::TextureView(&input.view),
+                                wgpu::BindingResource::Sampler(sampler),
+                                wgpu::BindingResource::Buffer(uniform_buffer.slice(..)),
+                            ],
+                        );
+
+                        pipeline.draw(
+                            Some(&bind_group),
+                            &output.view,
+                            wgpu::Color::TRANSPARENT,
+                            &mut encoder,
+                        );
+                        persistent.pipeline = Some((input.format, pipeline));
+                    }
+
+                    renderer.queue.submit(Some(encoder.finish()));
+
+                    let output: OwnedRefMut<Unique<TextureType>> =
+                        context.allocator_handle.allocate_object::<TextureType>(TextureDescriptor::new(
+                            TextureAllocation::Texture(output),
+                        ));
+
+                    context.outputs[0].replace(output.upcast(), context.allocator_handle).unwrap();
+                    context.clear_error();
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Persistent {
+    /// The pipeline drawing into targets of the format, kept until the format of the input changes.
+    pipeline: Option<(wgpu::TextureFormat, FullscreenPipeline)>,
+    /// Created once the GPU has been acquired, kept until the settings change.
+    sampler: Option<wgpu::Sampler>,
+    uniform_buffer: Option<wgpu::Buffer>,
+    /// The transform last written to the uniform buffer.
+    transform: Option<UvTransform>,
+}
@@ -0,0 +1,374 @@
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent,
+            NodeIcon, NodeStateClosure,
+        },
+        ArrayType, BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveChannelValue,
+        PrimitiveType, PrimitiveTypeEnum, TypeEnum,
+    },
+    style::{Theme, Themeable},
+    util::easing::Easing,
+    widgets::{enum_dropdown, EnumDropdown},
+};
+use byteorder::{ByteOrder, LittleEndian};
+use iced::canvas::{self, Canvas, Cursor, Frame, Geometry, Path, Stroke};
+use iced::{Align, Color, Column, Element, Length, Point, Rectangle, Row};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Cursor as IoCursor;
+
+/// The height of the canvas the easing curve is drawn onto.
+const PREVIEW_HEIGHT: u16 = 48;
+/// The number of line segments the easing curve is drawn with.
+const PREVIEW_SEGMENTS: usize = 64;
+
+/// How `t` outside of the range from 0 to 1 is treated.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TweenRange {
+    /// Limits `t` to the range, so that the output stays between the inputs, unless the easing
+    /// overshoots.
+    Clamp,
+    /// Continues the easing curve beyond the inputs.
+    Extrapolate,
+}
+
+impl TweenRange {
+    pub const VALUES: [TweenRange; 2] = [TweenRange::Clamp, TweenRange::Extrapolate];
+
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            TweenRange::Clamp => t.max(0.0).min(1.0),
+            TweenRange::Extrapolate => t,
+        }
+    }
+}
+
+impl Display for TweenRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TweenRange::Clamp => write!(f, "Clamp"),
+            TweenRange::Extrapolate => write!(f, "Extrapolate"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TweenSettings {
+    /// The type of the components of the inputs and of the output.
+    pub ty: PrimitiveTypeEnum,
+    /// The number of components of the inputs and of the output, values of more than one
+    /// component are vectors, whose components are interpolated separately.
+    pub components: usize,
+    pub easing: Easing,
+    pub range: TweenRange,
+}
+
+impl Default for TweenSettings {
+    fn default() -> Self {
+        Self { ty: PrimitiveTypeEnum::F32, components: 1, easing: Easing::Linear, range: TweenRange::Clamp }
+    }
+}
+
+impl TweenSettings {
+    /// The selectable numbers of components, as in the vectors of shaders.
+    const COMPONENTS: [usize; 4] = [1, 2, 3, 4];
+
+    fn value_type(&self) -> TypeEnum {
+        if self.components == 1 {
+            self.ty.into()
+        } else {
+            ArrayType::new_if_sized(self.ty, self.components).unwrap().into()
+        }
+    }
+
+    /// Interpolates from `a` to `b`, computed in `f64` and rounded for integer types.
+    pub fn apply(&self, a: PrimitiveChannelValue, b: PrimitiveChannelValue, t: f32) -> PrimitiveChannelValue {
+        let t = self.easing.apply(self.range.apply(t) as f64);
+        let (a, b) = (a.to_f64(), b.to_f64());
+
+        self.ty.from_f64(a + (b - a) * t)
+    }
+
+    /// Interpolates each component of `a` to the one of `b`, the components are stored one after
+    /// another.
+    pub fn apply_components(&self, a: &[u8], b: &[u8], t: f32) -> Vec<PrimitiveChannelValue> {
+        let size = self.ty.value_size();
+
+        a.chunks_exact(size)
+            .zip(b.chunks_exact(size))
+            .take(self.components)
+            .map(|(a, b)| {
+                let a = self.ty.read::<LittleEndian, _>(a).unwrap();
+                let b = self.ty.read::<LittleEndian, _>(b).unwrap();
+
+                self.apply(a, b, t)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TweenNodeMessage {
+    UpdateType(PrimitiveTypeEnum),
+    UpdateComponents(usize),
+    UpdateEasing(Easing),
+    UpdateRange(TweenRange),
+}
+
+/// Interpolates between two values or vectors, with the progress `t` shaped by an easing curve.
+#[derive(Debug, Clone, Default)]
+pub struct TweenNodeBehaviour {
+    settings: TweenSettings,
+    ty_state: enum_dropdown::State,
+    components_state: enum_dropdown::State,
+    easing_state: enum_dropdown::State,
+    range_state: enum_dropdown::State,
+}
+
+impl TweenNodeBehaviour {
+    pub fn get_configure_command(&self) -> NodeCommand {
+        NodeCommand::Configure(
+            NodeConfiguration::default()
+                .with_input_value(Channel::new("a", self.settings.value_type()))
+                .with_input_value(Channel::new("b", self.settings.value_type()))
+                .with_input_value(
+                    Channel::new("t", PrimitiveType::<f32>::default())
+                        .with_description("From 0 at `a` to 1 at `b`"),
+                )
+                .with_output_value(Channel::new("result", self.settings.value_type())),
+        )
+    }
+}
+
+impl NodeBehaviour for TweenNodeBehaviour {
+    type Message = TweenNodeMessage;
+
+    fn name(&self) -> &str {
+        "Tween"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => match message {
+                TweenNodeMessage::UpdateType(ty) => {
+                    self.settings.ty = ty;
+                    vec![self.get_configure_command()]
+                }
+                TweenNodeMessage::UpdateComponents(components) => {
+                    self.settings.components = components;
+                    vec![self.get_configure_command()]
+                }
+                TweenNodeMessage::UpdateEasing(easing) => {
+                    self.settings.easing = easing;
+                    vec![]
+                }
+                TweenNodeMessage::UpdateRange(range) => {
+                    self.settings.range = range;
+                    vec![]
+                }
+            },
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        Some(
+            Column::new()
+                .theme(theme)
+                .push(
+                    Row::new()
+                        .theme(theme)
+                        .push(
+                            EnumDropdown::new(
+                                &mut self.ty_state,
+                                &PrimitiveTypeEnum::VALUES[..],
+                                self.settings.ty,
+                                TweenNodeMessage::UpdateType,
+                            )
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .push(
+                            EnumDropdown::new(
+                                &mut self.components_state,
+                                &TweenSettings::COMPONENTS[..],
+                                self.settings.components,
+                                TweenNodeMessage::UpdateComponents,
+                            )
+                            .theme(theme)
+                            .width(Length::Units(40)),
+                        )
+                        .push(
+                            EnumDropdown::new(
+                                &mut self.range_state,
+                                &TweenRange::VALUES[..],
+                                self.settings.range,
+                                TweenNodeMessage::UpdateRange,
+                            )
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .align_items(Align::Center)
+                        .width(Length::Fill),
+                )
+                .push(
+                    EnumDropdown::new(
+                        &mut self.easing_state,
+                        &Easing::VALUES[..],
+                        self.settings.easing,
+                        TweenNodeMessage::UpdateEasing,
+                    )
+                    .theme(theme)
+                    .width(Length::Fill),
+                )
+                .push(
+                    Canvas::new(EasingCurve {
+                        easing: self.settings.easing,
+                        color: theme.status_colors().highlight,
+                    })
+                    .width(Length::Fill)
+                    .height(Length::Units(PREVIEW_HEIGHT)),
+                )
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.settings = serde_json::from_value(settings)?;
+        Ok(())
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            (),
+            move |behaviour: &Self, _application_context: &ApplicationContext, _persistent: &mut ()| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings;
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, _persistent: &mut ()| {
+                    // Executed once per graph execution.
+                    let t = LittleEndian::read_f32(context.inputs[2].as_bytes().unwrap());
+                    let result = settings.apply_components(
+                        context.inputs[0].as_bytes().unwrap(),
+                        context.inputs[1].as_bytes().unwrap(),
+                        t,
+                    );
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            let mut output_cursor = IoCursor::new(bytes);
+
+                            for component in &result {
+                                component.write::<LittleEndian>(&mut output_cursor).unwrap();
+                            }
+                        })
+                        .unwrap();
+                }) as Box<dyn ExecutorClosure<'state> + 'state>
+            },
+        )
+    }
+}
+
+/// Draws the easing curve for `t` from 0 to 1, scaled vertically to fit overshooting curves.
+struct EasingCurve {
+    easing: Easing,
+    color: Color,
+}
+
+impl canvas::Program<TweenNodeMessage> for EasingCurve {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        /// Keeps the stroke within the bounds.
+        const PADDING: f32 = 2.0;
+
+        let mut frame = Frame::new(bounds.size());
+        let samples = (0..=PREVIEW_SEGMENTS)
+            .map(|index| {
+                let t = index as f64 / PREVIEW_SEGMENTS as f64;
+
+                (t, self.easing.apply(t))
+            })
+            .collect::<Vec<_>>();
+        let min = samples.iter().map(|(_, value)| *value).fold(0.0, f64::min);
+        let max = samples.iter().map(|(_, value)| *value).fold(1.0, f64::max);
+        let width = bounds.width - 2.0 * PADDING;
+        let height = bounds.height - 2.0 * PADDING;
+        let to_point = |(t, value): (f64, f64)| {
+            Point::new(
+                PADDING + t as f32 * width,
+                PADDING + (1.0 - ((value - min) / (max - min)) as f32) * height,
+            )
+        };
+        let path = Path::new(|builder| {
+            builder.move_to(to_point(samples[0]));
+
+            for sample in &samples[1..] {
+                builder.line_to(to_point(*sample));
+            }
+        });
+
+        frame.stroke(&path, Stroke { color: self.color, width: 1.5, ..Default::default() });
+
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(components: &[f32]) -> Vec<u8> {
+        let mut bytes = vec![0; components.len() * 4];
+
+        LittleEndian::write_f32_into(components, &mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn vectors_are_interpolated_per_component() {
+        let settings = TweenSettings { components: 3, ..Default::default() };
+        let (a, b) = (bytes(&[0.0, 10.0, -2.0]), bytes(&[1.0, 20.0, 2.0]));
+        let result = |settings: &TweenSettings, t| {
+            settings
+                .apply_components(&a, &b, t)
+                .into_iter()
+                .map(|component| component.to_f64())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(result(&settings, 0.25), vec![0.25, 12.5, -1.0]);
+        assert_eq!(result(&settings, 2.0), vec![1.0, 20.0, 2.0]);
+
+        let settings = TweenSettings { range: TweenRange::Extrapolate, ..settings };
+
+        assert_eq!(result(&settings, 2.0), vec![2.0, 30.0, 6.0]);
+    }
+
+    #[test]
+    fn integers_are_rounded_and_saturated() {
+        use PrimitiveChannelValue::U8;
+        let settings = TweenSettings { ty: PrimitiveTypeEnum::U8, ..Default::default() };
+
+        assert_eq!(settings.apply(U8(0), U8(3), 0.5), U8(2));
+        assert_eq!(settings.apply(U8(0), U8(3), 0.4), U8(1));
+
+        let settings = TweenSettings { range: TweenRange::Extrapolate, ..settings };
+
+        assert_eq!(settings.apply(U8(250), U8(255), 2.0), U8(255));
+        assert_eq!(settings.apply(U8(5), U8(0), 2.0), U8(0));
+    }
+}
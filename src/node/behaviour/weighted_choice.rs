@@ -0,0 +1,345 @@
+use crate::node::behaviour::random::{seed_field, SeededPrng, TriggerInput};
+use crate::node::PrimitiveChannelValue;
+use crate::{
+    node::{
+        behaviour::{
+            ApplicationContext, ConnectedTypeInfo, ExecutionContext, ExecutorClosure, NodeBehaviour,
+            NodeCommand, NodeEvent, NodeIcon, NodeStateClosure, NodeValidationIssue,
+        },
+        BytesRefExt, Channel, NodeConfiguration, OptionRefMutExt, PrimitiveType, PrimitiveTypeEnum,
+    },
+    style::{Theme, Themeable},
+    widgets::{drag_value, DragValue},
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use iced::{
+    button::{self, Button},
+    pick_list::{self, PickList},
+    Align, Column, Container, Element, Length, Row, Text,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Cursor;
+
+/// How the choice of a [`WeightedChoiceNodeBehaviour`] is output.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum WeightedChoiceOutput {
+    /// An output per weight, 1 while the trigger which chose it is high, 0 otherwise.
+    Outputs,
+    /// A single output holding the index of the last choice.
+    Index,
+}
+
+impl WeightedChoiceOutput {
+    pub const VALUES: [WeightedChoiceOutput; 2] =
+        [WeightedChoiceOutput::Outputs, WeightedChoiceOutput::Index];
+}
+
+impl Display for WeightedChoiceOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeightedChoiceOutput::Outputs => write!(f, "Outputs"),
+            WeightedChoiceOutput::Index => write!(f, "Index"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightedChoiceSettings {
+    /// Not normalized, the probability of a choice is its weight divided by the sum of the weights.
+    pub weights: Vec<f32>,
+    pub output: WeightedChoiceOutput,
+    pub seed: u32,
+}
+
+impl Default for WeightedChoiceSettings {
+    fn default() -> Self {
+        Self { weights: vec![1.0, 1.0], output: WeightedChoiceOutput::Outputs, seed: 0 }
+    }
+}
+
+impl WeightedChoiceSettings {
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.weights.is_empty() {
+            Err("There must be at least one weight.")
+        } else if !self.weights.iter().all(|weight| *weight >= 0.0 && weight.is_finite()) {
+            Err("The weights must not be negative.")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum WeightedChoiceNodeMessage {
+    UpdateWeight(usize, f32),
+    AddWeight,
+    RemoveWeight,
+    UpdateOutput(WeightedChoiceOutput),
+    UpdateSeed(u32),
+}
+
+#[derive(Debug, Clone, Default)]
+struct WeightedChoiceUiState {
+    /// The labels and the states of the fields of the weights.
+    weight_fields: Vec<(String, drag_value::State)>,
+    output_state: pick_list::State<WeightedChoiceOutput>,
+    seed_state: drag_value::State,
+    button_add_state: button::State,
+    button_remove_state: button::State,
+}
+
+/// Chooses one of several outputs on each trigger, with probabilities proportional to their
+/// weights.
+#[derive(Debug, Clone)]
+pub struct WeightedChoiceNodeBehaviour {
+    settings: WeightedChoiceSettings,
+    ui_state: WeightedChoiceUiState,
+}
+
+impl Default for WeightedChoiceNodeBehaviour {
+    fn default() -> Self {
+        Self::with_settings(Default::default())
+    }
+}
+
+impl WeightedChoiceNodeBehaviour {
+    pub fn with_settings(settings: WeightedChoiceSettings) -> Self {
+        Self { settings, ui_state: Default::default() }
+    }
+
+    pub fn get_configure_command(&self) -> NodeCommand {
+        let configuration = NodeConfiguration::default()
+            .with_input_value(Channel::new("trigger", PrimitiveType::<f32>::default()));
+        let configuration = match self.settings.output {
+            WeightedChoiceOutput::Outputs => {
+                (0..self.settings.weights.len()).fold(configuration, |configuration, index| {
+                    configuration.with_output_value(Channel::new(
+                        format!("choice #{}", index),
+                        PrimitiveType::<f32>::default(),
+                    ))
+                })
+            }
+            WeightedChoiceOutput::Index => configuration.with_output_value(
+                Channel::new("index", PrimitiveType::<u32>::default())
+                    .with_description("The index of the last choice"),
+            ),
+        };
+
+        NodeCommand::Configure(configuration)
+    }
+}
+
+impl NodeBehaviour for WeightedChoiceNodeBehaviour {
+    type Message = WeightedChoiceNodeMessage;
+    type State<'state> = NodeStateClosure<'state, Self, Persistent>;
+
+    fn name(&self) -> &str {
+        "Weighted Choice"
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Operation)
+    }
+
+    fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
+        match event {
+            NodeEvent::Update => vec![self.get_configure_command()],
+            NodeEvent::Message(message) => {
+                use WeightedChoiceNodeMessage::*;
+
+                // The fields keep the values within the valid ranges.
+                match message {
+                    UpdateWeight(index, weight) => {
+                        if let Some(current) = self.settings.weights.get_mut(index) {
+                            *current = weight;
+                        }
+
+                        vec![]
+                    }
+                    AddWeight => {
+                        self.settings.weights.push(1.0);
+                        vec![self.get_configure_command()]
+                    }
+                    RemoveWeight => {
+                        if self.settings.weights.len() > 1 {
+                            self.settings.weights.pop();
+                            vec![self.get_configure_command()]
+                        } else {
+                            vec![]
+                        }
+                    }
+                    UpdateOutput(output) => {
+                        self.settings.output = output;
+                        vec![self.get_configure_command()]
+                    }
+                    UpdateSeed(seed) => {
+                        self.settings.seed = seed;
+                        vec![]
+                    }
+                }
+            }
+        }
+    }
+
+    fn view(&mut self, theme: &dyn Theme) -> Option<Element<Self::Message>> {
+        let settings = &self.settings;
+        let ui_state = &mut self.ui_state;
+        let weight_count = settings.weights.len();
+
+        ui_state.weight_fields.truncate(weight_count);
+
+        while ui_state.weight_fields.len() < weight_count {
+            let label = format!("#{}", ui_state.weight_fields.len());
+
+            ui_state.weight_fields.push((label, Default::default()));
+        }
+
+        let mut column = Column::new()
+            .theme(theme)
+            .push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        // Wrap PickList in a container because PickList's width resolution is buggy
+                        Container::new(
+                            PickList::new(
+                                &mut ui_state.output_state,
+                                &WeightedChoiceOutput::VALUES[..],
+                                Some(settings.output),
+                                WeightedChoiceNodeMessage::UpdateOutput,
+                            )
+                            .theme(theme)
+                            .width(Length::Fill),
+                        )
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        Button::new(&mut ui_state.button_add_state, Text::new("+"))
+                            .on_press(WeightedChoiceNodeMessage::AddWeight),
+                    )
+                    .push(
+                        Button::new(&mut ui_state.button_remove_state, Text::new("-"))
+                            .on_press(WeightedChoiceNodeMessage::RemoveWeight),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            )
+            .push(seed_field(
+                &mut ui_state.seed_state,
+                settings.seed,
+                WeightedChoiceNodeMessage::UpdateSeed,
+                theme,
+            ));
+
+        for (index, ((label, state), weight)) in
+            ui_state.weight_fields.iter_mut().zip(settings.weights.iter()).enumerate()
+        {
+            column = column.push(
+                DragValue::new(state, *weight, move |weight| {
+                    WeightedChoiceNodeMessage::UpdateWeight(index, weight as f32)
+                })
+                .label(label)
+                .speed(0.01)
+                .range(0.0, f64::INFINITY)
+                .theme(theme)
+                .width(Length::Fill),
+            );
+        }
+
+        Some(column.width(Length::Fill).into())
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let settings: WeightedChoiceSettings = serde_json::from_value(settings)?;
+
+        settings.validate().map_err(<serde_json::Error as serde::de::Error>::custom)?;
+
+        *self = Self::with_settings(settings);
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _configuration: &NodeConfiguration,
+        _connected: ConnectedTypeInfo<'_>,
+    ) -> Vec<NodeValidationIssue> {
+        if self.settings.weights.iter().all(|weight| *weight == 0.0) {
+            vec![NodeValidationIssue::warning("All weights are zero, the choices are equally likely.")]
+        } else {
+            vec![]
+        }
+    }
+
+    fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
+        NodeStateClosure::new(
+            self,
+            application_context,
+            Persistent::default(),
+            move |behaviour: &Self,
+                  _application_context: &ApplicationContext,
+                  _persistent: &mut Persistent| {
+                // Executed when the node settings have been changed to create the following
+                // executor closure.
+                let settings = behaviour.settings.clone();
+
+                Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // Executed once per graph execution.
+                    let trigger = match PrimitiveTypeEnum::F32
+                        .read::<LittleEndian, _>(&context.inputs[0].as_bytes().unwrap())
+                        .unwrap()
+                    {
+                        PrimitiveChannelValue::F32(trigger) => trigger,
+                        _ => unreachable!(),
+                    };
+                    let (high, rising) = persistent.trigger.advance(trigger);
+
+                    if rising {
+                        persistent.choice =
+                            persistent.prng.get(settings.seed).choose_weighted(&settings.weights);
+                    }
+
+                    // The weight of the last choice may have been removed since.
+                    let choice = persistent.choice.filter(|choice| *choice < settings.weights.len());
+
+                    match settings.output {
+                        WeightedChoiceOutput::Outputs => {
+                            for (index, output) in context.outputs.iter_mut().enumerate() {
+                                let value = if high && choice == Some(index) { 1.0 } else { 0.0 };
+
+                                output
+                                    .replace_with_bytes(context.allocator_handle, |bytes| {
+                                        Cursor::new(bytes).write_f32::<LittleEndian>(value).unwrap();
+                                    })
+                                    .unwrap();
+                            }
+                        }
+                        WeightedChoiceOutput::Index => {
+                            let index = choice.unwrap_or(0) as u32;
+
+                            context.outputs[0]
+                                .replace_with_bytes(context.allocator_handle, |bytes| {
+                                    Cursor::new(bytes).write_u32::<LittleEndian>(index).unwrap();
+                                })
+                                .unwrap();
+                        }
+                    }
+                }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
+            },
+        )
+    }
+}
+
+/// Kept when the schedule is regenerated, so that the sequence of choices continues where it was
+/// when the weights change, unless the seed is changed.
+#[derive(Default, Debug, Clone)]
+pub struct Persistent {
+    prng: SeededPrng,
+    trigger: TriggerInput,
+    /// `None` until the first trigger.
+    choice: Option<usize>,
+}
@@ -1,22 +1,52 @@
-use crate::graph::ApplicationContext;
+//! The window node, presenting its input into a window of its own.
+//!
+//! Frames are presented by a thread per window, see [`Presenter`]. Nothing is presented while the
+//! window is minimized, hidden or has a zero dimension, and the `visible` output of the node is 0
+//! meanwhile.
+//!
+//! # Manual verification
+//!
+//! Presenting depends on the windowing system and the GPU, which the unit tests do not cover.
+//! After changing the presentation, check that:
+//!
+//! - minimizing the window sets `visible` to 0 and restoring it presents again,
+//! - resizing the window to a zero height does not fail the validation of the swapchain,
+//! - toggling the Minimized and Visible settings of the node suspends and resumes presenting,
+//! - resizing the window by dragging presents at the new size without stretched frames,
+//! - a lost surface, such as after switching GPUs or locking the session, is recovered from, or
+//!   reported as the error of the node until the window is resized.
+
+use crate::graph::pipeline::{self, FullscreenPipeline};
+use crate::graph::{
+    ApplicationContext, OwnedTexture, Renderer, TextureCapabilityError, WindowSizeHandle, WindowSurfaceHandle,
+};
+use crate::logging::Category;
+use crate::node::prelude::*;
 use crate::{
     node::{
         behaviour::{
-            ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent, NodeStateClosure,
+            ExecutionContext, ExecutorClosure, NodeBehaviour, NodeCommand, NodeEvent, NodeIcon,
+            NodeStateClosure,
         },
-        NodeConfiguration,
+        Channel, NodeConfiguration, PrimitiveType, TextureType,
     },
-    style::{Theme, Themeable},
+    style::{consts, Theme, Themeable},
 };
+use byteorder::WriteBytesExt;
 use flume::{self, Receiver};
 use iced::widget::checkbox::Checkbox;
 use iced::widget::text_input::{self, TextInput};
-use iced::{Column, Element, Row};
+use iced::{Column, Element, Row, Text};
 use iced_wgpu::wgpu;
 use iced_winit::winit;
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::fmt::Debug;
-use std::sync::Arc;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 use vek::Vec2;
 use winit::dpi::PhysicalSize;
 use winit::event_loop::EventLoopWindowTarget;
@@ -135,6 +165,22 @@ impl WindowSettings {
     }
 }
 
+/// The persisted subset of [`WindowSettings`], the fullscreen mode depends on the connected
+/// monitors and is not persisted.
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    title: String,
+    inner_size: Vec2<u32>,
+    always_on_top: bool,
+    cursor_grab: bool,
+    cursor_visible: bool,
+    decorations: bool,
+    maximized: bool,
+    minimized: bool,
+    resizable: bool,
+    visible: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct UiState {
     title_state: text_input::State,
@@ -144,10 +190,22 @@ pub struct UiState {
     height_string: String,
 }
 
+/// Counts the frames that were not presented exactly once, because the executor and the display
+/// run at different rates.
+#[derive(Debug, Default)]
+pub struct PresentStatistics {
+    /// Frames replaced by a newer frame before they were presented.
+    dropped: AtomicU64,
+    /// Presentations of a frame that had already been presented.
+    repeated: AtomicU64,
+}
+
 #[derive(Clone, Debug)]
 pub struct WindowNodeBehaviour {
     settings: WindowSettings,
     ui_state: UiState,
+    /// Shared with the present thread of the window.
+    statistics: Arc<PresentStatistics>,
 }
 
 impl Default for WindowNodeBehaviour {
@@ -163,14 +221,25 @@ impl Default for WindowNodeBehaviour {
                 height_string: settings.inner_size[1].to_string(),
             },
             settings,
+            statistics: Default::default(),
         }
     }
 }
 
 impl WindowNodeBehaviour {
     pub fn get_configure_command(&self) -> NodeCommand {
-        // TODO: Output a framebuffer
-        NodeCommand::Configure(NodeConfiguration::default())
+        NodeCommand::Configure(
+            // The input is copied into the textures presented by the present thread.
+            NodeConfiguration::default()
+                .with_borrow(Channel::new(
+                    "texture",
+                    TextureType::new().with_usage(wgpu::TextureUsage::COPY_SRC),
+                ))
+                .with_output_value(Channel::new("visible", PrimitiveType::<u8>::default()).with_description(
+                    "1 while the window is presented into, 0 while it is minimized, hidden or has a zero \
+                     size, so that the nodes rendering into it can be turned off",
+                )),
+        )
     }
 }
 
@@ -182,6 +251,18 @@ impl NodeBehaviour for WindowNodeBehaviour {
         "Window"
     }
 
+    fn is_sink(&self) -> bool {
+        true
+    }
+
+    fn is_external(&self) -> bool {
+        true
+    }
+
+    fn icon(&self) -> Option<NodeIcon> {
+        Some(NodeIcon::Output)
+    }
+
     fn update(&mut self, event: NodeEvent<Self::Message>) -> Vec<NodeCommand> {
         match event {
             NodeEvent::Update => vec![self.get_configure_command()],
@@ -320,10 +401,57 @@ impl NodeBehaviour for WindowNodeBehaviour {
                     })
                     .theme(theme),
                 )
+                .push(
+                    Text::new(format!(
+                        "Dropped frames: {}, repeated frames: {}",
+                        self.statistics.dropped.load(Ordering::Relaxed),
+                        self.statistics.repeated.load(Ordering::Relaxed),
+                    ))
+                    .size(consts::TEXT_SIZE_REGULAR),
+                )
                 .into(),
         )
     }
 
+    fn save(&self) -> serde_json::Value {
+        let settings = &self.settings;
+
+        serde_json::to_value(SavedSettings {
+            title: settings.title.to_string(),
+            inner_size: settings.inner_size,
+            always_on_top: settings.always_on_top,
+            cursor_grab: settings.cursor_grab,
+            cursor_visible: settings.cursor_visible,
+            decorations: settings.decorations,
+            maximized: settings.maximized,
+            minimized: settings.minimized,
+            resizable: settings.resizable,
+            visible: settings.visible,
+        })
+        .unwrap()
+    }
+
+    fn load(&mut self, settings: serde_json::Value) -> Result<(), serde_json::Error> {
+        let saved: SavedSettings = serde_json::from_value(settings)?;
+
+        self.settings = WindowSettings {
+            title: Cow::Owned(saved.title),
+            inner_size: saved.inner_size,
+            fullscreen: None,
+            always_on_top: saved.always_on_top,
+            cursor_grab: saved.cursor_grab,
+            cursor_visible: saved.cursor_visible,
+            decorations: saved.decorations,
+            maximized: saved.maximized,
+            minimized: saved.minimized,
+            resizable: saved.resizable,
+            visible: saved.visible,
+        };
+        self.ui_state.width_string = saved.inner_size[0].to_string();
+        self.ui_state.height_string = saved.inner_size[1].to_string();
+        Ok(())
+    }
+
     fn create_state<'state>(&self, application_context: &ApplicationContext) -> Self::State<'state> {
         NodeStateClosure::new(
             self,
@@ -337,56 +465,100 @@ impl NodeBehaviour for WindowNodeBehaviour {
 
                 // Copy the constant value from the GUI settings.
                 let settings = behaviour.settings.clone();
+                let statistics = behaviour.statistics.clone();
 
                 Box::new(move |context: ExecutionContext<'_, 'state>, persistent: &mut Persistent| {
+                    // The window is only opened once it can be presented into.
+                    let renderer = if let Some(renderer) = context.renderer() { renderer } else { return };
+
                     if persistent.window.is_none() {
                         if let Some(window_receiver) = persistent.window_receiver.as_mut() {
                             // The window creation task has been sent, poll the response.
-                            if let Ok(window) = window_receiver.try_recv() {
-                                persistent.window = Some(WindowSurface::from(window, &context));
+                            if let Ok((window, size_handle)) = window_receiver.try_recv() {
+                                let surface = unsafe { renderer.instance.create_surface(&window) };
+                                let extent = settings.presented_extent(None);
+
+                                persistent.present_thread = Some(PresentThread::spawn(
+                                    surface,
+                                    renderer.clone(),
+                                    extent,
+                                    statistics.clone(),
+                                ));
+                                persistent.surface =
+                                    Some(context.register_window_surface(settings.swapchain_extent()));
+                                persistent.size_handle = Some(size_handle);
+                                persistent.window = Some(window);
                             }
                         } else {
                             // If the window creation task was not sent yet, send it.
                             let window_attributes = settings.get_builder().window;
+                            let window_sizes = context.application_context.window_sizes.clone();
                             let (window_sender, window_receiver) = flume::unbounded();
                             let task =
                                 Box::new(move |window_target: &EventLoopWindowTarget<crate::Message>| {
                                     let mut builder = WindowBuilder::new();
                                     builder.window = window_attributes;
                                     let window = builder.build(window_target).unwrap();
-                                    let _result = window_sender.send(window);
+                                    let size_handle = window_sizes.track(&window);
+                                    let _result = window_sender.send((window, size_handle));
                                 });
-                            let _result = context.application_context.main_thread_task_sender.send(task);
-                            persistent.window_receiver = Some(window_receiver);
+                            // If the queue is full, the submission is retried during the next execution.
+                            if context.submit_main_thread_task(task).is_ok() {
+                                persistent.window_receiver = Some(window_receiver);
+                            }
                         }
                     }
 
-                    if let Some(window) = persistent.window.as_mut() {
-                        let recreate_swapchain =
-                            persistent.current_settings.inner_size != settings.inner_size;
-
-                        persistent.current_settings.apply_difference(&settings, &window.window);
-
-                        if window.swapchain.is_none() || recreate_swapchain {
-                            window.swapchain =
-                                Some(context.application_context.renderer.device.create_swap_chain(
-                                    &window.surface,
-                                    &wgpu::SwapChainDescriptor {
-                                        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-                                        format: wgpu::TextureFormat::Bgra8UnormSrgb,
-                                        width: persistent.current_settings.inner_size[0],
-                                        height: persistent.current_settings.inner_size[1],
-                                        present_mode: wgpu::PresentMode::Mailbox,
-                                    },
-                                ));
+                    let extent = match (persistent.window.as_ref(), persistent.present_thread.as_ref()) {
+                        (Some(window), Some(present_thread)) => {
+                            persistent.current_settings.apply_difference(&settings, window);
+
+                            let observed_size = context.application_context.window_sizes.get(window.id());
+                            let (extent, resized) =
+                                present_thread.presenter.resize_to_window(&settings, observed_size);
+
+                            if let (true, Some(surface), Some(extent)) =
+                                (resized, persistent.surface.as_ref(), extent)
+                            {
+                                surface.set_size(extent);
+                            }
+
+                            extent
                         }
+                        _ => None,
+                    };
+
+                    context.outputs[0]
+                        .replace_with_bytes(context.allocator_handle, |bytes| {
+                            Cursor::new(bytes).write_u8(is_visible(extent) as u8).unwrap();
+                        })
+                        .unwrap();
+
+                    // Neither copy nor present the input while the window cannot be presented into.
+                    let present_thread = match persistent.present_thread.as_ref() {
+                        Some(present_thread) if extent.is_some() => present_thread,
+                        _ => {
+                            context.clear_error();
+                            return;
+                        }
+                    };
+
+                    if let Some(failure) = present_thread.presenter.failure() {
+                        context.report_error(failure);
+                        return;
+                    }
 
-                        // Drop the previous swapchain frame, presenting it.
-                        window.swapchain_frame = None;
-                        let swapchain = window.swapchain.as_mut().unwrap();
-                        // Unwrap safe, because we made sure to drop the previous frame.
-                        let frame = swapchain.get_current_frame().unwrap();
-                        window.swapchain_frame = Some(frame);
+                    let input = context.borrows[0].clone().downcast_ref::<TextureType>().unwrap();
+                    let input = if let Some(input) = input.texture().owned_texture() {
+                        input
+                    } else {
+                        context.report_error("Only textures owned by nodes can be presented.");
+                        return;
+                    };
+
+                    match present_thread.presenter.submit(input, renderer, &context) {
+                        Ok(()) => context.clear_error(),
+                        Err(error) => context.report_error(error),
                     }
                 }) as Box<dyn ExecutorClosure<'state, Persistent> + 'state>
             },
@@ -394,28 +566,501 @@ impl NodeBehaviour for WindowNodeBehaviour {
     }
 }
 
+/// The format of the swapchains of the windows.
+const SWAPCHAIN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+/// How long the present thread sleeps between checks of the extent, while nothing is presented.
+const SUSPENDED_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+impl WindowSettings {
+    /// The size of the swapchain, which must not be zero.
+    fn swapchain_extent(&self) -> Vec2<u32> {
+        self.inner_size.map(|x| std::cmp::max(1, x))
+    }
+
+    /// The size of the swapchain of a window of the observed inner size, or of the configured size,
+    /// if it has not been observed yet. `None` while the window is minimized or hidden, or while its
+    /// surface has a zero dimension, such as when minimized on some platforms, in which case
+    /// nothing is presented.
+    fn presented_extent(&self, observed_size: Option<Vec2<u32>>) -> Option<Vec2<u32>> {
+        let size = observed_size.unwrap_or(self.inner_size);
+
+        if self.minimized || !self.visible || size.x == 0 || size.y == 0 {
+            None
+        } else {
+            Some(size)
+        }
+    }
+}
+
+/// The value of the `visible` output, whether anything is presented at the extent, see
+/// [`WindowSettings::presented_extent`].
+fn is_visible(extent: Option<Vec2<u32>>) -> bool {
+    extent.is_some()
+}
+
+/// Hands the frames over from the executor to the present thread of a window, through a pair of
+/// textures. The executor copies its input into the back texture and flips the textures, while the
+/// present thread draws the front texture into the swapchain at the pace of the display, so that
+/// a slow window does not block the execution of the graph.
 #[derive(Debug)]
-pub struct WindowSurface {
-    window: Window,
-    surface: wgpu::Surface,
-    swapchain: Option<wgpu::SwapChain>,
-    swapchain_frame: Option<wgpu::SwapChainFrame>,
+pub struct Presenter {
+    /// Locked while being written to by the executor or read from by the present thread.
+    textures: [Mutex<Option<OwnedTexture>>; 2],
+    /// The index of the front texture, only changed by the executor.
+    front: AtomicUsize,
+    /// Whether the front texture has not been presented yet.
+    fresh: AtomicBool,
+    /// The size of the swapchain, `None` while nothing is presented, see
+    /// [`WindowSettings::presented_extent`]. Locked during the whole presentation of a frame, so
+    /// that a frame is never presented into a swapchain of an outdated size.
+    extent: Mutex<Option<Vec2<u32>>>,
+    /// Why frames cannot be presented into the window, reported as the error of the node.
+    failure: Mutex<Option<&'static str>>,
+    closed: AtomicBool,
+    statistics: Arc<PresentStatistics>,
 }
 
-impl WindowSurface {
-    pub fn from(window: Window, context: &ExecutionContext<'_, '_>) -> Self {
+impl Presenter {
+    fn new(extent: Option<Vec2<u32>>, statistics: Arc<PresentStatistics>) -> Self {
         Self {
-            surface: unsafe { context.application_context.renderer.instance.create_surface(&window) },
-            window,
-            swapchain: None,
-            swapchain_frame: None,
+            textures: Default::default(),
+            front: AtomicUsize::new(0),
+            fresh: AtomicBool::new(false),
+            extent: Mutex::new(extent),
+            failure: Mutex::new(None),
+            closed: AtomicBool::new(false),
+            statistics,
+        }
+    }
+
+    /// Copies the texture into the back texture and makes it the front texture.
+    fn submit(
+        &self,
+        input: &OwnedTexture,
+        renderer: &Renderer,
+        context: &ExecutionContext<'_, '_>,
+    ) -> Result<(), TextureCapabilityError> {
+        let back = 1 - self.front.load(Ordering::Acquire);
+
+        {
+            let mut texture = self.textures[back].lock().unwrap();
+            let reallocate = texture
+                .as_ref()
+                .map_or(true, |texture| texture.size != input.size || texture.format != input.format);
+
+            if reallocate {
+                *texture = Some(OwnedTexture::try_new(renderer, input.size, input.format)?);
+            }
+
+            let mut encoder = context.create_command_encoder();
+
+            input.copy_to(texture.as_ref().unwrap(), &mut encoder);
+            renderer.queue.submit(Some(encoder.finish()));
+        }
+
+        self.front.store(back, Ordering::Release);
+
+        if self.fresh.swap(true, Ordering::AcqRel) {
+            self.statistics.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    /// The swapchain is recreated before the next frame, without waiting for the frame being
+    /// presented. If the extent is `None`, the swapchain is dropped instead, and recreated once
+    /// there is an extent again. Returns whether the extent changed.
+    fn resize(&self, extent: Option<Vec2<u32>>) -> bool {
+        let mut current = self.extent.lock().unwrap();
+        let changed = *current != extent;
+
+        *current = extent;
+        changed
+    }
+
+    /// Resizes the swapchain to the extent presented into a window of the observed size, see
+    /// [`WindowSettings::presented_extent`]. Returns the extent and whether it changed.
+    fn resize_to_window(
+        &self,
+        settings: &WindowSettings,
+        observed_size: Option<Vec2<u32>>,
+    ) -> (Option<Vec2<u32>>, bool) {
+        let extent = settings.presented_extent(observed_size);
+
+        (extent, self.resize(extent))
+    }
+
+    pub fn failure(&self) -> Option<&'static str> {
+        *self.failure.lock().unwrap()
+    }
+
+    fn fail(&self, failure: &'static str) {
+        warn!(Category::Executor, "{}", failure);
+        *self.failure.lock().unwrap() = Some(failure);
+    }
+
+    fn run(&self, surface: wgpu::Surface, renderer: Renderer) {
+        let blit = Blit::new(&renderer.device, SWAPCHAIN_FORMAT);
+        let mut swapchain: Option<(wgpu::SwapChain, Vec2<u32>)> = None;
+        let mut recovery = SwapchainRecovery::default();
+
+        while !self.closed.load(Ordering::Acquire) {
+            // Copied, so that the window is not blocked from being resized while the frame is
+            // being acquired, which may wait for the display.
+            let extent = *self.extent.lock().unwrap();
+            let extent = match recovery.presented_extent(extent) {
+                Some(extent) => extent,
+                None => {
+                    // Nothing is presented while the window is minimized, the swapchain is
+                    // recreated once it is restored.
+                    if extent.is_none() {
+                        swapchain = None;
+                    }
+
+                    thread::sleep(SUSPENDED_POLL_INTERVAL);
+                    continue;
+                }
+            };
+
+            if swapchain.as_ref().map_or(true, |(_, swapchain_extent)| *swapchain_extent != extent) {
+                let descriptor = wgpu::SwapChainDescriptor {
+                    usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+                    format: SWAPCHAIN_FORMAT,
+                    width: extent[0],
+                    height: extent[1],
+                    // Blocks until the display is ready for the next frame.
+                    present_mode: wgpu::PresentMode::Fifo,
+                };
+
+                swapchain = Some((renderer.device.create_swap_chain(&surface, &descriptor), extent));
+            }
+
+            let frame = match swapchain.as_mut().unwrap().0.get_current_frame() {
+                Ok(frame) => frame,
+                Err(wgpu::SwapChainError::Timeout) => continue,
+                Err(wgpu::SwapChainError::Outdated) | Err(wgpu::SwapChainError::Lost) => {
+                    swapchain = None;
+
+                    if recovery.lost(extent) {
+                        self.fail(
+                            "The swapchain of the window could not be recreated, resize the window to retry.",
+                        );
+                    }
+
+                    continue;
+                }
+                Err(wgpu::SwapChainError::OutOfMemory) => {
+                    self.fail("Could not acquire a swapchain frame, the window is no longer presented.");
+                    return;
+                }
+            };
+
+            if recovery.acquired() {
+                *self.failure.lock().unwrap() = None;
+            }
+
+            let front = self.front.load(Ordering::Acquire);
+            let texture = self.textures[front].lock().unwrap();
+            let mut encoder = renderer
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("window present") });
+
+            blit.draw(&renderer.device, texture.as_ref(), &frame.output.view, &mut encoder);
+            // Submitted before the texture is unlocked, so that the texture is not overwritten
+            // before it is drawn.
+            renderer.queue.submit(Some(encoder.finish()));
+
+            if !self.fresh.swap(false, Ordering::AcqRel) && texture.is_some() {
+                self.statistics.repeated.fetch_add(1, Ordering::Relaxed);
+            }
+
+            drop(texture);
+            // Present the frame, before the swapchain can be recreated.
+            drop(frame);
+        }
+    }
+}
+
+/// Recovers the swapchain of a [`Presenter`] from being lost or outdated. The swapchain is recreated
+/// once, and if that fails as well, it is given up on until the extent changes.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct SwapchainRecovery {
+    /// Whether the swapchain has been recreated, because it was lost or outdated, and no frame has
+    /// been acquired from it since.
+    recreated: bool,
+    /// The extent at which the swapchain was lost or outdated again after being recreated.
+    failed_extent: Option<Vec2<u32>>,
+}
+
+impl SwapchainRecovery {
+    /// The extent to present at, `None` while the extent is `None` or the swapchain was given up on
+    /// at the extent. A swapchain given up on is retried once the window is minimized or resized.
+    fn presented_extent(&mut self, extent: Option<Vec2<u32>>) -> Option<Vec2<u32>> {
+        if extent.is_none() {
+            self.failed_extent = None;
+        }
+
+        extent.filter(|extent| self.failed_extent != Some(*extent))
+    }
+
+    /// Called when the swapchain of the extent was lost or outdated, and is dropped. Returns
+    /// whether it is given up on, because it was lost or outdated again after being recreated.
+    fn lost(&mut self, extent: Vec2<u32>) -> bool {
+        if self.recreated {
+            self.recreated = false;
+            self.failed_extent = Some(extent);
+            true
+        } else {
+            self.recreated = true;
+            false
+        }
+    }
+
+    /// Called when a frame was acquired. Returns whether the swapchain recovered, in which case the
+    /// failure reported by the presenter is cleared.
+    fn acquired(&mut self) -> bool {
+        let recovered = self.recreated || self.failed_extent.is_some();
+
+        *self = Self::default();
+        recovered
+    }
+}
+
+/// The present thread of a window, which is stopped when dropped.
+#[derive(Debug)]
+pub struct PresentThread {
+    presenter: Arc<Presenter>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl PresentThread {
+    fn spawn(
+        surface: wgpu::Surface,
+        renderer: Renderer,
+        extent: Option<Vec2<u32>>,
+        statistics: Arc<PresentStatistics>,
+    ) -> Self {
+        let presenter = Arc::new(Presenter::new(extent, statistics));
+        let join_handle = {
+            let presenter = presenter.clone();
+
+            thread::spawn(move || presenter.run(surface, renderer))
+        };
+
+        Self { presenter, join_handle: Some(join_handle) }
+    }
+}
+
+impl Drop for PresentThread {
+    fn drop(&mut self) {
+        self.presenter.closed.store(true, Ordering::Release);
+
+        if let Some(join_handle) = self.join_handle.take() {
+            let _ = join_handle.join();
         }
     }
 }
 
+/// Draws a texture stretched over the whole render target, with linear filtering.
+pub struct Blit {
+    pipeline: FullscreenPipeline,
+    sampler: wgpu::Sampler,
+}
+
+impl Blit {
+    /// Creates the pipeline drawing into render targets of the format.
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let pipeline = FullscreenPipeline::new(
+            device,
+            &pipeline::fullscreen_vertex_module(device),
+            &pipeline::sample_fragment_module(device),
+            &[pipeline::sampled_texture_entry(0), pipeline::sampler_entry(1)],
+            format,
+        );
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self { pipeline, sampler }
+    }
+
+    /// Records a render pass drawing the texture into the target, or clearing the target, if there
+    /// is no texture.
+    pub fn draw(
+        &self,
+        device: &wgpu::Device,
+        texture: Option<&OwnedTexture>,
+        target: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let bind_group = texture.map(|texture| {
+            self.pipeline.create_bind_group(
+                device,
+                vec![
+                    wgpu::BindingResource::TextureView(&texture.view),
+                    wgpu::BindingResource::Sampler(&self.sampler),
+                ],
+            )
+        });
+
+        self.pipeline.draw(bind_group.as_ref(), target, wgpu::Color::BLACK, encoder);
+    }
+}
+
+impl Debug for Blit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Blit").finish()
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Persistent {
     current_settings: WindowSettings,
-    window_receiver: Option<Receiver<Window>>,
-    window: Option<WindowSurface>,
+    window_receiver: Option<Receiver<(Window, WindowSizeHandle)>>,
+    /// Keeps the size of the window tracked, while it is open.
+    size_handle: Option<WindowSizeHandle>,
+    /// Declared before the window, so that the thread presenting into the window is stopped
+    /// before the window is dropped.
+    present_thread: Option<PresentThread>,
+    /// Unregisters the size of the surface when the window is closed.
+    surface: Option<WindowSurfaceHandle>,
+    window: Option<Window>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Vec2<u32> = Vec2 { x: 800, y: 450 };
+
+    fn presenter(extent: Option<Vec2<u32>>) -> Presenter {
+        Presenter::new(extent, Default::default())
+    }
+
+    #[test]
+    fn the_configured_size_is_presented_until_the_size_is_observed() {
+        let settings = WindowSettings::default();
+
+        assert_eq!(settings.presented_extent(None), Some(settings.inner_size));
+        assert_eq!(settings.presented_extent(Some(Vec2::new(1024, 768))), Some(Vec2::new(1024, 768)));
+    }
+
+    #[test]
+    fn nothing_is_presented_into_zero_sized_surfaces() {
+        let settings = WindowSettings::default();
+
+        assert_eq!(settings.presented_extent(Some(Vec2::new(0, 0))), None);
+        assert_eq!(settings.presented_extent(Some(Vec2::new(SIZE.x, 0))), None);
+        assert_eq!(settings.presented_extent(Some(Vec2::new(0, SIZE.y))), None);
+
+        let settings = WindowSettings { inner_size: Vec2::new(SIZE.x, 0), ..Default::default() };
+
+        assert_eq!(settings.presented_extent(None), None);
+        assert_eq!(settings.swapchain_extent(), Vec2::new(SIZE.x, 1));
+    }
+
+    #[test]
+    fn nothing_is_presented_into_minimized_or_hidden_windows() {
+        let minimized = WindowSettings { minimized: true, ..Default::default() };
+        let hidden = WindowSettings { visible: false, ..Default::default() };
+
+        for settings in &[minimized, hidden] {
+            assert_eq!(settings.presented_extent(None), None);
+            assert_eq!(settings.presented_extent(Some(SIZE)), None);
+        }
+    }
+
+    #[test]
+    fn visible_output_follows_resizing_minimizing_and_restoring() {
+        let mut settings = WindowSettings::default();
+        let presenter = presenter(settings.presented_extent(None));
+        let dragged = Vec2::new(1024, 768);
+        // The observed size, whether the node minimizes the window, the expected value of the
+        // `visible` output and whether the swapchain is resized, during each execution.
+        let executions = [
+            (None, false, true, false),
+            (Some(SIZE), false, true, false),
+            (Some(dragged), false, true, true),
+            // Minimized through the settings of the node.
+            (Some(dragged), true, false, true),
+            (Some(dragged), true, false, false),
+            // Restored.
+            (Some(dragged), false, true, true),
+            // Minimized by the user, reported as a zero size on some platforms.
+            (Some(Vec2::new(0, 0)), false, false, true),
+            (Some(dragged), false, true, true),
+        ];
+
+        for &(observed_size, minimized, visible, resize) in &executions {
+            settings.minimized = minimized;
+
+            let (extent, resized) = presenter.resize_to_window(&settings, observed_size);
+
+            assert_eq!(is_visible(extent), visible);
+            assert_eq!(resized, resize);
+            assert_eq!(*presenter.extent.lock().unwrap(), extent);
+        }
+    }
+
+    #[test]
+    fn resizing_reports_whether_the_extent_changed() {
+        let presenter = presenter(Some(SIZE));
+
+        assert!(!presenter.resize(Some(SIZE)));
+        assert!(presenter.resize(None));
+        assert!(!presenter.resize(None));
+        assert!(presenter.resize(Some(SIZE)));
+        assert_eq!(*presenter.extent.lock().unwrap(), Some(SIZE));
+    }
+
+    #[test]
+    fn failures_are_reported_until_cleared() {
+        let presenter = presenter(Some(SIZE));
+
+        assert_eq!(presenter.failure(), None);
+        presenter.fail("failure");
+        assert_eq!(presenter.failure(), Some("failure"));
+        presenter.resize(None);
+        assert_eq!(presenter.failure(), Some("failure"));
+    }
+
+    #[test]
+    fn lost_swapchains_are_recreated_once() {
+        let mut recovery = SwapchainRecovery::default();
+
+        assert!(!recovery.lost(SIZE));
+        assert_eq!(recovery.presented_extent(Some(SIZE)), Some(SIZE));
+        assert!(recovery.acquired());
+        assert!(!recovery.acquired());
+
+        assert!(!recovery.lost(SIZE));
+        assert!(recovery.lost(SIZE));
+        assert_eq!(recovery.presented_extent(Some(SIZE)), None);
+    }
+
+    #[test]
+    fn swapchains_given_up_on_are_retried_once_resized() {
+        let mut recovery = SwapchainRecovery::default();
+        let resized = Vec2::new(1024, 768);
+
+        recovery.lost(SIZE);
+        recovery.lost(SIZE);
+        assert_eq!(recovery.presented_extent(Some(SIZE)), None);
+        assert_eq!(recovery.presented_extent(Some(resized)), Some(resized));
+        assert!(recovery.acquired());
+        assert_eq!(recovery, SwapchainRecovery::default());
+    }
+
+    #[test]
+    fn swapchains_given_up_on_are_retried_once_restored() {
+        let mut recovery = SwapchainRecovery::default();
+
+        recovery.lost(SIZE);
+        recovery.lost(SIZE);
+        assert_eq!(recovery.presented_extent(None), None);
+        assert_eq!(recovery.presented_extent(Some(SIZE)), Some(SIZE));
+    }
 }
@@ -1,5 +1,6 @@
 use crate::graph::{ChannelIdentifier, Connection, EdgeEndpoint, NodeIndex};
 use crate::util::StrokeType;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::ops::{Deref, DerefMut, Index, IndexMut};
@@ -10,7 +11,7 @@ pub mod ty;
 
 pub mod behaviour;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum ChannelDirection {
     In,
     Out,
@@ -90,7 +91,7 @@ impl From<ChannelPassBy> for ConnectionPassBy {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, PartialOrd, Ord, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum ChannelPassBy {
     SharedReference,
@@ -109,23 +110,31 @@ pub struct Channel {
     pub title: String,
     pub description: Option<String>,
     pub ty: TypeEnum,
+    /// Consecutive channels of the same group are displayed under a collapsible header.
+    pub group: Option<String>,
 }
 
 impl Channel {
     pub fn new(title: impl ToString, ty: impl Into<TypeEnum>) -> Self {
-        Self { title: title.to_string(), description: None, ty: ty.into() }
+        Self { title: title.to_string(), description: None, ty: ty.into(), group: None }
     }
 
     pub fn with_description(mut self, description: impl ToString) -> Self {
         self.description = Some(description.to_string());
         self
     }
+
+    pub fn with_group(mut self, group: impl ToString) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
 }
 
 pub struct ChannelRef<'a> {
     pub title: &'a str,
     pub description: Option<&'a str>,
     pub ty: &'a TypeEnum,
+    pub group: Option<&'a str>,
     pub edge_endpoint: EdgeEndpoint,
     pub direction: ChannelDirection,
 }
@@ -136,6 +145,7 @@ impl<'a> ChannelRef<'a> {
             title: &other.title,
             description: other.description.as_ref().map(String::as_str),
             ty: &other.ty,
+            group: other.group.as_ref().map(String::as_str),
             edge_endpoint,
             direction,
         }
@@ -150,6 +160,7 @@ pub struct ChannelRefMut<'a> {
     pub title: &'a mut str,
     pub description: Option<&'a mut String>,
     pub ty: &'a mut TypeEnum,
+    pub group: Option<&'a mut String>,
     pub edge_endpoint: EdgeEndpoint,
     pub direction: ChannelDirection,
 }
@@ -160,6 +171,7 @@ impl<'a> ChannelRefMut<'a> {
             title: &mut other.title,
             description: other.description.as_mut(),
             ty: &mut other.ty,
+            group: other.group.as_mut(),
             edge_endpoint,
             direction,
         }
@@ -170,7 +182,7 @@ impl<'a> ChannelRefMut<'a> {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Hash)]
 pub struct NodeConfiguration {
     pub channels_by_shared_reference: Vec<Channel>,
     pub channels_by_mutable_reference: Vec<Channel>,
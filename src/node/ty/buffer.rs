@@ -0,0 +1,105 @@
+use super::{
+    Bytes, DowncastFromTypeEnum, DynTypeDescriptor, DynTypeTrait, RecordLayout, Ref, RefAny, TypeEnum,
+    TypeFormatter, TypedBytes, ValueFormatter,
+};
+use iced_wgpu::wgpu;
+use std::fmt::Display;
+use std::sync::Arc;
+
+pub mod prelude {
+    pub use super::BufferRefExt;
+}
+
+/// An opaque handle of a GPU buffer, to be consumed by nodes drawing its contents.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default)]
+pub struct BufferType;
+
+impl BufferType {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Display for BufferType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Buffer")
+    }
+}
+
+impl From<BufferType> for TypeEnum {
+    fn from(other: BufferType) -> Self {
+        TypeEnum::Buffer(other).into()
+    }
+}
+
+/// A buffer of records, shared with the node that keeps it up to date.
+#[derive(Debug, Clone)]
+pub struct BufferAllocation {
+    pub buffer: Arc<wgpu::Buffer>,
+    /// The size of the buffer in bytes, which may exceed the size of the records.
+    pub size: wgpu::BufferAddress,
+    /// The layout of the records in the buffer.
+    pub layout: RecordLayout,
+    /// The number of records in the buffer.
+    pub len: usize,
+}
+
+pub struct BufferDescriptor {
+    allocation: BufferAllocation,
+}
+
+impl BufferDescriptor {
+    pub fn new(allocation: BufferAllocation) -> Self {
+        Self { allocation }
+    }
+}
+
+impl DynTypeDescriptor<BufferType> for BufferDescriptor {
+    fn get_type(&self) -> BufferType {
+        BufferType
+    }
+}
+
+impl DynTypeTrait for BufferType {
+    type Descriptor = BufferDescriptor;
+    type DynAlloc = BufferAllocation;
+
+    fn create_value_from_descriptor(descriptor: Self::Descriptor) -> Self::DynAlloc {
+        descriptor.allocation
+    }
+
+    fn is_abi_compatible(&self, _other: &Self) -> bool {
+        true
+    }
+
+    unsafe fn children<'a>(&'a self, _data: TypedBytes<'a>) -> Vec<TypedBytes<'a>> {
+        // Buffers do not contain any references to other allocations.
+        Vec::new()
+    }
+}
+
+pub trait BufferRefExt<'a> {
+    fn buffer(&self) -> &BufferAllocation;
+}
+
+impl<'a, R> BufferRefExt<'a> for R
+where R: Ref<'a, BufferType>
+{
+    fn buffer(&self) -> &BufferAllocation {
+        let typed_bytes = unsafe { self.typed_bytes() };
+        typed_bytes.bytes().downcast_ref_unwrap::<BufferAllocation>()
+    }
+}
+
+/// Describes buffers by the number of records, as their contents reside on the GPU.
+pub struct BufferFormatter;
+
+impl TypeFormatter for BufferFormatter {
+    fn format_short(&self, _formatter: &ValueFormatter, typed_bytes: TypedBytes<'_>) -> String {
+        let allocation = typed_bytes.bytes().downcast_ref_unwrap::<BufferAllocation>();
+
+        format!("Buffer of {} records, {} bytes", allocation.len, allocation.size)
+    }
+}
+
+impl_downcast_from_type_enum!(Buffer(BufferType));
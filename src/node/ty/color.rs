@@ -0,0 +1,234 @@
+//! Colors passed between nodes.
+//!
+//! Colors consist of four `F32` components, red, green, blue and alpha, from 0 to 1. The color
+//! components are in the linear color space, unless encoded by the Color Space Conversion node, and
+//! the alpha is not premultiplied. Colors are laid out as `[F32; 4]`, so that they can be connected
+//! to channels of arrays of floats and vice versa.
+
+use super::{
+    ArrayType, CloneableTypeExt, DowncastFromTypeEnum, PrimitiveChannelValue, PrimitiveType,
+    SafeBinaryRepresentationTypeExt, SizedTypeExt, TypeDesc, TypeEnum, TypeExt, TypeFormatter, TypeTrait,
+    TypedBytes, ValueFormatter,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use std::fmt::Display;
+
+pub mod prelude {}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default)]
+pub struct ColorType;
+
+impl ColorType {
+    pub const COMPONENTS: usize = 4;
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The type of arrays with the same layout as colors.
+    pub fn as_array(&self) -> ArrayType {
+        ArrayType::new(PrimitiveType::<f32>::default(), Self::COMPONENTS)
+    }
+}
+
+impl Display for ColorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Color")
+    }
+}
+
+unsafe impl SizedTypeExt for ColorType {
+    fn value_size(&self) -> usize {
+        Self::COMPONENTS * std::mem::size_of::<f32>()
+    }
+}
+
+unsafe impl SafeBinaryRepresentationTypeExt for ColorType {}
+
+unsafe impl CloneableTypeExt for ColorType {}
+
+unsafe impl TypeExt for ColorType {
+    fn is_abi_compatible(&self, _other: &Self) -> bool {
+        true
+    }
+
+    unsafe fn children<'a>(&'a self, _data: TypedBytes<'a>) -> Vec<TypedBytes<'a>> {
+        vec![]
+    }
+
+    fn value_size_if_sized(&self) -> Option<usize> {
+        Some(self.value_size())
+    }
+
+    fn has_safe_binary_representation(&self) -> bool {
+        true
+    }
+
+    fn is_cloneable(&self) -> bool {
+        true
+    }
+}
+
+unsafe impl TypeDesc for ColorType {}
+impl TypeTrait for ColorType {}
+
+impl From<ColorType> for TypeEnum {
+    fn from(other: ColorType) -> Self {
+        TypeEnum::Color(other)
+    }
+}
+
+impl_downcast_from_type_enum!(Color(ColorType));
+
+/// Reads the components of a color from its binary representation.
+pub fn read_color(bytes: &[u8]) -> [f32; 4] {
+    let mut color = [0.0; 4];
+
+    LittleEndian::read_f32_into(&bytes[..std::mem::size_of::<[f32; 4]>()], &mut color);
+    color
+}
+
+/// Writes the components of a color into its binary representation.
+pub fn write_color(bytes: &mut [u8], color: [f32; 4]) {
+    LittleEndian::write_f32_into(&color, &mut bytes[..std::mem::size_of::<[f32; 4]>()]);
+}
+
+/// Converts a color component encoded with the sRGB transfer function to the linear color space.
+pub fn srgb_to_linear(component: f32) -> f32 {
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a color component in the linear color space with the sRGB transfer function.
+pub fn linear_to_srgb(component: f32) -> f32 {
+    if component <= 0.003_130_8 {
+        component * 12.92
+    } else {
+        1.055 * component.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts a color from HSV to RGB, in the color space the HSV components describe. The hue is
+/// measured in turns and wraps around, the saturation and the value are from 0 to 1.
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let sector = hue.rem_euclid(1.0) * 6.0;
+    let chroma = value * saturation;
+    let intermediate = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (red, green, blue) = match sector as u32 {
+        0 => (chroma, intermediate, 0.0),
+        1 => (intermediate, chroma, 0.0),
+        2 => (0.0, chroma, intermediate),
+        3 => (0.0, intermediate, chroma),
+        4 => (intermediate, 0.0, chroma),
+        _ => (chroma, 0.0, intermediate),
+    };
+    let offset = value - chroma;
+
+    [red + offset, green + offset, blue + offset]
+}
+
+/// Formats colors by their components.
+pub struct ColorFormatter;
+
+impl TypeFormatter for ColorFormatter {
+    fn format_short(&self, formatter: &ValueFormatter, typed_bytes: TypedBytes<'_>) -> String {
+        let color = read_color(typed_bytes.bytes().bytes().unwrap());
+        let components = color
+            .iter()
+            .map(|component| formatter.format_primitive(PrimitiveChannelValue::F32(*component)))
+            .collect::<Vec<_>>();
+
+        format!("Color({})", components.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f32 = 1e-5;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert!(
+            actual.iter().zip(expected).all(|(actual, expected)| (actual - expected).abs() <= TOLERANCE),
+            "{:?} is not {:?}",
+            actual,
+            expected,
+        );
+    }
+
+    #[test]
+    fn srgb_to_linear_matches_reference_values() {
+        let references = [
+            (0.0, 0.0),
+            (0.04045, 0.003_130_805),
+            (0.2, 0.033_104_767),
+            (0.5, 0.214_041_14),
+            (128.0 / 255.0, 0.215_860_5),
+            (0.8, 0.603_827_34),
+            (1.0, 1.0),
+        ];
+
+        for (srgb, linear) in references.iter().copied() {
+            assert_close(&[srgb_to_linear(srgb)], &[linear]);
+        }
+    }
+
+    #[test]
+    fn linear_to_srgb_matches_reference_values() {
+        let references =
+            [(0.0, 0.0), (0.01, 0.099_852_82), (0.18, 0.461_356_13), (0.5, 0.735_356_98), (1.0, 1.0)];
+
+        for (linear, srgb) in references.iter().copied() {
+            assert_close(&[linear_to_srgb(linear)], &[srgb]);
+        }
+    }
+
+    #[test]
+    fn srgb_conversions_invert_each_other() {
+        for value in 0..=255 {
+            let srgb = value as f32 / 255.0;
+
+            assert_close(&[linear_to_srgb(srgb_to_linear(srgb))], &[srgb]);
+            assert_close(&[srgb_to_linear(linear_to_srgb(srgb))], &[srgb]);
+        }
+    }
+
+    #[test]
+    fn hsv_to_rgb_matches_reference_values() {
+        let references = [
+            ([0.0, 1.0, 1.0], [1.0, 0.0, 0.0]),
+            ([1.0 / 6.0, 1.0, 1.0], [1.0, 1.0, 0.0]),
+            ([1.0 / 3.0, 1.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.5, 1.0, 1.0], [0.0, 1.0, 1.0]),
+            ([2.0 / 3.0, 1.0, 1.0], [0.0, 0.0, 1.0]),
+            ([5.0 / 6.0, 1.0, 1.0], [1.0, 0.0, 1.0]),
+            ([0.25, 0.5, 0.8], [0.6, 0.8, 0.4]),
+            ([0.7, 0.0, 0.3], [0.3, 0.3, 0.3]),
+            ([0.4, 1.0, 0.0], [0.0, 0.0, 0.0]),
+            // The hue wraps around.
+            ([1.0, 1.0, 1.0], [1.0, 0.0, 0.0]),
+            ([-1.0 / 3.0, 1.0, 1.0], [0.0, 0.0, 1.0]),
+        ];
+
+        for ([hue, saturation, value], rgb) in references.iter().copied() {
+            assert_close(&hsv_to_rgb(hue, saturation, value), &rgb);
+        }
+    }
+
+    #[test]
+    fn colors_are_laid_out_as_arrays_of_floats() {
+        let color = [0.25, -1.0, 2.5, 1.0];
+        let mut bytes = [0; 16];
+
+        write_color(&mut bytes, color);
+
+        let components = bytes.chunks(4).map(LittleEndian::read_f32).collect::<Vec<_>>();
+
+        assert_eq!(components, color);
+        assert_eq!(read_color(&bytes), color);
+    }
+}
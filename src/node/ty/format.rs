@@ -0,0 +1,612 @@
+//! Formatting of values for display, shared by all surfaces displaying values, such as the Debug
+//! and Table View nodes, so that values are displayed consistently.
+//!
+//! Values of most types are formatted by the [`ValueFormatter`] itself, while values of opaque
+//! types, such as textures, are formatted by the [`TypeFormatter`] registered for their type.
+
+use super::{
+    typed_bytes_to_ptr, BufferFormatter, BufferType, ColorFormatter, ColorType, PrimitiveChannelValue,
+    PrimitiveKind, PrimitiveTypeEnum, TextureFormatter, TextureType, TypeEnum, TypedBytes,
+};
+use crate::graph::alloc::Allocator;
+use crate::util::truncate;
+use byteorder::LittleEndian;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::mem::{self, Discriminant};
+use std::sync::{Arc, RwLock};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormatSettings {
+    /// The number of decimal places of floats, or `None` to display as many as needed to tell
+    /// values apart.
+    pub float_precision: Option<usize>,
+    /// The number of items displayed at the start and at the end of collections.
+    pub preview_items: usize,
+    /// The number of characters single line formats are truncated to.
+    pub max_short_length: usize,
+}
+
+impl Default for FormatSettings {
+    fn default() -> Self {
+        Self { float_precision: None, preview_items: 3, max_short_length: 80 }
+    }
+}
+
+/// Overrides how a single primitive value is formatted, see
+/// [`ValueFormatter::format_primitive_as`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrimitiveFormat {
+    /// Formatted according to the [`FormatSettings`].
+    Default,
+    /// Floats with a fixed number of decimal places.
+    Precision(usize),
+    LowerHex,
+    UpperHex,
+    Binary,
+    Octal,
+}
+
+impl Default for PrimitiveFormat {
+    fn default() -> Self {
+        PrimitiveFormat::Default
+    }
+}
+
+impl PrimitiveFormat {
+    /// Parses a format spec, as in `format!`: `.N` for the precision of floats, `x`, `X`, `b` or
+    /// `o` for the radix of integers, or an empty spec for the default format.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        use PrimitiveFormat::*;
+        match spec {
+            "" => Ok(Default),
+            "x" => Ok(LowerHex),
+            "X" => Ok(UpperHex),
+            "b" => Ok(Binary),
+            "o" => Ok(Octal),
+            _ => match spec.strip_prefix('.') {
+                Some(precision) => precision
+                    .parse::<usize>()
+                    .map(Precision)
+                    .map_err(|_| format!("The precision `{}` is not a non-negative integer.", precision)),
+                None => Err(format!("Unknown format spec `{}`, expected `.N`, `x`, `X`, `b` or `o`.", spec)),
+            },
+        }
+    }
+
+    /// Whether values of the type can be formatted this way.
+    pub fn supports(&self, ty: PrimitiveTypeEnum) -> bool {
+        match self {
+            PrimitiveFormat::Default => true,
+            PrimitiveFormat::Precision(_) => ty.kind() == PrimitiveKind::Float,
+            _ => ty.kind() != PrimitiveKind::Float,
+        }
+    }
+}
+
+/// A value formatted for expandable display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormattedTree {
+    pub label: String,
+    pub children: Vec<FormattedTree>,
+    /// The number of children left out, because the collection is too long or the budget has been
+    /// exhausted.
+    pub omitted: usize,
+}
+
+impl FormattedTree {
+    pub fn leaf(label: impl Into<String>) -> Self {
+        Self { label: label.into(), children: Vec::new(), omitted: 0 }
+    }
+}
+
+/// Formats values of a type the [`ValueFormatter`] cannot look into, such as opaque objects.
+pub trait TypeFormatter: Send + Sync {
+    /// A single line description of the value.
+    fn format_short(&self, formatter: &ValueFormatter, typed_bytes: TypedBytes<'_>) -> String;
+
+    /// A nested description of the value, consisting of at most `budget` nodes, which are to be
+    /// subtracted from the budget.
+    fn format_detailed(
+        &self,
+        formatter: &ValueFormatter,
+        typed_bytes: TypedBytes<'_>,
+        budget: &mut usize,
+    ) -> FormattedTree {
+        *budget = budget.saturating_sub(1);
+        FormattedTree::leaf(self.format_short(formatter, typed_bytes))
+    }
+}
+
+pub struct ValueFormatter {
+    settings: RwLock<FormatSettings>,
+    /// The formatters of values of the registered variants of [`TypeEnum`].
+    type_formatters: RwLock<HashMap<Discriminant<TypeEnum>, Arc<dyn TypeFormatter>>>,
+}
+
+impl ValueFormatter {
+    pub fn get() -> &'static ValueFormatter {
+        lazy_static! {
+            static ref INSTANCE: ValueFormatter = ValueFormatter::new();
+        }
+        &*INSTANCE
+    }
+
+    fn new() -> Self {
+        let formatter = Self { settings: Default::default(), type_formatters: RwLock::new(HashMap::new()) };
+
+        formatter.register(&TextureType::new().into(), TextureFormatter);
+        formatter.register(&BufferType::new().into(), BufferFormatter);
+        formatter.register(&ColorType::new().into(), ColorFormatter);
+        formatter
+    }
+
+    pub fn settings(&self) -> FormatSettings {
+        *self.settings.read().unwrap()
+    }
+
+    pub fn set_settings(&self, settings: FormatSettings) {
+        *self.settings.write().unwrap() = settings;
+    }
+
+    /// Registers the formatter of values of the variant of the type, replacing the previously
+    /// registered one. Registered formatters take precedence over the default formatting.
+    pub fn register(&self, ty: &TypeEnum, type_formatter: impl TypeFormatter + 'static) {
+        self.type_formatters.write().unwrap().insert(mem::discriminant(ty), Arc::new(type_formatter));
+    }
+
+    fn type_formatter(&self, ty: &TypeEnum) -> Option<Arc<dyn TypeFormatter>> {
+        self.type_formatters.read().unwrap().get(&mem::discriminant(ty)).cloned()
+    }
+
+    pub fn format_primitive(&self, value: PrimitiveChannelValue) -> String {
+        match (value, self.settings().float_precision) {
+            (PrimitiveChannelValue::F32(value), Some(precision)) => format!("{:.*}", precision, value),
+            (PrimitiveChannelValue::F64(value), Some(precision)) => format!("{:.*}", precision, value),
+            _ => value.value_to_string(),
+        }
+    }
+
+    /// Formats the value in the format, or according to the [`FormatSettings`], if the format does
+    /// not support the type of the value, see [`PrimitiveFormat::supports`].
+    pub fn format_primitive_as(&self, value: PrimitiveChannelValue, format: PrimitiveFormat) -> String {
+        macro_rules! format_value {
+            ($($variant:ident),*$(,)?) => {
+                match value {
+                    $(
+                        PrimitiveChannelValue::$variant(value) => match format {
+                            PrimitiveFormat::LowerHex => format!("{:x}", value),
+                            PrimitiveFormat::UpperHex => format!("{:X}", value),
+                            PrimitiveFormat::Binary => format!("{:b}", value),
+                            PrimitiveFormat::Octal => format!("{:o}", value),
+                            _ => self.format_primitive(value.into()),
+                        },
+                    )*
+                    PrimitiveChannelValue::F32(value) => match format {
+                        PrimitiveFormat::Precision(precision) => format!("{:.*}", precision, value),
+                        _ => self.format_primitive(value.into()),
+                    },
+                    PrimitiveChannelValue::F64(value) => match format {
+                        PrimitiveFormat::Precision(precision) => format!("{:.*}", precision, value),
+                        _ => self.format_primitive(value.into()),
+                    },
+                }
+            };
+        }
+
+        format_value!(U8, U16, U32, U64, U128, I8, I16, I32, I64, I128)
+    }
+
+    /// A single line description of the value, truncated to
+    /// [`FormatSettings::max_short_length`] characters.
+    ///
+    /// Safety: The value and the values it points to must not be mutated during the call, see
+    /// [`TypedBytes::children`].
+    pub unsafe fn format_short(&self, typed_bytes: TypedBytes<'_>) -> String {
+        let settings = self.settings();
+
+        truncate(self.format_short_impl(typed_bytes, &settings), settings.max_short_length)
+    }
+
+    unsafe fn format_short_impl(&self, typed_bytes: TypedBytes<'_>, settings: &FormatSettings) -> String {
+        let ty = typed_bytes.borrow().ty();
+
+        if let Some(type_formatter) = self.type_formatter(&ty) {
+            return type_formatter.format_short(self, typed_bytes.borrow());
+        }
+
+        if let Some(primitive_ty) = ty.as_primitive_type_enum() {
+            let bytes = typed_bytes.borrow().bytes().bytes().unwrap();
+
+            return self.format_primitive(primitive_ty.read::<LittleEndian, _>(bytes).unwrap());
+        }
+
+        match ty.as_ref() {
+            TypeEnum::Option(_) => match typed_bytes.children().into_iter().next() {
+                Some(child) => format!("Some({})", self.format_short_impl(child, settings)),
+                None => "None".to_string(),
+            },
+            TypeEnum::Array(_) | TypeEnum::List(_) => {
+                let children = typed_bytes.children();
+                let len = children.len();
+                let mut items = Vec::new();
+                let mut previous_index = None;
+
+                for index in preview_indices(len, settings.preview_items) {
+                    if previous_index.map(|previous_index| previous_index + 1 != index).unwrap_or(false) {
+                        items.push("…".to_string());
+                    }
+
+                    items.push(self.format_short_impl(children[index].borrow(), settings));
+                    previous_index = Some(index);
+                }
+
+                if let TypeEnum::List(_) = ty.as_ref() {
+                    format!("[{}] ({} items)", items.join(", "), len)
+                } else {
+                    format!("[{}]", items.join(", "))
+                }
+            }
+            TypeEnum::Shared(_) | TypeEnum::Unique(_) => {
+                let target = typed_bytes.children().into_iter().next().unwrap();
+
+                format!(
+                    "{} → {}",
+                    pointer_label(typed_bytes.borrow()),
+                    self.format_short_impl(target, settings)
+                )
+            }
+            // Opaque values without a registered formatter.
+            _ => ty.to_string(),
+        }
+    }
+
+    /// A nested description of the value, consisting of at most `budget` nodes.
+    ///
+    /// Safety: The value and the values it points to must not be mutated during the call, see
+    /// [`TypedBytes::children`].
+    pub unsafe fn format_detailed(&self, typed_bytes: TypedBytes<'_>, mut budget: usize) -> FormattedTree {
+        let settings = self.settings();
+
+        self.format_detailed_impl(typed_bytes, &settings, &mut budget)
+    }
+
+    unsafe fn format_detailed_impl(
+        &self,
+        typed_bytes: TypedBytes<'_>,
+        settings: &FormatSettings,
+        budget: &mut usize,
+    ) -> FormattedTree {
+        let ty = typed_bytes.borrow().ty();
+
+        if let Some(type_formatter) = self.type_formatter(&ty) {
+            return type_formatter.format_detailed(self, typed_bytes.borrow(), budget);
+        }
+
+        let label = match ty.as_ref() {
+            TypeEnum::Option(_) | TypeEnum::Array(_) | TypeEnum::List(_) => ty.to_string(),
+            TypeEnum::Shared(_) | TypeEnum::Unique(_) => pointer_label(typed_bytes.borrow()),
+            _ => {
+                *budget = budget.saturating_sub(1);
+                return FormattedTree::leaf(truncate(
+                    self.format_short_impl(typed_bytes.borrow(), settings),
+                    settings.max_short_length,
+                ));
+            }
+        };
+        let children = typed_bytes.children();
+        let label = match ty.as_ref() {
+            TypeEnum::Option(_) if children.is_empty() => "None".to_string(),
+            TypeEnum::Option(_) => "Some".to_string(),
+            TypeEnum::Array(_) | TypeEnum::List(_) => format!("{} ({} items)", label, children.len()),
+            _ => label,
+        };
+        let mut tree = FormattedTree { label, children: Vec::new(), omitted: children.len() };
+
+        *budget = budget.saturating_sub(1);
+
+        for index in preview_indices(children.len(), settings.preview_items) {
+            if *budget == 0 {
+                break;
+            }
+
+            tree.children.push(self.format_detailed_impl(children[index].borrow(), settings, budget));
+            tree.omitted -= 1;
+        }
+
+        tree
+    }
+}
+
+/// The type of the pointer along with the number of references to the target.
+fn pointer_label(typed_bytes: TypedBytes<'_>) -> String {
+    let ty = typed_bytes.borrow().ty();
+    let refcount = typed_bytes_to_ptr(typed_bytes.borrow()).and_then(|ptr| Allocator::get().refcount(ptr));
+
+    match refcount {
+        Some(refcount) => format!("{}, {}", ty, refcount_label(refcount)),
+        None => ty.to_string(),
+    }
+}
+
+/// The number of references to an allocation, as displayed next to pointers.
+pub fn refcount_label(refcount: usize) -> String {
+    match refcount {
+        1 => "1 reference".to_string(),
+        refcount => format!("{} references", refcount),
+    }
+}
+
+/// The indices of the displayed items of a collection of `len` items, the first and last
+/// `preview_items` ones.
+fn preview_indices(len: usize, preview_items: usize) -> impl Iterator<Item = usize> {
+    let head_end = len.min(preview_items);
+    let tail_start = len.saturating_sub(preview_items).max(head_end);
+
+    (0..head_end).chain(tail_start..len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::alloc::GlobalRefcounter;
+    use crate::graph::testing::executor_lock;
+    use crate::node::{
+        AllocatedType, ArrayType, Bytes, ListAllocation, ListDescriptor, ListType, OptionType, PrimitiveType,
+        Refcounter, Unique,
+    };
+    use std::borrow::Cow;
+    use std::f64::consts::PI;
+
+    fn formatter(settings: FormatSettings) -> ValueFormatter {
+        let formatter = ValueFormatter::new();
+
+        formatter.set_settings(settings);
+        formatter
+    }
+
+    fn typed_bytes(bytes: &[u8], ty: impl Into<TypeEnum>) -> TypedBytes<'_> {
+        TypedBytes::from(bytes, Cow::Owned(ty.into()), &())
+    }
+
+    fn f32_bytes(items: impl IntoIterator<Item = f32>) -> Vec<u8> {
+        items.into_iter().flat_map(|item| item.to_le_bytes().to_vec()).collect()
+    }
+
+    fn f32_list(items: impl IntoIterator<Item = f32>) -> ListAllocation {
+        let mut list = ListAllocation::from(ListDescriptor::new(PrimitiveType::<f32>::default()));
+
+        for item in items {
+            list.push(&item.to_le_bytes());
+        }
+
+        list
+    }
+
+    fn list_bytes(list: &ListAllocation) -> TypedBytes<'_> {
+        let bytes = Bytes::Object { ty_name: "ListAllocation", data: list as &dyn AllocatedType };
+
+        TypedBytes::from(bytes, Cow::Owned(ListType::new(PrimitiveType::<f32>::default()).into()), &())
+    }
+
+    #[test]
+    fn formats_every_primitive_variant() {
+        let formatter = formatter(Default::default());
+        let cases = [
+            (PrimitiveChannelValue::U8(255), "255"),
+            (PrimitiveChannelValue::U16(65535), "65535"),
+            (PrimitiveChannelValue::U32(7), "7"),
+            (PrimitiveChannelValue::U64(u64::MAX), "18446744073709551615"),
+            (PrimitiveChannelValue::U128(u128::MAX), "340282366920938463463374607431768211455"),
+            (PrimitiveChannelValue::I8(-128), "-128"),
+            (PrimitiveChannelValue::I16(-1), "-1"),
+            (PrimitiveChannelValue::I32(0), "0"),
+            (PrimitiveChannelValue::I64(i64::MIN), "-9223372036854775808"),
+            (PrimitiveChannelValue::I128(-5), "-5"),
+            (PrimitiveChannelValue::F32(0.1), "0.1"),
+            (PrimitiveChannelValue::F64(-2.5), "-2.5"),
+        ];
+
+        // Every variant is covered.
+        assert_eq!(cases.len(), PrimitiveTypeEnum::VALUES.len());
+
+        for (value, expected) in cases.iter() {
+            assert_eq!(formatter.format_primitive(*value), *expected, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn formats_floats_with_the_precision_of_the_settings() {
+        let formatter = formatter(FormatSettings { float_precision: Some(2), ..Default::default() });
+
+        assert_eq!(formatter.format_primitive(PrimitiveChannelValue::F32(1.0 / 3.0)), "0.33");
+        assert_eq!(formatter.format_primitive(PrimitiveChannelValue::F64(PI)), "3.14");
+        assert_eq!(formatter.format_primitive(PrimitiveChannelValue::U8(3)), "3");
+    }
+
+    #[test]
+    fn formats_primitives_in_every_format() {
+        use PrimitiveFormat::*;
+        let formatter = formatter(FormatSettings::default());
+        let cases = [
+            (PrimitiveChannelValue::U32(255), LowerHex, "ff"),
+            (PrimitiveChannelValue::U32(255), UpperHex, "FF"),
+            (PrimitiveChannelValue::U32(255), Binary, "11111111"),
+            (PrimitiveChannelValue::U32(255), Octal, "377"),
+            (PrimitiveChannelValue::U32(255), Default, "255"),
+            (PrimitiveChannelValue::I16(-1), LowerHex, "ffff"),
+            (PrimitiveChannelValue::F64(PI), Precision(3), "3.142"),
+            (PrimitiveChannelValue::F32(0.5), Default, "0.5"),
+            // Unsupported formats fall back to the default one.
+            (PrimitiveChannelValue::U32(255), Precision(3), "255"),
+            (PrimitiveChannelValue::F64(2.5), LowerHex, "2.5"),
+        ];
+
+        for (value, format, expected) in cases.iter() {
+            assert_eq!(
+                formatter.format_primitive_as(*value, *format),
+                *expected,
+                "{:?} as {:?}",
+                value,
+                format
+            );
+        }
+
+        assert!(Precision(3).supports(PrimitiveTypeEnum::F32));
+        assert!(!Precision(3).supports(PrimitiveTypeEnum::U32));
+        assert!(LowerHex.supports(PrimitiveTypeEnum::I16));
+        assert!(!LowerHex.supports(PrimitiveTypeEnum::F64));
+        assert!(PrimitiveTypeEnum::VALUES.iter().all(|ty| Default.supports(*ty)));
+    }
+
+    #[test]
+    fn parses_format_specs() {
+        assert_eq!(PrimitiveFormat::parse(""), Ok(PrimitiveFormat::Default));
+        assert_eq!(PrimitiveFormat::parse("x"), Ok(PrimitiveFormat::LowerHex));
+        assert_eq!(PrimitiveFormat::parse("X"), Ok(PrimitiveFormat::UpperHex));
+        assert_eq!(PrimitiveFormat::parse("b"), Ok(PrimitiveFormat::Binary));
+        assert_eq!(PrimitiveFormat::parse("o"), Ok(PrimitiveFormat::Octal));
+        assert_eq!(PrimitiveFormat::parse(".4"), Ok(PrimitiveFormat::Precision(4)));
+        assert!(PrimitiveFormat::parse(".-1").is_err());
+        assert!(PrimitiveFormat::parse("e").is_err());
+    }
+
+    #[test]
+    fn formats_options() {
+        let formatter = formatter(Default::default());
+        let ty = OptionType::new(PrimitiveType::<u32>::default());
+        let some = [&7u32.to_le_bytes()[..], &[1]].concat();
+        let none = [0; 5];
+
+        unsafe {
+            assert_eq!(formatter.format_short(typed_bytes(&some, ty.clone())), "Some(7)");
+            assert_eq!(formatter.format_short(typed_bytes(&none, ty.clone())), "None");
+            assert_eq!(
+                formatter.format_detailed(typed_bytes(&none, ty.clone()), 10),
+                FormattedTree::leaf("None")
+            );
+            assert_eq!(
+                formatter.format_detailed(typed_bytes(&some, ty), 10),
+                FormattedTree {
+                    label: "Some".to_string(),
+                    children: vec![FormattedTree::leaf("7")],
+                    omitted: 0
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn formats_arrays_with_previews() {
+        let formatter = formatter(FormatSettings { preview_items: 2, ..Default::default() });
+        let short = f32_bytes(vec![1.0, 2.0, 3.0]);
+        let long = f32_bytes((0..10).map(|item| item as f32));
+
+        unsafe {
+            assert_eq!(
+                formatter
+                    .format_short(typed_bytes(&short, ArrayType::new(PrimitiveType::<f32>::default(), 3))),
+                "[1, 2, 3]"
+            );
+            assert_eq!(
+                formatter
+                    .format_short(typed_bytes(&long, ArrayType::new(PrimitiveType::<f32>::default(), 10))),
+                "[0, 1, …, 8, 9]"
+            );
+        }
+    }
+
+    #[test]
+    fn formats_lists_with_previews() {
+        let formatter = formatter(FormatSettings { preview_items: 2, ..Default::default() });
+        let empty = f32_list(vec![]);
+        let long = f32_list((0..10).map(|item| item as f32));
+
+        unsafe {
+            assert_eq!(formatter.format_short(list_bytes(&empty)), "[] (0 items)");
+            assert_eq!(formatter.format_short(list_bytes(&long)), "[0, 1, …, 8, 9] (10 items)");
+
+            let tree = formatter.format_detailed(list_bytes(&long), 100);
+
+            assert_eq!(tree.label, "List<PrimitiveType<f32>> (10 items)");
+            assert_eq!(
+                tree.children,
+                ["0", "1", "8", "9"].iter().copied().map(FormattedTree::leaf).collect::<Vec<_>>()
+            );
+            assert_eq!(tree.omitted, 6);
+
+            // The list itself takes a node of the budget.
+            let tree = formatter.format_detailed(list_bytes(&long), 3);
+
+            assert_eq!(tree.children.len(), 2);
+            assert_eq!(tree.omitted, 8);
+        }
+    }
+
+    #[test]
+    fn formats_pointers_with_their_refcounts() {
+        let _lock = executor_lock();
+        let formatter = formatter(Default::default());
+        let ptr = Allocator::get().allocate_object_global::<ListType>(
+            ListDescriptor::new(PrimitiveType::<f32>::default()).upcast(),
+        );
+        let ty = Unique::new(ListType::new(PrimitiveType::<f32>::default()));
+
+        unsafe {
+            assert_eq!(
+                formatter.format_short(typed_bytes(ptr.as_bytes(), ty.clone())),
+                "Unique<List<PrimitiveType<f32>>>, 1 reference → [] (0 items)"
+            );
+
+            let tree = formatter.format_detailed(typed_bytes(ptr.as_bytes(), ty), 10);
+
+            assert_eq!(tree.label, "Unique<List<PrimitiveType<f32>>>, 1 reference");
+            assert_eq!(
+                tree.children,
+                vec![FormattedTree {
+                    label: "List<PrimitiveType<f32>> (0 items)".to_string(),
+                    children: Vec::new(),
+                    omitted: 0
+                }]
+            );
+        }
+
+        GlobalRefcounter.refcount_decrement(ptr);
+    }
+
+    #[test]
+    fn formats_opaque_values_with_the_registered_formatters() {
+        let formatter = formatter(Default::default());
+        let color = f32_bytes(vec![1.0, 0.5, 0.0, 1.0]);
+
+        unsafe {
+            assert_eq!(formatter.format_short(typed_bytes(&color, ColorType::new())), "Color(1, 0.5, 0, 1)");
+        }
+
+        // Without a registered formatter, only the type is displayed.
+        let formatter =
+            ValueFormatter { settings: Default::default(), type_formatters: RwLock::new(HashMap::new()) };
+
+        unsafe {
+            assert_eq!(formatter.format_short(typed_bytes(&color, ColorType::new())), "Color");
+        }
+    }
+
+    #[test]
+    fn truncates_short_formats() {
+        let formatter = formatter(FormatSettings { max_short_length: 8, ..Default::default() });
+        let ty = ArrayType::new(PrimitiveType::<f32>::default(), 3);
+        let bytes = f32_bytes(vec![100.0, 200.0, 300.0]);
+
+        unsafe {
+            assert_eq!(formatter.format_short(typed_bytes(&bytes, ty)), "[100, 2…");
+        }
+    }
+
+    #[test]
+    fn formats_refcounts() {
+        assert_eq!(refcount_label(1), "1 reference");
+        assert_eq!(refcount_label(0), "0 references");
+        assert_eq!(refcount_label(2), "2 references");
+    }
+}
@@ -0,0 +1,186 @@
+//! Layouts of records of sized primitive fields, as uploaded to GPU buffers.
+//!
+//! Values are stored tightly packed in lists, while shaders usually expect the std430 layout, in
+//! which fields are aligned to their size and records to their largest field.
+
+use super::{PrimitiveTypeEnum, TypeEnum, TypeExt};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::Display;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum LayoutRules {
+    /// Fields directly follow each other, without any padding.
+    Packed,
+    /// The layout of storage buffers in GLSL.
+    Std430,
+}
+
+impl LayoutRules {
+    pub const VALUES: [LayoutRules; 2] = [LayoutRules::Packed, LayoutRules::Std430];
+}
+
+impl Display for LayoutRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutRules::Packed => write!(f, "Packed"),
+            LayoutRules::Std430 => write!(f, "std430"),
+        }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub struct FieldLayout {
+    pub ty: PrimitiveTypeEnum,
+    /// The offset of the field from the start of the record, in bytes.
+    pub offset: usize,
+}
+
+impl FieldLayout {
+    pub fn size(&self) -> usize {
+        TypeEnum::from(self.ty).value_size_if_sized().unwrap()
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+pub struct RecordLayout {
+    pub rules: LayoutRules,
+    pub fields: Vec<FieldLayout>,
+    /// The distance between the starts of consecutive records, in bytes.
+    pub stride: usize,
+}
+
+impl RecordLayout {
+    pub fn new(fields: impl IntoIterator<Item = PrimitiveTypeEnum>, rules: LayoutRules) -> Self {
+        let mut offset = 0;
+        let mut record_alignment = 1;
+        let fields = fields
+            .into_iter()
+            .map(|ty| {
+                let size = TypeEnum::from(ty).value_size_if_sized().unwrap();
+                let alignment = match rules {
+                    LayoutRules::Packed => 1,
+                    LayoutRules::Std430 => size,
+                };
+
+                offset = align(offset, alignment);
+                record_alignment = record_alignment.max(alignment);
+
+                let field = FieldLayout { ty, offset };
+
+                offset += size;
+                field
+            })
+            .collect();
+
+        Self { rules, fields, stride: align(offset, record_alignment) }
+    }
+
+    /// The layout of values of the type as records. Primitives consist of a single field and
+    /// arrays of primitives of a field per item. Returns `None` for other types.
+    pub fn of_type(ty: &TypeEnum, rules: LayoutRules) -> Option<Self> {
+        fn collect_fields(ty: &TypeEnum, fields: &mut Vec<PrimitiveTypeEnum>) -> Option<()> {
+            if let Some(primitive_ty) = ty.as_primitive_type_enum() {
+                fields.push(primitive_ty);
+                return Some(());
+            }
+
+            if let TypeEnum::Array(array) = ty {
+                for _ in 0..array.len {
+                    collect_fields(&array.item_type, fields)?;
+                }
+
+                return Some(());
+            }
+
+            None
+        }
+
+        let mut fields = Vec::new();
+
+        collect_fields(ty, &mut fields)?;
+        Some(Self::new(fields, rules))
+    }
+
+    /// Whether records of both layouts consist of the same bytes.
+    pub fn is_identical(&self, other: &Self) -> bool {
+        self.stride == other.stride && self.fields == other.fields
+    }
+
+    /// The same fields laid out according to other rules.
+    pub fn with_rules(&self, rules: LayoutRules) -> Self {
+        Self::new(self.fields.iter().map(|field| field.ty), rules)
+    }
+}
+
+/// Rounds the offset up to the nearest multiple of the alignment.
+fn align(offset: usize, alignment: usize) -> usize {
+    (offset + alignment - 1) / alignment * alignment
+}
+
+/// A view of the contiguous items of a list as records of primitive fields.
+#[derive(Debug, Clone)]
+pub struct ListOfRecordsRef<'a> {
+    bytes: &'a [u8],
+    layout: RecordLayout,
+}
+
+impl<'a> ListOfRecordsRef<'a> {
+    /// Returns `None`, if the items are not records or the bytes are not a whole number of them.
+    pub fn new(item_ty: &TypeEnum, bytes: &'a [u8]) -> Option<Self> {
+        let layout = RecordLayout::of_type(item_ty, LayoutRules::Packed)?;
+
+        if layout.stride == 0 || bytes.len() % layout.stride != 0 {
+            return None;
+        }
+
+        Some(Self { bytes, layout })
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len() / self.layout.stride
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn layout(&self) -> &RecordLayout {
+        &self.layout
+    }
+
+    /// The tightly packed records.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The bytes of the field of each record.
+    pub fn field(&self, index: usize) -> impl Iterator<Item = &'a [u8]> + 'a {
+        let field = self.layout.fields[index];
+        let size = field.size();
+
+        self.bytes.chunks_exact(self.layout.stride).map(move |record| &record[field.offset..][..size])
+    }
+
+    /// The records laid out according to the rules, borrowed if no padding has to be inserted.
+    pub fn to_layout(&self, rules: LayoutRules) -> (RecordLayout, Cow<'a, [u8]>) {
+        let layout = self.layout.with_rules(rules);
+
+        if layout.is_identical(&self.layout) {
+            return (layout, Cow::Borrowed(self.bytes));
+        }
+
+        let mut bytes = vec![0; self.len() * layout.stride];
+
+        for (index, field) in layout.fields.iter().enumerate() {
+            let size = field.size();
+            let targets = bytes.chunks_exact_mut(layout.stride);
+
+            for (source, target) in self.field(index).zip(targets) {
+                target[field.offset..][..size].copy_from_slice(source);
+            }
+        }
+
+        (layout, Cow::Owned(bytes))
+    }
+}
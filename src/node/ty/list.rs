@@ -210,6 +210,9 @@ impl<T: TypeDesc> DynTypeTrait for ListType<T> {
 pub trait ListRefExt<'a, T: TypeDesc> {
     fn len(&self) -> usize;
     fn get(&self, index: usize) -> Result<BorrowedRef<'_, T>, ()>;
+    /// The bytes of all items laid out one after another, if the items have a safe binary
+    /// representation. Prefer this over [`ListRefExt::get`] to process many items.
+    fn items_bytes_if_safe(&self) -> Option<&[u8]>;
 }
 
 impl<'a, R, T> ListRefExt<'a, T> for R
@@ -244,6 +247,19 @@ where
             Ok(unsafe { BorrowedRef::from_unchecked_type(TypedBytes::from(bytes, child_ty, rc)) })
         }
     }
+
+    fn items_bytes_if_safe(&self) -> Option<&[u8]> {
+        let typed_bytes = unsafe { self.typed_bytes() };
+        let (bytes, ty, _) = typed_bytes.into();
+
+        if !ty.downcast_ref::<ListType>().unwrap().child_ty.has_safe_binary_representation() {
+            return None;
+        }
+
+        let list = bytes.downcast_ref_unwrap::<ListAllocation>();
+
+        Some(&list.data)
+    }
 }
 
 pub trait ListRefMutExt<'a, T: TypeDesc> {
@@ -462,3 +478,89 @@ impl<T: TypeDesc> DowncastFromTypeEnum for ListType<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{AllocatedType, PrimitiveType, SizeRefExt};
+    use std::convert::TryInto;
+    use test::Bencher;
+
+    const MILLION: usize = 1_000_000;
+
+    type U32List = ListType<PrimitiveType<u32>>;
+
+    /// The list of the items `0..len`.
+    fn counting_list(len: usize) -> ListAllocation {
+        let mut list = ListAllocation::from(ListDescriptor::new(PrimitiveType::<u32>::default()));
+
+        for item in 0..len as u32 {
+            list.push(&item.to_le_bytes());
+        }
+
+        list
+    }
+
+    fn list_ref(list: &ListAllocation) -> BorrowedRef<'_, U32List> {
+        let bytes = Bytes::Object { ty_name: "ListAllocation", data: list as &dyn AllocatedType };
+        let ty = ListType::new(PrimitiveType::<u32>::default());
+
+        unsafe { BorrowedRef::from_unchecked_type(TypedBytes::from(bytes, Cow::Owned(ty.into()), &())) }
+    }
+
+    /// Reading every item of a long list one by one takes a single pass over the items, so that any
+    /// access path that is not constant per item shows up as a test that does not finish.
+    #[test]
+    fn items_of_a_million_item_list_are_read_in_order() {
+        let allocation = counting_list(MILLION);
+        let list = list_ref(&allocation);
+        let items_bytes = list.items_bytes_if_safe().unwrap();
+
+        assert_eq!(list.len(), MILLION);
+        assert_eq!(items_bytes.len(), MILLION * std::mem::size_of::<u32>());
+
+        for (index, item_bytes) in items_bytes.chunks_exact(std::mem::size_of::<u32>()).enumerate() {
+            assert_eq!(item_bytes, (index as u32).to_le_bytes());
+            assert_eq!(list.get(index).unwrap().bytes_if_sized().unwrap(), item_bytes);
+        }
+
+        assert!(list.get(MILLION).is_err());
+    }
+
+    #[test]
+    fn items_bytes_of_an_empty_list_are_empty() {
+        let allocation = counting_list(0);
+        let list = list_ref(&allocation);
+
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.items_bytes_if_safe(), Some(&[][..]));
+        assert!(list.get(0).is_err());
+    }
+
+    #[bench]
+    fn get_each_of_a_million_items(bencher: &mut Bencher) {
+        let allocation = counting_list(MILLION);
+        let list = list_ref(&allocation);
+
+        bencher.iter(|| {
+            (0..list.len()).fold(0u32, |sum, index| {
+                let bytes = list.get(index).unwrap().bytes_if_sized().unwrap().try_into().unwrap();
+
+                sum.wrapping_add(u32::from_le_bytes(bytes))
+            })
+        });
+    }
+
+    #[bench]
+    fn items_bytes_of_a_million_items(bencher: &mut Bencher) {
+        let allocation = counting_list(MILLION);
+        let list = list_ref(&allocation);
+
+        bencher.iter(|| {
+            list.items_bytes_if_safe()
+                .unwrap()
+                .chunks_exact(std::mem::size_of::<u32>())
+                .fold(0u32, |sum, bytes| sum.wrapping_add(u32::from_le_bytes(bytes.try_into().unwrap())))
+        });
+    }
+}
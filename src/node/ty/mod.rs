@@ -1,5 +1,6 @@
 use crate::graph::alloc::{AllocatedType, AllocationInner};
 use crate::util::CowMapExt;
+use std::any::Any;
 use std::borrow::Cow;
 use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
@@ -7,6 +8,10 @@ use std::mem::Discriminant;
 use std::ops::Deref;
 
 pub use array::*;
+pub use buffer::*;
+pub use color::*;
+pub use format::*;
+pub use layout::*;
 pub use list::*;
 pub use option::*;
 pub use primitive::*;
@@ -46,6 +51,10 @@ macro_rules! impl_downcast_from_type_enum {
 }
 
 pub mod array;
+pub mod buffer;
+pub mod color;
+pub mod format;
+pub mod layout;
 pub mod list;
 pub mod option;
 pub mod primitive;
@@ -55,6 +64,8 @@ pub mod texture;
 
 pub mod prelude {
     pub use super::array::prelude::*;
+    pub use super::buffer::prelude::*;
+    pub use super::color::prelude::*;
     pub use super::list::prelude::*;
     pub use super::option::prelude::*;
     pub use super::primitive::prelude::*;
@@ -253,7 +264,7 @@ impl<'a> From<(Bytes<'a>, Cow<'a, TypeEnum>, &'a dyn Refcounter)> for TypedBytes
 #[derive(Debug)]
 pub enum BytesMut<'a> {
     Bytes(&'a mut [u8]),
-    Object { ty_name: &'static str, data: &'a mut dyn AllocatedType },
+    Object { ty_name: &'static str, data: &'a mut Box<dyn AllocatedType> },
 }
 
 impl<'a> BytesMut<'a> {
@@ -261,7 +272,9 @@ impl<'a> BytesMut<'a> {
         unsafe {
             match self {
                 BytesMut::Bytes(inner) => Bytes::Bytes(&*(inner as *const _)),
-                BytesMut::Object { ty_name, data } => Bytes::Object { ty_name, data: &*(data as *const _) },
+                BytesMut::Object { ty_name, data } => {
+                    Bytes::Object { ty_name, data: &*(data.as_ref() as *const _) }
+                }
             }
         }
     }
@@ -277,7 +290,7 @@ impl<'a> BytesMut<'a> {
     pub fn borrow(&self) -> Bytes<'_> {
         match self {
             BytesMut::Bytes(ref inner) => Bytes::Bytes(&**inner),
-            BytesMut::Object { ty_name, ref data } => Bytes::Object { ty_name, data: &**data },
+            BytesMut::Object { ty_name, ref data } => Bytes::Object { ty_name, data: &***data },
         }
     }
 
@@ -309,7 +322,7 @@ impl<'a> BytesMut<'a> {
 
     pub fn object(self) -> Option<&'a dyn AllocatedType> {
         if let BytesMut::Object { data, .. } = self {
-            Some(&*data)
+            Some(&**data)
         } else {
             None
         }
@@ -324,6 +337,16 @@ impl<'a> BytesMut<'a> {
     }
 
     pub fn object_mut(self) -> Option<&'a mut dyn AllocatedType> {
+        if let BytesMut::Object { data, .. } = self {
+            Some(&mut **data)
+        } else {
+            None
+        }
+    }
+
+    /// Provides access to the box of the object, so that it can be replaced without
+    /// reallocating the underlying allocation.
+    pub fn object_box_mut(self) -> Option<&'a mut Box<dyn AllocatedType>> {
         if let BytesMut::Object { data, .. } = self {
             Some(data)
         } else {
@@ -333,7 +356,7 @@ impl<'a> BytesMut<'a> {
 
     pub fn downcast_ref_unwrap<T: AllocatedType>(self) -> &'a T {
         if let BytesMut::Object { ty_name, data } = self {
-            data.downcast_ref::<T>().unwrap_or_else(|| {
+            (**data).downcast_ref::<T>().unwrap_or_else(|| {
                 panic!("Attempt to downcast type `{}` to `{}`.", ty_name, std::any::type_name::<T>())
             })
         } else {
@@ -347,7 +370,7 @@ impl<'a> BytesMut<'a> {
 
     pub fn downcast_mut_unwrap<T: AllocatedType>(self) -> &'a mut T {
         if let BytesMut::Object { ty_name, data } = self {
-            data.downcast_mut::<T>().unwrap_or_else(|| {
+            (**data).downcast_mut::<T>().unwrap_or_else(|| {
                 panic!("Attempt to downcast type `{}` to `{}`.", ty_name, std::any::type_name::<T>())
             })
         } else {
@@ -834,6 +857,11 @@ macro_rules! define_type_enum {
         impl TypeEnum {
             const VARIANT_NAMES: [&'static str; count_tokens!($($variant)*)] = [$(stringify!($variant), )*];
 
+            /// A type of each variant, with primitive inner types where the variant has any.
+            pub fn examples() -> Vec<TypeEnum> {
+                vec![$(TypeEnum::$variant($value_for_discriminant),)*]
+            }
+
             #[allow(unused_assignments)]
             fn variant_name_of(d: Discriminant<Self>) -> &'static str {
                 let mut index = 0;
@@ -896,6 +924,8 @@ define_type_enum![
     Array(ArrayType) <- ArrayType::single(PrimitiveType::<u8>::default()),
     List(ListType) <- ListType::new(PrimitiveType::<u8>::default()).upcast(),
     Texture(TextureType) <- TextureType::new(),
+    Buffer(BufferType) <- BufferType::new(),
+    Color(ColorType) <- ColorType::new(),
 
     // Primitives
     U8(PrimitiveType<u8>) <- Default::default(),
@@ -960,6 +990,10 @@ unsafe impl TypeExt for TypeEnum {
     fn is_abi_compatible(&self, other: &Self) -> bool {
         use TypeEnum::*;
         match (self, other) {
+            (Color(_), Color(_)) => return true,
+            // Colors are laid out as arrays of floats.
+            (Color(color), _) => return Array(color.as_array()).is_abi_compatible(other),
+            (_, Color(color)) => return self.is_abi_compatible(&Array(color.as_array())),
             (Array { .. }, _) | (_, Array { .. }) => {
                 if self.value_size_if_sized().is_none() || other.value_size_if_sized().is_none() {
                     return false;
@@ -979,9 +1013,11 @@ unsafe impl TypeExt for TypeEnum {
                 return TypeExt::is_abi_compatible(a.as_ref(), b.as_ref());
             }
             (Unique(a), Unique(b)) => return TypeExt::is_abi_compatible(a, b),
+            (Option(a), Option(b)) => return TypeExt::is_abi_compatible(a, b),
             (Shared(a), Shared(b)) => return TypeExt::is_abi_compatible(a, b),
             (List(a), List(b)) => return TypeExt::is_abi_compatible(a, b),
             (Texture(a), Texture(b)) => return TypeExt::is_abi_compatible(a, b),
+            (Buffer(a), Buffer(b)) => return TypeExt::is_abi_compatible(a, b),
             (a, b) => {
                 if let (Some(a), Some(b)) = (a.as_primitive_type_enum(), b.as_primitive_type_enum()) {
                     a.kind().is_abi_compatible(&b.kind())
@@ -1143,29 +1179,65 @@ where R: RefMutAny<'a>
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssignError {
+    /// The types of the swapped values are not compatible.
+    TypeMismatch,
+    /// At least one of the values is not sized and is not backed by an object.
+    Unsized,
+    /// One of the values is stored as bytes, while the other is backed by an object.
+    UnsupportedRepresentation,
+}
+
+impl Display for AssignError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssignError::TypeMismatch => write!(f, "Incompatible types."),
+            AssignError::Unsized => write!(f, "Unsized values cannot be assigned."),
+            AssignError::UnsupportedRepresentation => {
+                write!(f, "Values with different representations cannot be assigned.")
+            }
+        }
+    }
+}
+
 pub trait AssignRefMutExt {
-    fn swap<'b>(&mut self, from: &mut impl RefMutAny<'b>) -> Result<(), ()>;
-    fn assign<'b>(&mut self, from: impl RefMutAny<'b>) -> Result<(), ()>;
+    fn swap<'b>(&mut self, from: &mut impl RefMutAny<'b>) -> Result<(), AssignError>;
+    fn assign<'b>(&mut self, from: impl RefMutAny<'b>) -> Result<(), AssignError>;
 }
 
 impl<'a, R> AssignRefMutExt for R
 where R: RefMutAny<'a>
 {
-    fn swap<'b>(&mut self, from: &mut impl RefMutAny<'b>) -> Result<(), ()> {
+    fn swap<'b>(&mut self, from: &mut impl RefMutAny<'b>) -> Result<(), AssignError> {
         {
             let typed_bytes_a = unsafe { self.typed_bytes_mut() };
             let typed_bytes_b = unsafe { from.typed_bytes_mut() };
 
             // Swapped types must be compatible
             if !typed_bytes_a.borrow().ty().as_ref().is_abi_compatible(typed_bytes_b.borrow().ty().as_ref()) {
-                return Err(());
+                return Err(AssignError::TypeMismatch);
             }
 
-            // Ensure types are sized
-            if typed_bytes_a.borrow().ty().value_size_if_sized().is_none()
-                || typed_bytes_b.borrow().ty().value_size_if_sized().is_none()
-            {
-                return Err(());
+            match (typed_bytes_a.borrow().bytes(), typed_bytes_b.borrow().bytes()) {
+                (Bytes::Bytes(_), Bytes::Bytes(_)) => {
+                    // Ensure types are sized and of the same size
+                    match (
+                        typed_bytes_a.borrow().ty().value_size_if_sized(),
+                        typed_bytes_b.borrow().ty().value_size_if_sized(),
+                    ) {
+                        (Some(a), Some(b)) if a != b => return Err(AssignError::TypeMismatch),
+                        (Some(_), Some(_)) => (),
+                        _ => return Err(AssignError::Unsized),
+                    }
+                }
+                (Bytes::Object { data: a, .. }, Bytes::Object { data: b, .. }) => {
+                    // Objects must be of the same concrete type
+                    if Any::type_id(a) != Any::type_id(b) {
+                        return Err(AssignError::TypeMismatch);
+                    }
+                }
+                _ => return Err(AssignError::UnsupportedRepresentation),
             }
 
             unsafe {
@@ -1180,23 +1252,209 @@ where R: RefMutAny<'a>
         let mut typed_bytes_b = unsafe { from.typed_bytes_mut() };
 
         // Swap values
-        if let (Some(a), Some(b)) = (
-            typed_bytes_a.borrow_mut().bytes_mut().bytes_mut(),
-            typed_bytes_b.borrow_mut().bytes_mut().bytes_mut(),
-        ) {
-            assert_eq!(a.len(), b.len());
+        match (typed_bytes_a.borrow_mut().bytes_mut(), typed_bytes_b.borrow_mut().bytes_mut()) {
+            (BytesMut::Bytes(a), BytesMut::Bytes(b)) => {
+                assert_eq!(a.len(), b.len());
 
-            a.iter_mut().zip(b.iter_mut()).for_each(|(a, b)| {
+                a.iter_mut().zip(b.iter_mut()).for_each(|(a, b)| {
+                    std::mem::swap(a, b);
+                });
+            }
+            (BytesMut::Object { data: a, .. }, BytesMut::Object { data: b, .. }) => {
+                // Only the pointers to the objects are swapped
                 std::mem::swap(a, b);
-            });
-
-            return Ok(());
-        } else {
-            unreachable!()
+            }
+            _ => unreachable!(),
         }
+
+        Ok(())
     }
 
-    fn assign<'b>(&mut self, mut from: impl RefMutAny<'b>) -> Result<(), ()> {
+    fn assign<'b>(&mut self, mut from: impl RefMutAny<'b>) -> Result<(), AssignError> {
         self.swap(&mut from)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::alloc::{Allocator, GlobalRefcounter};
+    use crate::graph::testing::executor_lock;
+    use std::cell::RefCell;
+
+    /// Records the refcount changes instead of applying them.
+    #[derive(Debug, Default)]
+    struct RecordingRefcounter(RefCell<Vec<(AllocationPointer, isize)>>);
+
+    impl RecordingRefcounter {
+        fn changes(&self) -> Vec<(AllocationPointer, isize)> {
+            self.0.borrow().clone()
+        }
+    }
+
+    impl Refcounter for RecordingRefcounter {
+        fn refcount_increment(&self, ptr: AllocationPointer) {
+            self.0.borrow_mut().push((ptr, 1));
+        }
+
+        fn refcount_decrement(&self, ptr: AllocationPointer) {
+            self.0.borrow_mut().push((ptr, -1));
+        }
+    }
+
+    fn reference<'a, T: TypeDesc>(
+        bytes: impl Into<BytesMut<'a>>,
+        ty: impl Into<TypeEnum>,
+        rc: &'a mut dyn Refcounter,
+    ) -> BorrowedRefMut<'a, T> {
+        unsafe { BorrowedRefMut::from_unchecked_type(TypedBytesMut::from(bytes, Cow::Owned(ty.into()), rc)) }
+    }
+
+    fn f32_list(items: &[f32]) -> Box<dyn AllocatedType> {
+        let mut list = ListAllocation::from(ListDescriptor::new(PrimitiveType::<f32>::default()));
+
+        for item in items {
+            list.push(&item.to_le_bytes());
+        }
+
+        Box::new(list)
+    }
+
+    fn object(data: &mut Box<dyn AllocatedType>) -> BytesMut<'_> {
+        BytesMut::Object { ty_name: "ListAllocation", data }
+    }
+
+    fn address(data: &dyn AllocatedType) -> *const u8 {
+        data as *const dyn AllocatedType as *const u8
+    }
+
+    fn option_of(value: &[u8], some: bool) -> Vec<u8> {
+        let mut bytes = value.to_vec();
+
+        bytes.push(some as u8);
+        bytes
+    }
+
+    #[test]
+    fn swaps_sized_values() {
+        let (mut a, mut b) = (1.0f32.to_le_bytes(), 2.0f32.to_le_bytes());
+        let (mut rc_a, mut rc_b) = (RecordingRefcounter::default(), RecordingRefcounter::default());
+
+        reference::<!>(&mut a[..], PrimitiveType::<f32>::default(), &mut rc_a)
+            .swap(&mut reference::<!>(&mut b[..], PrimitiveType::<f32>::default(), &mut rc_b))
+            .unwrap();
+
+        assert_eq!((f32::from_le_bytes(a), f32::from_le_bytes(b)), (2.0, 1.0));
+        assert!(rc_a.changes().is_empty() && rc_b.changes().is_empty());
+    }
+
+    #[test]
+    fn swaps_options() {
+        let ty = OptionType::new(PrimitiveType::<u32>::default());
+        let mut some = option_of(&7u32.to_le_bytes(), true);
+        let mut none = option_of(&[0; 4], false);
+
+        reference::<!>(&mut some[..], ty.clone(), &mut ())
+            .assign(reference::<!>(&mut none[..], ty.clone(), &mut ()))
+            .unwrap();
+
+        assert!(!ty.is_some_in(&some));
+        assert!(ty.is_some_in(&none));
+        assert_eq!(&none[..4], &7u32.to_le_bytes());
+    }
+
+    /// The pointer moves between the refcounters of the swapped values, so that the allocation is
+    /// neither leaked nor freed while it is still referenced.
+    #[test]
+    fn swaps_options_holding_pointers() {
+        let _lock = executor_lock();
+        let ty = OptionType::new(Unique::new(ListType::new(PrimitiveType::<f32>::default())));
+        let ptr = Allocator::get().allocate_object_global::<ListType>(
+            ListDescriptor::new(PrimitiveType::<f32>::default()).upcast(),
+        );
+        let mut some = option_of(ptr.as_bytes(), true);
+        let mut none = option_of(&[0; 8], false);
+        let (mut rc_some, mut rc_none) = (RecordingRefcounter::default(), RecordingRefcounter::default());
+
+        reference::<!>(&mut some[..], ty.clone(), &mut rc_some)
+            .swap(&mut reference::<!>(&mut none[..], ty.clone(), &mut rc_none))
+            .unwrap();
+
+        assert!(!ty.is_some_in(&some));
+        assert_eq!(&none[..8], ptr.as_bytes());
+        assert_eq!(rc_some.changes(), vec![(ptr, -1)]);
+        assert_eq!(rc_none.changes(), vec![(ptr, 1)]);
+
+        GlobalRefcounter.refcount_decrement(ptr);
+        assert_eq!(Allocator::get().refcount(ptr), None);
+    }
+
+    /// Only the boxes of the lists are swapped, not their items.
+    #[test]
+    fn swaps_lists() {
+        let ty = ListType::new(PrimitiveType::<f32>::default());
+        let (mut a, mut b) = (f32_list(&[1.0, 2.0, 3.0]), f32_list(&[]));
+        let (address_a, address_b) = (address(&*a), address(&*b));
+        let (mut rc_a, mut rc_b) = ((), ());
+
+        {
+            let mut ref_a = reference::<ListType<PrimitiveType<f32>>>(object(&mut a), ty.clone(), &mut rc_a);
+            let mut ref_b = reference::<ListType<PrimitiveType<f32>>>(object(&mut b), ty.clone(), &mut rc_b);
+
+            ref_a.swap(&mut ref_b).unwrap();
+            assert_eq!((ref_a.len(), ref_b.len()), (0, 3));
+        }
+
+        assert_eq!((address(&*a), address(&*b)), (address_b, address_a));
+    }
+
+    #[test]
+    fn refuses_incompatible_types() {
+        let (mut a, mut b) = (1.0f32.to_le_bytes(), 2u32.to_le_bytes());
+        let result = reference::<!>(&mut a[..], PrimitiveType::<f32>::default(), &mut ())
+            .swap(&mut reference::<!>(&mut b[..], PrimitiveType::<u32>::default(), &mut ()));
+
+        assert_eq!(result, Err(AssignError::TypeMismatch));
+        assert_eq!((f32::from_le_bytes(a), u32::from_le_bytes(b)), (1.0, 2));
+    }
+
+    /// Integers of the same signedness are compatible, but cannot be swapped unless they are of the
+    /// same size.
+    #[test]
+    fn refuses_values_of_different_sizes() {
+        let (mut a, mut b) = ([1u8], 2u32.to_le_bytes());
+        let result = reference::<!>(&mut a[..], PrimitiveType::<u8>::default(), &mut ())
+            .swap(&mut reference::<!>(&mut b[..], PrimitiveType::<u32>::default(), &mut ()));
+
+        assert_eq!(result, Err(AssignError::TypeMismatch));
+        assert_eq!((a, u32::from_le_bytes(b)), ([1], 2));
+    }
+
+    #[test]
+    fn refuses_unsized_bytes() {
+        let ty = ListType::new(PrimitiveType::<f32>::default());
+        let (mut a, mut b) = ([0u8; 4], [0u8; 4]);
+        let result = reference::<!>(&mut a[..], ty.clone(), &mut ()).swap(&mut reference::<!>(
+            &mut b[..],
+            ty.clone(),
+            &mut (),
+        ));
+
+        assert_eq!(result, Err(AssignError::Unsized));
+    }
+
+    #[test]
+    fn refuses_values_with_different_representations() {
+        let ty = ListType::new(PrimitiveType::<f32>::default());
+        let mut list = f32_list(&[1.0]);
+        let mut bytes = [0u8; 4];
+        let result = reference::<!>(object(&mut list), ty.clone(), &mut ()).swap(&mut reference::<!>(
+            &mut bytes[..],
+            ty.clone(),
+            &mut (),
+        ));
+
+        assert_eq!(result, Err(AssignError::UnsupportedRepresentation));
+        assert_eq!(reference::<ListType<PrimitiveType<f32>>>(object(&mut list), ty, &mut ()).len(), 1);
+    }
+}
@@ -102,6 +102,17 @@ impl<T: TypeDesc> OptionType<T> {
         bytes[value_size] = flags as u8;
     }
 
+    /// Whether the value stored in the bytes of its binary representation is `Some`.
+    pub fn is_some_in(&self, bytes: &[u8]) -> bool {
+        self.get_flags(Bytes::Bytes(bytes)) == OptionFlags::Some
+    }
+
+    /// Makes the value stored in the bytes of its binary representation `Some` or `None`, keeping
+    /// the bytes of the inner value.
+    pub fn set_some_in(&self, bytes: &mut [u8], some: bool) {
+        self.set_flags(BytesMut::Bytes(bytes), if some { OptionFlags::Some } else { OptionFlags::None });
+    }
+
     fn get_bytes<'a>(&'a self, data: TypedBytes<'a>) -> Option<TypedBytes<'a>> {
         let value_size = self.child_ty.value_size_if_sized().unwrap();
 
@@ -3,6 +3,7 @@ use super::{
     TypeEnum, TypeExt, TypeResolution, TypeTrait, TypedBytes,
 };
 use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
+use serde::{Deserialize, Serialize};
 use std::any::TypeId;
 use std::fmt::{Debug, Display};
 use std::hash::{Hash, Hasher};
@@ -75,7 +76,7 @@ macro_rules! impl_primitive_types {
             }
         }
 
-        #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+        #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
         pub enum PrimitiveTypeEnum {
             $($enum_variant,)*
         }
@@ -92,7 +93,7 @@ macro_rules! impl_primitive_types {
         }
 
         /// Should not be used for large data storage, as the size is defined by the largest variant.
-        #[derive(Debug, PartialEq, Clone, Copy)]
+        #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
         pub enum PrimitiveChannelValue {
             $($enum_variant($primitive_type),)*
         }
@@ -112,6 +113,15 @@ macro_rules! impl_primitive_types {
                 }
             }
 
+            pub fn value_size(&self) -> usize {
+                use PrimitiveTypeEnum::*;
+                match self {
+                    $(
+                        $enum_variant => std::mem::size_of::<$primitive_type>(),
+                    )*
+                }
+            }
+
             pub fn default_value(&self) -> PrimitiveChannelValue {
                 use PrimitiveTypeEnum::*;
                 match self {
@@ -121,6 +131,19 @@ macro_rules! impl_primitive_types {
                 }
             }
 
+            /// Converts the value to the type, rounding it to the nearest integer for integer types.
+            /// Values outside of the range of the type saturate.
+            pub fn from_f64(&self, value: f64) -> PrimitiveChannelValue {
+                use PrimitiveTypeEnum::*;
+                let value = if self.kind() == PrimitiveKind::Float { value } else { value.round() };
+
+                match self {
+                    $(
+                        $enum_variant => PrimitiveChannelValue::$enum_variant(value as $primitive_type),
+                    )*
+                }
+            }
+
             pub fn parse(&self, from: impl AsRef<str>) -> Option<PrimitiveChannelValue> {
                 use PrimitiveTypeEnum::*;
                 Some(match self {
@@ -141,6 +164,16 @@ macro_rules! impl_primitive_types {
                 }
             }
 
+            /// Converts the value to an `f64`, which may lose precision for large integers.
+            pub fn to_f64(&self) -> f64 {
+                use PrimitiveChannelValue::*;
+                match self {
+                    $(
+                        $enum_variant(value) => *value as f64,
+                    )*
+                }
+            }
+
             pub fn value_to_string(&self) -> String {
                 use PrimitiveChannelValue::*;
                 match self {
@@ -266,6 +299,37 @@ impl PrimitiveTypeEnum {
             F64 => PrimitiveChannelValue::F64(read.read_f64::<E>()?),
         })
     }
+
+    fn bits(&self) -> usize {
+        self.value_size() * 8
+    }
+
+    /// The number of bits of the significand of a float, including the implicit leading bit.
+    fn significand_bits(&self) -> usize {
+        match self {
+            PrimitiveTypeEnum::F32 => f32::MANTISSA_DIGITS as usize,
+            PrimitiveTypeEnum::F64 => f64::MANTISSA_DIGITS as usize,
+            _ => 0,
+        }
+    }
+
+    /// Whether every value of this type is exactly representable by the wider `target` type, so that
+    /// connections may convert the values implicitly, see [`PrimitiveChannelValue::promote`].
+    /// Conversions between integers of the same size are not promotions, as those types are already
+    /// ABI compatible.
+    pub fn promotes_to(&self, target: PrimitiveTypeEnum) -> bool {
+        use PrimitiveKind::*;
+        match (self.kind(), target.kind()) {
+            (UnsignedInteger, UnsignedInteger)
+            | (UnsignedInteger, SignedInteger)
+            | (SignedInteger, SignedInteger)
+            | (Float, Float) => self.bits() < target.bits(),
+            (UnsignedInteger, Float) => self.bits() <= target.significand_bits(),
+            // The sign is not stored in the significand.
+            (SignedInteger, Float) => self.bits() - 1 <= target.significand_bits(),
+            (SignedInteger, UnsignedInteger) | (Float, _) => false,
+        }
+    }
 }
 
 impl Display for PrimitiveTypeEnum {
@@ -292,4 +356,163 @@ impl PrimitiveChannelValue {
             F64(value) => write.write_f64::<E>(*value),
         }
     }
+
+    /// Converts the value to the `target` type, if the type of the value
+    /// [promotes](PrimitiveTypeEnum::promotes_to) to it.
+    pub fn promote(&self, target: PrimitiveTypeEnum) -> Option<PrimitiveChannelValue> {
+        use PrimitiveChannelValue::*;
+
+        if !self.ty().promotes_to(target) {
+            return None;
+        }
+
+        // The casts are exact, as the target type represents all values of the source type. Neither
+        // `U128` nor `I128` promote to any type, so all other integers fit into an `i128`.
+        let integer = match *self {
+            U8(value) => value as i128,
+            U16(value) => value as i128,
+            U32(value) => value as i128,
+            U64(value) => value as i128,
+            I8(value) => value as i128,
+            I16(value) => value as i128,
+            I32(value) => value as i128,
+            I64(value) => value as i128,
+            F32(value) => return Some(F64(value as f64)),
+            U128(_) | I128(_) | F64(_) => unreachable!("the type does not promote to any type"),
+        };
+
+        Some(match target {
+            PrimitiveTypeEnum::U8 => U8(integer as u8),
+            PrimitiveTypeEnum::U16 => U16(integer as u16),
+            PrimitiveTypeEnum::U32 => U32(integer as u32),
+            PrimitiveTypeEnum::U64 => U64(integer as u64),
+            PrimitiveTypeEnum::U128 => U128(integer as u128),
+            PrimitiveTypeEnum::I8 => I8(integer as i8),
+            PrimitiveTypeEnum::I16 => I16(integer as i16),
+            PrimitiveTypeEnum::I32 => I32(integer as i32),
+            PrimitiveTypeEnum::I64 => I64(integer as i64),
+            PrimitiveTypeEnum::I128 => I128(integer),
+            PrimitiveTypeEnum::F32 => F32(integer as f32),
+            PrimitiveTypeEnum::F64 => F64(integer as f64),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PrimitiveTypeEnum::*;
+
+    /// The types each type promotes to, all other pairs are lossy.
+    fn promotions(ty: PrimitiveTypeEnum) -> Vec<PrimitiveTypeEnum> {
+        match ty {
+            U8 => vec![U16, U32, U64, U128, I16, I32, I64, I128, F32, F64],
+            U16 => vec![U32, U64, U128, I32, I64, I128, F32, F64],
+            U32 => vec![U64, U128, I64, I128, F64],
+            U64 => vec![U128, I128],
+            I8 => vec![I16, I32, I64, I128, F32, F64],
+            I16 => vec![I32, I64, I128, F32, F64],
+            I32 => vec![I64, I128, F64],
+            I64 => vec![I128],
+            F32 => vec![F64],
+            U128 | I128 | F64 => vec![],
+        }
+    }
+
+    /// The extreme values of the type, along with the ones around zero.
+    fn extremes(ty: PrimitiveTypeEnum) -> Vec<PrimitiveChannelValue> {
+        use PrimitiveChannelValue as V;
+        match ty {
+            U8 => vec![V::U8(0), V::U8(1), V::U8(u8::MAX)],
+            U16 => vec![V::U16(0), V::U16(1), V::U16(u16::MAX)],
+            U32 => vec![V::U32(0), V::U32(1), V::U32(u32::MAX)],
+            U64 => vec![V::U64(0), V::U64(1), V::U64(u64::MAX)],
+            U128 => vec![V::U128(0), V::U128(1), V::U128(u128::MAX)],
+            I8 => vec![V::I8(i8::MIN), V::I8(-1), V::I8(0), V::I8(1), V::I8(i8::MAX)],
+            I16 => vec![V::I16(i16::MIN), V::I16(-1), V::I16(0), V::I16(1), V::I16(i16::MAX)],
+            I32 => vec![V::I32(i32::MIN), V::I32(-1), V::I32(0), V::I32(1), V::I32(i32::MAX)],
+            I64 => vec![V::I64(i64::MIN), V::I64(-1), V::I64(0), V::I64(1), V::I64(i64::MAX)],
+            I128 => vec![V::I128(i128::MIN), V::I128(-1), V::I128(0), V::I128(1), V::I128(i128::MAX)],
+            F32 => vec![
+                V::F32(f32::MIN),
+                V::F32(-f32::MIN_POSITIVE / 2.0),
+                V::F32(0.1),
+                V::F32(f32::MAX),
+                V::F32(f32::INFINITY),
+            ],
+            F64 => vec![V::F64(f64::MIN), V::F64(0.1), V::F64(f64::MAX), V::F64(f64::NEG_INFINITY)],
+        }
+    }
+
+    /// The value as an integer, if it is one. Exact for the extremes of all types but `U128`, which
+    /// does not promote to any type.
+    fn as_integer(value: PrimitiveChannelValue) -> Option<i128> {
+        use PrimitiveChannelValue as V;
+        Some(match value {
+            V::U8(value) => value as i128,
+            V::U16(value) => value as i128,
+            V::U32(value) => value as i128,
+            V::U64(value) => value as i128,
+            V::U128(value) => value as i128,
+            V::I8(value) => value as i128,
+            V::I16(value) => value as i128,
+            V::I32(value) => value as i128,
+            V::I64(value) => value as i128,
+            V::I128(value) => value,
+            V::F32(value) if value.fract() == 0.0 => value as i128,
+            V::F64(value) if value.fract() == 0.0 => value as i128,
+            V::F32(_) | V::F64(_) => return None,
+        })
+    }
+
+    #[test]
+    fn promotions_match_the_matrix() {
+        for source in PrimitiveTypeEnum::VALUES.iter().copied() {
+            let expected = promotions(source);
+
+            for target in PrimitiveTypeEnum::VALUES.iter().copied() {
+                assert_eq!(
+                    source.promotes_to(target),
+                    expected.contains(&target),
+                    "{} to {}",
+                    source,
+                    target
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn promotions_preserve_the_extremes() {
+        for source in PrimitiveTypeEnum::VALUES.iter().copied() {
+            for target in promotions(source) {
+                for value in extremes(source) {
+                    let promoted = value.promote(target).unwrap();
+
+                    assert_eq!(promoted.ty(), target);
+
+                    match (value, promoted) {
+                        (PrimitiveChannelValue::F32(value), PrimitiveChannelValue::F64(promoted)) => {
+                            assert_eq!(promoted as f32, value);
+                            assert_eq!(promoted, value as f64);
+                        }
+                        _ => assert_eq!(as_integer(promoted), as_integer(value), "{:?} to {}", value, target),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lossy_pairs_are_not_promoted() {
+        for source in PrimitiveTypeEnum::VALUES.iter().copied() {
+            let promotions = promotions(source);
+
+            for target in PrimitiveTypeEnum::VALUES.iter().filter(|target| !promotions.contains(target)) {
+                for value in extremes(source) {
+                    assert_eq!(value.promote(*target), None, "{:?} to {}", value, target);
+                }
+            }
+        }
+    }
 }
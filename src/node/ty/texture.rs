@@ -1,24 +1,194 @@
-use crate::graph::TextureAllocation;
+use crate::graph::{OwnedTexture, TextureAllocation};
 
-use super::{Bytes, DowncastFromTypeEnum, DynTypeDescriptor, DynTypeTrait, TypeEnum, TypedBytes};
+use super::{
+    Bytes, DowncastFromTypeEnum, DynTypeDescriptor, DynTypeTrait, Ref, RefAny, TypeEnum, TypeFormatter,
+    TypedBytes, ValueFormatter,
+};
+use iced_wgpu::wgpu;
 use std::fmt::Display;
 
-pub mod prelude {}
+pub mod prelude {
+    pub use super::TextureRefExt;
+}
+
+macro_rules! texture_formats {
+    ($($format:ident),*$(,)?) => {
+        /// The subset of [`wgpu::TextureFormat`]s textures passed between nodes can have.
+        #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+        pub enum TextureFormat {
+            $($format,)*
+        }
+
+        impl TextureFormat {
+            pub fn to_wgpu(self) -> wgpu::TextureFormat {
+                match self {
+                    $(TextureFormat::$format => wgpu::TextureFormat::$format,)*
+                }
+            }
+
+            /// Returns `None`, if the format is not supported.
+            pub fn from_wgpu(format: wgpu::TextureFormat) -> Option<Self> {
+                match format {
+                    $(wgpu::TextureFormat::$format => Some(TextureFormat::$format),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl Display for TextureFormat {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $(TextureFormat::$format => f.write_str(stringify!($format)),)*
+                }
+            }
+        }
+    };
+}
+
+texture_formats![
+    R8Unorm,
+    Rg8Unorm,
+    Rgba8Unorm,
+    Rgba8UnormSrgb,
+    Bgra8Unorm,
+    Bgra8UnormSrgb,
+    R16Float,
+    Rg16Float,
+    Rgba16Float,
+    R32Float,
+    Rg32Float,
+    Rgba32Float,
+];
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum TextureDimension {
+    D2,
+}
+
+impl Display for TextureDimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextureDimension::D2 => f.write_str("2D"),
+        }
+    }
+}
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+/// The static properties of textures passed through a channel. Unspecified properties are
+/// compatible with any other.
+///
+/// The properties of textures produced by a node are the properties the textures are guaranteed
+/// to have, while the properties of textures consumed by a node are its requirements.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default)]
 pub struct TextureType {
-    // TODO texture format, size?
+    pub format: Option<TextureFormat>,
+    pub dimension: Option<TextureDimension>,
+    /// The usages the texture was created with, or the usages required by the consumer.
+    pub usage: Option<wgpu::TextureUsage>,
 }
 
 impl TextureType {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// The properties of textures created by nodes, see [`OwnedTexture`].
+    pub fn owned(format: Option<TextureFormat>) -> Self {
+        Self { format, dimension: Some(TextureDimension::D2), usage: Some(OwnedTexture::usage()) }
+    }
+
+    /// The properties of the texture in the allocation.
+    pub fn of_allocation(allocation: &TextureAllocation) -> Self {
+        match allocation.owned_texture() {
+            Some(texture) => Self::owned(TextureFormat::from_wgpu(texture.format)),
+            None => Self::new().with_dimension(TextureDimension::D2),
+        }
+    }
+
+    pub fn with_format(mut self, format: TextureFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn with_dimension(mut self, dimension: TextureDimension) -> Self {
+        self.dimension = Some(dimension);
+        self
+    }
+
+    pub fn with_usage(mut self, usage: wgpu::TextureUsage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Checks whether the textures of this type satisfy the requirements of the `consumer`.
+    pub fn check_compatibility(&self, consumer: &Self) -> Result<(), TextureIncompatibility> {
+        if let (Some(produced), Some(required)) = (self.format, consumer.format) {
+            if produced != required {
+                return Err(TextureIncompatibility::Format { produced, required });
+            }
+        }
+
+        if let (Some(produced), Some(required)) = (self.dimension, consumer.dimension) {
+            if produced != required {
+                return Err(TextureIncompatibility::Dimension { produced, required });
+            }
+        }
+
+        if let (Some(produced), Some(required)) = (self.usage, consumer.usage) {
+            if !produced.contains(required) {
+                return Err(TextureIncompatibility::Usage { missing: required - produced });
+            }
+        }
+
+        Ok(())
     }
 }
 
 impl Display for TextureType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("Texture")
+        match (self.format, self.dimension) {
+            (Some(format), Some(dimension)) => write!(f, "Texture{}<{}>", dimension, format),
+            (Some(format), None) => write!(f, "Texture<{}>", format),
+            (None, Some(dimension)) => write!(f, "Texture{}", dimension),
+            (None, None) => f.write_str("Texture"),
+        }
+    }
+}
+
+/// Describes why textures of one type cannot be passed to a channel of another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureIncompatibility {
+    Format { produced: TextureFormat, required: TextureFormat },
+    Dimension { produced: TextureDimension, required: TextureDimension },
+    Usage { missing: wgpu::TextureUsage },
+}
+
+impl TextureIncompatibility {
+    /// Finds the textures of incompatible types contained within the types of the channels, if
+    /// the types are otherwise of the same structure.
+    pub fn find(producer: &TypeEnum, consumer: &TypeEnum) -> Option<Self> {
+        use TypeEnum::*;
+        match (producer, consumer) {
+            (Texture(a), Texture(b)) => a.check_compatibility(b).err(),
+            (Unique(a), Unique(b)) => Self::find(&a.child_ty, &b.child_ty),
+            (Shared(a), Shared(b)) => Self::find(&a.child_ty, &b.child_ty),
+            (Option(a), Option(b)) => Self::find(&a.child_ty, &b.child_ty),
+            (List(a), List(b)) => Self::find(&a.child_ty, &b.child_ty),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TextureIncompatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextureIncompatibility::Format { produced, required } => {
+                write!(f, "format mismatch: {} vs {}", produced, required)
+            }
+            TextureIncompatibility::Dimension { produced, required } => {
+                write!(f, "dimension mismatch: {} vs {}", produced, required)
+            }
+            TextureIncompatibility::Usage { missing } => write!(f, "usage mismatch: missing {:?}", missing),
+        }
     }
 }
 
@@ -30,11 +200,22 @@ impl From<TextureType> for TypeEnum {
 
 // TODO
 // pub struct TextureDispatcher;
-pub struct TextureDescriptor;
+/// Textures are created using the renderer, which is not available to the allocator.
+/// The descriptor therefore carries the already created texture to be moved into the allocation.
+pub struct TextureDescriptor {
+    ty: TextureType,
+    allocation: TextureAllocation,
+}
+
+impl TextureDescriptor {
+    pub fn new(allocation: TextureAllocation) -> Self {
+        Self { ty: TextureType::of_allocation(&allocation), allocation }
+    }
+}
 
 impl DynTypeDescriptor<TextureType> for TextureDescriptor {
     fn get_type(&self) -> TextureType {
-        TextureType {}
+        self.ty.clone()
     }
 }
 
@@ -43,16 +224,53 @@ impl DynTypeTrait for TextureType {
     type Descriptor = TextureDescriptor;
     type DynAlloc = TextureAllocation;
 
-    fn create_value_from_descriptor(_descriptor: Self::Descriptor) -> Self::DynAlloc {
-        todo!()
+    fn create_value_from_descriptor(descriptor: Self::Descriptor) -> Self::DynAlloc {
+        descriptor.allocation
     }
 
-    fn is_abi_compatible(&self, _other: &Self) -> bool {
-        todo!()
+    /// `self` is the type of the producer and `other` the type of the consumer.
+    fn is_abi_compatible(&self, other: &Self) -> bool {
+        self.check_compatibility(other).is_ok()
     }
 
     unsafe fn children<'a>(&'a self, _data: TypedBytes<'a>) -> Vec<TypedBytes<'a>> {
-        todo!()
+        // Textures do not contain any references to other allocations.
+        Vec::new()
+    }
+}
+
+pub trait TextureRefExt<'a> {
+    fn texture(&self) -> &TextureAllocation;
+}
+
+impl<'a, R> TextureRefExt<'a> for R
+where R: Ref<'a, TextureType>
+{
+    fn texture(&self) -> &TextureAllocation {
+        let typed_bytes = unsafe { self.typed_bytes() };
+        typed_bytes.bytes().downcast_ref_unwrap::<TextureAllocation>()
+    }
+}
+
+/// Describes textures by their size and format, as their contents reside on the GPU.
+pub struct TextureFormatter;
+
+impl TypeFormatter for TextureFormatter {
+    fn format_short(&self, _formatter: &ValueFormatter, typed_bytes: TypedBytes<'_>) -> String {
+        let allocation = typed_bytes.bytes().downcast_ref_unwrap::<TextureAllocation>();
+
+        match allocation {
+            TextureAllocation::Texture(_) | TextureAllocation::SharedTexture(_) => {
+                let texture = allocation.owned_texture().unwrap();
+                let format = TextureFormat::from_wgpu(texture.format)
+                    .map(|format| format.to_string())
+                    .unwrap_or_else(|| format!("{:?}", texture.format));
+
+                format!("Texture {}×{}, {}", texture.size.x, texture.size.y, format)
+            }
+            TextureAllocation::TextureView(_) => "Texture view".to_string(),
+            TextureAllocation::SwapchainFrame(_) => "Swapchain frame".to_string(),
+        }
     }
 }
 
@@ -0,0 +1,416 @@
+//! The overlay listing the kinds of nodes to insert into the focused graph, followed by the entries
+//! of the [`Library`].
+//!
+//! The kinds of nodes are identified by their [`NodeBehaviour::name`], which is also how graph
+//! files and presets refer to them, so the usage recorded in the settings survives plugins being
+//! reloaded and the kinds of nodes being reordered.
+//!
+//! [`NodeBehaviour::name`]: crate::node::behaviour::NodeBehaviour::name
+
+use crate::config::SharedConfig;
+use crate::keymap::{Action, Keymap};
+use crate::library::Library;
+use crate::node::behaviour::all_behaviours;
+use crate::style::{consts, Theme, Themeable};
+use iced::{button, text_input, Align, Button, Column, Container, Element, Length, Row, Text, TextInput};
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The number of the most recently inserted kinds of nodes listed.
+pub const MAX_RECENT_NODES: usize = 8;
+/// Added to the score of favorite kinds of nodes matching the query. Less than the bonus of a
+/// single consecutive match, so that favorites are preferred among similar matches only.
+const FAVORITE_BOOST: f32 = 1.5;
+/// Added to the score of the most recently inserted kind of node matching the query, decreasing
+/// with the position among the recent ones.
+const RECENT_BOOST: f32 = 1.0;
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+/// How often and when a kind of node has been inserted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeUsage {
+    pub count: u64,
+    /// In seconds since the Unix epoch.
+    pub last_used: u64,
+}
+
+/// The usage of the kinds of nodes, keyed by their names.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NodeUsageHistory(BTreeMap<String, NodeUsage>);
+
+impl NodeUsageHistory {
+    /// Counts an insertion of the kind of node, now.
+    pub fn record(&mut self, behaviour: &str) {
+        let usage = self.0.entry(behaviour.to_string()).or_default();
+
+        usage.count += 1;
+        usage.last_used = unix_time();
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The most recently inserted kinds of nodes, the most recent first. Kinds inserted within the
+    /// same second are ordered by how often they have been inserted.
+    pub fn recent(&self, count: usize) -> Vec<&str> {
+        let mut recent = self.0.iter().collect::<Vec<_>>();
+
+        recent.sort_by_key(|(name, usage)| (std::cmp::Reverse((usage.last_used, usage.count)), *name));
+        recent.into_iter().take(count).map(|(name, _)| name.as_str()).collect()
+    }
+}
+
+/// Scores how well the query matches the name, ignoring the case. The characters of the query
+/// have to appear in the name in order, `None` otherwise. Characters following the previous match
+/// or starting a word score higher, skipped characters lower the score slightly.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<f32> {
+    let name = name.chars().collect::<Vec<_>>();
+    let mut score = 0.0;
+    let mut position = 0;
+    let mut previous_match = None;
+
+    for query_char in query.chars().filter(|c| !c.is_whitespace()) {
+        let found =
+            (position..name.len()).find(|&index| name[index].to_lowercase().eq(query_char.to_lowercase()))?;
+        let word_start = found == 0
+            || !name[found - 1].is_alphanumeric()
+            || (name[found - 1].is_lowercase() && name[found].is_uppercase());
+
+        score += 1.0;
+
+        if previous_match.map_or(found == 0, |previous| found == previous + 1) {
+            score += 2.0;
+        } else if word_start {
+            score += 1.5;
+        }
+
+        score -= 0.1 * (found - position) as f32;
+        position = found + 1;
+        previous_match = Some(found);
+    }
+
+    Some(score)
+}
+
+/// Orders the names matching the query from the best match, boosting the favorites and the
+/// recently inserted kinds of nodes slightly. Equally scored names are ordered alphabetically.
+pub fn rank<'a>(
+    query: &str,
+    names: &'a [String],
+    usage: &NodeUsageHistory,
+    favorites: &BTreeSet<String>,
+) -> Vec<&'a str> {
+    let recent = usage.recent(MAX_RECENT_NODES);
+    let mut ranked = names
+        .iter()
+        .filter_map(|name| {
+            let mut score = fuzzy_score(query, name)?;
+
+            if favorites.contains(name) {
+                score += FAVORITE_BOOST;
+            }
+
+            if let Some(position) = recent.iter().position(|recent| *recent == name.as_str()) {
+                score += RECENT_BOOST * (1.0 - position as f32 / MAX_RECENT_NODES as f32);
+            }
+
+            Some((name.as_str(), score))
+        })
+        .collect::<Vec<_>>();
+
+    ranked.sort_by_key(|(name, score)| (std::cmp::Reverse(OrderedFloat(*score)), *name));
+    ranked.into_iter().map(|(name, _)| name).collect()
+}
+
+#[derive(Debug, Clone)]
+pub enum NodePaletteMessage {
+    UpdateQuery(String),
+    /// Creates a node of the kind, handled by the application.
+    Insert(String),
+    /// Copies the nodes of the library entry with the name, handled by the application.
+    InsertFromLibrary(String),
+    /// Pins the kind of node to the favorites, or unpins it.
+    ToggleFavorite(String),
+    Close,
+}
+
+/// The controls of a listed kind of node.
+#[derive(Debug, Default)]
+struct PaletteRow {
+    insert_state: button::State,
+    favorite_state: button::State,
+}
+
+/// The state of the node palette overlay.
+#[derive(Debug, Default)]
+pub struct NodePalette {
+    pub open: bool,
+    /// The names of all kinds of nodes, collected once the palette is opened.
+    names: Vec<String>,
+    /// The names and the summaries of the library entries, collected once the palette is opened.
+    library: Vec<(String, String)>,
+    query_state: text_input::State,
+    query: String,
+    rows: Vec<PaletteRow>,
+    close_state: button::State,
+}
+
+impl NodePalette {
+    /// Opens or closes the overlay, clearing the query.
+    pub fn toggle(&mut self, library: &Library) {
+        self.open = !self.open;
+
+        if self.open {
+            self.names = all_behaviours().iter().map(|behaviour| behaviour.name().to_string()).collect();
+            self.names.sort();
+            self.library =
+                library.entries().iter().map(|entry| (entry.info.name.clone(), entry.summary())).collect();
+            self.query_state = text_input::State::focused();
+            self.query.clear();
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn update(&mut self, message: NodePaletteMessage, config: &SharedConfig) {
+        match message {
+            NodePaletteMessage::UpdateQuery(query) => self.query = query,
+            NodePaletteMessage::ToggleFavorite(name) => {
+                config.modify(|config| config.toggle_favorite_node(&name))
+            }
+            NodePaletteMessage::Insert(_) | NodePaletteMessage::InsertFromLibrary(_) => (),
+            NodePaletteMessage::Close => self.close(),
+        }
+    }
+
+    /// Without a query, the favorites and the recently inserted kinds of nodes are listed above
+    /// all kinds of nodes. Otherwise, the matching ones are listed from the best match, which is
+    /// inserted by pressing Enter. Kinds that are not available anymore are left out. The library
+    /// entries matching the query are listed last, and inserted by pressing Enter if no kind of node
+    /// matches.
+    pub fn view(
+        &mut self,
+        usage: &NodeUsageHistory,
+        favorites: &BTreeSet<String>,
+        keymap: &Keymap,
+        theme: &dyn Theme,
+    ) -> Element<NodePaletteMessage> {
+        let names = &self.names;
+        let available = |name: &&str| names.iter().any(|available| available == name);
+        let mut best_match = None;
+        let query_empty = self.query.trim().is_empty();
+        let sections: Vec<(&str, Vec<&str>)> = if query_empty {
+            vec![
+                ("Favorites", favorites.iter().map(String::as_str).filter(available).collect()),
+                ("Recent", usage.recent(MAX_RECENT_NODES).into_iter().filter(available).collect()),
+                ("All nodes", names.iter().map(String::as_str).collect()),
+            ]
+        } else {
+            let ranked = rank(&self.query, names, usage, favorites);
+
+            best_match = ranked.first().map(|name| NodePaletteMessage::Insert(name.to_string()));
+            vec![("Results", ranked)]
+        };
+        let mut library = self
+            .library
+            .iter()
+            .filter_map(|(name, summary)| {
+                let score = if query_empty { 0.0 } else { fuzzy_score(&self.query, name)? };
+
+                Some((name, summary, score))
+            })
+            .collect::<Vec<_>>();
+
+        library.sort_by_key(|(name, _, score)| (std::cmp::Reverse(OrderedFloat(*score)), *name));
+
+        if best_match.is_none() {
+            best_match =
+                library.first().map(|(name, _, _)| NodePaletteMessage::InsertFromLibrary(name.to_string()));
+        }
+
+        let row_count = sections.iter().map(|(_, names)| names.len()).sum::<usize>() + library.len();
+
+        self.rows.resize_with(row_count, Default::default);
+
+        let mut query = TextInput::new(
+            &mut self.query_state,
+            "Search nodes",
+            &self.query,
+            NodePaletteMessage::UpdateQuery,
+        )
+        .theme(theme);
+
+        if let Some(best_match) = best_match {
+            query = query.on_submit(best_match);
+        }
+
+        let mut column = Column::new()
+            .theme(theme)
+            .push(Text::new("Insert node").size(consts::TEXT_SIZE_TITLE))
+            .push(query);
+        let mut rows = self.rows.iter_mut();
+
+        for (title, names) in sections {
+            if names.is_empty() {
+                continue;
+            }
+
+            column = column.push(Text::new(title).size(consts::TEXT_SIZE_TITLE));
+
+            for (name, row) in names.into_iter().zip(&mut rows) {
+                let favorite = favorites.contains(name);
+
+                column = column.push(
+                    Row::new()
+                        .theme(theme)
+                        .push(
+                            Button::new(
+                                &mut row.insert_state,
+                                Text::new(name).size(consts::TEXT_SIZE_REGULAR),
+                            )
+                            .width(Length::Fill)
+                            .on_press(NodePaletteMessage::Insert(name.to_string())),
+                        )
+                        .push(
+                            Button::new(&mut row.favorite_state, Text::new(if favorite { "★" } else { "☆" }))
+                                .on_press(NodePaletteMessage::ToggleFavorite(name.to_string())),
+                        )
+                        .align_items(Align::Center)
+                        .width(Length::Fill),
+                );
+            }
+        }
+
+        if !library.is_empty() {
+            column = column.push(Text::new("Library").size(consts::TEXT_SIZE_TITLE));
+        }
+
+        for ((name, summary, _), row) in library.into_iter().zip(&mut rows) {
+            column = column.push(
+                Button::new(
+                    &mut row.insert_state,
+                    Text::new(format!("{} ({})", name, summary)).size(consts::TEXT_SIZE_REGULAR),
+                )
+                .width(Length::Fill)
+                .on_press(NodePaletteMessage::InsertFromLibrary(name.clone())),
+            );
+        }
+
+        column = column.push(
+            Button::new(&mut self.close_state, Text::new("Close"))
+                .width(Length::Fill)
+                .on_press(NodePaletteMessage::Close),
+        );
+
+        if let Some(shortcut) = keymap.shortcut(Action::ToggleNodePalette) {
+            column = column.push(
+                Text::new(format!("Press {} to close. Star a node to pin it to the favorites.", shortcut))
+                    .size(consts::TEXT_SIZE_REGULAR),
+            );
+        }
+
+        let column = column.width(Length::Units(480));
+
+        Container::new(column)
+            .theme(theme)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    /// A history of the kinds of nodes, with their counts and times of their last insertions.
+    fn history(usages: &[(&str, u64, u64)]) -> NodeUsageHistory {
+        NodeUsageHistory(
+            usages
+                .iter()
+                .map(|(name, count, last_used)| {
+                    (name.to_string(), NodeUsage { count: *count, last_used: *last_used })
+                })
+                .collect(),
+        )
+    }
+
+    fn favorites(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn recent_kinds_are_ordered_by_time_then_by_count() {
+        let history = history(&[("Abs", 1, 10), ("Debug", 5, 30), ("Clock", 2, 30), ("Window", 9, 20)]);
+
+        assert_eq!(history.recent(8), vec!["Debug", "Clock", "Window", "Abs"]);
+        assert_eq!(history.recent(2), vec!["Debug", "Clock"]);
+    }
+
+    #[test]
+    fn names_are_ranked_by_the_match() {
+        let names = names(&["Absolute Value", "Abs", "Add", "Array Builder Sum", "Debug", "Gradient"]);
+        let ranked = rank("abs", &names, &Default::default(), &Default::default());
+
+        // Consecutive matches rank first, names not containing the query are left out.
+        assert_eq!(ranked, vec!["Abs", "Absolute Value", "Array Builder Sum"]);
+    }
+
+    #[test]
+    fn favorites_are_preferred_among_similar_matches_only() {
+        let names = names(&["Absolute Value", "Abs", "Array Builder Sum"]);
+
+        assert_eq!(
+            rank("abs", &names, &Default::default(), &favorites(&["Absolute Value"])),
+            vec!["Absolute Value", "Abs", "Array Builder Sum"]
+        );
+        // A favorite does not outrank better matches.
+        assert_eq!(
+            rank("abs", &names, &Default::default(), &favorites(&["Array Builder Sum"])),
+            vec!["Abs", "Absolute Value", "Array Builder Sum"]
+        );
+    }
+
+    #[test]
+    fn recent_kinds_are_boosted_by_their_recency() {
+        let names = names(&["Abs", "Absolute Value", "Clock", "Debug"]);
+        let history = history(&[("Clock", 1, 10), ("Debug", 1, 20)]);
+
+        // Without a query, all names match equally.
+        assert_eq!(
+            rank("", &names, &history, &Default::default()),
+            vec!["Debug", "Clock", "Abs", "Absolute Value"]
+        );
+        assert_eq!(
+            rank("", &names, &history, &favorites(&["Absolute Value"])),
+            vec!["Absolute Value", "Debug", "Clock", "Abs"]
+        );
+    }
+
+    #[test]
+    fn usage_of_unknown_kinds_is_ignored() {
+        let names = names(&["Abs", "Debug"]);
+        let history = history(&[("Removed Plugin Node", 100, 50)]);
+
+        assert_eq!(rank("", &names, &history, &favorites(&["Removed Plugin Node"])), vec!["Abs", "Debug"]);
+    }
+}
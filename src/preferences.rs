@@ -0,0 +1,752 @@
+use crate::config::{AdapterPreference, Config, ReconnectOnRemoval, SharedConfig, ThemeChoice};
+use crate::keymap::{Action, Keymap, Shortcut};
+use crate::logging::{Category, LevelFilter, Logger};
+use crate::preset::Presets;
+use crate::self_check::SelfCheck;
+use crate::style::{consts, Theme, Themeable};
+use crate::threading::{AppliedThreadSettings, ThreadPriority};
+use iced::keyboard::KeyCode;
+use iced::widget::checkbox::Checkbox;
+use iced::{
+    button::{self, Button},
+    pick_list::{self, PickList},
+    text_input::{self, TextInput},
+    Align, Color, Column, Container, Element, Length, Row, Text,
+};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+pub enum PreferencesMessage {
+    UpdateTheme(ThemeChoice),
+    UpdateAdapterPreference(AdapterPreference),
+    UpdateExecutorTargetRate(String),
+    UpdateWatchdogThreshold(String),
+    UpdateExecutorPriority(ThreadPriority),
+    UpdateExecutorAffinityMask(String),
+    UpdateGridSize(String),
+    UpdateAutosaveInterval(String),
+    UpdateNodeRemovalConfirmationThreshold(String),
+    UpdateReconnectOnRemoval(ReconnectOnRemoval),
+    UpdateConfirmBreakingEdits(bool),
+    UpdateCreateNodesOnDrop(bool),
+    UpdateReviewChangesBeforeSaving(bool),
+    UpdateShowThroughput(bool),
+    UpdateShowFrameRate(bool),
+    UpdateShowExecutionOrder(bool),
+    UpdateDimCrossingConnections(bool),
+    UpdateRecordSessions(bool),
+    UpdateMaxListLength(String),
+    UpdateHistoryDepth(String),
+    UpdateHistoryMemoryCap(String),
+    UpdatePluginDirectory(String),
+    /// Stops replacing the nodes of the behaviour with the name by placeholders, see
+    /// [`Config::disabled_behaviours`].
+    EnableBehaviour(String),
+    UpdateLogLevel(Category, LevelFilter),
+    /// Forgets which kinds of nodes have been inserted, keeping the favorites.
+    ClearNodeUsage,
+    /// Checks the integrity of the application and logs the findings.
+    RunSelfCheck,
+    /// Edits the name of the preset at the position, without renaming it yet.
+    UpdatePresetName(usize, String),
+    RenamePreset(usize),
+    DeletePreset(usize),
+    /// Creates a node from the preset at the position, handled by the application.
+    InsertPreset(usize),
+    /// Binds the next pressed shortcut to the action.
+    CaptureShortcut(Action),
+    ResetShortcuts,
+    Close,
+}
+
+#[derive(Debug, Default)]
+struct TextField {
+    state: text_input::State,
+    value: String,
+}
+
+impl TextField {
+    fn set(&mut self, value: impl ToString) {
+        self.value = value.to_string();
+    }
+
+    /// Stores the raw value and returns the parsed value, if it is valid.
+    fn update<T: FromStr>(&mut self, value: String) -> Option<T> {
+        let parsed = value.parse().ok();
+        self.value = value;
+        parsed
+    }
+}
+
+/// The controls of a preset.
+#[derive(Debug, Default)]
+struct PresetRow {
+    name: TextField,
+    insert_state: button::State,
+    delete_state: button::State,
+}
+
+/// The state of the preferences overlay.
+#[derive(Debug, Default)]
+pub struct Preferences {
+    pub open: bool,
+    theme_state: pick_list::State<ThemeChoice>,
+    adapter_preference_state: pick_list::State<AdapterPreference>,
+    executor_priority_state: pick_list::State<ThreadPriority>,
+    reconnect_on_removal_state: pick_list::State<ReconnectOnRemoval>,
+    executor_target_rate: TextField,
+    watchdog_threshold: TextField,
+    executor_affinity_mask: TextField,
+    grid_size: TextField,
+    autosave_interval: TextField,
+    node_removal_confirmation_threshold: TextField,
+    max_list_length: TextField,
+    history_depth: TextField,
+    history_memory_cap: TextField,
+    plugin_directory: TextField,
+    enable_behaviour_states: Vec<button::State>,
+    clear_node_usage_state: button::State,
+    log_level_states: [pick_list::State<LevelFilter>; Category::VALUES.len()],
+    self_check_state: button::State,
+    preset_rows: Vec<PresetRow>,
+    /// The reason the last change of the presets failed.
+    preset_error: Option<String>,
+    shortcut_states: [button::State; Action::VALUES.len()],
+    reset_shortcuts_state: button::State,
+    close_state: button::State,
+    /// The action the next pressed shortcut is bound to.
+    capturing: Option<Action>,
+}
+
+impl Preferences {
+    /// The number of the most recent log messages displayed below the log levels.
+    const RECENT_LOG_RECORDS: usize = 5;
+
+    /// Opens or closes the overlay, resetting the text fields to the current settings.
+    pub fn toggle(&mut self, config: &Config, presets: &Presets) {
+        self.open = !self.open;
+        self.capturing = None;
+
+        if self.open {
+            self.reset_presets(presets);
+            self.executor_target_rate.set(config.executor_target_rate);
+            self.watchdog_threshold.set(config.watchdog_threshold);
+            self.executor_affinity_mask.set(config.executor_affinity_mask);
+            self.grid_size.set(config.grid_size);
+            self.autosave_interval.set(config.autosave_interval);
+            self.node_removal_confirmation_threshold.set(config.node_removal_confirmation_threshold);
+            self.max_list_length.set(config.max_list_length);
+            self.history_depth.set(config.history_depth);
+            self.history_memory_cap.set(config.history_memory_cap);
+            self.plugin_directory.set(
+                config.plugin_directory.as_ref().map(|path| path.display().to_string()).unwrap_or_default(),
+            );
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.capturing = None;
+    }
+
+    /// Resets the names of the presets, whose positions change as they are renamed.
+    fn reset_presets(&mut self, presets: &Presets) {
+        self.preset_rows.resize_with(presets.entries().len(), Default::default);
+
+        for (row, entry) in self.preset_rows.iter_mut().zip(presets.entries()) {
+            row.name.set(&entry.preset.name);
+        }
+    }
+
+    /// Applies the changes immediately. Values that cannot be parsed are kept in the text fields,
+    /// but are not applied.
+    pub fn update(&mut self, message: PreferencesMessage, config: &SharedConfig, presets: &mut Presets) {
+        use PreferencesMessage::*;
+
+        match message {
+            UpdateTheme(theme) => config.modify(|config| config.theme = theme),
+            UpdateAdapterPreference(adapter_preference) => {
+                config.modify(|config| config.adapter_preference = adapter_preference)
+            }
+            UpdateExecutorTargetRate(value) => {
+                if let Some(value) = self.executor_target_rate.update::<f32>(value) {
+                    if value >= 0.0 {
+                        config.modify(|config| config.executor_target_rate = value);
+                    }
+                }
+            }
+            UpdateWatchdogThreshold(value) => {
+                if let Some(value) = self.watchdog_threshold.update::<f32>(value) {
+                    if value >= 0.0 {
+                        config.modify(|config| config.watchdog_threshold = value);
+                    }
+                }
+            }
+            UpdateExecutorPriority(executor_priority) => {
+                config.modify(|config| config.executor_priority = executor_priority)
+            }
+            UpdateExecutorAffinityMask(value) => {
+                if let Some(value) = self.executor_affinity_mask.update::<u64>(value) {
+                    config.modify(|config| config.executor_affinity_mask = value);
+                }
+            }
+            UpdateGridSize(value) => {
+                if let Some(value) = self.grid_size.update::<f32>(value) {
+                    if value >= 0.0 {
+                        config.modify(|config| config.grid_size = value);
+                    }
+                }
+            }
+            UpdateAutosaveInterval(value) => {
+                if let Some(value) = self.autosave_interval.update::<u64>(value) {
+                    config.modify(|config| config.autosave_interval = value);
+                }
+            }
+            UpdateNodeRemovalConfirmationThreshold(value) => {
+                if let Some(value) = self.node_removal_confirmation_threshold.update::<usize>(value) {
+                    config.modify(|config| config.node_removal_confirmation_threshold = value);
+                }
+            }
+            UpdateReconnectOnRemoval(reconnect_on_removal) => {
+                config.modify(|config| config.reconnect_on_removal = reconnect_on_removal)
+            }
+            UpdateConfirmBreakingEdits(confirm_breaking_edits) => {
+                config.modify(|config| config.confirm_breaking_edits = confirm_breaking_edits)
+            }
+            UpdateCreateNodesOnDrop(create_nodes_on_drop) => {
+                config.modify(|config| config.create_nodes_on_drop = create_nodes_on_drop)
+            }
+            UpdateReviewChangesBeforeSaving(review_changes_before_saving) => {
+                config.modify(|config| config.review_changes_before_saving = review_changes_before_saving)
+            }
+            UpdateShowThroughput(show_throughput) => {
+                config.modify(|config| config.show_throughput = show_throughput)
+            }
+            UpdateShowFrameRate(show_frame_rate) => {
+                config.modify(|config| config.show_frame_rate = show_frame_rate)
+            }
+            UpdateShowExecutionOrder(show_execution_order) => {
+                config.modify(|config| config.show_execution_order = show_execution_order)
+            }
+            UpdateDimCrossingConnections(dim_crossing_connections) => {
+                config.modify(|config| config.dim_crossing_connections = dim_crossing_connections)
+            }
+            UpdateRecordSessions(record_sessions) => {
+                config.modify(|config| config.record_sessions = record_sessions)
+            }
+            UpdateMaxListLength(value) => {
+                if let Some(value) = self.max_list_length.update::<usize>(value) {
+                    config.modify(|config| config.max_list_length = value);
+                }
+            }
+            UpdateHistoryDepth(value) => {
+                if let Some(value) = self.history_depth.update::<usize>(value) {
+                    config.modify(|config| config.history_depth = value);
+                }
+            }
+            UpdateHistoryMemoryCap(value) => {
+                if let Some(value) = self.history_memory_cap.update::<usize>(value) {
+                    config.modify(|config| config.history_memory_cap = value);
+                }
+            }
+            UpdatePluginDirectory(value) => {
+                self.plugin_directory.value = value.clone();
+                let plugin_directory = if value.is_empty() { None } else { Some(PathBuf::from(value)) };
+                config.modify(|config| config.plugin_directory = plugin_directory);
+            }
+            EnableBehaviour(name) => config.modify(|config| {
+                config.disabled_behaviours.remove(&name);
+            }),
+            UpdateLogLevel(category, filter) => {
+                Logger::get().set_filter(category, filter);
+                config.modify(|config| config.log_levels.set(category, filter));
+            }
+            ClearNodeUsage => config.modify(|config| config.node_usage.clear()),
+            // The test graph is not executed, as the executor is running.
+            RunSelfCheck => SelfCheck::run(&config.get(), None).log(),
+            UpdatePresetName(index, value) => {
+                if let Some(row) = self.preset_rows.get_mut(index) {
+                    row.name.value = value;
+                }
+            }
+            RenamePreset(index) => {
+                if let Some(row) = self.preset_rows.get(index) {
+                    let result = presets.rename(index, &row.name.value);
+
+                    self.preset_error = result.err().map(|error| error.to_string());
+                    self.reset_presets(presets);
+                }
+            }
+            DeletePreset(index) => {
+                self.preset_error = presets.remove(index).err().map(|error| error.to_string());
+                self.reset_presets(presets);
+            }
+            InsertPreset(_) => (),
+            CaptureShortcut(action) => self.capturing = Some(action),
+            ResetShortcuts => config.modify(|config| config.shortcuts = Keymap::default()),
+            Close => self.close(),
+        }
+    }
+
+    /// Whether the next key press is to be bound to an action, rather than trigger one.
+    pub fn is_capturing(&self) -> bool {
+        self.open && self.capturing.is_some()
+    }
+
+    /// Binds the shortcut to the action being captured. Escape cancels the capture and Backspace
+    /// unbinds the action.
+    pub fn capture(&mut self, shortcut: Shortcut, config: &SharedConfig) {
+        let action = if let Some(action) = self.capturing.take() { action } else { return };
+
+        if shortcut == Shortcut::from(KeyCode::Escape) {
+            return;
+        }
+
+        let shortcut = if shortcut == Shortcut::from(KeyCode::Backspace) { None } else { Some(shortcut) };
+
+        config.modify(|config| config.shortcuts.bind(action, shortcut));
+    }
+
+    /// `executor_thread` are the settings applied to the running executor, which may differ from
+    /// the configured ones until it is restarted, or if they have been denied by the OS.
+    pub fn view(
+        &mut self,
+        config: &Config,
+        presets: &Presets,
+        executor_thread: Option<AppliedThreadSettings>,
+        theme: &dyn Theme,
+    ) -> Element<PreferencesMessage> {
+        fn labeled<'a>(
+            label: &str,
+            element: impl Into<Element<'a, PreferencesMessage>>,
+            theme: &dyn Theme,
+        ) -> Row<'a, PreferencesMessage> {
+            Row::new()
+                .theme(theme)
+                .push(Text::new(label).width(Length::FillPortion(1)))
+                .push(Container::new(element).width(Length::FillPortion(1)))
+                .align_items(Align::Center)
+                .width(Length::Fill)
+        }
+
+        let mut column = Column::new()
+            .theme(theme)
+            .push(Text::new("Preferences").size(consts::TEXT_SIZE_TITLE))
+            .push(labeled(
+                "Theme",
+                PickList::new(
+                    &mut self.theme_state,
+                    &ThemeChoice::VALUES[..],
+                    Some(config.theme),
+                    PreferencesMessage::UpdateTheme,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+                theme,
+            ))
+            .push(labeled(
+                "Adapter (requires restart)",
+                PickList::new(
+                    &mut self.adapter_preference_state,
+                    &AdapterPreference::VALUES[..],
+                    Some(config.adapter_preference),
+                    PreferencesMessage::UpdateAdapterPreference,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+                theme,
+            ))
+            .push(labeled(
+                "Executions per second (0 = unlimited)",
+                TextInput::new(
+                    &mut self.executor_target_rate.state,
+                    "0",
+                    &self.executor_target_rate.value,
+                    PreferencesMessage::UpdateExecutorTargetRate,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Warn about nodes executing longer than this many seconds (0 = never)",
+                TextInput::new(
+                    &mut self.watchdog_threshold.state,
+                    "0",
+                    &self.watchdog_threshold.value,
+                    PreferencesMessage::UpdateWatchdogThreshold,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Executor priority (requires restart)",
+                PickList::new(
+                    &mut self.executor_priority_state,
+                    &ThreadPriority::VALUES[..],
+                    Some(config.executor_priority),
+                    PreferencesMessage::UpdateExecutorPriority,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+                theme,
+            ))
+            .push(labeled(
+                "Executor CPU affinity mask (0 = any CPU, requires restart)",
+                TextInput::new(
+                    &mut self.executor_affinity_mask.state,
+                    "0",
+                    &self.executor_affinity_mask.value,
+                    PreferencesMessage::UpdateExecutorAffinityMask,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(
+                Text::new(match executor_thread {
+                    Some(AppliedThreadSettings { priority, affinity_mask: Some(affinity_mask) }) => {
+                        format!(
+                            "The executor is running with the {} priority on the CPUs {:#b}.",
+                            priority, affinity_mask
+                        )
+                    }
+                    Some(AppliedThreadSettings { priority, affinity_mask: None }) => {
+                        format!("The executor is running with the {} priority on any CPU.", priority)
+                    }
+                    None => "The executor has not been started yet.".to_string(),
+                })
+                .size(consts::TEXT_SIZE_REGULAR),
+            )
+            .push(labeled(
+                "Grid size (0 = no snapping)",
+                TextInput::new(
+                    &mut self.grid_size.state,
+                    "0",
+                    &self.grid_size.value,
+                    PreferencesMessage::UpdateGridSize,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Autosave interval in seconds (0 = disabled)",
+                TextInput::new(
+                    &mut self.autosave_interval.state,
+                    "0",
+                    &self.autosave_interval.value,
+                    PreferencesMessage::UpdateAutosaveInterval,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Confirm removing nodes with this many connections (0 = never)",
+                TextInput::new(
+                    &mut self.node_removal_confirmation_threshold.state,
+                    "0",
+                    &self.node_removal_confirmation_threshold.value,
+                    PreferencesMessage::UpdateNodeRemovalConfirmationThreshold,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Reconnect the neighbours of removed nodes",
+                PickList::new(
+                    &mut self.reconnect_on_removal_state,
+                    &ReconnectOnRemoval::VALUES[..],
+                    Some(config.reconnect_on_removal),
+                    PreferencesMessage::UpdateReconnectOnRemoval,
+                )
+                .theme(theme)
+                .width(Length::Fill),
+                theme,
+            ))
+            .push(labeled(
+                "List what edits disconnect or invalidate before applying them",
+                Checkbox::new(
+                    config.confirm_breaking_edits,
+                    "",
+                    PreferencesMessage::UpdateConfirmBreakingEdits,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Create nodes when dropping connections on empty space (hold Shift to skip)",
+                Checkbox::new(config.create_nodes_on_drop, "", PreferencesMessage::UpdateCreateNodesOnDrop)
+                    .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "List the changes before saving over a graph file",
+                Checkbox::new(
+                    config.review_changes_before_saving,
+                    "",
+                    PreferencesMessage::UpdateReviewChangesBeforeSaving,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Show the number of values consumed per second through connections",
+                Checkbox::new(config.show_throughput, "", PreferencesMessage::UpdateShowThroughput)
+                    .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Show the frame rate of the editor next to the status of the schedule",
+                Checkbox::new(config.show_frame_rate, "", PreferencesMessage::UpdateShowFrameRate)
+                    .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Number the nodes in the order they are executed in",
+                Checkbox::new(config.show_execution_order, "", PreferencesMessage::UpdateShowExecutionOrder)
+                    .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Dim connections where they pass over other nodes",
+                Checkbox::new(
+                    config.dim_crossing_connections,
+                    "",
+                    PreferencesMessage::UpdateDimCrossingConnections,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Record the session to attach to bug reports",
+                Checkbox::new(config.record_sessions, "", PreferencesMessage::UpdateRecordSessions)
+                    .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Maximum length of generated lists",
+                TextInput::new(
+                    &mut self.max_list_length.state,
+                    "0",
+                    &self.max_list_length.value,
+                    PreferencesMessage::UpdateMaxListLength,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Recorded past values per node",
+                TextInput::new(
+                    &mut self.history_depth.state,
+                    "0",
+                    &self.history_depth.value,
+                    PreferencesMessage::UpdateHistoryDepth,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Memory for past values per node in bytes",
+                TextInput::new(
+                    &mut self.history_memory_cap.state,
+                    "0",
+                    &self.history_memory_cap.value,
+                    PreferencesMessage::UpdateHistoryMemoryCap,
+                )
+                .theme(theme),
+                theme,
+            ))
+            .push(labeled(
+                "Plugin directory",
+                TextInput::new(
+                    &mut self.plugin_directory.state,
+                    "None",
+                    &self.plugin_directory.value,
+                    PreferencesMessage::UpdatePluginDirectory,
+                )
+                .theme(theme),
+                theme,
+            ));
+
+        self.enable_behaviour_states.resize_with(config.disabled_behaviours.len(), Default::default);
+
+        // Enabling a kind of node takes effect once the graphs containing it are opened again.
+        for (name, enable_state) in config.disabled_behaviours.iter().zip(&mut self.enable_behaviour_states) {
+            column = column.push(labeled(
+                &format!("`{}` is disabled (applies to opened graphs)", name),
+                Button::new(enable_state, Text::new("Enable"))
+                    .on_press(PreferencesMessage::EnableBehaviour(name.clone()))
+                    .width(Length::Fill),
+                theme,
+            ));
+        }
+
+        let mut clear_node_usage =
+            Button::new(&mut self.clear_node_usage_state, Text::new("Clear")).width(Length::Fill);
+
+        // Buttons without a message are displayed as disabled.
+        if !config.node_usage.is_empty() {
+            clear_node_usage = clear_node_usage.on_press(PreferencesMessage::ClearNodeUsage);
+        }
+
+        column = column
+            .push(labeled("Recently inserted nodes in the node palette", clear_node_usage, theme))
+            .push(Text::new("Presets").size(consts::TEXT_SIZE_TITLE));
+
+        let dimmed = {
+            let color = theme.status_colors().idle;
+
+            Color { a: color.a * 0.4, ..color }
+        };
+
+        if presets.entries().is_empty() {
+            column = column.push(
+                Text::new("Save a node as a preset from its context menu to insert it into any graph.")
+                    .size(consts::TEXT_SIZE_REGULAR),
+            );
+        }
+
+        // Presets of kinds of nodes that are not available anymore are listed dimmed, with the
+        // reason, so that they can still be renamed or deleted.
+        for (index, (entry, row)) in presets.entries().iter().zip(self.preset_rows.iter_mut()).enumerate() {
+            let mut kind = Text::new(&entry.preset.behaviour).size(consts::TEXT_SIZE_REGULAR);
+            let mut insert = Button::new(&mut row.insert_state, Text::new("Insert"));
+
+            if entry.unavailable.is_some() {
+                kind = kind.color(dimmed);
+            } else {
+                insert = insert.on_press(PreferencesMessage::InsertPreset(index));
+            }
+
+            column = column.push(
+                Row::new()
+                    .theme(theme)
+                    .push(kind.width(Length::FillPortion(1)))
+                    .push(
+                        TextInput::new(&mut row.name.state, "Name", &row.name.value, move |value| {
+                            PreferencesMessage::UpdatePresetName(index, value)
+                        })
+                        .on_submit(PreferencesMessage::RenamePreset(index))
+                        .theme(theme)
+                        .width(Length::FillPortion(2)),
+                    )
+                    .push(insert)
+                    .push(
+                        Button::new(&mut row.delete_state, Text::new("Delete"))
+                            .on_press(PreferencesMessage::DeletePreset(index)),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+
+            if let Some(reason) = entry.unavailable.as_ref() {
+                column = column.push(Text::new(reason).size(consts::TEXT_SIZE_REGULAR).color(dimmed));
+            }
+        }
+
+        if !presets.entries().is_empty() {
+            column = column.push(
+                Text::new(
+                    "Press Enter to rename a preset. Save a node under an existing name to overwrite the \
+                     preset.",
+                )
+                .size(consts::TEXT_SIZE_REGULAR),
+            );
+        }
+
+        let presets_error = presets
+            .read_only()
+            .map(|reason| format!("The presets cannot be changed. {}", reason))
+            .or_else(|| self.preset_error.clone());
+
+        if let Some(error) = presets_error {
+            column = column.push(Text::new(error).size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        column = column.push(Text::new("Logging").size(consts::TEXT_SIZE_TITLE));
+
+        for (category, state) in Category::VALUES.iter().zip(self.log_level_states.iter_mut()) {
+            let category = *category;
+
+            column = column.push(labeled(
+                &category.to_string(),
+                PickList::new(
+                    state,
+                    &LevelFilter::VALUES[..],
+                    Some(config.log_levels.get(category)),
+                    move |filter| PreferencesMessage::UpdateLogLevel(category, filter),
+                )
+                .theme(theme)
+                .width(Length::Fill),
+                theme,
+            ));
+        }
+
+        column = column.push(
+            Button::new(&mut self.self_check_state, Text::new("Run self-check"))
+                .width(Length::Fill)
+                .on_press(PreferencesMessage::RunSelfCheck),
+        );
+
+        for record in Logger::get().recent_records(Self::RECENT_LOG_RECORDS) {
+            column = column.push(Text::new(record.to_string()).size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        column = column.push(Text::new("Shortcuts").size(consts::TEXT_SIZE_TITLE));
+
+        for (action, state) in Action::VALUES.iter().zip(self.shortcut_states.iter_mut()) {
+            let label = if self.capturing == Some(*action) {
+                "Press a key (Escape to cancel, Backspace to unbind)".to_string()
+            } else {
+                config.shortcuts.shortcut(*action).map(|shortcut| shortcut.to_string()).unwrap_or_default()
+            };
+
+            column = column.push(labeled(
+                &action.to_string(),
+                Button::new(state, Text::new(label).size(consts::TEXT_SIZE_REGULAR))
+                    .width(Length::Fill)
+                    .on_press(PreferencesMessage::CaptureShortcut(*action)),
+                theme,
+            ));
+        }
+
+        for conflict in config.shortcuts.conflicts() {
+            column = column.push(
+                Text::new(format!(
+                    "{} is bound to both \"{}\" and \"{}\", the former takes precedence.",
+                    conflict.shortcut, conflict.action, conflict.shadowed
+                ))
+                .size(consts::TEXT_SIZE_REGULAR),
+            );
+        }
+
+        column = column.push(
+            Row::new()
+                .theme(theme)
+                .push(
+                    Button::new(&mut self.reset_shortcuts_state, Text::new("Reset shortcuts"))
+                        .width(Length::Fill)
+                        .on_press(PreferencesMessage::ResetShortcuts),
+                )
+                .push(
+                    Button::new(&mut self.close_state, Text::new("Close"))
+                        .width(Length::Fill)
+                        .on_press(PreferencesMessage::Close),
+                )
+                .width(Length::Fill),
+        );
+
+        if let Some(shortcut) = config.shortcuts.shortcut(Action::TogglePreferences) {
+            column = column.push(Text::new(format!("Press {} to close.", shortcut)));
+        }
+
+        let column = column.width(Length::Units(480));
+
+        Container::new(column)
+            .theme(theme)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+}
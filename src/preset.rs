@@ -0,0 +1,278 @@
+//! Nodes saved along with their settings, to be inserted into any graph again.
+//!
+//! All presets are stored in a single JSON file in the settings directory, keyed by their names.
+//! The file carries a format version. Files written by newer versions of DVSynth are read, as far
+//! as they can be, but never overwritten, so that going back to an older version does not lose
+//! any presets.
+
+use crate::logging::Category;
+use crate::node::behaviour::{create_behaviour, NodeBehaviourContainer};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Incremented whenever the presets file changes incompatibly.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    /// The [`NodeBehaviour::name`](crate::node::behaviour::NodeBehaviour::name) of the kind of node
+    /// the preset creates.
+    pub behaviour: String,
+    /// As saved by [`NodeBehaviourContainer::save`].
+    pub settings: serde_json::Value,
+}
+
+impl Preset {
+    /// Captures the kind and the settings of the node.
+    pub fn from_behaviour(name: impl Into<String>, behaviour: &dyn NodeBehaviourContainer) -> Self {
+        Self { name: name.into(), behaviour: behaviour.name().to_string(), settings: behaviour.save() }
+    }
+
+    /// Labels the preset by the kind of its node, e.g. `Gradient ▸ Sunset`.
+    pub fn label(&self) -> String {
+        format!("{} ▸ {}", self.behaviour, self.name)
+    }
+
+    /// Creates the behaviour of the node with the settings of the preset applied.
+    pub fn instantiate(&self) -> Result<Box<dyn NodeBehaviourContainer>, PresetError> {
+        let mut behaviour = create_behaviour(&self.behaviour)
+            .ok_or_else(|| PresetError::UnknownBehaviour(self.behaviour.clone()))?;
+
+        behaviour.load(self.settings.clone()).map_err(PresetError::InvalidSettings)?;
+        Ok(behaviour)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PresetFile {
+    version: u32,
+    presets: Vec<Preset>,
+}
+
+/// Read first, so that the version is known even if the rest of the file cannot be parsed.
+#[derive(Deserialize)]
+struct PresetFileVersion {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum PresetError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The kind of node does not exist anymore, such as one provided by a removed plugin.
+    UnknownBehaviour(String),
+    InvalidSettings(serde_json::Error),
+    EmptyName,
+    /// Another preset already has the name.
+    DuplicateName(String),
+    /// The presets file is not overwritten, for the contained reason.
+    ReadOnly(String),
+    NoSettingsDirectory,
+}
+
+impl From<io::Error> for PresetError {
+    fn from(error: io::Error) -> Self {
+        PresetError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for PresetError {
+    fn from(error: serde_json::Error) -> Self {
+        PresetError::Json(error)
+    }
+}
+
+impl Display for PresetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use PresetError::*;
+        match self {
+            Io(error) => write!(f, "I/O error: {}", error),
+            Json(error) => write!(f, "The presets file is not valid: {}", error),
+            UnknownBehaviour(name) => write!(f, "The kind of node `{}` is not available.", name),
+            InvalidSettings(error) => write!(f, "The settings are not valid anymore: {}", error),
+            EmptyName => write!(f, "The name of a preset must not be empty."),
+            DuplicateName(name) => write!(f, "A preset named `{}` already exists.", name),
+            ReadOnly(reason) => write!(f, "The presets cannot be changed: {}", reason),
+            NoSettingsDirectory => write!(f, "The settings directory could not be determined."),
+        }
+    }
+}
+
+/// A preset along with the reason it cannot be instantiated, if any.
+#[derive(Debug, Clone)]
+pub struct PresetEntry {
+    pub preset: Preset,
+    /// Determined once the preset is read or changed, as instantiating presets is not free.
+    pub unavailable: Option<String>,
+}
+
+impl From<Preset> for PresetEntry {
+    fn from(preset: Preset) -> Self {
+        let unavailable = preset.instantiate().err().map(|error| error.to_string());
+
+        Self { preset, unavailable }
+    }
+}
+
+/// The presets of the settings directory, ordered by the kinds of their nodes and their names.
+/// Every change is written to the file immediately.
+#[derive(Debug, Default)]
+pub struct Presets {
+    entries: Vec<PresetEntry>,
+    /// Set if the file must not be overwritten, such as when it was written by a newer version.
+    read_only: Option<String>,
+}
+
+impl Presets {
+    const FILE_NAME: &'static str = "presets.json";
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("dvsynth").join(Self::FILE_NAME))
+    }
+
+    /// Reads the presets, starting without any if the file does not exist. Files that cannot be
+    /// read are left untouched.
+    pub fn load() -> Self {
+        let path = if let Some(path) = Self::path() { path } else { return Default::default() };
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Default::default(),
+            Err(error) => return Self::unreadable(format!("Could not read `{}`: {}", path.display(), error)),
+        };
+        let version = match serde_json::from_str::<PresetFileVersion>(&source) {
+            Ok(PresetFileVersion { version }) => version,
+            Err(error) => return Self::unreadable(format!("`{}` is not valid: {}", path.display(), error)),
+        };
+        let read_only = Some(version).filter(|version| *version > FORMAT_VERSION).map(|version| {
+            format!("They were saved by a newer version of DVSynth, in the format version {}.", version)
+        });
+
+        // Newer files are read as far as they are compatible, unknown fields are ignored.
+        match serde_json::from_str::<PresetFile>(&source) {
+            Ok(file) => {
+                if let Some(reason) = read_only.as_ref() {
+                    warn!(Category::Ui, "The presets will not be saved. {}", reason);
+                }
+
+                let mut presets =
+                    Self { entries: file.presets.into_iter().map(Into::into).collect(), read_only };
+
+                presets.sort();
+                presets
+            }
+            Err(error) => Self::unreadable(format!("`{}` is not valid: {}", path.display(), error)),
+        }
+    }
+
+    fn unreadable(reason: String) -> Self {
+        error!(Category::Ui, "Could not load the presets, they will not be saved: {}", reason);
+
+        Self { entries: Vec::new(), read_only: Some(reason) }
+    }
+
+    fn save(&self) -> Result<(), PresetError> {
+        let path = Self::path().ok_or(PresetError::NoSettingsDirectory)?;
+        let file = PresetFile {
+            version: FORMAT_VERSION,
+            presets: self.entries.iter().map(|entry| entry.preset.clone()).collect(),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&path, serde_json::to_vec_pretty(&file)?)?;
+        Ok(())
+    }
+
+    fn sort(&mut self) {
+        self.entries.sort_by(|a, b| {
+            (&a.preset.behaviour, &a.preset.name).cmp(&(&b.preset.behaviour, &b.preset.name))
+        });
+    }
+
+    /// Applies the change to the presets and writes them into the file.
+    fn modify(&mut self, modify: impl FnOnce(&mut Vec<PresetEntry>)) -> Result<(), PresetError> {
+        if let Some(reason) = self.read_only.as_ref() {
+            return Err(PresetError::ReadOnly(reason.clone()));
+        }
+
+        (modify)(&mut self.entries);
+        self.sort();
+        self.save()
+    }
+
+    pub fn entries(&self) -> &[PresetEntry] {
+        &self.entries
+    }
+
+    /// The reason the presets cannot be changed, if they cannot.
+    pub fn read_only(&self) -> Option<&str> {
+        self.read_only.as_deref()
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.preset.name == name)
+    }
+
+    /// The name, followed by the lowest number that makes it unique, if it is taken.
+    pub fn unique_name(&self, name: &str) -> String {
+        if self.position(name).is_none() {
+            return name.to_string();
+        }
+
+        (2..)
+            .map(|number| format!("{} ({})", name, number))
+            .find(|candidate| self.position(candidate).is_none())
+            .unwrap()
+    }
+
+    /// Adds the preset, replacing the preset with the same name, if `overwrite` is set.
+    pub fn insert(&mut self, mut preset: Preset, overwrite: bool) -> Result<(), PresetError> {
+        preset.name = validate_name(&preset.name)?;
+
+        let existing = self.position(&preset.name);
+
+        if existing.is_some() && !overwrite {
+            return Err(PresetError::DuplicateName(preset.name));
+        }
+
+        self.modify(|entries| {
+            if let Some(index) = existing {
+                entries.remove(index);
+            }
+
+            entries.push(preset.into());
+        })
+    }
+
+    pub fn rename(&mut self, index: usize, name: &str) -> Result<(), PresetError> {
+        let name = validate_name(name)?;
+
+        if self.position(&name).map_or(false, |existing| existing != index) {
+            return Err(PresetError::DuplicateName(name));
+        }
+
+        self.modify(|entries| entries[index].preset.name = name)
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<(), PresetError> {
+        self.modify(|entries| {
+            entries.remove(index);
+        })
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, PresetError> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        Err(PresetError::EmptyName)
+    } else {
+        Ok(name.to_string())
+    }
+}
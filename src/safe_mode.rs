@@ -0,0 +1,207 @@
+//! Detection of crash loops during startup, which are escaped by starting in safe mode.
+//!
+//! A marker is written into the cache directory at launch, and removed once the first frame has
+//! been drawn and the executor has completed a pass over the opened graph, see
+//! [`StartupMarker::complete`]. A marker left over from the previous launch means that it died
+//! during startup, before the user could intervene, for example while executing a graph whose
+//! nodes crash the GPU driver. The marker counts such launches, and once there have been
+//! [`MAX_FAILED_STARTUPS`] of them in a row, the application starts in safe mode. In safe mode, the
+//! nodes of external behaviours are replaced by placeholders when graphs are loaded, see
+//! [`PlaceholderNodeBehaviour`](crate::node::behaviour::PlaceholderNodeBehaviour). The kinds of
+//! nodes disabled permanently from the banner are replaced outside of safe mode as well, see
+//! [`Substitutions`].
+
+use crate::logging::Category;
+use crate::node::behaviour::{NodeBehaviourContainer, PlaceholderReason};
+use crate::style::{consts, Theme, Themeable};
+use iced::{button, container, Align, Button, Color, Column, Container, Element, Length, Row, Text};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The number of consecutive launches which died during startup, after which safe mode is used.
+pub const MAX_FAILED_STARTUPS: u32 = 2;
+const MARKER_FILE_NAME: &str = "starting";
+
+fn directory() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("dvsynth"))
+}
+
+/// The marker of a launch which has not finished starting up yet.
+#[derive(Debug)]
+pub struct StartupMarker {
+    /// `None` if the cache directory is unknown.
+    path: Option<PathBuf>,
+    /// The number of launches preceding this one which died during startup.
+    failed_startups: u32,
+    completed: AtomicBool,
+}
+
+impl StartupMarker {
+    /// Reads the marker left over by the previous launch, if any, and writes the marker of this
+    /// launch.
+    pub fn begin() -> Self {
+        let path = match directory() {
+            Some(directory) => directory.join(MARKER_FILE_NAME),
+            None => return Self { path: None, failed_startups: 0, completed: AtomicBool::new(false) },
+        };
+        let failed_startups = match fs::read_to_string(&path) {
+            // The marker stores the number of failed startups preceding the launch that wrote it.
+            Ok(source) => source.trim().parse::<u32>().unwrap_or(0).saturating_add(1),
+            Err(_) => 0,
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, failed_startups.to_string()));
+
+        if let Err(error) = written {
+            warn!(Category::Ui, "Could not write the startup marker `{}`: {}", path.display(), error);
+        }
+
+        Self { path: Some(path), failed_startups, completed: AtomicBool::new(false) }
+    }
+
+    pub fn failed_startups(&self) -> u32 {
+        self.failed_startups
+    }
+
+    pub fn is_safe_mode(&self) -> bool {
+        self.failed_startups >= MAX_FAILED_STARTUPS
+    }
+
+    /// Removes the marker, so that the next launch is not considered to follow a failed startup.
+    /// Only the first invocation has an effect.
+    pub fn complete(&self) {
+        if self.completed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        if let Some(path) = self.path.as_ref() {
+            match fs::remove_file(path) {
+                Ok(()) => (),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+                Err(error) => {
+                    warn!(Category::Ui, "Could not remove the startup marker `{}`: {}", path.display(), error)
+                }
+            }
+        }
+    }
+}
+
+/// Which nodes are replaced by placeholders when graphs are loaded.
+#[derive(Debug, Clone, Default)]
+pub struct Substitutions {
+    /// Whether the nodes of all external behaviours are replaced.
+    pub safe_mode: bool,
+    /// The names of the behaviours replaced regardless of safe mode, see
+    /// [`Config::disabled_behaviours`](crate::config::Config::disabled_behaviours).
+    pub disabled_behaviours: BTreeSet<String>,
+}
+
+impl Substitutions {
+    /// Why the node of the behaviour is to be replaced, `None` if it is kept.
+    pub fn reason(&self, behaviour: &dyn NodeBehaviourContainer) -> Option<PlaceholderReason> {
+        if self.disabled_behaviours.contains(behaviour.name()) {
+            Some(PlaceholderReason::Disabled)
+        } else if self.safe_mode && behaviour.is_external() {
+            Some(PlaceholderReason::SafeMode)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SafeModeMessage {
+    /// Leaves safe mode, restoring the nodes replaced by placeholders, other than the disabled
+    /// ones.
+    RetryNormalMode,
+    /// Keeps replacing the nodes of the behaviour with the name by placeholders, even outside of
+    /// safe mode.
+    DisableBehaviour(String),
+}
+
+/// The banner displayed above the graph while in safe mode.
+#[derive(Debug)]
+pub struct SafeModeBanner {
+    failed_startups: u32,
+    retry_state: button::State,
+    disable_states: Vec<button::State>,
+}
+
+impl SafeModeBanner {
+    pub fn new(failed_startups: u32) -> Self {
+        Self { failed_startups, retry_state: Default::default(), disable_states: Vec::new() }
+    }
+
+    /// `replaced_behaviours` are the names of the behaviours of the nodes replaced by placeholders
+    /// in the open documents, each offered to be disabled, unless it is among the
+    /// `disabled_behaviours` already.
+    pub fn view(
+        &mut self,
+        replaced_behaviours: &BTreeSet<String>,
+        disabled_behaviours: &BTreeSet<String>,
+        theme: &dyn Theme,
+    ) -> Element<SafeModeMessage> {
+        struct StyleSheet(container::Style, Color);
+
+        impl container::StyleSheet for StyleSheet {
+            fn style(&self) -> container::Style {
+                container::Style { border_color: self.1, border_width: 2.0, ..self.0 }
+            }
+        }
+
+        let description = format!(
+            "Safe mode: DVSynth did not finish starting up {} times in a row. Nodes using the GPU are \
+             disabled, their settings and connections are kept until normal mode is restored. The kinds \
+             of nodes disabled permanently stay disabled in normal mode, until they are enabled in \
+             the preferences.",
+            self.failed_startups
+        );
+        let row = Row::new()
+            .theme(theme)
+            .push(Text::new(description).size(consts::TEXT_SIZE_REGULAR).width(Length::Fill))
+            .push(
+                Button::new(&mut self.retry_state, Text::new("Retry normal mode"))
+                    .on_press(SafeModeMessage::RetryNormalMode),
+            )
+            .align_items(Align::Center)
+            .width(Length::Fill);
+        let mut column = Column::new().theme(theme).push(row).width(Length::Fill);
+
+        self.disable_states.resize_with(replaced_behaviours.len(), Default::default);
+
+        for (name, disable_state) in replaced_behaviours.iter().zip(&mut self.disable_states) {
+            let disabled = disabled_behaviours.contains(name);
+            let mut disable = Button::new(
+                disable_state,
+                Text::new(if disabled { "Disabled" } else { "Disable permanently" }),
+            );
+
+            // Buttons without a message are displayed as disabled.
+            if !disabled {
+                disable = disable.on_press(SafeModeMessage::DisableBehaviour(name.clone()));
+            }
+
+            column = column.push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        Text::new(format!("`{}`", name)).size(consts::TEXT_SIZE_REGULAR).width(Length::Fill),
+                    )
+                    .push(disable)
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+        }
+
+        Container::new(column)
+            .style(StyleSheet(theme.container().style(), theme.status_colors().error))
+            .padding(consts::SPACING_VERTICAL)
+            .width(Length::Fill)
+            .into()
+    }
+}
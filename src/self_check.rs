@@ -0,0 +1,313 @@
+//! Checks of the integrity of the application, which catch misconfigured registries and settings
+//! early. Run by the `--check` command line flag, which prints the findings and exits, or from the
+//! preferences, which log the findings instead.
+//!
+//! A test graph is only executed by the `--check` flag, before the executor is spawned, because
+//! the allocator is shared with the executor, which would be disturbed by another schedule.
+
+use crate::config::{Config, SharedConfig};
+use crate::graph::{ApplicationContext, ExecutionGraph, NodeData, PreparedExecution, Renderer};
+use crate::logging::{Category, Level};
+use crate::node::behaviour::{all_behaviours, ConstantNodeBehaviour, DebugNodeBehaviour};
+use crate::node::{ChannelPassBy, PrimitiveTypeEnum, TypeEnum, TypeExt};
+use crate::template::GraphBuilder;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// The number of passes the test graph is executed for.
+const TEST_PASSES: usize = 3;
+const PLUGIN_EXTENSIONS: [&str; 3] = ["so", "dylib", "dll"];
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    /// [`Level::Error`], [`Level::Warn`] or [`Level::Info`].
+    pub level: Level,
+    pub category: Category,
+    pub message: String,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}] {}", self.level, self.category, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SelfCheck {
+    findings: Vec<Finding>,
+}
+
+impl SelfCheck {
+    /// Runs all checks. The test graph is only executed with a `renderer`, see the module
+    /// documentation.
+    pub fn run(config: &Config, renderer: Option<Renderer>) -> Self {
+        let mut check = Self::default();
+
+        check.check_behaviours();
+        check.check_types();
+        check.check_graph(config, renderer);
+        check.check_config();
+        check.check_plugins(config);
+        check
+    }
+
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.findings.iter().any(|finding| finding.level == Level::Error)
+    }
+
+    /// Records the findings in the log, subject to the log level filters.
+    pub fn log(&self) {
+        for finding in &self.findings {
+            log!(finding.category, finding.level, "Self-check: {}", finding.message);
+        }
+
+        let errors = self.count(Level::Error);
+        let warnings = self.count(Level::Warn);
+
+        if errors > 0 {
+            error!(Category::Ui, "Self-check finished with {} errors and {} warnings.", errors, warnings);
+        } else if warnings > 0 {
+            warn!(Category::Ui, "Self-check finished with {} warnings.", warnings);
+        } else {
+            info!(Category::Ui, "Self-check passed.");
+        }
+    }
+
+    fn count(&self, level: Level) -> usize {
+        self.findings.iter().filter(|finding| finding.level == level).count()
+    }
+
+    fn report(&mut self, level: Level, category: Category, message: impl ToString) {
+        self.findings.push(Finding { level, category, message: message.to_string() });
+    }
+
+    /// Every behaviour must have a unique name, so that [`create_behaviour`] finds it, and a node
+    /// must be constructible from it.
+    ///
+    /// [`create_behaviour`]: crate::node::behaviour::create_behaviour
+    fn check_behaviours(&mut self) {
+        let behaviours = match catch_panic(all_behaviours) {
+            Ok(behaviours) => behaviours,
+            Err(message) => {
+                return self.report(
+                    Level::Error,
+                    Category::Ui,
+                    format!("Constructing the behaviours panicked: {}", message),
+                );
+            }
+        };
+        let mut names = HashSet::new();
+
+        for behaviour in &behaviours {
+            if !names.insert(behaviour.name().to_string()) {
+                self.report(
+                    Level::Error,
+                    Category::Ui,
+                    format!(
+                        "Several behaviours are named `{}`, only the first one can be created.",
+                        behaviour.name()
+                    ),
+                );
+            }
+        }
+
+        for behaviour in behaviours {
+            let name = behaviour.name().to_string();
+
+            if let Err(message) =
+                catch_panic(AssertUnwindSafe(move || drop(NodeData::new(&name, [0.0, 0.0], behaviour))))
+            {
+                self.report(Level::Error, Category::Ui, format!("Creating a node panicked: {}", message));
+            }
+        }
+
+        self.report(Level::Info, Category::Ui, format!("{} behaviours are registered.", names.len()));
+    }
+
+    /// Every type must be ABI compatible with itself, or values of it could not be passed between
+    /// channels of the same type.
+    fn check_types(&mut self) {
+        for ty in TypeEnum::examples() {
+            if !ty.is_abi_compatible(&ty.clone()) {
+                self.report(
+                    Level::Error,
+                    Category::Scheduler,
+                    format!("`{}` is not ABI compatible with itself.", ty),
+                );
+            }
+        }
+    }
+
+    /// Schedules a constant connected to a debug node and executes it.
+    fn check_graph(&mut self, config: &Config, renderer: Option<Renderer>) {
+        let mut builder = GraphBuilder::default();
+        let constant = builder.node("Constant", [10.0, 10.0], ConstantNodeBehaviour::new(1.0_f32));
+        let debug = builder.node("Debug", [260.0, 10.0], DebugNodeBehaviour::new(PrimitiveTypeEnum::F32));
+
+        builder.connect((constant, 0), (debug, ChannelPassBy::SharedReference, 0));
+
+        let mut graph: ExecutionGraph = builder.build().into();
+
+        if graph.get_connections().len() != 1 {
+            return self.report(Level::Error, Category::Scheduler, "The test graph could not be connected.");
+        }
+
+        if let Err(errors) = graph.update_schedule() {
+            for error in errors {
+                self.report(
+                    Level::Error,
+                    Category::Scheduler,
+                    format!("The test graph could not be scheduled: {}", error.display()),
+                );
+            }
+
+            return;
+        }
+
+        let renderer = if let Some(renderer) = renderer {
+            renderer
+        } else {
+            return self.report(
+                Level::Info,
+                Category::Executor,
+                "The test graph was scheduled, but not executed, as the executor is running.",
+            );
+        };
+        let schedule = graph.active_schedule.load_full().unwrap();
+        let mut context =
+            ApplicationContext::headless(Some(renderer), Arc::new(SharedConfig::new(config.clone())));
+        let result = catch_panic(AssertUnwindSafe(|| {
+            let mut prepared_execution = PreparedExecution::from(&schedule, &mut context, None);
+
+            for _ in 0..TEST_PASSES {
+                prepared_execution.execute(&schedule, &mut context, &mut None);
+            }
+        }));
+
+        if let Err(message) = result {
+            return self.report(
+                Level::Error,
+                Category::Executor,
+                format!("Executing the test graph panicked: {}", message),
+            );
+        }
+
+        for (node, error) in context.node_errors.get_all() {
+            self.report(
+                Level::Error,
+                Category::Executor,
+                format!("Node #{} of the test graph failed: {}", node.index(), error.message),
+            );
+        }
+    }
+
+    fn check_config(&mut self) {
+        let path = if let Some(path) = Config::path() {
+            path
+        } else {
+            return self.report(Level::Warn, Category::Ui, "The settings directory could not be determined.");
+        };
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return,
+            Err(error) => {
+                return self.report(
+                    Level::Error,
+                    Category::Ui,
+                    format!("Could not read `{}`: {}", path.display(), error),
+                );
+            }
+        };
+
+        match Config::validate(&source) {
+            Ok(problems) => {
+                for problem in problems {
+                    self.report(Level::Warn, Category::Ui, format!("`{}`: {}", path.display(), problem));
+                }
+            }
+            Err(error) => self.report(Level::Error, Category::Ui, format!("`{}`: {}", path.display(), error)),
+        }
+    }
+
+    /// Plugins are not loaded yet, so the libraries in the plugin directory are only listed.
+    fn check_plugins(&mut self, config: &Config) {
+        let directory = if let Some(directory) = config.plugin_directory.as_ref() {
+            directory
+        } else {
+            return;
+        };
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(error) => {
+                return self.report(
+                    Level::Warn,
+                    Category::Plugin,
+                    format!("Could not read the plugin directory `{}`: {}", directory.display(), error),
+                );
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_library = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map_or(false, |extension| PLUGIN_EXTENSIONS.contains(&extension));
+
+            if is_library {
+                self.report(
+                    Level::Info,
+                    Category::Plugin,
+                    format!(
+                        "`{}` is not loaded, plugins are not supported yet, so its ABI is not checked.",
+                        path.display()
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Runs the closure, converting a panic into its message.
+fn catch_panic<T>(f: impl FnOnce() -> T + panic::UnwindSafe) -> Result<T, String> {
+    panic::catch_unwind(f).map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string())
+    })
+}
+
+/// Runs the checks for the `--check` command line flag, printing the findings. Returns the exit
+/// code, which is non-zero if any errors were found.
+pub fn run_from_command_line() -> i32 {
+    let config = Config::load();
+    let renderer = match catch_panic(AssertUnwindSafe(|| Renderer::new(&config))) {
+        Ok(renderer) => Some(renderer),
+        Err(message) => {
+            println!("{} [{}] Could not set up the renderer: {}", Level::Error, Category::Executor, message);
+            None
+        }
+    };
+    let renderer_missing = renderer.is_none();
+    let check = SelfCheck::run(&config, renderer);
+
+    for finding in check.findings() {
+        println!("{}", finding);
+    }
+
+    if check.has_errors() || renderer_missing {
+        1
+    } else {
+        0
+    }
+}
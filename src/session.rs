@@ -0,0 +1,534 @@
+//! Recording of the messages processed by the editor into session logs, so that the bugs reported
+//! along with them can be reproduced, and their replay.
+//!
+//! A session log is a JSON Lines file: a [`SessionHeader`] identifying the graph the session
+//! started with, followed by one [`SessionEntry`] per line. Messages are recorded before they are
+//! processed, followed by the results of the graph commands they applied. Messages whose payload
+//! cannot be serialized, such as the messages of node behaviours, are recorded as opaque markers,
+//! which the replay skips with a warning. Messages caused by the environment rather than by the
+//! user, such as the statistics samples, are not recorded at all. The raw mouse and keyboard
+//! events are recorded as well, to tell the gestures leading to a bug, but they are not replayed.
+//!
+//! Sessions are recorded while [`Config::record_sessions`](crate::config::Config::record_sessions)
+//! is enabled and replayed with the `--replay-session <path>` command-line argument.
+
+use crate::graph::file::{GraphFile, GraphMetadata};
+use crate::graph::Graph;
+use crate::logging::Category;
+use crate::widgets::floating_panes::LayoutChange;
+use crate::Message;
+use iced_winit::winit::event::{ElementState, MouseScrollDelta, WindowEvent};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const FORMAT_VERSION: u32 = 1;
+/// How many times faster than recorded the messages are replayed.
+const REPLAY_SPEEDUP: u32 = 10;
+/// The longest pause between two replayed messages, so that idle periods are skipped.
+const MAX_REPLAY_DELAY: Duration = Duration::from_millis(500);
+
+fn directory() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("dvsynth").join("sessions"))
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+/// Hashes the nodes and the connections of the graph with FNV-1a, like the contents of library
+/// entries, to tell whether a replay started from and arrived at the recorded graph.
+pub fn graph_hash(graph: &Graph) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let file = GraphFile::from_graph(graph, &GraphMetadata::default());
+    let content = serde_json::json!({ "nodes": file.nodes, "edges": file.edges });
+    let hash =
+        content.to_string().bytes().fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME));
+
+    format!("{:016x}", hash)
+}
+
+/// Whether the message is caused by the environment rather than by the user, and is therefore
+/// left out of session logs. Key presses are recorded as the messages of their actions.
+fn is_environmental(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::ValueSnapshotTaken(_)
+            | Message::ExecutingGenerationChanged
+            | Message::MainThreadTasksSubmitted
+            | Message::SampleStatistics
+            | Message::TaskStalled(_)
+            | Message::KeyPressed { .. }
+            | Message::LayoutChange(LayoutChange::Update)
+            | Message::LayoutChange(LayoutChange::CollapsedPaneHovered(_))
+            | Message::PreviewCollapsedPane { .. }
+            | Message::ReplaySessionStep
+    )
+}
+
+/// Whether the message is fed back into the application by the replay. Messages opening file
+/// dialogs, writing files or exiting are skipped, as they would interrupt the replay.
+fn is_replayable(message: &Message) -> bool {
+    !matches!(
+        message,
+        Message::OpenGraph
+            | Message::SaveGraph
+            | Message::SaveSelectionToLibrary
+            | Message::ExportGraphImage
+            | Message::ExportStatistics
+            | Message::ToggleRecording
+            | Message::ToggleReplay
+            | Message::CloseRequested
+    )
+}
+
+/// The first line of a session log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHeader {
+    pub version: u32,
+    pub dvsynth_version: String,
+    /// Seconds since the Unix epoch.
+    pub started: u64,
+    /// The file the focused graph was opened from, if any.
+    pub graph_path: Option<PathBuf>,
+    /// The [`graph_hash`] of the focused graph when the recording started.
+    pub graph_hash: String,
+}
+
+/// A mouse or keyboard event received by any window of the application.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum InputEvent {
+    CursorMoved { x: f64, y: f64 },
+    MouseButton { button: String, pressed: bool },
+    MouseWheel { x: f64, y: f64, lines: bool },
+    Key { key: Option<String>, pressed: bool },
+    Modifiers { shift: bool, control: bool, alt: bool, logo: bool },
+}
+
+impl InputEvent {
+    fn from_window_event(event: &WindowEvent<'_>) -> Option<Self> {
+        let pressed = |state: &ElementState| *state == ElementState::Pressed;
+
+        Some(match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                InputEvent::CursorMoved { x: position.x, y: position.y }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                InputEvent::MouseButton { button: format!("{:?}", button), pressed: pressed(state) }
+            }
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(x, y) => {
+                    InputEvent::MouseWheel { x: *x as f64, y: *y as f64, lines: true }
+                }
+                MouseScrollDelta::PixelDelta(position) => {
+                    InputEvent::MouseWheel { x: position.x, y: position.y, lines: false }
+                }
+            },
+            WindowEvent::KeyboardInput { input, .. } => InputEvent::Key {
+                key: input.virtual_keycode.map(|key| format!("{:?}", key)),
+                pressed: pressed(&input.state),
+            },
+            WindowEvent::ModifiersChanged(modifiers) => InputEvent::Modifiers {
+                shift: modifiers.shift(),
+                control: modifiers.ctrl(),
+                alt: modifiers.alt(),
+                logo: modifiers.logo(),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// A line of a session log following the header. Times are in seconds since the recording
+/// started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionEntry {
+    Message {
+        time: f64,
+        message: Message,
+    },
+    /// A message which could not be serialized, described by its debug representation.
+    Opaque {
+        time: f64,
+        description: String,
+    },
+    /// The result of a graph command applied by the preceding message, with the error it failed
+    /// with, if any.
+    Command {
+        error: Option<String>,
+    },
+    Input {
+        time: f64,
+        #[serde(flatten)]
+        event: InputEvent,
+    },
+    /// Written when the recording is stopped, including when the application exits.
+    End {
+        time: f64,
+        graph_hash: String,
+    },
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The file is not a session log or has been recorded by an incompatible version.
+    InvalidFormat,
+}
+
+impl From<io::Error> for SessionError {
+    fn from(error: io::Error) -> Self {
+        SessionError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SessionError {
+    fn from(error: serde_json::Error) -> Self {
+        SessionError::Json(error)
+    }
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::Io(error) => write!(f, "I/O error: {}", error),
+            SessionError::Json(error) => write!(f, "Invalid session entry: {}", error),
+            SessionError::InvalidFormat => write!(f, "The file is not a valid session log."),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SessionLog {
+    path: PathBuf,
+    started: Instant,
+    writer: BufWriter<File>,
+}
+
+/// Writes the session log while recording. Shared with the event handler of the event loop, which
+/// records the raw input events.
+#[derive(Debug, Default)]
+pub struct SessionRecorder {
+    log: Mutex<Option<SessionLog>>,
+}
+
+impl SessionRecorder {
+    pub fn is_recording(&self) -> bool {
+        self.log.lock().unwrap().is_some()
+    }
+
+    /// Starts recording into a new file in the cache directory, returning its path.
+    pub fn start(&self, graph: &Graph, graph_path: Option<&Path>) -> Result<PathBuf, SessionError> {
+        let directory = directory()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the cache directory is unknown"))?;
+
+        fs::create_dir_all(&directory)?;
+
+        let started = unix_time();
+        let path = directory.join(format!("session-{}.jsonl", started));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        let header = SessionHeader {
+            version: FORMAT_VERSION,
+            dvsynth_version: env!("CARGO_PKG_VERSION").to_string(),
+            started,
+            graph_path: graph_path.map(Path::to_path_buf),
+            graph_hash: graph_hash(graph),
+        };
+
+        serde_json::to_writer(&mut writer, &header)?;
+        writeln!(writer)?;
+        writer.flush()?;
+
+        *self.log.lock().unwrap() = Some(SessionLog { path: path.clone(), started: Instant::now(), writer });
+        Ok(path)
+    }
+
+    /// Stops recording, ending the log with the hash of the graph the session arrived at.
+    pub fn stop(&self, graph: &Graph) {
+        self.write(|time| Some(SessionEntry::End { time, graph_hash: graph_hash(graph) }), true);
+        *self.log.lock().unwrap() = None;
+    }
+
+    /// Records the message about to be processed, unless it is caused by the environment.
+    pub fn record_message(&self, message: &Message) {
+        if is_environmental(message) {
+            return;
+        }
+
+        self.write(|time| Some(SessionEntry::Message { time, message: message.clone() }), true);
+    }
+
+    /// Records the result of a graph command applied by the message being processed.
+    pub fn record_command(&self, error: Option<&dyn Display>) {
+        self.write(|_| Some(SessionEntry::Command { error: error.map(ToString::to_string) }), false);
+    }
+
+    pub fn record_input(&self, event: &WindowEvent<'_>) {
+        self.write(
+            |time| Some(SessionEntry::Input { time, event: InputEvent::from_window_event(event)? }),
+            false,
+        );
+    }
+
+    /// Writes the entry created for the time since the recording started, if recording. Messages
+    /// are flushed right away, so that the log is complete even if processing them crashes.
+    fn write(&self, entry: impl FnOnce(f64) -> Option<SessionEntry>, flush: bool) {
+        let mut log = self.log.lock().unwrap();
+        let session_log = match log.as_mut() {
+            Some(session_log) => session_log,
+            None => return,
+        };
+        let time = session_log.started.elapsed().as_secs_f64();
+        let entry = match entry(time) {
+            Some(entry) => entry,
+            None => return,
+        };
+        let line = serde_json::to_string(&entry).or_else(|_| match entry {
+            SessionEntry::Message { time, message } => {
+                serde_json::to_string(&SessionEntry::Opaque { time, description: format!("{:?}", message) })
+            }
+            _ => unreachable!("only messages may fail to serialize"),
+        });
+        let result = line.map_err(SessionError::from).and_then(|line| {
+            writeln!(session_log.writer, "{}", line)?;
+
+            if flush {
+                session_log.writer.flush()?;
+            }
+
+            Ok(())
+        });
+
+        if let Err(error) = result {
+            error!(
+                Category::Ui,
+                "Stopping the recording of the session into `{}`: {}",
+                session_log.path.display(),
+                error
+            );
+            *log = None;
+        }
+    }
+}
+
+/// A recorded message along with the results of the graph commands it applied.
+#[derive(Debug, Clone)]
+pub struct ReplayStep {
+    /// The pause before the message, shortened by the speedup of the replay.
+    pub delay: Duration,
+    /// `None` for opaque messages, which are skipped.
+    pub message: Option<Message>,
+    pub description: String,
+    pub commands: Vec<Option<String>>,
+}
+
+/// Feeds the messages of a session log back into the application, reporting where the replay
+/// diverges from the recording.
+#[derive(Debug)]
+pub struct SessionReplay {
+    pub path: PathBuf,
+    pub header: SessionHeader,
+    steps: Vec<ReplayStep>,
+    delays: Arc<[Duration]>,
+    position: usize,
+    /// The hash of the graph the recorded session ended with, `None` if the recording did not
+    /// end properly, e.g. because the application crashed.
+    end_hash: Option<String>,
+    /// The results of the graph commands applied by the message being replayed.
+    commands: Vec<Option<String>>,
+    divergences: usize,
+}
+
+impl SessionReplay {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SessionError> {
+        let path = path.as_ref();
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header: SessionHeader = serde_json::from_str(&lines.next().ok_or(SessionError::InvalidFormat)??)
+            .map_err(|_| SessionError::InvalidFormat)?;
+
+        if header.version != FORMAT_VERSION {
+            return Err(SessionError::InvalidFormat);
+        }
+
+        let mut steps = Vec::<ReplayStep>::new();
+        let mut end_hash = None;
+        let mut previous_time = 0.0;
+        let mut step = |time: f64, message: Option<Message>, description: String| {
+            let delay = Duration::from_secs_f64((time - previous_time).max(0.0) / REPLAY_SPEEDUP as f64);
+
+            previous_time = time;
+            ReplayStep { delay: delay.min(MAX_REPLAY_DELAY), message, description, commands: Vec::new() }
+        };
+
+        for line in lines {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<SessionEntry>(&line)? {
+                SessionEntry::Message { time, message } => {
+                    let description = format!("{:?}", message);
+
+                    steps.push(step(time, Some(message), description));
+                }
+                SessionEntry::Opaque { time, description } => steps.push(step(time, None, description)),
+                SessionEntry::Command { error } => {
+                    if let Some(step) = steps.last_mut() {
+                        step.commands.push(error);
+                    }
+                }
+                SessionEntry::Input { .. } => (),
+                SessionEntry::End { graph_hash, .. } => end_hash = Some(graph_hash),
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            header,
+            delays: steps.iter().map(|step| step.delay).collect::<Vec<_>>().into(),
+            steps,
+            position: 0,
+            end_hash,
+            commands: Vec::new(),
+            divergences: 0,
+        })
+    }
+
+    /// The pauses before the steps, for the subscription driving the replay.
+    pub fn delays(&self) -> Arc<[Duration]> {
+        self.delays.clone()
+    }
+
+    /// Announces the replay, warning if the graph it starts from is not the one the recording
+    /// started from.
+    pub fn start(&mut self, graph: &Graph) {
+        let hash = graph_hash(graph);
+
+        if hash != self.header.graph_hash {
+            self.divergences += 1;
+            warn!(
+                Category::Ui,
+                "The session was recorded starting from a different graph ({}) than the replay ({}).",
+                self.header.graph_hash,
+                hash
+            );
+        }
+
+        info!(
+            Category::Ui,
+            "Replaying {} messages of the session `{}`, {} times faster than recorded.",
+            self.steps.len(),
+            self.path.display(),
+            REPLAY_SPEEDUP
+        );
+    }
+
+    /// Takes the next step to replay, `None` once all of them have been replayed. The message of
+    /// the step is `None` if it is to be skipped.
+    pub fn next_step(&mut self) -> Option<ReplayStep> {
+        let mut step = self.steps.get(self.position).cloned()?;
+
+        self.position += 1;
+        self.commands.clear();
+
+        match &step.message {
+            None => warn!(
+                Category::Ui,
+                "Skipping the message #{} of the session, which could not be recorded: {}",
+                self.position,
+                step.description
+            ),
+            Some(message) if !is_replayable(message) => {
+                warn!(
+                    Category::Ui,
+                    "Skipping the message #{} of the session, which would interrupt the replay: {}",
+                    self.position,
+                    step.description
+                );
+                step.message = None;
+            }
+            Some(_) => (),
+        }
+
+        Some(step)
+    }
+
+    /// Whether all steps have been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.steps.len()
+    }
+
+    pub fn record_command(&mut self, error: Option<&dyn Display>) {
+        self.commands.push(error.map(ToString::to_string));
+    }
+
+    /// Compares the results of the graph commands applied by the replayed step with the recorded
+    /// ones.
+    pub fn check_step(&mut self, step: &ReplayStep) {
+        if step.message.is_none() || self.commands == step.commands {
+            return;
+        }
+
+        self.divergences += 1;
+
+        let describe = |commands: &[Option<String>]| {
+            let failures = commands.iter().flatten().map(String::as_str).collect::<Vec<_>>();
+
+            if failures.is_empty() {
+                format!("{} commands succeeded", commands.len())
+            } else {
+                format!("{} of {} commands failed: {}", failures.len(), commands.len(), failures.join("; "))
+            }
+        };
+
+        warn!(
+            Category::Ui,
+            "The replay diverges at message #{} ({}): {} during the recording, but {} during the replay.",
+            self.position,
+            step.description,
+            describe(&step.commands),
+            describe(&self.commands)
+        );
+    }
+
+    /// Reports the outcome of the replay, once all steps have been replayed.
+    pub fn finish(&mut self, graph: &Graph) {
+        let hash = graph_hash(graph);
+
+        match &self.end_hash {
+            Some(end_hash) if *end_hash != hash => {
+                self.divergences += 1;
+                warn!(
+                    Category::Ui,
+                    "The replay of the session arrived at a different graph ({}) than the recording ({}).",
+                    hash,
+                    end_hash
+                );
+            }
+            Some(_) => (),
+            None => warn!(
+                Category::Ui,
+                "The recording of the session did not end properly, the final graph cannot be compared."
+            ),
+        }
+
+        info!(
+            Category::Ui,
+            "Replayed {} messages of the session `{}`, {} divergences.",
+            self.steps.len(),
+            self.path.display(),
+            self.divergences
+        );
+    }
+}
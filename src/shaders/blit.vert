@@ -0,0 +1,13 @@
+#version 450
+
+layout(location = 0) out vec2 v_uv;
+
+// Draws a single triangle covering the whole viewport, when invoked with 3 vertices.
+void main() {
+    int x = (gl_VertexIndex << 1) & 2;
+    int y = gl_VertexIndex & 2;
+
+    // Texture coordinates point down, unlike the clip space coordinates.
+    v_uv = vec2(x, 1.0 - y);
+    gl_Position = vec4(x * 2.0 - 1.0, y * 2.0 - 1.0, 0.0, 1.0);
+}
@@ -0,0 +1,23 @@
+#version 450
+
+layout(location = 0) out vec2 v_uv;
+
+// The rows of the affine transform from the texture coordinates of the render target to the
+// texture coordinates to sample at, in the first 3 components of each row.
+layout(set = 0, binding = 2) uniform Transform {
+    vec4 u_row_x;
+    vec4 u_row_y;
+};
+
+// Draws a single triangle covering the whole viewport, when invoked with 3 vertices.
+void main() {
+    int x = (gl_VertexIndex << 1) & 2;
+    int y = gl_VertexIndex & 2;
+
+    // Texture coordinates point down, unlike the clip space coordinates.
+    // Affine transforms of them are interpolated exactly across the triangle.
+    vec3 uv = vec3(x, 1.0 - y, 1.0);
+
+    v_uv = vec2(dot(u_row_x.xyz, uv), dot(u_row_y.xyz, uv));
+    gl_Position = vec4(x * 2.0 - 1.0, y * 2.0 - 1.0, 0.0, 1.0);
+}
@@ -0,0 +1,131 @@
+use crate::keymap::{Action, Keymap};
+use crate::style::{consts, Theme, Themeable};
+use crate::template::Template;
+use iced::{button, Align, Button, Column, Container, Element, Length, Row, Text};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub enum StartScreenMessage {
+    OpenRecent(PathBuf),
+    OpenTemplate(Template),
+    /// Picks the graph to open with a file dialog.
+    Browse,
+    Close,
+}
+
+/// The state of the overlay listing the recently opened graphs and the templates to start a new
+/// graph from.
+#[derive(Debug, Default)]
+pub struct StartScreen {
+    pub open: bool,
+    recent_states: Vec<button::State>,
+    empty_state: button::State,
+    example_states: [button::State; Template::EXAMPLES.len()],
+    browse_state: button::State,
+    close_state: button::State,
+}
+
+impl StartScreen {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Recent graphs whose files no longer exist are displayed, but cannot be opened.
+    pub fn view(
+        &mut self,
+        recent_graphs: &[PathBuf],
+        keymap: &Keymap,
+        theme: &dyn Theme,
+    ) -> Element<StartScreenMessage> {
+        fn label(path: &Path) -> String {
+            let file_name = path.file_name().map(|file_name| file_name.to_string_lossy()).unwrap_or_default();
+
+            match path.parent() {
+                Some(parent) => format!("{} — {}", file_name, parent.display()),
+                None => file_name.into_owned(),
+            }
+        }
+
+        let mut column = Column::new()
+            .theme(theme)
+            .push(Text::new("DVSynth").size(consts::TEXT_SIZE_TITLE))
+            .push(Text::new("Recent graphs").size(consts::TEXT_SIZE_TITLE));
+
+        if recent_graphs.is_empty() {
+            column =
+                column.push(Text::new("No graphs have been opened yet.").size(consts::TEXT_SIZE_REGULAR));
+        }
+
+        self.recent_states.resize_with(recent_graphs.len(), Default::default);
+
+        for (path, state) in recent_graphs.iter().zip(self.recent_states.iter_mut()) {
+            let mut button = Button::new(state, Text::new(label(path)).size(consts::TEXT_SIZE_REGULAR))
+                .width(Length::Fill);
+
+            // Buttons without a message are displayed as disabled.
+            if path.exists() {
+                button = button.on_press(StartScreenMessage::OpenRecent(path.clone()));
+            }
+
+            column = column.push(button);
+        }
+
+        column = column.push(Text::new("New graph").size(consts::TEXT_SIZE_TITLE)).push(
+            Button::new(&mut self.empty_state, Text::new("New empty graph"))
+                .width(Length::Fill)
+                .on_press(StartScreenMessage::OpenTemplate(Template::Empty)),
+        );
+
+        for (template, state) in Template::EXAMPLES.iter().zip(self.example_states.iter_mut()) {
+            column = column.push(
+                Row::new()
+                    .theme(theme)
+                    .push(
+                        Button::new(state, Text::new(template.to_string()))
+                            .width(Length::FillPortion(1))
+                            .on_press(StartScreenMessage::OpenTemplate(*template)),
+                    )
+                    .push(
+                        Text::new(template.description())
+                            .size(consts::TEXT_SIZE_REGULAR)
+                            .width(Length::FillPortion(2)),
+                    )
+                    .align_items(Align::Center)
+                    .width(Length::Fill),
+            );
+        }
+
+        column = column.push(
+            Row::new()
+                .theme(theme)
+                .push(
+                    Button::new(&mut self.browse_state, Text::new("Open…"))
+                        .width(Length::Fill)
+                        .on_press(StartScreenMessage::Browse),
+                )
+                .push(
+                    Button::new(&mut self.close_state, Text::new("Close"))
+                        .width(Length::Fill)
+                        .on_press(StartScreenMessage::Close),
+                )
+                .width(Length::Fill),
+        );
+
+        if let Some(shortcut) = keymap.shortcut(Action::ToggleStartScreen) {
+            column = column.push(
+                Text::new(format!("Press {} to show this screen again.", shortcut))
+                    .size(consts::TEXT_SIZE_REGULAR),
+            );
+        }
+
+        let column = column.width(Length::Units(480));
+
+        Container::new(column)
+            .theme(theme)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+}
@@ -0,0 +1,71 @@
+//! Coarse timing of the startup, logged so that regressions of the time it takes for the editor
+//! to appear are measurable.
+//!
+//! The steps which may take long, such as acquiring the GPU, are timed with [`span`], and the
+//! [`Milestone`]s are logged relative to [`begin`], the first time they are reached.
+
+use crate::logging::Category;
+use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+lazy_static! {
+    static ref STARTED: Instant = Instant::now();
+}
+
+/// Points during the startup, logged once each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(usize)]
+pub enum Milestone {
+    /// The view of the editor has been built for the first time.
+    FirstLayout,
+    /// The first frame of the editor has been presented.
+    FirstFrame,
+    /// The executor has completed its first pass over a schedule, or there has been nothing to
+    /// execute at launch.
+    FirstExecution,
+}
+
+impl Milestone {
+    fn description(self) -> &'static str {
+        match self {
+            Milestone::FirstLayout => "first layout",
+            Milestone::FirstFrame => "first frame presented",
+            Milestone::FirstExecution => "first execution completed",
+        }
+    }
+}
+
+static REACHED: [AtomicBool; 3] = [AtomicBool::new(false), AtomicBool::new(false), AtomicBool::new(false)];
+
+/// Starts measuring the time of the startup, to be called as early as possible.
+pub fn begin() {
+    lazy_static::initialize(&STARTED);
+}
+
+/// Runs the step, logging how long it took.
+pub fn span<T>(category: Category, step: &str, run: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = run();
+
+    info!(category, "Startup: {} took {:.1} ms", step, start.elapsed().as_secs_f64() * 1000.0);
+    result
+}
+
+pub fn is_reached(milestone: Milestone) -> bool {
+    REACHED[milestone as usize].load(Ordering::Relaxed)
+}
+
+/// Logs the time since [`begin`], if the milestone has not been reached before.
+pub fn reach(milestone: Milestone) {
+    if REACHED[milestone as usize].swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    info!(
+        Category::Ui,
+        "Startup: {} after {:.1} ms",
+        milestone.description(),
+        STARTED.elapsed().as_secs_f64() * 1000.0
+    );
+}
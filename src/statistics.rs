@@ -0,0 +1,322 @@
+//! Execution statistics of the focused graph, and their export.
+//!
+//! The statistics are sampled from the [`ExecutionTimes`](crate::graph::ExecutionTimes) of the
+//! active schedule once per [`SAMPLE_INTERVAL`]. Each sample covers the window since the previous
+//! one, and the export writes the last sample rather than the live measurements, so that the
+//! exported values are consistent with each other.
+
+use crate::graph::alloc::{Allocator, AllocatorStatistics};
+use crate::graph::{Connection, ExecutionGraph, NodeId, NodeIndex, Schedule, TaskExecutionTimes};
+use crate::node::ChannelDirection;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The length of the windows the statistics and the throughput of connections are sampled over.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A connection of a channel, seen from the channel.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionStatistics {
+    /// The node at the other end of the connection.
+    pub node_id: NodeId,
+    /// The title of the channel at the other end of the connection.
+    pub channel: String,
+    /// The number of values consumed per second, `None` unless the throughput is displayed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelStatistics {
+    pub title: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub connections: Vec<ConnectionStatistics>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeStatistics {
+    pub id: NodeId,
+    pub title: String,
+    pub behaviour: String,
+    /// The number of executions within the window.
+    pub executions: u64,
+    /// In seconds, `None` if the node has not been executed within the window.
+    pub mean_time: Option<f64>,
+    pub min_time: Option<f64>,
+    pub max_time: Option<f64>,
+    /// The number of non-finite values replaced in the outputs within the window, while the graph
+    /// sanitizes numerics.
+    pub sanitized: u64,
+    pub inputs: Vec<ChannelStatistics>,
+    pub outputs: Vec<ChannelStatistics>,
+}
+
+impl NodeStatistics {
+    /// The number of values consumed per second through the inputs, zero unless the throughput is
+    /// displayed.
+    pub fn input_throughput(&self) -> f64 {
+        self.inputs
+            .iter()
+            .flat_map(|channel| &channel.connections)
+            .filter_map(|connection| connection.throughput)
+            .sum()
+    }
+}
+
+/// The statistics of a single window.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionStatistics {
+    /// The generation of the schedule executed within the window.
+    pub generation: usize,
+    /// The length of the window, in seconds.
+    pub window: f64,
+    /// The number of passes finished within the window.
+    pub passes: u64,
+    /// Passes per second.
+    pub pass_rate: f64,
+    /// Whether the throughput of connections was measured.
+    pub throughput: bool,
+    /// The allocator counters at the end of the window.
+    pub allocator: AllocatorStatistics,
+    /// All nodes of the graph, ordered by their identifiers. Nodes excluded from execution are not
+    /// executed within any window.
+    pub nodes: Vec<NodeStatistics>,
+}
+
+impl ExecutionStatistics {
+    /// Takes the measured window of the schedule. The `throughput` of connections is included, if
+    /// it is displayed.
+    pub fn sample(
+        schedule: &Schedule,
+        graph: &ExecutionGraph,
+        throughput: Option<&HashMap<Connection, f64>>,
+    ) -> Self {
+        let window = schedule.execution_times.take_window();
+        let window_length = window.start.elapsed().as_secs_f64();
+        let times = schedule
+            .tasks
+            .iter()
+            .zip(window.tasks.iter())
+            .filter_map(|(task, times)| task.as_ref().map(|task| (task.node_index, *times)))
+            .collect::<HashMap<NodeIndex, TaskExecutionTimes>>();
+        let connections = graph.get_connections();
+        let channel_title = |node_index: NodeIndex, direction: ChannelDirection, connection: &Connection| {
+            let channel = connection.channel(direction);
+
+            graph[node_index].configuration.channel(direction, channel.into()).title.to_string()
+        };
+        let channel_statistics = |node_index: NodeIndex, direction: ChannelDirection| {
+            graph[node_index]
+                .configuration
+                .channels(direction)
+                .map(|channel| {
+                    let connections = connections
+                        .iter()
+                        .filter(|connection| {
+                            let end = connection.channel(direction);
+
+                            end.node_index == node_index && channel.edge_endpoint == end.into()
+                        })
+                        .map(|connection| {
+                            let other_end = connection.channel(direction.inverse());
+
+                            ConnectionStatistics {
+                                node_id: graph[other_end.node_index].id,
+                                channel: channel_title(other_end.node_index, direction.inverse(), connection),
+                                throughput: throughput
+                                    .map(|throughput| throughput.get(connection).copied().unwrap_or(0.0)),
+                            }
+                        })
+                        .collect();
+
+                    ChannelStatistics {
+                        title: channel.title.to_string(),
+                        ty: channel.ty.to_string(),
+                        connections,
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut nodes = graph
+            .node_indices()
+            .map(|node_index| {
+                let node = &graph[node_index];
+                let times = times.get(&node_index).copied().unwrap_or_default();
+                let executed =
+                    |duration: Duration| Some(duration.as_secs_f64()).filter(|_| times.executions > 0);
+
+                NodeStatistics {
+                    id: node.id,
+                    title: node.title.clone(),
+                    behaviour: node.behaviour.name().to_string(),
+                    executions: times.executions,
+                    mean_time: times.mean().map(|mean| mean.as_secs_f64()),
+                    min_time: executed(times.min),
+                    max_time: executed(times.max),
+                    sanitized: times.sanitized,
+                    inputs: channel_statistics(node_index, ChannelDirection::In),
+                    outputs: channel_statistics(node_index, ChannelDirection::Out),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        nodes.sort_by_key(|node| node.id);
+
+        Self {
+            generation: schedule.generation,
+            window: window_length,
+            passes: window.passes,
+            pass_rate: if window_length > 0.0 { window.passes as f64 / window_length } else { 0.0 },
+            throughput: throughput.is_some(),
+            allocator: Allocator::get().statistics(),
+            nodes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatisticsFormat {
+    /// One row per node, without the channels.
+    Csv,
+    /// The nodes along with their channels and connections.
+    Json,
+}
+
+impl StatisticsFormat {
+    /// The format selected by the extension of the path.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+
+        if extension.eq_ignore_ascii_case("csv") {
+            Some(StatisticsFormat::Csv)
+        } else if extension.eq_ignore_ascii_case("json") {
+            Some(StatisticsFormat::Json)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StatisticsExportError {
+    /// No window has been sampled for the focused graph yet.
+    NotSampled,
+    UnknownFormat,
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for StatisticsExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatisticsExportError::NotSampled => {
+                write!(f, "No statistics have been measured yet, the graph is not being executed.")
+            }
+            StatisticsExportError::UnknownFormat => {
+                write!(f, "The format is selected by the file extension, which must be `csv` or `json`.")
+            }
+            StatisticsExportError::Io(error) => write!(f, "Could not write the file: {}", error),
+            StatisticsExportError::Json(error) => write!(f, "Could not serialize the statistics: {}", error),
+        }
+    }
+}
+
+/// Describes the exported statistics.
+#[derive(Debug, Clone, Serialize)]
+struct ExportMetadata<'a> {
+    title: &'a str,
+    /// Seconds since the Unix epoch.
+    exported: u64,
+    dvsynth_version: &'a str,
+    generation: usize,
+}
+
+#[derive(Serialize)]
+struct StatisticsFile<'a> {
+    metadata: ExportMetadata<'a>,
+    statistics: &'a ExecutionStatistics,
+}
+
+/// Quotes the field, if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_number(number: Option<f64>) -> String {
+    number.map(|number| number.to_string()).unwrap_or_default()
+}
+
+fn to_csv(metadata: &ExportMetadata, statistics: &ExecutionStatistics) -> String {
+    let allocator = &statistics.allocator;
+    let mut csv = String::new();
+
+    // The metadata is prepended as comments, which spreadsheet applications can skip.
+    csv += &format!("# DVSynth {} execution statistics\n", metadata.dvsynth_version);
+    csv += &format!("# Graph: {}\n", metadata.title.replace('\n', " "));
+    csv += &format!("# Exported: {} (seconds since the Unix epoch)\n", metadata.exported);
+    csv += &format!("# Schedule generation: {}\n", metadata.generation);
+    csv += &format!(
+        "# Window: {} s, {} passes, {} passes per second\n",
+        statistics.window, statistics.passes, statistics.pass_rate
+    );
+    csv += &format!(
+        "# Allocator: {} live, {} allocated, {} deallocated, {} double frees\n",
+        allocator.live, allocator.total_allocated, allocator.total_deallocated, allocator.double_frees
+    );
+    csv += "node_id,title,behaviour,executions,mean_time,min_time,max_time,sanitized,input_throughput\n";
+
+    for node in &statistics.nodes {
+        let fields = [
+            node.id.0.to_string(),
+            csv_field(&node.title),
+            csv_field(&node.behaviour),
+            node.executions.to_string(),
+            csv_number(node.mean_time),
+            csv_number(node.min_time),
+            csv_number(node.max_time),
+            node.sanitized.to_string(),
+            csv_number(Some(node.input_throughput()).filter(|_| statistics.throughput)),
+        ];
+
+        csv += &fields.join(",");
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Writes the statistics into the file, in the format selected by its extension.
+pub fn export_statistics(
+    statistics: Option<&ExecutionStatistics>,
+    title: &str,
+    path: &Path,
+) -> Result<(), StatisticsExportError> {
+    let statistics = statistics.ok_or(StatisticsExportError::NotSampled)?;
+    let format = StatisticsFormat::from_path(path).ok_or(StatisticsExportError::UnknownFormat)?;
+    let metadata = ExportMetadata {
+        title,
+        exported: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0),
+        dvsynth_version: env!("CARGO_PKG_VERSION"),
+        generation: statistics.generation,
+    };
+    let contents = match format {
+        StatisticsFormat::Csv => to_csv(&metadata, statistics).into_bytes(),
+        StatisticsFormat::Json => serde_json::to_vec_pretty(&StatisticsFile { metadata, statistics })
+            .map_err(StatisticsExportError::Json)?,
+    };
+
+    fs::write(path, contents).map_err(StatisticsExportError::Io)
+}
@@ -1,7 +1,10 @@
 use crate::util::rgb;
-use crate::widgets::{floating_panes, node};
+use crate::widgets::{
+    color_picker, drag_value, enum_dropdown, floating_panes, gradient_bar, node, range_slider,
+};
 use crate::Spacing;
 use iced::{checkbox, container, pick_list, text_input, widget, Color};
+use serde::{Deserialize, Serialize};
 
 pub mod consts {
     use super::*;
@@ -17,6 +20,17 @@ pub trait Themeable: Sized {
     fn theme(self, theme: &dyn Theme) -> Self;
 }
 
+/// The colors signaling the state of channels and connections. Each state is also signaled by
+/// other means than color, such as the size of the connection points or the stroke pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusColors {
+    pub idle: Color,
+    pub highlight: Color,
+    /// A connection that is being created.
+    pub pending: Color,
+    pub error: Color,
+}
+
 pub trait StyleSheetProvider: std::fmt::Debug {
     fn container(&self) -> Box<dyn container::StyleSheet>;
     fn pick_list(&self) -> Box<dyn pick_list::StyleSheet>;
@@ -25,6 +39,7 @@ pub trait StyleSheetProvider: std::fmt::Debug {
     fn floating_panes(&self) -> Box<dyn floating_panes::FloatingPanesStyleSheet>;
     fn floating_pane(&self) -> Box<dyn floating_panes::FloatingPaneStyleSheet>;
     fn tooltip(&self) -> Box<dyn node::TooltipStyleSheet>;
+    fn status_colors(&self) -> StatusColors;
 }
 
 pub trait Theme: StyleSheetProvider {}
@@ -261,7 +276,7 @@ macro_rules! themes {
                                                         color.a = 0.9;
                                                         color.into()
                                                     },
-                                                    text_color: rgb(0xFF0000).into(),
+                                                    text_color: STATUS_COLORS.error.into(),
                                                     ..container::Style::default()
                                                 }
                                             }
@@ -275,12 +290,56 @@ macro_rules! themes {
 
                         Box::new(Tooltip)
                     }
+
+                    fn status_colors(&self) -> StatusColors {
+                        STATUS_COLORS
+                    }
                 }
             }
         )*
     }
 }
 
+/// A color tag that can be assigned to a node to visually group it with other nodes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+pub enum NodeColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Purple,
+    Pink,
+}
+
+impl NodeColor {
+    pub const VALUES: [NodeColor; 8] = [
+        NodeColor::Red,
+        NodeColor::Orange,
+        NodeColor::Yellow,
+        NodeColor::Green,
+        NodeColor::Cyan,
+        NodeColor::Blue,
+        NodeColor::Purple,
+        NodeColor::Pink,
+    ];
+
+    pub fn color(self) -> Color {
+        use NodeColor::*;
+        match self {
+            Red => rgb(0xc0392b),
+            Orange => rgb(0xd35400),
+            Yellow => rgb(0xc9a227),
+            Green => rgb(0x27ae60),
+            Cyan => rgb(0x16a085),
+            Blue => rgb(0x2e6fba),
+            Purple => rgb(0x8e44ad),
+            Pink => rgb(0xc2477f),
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
 pub enum InteractionStatus {
     Idle,
@@ -322,6 +381,61 @@ where
     }
 }
 
+impl<'a, T, M, R> Themeable for enum_dropdown::EnumDropdown<'a, T, M, R>
+where
+    T: 'static + Copy + Eq + std::fmt::Display,
+    R: enum_dropdown::WidgetRenderer,
+    <R as enum_dropdown::WidgetRenderer>::Style: From<Box<dyn pick_list::StyleSheet>>,
+{
+    fn theme(self, theme: &dyn Theme) -> Self {
+        self.style(theme.pick_list()).text_size(consts::TEXT_SIZE_REGULAR).padding(consts::SPACING_VERTICAL)
+    }
+}
+
+impl<'a, M, R> Themeable for drag_value::DragValue<'a, M, R>
+where
+    R: drag_value::WidgetRenderer,
+    <R as drag_value::WidgetRenderer>::Style: From<Box<dyn text_input::StyleSheet>>,
+{
+    fn theme(self, theme: &dyn Theme) -> Self {
+        self.style(theme.text_input()).text_size(consts::TEXT_SIZE_REGULAR).padding(consts::SPACING_VERTICAL)
+    }
+}
+
+impl<'a, M> Themeable for color_picker::ColorPicker<'a, M> {
+    fn theme(self, theme: &dyn Theme) -> Self {
+        self.style(color_picker::Style { marker: theme.status_colors().highlight })
+    }
+}
+
+impl<'a, M> Themeable for gradient_bar::GradientBar<'a, M> {
+    fn theme(self, theme: &dyn Theme) -> Self {
+        let status_colors = theme.status_colors();
+
+        self.style(gradient_bar::Style {
+            handle_border: status_colors.idle,
+            selected_handle_border: status_colors.highlight,
+        })
+    }
+}
+
+impl<'a, M> Themeable for range_slider::RangeSlider<'a, M> {
+    fn theme(self, theme: &dyn Theme) -> Self {
+        let status_colors = theme.status_colors();
+        let text_input = theme.text_input();
+
+        self.style(range_slider::Style {
+            track: status_colors.idle,
+            range: status_colors.highlight,
+            handle: text_input.value_color(),
+            focused_handle_border: status_colors.highlight,
+            text: text_input.value_color(),
+            invalid_text: status_colors.error,
+        })
+        .text_size(consts::TEXT_SIZE_REGULAR)
+    }
+}
+
 impl<'a, M: Clone> Themeable for text_input::TextInput<'a, M> {
     fn theme(self, theme: &dyn Theme) -> Self {
         self.style(theme.text_input()).size(consts::TEXT_SIZE_REGULAR).padding(consts::SPACING_VERTICAL)
@@ -378,6 +492,12 @@ themes! {
             rgb(0xf0efed),
             rgb(0xfefefd),
         ];
+        const STATUS_COLORS: StatusColors = StatusColors {
+            idle: rgb(0xffffff),
+            highlight: rgb(0x80ff00),
+            pending: rgb(0xff9900),
+            error: rgb(0xff0000),
+        };
     }
 
     Light, light {
@@ -394,5 +514,34 @@ themes! {
             rgb(0x191510),
             rgb(0x100c06),
         ];
+        const STATUS_COLORS: StatusColors = StatusColors {
+            idle: rgb(0x100c06),
+            highlight: rgb(0x2e8b00),
+            pending: rgb(0xd35400),
+            error: rgb(0xd00000),
+        };
+    }
+
+    // Distinguishable with the common kinds of color blindness, based on the Okabe-Ito palette.
+    HighContrast, high_contrast {
+        const COLORS: [Color; 11] = [
+            rgb(0x000000),
+            rgb(0x000000),
+            rgb(0x0d0d0d),
+            rgb(0x262626),
+            rgb(0x404040),
+            rgb(0x737373),
+            rgb(0xa6a6a6),
+            rgb(0xcccccc),
+            rgb(0xe6e6e6),
+            rgb(0xf5f5f5),
+            rgb(0xffffff),
+        ];
+        const STATUS_COLORS: StatusColors = StatusColors {
+            idle: rgb(0xffffff),
+            highlight: rgb(0x56b4e9),
+            pending: rgb(0xf0e442),
+            error: rgb(0xd55e00),
+        };
     }
 }
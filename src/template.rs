@@ -0,0 +1,234 @@
+//! Graphs to start from, constructed programmatically rather than loaded from files, so that they
+//! do not depend on the file format.
+
+use crate::graph::controller::GraphCommand;
+use crate::graph::{Connection, EdgeEndpoint, ExecutionGraph, Graph, NodeIndex};
+use crate::logging::Category;
+use crate::node::behaviour::*;
+use crate::node::{ChannelPassBy, PrimitiveTypeEnum};
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+    Empty,
+    /// A sample of the available nodes.
+    Demo,
+    /// Two constants combined by a binary operation, whose result is displayed.
+    Arithmetic,
+    /// A counter, whose count is displayed.
+    HelloCounter,
+    /// A range of numbers summed up.
+    ListProcessing,
+    /// A gradient swept by a clock and presented in a window.
+    GradientWindow,
+    /// Rendered text delayed by a texture history and presented in a window.
+    TextureHistory,
+}
+
+impl Template {
+    /// The templates listed as examples, besides the empty graph.
+    pub const EXAMPLES: [Template; 6] = [
+        Template::HelloCounter,
+        Template::Arithmetic,
+        Template::ListProcessing,
+        Template::GradientWindow,
+        Template::TextureHistory,
+        Template::Demo,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Template::Empty => "A graph without any nodes.",
+            Template::Demo => "A sample of the available nodes, left unconnected.",
+            Template::Arithmetic => "Two constants combined by a binary operation.",
+            Template::HelloCounter => "A counter incremented by every execution, with its count displayed.",
+            Template::ListProcessing => "A range of numbers summed up by a ListReduce node.",
+            Template::GradientWindow => "A clock sweeping along a gradient, which is displayed in a window.",
+            Template::TextureHistory => "Rendered text passed through a texture history into a window.",
+        }
+    }
+
+    pub fn graph(&self) -> Graph {
+        match self {
+            Template::Empty => Graph::new(),
+            Template::Demo => demo(),
+            Template::Arithmetic => arithmetic(),
+            Template::HelloCounter => hello_counter(),
+            Template::ListProcessing => list_processing(),
+            Template::GradientWindow => gradient_window(),
+            Template::TextureHistory => texture_history(),
+        }
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Template::Empty => write!(f, "Empty graph"),
+            Template::Demo => write!(f, "Demo"),
+            Template::Arithmetic => write!(f, "Arithmetic"),
+            Template::HelloCounter => write!(f, "Hello Counter"),
+            Template::ListProcessing => write!(f, "List processing"),
+            Template::GradientWindow => write!(f, "Gradient window"),
+            Template::TextureHistory => write!(f, "Texture history"),
+        }
+    }
+}
+
+/// Adds nodes and connections to a graph, so that a template is only a few lines long. The graph is
+/// edited through the same commands as the graph edited by the user.
+pub(crate) struct GraphBuilder {
+    graph: ExecutionGraph,
+}
+
+impl Default for GraphBuilder {
+    fn default() -> Self {
+        Self { graph: Graph::new().into() }
+    }
+}
+
+impl GraphBuilder {
+    pub(crate) fn node(
+        &mut self,
+        title: &str,
+        position: [f32; 2],
+        behaviour: impl NodeBehaviourContainer,
+    ) -> NodeIndex {
+        let command = GraphCommand::AddNode {
+            title: title.to_string(),
+            position: position.into(),
+            behaviour: Box::new(behaviour),
+        };
+
+        command.apply(&mut self.graph).unwrap().added_node().unwrap()
+    }
+
+    /// Connects the output with the index `from` to the channel with the index `to` within the
+    /// channels passed by the specified method.
+    pub(crate) fn connect(
+        &mut self,
+        (from_node, from): (NodeIndex, usize),
+        (to_node, to_pass_by, to): (NodeIndex, ChannelPassBy, usize),
+    ) {
+        let connection = Connection([
+            EdgeEndpoint { channel_index: from, pass_by: ChannelPassBy::Value }
+                .into_undirected_identifier(from_node),
+            EdgeEndpoint { channel_index: to, pass_by: to_pass_by }.into_undirected_identifier(to_node),
+        ]);
+
+        let command = GraphCommand::InsertEdge { connection, primary: false };
+
+        if let Err(error) = command.apply(&mut self.graph) {
+            error!(Category::Ui, "Could not connect the nodes of a template: {}", error);
+        }
+    }
+
+    pub(crate) fn build(self) -> Graph {
+        self.graph.graph
+    }
+}
+
+fn demo() -> Graph {
+    let mut builder = GraphBuilder::default();
+
+    builder.node("My Constant Node #1", [210.0, 10.0], ConstantNodeBehaviour::new(42.0_f32));
+    builder.node("My Constant Node #2", [10.0, 10.0], ConstantNodeBehaviour::new(84.0_f32));
+    builder.node("My Bin Op #1", [410.0, 10.0], BinaryOpNodeBehaviour::default());
+    builder.node("My Window #1", [610.0, 10.0], WindowNodeBehaviour::default());
+    builder.node("My Array Constructor", [10.0, 310.0], ArrayConstructorNodeBehaviour::default());
+    builder.node("My List Constructor", [10.0, 510.0], ListConstructorNodeBehaviour::default());
+    builder.node("My Debug", [210.0, 510.0], DebugNodeBehaviour::default());
+    builder.node("My Debug 2", [410.0, 510.0], DebugNodeBehaviour::default());
+    builder.node("My Counter", [810.0, 10.0], CounterNodeBehaviour::default());
+    builder.node("My Text Render", [1010.0, 210.0], TextRenderNodeBehaviour::default());
+
+    builder.build()
+}
+
+fn arithmetic() -> Graph {
+    let mut builder = GraphBuilder::default();
+    let lhs = builder.node("Left", [10.0, 10.0], ConstantNodeBehaviour::new(3.0_f32));
+    let rhs = builder.node("Right", [10.0, 160.0], ConstantNodeBehaviour::new(4.0_f32));
+    let op = builder.node("Sum", [260.0, 60.0], BinaryOpNodeBehaviour::default());
+    let result = builder.node("Result", [510.0, 60.0], DebugNodeBehaviour::default());
+
+    builder.connect((lhs, 0), (op, ChannelPassBy::Value, 0));
+    builder.connect((rhs, 0), (op, ChannelPassBy::Value, 1));
+    builder.connect((op, 0), (result, ChannelPassBy::SharedReference, 0));
+    builder.build()
+}
+
+fn hello_counter() -> Graph {
+    let mut builder = GraphBuilder::default();
+    let counter = builder.node("Counter", [10.0, 10.0], CounterNodeBehaviour::default());
+    let debug = builder.node("Count", [260.0, 10.0], DebugNodeBehaviour::new(PrimitiveTypeEnum::U32));
+
+    builder.connect((counter, 0), (debug, ChannelPassBy::SharedReference, 0));
+    builder.build()
+}
+
+fn list_processing() -> Graph {
+    let mut builder = GraphBuilder::default();
+    let range = builder.node("Range", [10.0, 10.0], RangeNodeBehaviour::default());
+    let sum = builder.node("Sum", [260.0, 10.0], ListReduceNodeBehaviour::default());
+    let debug = builder.node("Result", [510.0, 10.0], DebugNodeBehaviour::default());
+
+    builder.connect((range, 0), (sum, ChannelPassBy::SharedReference, 0));
+    builder.connect((sum, 0), (debug, ChannelPassBy::SharedReference, 0));
+    builder.build()
+}
+
+fn gradient_window() -> Graph {
+    let mut builder = GraphBuilder::default();
+    let clock = builder.node("Clock", [10.0, 10.0], ClockNodeBehaviour::default());
+    let gradient = builder.node("Gradient", [260.0, 10.0], GradientNodeBehaviour::baked());
+    let phase = builder.node("Phase", [260.0, 310.0], DebugNodeBehaviour::default());
+    let window = builder.node("Window", [510.0, 10.0], WindowNodeBehaviour::default());
+
+    // The phase of the clock
+    builder.connect((clock, 2), (gradient, ChannelPassBy::Value, 0));
+    builder.connect((clock, 2), (phase, ChannelPassBy::SharedReference, 0));
+    // The baked texture
+    builder.connect((gradient, 1), (window, ChannelPassBy::SharedReference, 0));
+    builder.build()
+}
+
+fn texture_history() -> Graph {
+    let mut builder = GraphBuilder::default();
+    let text = builder.node("Text Render", [10.0, 10.0], TextRenderNodeBehaviour::default());
+    let history = builder.node("Texture History", [260.0, 10.0], TextureHistoryNodeBehaviour::default());
+    let window = builder.node("Window", [510.0, 10.0], WindowNodeBehaviour::default());
+
+    builder.connect((text, 0), (history, ChannelPassBy::SharedReference, 0));
+    builder.connect((history, 0), (window, ChannelPassBy::SharedReference, 0));
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::testing::schedule;
+
+    /// The number of connections of the example, which are not made if they are refused.
+    fn connection_count(template: Template) -> usize {
+        match template {
+            Template::Empty | Template::Demo => 0,
+            Template::HelloCounter => 1,
+            Template::ListProcessing | Template::TextureHistory => 2,
+            Template::Arithmetic | Template::GradientWindow => 3,
+        }
+    }
+
+    #[test]
+    fn examples_are_connected_and_scheduled() {
+        for template in std::iter::once(Template::Empty).chain(Template::EXAMPLES.iter().copied()) {
+            let mut graph = ExecutionGraph::from(template.graph());
+
+            assert_eq!(graph.edge_count(), connection_count(template), "{}", template);
+
+            let schedule = schedule(&mut graph);
+
+            assert_eq!(schedule.node_indices.len(), graph.node_count(), "{}", template);
+        }
+    }
+}
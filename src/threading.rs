@@ -0,0 +1,250 @@
+//! Spawning of threads with a name, a scheduling priority and a CPU affinity.
+//!
+//! The priority and the affinity are requests the OS may deny, for example because realtime
+//! scheduling requires privileges the user does not have. Denied requests are reported as
+//! warnings and the thread falls back to the closest setting that could be applied, so that the
+//! thread is always spawned.
+
+use crate::logging::Category;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::thread::{self, JoinHandle};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadPriority {
+    Default,
+    /// Preferred over other threads, but without starving them, `nice -10` on Unix.
+    High,
+    /// Always preferred over threads of non-realtime priorities, `SCHED_FIFO` on Unix.
+    /// Usually requires privileges.
+    Realtime,
+}
+
+impl ThreadPriority {
+    pub const VALUES: [ThreadPriority; 3] =
+        [ThreadPriority::Default, ThreadPriority::High, ThreadPriority::Realtime];
+
+    /// The next lower priority to fall back to, if this one has been denied.
+    fn fallback(&self) -> Option<ThreadPriority> {
+        match self {
+            ThreadPriority::Default => None,
+            ThreadPriority::High => Some(ThreadPriority::Default),
+            ThreadPriority::Realtime => Some(ThreadPriority::High),
+        }
+    }
+}
+
+impl Display for ThreadPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThreadPriority::Default => write!(f, "Default"),
+            ThreadPriority::High => write!(f, "High"),
+            ThreadPriority::Realtime => write!(f, "Realtime"),
+        }
+    }
+}
+
+/// The settings that could be applied to a running thread, which may differ from the requested
+/// ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppliedThreadSettings {
+    pub priority: ThreadPriority,
+    /// The CPUs the thread is restricted to, one bit per CPU, or `None` if it may run on any CPU.
+    pub affinity_mask: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadSettings {
+    /// Displayed by debuggers and profilers.
+    pub name: String,
+    pub priority: ThreadPriority,
+    /// The CPUs to restrict the thread to, one bit per CPU, or `None` to run on any CPU.
+    pub affinity_mask: Option<u64>,
+}
+
+impl ThreadSettings {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), priority: ThreadPriority::Default, affinity_mask: None }
+    }
+
+    pub fn with_priority(mut self, priority: ThreadPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_affinity_mask(mut self, affinity_mask: Option<u64>) -> Self {
+        self.affinity_mask = affinity_mask.filter(|affinity_mask| *affinity_mask != 0);
+        self
+    }
+
+    /// Spawns the thread and applies the settings from within it, before calling `f` with the
+    /// settings that could be applied.
+    pub fn spawn<F, T>(self, f: F) -> JoinHandle<T>
+    where
+        F: FnOnce(AppliedThreadSettings) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let name = self.name.clone();
+
+        thread::Builder::new()
+            .name(name.clone())
+            .spawn(move || f(self.apply_to_current()))
+            .unwrap_or_else(|error| panic!("Could not spawn the thread `{}`: {}", name, error))
+    }
+
+    fn apply_to_current(&self) -> AppliedThreadSettings {
+        let mut priority = self.priority;
+
+        while let Err(error) = platform::set_current_priority(priority) {
+            // The default priority is left as it is, which cannot fail.
+            let fallback = priority.fallback().unwrap();
+
+            warn!(
+                Category::Executor,
+                "Could not set the priority of the thread `{}` to {}, falling back to {}: {}",
+                self.name,
+                priority,
+                fallback,
+                error
+            );
+
+            priority = fallback;
+        }
+
+        let affinity_mask = self.affinity_mask.and_then(|affinity_mask| {
+            match platform::set_current_affinity_mask(affinity_mask) {
+                Ok(()) => Some(affinity_mask),
+                Err(error) => {
+                    warn!(
+                        Category::Executor,
+                        "Could not restrict the thread `{}` to the CPUs {:#b}, running it on any CPU: {}",
+                        self.name,
+                        affinity_mask,
+                        error
+                    );
+                    None
+                }
+            }
+        });
+
+        AppliedThreadSettings { priority, affinity_mask }
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use super::ThreadPriority;
+    use std::io;
+
+    /// The niceness of threads of the high priority, the lower the more CPU time they are given.
+    const HIGH_PRIORITY_NICENESS: libc::c_int = -10;
+
+    pub fn set_current_priority(priority: ThreadPriority) -> Result<(), io::Error> {
+        match priority {
+            // Threads inherit the priority of the spawning thread, which is left as it is.
+            ThreadPriority::Default => Ok(()),
+            // On Linux, the niceness of the process `0` is the niceness of the calling thread.
+            ThreadPriority::High => {
+                if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, HIGH_PRIORITY_NICENESS) } == 0 {
+                    Ok(())
+                } else {
+                    Err(io::Error::last_os_error())
+                }
+            }
+            ThreadPriority::Realtime => unsafe {
+                let min = libc::sched_get_priority_min(libc::SCHED_FIFO);
+                let max = libc::sched_get_priority_max(libc::SCHED_FIFO);
+                // Other fields of the parameters differ between platforms.
+                let mut param: libc::sched_param = std::mem::zeroed();
+
+                // Leave room for threads that have to preempt the executor, such as audio threads.
+                param.sched_priority = min + (max - min) / 2;
+
+                match libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) {
+                    0 => Ok(()),
+                    error => Err(io::Error::from_raw_os_error(error)),
+                }
+            },
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn set_current_affinity_mask(affinity_mask: u64) -> Result<(), io::Error> {
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+
+            libc::CPU_ZERO(&mut set);
+
+            for cpu in (0..64).filter(|cpu| affinity_mask & (1 << cpu) != 0) {
+                libc::CPU_SET(cpu, &mut set);
+            }
+
+            // The thread `0` is the calling thread.
+            if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn set_current_affinity_mask(_affinity_mask: u64) -> Result<(), io::Error> {
+        Err(io::Error::new(io::ErrorKind::Other, "CPU affinity is not supported on this platform"))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::ThreadPriority;
+    use std::io;
+    use winapi::um::processthreadsapi::{GetCurrentThread, SetThreadPriority};
+    use winapi::um::winbase::{
+        SetThreadAffinityMask, THREAD_PRIORITY_HIGHEST, THREAD_PRIORITY_TIME_CRITICAL,
+    };
+
+    pub fn set_current_priority(priority: ThreadPriority) -> Result<(), io::Error> {
+        let priority = match priority {
+            // Threads inherit the priority of the spawning thread, which is left as it is.
+            ThreadPriority::Default => return Ok(()),
+            ThreadPriority::High => THREAD_PRIORITY_HIGHEST,
+            ThreadPriority::Realtime => THREAD_PRIORITY_TIME_CRITICAL,
+        };
+
+        if unsafe { SetThreadPriority(GetCurrentThread(), priority as _) } != 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub fn set_current_affinity_mask(affinity_mask: u64) -> Result<(), io::Error> {
+        // Returns the previous mask, which is never empty, on success.
+        if unsafe { SetThreadAffinityMask(GetCurrentThread(), affinity_mask as _) } != 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use super::ThreadPriority;
+    use std::io;
+
+    pub fn set_current_priority(priority: ThreadPriority) -> Result<(), io::Error> {
+        match priority {
+            ThreadPriority::Default => Ok(()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "thread priorities are not supported on this platform",
+            )),
+        }
+    }
+
+    pub fn set_current_affinity_mask(_affinity_mask: u64) -> Result<(), io::Error> {
+        Err(io::Error::new(io::ErrorKind::Other, "CPU affinity is not supported on this platform"))
+    }
+}
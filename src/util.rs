@@ -14,10 +14,16 @@ use std::ops::DerefMut;
 use std::ops::Range;
 use vek::Vec2;
 
+pub mod easing;
+
+/// `ZigZag` alternates between both sides of the curve, `amplitude` away from it, changing sides
+/// every half of the `wavelength`.
+#[derive(Debug, Clone, Copy)]
 pub enum StrokeType {
     Contiguous,
     Dashed { filled_length: f32, gap_length: f32 },
     Dotted { gap_length: f32 },
+    ZigZag { amplitude: f32, wavelength: f32 },
 }
 
 #[derive(Debug)]
@@ -187,55 +193,9 @@ impl<T: Segment<Scalar = f32> + ConnectionSegment> Segments<T> {
     pub fn stroke(&self, builder: &mut Builder, stroke_type: StrokeType) {
         const TOLERANCE: f32 = 0.1;
 
-        let (filled_length, gap_length) = match stroke_type {
-            StrokeType::Contiguous => {
-                self.build_segments(builder);
-                return;
-            }
-            StrokeType::Dashed { filled_length, gap_length } => (filled_length, gap_length),
-            StrokeType::Dotted { gap_length } => (0.0, gap_length),
-        };
-
-        let line_points = self.flattened(TOLERANCE);
-        let line_segments =
-            line_points.array_windows::<2>().map(|[from, to]| LineSegment { from: *from, to: *to });
-
-        let mut segment_length_remaining = filled_length;
-        let mut fill_segment = true;
-
-        for segment in line_segments {
-            let segment_length = segment.length();
-            let mut segment_offset = 0.0;
-
-            loop {
-                let from_t = partial_max(0.0, segment_offset) / segment_length;
-                let to_t = (segment_offset + segment_length_remaining) / segment_length;
-
-                if to_t < 1.0 {
-                    // Dash ends before the end of the segment
-                    if fill_segment {
-                        let dash = segment.split_range(from_t..to_t);
-
-                        builder.line_to(dash.to.to_array().into());
-                    } else {
-                        builder.move_to(segment.sample(to_t).to_array().into());
-                    }
-
-                    segment_offset += segment_length_remaining;
-                    fill_segment ^= true;
-                    segment_length_remaining = if fill_segment { filled_length } else { gap_length };
-                } else {
-                    // Dash continues in the next segment
-                    if fill_segment {
-                        let dash = segment.after_split(from_t);
-
-                        builder.line_to(dash.to.to_array().into());
-                    }
-
-                    segment_length_remaining -= (1.0 - from_t) * segment_length;
-                    break;
-                }
-            }
+        match stroke_type {
+            StrokeType::Contiguous => self.build_segments(builder),
+            _ => stroke_polyline(builder, &self.flattened(TOLERANCE), stroke_type),
         }
     }
 
@@ -253,6 +213,106 @@ impl<T: Segment<Scalar = f32> + ConnectionSegment> Segments<T> {
     }
 }
 
+/// Strokes the polyline, such as a part of a flattened curve, continuing from the current position
+/// of the builder, which is expected to be the first point. Zig-zag lines start a new subpath.
+pub fn stroke_polyline(builder: &mut Builder, points: &[Point], stroke_type: StrokeType) {
+    match stroke_type {
+        StrokeType::Contiguous => {
+            for point in points.iter().skip(1) {
+                builder.line_to(*point);
+            }
+        }
+        StrokeType::Dashed { filled_length, gap_length } => {
+            stroke_dashed(builder, points, filled_length, gap_length)
+        }
+        StrokeType::Dotted { gap_length } => stroke_dashed(builder, points, 0.0, gap_length),
+        StrokeType::ZigZag { amplitude, wavelength } => {
+            stroke_zig_zag(builder, points, amplitude, wavelength)
+        }
+    }
+}
+
+fn stroke_dashed(builder: &mut Builder, line_points: &[Point], filled_length: f32, gap_length: f32) {
+    let line_segments =
+        line_points.array_windows::<2>().map(|[from, to]| LineSegment { from: *from, to: *to });
+
+    let mut segment_length_remaining = filled_length;
+    let mut fill_segment = true;
+
+    for segment in line_segments {
+        let segment_length = segment.length();
+        let mut segment_offset = 0.0;
+
+        loop {
+            let from_t = partial_max(0.0, segment_offset) / segment_length;
+            let to_t = (segment_offset + segment_length_remaining) / segment_length;
+
+            if to_t < 1.0 {
+                // Dash ends before the end of the segment
+                if fill_segment {
+                    let dash = segment.split_range(from_t..to_t);
+
+                    builder.line_to(dash.to.to_array().into());
+                } else {
+                    builder.move_to(segment.sample(to_t).to_array().into());
+                }
+
+                segment_offset += segment_length_remaining;
+                fill_segment ^= true;
+                segment_length_remaining = if fill_segment { filled_length } else { gap_length };
+            } else {
+                // Dash continues in the next segment
+                if fill_segment {
+                    let dash = segment.after_split(from_t);
+
+                    builder.line_to(dash.to.to_array().into());
+                }
+
+                segment_length_remaining -= (1.0 - from_t) * segment_length;
+                break;
+            }
+        }
+    }
+}
+
+fn stroke_zig_zag(builder: &mut Builder, line_points: &[Point], amplitude: f32, wavelength: f32) {
+    let line_segments =
+        line_points.array_windows::<2>().map(|[from, to]| LineSegment { from: *from, to: *to });
+    let half_wavelength = wavelength / 2.0;
+    // The distance along the current segment of the next vertex of the zig-zag line
+    let mut vertex_offset = 0.0;
+    let mut side = 1.0;
+    let mut first_vertex = true;
+
+    for segment in line_segments {
+        let segment_length = segment.length();
+
+        if segment_length <= 0.0 {
+            continue;
+        }
+
+        let direction = Vec2::<f32>::from(segment.to.to_array()) - Vec2::from(segment.from.to_array());
+        let normal = Vec2::new(-direction.y, direction.x) / segment_length;
+
+        while vertex_offset <= segment_length {
+            let vertex = Vec2::<f32>::from(segment.sample(vertex_offset / segment_length).to_array())
+                + normal * amplitude * side;
+
+            if first_vertex {
+                builder.move_to(vertex.into_array().into());
+                first_vertex = false;
+            } else {
+                builder.line_to(vertex.into_array().into());
+            }
+
+            vertex_offset += half_wavelength;
+            side = -side;
+        }
+
+        vertex_offset -= segment_length;
+    }
+}
+
 pub fn get_connection_curve(from: Vec2<f32>, to: Vec2<f32>) -> Segments<QuadraticBezierSegment<f32>> {
     const CONTROL_POINT_DISTANCE_SLOPE: f32 = 1.0 / 3.0;
     const CONTROL_POINT_DISTANCE_ABS_SOFTNESS: f32 = 32.0;
@@ -407,6 +467,22 @@ pub trait RectangleExt: Sized {
     fn grow_uniform(&self, amount: f32) -> Self {
         self.grow(amount, amount, amount, amount)
     }
+
+    /// Whether the rectangles share any point, including their edges.
+    fn overlaps(&self, other: &Self) -> bool {
+        self.min_x() <= other.max_x()
+            && other.min_x() <= self.max_x()
+            && self.min_y() <= other.max_y()
+            && other.min_y() <= self.max_y()
+    }
+
+    /// The smallest rectangle enclosing both rectangles.
+    fn enclosing(&self, other: &Self) -> Self {
+        Self::from_min_max(
+            self.min().map2(other.min(), partial_min),
+            self.max().map2(other.max(), partial_max),
+        )
+    }
 }
 
 impl RectangleExt for Rectangle {
@@ -489,6 +565,52 @@ pub const fn rgb(rgb: u32) -> Color {
     )
 }
 
+/// Linearly interpolate between two colors, including their alpha
+pub fn mix_colors(a: Color, b: Color, t: f32) -> Color {
+    Color::from_rgba(
+        a.r + (b.r - a.r) * t,
+        a.g + (b.g - a.g) * t,
+        a.b + (b.b - a.b) * t,
+        a.a + (b.a - a.a) * t,
+    )
+}
+
+/// Multiplies the alpha of all colors of the primitive by the opacity, to draw translucent widgets.
+/// Images are drawn opaque, as their pixels are not accessible.
+pub fn fade_primitive(primitive: &mut Primitive, opacity: f32) {
+    match primitive {
+        Primitive::Group { primitives } => {
+            for primitive in primitives {
+                fade_primitive(primitive, opacity);
+            }
+        }
+        Primitive::Text { color, .. } => color.a *= opacity,
+        Primitive::Quad { background, border_color, .. } => {
+            match background {
+                Background::Color(color) => color.a *= opacity,
+            }
+
+            border_color.a *= opacity;
+        }
+        Primitive::Clip { content, .. } | Primitive::Translate { content, .. } => {
+            fade_primitive(content, opacity)
+        }
+        Primitive::Mesh2D { buffers, .. } => {
+            for vertex in &mut buffers.vertices {
+                vertex.color[3] *= opacity;
+            }
+        }
+        Primitive::Cached { cache } => {
+            // Cached primitives are shared with later frames, so a copy is faded instead.
+            let mut copy = (**cache).clone();
+
+            fade_primitive(&mut copy, opacity);
+            *primitive = copy;
+        }
+        _ => (),
+    }
+}
+
 pub trait CowMapExt<'a, B>
 where B: 'a + ToOwned<Owned = B>
 {
@@ -511,34 +633,6 @@ macro_rules! count_tokens {
     ( $x:tt $($xs:tt)* ) => (1usize + count_tokens!($($xs)*));
 }
 
-#[allow(unused_macros)]
-#[cfg(not(debug_assertions))]
-macro_rules! debug {
-    ($($tt:tt)*) => {};
-}
-
-#[allow(unused_macros)]
-#[cfg(debug_assertions)]
-macro_rules! debug {
-    ($($tt:tt)*) => {
-        print!($($tt)*)
-    };
-}
-
-#[allow(unused_macros)]
-#[cfg(not(debug_assertions))]
-macro_rules! debugln {
-    ($($tt:tt)*) => {};
-}
-
-#[allow(unused_macros)]
-#[cfg(debug_assertions)]
-macro_rules! debugln {
-    ($($tt:tt)*) => {
-        println!($($tt)*)
-    };
-}
-
 /// Like `SmallVec`, but with immutable length.
 pub struct SmallBoxedSlice<A: Array>(SmallVec<A>);
 
@@ -589,3 +683,17 @@ pub fn partial_min<T: PartialOrd>(a: T, b: T) -> T {
 pub fn partial_clamp<T: PartialOrd>(x: T, [min, max]: [T; 2]) -> T {
     partial_max(min, partial_min(max, x))
 }
+
+/// Truncates the string to at most `max_length` characters, ending it with an ellipsis, if
+/// truncated.
+pub fn truncate(mut string: String, max_length: usize) -> String {
+    if string.chars().count() > max_length {
+        if let Some((index, _)) = string.char_indices().nth(max_length.saturating_sub(1)) {
+            string.truncate(index);
+        }
+
+        string.push('…');
+    }
+
+    string
+}
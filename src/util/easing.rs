@@ -0,0 +1,212 @@
+//! Easing functions, which shape the progress of a transition. Every easing maps 0 to 0 and 1 to 1,
+//! values in between are reshaped, and values outside of that range are extrapolated along the
+//! same formula.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// The constant of the back easings, which overshoot by about 10 %.
+const BACK_OVERSHOOT: f64 = 1.70158;
+/// The exponential easings grow by a factor of `2^EXPONENTIAL_STEEPNESS` over the transition.
+const EXPONENTIAL_STEEPNESS: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    /// The Hermite interpolation `3t² - 2t³`.
+    Smoothstep,
+    ExponentialIn,
+    ExponentialOut,
+    ExponentialInOut,
+    /// Pulls back before the start of the transition.
+    BackIn,
+    /// Overshoots the end of the transition.
+    BackOut,
+    BackInOut,
+}
+
+impl Easing {
+    pub const VALUES: [Easing; 14] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::Smoothstep,
+        Easing::ExponentialIn,
+        Easing::ExponentialOut,
+        Easing::ExponentialInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+    ];
+
+    /// Whether the eased progress leaves the range from 0 to 1 for progress within that range.
+    /// The other easings are monotonic.
+    pub fn overshoots(self) -> bool {
+        matches!(self, Easing::BackIn | Easing::BackOut | Easing::BackInOut)
+    }
+
+    /// Eases the progress `t`.
+    pub fn apply(self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => quad(t),
+            Easing::QuadOut => ease_out(quad, t),
+            Easing::QuadInOut => ease_in_out(quad, t),
+            Easing::CubicIn => cubic(t),
+            Easing::CubicOut => ease_out(cubic, t),
+            Easing::CubicInOut => ease_in_out(cubic, t),
+            Easing::Smoothstep => t * t * (3.0 - 2.0 * t),
+            Easing::ExponentialIn => exponential(t),
+            Easing::ExponentialOut => ease_out(exponential, t),
+            Easing::ExponentialInOut => ease_in_out(exponential, t),
+            Easing::BackIn => back(BACK_OVERSHOOT, t),
+            Easing::BackOut => ease_out(|t| back(BACK_OVERSHOOT, t), t),
+            // The overshoot is scaled, so that the halves overshoot by about as much as `BackIn`.
+            Easing::BackInOut => ease_in_out(|t| back(BACK_OVERSHOOT * 1.525, t), t),
+        }
+    }
+}
+
+impl Display for Easing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Easing::*;
+        match self {
+            Linear => write!(f, "Linear"),
+            QuadIn => write!(f, "Quad in"),
+            QuadOut => write!(f, "Quad out"),
+            QuadInOut => write!(f, "Quad in-out"),
+            CubicIn => write!(f, "Cubic in"),
+            CubicOut => write!(f, "Cubic out"),
+            CubicInOut => write!(f, "Cubic in-out"),
+            Smoothstep => write!(f, "Smoothstep"),
+            ExponentialIn => write!(f, "Exponential in"),
+            ExponentialOut => write!(f, "Exponential out"),
+            ExponentialInOut => write!(f, "Exponential in-out"),
+            BackIn => write!(f, "Back in"),
+            BackOut => write!(f, "Back out"),
+            BackInOut => write!(f, "Back in-out"),
+        }
+    }
+}
+
+fn quad(t: f64) -> f64 {
+    t * t
+}
+
+fn cubic(t: f64) -> f64 {
+    t * t * t
+}
+
+/// Normalized, so that it passes through 0 and 1 exactly.
+fn exponential(t: f64) -> f64 {
+    let range = EXPONENTIAL_STEEPNESS.exp2() - 1.0;
+
+    ((EXPONENTIAL_STEEPNESS * t).exp2() - 1.0) / range
+}
+
+fn back(overshoot: f64, t: f64) -> f64 {
+    t * t * ((overshoot + 1.0) * t - overshoot)
+}
+
+/// Reverses the easing `ease_in`, which starts slowly, to end slowly instead.
+fn ease_out(ease_in: impl Fn(f64) -> f64, t: f64) -> f64 {
+    1.0 - ease_in(1.0 - t)
+}
+
+/// Eases in over the first half and out over the second half.
+fn ease_in_out(ease_in: impl Fn(f64) -> f64, t: f64) -> f64 {
+    if t < 0.5 {
+        ease_in(2.0 * t) / 2.0
+    } else {
+        1.0 - ease_in(2.0 - 2.0 * t) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 1e-9;
+
+    /// The eased progress at `t` of 0.5.
+    fn midpoint(easing: Easing) -> f64 {
+        use Easing::*;
+        match easing {
+            Linear | QuadInOut | CubicInOut | Smoothstep | ExponentialInOut | BackInOut => 0.5,
+            QuadIn => 0.25,
+            QuadOut => 0.75,
+            CubicIn => 0.125,
+            CubicOut => 0.875,
+            ExponentialIn => 31.0 / 1023.0,
+            ExponentialOut => 992.0 / 1023.0,
+            BackIn => 0.25 * (0.5 * (BACK_OVERSHOOT + 1.0) - BACK_OVERSHOOT),
+            BackOut => 1.0 - 0.25 * (0.5 * (BACK_OVERSHOOT + 1.0) - BACK_OVERSHOOT),
+        }
+    }
+
+    #[test]
+    fn easings_match_reference_values() {
+        for easing in Easing::VALUES.iter().copied() {
+            for (t, expected) in vec![(0.0, 0.0), (0.5, midpoint(easing)), (1.0, 1.0)] {
+                let actual = easing.apply(t);
+
+                assert!(
+                    (actual - expected).abs() <= TOLERANCE,
+                    "{} at {}: {} is not {}",
+                    easing,
+                    t,
+                    actual,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn back_easings_overshoot_by_about_a_tenth() {
+        for easing in [Easing::BackIn, Easing::BackOut].iter().copied() {
+            let overshoot = (0..=1000)
+                .map(|step| easing.apply(step as f64 / 1000.0))
+                .map(|value| (-value).max(value - 1.0))
+                .fold(0.0, f64::max);
+
+            assert!((overshoot - 0.1).abs() < 1e-3, "{} overshoots by {}", easing, overshoot);
+        }
+    }
+
+    #[test]
+    fn easings_without_overshoot_are_monotonic_within_the_range() {
+        const STEPS: usize = 1000;
+
+        for easing in Easing::VALUES.iter().copied() {
+            let values = (0..=STEPS).map(|step| easing.apply(step as f64 / STEPS as f64)).collect::<Vec<_>>();
+            let monotonic = values.windows(2).all(|pair| pair[0] <= pair[1]);
+            let within_range = values.iter().all(|value| (0.0..=1.0).contains(value));
+
+            if easing.overshoots() {
+                assert!(!within_range, "{} does not overshoot", easing);
+            } else {
+                assert!(monotonic, "{} is not monotonic", easing);
+                assert!(within_range, "{} overshoots", easing);
+            }
+        }
+    }
+
+    #[test]
+    fn easings_are_extrapolated_along_their_formulas() {
+        assert_eq!(Easing::Linear.apply(-0.5), -0.5);
+        assert_eq!(Easing::QuadIn.apply(2.0), 4.0);
+        assert_eq!(Easing::CubicOut.apply(2.0), 2.0);
+    }
+}
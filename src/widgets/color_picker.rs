@@ -0,0 +1,317 @@
+use crate::node::hsv_to_rgb;
+use iced_graphics::{self, Backend, Color, Primitive, Rectangle};
+use iced_native::event::Status;
+use iced_native::layout::{Layout, Limits, Node};
+use iced_native::mouse::{self, Button as MouseButton, Event as MouseEvent};
+use iced_native::widget::Widget;
+use iced_native::{self, Clipboard, Element, Event, Hasher, Length, Point, Size};
+use std::hash::Hash;
+
+const HEIGHT: f32 = 128.0;
+const HUE_STRIP_WIDTH: f32 = 16.0;
+const GAP: f32 = 4.0;
+/// The number of cells along each side of the saturation/value square.
+const SQUARE_CELLS: usize = 24;
+/// The number of cells of the hue strip.
+const HUE_CELLS: usize = 48;
+const MARKER_SIZE: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Area {
+    SaturationValue,
+    Hue,
+}
+
+/// The state of a [`ColorPicker`], to be stored in the node behaviour.
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    /// The area being dragged across.
+    dragging: Option<Area>,
+}
+
+/// The color of the markers of the picked color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub marker: Color,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self { marker: Color::WHITE }
+    }
+}
+
+/// Picks a color by its saturation and value from a square on the left and by its hue from a
+/// vertical strip on the right. Clicking either area picks the color under the cursor, dragging
+/// keeps picking.
+pub struct ColorPicker<'a, M> {
+    state: &'a mut State,
+    /// In turns.
+    hue: f32,
+    saturation: f32,
+    value: f32,
+    on_pick_saturation_value: Box<dyn Fn(f32, f32) -> M>,
+    on_pick_hue: Box<dyn Fn(f32) -> M>,
+    width: Length,
+    style: Style,
+}
+
+impl<'a, M> ColorPicker<'a, M> {
+    pub fn new(
+        state: &'a mut State,
+        [hue, saturation, value]: [f32; 3],
+        on_pick_saturation_value: impl Fn(f32, f32) -> M + 'static,
+        on_pick_hue: impl Fn(f32) -> M + 'static,
+    ) -> Self {
+        Self {
+            state,
+            hue,
+            saturation,
+            value,
+            on_pick_saturation_value: Box::new(on_pick_saturation_value),
+            on_pick_hue: Box::new(on_pick_hue),
+            width: Length::Fill,
+            style: Default::default(),
+        }
+    }
+
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    fn square_bounds(bounds: Rectangle) -> Rectangle {
+        Rectangle { width: (bounds.width - HUE_STRIP_WIDTH - GAP).max(1.0), ..bounds }
+    }
+
+    fn hue_strip_bounds(bounds: Rectangle) -> Rectangle {
+        Rectangle { x: bounds.x + bounds.width - HUE_STRIP_WIDTH, width: HUE_STRIP_WIDTH, ..bounds }
+    }
+
+    fn area_at(bounds: Rectangle, cursor_position: Point) -> Option<Area> {
+        if Self::square_bounds(bounds).contains(cursor_position) {
+            Some(Area::SaturationValue)
+        } else if Self::hue_strip_bounds(bounds).contains(cursor_position) {
+            Some(Area::Hue)
+        } else {
+            None
+        }
+    }
+
+    /// The picked color under the cursor, clamped to the area.
+    fn pick(&self, area: Area, bounds: Rectangle, cursor_position: Point) -> M {
+        let unit = |position: f32, start: f32, length: f32| ((position - start) / length).max(0.0).min(1.0);
+
+        match area {
+            Area::SaturationValue => {
+                let square = Self::square_bounds(bounds);
+                let saturation = unit(cursor_position.x, square.x, square.width);
+                let value = 1.0 - unit(cursor_position.y, square.y, square.height);
+
+                (self.on_pick_saturation_value)(saturation, value)
+            }
+            Area::Hue => {
+                let strip = Self::hue_strip_bounds(bounds);
+
+                (self.on_pick_hue)(unit(cursor_position.y, strip.y, strip.height))
+            }
+        }
+    }
+}
+
+impl<'a, M, R> Widget<M, R> for ColorPicker<'a, M>
+where
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn layout(&self, _renderer: &R, limits: &Limits) -> Node {
+        let intrinsic_size = Size::new(HEIGHT + GAP + HUE_STRIP_WIDTH, HEIGHT);
+
+        Node::new(limits.width(self.width).height(Length::Shrink).resolve(intrinsic_size))
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut R,
+        _defaults: &R::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) -> R::Output {
+        let bounds = layout.bounds();
+        let mouse_interaction =
+            if self.state.dragging.is_some() || Self::area_at(bounds, cursor_position).is_some() {
+                mouse::Interaction::Crosshair
+            } else {
+                mouse::Interaction::default()
+            };
+
+        renderer.draw(
+            Self::square_bounds(bounds),
+            Self::hue_strip_bounds(bounds),
+            [self.hue, self.saturation, self.value],
+            &self.style,
+            mouse_interaction,
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.width.hash(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<M>,
+        _renderer: &R,
+        _clipboard: Option<&dyn Clipboard>,
+    ) -> Status {
+        let bounds = layout.bounds();
+
+        match event {
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) => {
+                match Self::area_at(bounds, cursor_position) {
+                    Some(area) => {
+                        self.state.dragging = Some(area);
+                        messages.push(self.pick(area, bounds, cursor_position));
+                        Status::Captured
+                    }
+                    None => Status::Ignored,
+                }
+            }
+            Event::Mouse(MouseEvent::CursorMoved { .. }) => match self.state.dragging {
+                Some(area) => {
+                    messages.push(self.pick(area, bounds, cursor_position));
+                    Status::Captured
+                }
+                None => Status::Ignored,
+            },
+            Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)) if self.state.dragging.is_some() => {
+                self.state.dragging = None;
+                Status::Captured
+            }
+            _ => Status::Ignored,
+        }
+    }
+}
+
+impl<'a, M, R> From<ColorPicker<'a, M>> for Element<'a, M, R>
+where
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn from(other: ColorPicker<'a, M>) -> Self {
+        Element::new(other)
+    }
+}
+
+pub trait WidgetRenderer: iced_native::Renderer + Sized {
+    fn draw(
+        &mut self,
+        square_bounds: Rectangle,
+        hue_strip_bounds: Rectangle,
+        hsv: [f32; 3],
+        style: &Style,
+        mouse_interaction: mouse::Interaction,
+    ) -> Self::Output;
+}
+
+impl<B> WidgetRenderer for iced_graphics::Renderer<B>
+where B: Backend
+{
+    fn draw(
+        &mut self,
+        square_bounds: Rectangle,
+        hue_strip_bounds: Rectangle,
+        [hue, saturation, value]: [f32; 3],
+        style: &Style,
+        mouse_interaction: mouse::Interaction,
+    ) -> Self::Output {
+        let quad = |bounds: Rectangle, [red, green, blue]: [f32; 3]| Primitive::Quad {
+            bounds,
+            background: Color::from_rgb(red, green, blue).into(),
+            border_radius: 0,
+            border_width: 0,
+            border_color: Color::TRANSPARENT,
+        };
+        let marker = |center: Point| Primitive::Quad {
+            bounds: Rectangle {
+                x: center.x - MARKER_SIZE / 2.0,
+                y: center.y - MARKER_SIZE / 2.0,
+                width: MARKER_SIZE,
+                height: MARKER_SIZE,
+            },
+            background: Color::TRANSPARENT.into(),
+            border_radius: 1,
+            border_width: 2,
+            border_color: style.marker,
+        };
+        let cell_width = square_bounds.width / SQUARE_CELLS as f32;
+        let cell_height = square_bounds.height / SQUARE_CELLS as f32;
+        let mut primitives = Vec::with_capacity(SQUARE_CELLS * SQUARE_CELLS + HUE_CELLS + 2);
+
+        // The cells are colored by their centers.
+        for row in 0..SQUARE_CELLS {
+            for column in 0..SQUARE_CELLS {
+                let cell_saturation = (column as f32 + 0.5) / SQUARE_CELLS as f32;
+                let cell_value = 1.0 - (row as f32 + 0.5) / SQUARE_CELLS as f32;
+                let bounds = Rectangle {
+                    x: square_bounds.x + column as f32 * cell_width,
+                    y: square_bounds.y + row as f32 * cell_height,
+                    width: cell_width,
+                    height: cell_height,
+                };
+
+                primitives.push(quad(bounds, hsv_to_rgb(hue, cell_saturation, cell_value)));
+            }
+        }
+
+        let hue_cell_height = hue_strip_bounds.height / HUE_CELLS as f32;
+
+        for index in 0..HUE_CELLS {
+            let bounds = Rectangle {
+                y: hue_strip_bounds.y + index as f32 * hue_cell_height,
+                height: hue_cell_height,
+                ..hue_strip_bounds
+            };
+
+            primitives.push(quad(bounds, hsv_to_rgb((index as f32 + 0.5) / HUE_CELLS as f32, 1.0, 1.0)));
+        }
+
+        primitives.push(marker(Point::new(
+            square_bounds.x + saturation * square_bounds.width,
+            square_bounds.y + (1.0 - value) * square_bounds.height,
+        )));
+        primitives.push(Primitive::Quad {
+            bounds: Rectangle {
+                y: hue_strip_bounds.y + hue * hue_strip_bounds.height - 1.0,
+                height: 2.0,
+                ..hue_strip_bounds
+            },
+            background: style.marker.into(),
+            border_radius: 0,
+            border_width: 0,
+            border_color: Color::TRANSPARENT,
+        });
+
+        (Primitive::Group { primitives }, mouse_interaction)
+    }
+}
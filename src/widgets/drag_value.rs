@@ -0,0 +1,489 @@
+use iced::text_input;
+use iced_graphics::{self, Backend, Primitive, Rectangle};
+use iced_native::event::Status;
+use iced_native::keyboard::{self, Event as KeyboardEvent, KeyCode};
+use iced_native::layout::{Layout, Limits, Node};
+use iced_native::mouse::{self, Button as MouseButton, Event as MouseEvent, ScrollDelta};
+use iced_native::widget::Widget;
+use iced_native::{self, Clipboard, Element, Event, Font, Hasher, Length, Point, Size};
+use iced_native::{HorizontalAlignment, VerticalAlignment};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// The longest time between two presses, which enter the text edit mode.
+const DOUBLE_CLICK_DURATION: Duration = Duration::from_millis(400);
+/// The shortest time between the changes emitted while dragging, so that the update loop is not
+/// flooded. The exact value is emitted on release.
+const DRAG_MESSAGE_INTERVAL: Duration = Duration::from_millis(50);
+/// The distance in pixels a line scrolled by a scroll wheel is equivalent to, if there is no step.
+const SCROLL_LINE_LENGTH: f64 = 10.0;
+/// The factors of the speed while Shift or Ctrl is held.
+const FINE_FACTOR: f64 = 0.1;
+const COARSE_FACTOR: f64 = 10.0;
+/// The most decimal places values are rounded to while dragging.
+const MAX_PRECISION: usize = 6;
+
+/// The state of a [`DragValue`], to be stored in the node behaviour.
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    drag: Option<Drag>,
+    /// The text entered since the widget was double-clicked.
+    editing: Option<Editing>,
+    last_press: Option<Instant>,
+    modifiers: keyboard::ModifiersState,
+}
+
+#[derive(Debug, Clone)]
+struct Drag {
+    cursor_x: f32,
+    /// Not rounded, so that dragging back and forth by less than the step is reversible.
+    value: f64,
+    /// The value emitted last and when.
+    emitted: Option<(f64, Instant)>,
+}
+
+#[derive(Debug, Clone)]
+struct Editing {
+    text: String,
+    /// Whether the text was rejected when it was last committed.
+    invalid: bool,
+}
+
+/// Displays a number, which is adjusted by dragging horizontally or by scrolling, with Shift for
+/// finer and Ctrl for coarser changes. Double-clicking enters a text edit mode, committed with Enter
+/// or by clicking elsewhere and cancelled with Escape.
+pub struct DragValue<'a, M, R: WidgetRenderer> {
+    state: &'a mut State,
+    value: f64,
+    on_change: Box<dyn Fn(f64) -> M + 'a>,
+    label: Option<&'a str>,
+    /// The change of the value per pixel dragged.
+    speed: f64,
+    min: f64,
+    max: f64,
+    step: Option<f64>,
+    width: Length,
+    padding: u16,
+    text_size: Option<u16>,
+    style: R::Style,
+}
+
+impl<'a, M, R: WidgetRenderer> DragValue<'a, M, R> {
+    pub fn new(state: &'a mut State, value: impl Into<f64>, on_change: impl Fn(f64) -> M + 'a) -> Self {
+        Self {
+            state,
+            value: value.into(),
+            on_change: Box::new(on_change),
+            label: None,
+            speed: 0.01,
+            min: f64::NEG_INFINITY,
+            max: f64::INFINITY,
+            step: None,
+            width: Length::Shrink,
+            padding: 0,
+            text_size: None,
+            style: Default::default(),
+        }
+    }
+
+    /// Displayed before the value.
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Clamps the values emitted, including the entered ones.
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    /// Rounds the values emitted to multiples of the step, including the entered ones.
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = Some(step).filter(|step| *step > 0.0);
+        self
+    }
+
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn padding(mut self, padding: u16) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn text_size(mut self, text_size: u16) -> Self {
+        self.text_size = Some(text_size);
+        self
+    }
+
+    pub fn style(mut self, style: impl Into<R::Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// The number of decimal places displayed, derived from the step or the speed.
+    fn precision(&self) -> usize {
+        let increment = self.step.unwrap_or(self.speed);
+
+        if increment > 0.0 && increment.is_finite() {
+            (-increment.log10().floor()).max(0.0).min(MAX_PRECISION as f64) as usize
+        } else {
+            MAX_PRECISION
+        }
+    }
+
+    /// Rounds the value to the step and clamps it to the range. Without a step, values adjusted by
+    /// dragging or scrolling are rounded to the displayed precision instead.
+    fn constrain(&self, value: f64, adjusted: bool) -> f64 {
+        let value = match self.step {
+            Some(step) => (value / step).round() * step,
+            None if adjusted => {
+                let scale = 10f64.powi(self.precision() as i32);
+
+                (value * scale).round() / scale
+            }
+            None => value,
+        };
+
+        value.max(self.min).min(self.max)
+    }
+
+    fn format(&self, value: f64) -> String {
+        format!("{:.*}", self.precision(), value)
+    }
+
+    fn speed_factor(&self) -> f64 {
+        if self.state.modifiers.shift {
+            FINE_FACTOR
+        } else if self.state.modifiers.control {
+            COARSE_FACTOR
+        } else {
+            1.0
+        }
+    }
+
+    /// Emits the entered value and leaves the text edit mode, if the text is a number.
+    fn commit(&mut self, messages: &mut Vec<M>) {
+        let parsed = match self.state.editing.as_ref() {
+            Some(editing) => editing.text.trim().parse::<f64>().ok().filter(|value| value.is_finite()),
+            None => return,
+        };
+
+        match parsed {
+            Some(value) => {
+                messages.push((self.on_change)(self.constrain(value, false)));
+                self.state.editing = None;
+            }
+            None => self.state.editing.as_mut().unwrap().invalid = true,
+        }
+    }
+
+    /// The text displayed instead of the value, while the value is being edited.
+    fn displayed_text(&self) -> String {
+        match (&self.state.editing, &self.state.drag) {
+            (Some(editing), _) => format!("{}|", editing.text),
+            (None, Some(drag)) => self.format(self.constrain(drag.value, true)),
+            (None, None) => self.format(self.value),
+        }
+    }
+}
+
+impl<'a, M, R> Widget<M, R> for DragValue<'a, M, R>
+where
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn layout(&self, renderer: &R, limits: &Limits) -> Node {
+        let text_size = self.text_size.unwrap_or(renderer.default_size());
+        let label_width = self
+            .label
+            .map(|label| {
+                renderer.measure(label, text_size, Default::default(), Size::INFINITY).0
+                    + f32::from(text_size)
+            })
+            .unwrap_or(0.0);
+        let value_width =
+            renderer.measure(&self.displayed_text(), text_size, Default::default(), Size::INFINITY).0;
+        let intrinsic_size = Size::new(
+            label_width + value_width + f32::from(self.padding) * 2.0,
+            f32::from(text_size) + f32::from(self.padding) * 2.0,
+        );
+
+        Node::new(limits.width(self.width).height(Length::Shrink).resolve(intrinsic_size))
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut R,
+        _defaults: &R::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) -> R::Output {
+        renderer.draw(
+            layout.bounds(),
+            cursor_position,
+            self.label,
+            &self.displayed_text(),
+            self.state.drag.is_some(),
+            self.state.editing.as_ref().map(|editing| editing.invalid),
+            self.text_size.unwrap_or(renderer.default_size()),
+            &self.style,
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.width.hash(state);
+        self.padding.hash(state);
+        self.text_size.hash(state);
+
+        if self.width == Length::Shrink {
+            self.label.hash(state);
+            self.displayed_text().hash(state);
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<M>,
+        _renderer: &R,
+        _clipboard: Option<&dyn Clipboard>,
+    ) -> Status {
+        let hovered = layout.bounds().contains(cursor_position);
+
+        match event {
+            Event::Keyboard(KeyboardEvent::ModifiersChanged(modifiers)) => self.state.modifiers = modifiers,
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) if self.state.editing.is_some() => {
+                if hovered {
+                    return Status::Captured;
+                }
+
+                // Clicking elsewhere commits valid text and discards invalid text.
+                self.commit(messages);
+                self.state.editing = None;
+            }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) if hovered => {
+                let now = Instant::now();
+                let double_click = self
+                    .state
+                    .last_press
+                    .map_or(false, |last_press| now - last_press <= DOUBLE_CLICK_DURATION);
+
+                if double_click {
+                    self.state.last_press = None;
+                    self.state.editing = Some(Editing { text: self.format(self.value), invalid: false });
+                } else {
+                    self.state.last_press = Some(now);
+                    self.state.drag =
+                        Some(Drag { cursor_x: cursor_position.x, value: self.value, emitted: None });
+                }
+
+                return Status::Captured;
+            }
+            Event::Mouse(MouseEvent::CursorMoved { x, .. }) if self.state.drag.is_some() => {
+                let factor = self.speed_factor();
+                let drag = self.state.drag.as_mut().unwrap();
+
+                drag.value += f64::from(x - drag.cursor_x) * self.speed * factor;
+                drag.value = drag.value.max(self.min).min(self.max);
+                drag.cursor_x = x;
+
+                let (raw_value, emitted) = (drag.value, drag.emitted);
+                let value = self.constrain(raw_value, true);
+                let now = Instant::now();
+                let due = match emitted {
+                    Some((emitted_value, time)) => {
+                        emitted_value != value && now - time >= DRAG_MESSAGE_INTERVAL
+                    }
+                    None => value != self.value,
+                };
+
+                if due {
+                    messages.push((self.on_change)(value));
+                    self.state.drag.as_mut().unwrap().emitted = Some((value, now));
+                }
+
+                return Status::Captured;
+            }
+            Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)) if self.state.drag.is_some() => {
+                let drag = self.state.drag.take().unwrap();
+                let value = self.constrain(drag.value, true);
+
+                // The exact final value, even if it has been emitted already, so that the drag ends
+                // with a single definite change.
+                if drag.emitted.is_some() || value != self.value {
+                    messages.push((self.on_change)(value));
+                }
+
+                return Status::Captured;
+            }
+            Event::Mouse(MouseEvent::WheelScrolled { delta })
+                if hovered && self.state.editing.is_none() && self.state.drag.is_none() =>
+            {
+                let amount = match delta {
+                    ScrollDelta::Lines { y, .. } => {
+                        f64::from(y) * self.step.unwrap_or(self.speed * SCROLL_LINE_LENGTH)
+                    }
+                    ScrollDelta::Pixels { y, .. } => f64::from(y) * self.speed,
+                };
+                let value = self.constrain(self.value + amount * self.speed_factor(), true);
+
+                if value != self.value {
+                    messages.push((self.on_change)(value));
+                }
+
+                return Status::Captured;
+            }
+            Event::Keyboard(KeyboardEvent::CharacterReceived(character)) if self.state.editing.is_some() => {
+                if !character.is_control() {
+                    let editing = self.state.editing.as_mut().unwrap();
+
+                    editing.text.push(character);
+                    editing.invalid = false;
+                }
+
+                return Status::Captured;
+            }
+            Event::Keyboard(KeyboardEvent::KeyPressed { key_code, .. }) if self.state.editing.is_some() => {
+                match key_code {
+                    KeyCode::Enter => self.commit(messages),
+                    KeyCode::Escape => self.state.editing = None,
+                    KeyCode::Backspace => {
+                        let editing = self.state.editing.as_mut().unwrap();
+
+                        editing.text.pop();
+                        editing.invalid = false;
+                    }
+                    _ => (),
+                }
+
+                // Shortcuts are not triggered while editing.
+                return Status::Captured;
+            }
+            _ => (),
+        }
+
+        Status::Ignored
+    }
+}
+
+impl<'a, M, R> From<DragValue<'a, M, R>> for Element<'a, M, R>
+where
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn from(other: DragValue<'a, M, R>) -> Self {
+        Element::new(other)
+    }
+}
+
+pub trait WidgetRenderer: iced_native::Renderer + iced_native::text::Renderer + Sized {
+    type Style: Default;
+
+    /// `invalid` is `None`, unless the value is being edited.
+    #[allow(clippy::too_many_arguments)]
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        cursor_position: Point,
+        label: Option<&str>,
+        text: &str,
+        dragging: bool,
+        invalid: Option<bool>,
+        text_size: u16,
+        style: &Self::Style,
+    ) -> Self::Output;
+}
+
+impl<B> WidgetRenderer for iced_graphics::Renderer<B>
+where B: Backend + iced_graphics::backend::Text
+{
+    type Style = Box<dyn text_input::StyleSheet>;
+
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        cursor_position: Point,
+        label: Option<&str>,
+        text: &str,
+        dragging: bool,
+        invalid: Option<bool>,
+        text_size: u16,
+        style: &Self::Style,
+    ) -> Self::Output {
+        let padding = (bounds.height - f32::from(text_size)) / 2.0;
+        let is_mouse_over = bounds.contains(cursor_position);
+        let editing = invalid.is_some();
+        let field_style = if editing || dragging {
+            style.focused()
+        } else if is_mouse_over {
+            style.hovered()
+        } else {
+            style.active()
+        };
+        let mut primitives = vec![Primitive::Quad {
+            bounds,
+            background: field_style.background,
+            border_radius: field_style.border_radius,
+            border_width: field_style.border_width,
+            // Rejected text is signaled by the border, in the color of the text.
+            border_color: if invalid == Some(true) { style.value_color() } else { field_style.border_color },
+        }];
+
+        if let Some(label) = label {
+            primitives.push(Primitive::Text {
+                content: label.to_string(),
+                bounds: Rectangle { x: bounds.x + padding, y: bounds.center_y(), ..bounds },
+                color: style.placeholder_color(),
+                size: f32::from(text_size),
+                font: Font::Default,
+                horizontal_alignment: HorizontalAlignment::Left,
+                vertical_alignment: VerticalAlignment::Center,
+            });
+        }
+
+        // The value is aligned opposite the label.
+        primitives.push(Primitive::Text {
+            content: text.to_string(),
+            bounds: Rectangle { x: bounds.x + bounds.width - padding, y: bounds.center_y(), ..bounds },
+            color: style.value_color(),
+            size: f32::from(text_size),
+            font: Font::Default,
+            horizontal_alignment: HorizontalAlignment::Right,
+            vertical_alignment: VerticalAlignment::Center,
+        });
+
+        let mouse_interaction = if editing {
+            mouse::Interaction::Text
+        } else if dragging || is_mouse_over {
+            mouse::Interaction::ResizingHorizontally
+        } else {
+            mouse::Interaction::default()
+        };
+
+        (Primitive::Group { primitives }, mouse_interaction)
+    }
+}
@@ -0,0 +1,474 @@
+use iced::pick_list;
+use iced_graphics::{self, Backend, Color, Primitive, Rectangle};
+use iced_native::event::Status;
+use iced_native::keyboard::{Event as KeyboardEvent, KeyCode};
+use iced_native::layout::{Layout, Limits, Node};
+use iced_native::mouse::{self, Button as MouseButton, Event as MouseEvent};
+use iced_native::overlay::{self, Overlay};
+use iced_native::widget::Widget;
+use iced_native::{self, Clipboard, Element, Event, Font, Hasher, Length, Point, Size};
+use iced_native::{HorizontalAlignment, VerticalAlignment};
+use ordered_float::OrderedFloat;
+use std::fmt::Display;
+use std::hash::Hash;
+
+/// The state of an [`EnumDropdown`], to be stored in the node behaviour.
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    /// Whether the dropdown receives keyboard input. Gained by clicking the dropdown and lost by
+    /// clicking anywhere else.
+    focused: bool,
+    /// The index of the highlighted option, while the menu is open.
+    menu: Option<usize>,
+}
+
+/// A dropdown for selecting one of the values of an enum, like `PickList`, but also operable with
+/// the keyboard: Enter or Space opens the menu, the arrows move the highlight, Enter or Space
+/// selects the highlighted value and Escape closes the menu.
+pub struct EnumDropdown<'a, T, M, R: WidgetRenderer> {
+    state: &'a mut State,
+    options: &'a [T],
+    selected: T,
+    on_select: Box<dyn Fn(T) -> M>,
+    width: Length,
+    padding: u16,
+    text_size: Option<u16>,
+    style: R::Style,
+}
+
+impl<'a, T, M, R> EnumDropdown<'a, T, M, R>
+where
+    T: 'static + Copy + Eq + Display,
+    R: WidgetRenderer,
+{
+    pub fn new(
+        state: &'a mut State,
+        options: &'a [T],
+        selected: T,
+        on_select: impl Fn(T) -> M + 'static,
+    ) -> Self {
+        Self {
+            state,
+            options,
+            selected,
+            on_select: Box::new(on_select),
+            width: Length::Shrink,
+            padding: 0,
+            text_size: None,
+            style: Default::default(),
+        }
+    }
+
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn padding(mut self, padding: u16) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn text_size(mut self, text_size: u16) -> Self {
+        self.text_size = Some(text_size);
+        self
+    }
+
+    pub fn style(mut self, style: impl Into<R::Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    fn open_menu(&mut self) {
+        let selected = self.selected;
+
+        self.state.menu = Some(self.options.iter().position(|option| *option == selected).unwrap_or(0));
+    }
+}
+
+impl<'a, T, M, R> Widget<M, R> for EnumDropdown<'a, T, M, R>
+where
+    T: 'static + Copy + Eq + Display,
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn layout(&self, renderer: &R, limits: &Limits) -> Node {
+        let text_size = self.text_size.unwrap_or(renderer.default_size());
+        let label_width = self
+            .options
+            .iter()
+            .map(|option| {
+                renderer.measure(&option.to_string(), text_size, Default::default(), Size::INFINITY).0
+            })
+            .fold(0.0, f32::max);
+        // Leave room for the arrow icon on the right
+        let intrinsic_size = Size::new(
+            label_width + f32::from(text_size) + f32::from(self.padding) * 2.0,
+            f32::from(text_size) + f32::from(self.padding) * 2.0,
+        );
+
+        Node::new(limits.width(self.width).height(Length::Shrink).resolve(intrinsic_size))
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut R,
+        _defaults: &R::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) -> R::Output {
+        renderer.draw_head(
+            layout.bounds(),
+            cursor_position,
+            &self.selected.to_string(),
+            self.state.focused,
+            self.text_size.unwrap_or(renderer.default_size()),
+            &self.style,
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.width.hash(state);
+        self.padding.hash(state);
+        self.text_size.hash(state);
+
+        if self.width == Length::Shrink {
+            for option in self.options {
+                option.to_string().hash(state);
+            }
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<M>,
+        _renderer: &R,
+        _clipboard: Option<&dyn Clipboard>,
+    ) -> Status {
+        match event {
+            Event::Mouse(MouseEvent::ButtonPressed(button)) => {
+                if button == MouseButton::Left && layout.bounds().contains(cursor_position) {
+                    self.state.focused = true;
+
+                    if self.state.menu.is_some() {
+                        self.state.menu = None;
+                    } else {
+                        self.open_menu();
+                    }
+
+                    return Status::Captured;
+                }
+
+                // Clicks on the options of the menu are handled by the overlay beforehand.
+                self.state.focused = false;
+                self.state.menu = None;
+            }
+            Event::Keyboard(KeyboardEvent::KeyPressed { key_code, .. }) if self.state.focused => {
+                match (key_code, self.state.menu) {
+                    (KeyCode::Enter, None) | (KeyCode::Space, None) => self.open_menu(),
+                    (KeyCode::Enter, Some(index)) | (KeyCode::Space, Some(index)) => {
+                        if let Some(option) = self.options.get(index) {
+                            messages.push((self.on_select)(*option));
+                        }
+
+                        self.state.menu = None;
+                    }
+                    (KeyCode::Up, Some(index)) => self.state.menu = Some(index.saturating_sub(1)),
+                    (KeyCode::Down, Some(index)) => {
+                        self.state.menu = Some(std::cmp::min(index + 1, self.options.len().saturating_sub(1)))
+                    }
+                    (KeyCode::Escape, Some(_)) => self.state.menu = None,
+                    _ => return Status::Ignored,
+                }
+
+                return Status::Captured;
+            }
+            _ => (),
+        }
+
+        Status::Ignored
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<overlay::Element<'_, M, R>> {
+        if self.state.menu.is_none() {
+            return None;
+        }
+
+        let bounds = layout.bounds();
+        let menu = Menu::<T, M, R> {
+            state: &mut *self.state,
+            options: self.options,
+            on_select: &*self.on_select,
+            width: bounds.width,
+            target_height: bounds.height,
+            padding: self.padding,
+            text_size: self.text_size,
+            style: &self.style,
+        };
+
+        Some(overlay::Element::new(Point::new(bounds.x, bounds.y + bounds.height), Box::new(menu)))
+    }
+}
+
+impl<'a, T, M, R> From<EnumDropdown<'a, T, M, R>> for Element<'a, M, R>
+where
+    T: 'static + Copy + Eq + Display,
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn from(other: EnumDropdown<'a, T, M, R>) -> Self {
+        Element::new(other)
+    }
+}
+
+/// The list of options of an open [`EnumDropdown`].
+struct Menu<'a, T, M, R: WidgetRenderer> {
+    state: &'a mut State,
+    options: &'a [T],
+    on_select: &'a dyn Fn(T) -> M,
+    width: f32,
+    /// The height of the dropdown, used to place the menu above the dropdown, if there is not
+    /// enough room below it.
+    target_height: f32,
+    padding: u16,
+    text_size: Option<u16>,
+    style: &'a R::Style,
+}
+
+impl<'a, T, M, R: WidgetRenderer> Menu<'a, T, M, R> {
+    fn option_height(&self, renderer: &R) -> f32 {
+        f32::from(self.text_size.unwrap_or(renderer.default_size())) + f32::from(self.padding) * 2.0
+    }
+}
+
+impl<'a, T, M, R> Overlay<M, R> for Menu<'a, T, M, R>
+where
+    T: 'static + Copy + Eq + Display,
+    R: WidgetRenderer,
+{
+    fn layout(&self, renderer: &R, bounds: Size, mut position: Point) -> Node {
+        let size = Size::new(self.width, self.option_height(renderer) * self.options.len() as f32);
+        let above = position.y - self.target_height - size.height;
+
+        if position.y + size.height > bounds.height && above >= 0.0 {
+            position.y = above;
+        }
+
+        let mut node = Node::new(size);
+
+        node.move_to(position);
+
+        node
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut R,
+        _defaults: &R::Defaults,
+        layout: Layout<'_>,
+        _cursor_position: Point,
+    ) -> R::Output {
+        let labels = self.options.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        renderer.draw_menu(
+            layout.bounds(),
+            &labels,
+            self.state.menu,
+            self.padding,
+            self.text_size.unwrap_or(renderer.default_size()),
+            self.style,
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher, position: Point) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        OrderedFloat::from(position.x).hash(state);
+        OrderedFloat::from(position.y).hash(state);
+        OrderedFloat::from(self.width).hash(state);
+        OrderedFloat::from(self.target_height).hash(state);
+        self.options.len().hash(state);
+        self.padding.hash(state);
+        self.text_size.hash(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<M>,
+        renderer: &R,
+        _clipboard: Option<&dyn Clipboard>,
+    ) -> Status {
+        let bounds = layout.bounds();
+        let hovered_index = if bounds.contains(cursor_position) {
+            Some(((cursor_position.y - bounds.y) / self.option_height(renderer)) as usize)
+                .filter(|index| *index < self.options.len())
+        } else {
+            None
+        };
+
+        match event {
+            Event::Mouse(MouseEvent::CursorMoved { .. }) => {
+                if let Some(index) = hovered_index {
+                    self.state.menu = Some(index);
+                }
+            }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) => {
+                if let Some(index) = hovered_index {
+                    messages.push((self.on_select)(self.options[index]));
+                    self.state.menu = None;
+
+                    return Status::Captured;
+                }
+            }
+            _ => (),
+        }
+
+        Status::Ignored
+    }
+}
+
+pub trait WidgetRenderer: iced_native::Renderer + iced_native::text::Renderer + Sized {
+    type Style: Default;
+
+    fn draw_head(
+        &mut self,
+        bounds: Rectangle,
+        cursor_position: Point,
+        label: &str,
+        focused: bool,
+        text_size: u16,
+        style: &Self::Style,
+    ) -> Self::Output;
+
+    fn draw_menu(
+        &mut self,
+        bounds: Rectangle,
+        labels: &[String],
+        highlighted_index: Option<usize>,
+        padding: u16,
+        text_size: u16,
+        style: &Self::Style,
+    ) -> Self::Output;
+}
+
+impl<B> WidgetRenderer for iced_graphics::Renderer<B>
+where B: Backend + iced_graphics::backend::Text
+{
+    type Style = Box<dyn pick_list::StyleSheet>;
+
+    fn draw_head(
+        &mut self,
+        bounds: Rectangle,
+        cursor_position: Point,
+        label: &str,
+        focused: bool,
+        text_size: u16,
+        style: &Self::Style,
+    ) -> Self::Output {
+        // The dropdown is exactly as tall as the text with the padding around it.
+        let padding = (bounds.height - f32::from(text_size)) / 2.0;
+        let is_mouse_over = bounds.contains(cursor_position);
+        // The focus is signaled the same way as hovering, there is no dedicated style for it.
+        let style = if is_mouse_over || focused { style.hovered() } else { style.active() };
+        let background = Primitive::Quad {
+            bounds,
+            background: style.background,
+            border_radius: style.border_radius,
+            border_width: style.border_width,
+            border_color: style.border_color,
+        };
+        let label = Primitive::Text {
+            content: label.to_string(),
+            bounds: Rectangle { x: bounds.x + padding, y: bounds.center_y(), ..bounds },
+            color: style.text_color,
+            size: f32::from(text_size),
+            font: Font::Default,
+            horizontal_alignment: HorizontalAlignment::Left,
+            vertical_alignment: VerticalAlignment::Center,
+        };
+        let arrow_down = Primitive::Text {
+            content: B::ARROW_DOWN_ICON.to_string(),
+            bounds: Rectangle { x: bounds.x + bounds.width - padding, y: bounds.center_y(), ..bounds },
+            color: style.text_color,
+            size: bounds.height * style.icon_size,
+            font: B::ICON_FONT,
+            horizontal_alignment: HorizontalAlignment::Right,
+            vertical_alignment: VerticalAlignment::Center,
+        };
+        let mouse_interaction =
+            if is_mouse_over { mouse::Interaction::Pointer } else { mouse::Interaction::default() };
+
+        (Primitive::Group { primitives: vec![background, label, arrow_down] }, mouse_interaction)
+    }
+
+    fn draw_menu(
+        &mut self,
+        bounds: Rectangle,
+        labels: &[String],
+        highlighted_index: Option<usize>,
+        padding: u16,
+        text_size: u16,
+        style: &Self::Style,
+    ) -> Self::Output {
+        let style = style.menu();
+        let option_height = f32::from(text_size) + f32::from(padding) * 2.0;
+        let mut primitives = vec![Primitive::Quad {
+            bounds,
+            background: style.background,
+            border_radius: 0,
+            border_width: style.border_width,
+            border_color: style.border_color,
+        }];
+
+        for (index, label) in labels.iter().enumerate() {
+            let option_bounds =
+                Rectangle { y: bounds.y + option_height * index as f32, height: option_height, ..bounds };
+            let highlighted = highlighted_index == Some(index);
+
+            if highlighted {
+                primitives.push(Primitive::Quad {
+                    bounds: option_bounds,
+                    background: style.selected_background,
+                    border_radius: 0,
+                    border_width: 0,
+                    border_color: Color::TRANSPARENT,
+                });
+            }
+
+            primitives.push(Primitive::Text {
+                content: label.clone(),
+                bounds: Rectangle {
+                    x: option_bounds.x + f32::from(padding),
+                    y: option_bounds.center_y(),
+                    ..option_bounds
+                },
+                color: if highlighted { style.selected_text_color } else { style.text_color },
+                size: f32::from(text_size),
+                font: Font::Default,
+                horizontal_alignment: HorizontalAlignment::Left,
+                vertical_alignment: VerticalAlignment::Center,
+            });
+        }
+
+        (Primitive::Group { primitives }, mouse::Interaction::Pointer)
+    }
+}
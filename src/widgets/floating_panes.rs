@@ -1,19 +1,32 @@
 use super::*;
 use crate::style::InteractionStatus;
-use crate::util::RectangleExt;
+use crate::util::{self, RectangleExt};
 use iced_graphics::{self, Backend, Background, Color, Primitive, Rectangle};
 use iced_native::event::Status;
+use iced_native::keyboard::{self, Event as KeyboardEvent, KeyCode};
 use iced_native::layout::{Layout, Limits, Node};
-use iced_native::mouse::{self, Button as MouseButton, Event as MouseEvent};
+use iced_native::mouse::{self, Button as MouseButton, Event as MouseEvent, ScrollDelta};
 use iced_native::widget::{Container, Widget};
-use iced_native::{self, Clipboard, Column, Event, Hasher, Length, Point, Size, Text};
+use iced_native::{self, Align, Clipboard, Column, Event, Hasher, Length, Point, Row, Size, Text};
 use iced_native::{overlay, Element};
 use indexmap::IndexMap;
 use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
 use vek::Vec2;
 
+/// The distance in pixels panned or scrolled per line scrolled by a scroll wheel.
+pub const SCROLL_LINE_LENGTH: f32 = 40.0;
+/// The longest time between two presses of a title bar, which toggles the collapsed display mode
+/// of the pane.
+const DOUBLE_CLICK_DURATION: Duration = Duration::from_millis(400);
+/// The lowest opacity panes can be drawn with, so that they never disappear entirely.
+pub const MIN_PANE_OPACITY: f32 = 0.1;
+/// Passed to the panes below the pane capturing the cursor instead of the position of the cursor.
+const HIDDEN_CURSOR_POSITION: Point = Point { x: f32::NEG_INFINITY, y: f32::NEG_INFINITY };
+
 pub struct ContentDrawResult<R: WidgetRenderer> {
     pub override_parent_cursor: bool,
     pub output: R::Output,
@@ -21,7 +34,7 @@ pub struct ContentDrawResult<R: WidgetRenderer> {
 
 /// A widget-like trait for customizing the behaviour of the [`FloatingPanes`] widget
 pub trait FloatingPanesBehaviour<'a, M: 'a, R: 'a + WidgetRenderer>: Sized {
-    type FloatingPaneIndex: Hash + Eq;
+    type FloatingPaneIndex: Hash + Eq + Clone;
 
     /// Additional data passed by value during construction of each pane.
     /// Custom data to pass to the FloatingPanes widget (shared by all floating panes) can be
@@ -88,14 +101,14 @@ impl<'a, M: 'a, B: 'a + Backend + iced_graphics::backend::Text>
             output: (
                 Primitive::Group {
                     primitives: panes
-                        .children
-                        .iter()
-                        .zip(layout.panes())
-                        .map(|((_, child), layout)| {
-                            let (primitive, new_mouse_interaction) = child.element_tree.draw(
+                        .z_order()
+                        .into_iter()
+                        .map(|layout_index| {
+                            let (_, child) = panes.children.get_index(layout_index).unwrap();
+                            let (mut primitive, new_mouse_interaction) = child.element_tree.draw(
                                 renderer,
                                 defaults,
-                                layout.into(),
+                                layout.pane_with_index(layout_index).into(),
                                 cursor_position,
                                 viewport,
                             );
@@ -104,6 +117,10 @@ impl<'a, M: 'a, B: 'a + Backend + iced_graphics::backend::Text>
                                 mouse_interaction = new_mouse_interaction;
                             }
 
+                            if child.state.opacity < 1.0 {
+                                util::fade_primitive(&mut primitive, child.state.opacity);
+                            }
+
                             primitive
                         })
                         .collect(),
@@ -167,6 +184,14 @@ pub struct FloatingPaneBuilder<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + Floati
     pub title_size: Option<u16>,
     pub title_margin: Spacing,
     pub style: Option<<R as WidgetRenderer>::StyleFloatingPane>,
+    /// A color to blend the style of the pane with
+    pub tint: Option<Color>,
+    /// A glyph displayed before the title
+    pub icon: Option<char>,
+    /// Displayed at the end of the title bar, if the pane has a title
+    pub title_controls: Option<Element<'a, M, R>>,
+    /// Whether the pane is displayed dimmed, e.g. because its content is inactive
+    pub dimmed: bool,
     /// Whether the floating pane is resizeable in each axis
     pub min_size: Vec2<f32>,
     pub resizeable: Vec2<bool>,
@@ -191,6 +216,10 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
             title_size: Default::default(),
             title_margin: Default::default(),
             style: Default::default(),
+            tint: Default::default(),
+            icon: Default::default(),
+            title_controls: Default::default(),
+            dimmed: Default::default(),
             min_size: [0.0, 0.0].into(),
             resizeable: Default::default(),
             __marker: Default::default(),
@@ -218,6 +247,26 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
         self
     }
 
+    pub fn tint(mut self, tint: Option<Color>) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    pub fn icon(mut self, icon: Option<char>) -> Self {
+        self.icon = icon;
+        self
+    }
+
+    pub fn title_controls(mut self, title_controls: Option<impl Into<Element<'a, M, R>>>) -> Self {
+        self.title_controls = title_controls.map(Into::into);
+        self
+    }
+
+    pub fn dimmed(mut self, dimmed: bool) -> Self {
+        self.dimmed = dimmed;
+        self
+    }
+
     pub fn min_width(mut self, min_width: f32) -> Self {
         self.min_size[0] = min_width;
         self
@@ -241,32 +290,55 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
     pub fn build(mut self) -> FloatingPane<'a, M, R, C> {
         FloatingPane {
             behaviour_data: self.behaviour_data,
+            tint: self.tint,
+            dimmed: self.dimmed,
             min_size: self.min_size,
             resizeable: self.resizeable,
             element_tree: {
                 let mut column = Column::<M, R>::new();
 
                 if let Some(title) = self.title.take() {
-                    let mut text = Text::new(title.to_string());
+                    let mut text = Text::new(if let Some(icon) = self.icon {
+                        format!("{} {}", icon, title)
+                    } else {
+                        title.to_string()
+                    });
 
                     if let Some(title_size) = self.title_size.take() {
                         text = text.size(title_size);
                     }
 
-                    column = column.push(Margin::new(text, self.title_margin.clone()));
+                    column = match self.title_controls.take() {
+                        Some(title_controls) => column.push(Margin::new(
+                            Row::new()
+                                .push(text.width(Length::Fill))
+                                .push(title_controls)
+                                .align_items(Align::Center)
+                                .width(Length::Fill),
+                            self.title_margin.clone(),
+                        )),
+                        None => column.push(Margin::new(text, self.title_margin.clone())),
+                    };
                 }
 
                 let mut element_container = Container::new(self.content);
 
                 if let Some(style) = self.style.as_ref() {
-                    element_container =
-                        element_container.style(style.content_container_style(self.state.title_bar_status));
+                    element_container = element_container.style(style.content_container_style(
+                        self.state.title_bar_status,
+                        self.tint,
+                        self.dimmed,
+                    ));
                 }
 
                 let mut container = Container::new(column.push(element_container));
 
                 if let Some(style) = self.style.as_ref() {
-                    container = container.style(style.root_container_style(self.state.title_bar_status));
+                    container = container.style(style.root_container_style(
+                        self.state.title_bar_status,
+                        self.tint,
+                        self.dimmed,
+                    ));
                 }
 
                 container = match self.state.size[0] {
@@ -275,6 +347,8 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
                 };
 
                 container = match self.state.size[1] {
+                    // Previewed panes fit their content, rather than the height they are resized to.
+                    _ if self.state.collapsed => container,
                     FloatingPaneLength::Shrink => container,
                     FloatingPaneLength::Units(units) => container.height(Length::Units(units)),
                 };
@@ -288,11 +362,48 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct FloatingPaneState {
     pub position: Vec2<f32>,
     pub size: Vec2<FloatingPaneLength>,
     pub title_bar_status: InteractionStatus,
+    /// Whether only the title bar and a compact row of the connection points is displayed. The
+    /// height of collapsed panes is not fixed.
+    pub collapsed: bool,
+    /// Whether the full content of the collapsed pane is displayed while the cursor stays on the
+    /// pane, see [`LayoutChange::CollapsedPaneHovered`]. Not persisted.
+    pub previewed: bool,
+    /// Whether the pane is drawn and hit-tested above all panes that are not pinned.
+    pub pinned: bool,
+    /// The opacity the pane is drawn with, from 0 to 1. Translucent panes still capture the cursor,
+    /// unless they are `click_through`.
+    pub opacity: f32,
+    /// Whether the content of the pane lets the cursor through to whatever is below it. The title
+    /// bar still captures the cursor, so that the pane can be moved and its context menu opened.
+    pub click_through: bool,
+    /// When the pane was last pressed, see [`FloatingPanesState::last_raised`]. Panes pressed more
+    /// recently are above the others.
+    pub raised: u64,
+    /// The size of the pane in the most recently computed layout, zero until the pane receives an
+    /// event.
+    pub laid_out_size: Vec2<f32>,
+}
+
+impl Default for FloatingPaneState {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            size: Default::default(),
+            title_bar_status: Default::default(),
+            collapsed: false,
+            previewed: false,
+            pinned: false,
+            opacity: 1.0,
+            click_through: false,
+            raised: 0,
+            laid_out_size: Default::default(),
+        }
+    }
 }
 
 impl Hash for FloatingPaneState {
@@ -301,6 +412,11 @@ impl Hash for FloatingPaneState {
         self.position.map(OrderedFloat::from).as_slice().hash(state);
         self.size.hash(state);
         self.title_bar_status.hash(state);
+        self.collapsed.hash(state);
+        self.previewed.hash(state);
+        self.pinned.hash(state);
+        OrderedFloat(self.opacity).hash(state);
+        self.click_through.hash(state);
     }
 }
 
@@ -323,6 +439,16 @@ impl FloatingPaneState {
         self.size[1] = height.into();
         self
     }
+
+    pub fn with_collapsed(mut self, collapsed: bool) -> Self {
+        self.collapsed = collapsed;
+        self
+    }
+
+    /// Whether the pane is displayed collapsed, which a previewed pane is not.
+    pub fn displays_collapsed(&self) -> bool {
+        self.collapsed && !self.previewed
+    }
 }
 
 /// A single floating pane within the [`FloatingPanes`] widget.
@@ -330,6 +456,8 @@ pub struct FloatingPane<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanes
     pub state: &'a mut FloatingPaneState,
     pub behaviour_data: C::FloatingPaneBehaviourData,
     pub style: Option<<R as WidgetRenderer>::StyleFloatingPane>,
+    pub tint: Option<Color>,
+    pub dimmed: bool,
     pub element_tree: Element<'a, M, R>,
     pub min_size: Vec2<f32>,
     pub resizeable: Vec2<bool>,
@@ -541,12 +669,152 @@ impl Gesture {
     }
 }
 
+/// Arranges panes relative to each other, see [`PaneAlignment::positions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PaneAlignment {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// Centers the panes on a vertical line through the center of their bounding box.
+    HorizontalCenters,
+    /// Centers the panes on a horizontal line through the center of their bounding box.
+    VerticalCenters,
+    /// Moves the panes between the leftmost and the rightmost one, so that the horizontal gaps
+    /// between neighbouring panes are equal.
+    DistributeHorizontally,
+    /// Moves the panes between the topmost and the bottommost one, so that the vertical gaps
+    /// between neighbouring panes are equal.
+    DistributeVertically,
+}
+
+impl PaneAlignment {
+    /// The index of the axis the panes are moved along.
+    pub fn axis(self) -> usize {
+        use PaneAlignment::*;
+        match self {
+            Left | Right | HorizontalCenters | DistributeHorizontally => 0,
+            Top | Bottom | VerticalCenters | DistributeVertically => 1,
+        }
+    }
+
+    /// The positions of the panes once aligned, given the positions and the sizes of the panes as
+    /// `(position, size)` pairs. The panes are only moved along [`Self::axis`].
+    ///
+    /// Distributing fewer than three panes leaves them in place. Panes wider than the span they
+    /// are distributed in are overlapped evenly instead. Panes with equal positions are ordered
+    /// by their indices, so that the result does not depend on the sort.
+    pub fn positions(self, panes: &[(Vec2<f32>, Vec2<f32>)]) -> Vec<Vec2<f32>> {
+        use PaneAlignment::*;
+        let axis = self.axis();
+        let mut positions = panes.iter().map(|(position, _)| *position).collect::<Vec<_>>();
+        let start = panes.iter().map(|(position, _)| position[axis]).fold(f32::INFINITY, f32::min);
+        let end = panes
+            .iter()
+            .map(|(position, size)| position[axis] + size[axis])
+            .fold(f32::NEG_INFINITY, f32::max);
+
+        match self {
+            Left | Top => {
+                for position in &mut positions {
+                    position[axis] = start;
+                }
+            }
+            Right | Bottom => {
+                for (position, (_, size)) in positions.iter_mut().zip(panes) {
+                    position[axis] = end - size[axis];
+                }
+            }
+            HorizontalCenters | VerticalCenters => {
+                let center = (start + end) / 2.0;
+
+                for (position, (_, size)) in positions.iter_mut().zip(panes) {
+                    position[axis] = center - size[axis] / 2.0;
+                }
+            }
+            DistributeHorizontally | DistributeVertically => {
+                if panes.len() < 3 {
+                    return positions;
+                }
+
+                let mut order = (0..panes.len()).collect::<Vec<_>>();
+
+                order.sort_by_key(|index| (OrderedFloat(panes[*index].0[axis]), *index));
+
+                // The outer panes stay in place.
+                let first = &panes[order[0]];
+                let last = &panes[order[order.len() - 1]];
+                let span = last.0[axis] + last.1[axis] - first.0[axis];
+                let total_size: f32 = panes.iter().map(|(_, size)| size[axis]).sum();
+                let gap = (span - total_size) / (panes.len() - 1) as f32;
+
+                if !gap.is_finite() {
+                    return positions;
+                }
+
+                let mut cursor = first.0[axis] + first.1[axis] + gap;
+
+                for index in &order[1..order.len() - 1] {
+                    positions[*index][axis] = cursor;
+                    cursor += panes[*index].1[axis] + gap;
+                }
+            }
+        }
+
+        positions
+    }
+}
+
+/// The position and size of a pane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaneGeometry {
+    pub position: Vec2<f32>,
+    pub size: Vec2<FloatingPaneLength>,
+}
+
+/// A change of the layout made by a finished gesture, or by double-clicking the title bar of a pane,
+/// which collapses or expands it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutEdit<I> {
+    PaneMoved { pane: I, from: Vec2<f32>, to: Vec2<f32> },
+    PaneResized { pane: I, from: PaneGeometry, to: PaneGeometry },
+    PanesOffset { from: Vec2<f32>, to: Vec2<f32> },
+    PaneCollapsed { pane: I, collapsed: bool },
+}
+
+/// Reported through [`FloatingPanes::on_layout_change`]. A gesture is reported once when it
+/// begins and once when it ends, the changes in between only request the layout to be recomputed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutChange<I> {
+    /// The layout has to be recomputed, because only the mutable state of the widget has changed.
+    /// Requested at most once until the widget is rebuilt.
+    Update,
+    GestureBegin,
+    /// The edit made by the gesture, if it changed the layout at all.
+    GestureEnd(Option<LayoutEdit<I>>),
+    /// The cursor has moved onto a collapsed pane, whose content may be previewed after a while by
+    /// setting [`FloatingPaneState::previewed`], or off of it, which ends the preview.
+    CollapsedPaneHovered(Option<I>),
+}
+
 #[derive(Default, Debug)]
 pub struct FloatingPanesState {
     pub cursor_position: Vec2<f32>,
     /// The vector to offset all floating panes' positions by
     pub panes_offset: Vec2<f32>,
     pub gesture: Option<Gesture>,
+    /// The modifiers currently held, Shift turns vertical scrolling into horizontal panning.
+    pub modifiers: keyboard::ModifiersState,
+    /// Whether Space is held, which makes dragging pan the view even over panes.
+    pub space_held: bool,
+    /// Whether a message causing the widget to be rebuilt has been emitted since it was last built.
+    pub layout_update_requested: bool,
+    /// The index of the pane whose title bar was pressed last and when, to detect double-clicks.
+    pub last_title_bar_press: Option<(usize, Instant)>,
+    /// The index of the collapsed pane the cursor is on, see [`LayoutChange::CollapsedPaneHovered`].
+    pub hovered_collapsed_pane: Option<usize>,
+    /// Incremented whenever a pane is pressed, to order the panes by their most recent interaction.
+    pub last_raised: u64,
 }
 
 impl Hash for FloatingPanesState {
@@ -566,7 +834,9 @@ pub struct FloatingPanes<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPane
     pub extents: Vec2<u32>,
     pub style: Option<<R as WidgetRenderer>::StyleFloatingPanes>,
     pub children: IndexMap<C::FloatingPaneIndex, FloatingPane<'a, M, R, C>>,
-    pub on_layout_change: Box<dyn Fn() -> M>,
+    /// The size of the grid panes snap to when moved.
+    pub grid_size: Option<f32>,
+    pub on_layout_change: Box<dyn Fn(LayoutChange<C::FloatingPaneIndex>) -> M>,
 }
 
 impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>> FloatingPanes<'a, M, R, C> {
@@ -574,8 +844,11 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
         state: &'a mut FloatingPanesState,
         behaviour_state: &'a mut C::FloatingPanesBehaviourState,
         behaviour: C,
-        on_layout_change: Box<dyn Fn() -> M>,
+        on_layout_change: Box<dyn Fn(LayoutChange<C::FloatingPaneIndex>) -> M>,
     ) -> Self {
+        // The messages emitted since the last build have been processed.
+        state.layout_update_requested = false;
+
         Self {
             state,
             behaviour_state,
@@ -585,10 +858,16 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
             extents: [u32::MAX, u32::MAX].into(),
             style: None,
             children: Default::default(),
+            grid_size: None,
             on_layout_change,
         }
     }
 
+    pub fn grid_size(mut self, grid_size: Option<f32>) -> Self {
+        self.grid_size = grid_size;
+        self
+    }
+
     pub fn width(mut self, width: Length) -> Self {
         self.width = width;
         self
@@ -634,28 +913,201 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
         self.children.get_index_of(pane_index)
     }
 
+    /// The layout indices of the panes, from the bottommost to the topmost. Pinned panes are above
+    /// all others, otherwise the panes pressed more recently are above the ones pressed earlier.
+    pub fn z_order(&self) -> Vec<usize> {
+        let mut z_order = (0..self.children.len()).collect::<Vec<_>>();
+
+        // The sort is stable, so that panes never pressed keep the order they were inserted in.
+        z_order.sort_by_key(|layout_index| {
+            let (_, pane) = self.children.get_index(*layout_index).unwrap();
+
+            (pane.state.pinned, pane.state.raised)
+        });
+        z_order
+    }
+
+    /// The layout index of the topmost pane capturing the cursor at the point. Only the title bars
+    /// of click-through panes capture the cursor.
+    pub fn pane_at(&self, layout: FloatingPanesLayout<'_>, point: Point) -> Option<usize> {
+        self.z_order().into_iter().rev().find(|layout_index| {
+            let (_, pane) = self.children.get_index(*layout_index).unwrap();
+            let pane_layout = layout.pane_with_index(*layout_index);
+
+            pane_layout.bounds().contains(point)
+                && !(pane.state.click_through && pane_layout.content().bounds().contains(point))
+        })
+    }
+
+    /// Brings the pane above the other panes, except for pinned ones, unless it is pinned too.
+    fn raise(&mut self, layout_index: usize) {
+        if let Some((_, pane)) = self.children.get_index_mut(layout_index) {
+            self.state.last_raised += 1;
+            pane.state.raised = self.state.last_raised;
+        }
+    }
+
+    /// Emits the layout change, coalescing the requests to recompute the layout, so that only a
+    /// single message is emitted per batch of events.
+    fn push_layout_change(&mut self, change: LayoutChange<C::FloatingPaneIndex>, messages: &mut Vec<M>) {
+        if matches!(change, LayoutChange::Update) && self.state.layout_update_requested {
+            return;
+        }
+
+        self.state.layout_update_requested = true;
+        messages.push((self.on_layout_change)(change));
+    }
+
+    /// Toggles the collapsed display mode of the pane, if its title bar has been pressed twice within
+    /// [`DOUBLE_CLICK_DURATION`].
+    fn press_title_bar(&mut self, pane_index: usize) -> Option<LayoutEdit<C::FloatingPaneIndex>> {
+        let now = Instant::now();
+        let double_click = matches!(
+            self.state.last_title_bar_press,
+            Some((last_pane_index, time)) if last_pane_index == pane_index && now - time <= DOUBLE_CLICK_DURATION
+        );
+
+        if !double_click {
+            self.state.last_title_bar_press = Some((pane_index, now));
+            return None;
+        }
+
+        self.state.last_title_bar_press = None;
+
+        let (index, pane) = self.children.get_index_mut(pane_index)?;
+
+        pane.state.collapsed = !pane.state.collapsed;
+        pane.state.title_bar_status = InteractionStatus::Hovered;
+
+        Some(LayoutEdit::PaneCollapsed { pane: index.clone(), collapsed: pane.state.collapsed })
+    }
+
+    /// The edit made by the current gesture so far, if any.
+    fn get_gesture_edit(&self) -> Option<LayoutEdit<C::FloatingPaneIndex>> {
+        match self.state.gesture.as_ref()? {
+            Gesture::GrabPane { pane_index, grab_state } => {
+                let (index, pane) = self.children.get_index(*pane_index)?;
+                let from = grab_state.grab_element_position;
+                let to = pane.state.position;
+
+                if from != to {
+                    Some(LayoutEdit::PaneMoved { pane: index.clone(), from, to })
+                } else {
+                    None
+                }
+            }
+            Gesture::GrabBackground(grab_state) => {
+                let from = grab_state.grab_element_position;
+                let to = self.state.panes_offset;
+
+                if from != to {
+                    Some(LayoutEdit::PanesOffset { from, to })
+                } else {
+                    None
+                }
+            }
+            Gesture::ResizePane { pending: false, pane_index, grab_state, .. } => {
+                let (index, pane) = self.children.get_index(*pane_index)?;
+                let to = PaneGeometry { position: pane.state.position, size: pane.state.size };
+                // Only the lengths specified in units are resized.
+                let from = PaneGeometry {
+                    position: grab_state.grab_element_position,
+                    size: Vec2::new(0usize, 1).map(|component_index| match to.size[component_index] {
+                        FloatingPaneLength::Units(_) => {
+                            FloatingPaneLength::Units(grab_state.grab_element_size[component_index] as u16)
+                        }
+                        length => length,
+                    }),
+                };
+
+                if from != to {
+                    Some(LayoutEdit::PaneResized { pane: index.clone(), from, to })
+                } else {
+                    None
+                }
+            }
+            Gesture::ResizePane { pending: true, .. } => None,
+        }
+    }
+
+    /// Converts a distance on the screen to a distance in the coordinates of the panes, which
+    /// are the same as long as the view cannot be zoomed.
+    fn screen_to_panes_delta(&self, delta: Vec2<f32>) -> Vec2<f32> {
+        delta
+    }
+
+    /// Pans the view by the scrolled distance. Scroll wheels pan vertically, or horizontally with
+    /// Shift held, while touchpads report the distance in pixels along both axes.
+    fn pan_by_scroll(&mut self, delta: ScrollDelta, messages: &mut Vec<M>) {
+        let screen_delta = match delta {
+            ScrollDelta::Lines { x, y } if self.state.modifiers.shift => Vec2::new(y, x) * SCROLL_LINE_LENGTH,
+            ScrollDelta::Lines { x, y } => Vec2::new(x, y) * SCROLL_LINE_LENGTH,
+            ScrollDelta::Pixels { x, y } => Vec2::new(x, y),
+        };
+
+        if screen_delta == Vec2::zero() {
+            return;
+        }
+
+        self.state.panes_offset += self.screen_to_panes_delta(screen_delta);
+        self.push_layout_change(LayoutChange::Update, messages);
+    }
+
     pub fn update_pending_gestures(&mut self, layout: FloatingPanesLayout, messages: &mut Vec<M>) {
-        // Update the interaction status of title bars
-        for ((_, (_, pane)), pane_layout) in self.children.iter_mut().enumerate().zip(layout.panes()) {
+        let mut title_bar_status_changed = false;
+        let cursor_point: Point = self.state.cursor_position.into_array().into();
+        let topmost_pane = self.pane_at(layout, cursor_point);
+        let hovered_collapsed_pane = topmost_pane
+            .filter(|index| self.children.get_index(*index).map_or(false, |(_, pane)| pane.state.collapsed));
+
+        if hovered_collapsed_pane != self.state.hovered_collapsed_pane {
+            // The cursor has left the previewed pane.
+            if let Some((_, pane)) =
+                self.state.hovered_collapsed_pane.and_then(|index| self.children.get_index_mut(index))
+            {
+                if pane.state.previewed {
+                    pane.state.previewed = false;
+                    title_bar_status_changed = true;
+                }
+            }
+
+            let pane = hovered_collapsed_pane.map(|index| self.children.get_index(index).unwrap().0.clone());
+
+            self.state.hovered_collapsed_pane = hovered_collapsed_pane;
+            self.push_layout_change(LayoutChange::CollapsedPaneHovered(pane), messages);
+        }
+
+        // Update the interaction status of title bars, only the topmost one can be hovered
+        for ((index, (_, pane)), pane_layout) in self.children.iter_mut().enumerate().zip(layout.panes()) {
             let content_layout = pane_layout.content();
-            let pane_bounds = pane_layout.bounds();
-            let cursor_on_pane = pane_bounds.contains(self.state.cursor_position.into_array().into());
-            let cursor_on_title = cursor_on_pane
-                && !content_layout.bounds().contains(self.state.cursor_position.into_array().into());
+            let cursor_on_title =
+                topmost_pane == Some(index) && !content_layout.bounds().contains(cursor_point);
 
             let new_title_bar_status =
                 if cursor_on_title { InteractionStatus::Hovered } else { InteractionStatus::Idle };
 
             if new_title_bar_status != pane.state.title_bar_status {
                 pane.state.title_bar_status = new_title_bar_status;
-
-                messages.push((self.on_layout_change)());
+                title_bar_status_changed = true;
             }
         }
 
-        self.state.gesture = self.children.iter_mut().enumerate().zip(layout.panes()).find_map({
+        if title_bar_status_changed {
+            self.push_layout_change(LayoutChange::Update, messages);
+        }
+
+        // The edges of the topmost panes are grabbed first, panes below other panes cannot be resized
+        // through them.
+        self.state.gesture = self.z_order().into_iter().rev().find_map({
             let panes_state = &self.state;
-            move |((pane_index, (_, pane)), pane_layout)| {
+            let children = &self.children;
+            move |pane_index| {
+                if topmost_pane.map_or(false, |topmost_pane| topmost_pane != pane_index) {
+                    return None;
+                }
+
+                let (_, pane) = children.get_index(pane_index).unwrap();
+                let pane_layout = layout.pane_with_index(pane_index);
                 let resize_directions =
                     pane.get_pane_resize_directions(pane_layout, panes_state.cursor_position);
 
@@ -737,6 +1189,14 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
         for (_, child) in &self.children {
             child.state.hash(state);
             child.element_tree.hash_layout(state);
+
+            if let Some(tint) = child.tint {
+                for component in &[tint.r, tint.g, tint.b, tint.a] {
+                    OrderedFloat(*component).hash(state);
+                }
+            }
+
+            child.dimmed.hash(state);
         }
 
         C::hash_panes(&self, state);
@@ -753,17 +1213,36 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
     ) -> Status {
         let layout: FloatingPanesLayout = layout.into();
 
-        if C::on_event(self, event.clone(), layout, cursor_position, messages, renderer, clipboard)
-            == Status::Captured
+        // Kept for the edits computed outside of the widget, such as aligning panes.
+        for ((_, pane), pane_layout) in self.children.iter_mut().zip(layout.panes()) {
+            pane.state.laid_out_size = Into::<[f32; 2]>::into(pane_layout.bounds().size()).into();
+        }
+
+        match &event {
+            Event::Keyboard(KeyboardEvent::ModifiersChanged(modifiers)) => self.state.modifiers = *modifiers,
+            Event::Keyboard(KeyboardEvent::KeyPressed { key_code: KeyCode::Space, .. }) => {
+                self.state.space_held = true
+            }
+            Event::Keyboard(KeyboardEvent::KeyReleased { key_code: KeyCode::Space, .. }) => {
+                self.state.space_held = false
+            }
+            _ => (),
+        }
+
+        // Neither the behaviour nor the panes receive mouse events while Space is held, so that
+        // dragging pans the view regardless of what is under the cursor.
+        let space_panning = self.state.space_held && matches!(event, Event::Mouse(_));
+
+        if !space_panning
+            && C::on_event(self, event.clone(), layout, cursor_position, messages, renderer, clipboard)
+                == Status::Captured
         {
             return Status::Captured;
         }
 
         // Set to `true`, if the event should not be propagated to child panes.
-        let mut status = Status::Ignored;
+        let mut status = if space_panning { Status::Captured } else { Status::Ignored };
 
-        // TODO: Make it possible to bind keyboard/mouse buttons to pan regardless of whether the
-        // cursor is on top of a pane.
         match &event {
             Event::Mouse(MouseEvent::CursorMoved { x, y }) => {
                 self.state.cursor_position = [*x, *y].into();
@@ -771,21 +1250,37 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
                 match self.state.gesture.clone() {
                     Some(Gesture::GrabPane { pane_index, grab_state }) => {
                         if let Some((_, pane)) = self.children.get_index_mut(pane_index) {
-                            pane.state.position = self.state.cursor_position.as_::<f32>()
+                            let position = self.state.cursor_position.as_::<f32>()
                                 + grab_state.grab_element_position
                                 - grab_state.grab_mouse_position;
-                            messages.push((self.on_layout_change)());
+
+                            pane.state.position = if let Some(grid_size) = self.grid_size {
+                                position.map(|coord| (coord / grid_size).round() * grid_size)
+                            } else {
+                                position
+                            };
+                            self.push_layout_change(LayoutChange::Update, messages);
                         }
                     }
                     Some(Gesture::GrabBackground(grab_state)) => {
-                        self.state.panes_offset = self.state.cursor_position.as_::<f32>()
-                            + grab_state.grab_element_position
-                            - grab_state.grab_mouse_position;
-                        messages.push((self.on_layout_change)());
+                        self.state.panes_offset = grab_state.grab_element_position
+                            + self.screen_to_panes_delta(
+                                self.state.cursor_position - grab_state.grab_mouse_position,
+                            );
+                        self.push_layout_change(LayoutChange::Update, messages);
                     }
                     Some(Gesture::ResizePane { pending: false, pane_index, grab_state, directions }) => {
                         if let Some((_, pane)) = self.children.get_index_mut(pane_index) {
                             for component_index in 0..2 {
+                                // Panes fitting their content are resized from their current size
+                                if directions[component_index] != PaneResizeDirection::None
+                                    && pane.state.size[component_index] == FloatingPaneLength::Shrink
+                                {
+                                    pane.state.size[component_index] = FloatingPaneLength::Units(
+                                        grab_state.grab_element_size[component_index] as u16,
+                                    );
+                                }
+
                                 if let FloatingPaneLength::Units(pane_size) =
                                     &mut pane.state.size[component_index]
                                 {
@@ -824,7 +1319,7 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
                                 }
                             }
 
-                            messages.push((self.on_layout_change)());
+                            self.push_layout_change(LayoutChange::Update, messages);
                         }
                     }
                     _ => {
@@ -832,7 +1327,22 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
                     }
                 }
             }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) if space_panning => {
+                self.state.gesture = Some(Gesture::GrabBackground(GrabStateMove {
+                    grab_mouse_position: self.state.cursor_position,
+                    grab_element_position: self.state.panes_offset,
+                }));
+                self.push_layout_change(LayoutChange::GestureBegin, messages);
+            }
             Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) => {
+                let cursor_point: Point = self.state.cursor_position.into_array().into();
+                let pressed_pane = self.pane_at(layout, cursor_point);
+
+                // Pressing a pane brings it to the front
+                if let Some(pressed_pane) = pressed_pane {
+                    self.raise(pressed_pane);
+                }
+
                 self.state.gesture = self.children.iter_mut().enumerate().find_map({
                     let panes_state = &self.state;
                     move |(pane_index, (_, pane))| {
@@ -859,36 +1369,68 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
                 });
 
                 if self.state.gesture.is_none() {
-                    let mouse_on_top_of_pane = layout.panes().any({
-                        let panes_state = &self.state;
-                        let cursor_point: Point = panes_state.cursor_position.into_array().into();
-                        move |pane_layout| pane_layout.bounds().contains(cursor_point)
-                    });
-
-                    if !mouse_on_top_of_pane {
+                    // The content of click-through panes lets the view be panned.
+                    if pressed_pane.is_none() {
                         self.state.gesture = Some(Gesture::GrabBackground(GrabStateMove {
                             grab_mouse_position: self.state.cursor_position,
                             grab_element_position: self.state.panes_offset,
                         }));
                     }
-                } else {
-                    messages.push((self.on_layout_change)());
+                }
+
+                // A double-click toggles the pane instead of moving it.
+                if let Some(Gesture::GrabPane { pane_index, .. }) = self.state.gesture {
+                    if let Some(edit) = self.press_title_bar(pane_index) {
+                        self.state.gesture = None;
+                        self.push_layout_change(LayoutChange::GestureEnd(Some(edit)), messages);
+                    }
+                }
+
+                if self.state.gesture.is_some() {
+                    self.push_layout_change(LayoutChange::GestureBegin, messages);
                 }
             }
             Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)) => {
+                let gesture_active = match self.state.gesture {
+                    Some(Gesture::ResizePane { pending, .. }) => !pending,
+                    Some(_) => true,
+                    None => false,
+                };
+
+                if gesture_active {
+                    let edit = self.get_gesture_edit();
+
+                    self.push_layout_change(LayoutChange::GestureEnd(edit), messages);
+                }
+
                 self.update_pending_gestures(layout, messages);
             }
             _ => (),
         }
 
         if status == Status::Ignored {
-            status = self.children.iter_mut().zip(layout.panes()).fold(
+            // Presses and scrolling only reach the topmost pane capturing the cursor, so that the
+            // widgets of the panes below do not react to them too.
+            let pointed_pane = match &event {
+                Event::Mouse(MouseEvent::ButtonPressed(_))
+                | Event::Mouse(MouseEvent::WheelScrolled { .. }) => {
+                    Some(self.pane_at(layout, cursor_position))
+                }
+                _ => None,
+            };
+
+            status = self.children.iter_mut().zip(layout.panes()).enumerate().fold(
                 Status::Ignored,
-                |status, ((_, pane), pane_layout)| {
+                |status, (pane_index, ((_, pane), pane_layout))| {
+                    let pane_cursor_position = match pointed_pane {
+                        Some(pointed_pane) if pointed_pane != Some(pane_index) => HIDDEN_CURSOR_POSITION,
+                        _ => cursor_position,
+                    };
+
                     status.merge(pane.element_tree.on_event(
                         event.clone(),
                         pane_layout.into(),
-                        cursor_position,
+                        pane_cursor_position,
                         messages,
                         renderer,
                         clipboard,
@@ -897,6 +1439,12 @@ impl<'a, M: 'a, R: 'a + WidgetRenderer, C: 'a + FloatingPanesBehaviour<'a, M, R>
             );
         }
 
+        // Panes get to handle scrolling first, the view is panned only if they do not.
+        if let (Status::Ignored, Event::Mouse(MouseEvent::WheelScrolled { delta })) = (status, &event) {
+            self.pan_by_scroll(*delta, messages);
+            status = Status::Captured;
+        }
+
         status
     }
 
@@ -920,6 +1468,7 @@ pub trait WidgetRenderer:
     + iced_native::Renderer
     + iced_native::text::Renderer
     + iced_native::column::Renderer
+    + iced_native::row::Renderer
     + iced_native::widget::container::Renderer
     + Sized
 {
@@ -999,15 +1548,57 @@ pub trait StyleFloatingPaneBounds<R: WidgetRenderer> {
     fn root_container_style(
         &self,
         title_bar_status: InteractionStatus,
+        tint: Option<Color>,
+        dimmed: bool,
     ) -> <R as iced_native::widget::container::Renderer>::Style;
     fn content_container_style(
         &self,
         title_bar_status: InteractionStatus,
+        tint: Option<Color>,
+        dimmed: bool,
     ) -> <R as iced_native::widget::container::Renderer>::Style;
 }
 
 pub trait FloatingPaneStyleSheet {
     fn style(&self, title_bar_status: InteractionStatus) -> FloatingPaneStyle;
+
+    /// The style of a pane with a color tint. Themes may override this to customize how the tint
+    /// is blended with the base style.
+    fn tinted_style(&self, title_bar_status: InteractionStatus, tint: Color) -> FloatingPaneStyle {
+        let mut style = self.style(title_bar_status);
+        style.title_background_color = util::mix_colors(style.title_background_color, tint, 0.5);
+        style
+    }
+
+    /// Dims the style of a pane. Themes may override this to customize how inactive panes are
+    /// distinguished.
+    fn dimmed_style(&self, style: FloatingPaneStyle) -> FloatingPaneStyle {
+        let dim = |color: Color| Color { a: color.a * 0.5, ..color };
+
+        FloatingPaneStyle {
+            title_background_color: dim(style.title_background_color),
+            title_text_color: dim(style.title_text_color),
+            body_background_color: dim(style.body_background_color),
+        }
+    }
+
+    fn style_with_tint(
+        &self,
+        title_bar_status: InteractionStatus,
+        tint: Option<Color>,
+        dimmed: bool,
+    ) -> FloatingPaneStyle {
+        let style = match tint {
+            Some(tint) => self.tinted_style(title_bar_status, tint),
+            None => self.style(title_bar_status),
+        };
+
+        if dimmed {
+            self.dimmed_style(style)
+        } else {
+            style
+        }
+    }
 }
 
 impl<B> StyleFloatingPaneBounds<iced_graphics::Renderer<B>> for Box<dyn FloatingPaneStyleSheet>
@@ -1016,6 +1607,8 @@ where B: Backend + iced_graphics::backend::Text
     fn root_container_style(
         &self,
         title_bar_status: InteractionStatus,
+        tint: Option<Color>,
+        dimmed: bool,
     ) -> Box<(dyn iced::container::StyleSheet + 'static)> {
         struct StyleSheet(FloatingPaneStyle);
 
@@ -1029,12 +1622,14 @@ where B: Backend + iced_graphics::backend::Text
             }
         }
 
-        Box::new(StyleSheet(self.style(title_bar_status)))
+        Box::new(StyleSheet(self.style_with_tint(title_bar_status, tint, dimmed)))
     }
 
     fn content_container_style(
         &self,
         title_bar_status: InteractionStatus,
+        tint: Option<Color>,
+        dimmed: bool,
     ) -> Box<(dyn iced::container::StyleSheet + 'static)> {
         struct StyleSheet(FloatingPaneStyle);
 
@@ -1047,7 +1642,7 @@ where B: Backend + iced_graphics::backend::Text
             }
         }
 
-        Box::new(StyleSheet(self.style(title_bar_status)))
+        Box::new(StyleSheet(self.style_with_tint(title_bar_status, tint, dimmed)))
     }
 }
 
@@ -1095,3 +1690,89 @@ typed_layout! {
         },
     ],
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panes(geometries: &[[f32; 4]]) -> Vec<(Vec2<f32>, Vec2<f32>)> {
+        geometries
+            .iter()
+            .map(|[x, y, width, height]| (Vec2::new(*x, *y), Vec2::new(*width, *height)))
+            .collect()
+    }
+
+    fn coordinates(positions: Vec<Vec2<f32>>, axis: usize) -> Vec<f32> {
+        positions.into_iter().map(|position| position[axis]).collect()
+    }
+
+    #[test]
+    fn panes_are_aligned_to_the_bounding_box() {
+        let panes = panes(&[[0.0, 0.0, 100.0, 50.0], [200.0, 30.0, 50.0, 100.0], [80.0, 200.0, 120.0, 20.0]]);
+        let alignments = vec![
+            (PaneAlignment::Left, vec![0.0, 0.0, 0.0]),
+            (PaneAlignment::Right, vec![150.0, 200.0, 130.0]),
+            (PaneAlignment::HorizontalCenters, vec![75.0, 100.0, 65.0]),
+            (PaneAlignment::Top, vec![0.0, 0.0, 0.0]),
+            (PaneAlignment::Bottom, vec![170.0, 120.0, 200.0]),
+            (PaneAlignment::VerticalCenters, vec![85.0, 60.0, 100.0]),
+        ];
+
+        for (alignment, expected) in alignments {
+            let positions = alignment.positions(&panes);
+            let other_axis = 1 - alignment.axis();
+
+            assert_eq!(coordinates(positions.clone(), alignment.axis()), expected, "{:?}", alignment);
+            // The panes are only moved along the axis of the alignment.
+            assert_eq!(
+                coordinates(positions, other_axis),
+                coordinates(panes.iter().map(|(position, _)| *position).collect(), other_axis),
+            );
+        }
+    }
+
+    #[test]
+    fn distributing_equalizes_the_gaps() {
+        let panes = panes(&[[0.0, 0.0, 10.0, 10.0], [100.0, 0.0, 10.0, 10.0], [15.0, 0.0, 10.0, 10.0]]);
+
+        assert_eq!(
+            coordinates(PaneAlignment::DistributeHorizontally.positions(&panes), 0),
+            vec![0.0, 100.0, 50.0]
+        );
+        assert_eq!(
+            coordinates(PaneAlignment::DistributeVertically.positions(&panes), 1),
+            vec![0.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn distributing_panes_wider_than_the_span_overlaps_them_evenly() {
+        let panes = panes(&[[0.0, 0.0, 100.0, 10.0], [200.0, 0.0, 50.0, 10.0], [80.0, 0.0, 120.0, 10.0]]);
+
+        // The gaps are -10 each.
+        assert_eq!(
+            coordinates(PaneAlignment::DistributeHorizontally.positions(&panes), 0),
+            vec![0.0, 200.0, 90.0]
+        );
+    }
+
+    #[test]
+    fn distributing_orders_panes_with_equal_positions_by_their_indices() {
+        let panes = panes(&[[0.0, 0.0, 10.0, 10.0], [90.0, 0.0, 10.0, 10.0], [0.0, 0.0, 10.0, 10.0]]);
+
+        assert_eq!(
+            coordinates(PaneAlignment::DistributeHorizontally.positions(&panes), 0),
+            vec![0.0, 90.0, 45.0]
+        );
+    }
+
+    #[test]
+    fn distributing_fewer_than_three_panes_leaves_them_in_place() {
+        let panes = panes(&[[0.0, 0.0, 10.0, 10.0], [90.0, 40.0, 10.0, 10.0]]);
+        let original = panes.iter().map(|(position, _)| *position).collect::<Vec<_>>();
+
+        assert_eq!(PaneAlignment::DistributeHorizontally.positions(&panes), original);
+        assert_eq!(PaneAlignment::DistributeVertically.positions(&panes), original);
+        assert!(PaneAlignment::Left.positions(&[]).is_empty());
+    }
+}
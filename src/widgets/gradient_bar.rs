@@ -0,0 +1,288 @@
+use iced_graphics::{self, Backend, Color, Primitive, Rectangle};
+use iced_native::event::Status;
+use iced_native::layout::{Layout, Limits, Node};
+use iced_native::mouse::{self, Button as MouseButton, Event as MouseEvent};
+use iced_native::widget::Widget;
+use iced_native::{self, Clipboard, Element, Event, Hasher, Length, Point, Size};
+use std::hash::Hash;
+
+const BAR_HEIGHT: f32 = 16.0;
+const HANDLE_HEIGHT: f32 = 10.0;
+/// Also the horizontal margin of the bar, so that the handles at both ends are fully visible.
+const HANDLE_HALF_WIDTH: f32 = 5.0;
+
+/// The state of a [`GradientBar`], to be stored in the node behaviour.
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    /// The index of the handle being dragged.
+    dragging: Option<usize>,
+}
+
+/// A color stop displayed by a [`GradientBar`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Handle {
+    /// From 0 to 1.
+    pub position: f32,
+    pub color: Color,
+}
+
+/// The colors of the borders of the handles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub handle_border: Color,
+    pub selected_handle_border: Color,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self { handle_border: Color::WHITE, selected_handle_border: Color::WHITE }
+    }
+}
+
+/// Displays a gradient as a horizontal bar with a handle per color stop below it. Clicking a
+/// handle selects it, dragging it moves it and clicking anywhere else adds a stop.
+pub struct GradientBar<'a, M> {
+    state: &'a mut State,
+    /// The colors of the gradient sampled at regular intervals, from left to right.
+    samples: Vec<Color>,
+    handles: Vec<Handle>,
+    selected: Option<usize>,
+    on_select: Box<dyn Fn(usize) -> M>,
+    on_move: Box<dyn Fn(usize, f32) -> M>,
+    on_add: Box<dyn Fn(f32) -> M>,
+    width: Length,
+    style: Style,
+}
+
+impl<'a, M> GradientBar<'a, M> {
+    pub fn new(
+        state: &'a mut State,
+        samples: Vec<Color>,
+        handles: Vec<Handle>,
+        selected: Option<usize>,
+        on_select: impl Fn(usize) -> M + 'static,
+        on_move: impl Fn(usize, f32) -> M + 'static,
+        on_add: impl Fn(f32) -> M + 'static,
+    ) -> Self {
+        Self {
+            state,
+            samples,
+            handles,
+            selected,
+            on_select: Box::new(on_select),
+            on_move: Box::new(on_move),
+            on_add: Box::new(on_add),
+            width: Length::Fill,
+            style: Default::default(),
+        }
+    }
+
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// The position along the gradient under the cursor, clamped to the range from 0 to 1.
+    fn position(bounds: Rectangle, cursor_position: Point) -> f32 {
+        let width = (bounds.width - HANDLE_HALF_WIDTH * 2.0).max(1.0);
+
+        ((cursor_position.x - bounds.x - HANDLE_HALF_WIDTH) / width).max(0.0).min(1.0)
+    }
+
+    /// The handle under the cursor, the closest one if they overlap.
+    fn handle_at(&self, bounds: Rectangle, cursor_position: Point) -> Option<usize> {
+        let width = bounds.width - HANDLE_HALF_WIDTH * 2.0;
+
+        self.handles
+            .iter()
+            .enumerate()
+            .map(|(index, handle)| {
+                let x = bounds.x + HANDLE_HALF_WIDTH + handle.position * width;
+
+                (index, (x - cursor_position.x).abs())
+            })
+            .filter(|(_, distance)| *distance <= HANDLE_HALF_WIDTH)
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(index, _)| index)
+    }
+}
+
+impl<'a, M, R> Widget<M, R> for GradientBar<'a, M>
+where
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn layout(&self, _renderer: &R, limits: &Limits) -> Node {
+        let intrinsic_size = Size::new(HANDLE_HALF_WIDTH * 2.0, BAR_HEIGHT + HANDLE_HEIGHT);
+
+        Node::new(limits.width(self.width).height(Length::Shrink).resolve(intrinsic_size))
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut R,
+        _defaults: &R::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) -> R::Output {
+        let bounds = layout.bounds();
+        let mouse_interaction = if self.state.dragging.is_some() {
+            mouse::Interaction::Grabbing
+        } else if self.handle_at(bounds, cursor_position).is_some() {
+            mouse::Interaction::Grab
+        } else if bounds.contains(cursor_position) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        };
+
+        renderer.draw(bounds, &self.samples, &self.handles, self.selected, &self.style, mouse_interaction)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.width.hash(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<M>,
+        _renderer: &R,
+        _clipboard: Option<&dyn Clipboard>,
+    ) -> Status {
+        let bounds = layout.bounds();
+
+        match event {
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left))
+                if bounds.contains(cursor_position) =>
+            {
+                match self.handle_at(bounds, cursor_position) {
+                    Some(index) => {
+                        self.state.dragging = Some(index);
+                        messages.push((self.on_select)(index));
+                    }
+                    None => messages.push((self.on_add)(Self::position(bounds, cursor_position))),
+                }
+
+                Status::Captured
+            }
+            Event::Mouse(MouseEvent::CursorMoved { .. }) => match self.state.dragging {
+                Some(index) => {
+                    messages.push((self.on_move)(index, Self::position(bounds, cursor_position)));
+                    Status::Captured
+                }
+                None => Status::Ignored,
+            },
+            Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)) if self.state.dragging.is_some() => {
+                self.state.dragging = None;
+                Status::Captured
+            }
+            _ => Status::Ignored,
+        }
+    }
+}
+
+impl<'a, M, R> From<GradientBar<'a, M>> for Element<'a, M, R>
+where
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn from(other: GradientBar<'a, M>) -> Self {
+        Element::new(other)
+    }
+}
+
+pub trait WidgetRenderer: iced_native::Renderer + Sized {
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        samples: &[Color],
+        handles: &[Handle],
+        selected: Option<usize>,
+        style: &Style,
+        mouse_interaction: mouse::Interaction,
+    ) -> Self::Output;
+}
+
+impl<B> WidgetRenderer for iced_graphics::Renderer<B>
+where B: Backend
+{
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        samples: &[Color],
+        handles: &[Handle],
+        selected: Option<usize>,
+        style: &Style,
+        mouse_interaction: mouse::Interaction,
+    ) -> Self::Output {
+        let bar_bounds = Rectangle {
+            x: bounds.x + HANDLE_HALF_WIDTH,
+            width: bounds.width - HANDLE_HALF_WIDTH * 2.0,
+            height: BAR_HEIGHT,
+            ..bounds
+        };
+        let sample_width = bar_bounds.width / samples.len().max(1) as f32;
+        let mut primitives = samples
+            .iter()
+            .enumerate()
+            .map(|(index, color)| Primitive::Quad {
+                bounds: Rectangle {
+                    x: bar_bounds.x + sample_width * index as f32,
+                    width: sample_width,
+                    ..bar_bounds
+                },
+                background: (*color).into(),
+                border_radius: 0,
+                border_width: 0,
+                border_color: Color::TRANSPARENT,
+            })
+            .collect::<Vec<_>>();
+
+        // The selected handle is drawn last, on top of the others.
+        let order = (0..handles.len()).filter(|index| Some(*index) != selected).chain(selected);
+
+        for index in order {
+            let handle = &handles[index];
+            let x = bar_bounds.x + handle.position * bar_bounds.width;
+
+            primitives.push(Primitive::Quad {
+                bounds: Rectangle {
+                    x: x - HANDLE_HALF_WIDTH,
+                    y: bar_bounds.y + BAR_HEIGHT,
+                    width: HANDLE_HALF_WIDTH * 2.0,
+                    height: HANDLE_HEIGHT,
+                },
+                background: handle.color.into(),
+                border_radius: 1,
+                border_width: if Some(index) == selected { 2 } else { 1 },
+                border_color: if Some(index) == selected {
+                    style.selected_handle_border
+                } else {
+                    style.handle_border
+                },
+            });
+        }
+
+        (Primitive::Group { primitives }, mouse_interaction)
+    }
+}
@@ -1,11 +1,23 @@
 #[macro_use]
 pub mod layout;
 
+pub mod color_picker;
+pub mod drag_value;
+pub mod enum_dropdown;
 pub mod floating_panes;
+pub mod gradient_bar;
 pub mod margin;
 pub mod node;
+pub mod range_slider;
+pub mod value_editor;
 
+pub use color_picker::ColorPicker;
+pub use drag_value::DragValue;
+pub use enum_dropdown::EnumDropdown;
 pub use floating_panes::*;
+pub use gradient_bar::GradientBar;
 pub use layout::*;
 pub use margin::*;
 pub use node::*;
+pub use range_slider::RangeSlider;
+pub use value_editor::{ValueEditor, ValueEditorMessage};
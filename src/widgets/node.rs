@@ -1,7 +1,18 @@
 use super::*;
-use crate::graph::{GraphValidationErrorAffectedElement, GraphValidationErrors};
-use crate::node::{ChannelPassBy, ChannelRef, ConnectionPassBy, NodeConfiguration, TypeEnum, TypeExt};
-use crate::style::InteractionStatus;
+use crate::connection_history::ReconnectCandidate;
+use crate::graph::alloc::Allocator;
+use crate::graph::recording::RecordingStatus;
+use crate::graph::{
+    pair_channels, ConnectionValidityError, GraphValidationErrorAffectedElement, GraphValidationErrors,
+    MoveRecipient, Promotion, ValueSnapshot, ValueTaps,
+};
+use crate::keymap::{Action, Keymap, Shortcut};
+use crate::node::behaviour::ContextMenuItem;
+use crate::node::{
+    is_pointer, refcount_label, ChannelPassBy, ChannelRef, ConnectionPassBy, NodeConfiguration, TypeEnum,
+    TypeExt,
+};
+use crate::style::{InteractionStatus, NodeColor, StatusColors};
 use crate::util::{RectangleExt, Segments, StrokeType};
 use crate::{style, util, ChannelDirection, ChannelIdentifier, Connection};
 use iced::widget::canvas::{Fill, FillRule};
@@ -10,42 +21,207 @@ use iced::Size;
 use iced_graphics::canvas::{Frame, LineCap, LineJoin, Path, Stroke};
 use iced_graphics::{self, Backend, Primitive};
 use iced_native::event::Status;
+use iced_native::keyboard::{self, Event as KeyboardEvent};
 use iced_native::layout::{Layout, Limits, Node};
-use iced_native::mouse::{self, Button as MouseButton, Event as MouseEvent};
+use iced_native::mouse::{self, Button as MouseButton, Event as MouseEvent, ScrollDelta};
+use iced_native::widget::button::{self, Button};
 use iced_native::widget::container::Container;
+use iced_native::widget::rule::Rule;
 use iced_native::widget::Widget;
-use iced_native::Color;
-use iced_native::{self, Align, Clipboard, Column, Event, Hasher, Length, Point, Rectangle, Row, Text};
+use iced_native::{
+    self, Align, Background, Clipboard, Color, Column, Event, Font, Hasher, HorizontalAlignment, Length,
+    Point, Rectangle, Row, Text, Vector, VerticalAlignment,
+};
 use iced_native::{
     overlay::{self, Overlay},
     Element,
 };
+use indexmap::IndexMap;
 use lyon_geom::QuadraticBezierSegment;
 use ordered_float::OrderedFloat;
 use petgraph::graph::NodeIndex;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 use vek::Vec2;
 
+/// The average width of a character relative to the text size, used to estimate the width of text
+/// before it is laid out.
+const AVERAGE_CHARACTER_WIDTH: f32 = 0.5;
+/// The number of rows of channels that remain visible when the pane is resized to its minimum size.
+const MIN_VISIBLE_CHANNEL_ROWS: usize = 3;
+const SCROLLBAR_WIDTH: f32 = 3.0;
+const SCROLLBAR_MIN_THUMB_LENGTH: f32 = 16.0;
+/// The size of the slot of each row of channels in collapsed nodes, see
+/// [`NodeElementBuilder::collapsed`].
+const COMPACT_CHANNEL_SIZE: u16 = 10;
+/// The distance from the cursor within which connections are highlighted.
+const MAX_CONNECTION_HIGHLIGHT_DISTANCE: f32 = 6.0;
+
 impl<'a> ChannelRef<'a> {
-    pub fn render<M: 'a + Clone, R: 'a + WidgetRenderer>(&self) -> Element<'a, M, R> {
-        Text::new(self.title.to_string()).size(style::consts::TEXT_SIZE_REGULAR).into()
+    /// The title is ellipsized, if longer than `max_title_length` characters. The full title is
+    /// displayed in the tooltip of the channel.
+    pub fn render<M: 'a + Clone, R: 'a + WidgetRenderer>(
+        &self,
+        max_title_length: Option<usize>,
+    ) -> Element<'a, M, R> {
+        let title = match max_title_length {
+            Some(max_title_length) => util::truncate(self.title.to_string(), max_title_length),
+            None => self.title.to_string(),
+        };
+
+        Text::new(title).size(style::consts::TEXT_SIZE_REGULAR).into()
     }
 }
 
 #[derive(Default)]
 pub struct NodeElementState {
+    /// The groups of channels that are collapsed, identified by their direction and name.
+    collapsed_channel_groups: HashSet<(ChannelDirection, String)>,
+    /// The vertical offset of the content scrolled within the element, clamped when laid out.
+    scroll_offset: f32,
+    /// The minimum height of the element, measured whenever it is laid out, as the content cannot be
+    /// measured before. Behind a mutex, as the element is laid out through a shared reference.
+    min_height: Mutex<f32>,
     __marker: (), // prevent direct construction for future proofing
 }
 
+impl NodeElementState {
+    pub fn set_scroll_offset(&mut self, scroll_offset: f32) {
+        self.scroll_offset = scroll_offset;
+    }
+
+    /// The height the element can be shrunk to, such that the node behaviour element and the first
+    /// few rows of channels remain visible. Zero, until the element is first laid out.
+    pub fn min_height(&self) -> f32 {
+        *self.min_height.lock().unwrap()
+    }
+
+    pub fn is_channel_group_collapsed(&self, direction: ChannelDirection, group: &str) -> bool {
+        self.collapsed_channel_groups.contains(&(direction, group.to_string()))
+    }
+
+    pub fn toggle_channel_group(&mut self, direction: ChannelDirection, group: &str) {
+        let key = (direction, group.to_string());
+
+        if !self.collapsed_channel_groups.remove(&key) {
+            self.collapsed_channel_groups.insert(key);
+        }
+    }
+
+    /// The collapsed groups of channels, sorted so that they are saved in a stable order.
+    pub fn collapsed_channel_groups(&self) -> Vec<(ChannelDirection, String)> {
+        let mut groups = self.collapsed_channel_groups.iter().cloned().collect::<Vec<_>>();
+
+        groups.sort();
+        groups
+    }
+
+    pub fn set_collapsed_channel_groups(
+        &mut self,
+        groups: impl IntoIterator<Item = (ChannelDirection, String)>,
+    ) {
+        self.collapsed_channel_groups = groups.into_iter().collect();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChannelRow {
+    GroupHeader {
+        group: String,
+        collapsed: bool,
+    },
+    /// The global index of the channel, see [`NodeConfiguration::get_global_channel_index`].
+    Channel(usize),
+}
+
+/// The rows of the channel list of a single direction, with a header before every group of
+/// channels. Channels of collapsed groups are not displayed; connections to them are attached to
+/// the header of their group instead.
+///
+/// The channels are listed in their display order, which may differ from the order of their
+/// indices, see [`ChannelOrder`](crate::graph::ChannelOrder). Layouts of channels are looked up by
+/// their rows, never by the indices of the channels.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelRows {
+    rows: Vec<ChannelRow>,
+    /// The row of each channel, indexed by the global index of the channel.
+    channel_rows: Vec<usize>,
+}
+
+impl ChannelRows {
+    /// Lists the channels of the direction in the `order` of their global indices, see
+    /// [`ChannelOrder::resolve`](crate::graph::ChannelOrder::resolve).
+    pub fn new(
+        configuration: &NodeConfiguration,
+        direction: ChannelDirection,
+        state: &NodeElementState,
+        order: &[usize],
+    ) -> Self {
+        let channels = configuration.channels(direction).collect::<Vec<_>>();
+        let mut result = Self { rows: Vec::new(), channel_rows: vec![0; channels.len()] };
+        let mut current_group: Option<(&str, bool)> = None;
+
+        for &global_channel_index in order {
+            let channel = &channels[global_channel_index];
+            let collapsed = match channel.group {
+                Some(group) => {
+                    if current_group.map(|(current_group, _)| current_group) != Some(group) {
+                        let collapsed = state.is_channel_group_collapsed(direction, group);
+
+                        result.rows.push(ChannelRow::GroupHeader { group: group.to_string(), collapsed });
+                        current_group = Some((group, collapsed));
+                    }
+
+                    current_group.unwrap().1
+                }
+                None => {
+                    current_group = None;
+                    false
+                }
+            };
+
+            if collapsed {
+                result.channel_rows[global_channel_index] = result.rows.len() - 1;
+            } else {
+                result.channel_rows[global_channel_index] = result.rows.len();
+                result.rows.push(ChannelRow::Channel(global_channel_index));
+            }
+        }
+
+        result
+    }
+
+    pub fn rows(&self) -> &[ChannelRow] {
+        &self.rows
+    }
+
+    /// The row of the channel, or of the header of its group, if the group is collapsed.
+    pub fn row_of_channel(&self, global_channel_index: usize) -> usize {
+        self.channel_rows[global_channel_index]
+    }
+
+    pub fn is_channel_visible(&self, global_channel_index: usize) -> bool {
+        matches!(self.rows[self.row_of_channel(global_channel_index)], ChannelRow::Channel(_))
+    }
+}
+
 pub struct NodeElementBuilder<'a, M: 'a + Clone, R: 'a + WidgetRenderer> {
     index: NodeIndex,
     state: &'a mut NodeElementState,
     node_behaviour_element: Option<Element<'a, M, R>>,
     width: Length,
     height: Length,
+    max_height: u32,
+    channels_width: Option<u16>,
     input_channels: Vec<ChannelRef<'a>>,
     output_channels: Vec<ChannelRef<'a>>,
+    input_channel_rows: Option<ChannelRows>,
+    output_channel_rows: Option<ChannelRows>,
+    collapsed: bool,
+    on_scroll: Option<Box<dyn Fn(f32) -> M + 'a>>,
     __marker: std::marker::PhantomData<&'a (M, R)>,
 }
 
@@ -57,6 +233,8 @@ pub struct NodeElement<'a, M: 'a + Clone, R: 'a + WidgetRenderer> {
     state: &'a mut NodeElementState,
     width: Length,
     height: Length,
+    max_height: u32,
+    on_scroll: Option<Box<dyn Fn(f32) -> M + 'a>>,
     element_tree: Element<'a, M, R>,
 }
 
@@ -68,8 +246,14 @@ impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> NodeElementBuilder<'a, M, R> {
             node_behaviour_element: None,
             width: Length::Shrink,
             height: Length::Shrink,
+            max_height: u32::MAX,
+            channels_width: None,
             input_channels: Default::default(),
             output_channels: Default::default(),
+            input_channel_rows: None,
+            output_channel_rows: None,
+            collapsed: false,
+            on_scroll: None,
             __marker: Default::default(),
         }
     }
@@ -92,6 +276,34 @@ impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> NodeElementBuilder<'a, M, R> {
         self
     }
 
+    /// The content taller than `max_height` is scrolled.
+    pub fn max_height(mut self, max_height: u32) -> Self {
+        self.max_height = max_height;
+        self
+    }
+
+    /// Ellipsizes the titles of channels, so that the channels fit into the width of the element.
+    /// The titles are not ellipsized by default.
+    pub fn channels_width(mut self, width: u16) -> Self {
+        self.channels_width = Some(width);
+        self
+    }
+
+    /// Displays only a compact row of unlabeled slots, one per row of channels, which the connection
+    /// points are drawn at. The node behaviour element is not displayed.
+    pub fn collapsed(mut self, collapsed: bool) -> Self {
+        self.collapsed = collapsed;
+        self
+    }
+
+    /// The message to emit with the new scroll offset, when the content overflowing the element is
+    /// scrolled, see [`NodeElementState::set_scroll_offset`]. The content is not scrollable without
+    /// it.
+    pub fn on_scroll(mut self, on_scroll: impl Fn(f32) -> M + 'a) -> Self {
+        self.on_scroll = Some(Box::new(on_scroll));
+        self
+    }
+
     pub fn push_input_channel(mut self, channel: impl Into<ChannelRef<'a>>) -> Self {
         self.input_channels.push(channel.into());
         self
@@ -102,12 +314,88 @@ impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> NodeElementBuilder<'a, M, R> {
         self
     }
 
+    /// Arranges the channels of the direction into rows. Without rows, every channel is displayed in
+    /// its own row.
+    pub fn channel_rows(mut self, direction: ChannelDirection, rows: ChannelRows) -> Self {
+        match direction {
+            ChannelDirection::In => self.input_channel_rows = Some(rows),
+            ChannelDirection::Out => self.output_channel_rows = Some(rows),
+        }
+        self
+    }
+
+    /// The number of characters the titles of the channels of the direction are ellipsized to, if
+    /// [`Self::channels_width`] is specified. Channels of both directions share the width equally.
+    fn max_channel_title_length(&self, direction: ChannelDirection) -> Option<usize> {
+        let spacing = style::consts::SPACING_HORIZONTAL as f32;
+        let mut width = self.channels_width? as f32 - 2.0 * spacing;
+        let opposite_channels = match direction {
+            ChannelDirection::In => &self.output_channels,
+            ChannelDirection::Out => &self.input_channels,
+        };
+
+        if !opposite_channels.is_empty() {
+            width = (width - 2.0 * spacing) / 2.0;
+        }
+
+        let character_width = style::consts::TEXT_SIZE_REGULAR as f32 * AVERAGE_CHARACTER_WIDTH;
+
+        Some((width / character_width).max(1.0) as usize)
+    }
+
+    fn render_channels(
+        channels: &[ChannelRef<'a>],
+        rows: Option<&ChannelRows>,
+        max_title_length: Option<usize>,
+        align: Align,
+    ) -> Column<'a, M, R> {
+        let mut column = Column::new().spacing(style::consts::SPACING_VERTICAL).align_items(align);
+
+        if let Some(rows) = rows {
+            for row in rows.rows() {
+                column = column.push(match row {
+                    ChannelRow::GroupHeader { group, collapsed } => {
+                        let header = format!("{} {}", if *collapsed { '▸' } else { '▾' }, group);
+                        let header = match max_title_length {
+                            Some(max_title_length) => util::truncate(header, max_title_length),
+                            None => header,
+                        };
+
+                        Text::new(header).size(style::consts::TEXT_SIZE_REGULAR).into()
+                    }
+                    ChannelRow::Channel(index) => channels[*index].render(max_title_length),
+                });
+            }
+        } else {
+            for channel in channels {
+                column = column.push(channel.render(max_title_length));
+            }
+        }
+
+        column
+    }
+
+    /// Keeps a slot for every row, so that the channels are laid out in the same rows as in
+    /// [`Self::render_channels`].
+    fn render_compact_channels(channels: &[ChannelRef<'a>], rows: Option<&ChannelRows>) -> Row<'a, M, R> {
+        let row_count = rows.map_or(channels.len(), |rows| rows.rows().len());
+
+        (0..row_count).fold(Row::new().spacing(style::consts::SPACING_HORIZONTAL), |row, _| {
+            row.push(Space::new(Length::Units(COMPACT_CHANNEL_SIZE), Length::Units(COMPACT_CHANNEL_SIZE)))
+        })
+    }
+
     pub fn build(self) -> NodeElement<'a, M, R> {
+        let max_input_title_length = self.max_channel_title_length(ChannelDirection::In);
+        let max_output_title_length = self.max_channel_title_length(ChannelDirection::Out);
+
         NodeElement {
             index: self.index,
             state: self.state,
             width: self.width,
             height: self.height,
+            max_height: self.max_height,
+            on_scroll: self.on_scroll,
             element_tree: {
                 // Element { Margin { Row [ Column [ .. ], Column [ .. ] ] } }
                 Margin::new(
@@ -115,41 +403,55 @@ impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> NodeElementBuilder<'a, M, R> {
                         let mut column =
                             Column::new().width(Length::Fill).spacing(style::consts::SPACING_VERTICAL);
 
-                        if let Some(node_behaviour_element) = self.node_behaviour_element {
-                            column = column.push(node_behaviour_element);
-                        } else {
-                            // insert space to keep layout indices consistent
-                            column = column.push(Space::new(Length::Shrink, Length::Shrink));
+                        match self.node_behaviour_element {
+                            Some(node_behaviour_element) if !self.collapsed => {
+                                column = column.push(node_behaviour_element);
+                            }
+                            _ => {
+                                // insert space to keep layout indices consistent
+                                column = column.push(Space::new(Length::Shrink, Length::Shrink));
+                            }
                         }
 
+                        let (input_channels, output_channels): (Element<'a, M, R>, Element<'a, M, R>) =
+                            if self.collapsed {
+                                (
+                                    Self::render_compact_channels(
+                                        &self.input_channels,
+                                        self.input_channel_rows.as_ref(),
+                                    )
+                                    .into(),
+                                    Self::render_compact_channels(
+                                        &self.output_channels,
+                                        self.output_channel_rows.as_ref(),
+                                    )
+                                    .into(),
+                                )
+                            } else {
+                                (
+                                    Self::render_channels(
+                                        &self.input_channels,
+                                        self.input_channel_rows.as_ref(),
+                                        max_input_title_length,
+                                        Align::Start,
+                                    )
+                                    .into(),
+                                    Self::render_channels(
+                                        &self.output_channels,
+                                        self.output_channel_rows.as_ref(),
+                                        max_output_title_length,
+                                        Align::End,
+                                    )
+                                    .into(),
+                                )
+                            };
+
                         column = column.push(
                             Row::new()
                                 .spacing(style::consts::SPACING_HORIZONTAL)
-                                .push({
-                                    // input channels
-                                    let mut column = Column::new()
-                                        .spacing(style::consts::SPACING_VERTICAL)
-                                        .align_items(Align::Start);
-
-                                    for input_channel in &self.input_channels {
-                                        column = column.push(input_channel.render());
-                                    }
-
-                                    column
-                                })
+                                .push(input_channels)
                                 .push(Space::with_width(Length::Fill))
-                                .push({
-                                    // output channels
-                                    let mut column = Column::new()
-                                        .spacing(style::consts::SPACING_VERTICAL)
-                                        .align_items(Align::End);
-
-                                    for output_channel in &self.output_channels {
-                                        column = column.push(output_channel.render());
-                                    }
-
-                                    column
-                                }),
+                                .push(output_channels),
                         );
 
                         column
@@ -167,62 +469,92 @@ impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> NodeElement<'a, M, R> {
         NodeElementBuilder::new(index, state)
     }
 
-    fn get_connection_point(layout: ChannelLayout, direction: ChannelDirection) -> Vec2<f32> {
-        let field_position: Vec2<f32> = Into::<[f32; 2]>::into(layout.position()).into();
-        let field_size: Vec2<f32> = Into::<[f32; 2]>::into(layout.bounds().size()).into();
+    /// The cursor is hidden from the content scrolled out of view.
+    fn content_cursor_position(layout: Layout<'_>, cursor_position: Point) -> Point {
+        let bounds = layout.bounds();
+        let content_bounds = layout.children().next().unwrap().bounds();
 
-        match direction {
-            ChannelDirection::In => {
-                field_position + field_size * Vec2::new(0.0, 0.5)
-                    - Vec2::new(style::consts::SPACING_HORIZONTAL as f32, 0.0)
-            }
-            ChannelDirection::Out => {
-                field_position
-                    + field_size * Vec2::new(1.0, 0.5)
-                    + Vec2::new(style::consts::SPACING_HORIZONTAL as f32, 0.0)
-            }
+        if content_bounds.height > bounds.height && !bounds.contains(cursor_position) {
+            Point::new(-1.0, -1.0)
+        } else {
+            cursor_position
         }
     }
 
-    fn is_channel_selected(
-        channel_layout: ChannelLayout,
-        channel_direction: ChannelDirection,
-        cursor_position: Vec2<f32>,
-    ) -> bool {
-        const GRAB_RADIUS: f32 = 6.0;
-
-        let mut bounds = channel_layout.bounds();
-        bounds = match channel_direction {
-            ChannelDirection::Out => bounds.grow(
-                style::consts::SPACING_HORIZONTAL as f32,
-                style::consts::SPACING_VERTICAL as f32 * 0.5,
-                0.0,
-                style::consts::SPACING_VERTICAL as f32 * 0.5,
-            ),
-            ChannelDirection::In => bounds.grow(
-                0.0,
-                style::consts::SPACING_VERTICAL as f32 * 0.5,
-                style::consts::SPACING_HORIZONTAL as f32,
-                style::consts::SPACING_VERTICAL as f32 * 0.5,
-            ),
-        };
+    pub fn get_layout_index_from_channel(
+        panes: &FloatingPanes<'a, M, R, FloatingPanesBehaviour<M, R>>,
+        channel: ChannelIdentifier,
+    ) -> Option<usize> {
+        panes.get_layout_index_from_pane_index(&channel.node_index)
+    }
+}
+
+/// The connection points of collapsed nodes are centered in the slots of their rows, see
+/// [`NodeElementBuilder::collapsed`].
+fn get_connection_point(
+    channel_bounds: Rectangle,
+    direction: ChannelDirection,
+    collapsed: bool,
+) -> Vec2<f32> {
+    let field_position = Vec2::new(channel_bounds.x, channel_bounds.y);
+    let field_size = Vec2::new(channel_bounds.width, channel_bounds.height);
+
+    if collapsed {
+        return field_position + field_size * 0.5;
+    }
 
-        if bounds.contains(cursor_position.into_array().into()) {
-            return true;
+    match direction {
+        ChannelDirection::In => {
+            field_position + field_size * Vec2::new(0.0, 0.5)
+                - Vec2::new(style::consts::SPACING_HORIZONTAL as f32, 0.0)
         }
+        ChannelDirection::Out => {
+            field_position
+                + field_size * Vec2::new(1.0, 0.5)
+                + Vec2::new(style::consts::SPACING_HORIZONTAL as f32, 0.0)
+        }
+    }
+}
+
+/// Whether the cursor hovers the channel with the bounds, or its connection point.
+fn is_channel_selected(
+    channel_bounds: Rectangle,
+    channel_direction: ChannelDirection,
+    collapsed: bool,
+    cursor_position: Vec2<f32>,
+) -> bool {
+    const GRAB_RADIUS: f32 = 6.0;
 
-        let connection_point = Self::get_connection_point(channel_layout, channel_direction);
-        let distance_squared = cursor_position.distance_squared(connection_point);
+    // The slots of collapsed nodes are too close to each other to be grown.
+    if collapsed {
+        let connection_point = get_connection_point(channel_bounds, channel_direction, true);
 
-        distance_squared <= GRAB_RADIUS * GRAB_RADIUS
+        return cursor_position.distance_squared(connection_point) <= GRAB_RADIUS * GRAB_RADIUS;
     }
 
-    pub fn get_layout_index_from_channel(
-        panes: &FloatingPanes<'a, M, R, FloatingPanesBehaviour<M, R>>,
-        channel: ChannelIdentifier,
-    ) -> Option<usize> {
-        panes.get_layout_index_from_pane_index(&channel.node_index)
+    let bounds = match channel_direction {
+        ChannelDirection::Out => channel_bounds.grow(
+            style::consts::SPACING_HORIZONTAL as f32,
+            style::consts::SPACING_VERTICAL as f32 * 0.5,
+            0.0,
+            style::consts::SPACING_VERTICAL as f32 * 0.5,
+        ),
+        ChannelDirection::In => channel_bounds.grow(
+            0.0,
+            style::consts::SPACING_VERTICAL as f32 * 0.5,
+            style::consts::SPACING_HORIZONTAL as f32,
+            style::consts::SPACING_VERTICAL as f32 * 0.5,
+        ),
+    };
+
+    if bounds.contains(cursor_position.into_array().into()) {
+        return true;
     }
+
+    let connection_point = get_connection_point(channel_bounds, channel_direction, false);
+    let distance_squared = cursor_position.distance_squared(connection_point);
+
+    distance_squared <= GRAB_RADIUS * GRAB_RADIUS
 }
 
 impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> Widget<M, R> for NodeElement<'a, M, R> {
@@ -234,13 +566,44 @@ impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> Widget<M, R> for NodeElement<'a,
         self.height
     }
 
+    /// The content is laid out without limiting its height and scrolled within the element, so that
+    /// the channels keep their layout when the element is shorter than its content.
     fn layout(&self, renderer: &R, limits: &Limits) -> Node {
-        // let limits = limits
-        //     .max_width(self.extents[0])
-        //     .max_height(self.extents[1])
-        //     .width(self.width)
-        //     .height(self.height);
-        self.element_tree.layout(renderer, limits)
+        let limits = limits.max_height(self.max_height).width(self.width).height(self.height);
+        let content_limits =
+            Limits::new(Size::new(limits.min().width, 0.0), Size::new(limits.max().width, f32::INFINITY));
+        let mut content = self.element_tree.layout(renderer, &content_limits);
+        let size = limits.resolve(content.size());
+        let max_scroll_offset = (content.size().height - size.height).max(0.0);
+
+        content.move_to(Point::new(
+            0.0,
+            -util::partial_clamp(self.state.scroll_offset, [0.0, max_scroll_offset]),
+        ));
+
+        // Measure the height of the node behaviour element and the first few rows of channels.
+        let content_layout = NodeContentLayout::from(Layout::new(&content));
+        let min_height = [ChannelDirection::In, ChannelDirection::Out]
+            .iter()
+            .filter_map(|direction| {
+                content_layout
+                    .channels_with_direction(*direction)
+                    .channels()
+                    .take(MIN_VISIBLE_CHANNEL_ROWS)
+                    .last()
+            })
+            .map(|row| {
+                let bounds = row.bounds();
+
+                OrderedFloat(bounds.y + bounds.height - content_layout.position().y)
+            })
+            .max()
+            .map(|rows_bottom| rows_bottom.0 + style::consts::SPACING_VERTICAL as f32)
+            .unwrap_or(f32::INFINITY);
+
+        *self.state.min_height.lock().unwrap() = min_height.min(content.size().height);
+
+        Node::with_children(size, vec![content])
     }
 
     fn draw(
@@ -251,13 +614,24 @@ impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> Widget<M, R> for NodeElement<'a,
         cursor_position: Point,
         viewport: &Rectangle,
     ) -> <R as iced_native::Renderer>::Output {
-        self.element_tree.draw(renderer, defaults, layout, cursor_position, viewport)
+        let content_layout = layout.children().next().unwrap();
+        let content = self.element_tree.draw(
+            renderer,
+            defaults,
+            content_layout,
+            Self::content_cursor_position(layout, cursor_position),
+            viewport,
+        );
+
+        renderer.draw_scrolled_content(defaults, layout.bounds(), content_layout.bounds(), content)
     }
 
     fn hash_layout(&self, state: &mut Hasher) {
         struct Marker;
         std::any::TypeId::of::<Marker>().hash(state);
 
+        OrderedFloat::from(self.state.scroll_offset).hash(state);
+        self.max_height.hash(state);
         self.element_tree.hash_layout(state);
     }
 
@@ -270,11 +644,44 @@ impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> Widget<M, R> for NodeElement<'a,
         renderer: &R,
         clipboard: Option<&dyn Clipboard>,
     ) -> Status {
-        self.element_tree.on_event(event, layout, cursor_position, messages, renderer, clipboard)
+        let bounds = layout.bounds();
+        let content_layout = layout.children().next().unwrap();
+        let content_bounds = content_layout.bounds();
+        let status = self.element_tree.on_event(
+            event.clone(),
+            content_layout,
+            Self::content_cursor_position(layout, cursor_position),
+            messages,
+            renderer,
+            clipboard,
+        );
+
+        if status == Status::Captured || !bounds.contains(cursor_position) {
+            return status;
+        }
+
+        if let (Some(on_scroll), Event::Mouse(MouseEvent::WheelScrolled { delta })) = (&self.on_scroll, event)
+        {
+            let delta = match delta {
+                ScrollDelta::Lines { y, .. } => y * SCROLL_LINE_LENGTH,
+                ScrollDelta::Pixels { y, .. } => y,
+            };
+            let scroll_offset = bounds.y - content_bounds.y;
+            let max_scroll_offset = (content_bounds.height - bounds.height).max(0.0);
+            let new_scroll_offset = util::partial_clamp(scroll_offset - delta, [0.0, max_scroll_offset]);
+
+            // Let the panes pan, once scrolled to the end.
+            if new_scroll_offset != scroll_offset {
+                messages.push(on_scroll(new_scroll_offset));
+                return Status::Captured;
+            }
+        }
+
+        status
     }
 
     fn overlay(&mut self, layout: Layout<'_>) -> Option<overlay::Element<'_, M, R>> {
-        self.element_tree.overlay(layout)
+        self.element_tree.overlay(layout.children().next().unwrap())
     }
 }
 
@@ -284,366 +691,2288 @@ impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> From<NodeElement<'a, M, R>> for
     }
 }
 
-pub struct FloatingPanesBehaviour<M, R: WidgetRenderer> {
-    pub on_channel_disconnect: fn(ChannelIdentifier) -> M,
-    pub on_connection_create: fn(Connection) -> M,
-    pub connections: Vec<Connection>,
-    // FIXME: Make it possible to store references instead of cloning
-    pub graph_validation_errors: GraphValidationErrors,
-    pub tooltip_style: Option<<R as WidgetRenderer>::StyleTooltip>,
-}
+impl<'a, M: 'a + Clone, R: 'a + WidgetRenderer> FloatingPanes<'a, M, R, FloatingPanesBehaviour<M, R>> {
+    fn behaviour_data(&self, node_index: NodeIndex) -> &FloatingPaneBehaviourData {
+        &self.children.get(&node_index).unwrap().behaviour_data
+    }
 
-macro_rules! get_is_aliased {
-    ($panes:expr) => {
-        move |from| {
-            $panes.behaviour.connections.iter().filter(|connection| connection.from() == from).count() > 1
+    /// The curve between the connection points of the channels of the connection.
+    fn connection_curve(&self, layout: FloatingPanesLayout<'_>, connection: &Connection) -> ConnectionCurve {
+        let layout_from = layout.pane_with_index(
+            NodeElement::<M, R>::get_layout_index_from_channel(self, connection.from()).unwrap(),
+        );
+        let layout_to = layout.pane_with_index(
+            NodeElement::<M, R>::get_layout_index_from_channel(self, connection.to()).unwrap(),
+        );
+        let from = self
+            .behaviour_data(connection.from().node_index)
+            .connection_point(layout_from, connection.from());
+        let to = self.behaviour_data(connection.to().node_index).connection_point(layout_to, connection.to());
+
+        ConnectionCurve { from, to }
+    }
+
+    /// The indices of the connections, grouped by the two nodes they connect.
+    fn connection_groups(&self) -> Vec<Vec<usize>> {
+        let mut groups = Vec::<((NodeIndex, NodeIndex), Vec<usize>)>::new();
+
+        for (index, connection) in self.behaviour.connections.iter().enumerate() {
+            let nodes = (connection.from().node_index, connection.to().node_index);
+
+            match groups.iter_mut().find(|(group_nodes, _)| *group_nodes == nodes) {
+                Some((_, group)) => group.push(index),
+                None => groups.push((nodes, vec![index])),
+            }
         }
-    };
-}
 
-impl<M: Clone, R: WidgetRenderer> FloatingPanesBehaviour<M, R> {
-    /// A reflexive function to check whether two channels can be connected
-    fn can_connect<'a>(
-        panes: &FloatingPanes<'a, M, R, Self>,
-        from: ChannelIdentifier,
-        to: ChannelIdentifier,
-    ) -> bool {
-        if let Some(connection) = Connection::try_from_identifiers([from, to]) {
-            connection.is_valid(&get_is_aliased!(panes), &move |channel| {
-                let pane = panes.children.get(&channel.node_index).unwrap();
+        groups.into_iter().map(|(_, group)| group).collect()
+    }
+
+    /// Lays out a group of connections, bundling them, if there are several.
+    fn connection_layout(&self, layout: FloatingPanesLayout<'_>, group: &[usize]) -> ConnectionLayout<'_> {
+        let mut connections = group
+            .iter()
+            .map(|index| {
+                let connection = &self.behaviour.connections[*index];
 
-                pane.behaviour_data.node_configuration.channel(channel.channel_direction, channel.into())
+                (connection, self.connection_curve(layout, connection))
             })
+            .collect::<Vec<_>>();
+
+        if connections.len() == 1 {
+            let (connection, curve) = connections.pop().unwrap();
+
+            ConnectionLayout::Single(connection, curve)
         } else {
-            false
+            let from = connections[0].0.from().node_index;
+            let to = connections[0].0.to().node_index;
+
+            ConnectionLayout::Bundle(ConnectionBundle { from, to, connections })
         }
     }
 
-    fn is_connected(&self, channel: ChannelIdentifier) -> bool {
-        self.connections.iter().any(|connection| connection.channel(channel.channel_direction) == channel)
+    /// Lays out the connections, bundling parallel connections between the same two nodes.
+    fn connection_layouts(&self, layout: FloatingPanesLayout<'_>) -> Vec<ConnectionLayout<'_>> {
+        self.connection_groups().iter().map(|group| self.connection_layout(layout, group)).collect()
     }
-}
 
-impl<'a, M: Clone + 'a, R: 'a + WidgetRenderer> floating_panes::FloatingPanesBehaviour<'a, M, R>
-    for FloatingPanesBehaviour<M, R>
-{
-    type FloatingPaneIndex = NodeIndex;
-    type FloatingPaneBehaviourData = FloatingPaneBehaviourData;
-    type FloatingPaneBehaviourState = FloatingPaneBehaviourState;
-    type FloatingPanesBehaviourState = FloatingPanesBehaviourState;
+    /// Brings the [`HitTestIndex`] up to date with the layout. Only the panes whose bounds have
+    /// changed, such as a dragged pane, are reindexed along with their connections, unless the
+    /// panes or the connections themselves have changed.
+    fn update_hit_test_index(&mut self, layout: FloatingPanesLayout<'_>) {
+        let origin = layout.position();
+        // Panning the view does not move anything in the coordinates of the panes.
+        let to_panes_space =
+            |bounds: Rectangle| Rectangle { x: bounds.x - origin.x, y: bounds.y - origin.y, ..bounds };
+        let pane_bounds = layout
+            .panes()
+            .zip(self.children.keys().copied())
+            .map(|(pane_layout, node_index)| {
+                let bounds =
+                    pane_layout.bounds().grow_symmetrical(style::consts::SPACING_HORIZONTAL as f32, 0.0);
+
+                (node_index, to_panes_space(bounds))
+            })
+            .collect::<IndexMap<_, _>>();
+        let mut index = std::mem::take(&mut self.behaviour_state.hit_test_index);
+        let group_bounds = |group: &[usize]| {
+            to_panes_space(
+                self.connection_layout(layout, group).bounds().grow_uniform(HitTestIndex::CONNECTION_MARGIN),
+            )
+        };
 
-    fn draw_panes(
-        panes: &FloatingPanes<'a, M, R, Self>,
-        renderer: &mut R,
-        defaults: &<R as iced_native::Renderer>::Defaults,
-        layout: FloatingPanesLayout<'_>,
-        cursor_position: Point,
-        viewport: &Rectangle,
-    ) -> ContentDrawResult<R> {
-        <R as WidgetRenderer>::draw_panes(renderer, panes, defaults, layout, cursor_position, viewport)
+        index.update(&self.behaviour.connections, pane_bounds, || self.connection_groups(), group_bounds);
+        self.behaviour_state.hit_test_index = index;
     }
+}
 
-    fn hash_panes(_panes: &FloatingPanes<'a, M, R, Self>, _state: &mut Hasher) {
-        // This implementation of [`floating_panes::FloatingPanesBehaviour`] does not influence the
-        // layout of the floating panes.
+/// How a connection is drawn, either on its own or as part of a bundle.
+enum ConnectionLayout<'a> {
+    Single(&'a Connection, ConnectionCurve),
+    Bundle(ConnectionBundle<'a>),
+}
+
+impl ConnectionLayout<'_> {
+    /// The bounds of all curves the connections are drawn along.
+    fn bounds(&self) -> Rectangle {
+        match self {
+            ConnectionLayout::Single(_, curve) => curve.bounds(),
+            ConnectionLayout::Bundle(bundle) => {
+                let cable = bundle.cable();
+
+                bundle.connections.iter().fold(cable.bounds(), |bounds, (_, curve)| {
+                    ConnectionBundle::stubs(&cable, curve)
+                        .iter()
+                        .fold(bounds, |bounds, stub| bounds.enclosing(&stub.bounds()))
+                })
+            }
+        }
     }
+}
 
-    fn on_event(
-        panes: &mut FloatingPanes<'a, M, R, Self>,
-        event: Event,
-        layout: FloatingPanesLayout<'_>,
-        cursor_position: Point,
-        messages: &mut Vec<M>,
-        renderer: &R,
-        clipboard: Option<&dyn Clipboard>,
-    ) -> Status {
-        match event {
-            Event::Mouse(MouseEvent::CursorMoved { x, y }) => {
-                let cursor_position = Vec2::new(x, y);
+/// Parallel connections between the same two nodes, drawn as a single cable along a common curve,
+/// which fans out into short stubs near the connection points of the channels.
+struct ConnectionBundle<'a> {
+    from: NodeIndex,
+    to: NodeIndex,
+    /// The connections along with the curves between their connection points.
+    connections: Vec<(&'a Connection, ConnectionCurve)>,
+}
 
-                panes.behaviour_state.highlight = None;
+impl ConnectionBundle<'_> {
+    /// The horizontal distance between the connection points and the ends of the cable.
+    const FAN_LENGTH: f32 = 32.0;
 
-                // Highlight channel, if possible
-                for (pane_layout, node_index) in layout.panes().zip(panes.children.keys().copied()) {
-                    let pane_bounding_box =
-                        pane_layout.bounds().grow_symmetrical(style::consts::SPACING_HORIZONTAL as f32, 0.0);
+    /// The common curve of the connections.
+    fn cable(&self) -> ConnectionCurve {
+        let count = self.connections.len() as f32;
+        let from = self.connections.iter().fold(Vec2::zero(), |sum, (_, curve)| sum + curve.from) / count;
+        let to = self.connections.iter().fold(Vec2::zero(), |sum, (_, curve)| sum + curve.to) / count;
 
-                    if !pane_bounding_box.contains(cursor_position.into_array().into()) {
-                        continue;
-                    }
+        ConnectionCurve {
+            from: from + Vec2::new(Self::FAN_LENGTH, 0.0),
+            to: to - Vec2::new(Self::FAN_LENGTH, 0.0),
+        }
+    }
 
-                    let node = panes.children.get(&node_index).unwrap();
-                    let inputs_layout = pane_layout
-                        .content()
-                        .channels_with_direction(ChannelDirection::In)
-                        .channels()
-                        .zip(node.behaviour_data.node_configuration.channels(ChannelDirection::In));
-                    let outputs_layout = pane_layout
-                        .content()
-                        .channels_with_direction(ChannelDirection::Out)
-                        .channels()
-                        .zip(node.behaviour_data.node_configuration.channels(ChannelDirection::Out));
-                    let channel_layouts = inputs_layout.chain(outputs_layout);
-
-                    let highlighted_channel = channel_layouts
-                        .filter(|(channel_layout, channel_ref)| {
-                            // If a new connection is being formed, make sure the target channel
-                            // can be connected to.
-                            if let Some(selected_channel) = panes.behaviour_state.selected_channel.as_ref() {
-                                let channel = channel_ref.into_identifier(node_index);
-
-                                if !FloatingPanesBehaviour::can_connect(panes, *selected_channel, channel) {
-                                    return false;
-                                }
-                            }
+    /// The curves between the connection points of the connection and the ends of the cable.
+    fn stubs(cable: &ConnectionCurve, curve: &ConnectionCurve) -> [ConnectionCurve; 2] {
+        [
+            ConnectionCurve { from: curve.from, to: cable.from },
+            ConnectionCurve { from: cable.to, to: curve.to },
+        ]
+    }
+}
 
-                            NodeElement::<M, R>::is_channel_selected(
-                                channel_layout.clone(),
-                                channel_ref.direction,
-                                cursor_position,
-                            )
-                        })
-                        .next();
+/// Whether the executor runs the schedule of the graph being edited, displayed in the bottom right
+/// corner of the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleStatus {
+    /// The number of times the schedule has been updated, successfully or not.
+    pub edit_generation: usize,
+    /// The generation of the active schedule, which the executor is expected to execute.
+    pub scheduled_generation: Option<usize>,
+    /// The generation of the schedule the executor is executing.
+    pub executing_generation: Option<usize>,
+    /// Whether the most recent update of the schedule failed, so that the executed schedule does
+    /// not reflect the most recent edits.
+    pub failed: bool,
+    /// The frames of the UI drawn per second, if enabled in the preferences.
+    pub ui_frame_rate: Option<u32>,
+    /// The number of background tasks of nodes, including the cancelled ones still running.
+    pub background_tasks: usize,
+    /// The number of cancelled background tasks that did not finish in time.
+    pub unresponsive_background_tasks: usize,
+    /// Whether the outputs of source nodes are being recorded or replayed.
+    pub recording: RecordingStatus,
+}
 
-                    if let Some((_channel_layout, channel_ref)) = highlighted_channel {
-                        let channel = channel_ref.into_identifier(node_index);
-                        panes.behaviour_state.highlight = Some(Highlight::Channel(channel));
-                    }
-                }
+impl ScheduleStatus {
+    pub fn label(&self) -> String {
+        let mut label = self.executing_label();
 
-                // Otherwise, highlight a connection, if one is not being created
-                if panes.behaviour_state.highlight.is_none()
-                    && panes.behaviour_state.selected_channel.is_none()
-                {
-                    const MAX_CONNECTION_HIGHLIGHT_DISTANCE: f32 = 6.0;
+        if let Some(frame_rate) = self.ui_frame_rate {
+            label.push_str(&format!(" · UI {} fps", frame_rate));
+        }
 
-                    let closest_connection = panes
-                        .behaviour
+        if self.background_tasks > 0 {
+            label.push_str(&format!(" · {} background tasks", self.background_tasks));
+
+            if self.unresponsive_background_tasks > 0 {
+                label.push_str(&format!(" ({} unresponsive)", self.unresponsive_background_tasks));
+            }
+        }
+
+        match self.recording {
+            RecordingStatus::Idle => (),
+            RecordingStatus::Recording => label.push_str(" · recording"),
+            RecordingStatus::Replaying => label.push_str(" · replaying"),
+        }
+
+        label
+    }
+
+    fn executing_label(&self) -> String {
+        let executing = match self.executing_generation {
+            Some(generation) => format!("schedule {}", generation),
+            None => "nothing".to_string(),
+        };
+
+        if self.failed {
+            format!(
+                "Edit {} · scheduling failed, running {} · see the log in the preferences",
+                self.edit_generation, executing
+            )
+        } else if self.executing_generation != self.scheduled_generation {
+            format!(
+                "Edit {} · schedule {} pending, running {}",
+                self.edit_generation,
+                self.scheduled_generation.map(|generation| generation.to_string()).unwrap_or_default(),
+                executing
+            )
+        } else {
+            format!("Edit {} · running {}", self.edit_generation, executing)
+        }
+    }
+
+    /// Green while the executed schedule is up to date, yellow while the executor has not picked
+    /// up the newest schedule yet and red if the newest edits could not be scheduled.
+    pub fn color(&self, status_colors: &StatusColors) -> Color {
+        if self.failed {
+            status_colors.error
+        } else if self.executing_generation != self.scheduled_generation {
+            status_colors.pending
+        } else {
+            status_colors.highlight
+        }
+    }
+}
+
+/// The tabs of the open documents, drawn in the top left corner of the canvas, followed by a
+/// button opening a new tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentTabs {
+    pub titles: Vec<String>,
+    /// Whether the document of each tab keeps running while another tab is focused.
+    pub pinned: Vec<bool>,
+    /// The position of the tab of the focused document.
+    pub focused: usize,
+}
+
+/// The part of the tab bar under the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DocumentTabsTarget {
+    Select(usize),
+    Pin(usize),
+    Close(usize),
+    Create,
+}
+
+impl DocumentTabs {
+    const TAB_WIDTH: f32 = 160.0;
+    const TAB_HEIGHT: f32 = 24.0;
+    /// The width of the buttons pinning and closing the tabs and of the button opening a new tab.
+    const BUTTON_WIDTH: f32 = 20.0;
+
+    /// The bounds of the tabs, followed by the bounds of the button opening a new tab, relative to
+    /// the top left corner of the viewport.
+    fn bounds(&self, origin: Point) -> impl Iterator<Item = Rectangle> + '_ {
+        let spacing = style::consts::SPACING_HORIZONTAL as f32;
+
+        (0..=self.titles.len()).map(move |index| Rectangle {
+            x: origin.x + spacing + index as f32 * (Self::TAB_WIDTH + spacing),
+            y: origin.y + spacing,
+            width: if index < self.titles.len() { Self::TAB_WIDTH } else { Self::BUTTON_WIDTH },
+            height: Self::TAB_HEIGHT,
+        })
+    }
+
+    /// The button at the right edge of the tab.
+    fn close_button_bounds(tab_bounds: Rectangle) -> Rectangle {
+        Rectangle {
+            x: tab_bounds.x + tab_bounds.width - Self::BUTTON_WIDTH,
+            width: Self::BUTTON_WIDTH,
+            ..tab_bounds
+        }
+    }
+
+    /// The button left of the close button.
+    fn pin_button_bounds(tab_bounds: Rectangle) -> Rectangle {
+        Rectangle {
+            x: tab_bounds.x + tab_bounds.width - 2.0 * Self::BUTTON_WIDTH,
+            width: Self::BUTTON_WIDTH,
+            ..tab_bounds
+        }
+    }
+
+    fn is_pinned(&self, index: usize) -> bool {
+        self.pinned.get(index).copied().unwrap_or(false)
+    }
+
+    fn target(&self, origin: Point, cursor_position: Point) -> Option<DocumentTabsTarget> {
+        let tab_count = self.titles.len();
+
+        self.bounds(origin).enumerate().find(|(_, bounds)| bounds.contains(cursor_position)).map(
+            |(index, bounds)| {
+                if index == tab_count {
+                    DocumentTabsTarget::Create
+                } else if Self::close_button_bounds(bounds).contains(cursor_position) {
+                    DocumentTabsTarget::Close(index)
+                } else if Self::pin_button_bounds(bounds).contains(cursor_position) {
+                    DocumentTabsTarget::Pin(index)
+                } else {
+                    DocumentTabsTarget::Select(index)
+                }
+            },
+        )
+    }
+}
+
+pub struct FloatingPanesBehaviour<M, R: WidgetRenderer> {
+    pub on_channel_disconnect: fn(ChannelIdentifier) -> M,
+    pub on_connection_create: fn(Connection) -> M,
+    /// Creates the connections between two nodes proposed by dragging from the title bar of one to
+    /// the title bar of the other with Alt held.
+    pub on_connections_create: fn(Vec<Connection>) -> M,
+    pub on_reconnect: fn(Connection) -> M,
+    /// Replaces the connection by the pair of connections through a node, the one leading into the
+    /// node first.
+    pub on_connection_splice: fn(Connection, [Connection; 2]) -> M,
+    /// Freezes the value of the output channel into a `Constant` node.
+    pub on_value_freeze: fn(ChannelIdentifier) -> M,
+    /// Creates a node connected to the channel, at the position relative to the panes.
+    pub on_node_create: fn(ChannelIdentifier, Vec2<f32>) -> M,
+    pub on_node_color: fn(NodeIndex, Option<NodeColor>) -> M,
+    pub on_node_remove: fn(NodeIndex) -> M,
+    pub on_node_mute: fn(NodeIndex, bool) -> M,
+    /// Draws the pane of the node above all panes that are not pinned, or among them.
+    pub on_node_pin: fn(NodeIndex, bool) -> M,
+    /// Sets the opacity the pane of the node is drawn with, from 0 to 1.
+    pub on_node_opacity: fn(NodeIndex, f32) -> M,
+    /// Lets the cursor through the content of the pane of the node, or makes it capture the cursor.
+    pub on_node_click_through: fn(NodeIndex, bool) -> M,
+    /// Saves the kind and the settings of the node as a preset.
+    pub on_preset_save: fn(NodeIndex) -> M,
+    pub on_channel_group_toggle: fn(NodeIndex, ChannelDirection, String) -> M,
+    /// Enters or leaves the mode in which the channel rows of the node are dragged to reorder them.
+    pub on_channels_arrange: fn(NodeIndex, bool) -> M,
+    /// Displays the channel at the position of the target channel, both specified by their global
+    /// indices.
+    pub on_channel_move: fn(NodeIndex, ChannelDirection, usize, usize) -> M,
+    /// Displays the channels of the node in the order of their indices.
+    pub on_channel_order_reset: fn(NodeIndex) -> M,
+    /// Selects or deselects the node, when its title bar is clicked with Ctrl held.
+    pub on_node_select: fn(NodeIndex, bool) -> M,
+    /// Marks the connection as the one the value of its output is moved into.
+    pub on_connection_primary: fn(Connection) -> M,
+    /// Focuses the tab at the position.
+    pub on_document_select: fn(usize) -> M,
+    pub on_document_close: fn(usize) -> M,
+    /// Called with the position of the tab and whether it should keep running in the background.
+    pub on_document_pin: fn(usize, bool) -> M,
+    pub on_document_create: fn() -> M,
+    /// The items contributed by the behaviour of the node whose context menu is open, listed below
+    /// the standard ones.
+    pub node_context_menu_items: Vec<ContextMenuItem<M>>,
+    pub connections: Vec<Connection>,
+    /// The connections the values of the outputs are moved into, by the outputs.
+    pub move_recipients: IndexMap<ChannelIdentifier, MoveRecipient>,
+    /// Whether outputs may be connected to inputs of wider primitive types, see
+    /// [`ExecutionGraph::implicit_promotion`](crate::graph::ExecutionGraph::implicit_promotion).
+    pub implicit_promotion: bool,
+    // FIXME: Make it possible to store references instead of cloning
+    pub graph_validation_errors: GraphValidationErrors,
+    /// Previously removed connections, offered in the context menu of their channels.
+    pub reconnect_candidates: Vec<ReconnectCandidate>,
+    /// Whether dropping a pending connection on empty space creates a node connected to it.
+    pub create_nodes_on_drop: bool,
+    /// Whether the parts of connections passing over unrelated panes are dimmed, see
+    /// [`CrossingCache`].
+    pub dim_crossing_connections: bool,
+    pub keymap: Keymap,
+    pub status_colors: StatusColors,
+    pub tooltip_style: Option<<R as WidgetRenderer>::StyleTooltip>,
+    /// Not displayed if `None`, such as in exported images.
+    pub schedule_status: Option<ScheduleStatus>,
+    /// Not displayed if `None`, such as in exported images.
+    pub document_tabs: Option<DocumentTabs>,
+    /// Used to look up the refcounts of the allocations pointed to by the hovered channels, not
+    /// displayed if `None`.
+    pub value_taps: Option<Arc<ValueTaps>>,
+    /// The number of values consumed per second through the connections, not displayed if `None`.
+    /// Connections missing from the map are not consumed, as the node of their input is disabled.
+    pub throughput: Option<HashMap<Connection, f64>>,
+    /// The positions of the nodes in the order of execution, see [`Schedule::execution_order`],
+    /// not displayed if `None`.
+    ///
+    /// [`Schedule::execution_order`]: crate::graph::Schedule::execution_order
+    pub execution_order: Option<HashMap<NodeIndex, Option<usize>>>,
+}
+
+/// Whether outputs are aliased, optionally for an existing connection, for which the output is not
+/// aliased if the value of the output is moved into it.
+macro_rules! get_is_aliased {
+    ($panes:expr) => {
+        move |from| {
+            $panes.behaviour.connections.iter().filter(|connection| connection.from() == from).count() > 1
+        }
+    };
+    ($panes:expr, $connection:expr) => {
+        move |from| {
+            !$panes.behaviour.is_move_recipient($connection)
+                && $panes.behaviour.connections.iter().filter(|connection| connection.from() == from).count()
+                    > 1
+        }
+    };
+}
+
+/// Whether outputs would be aliased once the pending connection is created, in addition to the
+/// current connections of its output.
+macro_rules! get_pending_is_aliased {
+    ($panes:expr) => {
+        move |from| {
+            $panes.behaviour.connections.iter().filter(|connection| connection.from() == from).count() > 0
+        }
+    };
+}
+
+/// The reason why a pending connection cannot be created.
+#[derive(Debug, Clone)]
+pub enum ConnectionRefusal {
+    SameDirection(ChannelDirection),
+    Invalid { connection: Connection, error: ConnectionValidityError },
+}
+
+impl ConnectionRefusal {
+    pub fn description(&self) -> Cow<'_, str> {
+        match self {
+            ConnectionRefusal::SameDirection(ChannelDirection::In) => {
+                Cow::Borrowed("Cannot connect two input channels.")
+            }
+            ConnectionRefusal::SameDirection(ChannelDirection::Out) => {
+                Cow::Borrowed("Cannot connect two output channels.")
+            }
+            ConnectionRefusal::Invalid { connection, error } => error.display(connection).description,
+        }
+    }
+}
+
+/// Whether outputs would be aliased once the connection is created, which replaces the current
+/// connection of its input, unless the value of the output would be moved into it.
+macro_rules! get_prospective_is_aliased {
+    ($panes:expr, $connection:expr) => {
+        move |from| {
+            let remaining_connections = $panes
+                .behaviour
+                .connections
+                .iter()
+                .filter(|existing| existing.from() == from && existing.to() != $connection.to())
+                .count();
+            let created_connections = if $connection.from() == from { 1 } else { 0 };
+
+            !$connection.would_receive_move(&$panes.behaviour.connections)
+                && remaining_connections + created_connections > 1
+        }
+    };
+}
+
+impl<M: Clone, R: WidgetRenderer> FloatingPanesBehaviour<M, R> {
+    /// Whether the value of the output of the connection is moved into it, while other connections
+    /// of the output borrow it.
+    fn is_move_recipient(&self, connection: &Connection) -> bool {
+        self.move_recipients
+            .get(&connection.from())
+            .map_or(false, |recipient| recipient.connection == *connection)
+    }
+
+    /// A reflexive function to check whether two channels can be connected
+    fn can_connect<'a>(
+        panes: &FloatingPanes<'a, M, R, Self>,
+        from: ChannelIdentifier,
+        to: ChannelIdentifier,
+    ) -> bool {
+        Self::check_connection(panes, from, to).is_ok()
+    }
+
+    /// Checks whether two channels can be connected, taking into account that the new connection
+    /// replaces the current connection of the input and may cause the output to become aliased.
+    fn check_connection<'a>(
+        panes: &FloatingPanes<'a, M, R, Self>,
+        a: ChannelIdentifier,
+        b: ChannelIdentifier,
+    ) -> Result<Connection, ConnectionRefusal> {
+        let connection = Connection::try_from_identifiers([a, b])
+            .ok_or(ConnectionRefusal::SameDirection(a.channel_direction))?;
+
+        connection
+            .check_validity(
+                &get_prospective_is_aliased!(panes, &connection),
+                &move |channel| {
+                    let pane = panes.children.get(&channel.node_index).unwrap();
+
+                    pane.behaviour_data.node_configuration.channel(channel.channel_direction, channel.into())
+                },
+                panes.behaviour.implicit_promotion,
+            )
+            .map(|()| connection.clone())
+            .map_err(|error| ConnectionRefusal::Invalid { connection: connection.clone(), error })
+    }
+
+    /// Summarizes the types of the channels and the method of passing the value, e.g.
+    /// `F32 → F32, by value (move)`.
+    fn describe_connection(panes: &FloatingPanes<'_, M, R, Self>, connection: &Connection) -> String {
+        let get_channel = |channel: ChannelIdentifier| {
+            let pane = panes.children.get(&channel.node_index).unwrap();
+
+            pane.behaviour_data.node_configuration.channel(channel.channel_direction, channel.into())
+        };
+        let pass_by = match connection.to().pass_by {
+            ChannelPassBy::SharedReference => "shared",
+            ChannelPassBy::MutableReference => "mutable",
+            ChannelPassBy::Value => "by value (move)",
+        };
+
+        let promoted = if Self::promotion(panes, connection).is_some() { " (promoted)" } else { "" };
+
+        format!(
+            "{} → {}{}, {}",
+            get_channel(connection.from()).ty,
+            get_channel(connection.to()).ty,
+            promoted,
+            pass_by
+        )
+    }
+
+    /// The conversion of the values passed through the connection, if implicit promotion is
+    /// enabled and the connection requires it.
+    fn promotion(panes: &FloatingPanes<'_, M, R, Self>, connection: &Connection) -> Option<Promotion> {
+        if !panes.behaviour.implicit_promotion {
+            return None;
+        }
+
+        connection.promotion(&|channel| {
+            let pane = panes.children.get(&channel.node_index).unwrap();
+
+            pane.behaviour_data.node_configuration.channel(channel.channel_direction, channel.into())
+        })
+    }
+
+    /// The full title and the type of the channel, along with its description. Channels of pointer
+    /// types are also described by the number of references to the allocation they most recently
+    /// passed. Inputs pass the allocations of the outputs they are connected to.
+    fn describe_channel(panes: &FloatingPanes<'_, M, R, Self>, channel: ChannelIdentifier) -> Option<String> {
+        let pane = panes.children.get(&channel.node_index)?;
+        let channel_ref =
+            pane.behaviour_data.node_configuration.channel(channel.channel_direction, channel.into());
+        let mut description = format!("{}: {}", channel_ref.title, channel_ref.ty);
+
+        if let Some(value_taps) = panes.behaviour.value_taps.as_ref().filter(|_| is_pointer(channel_ref.ty)) {
+            description.push_str(", ");
+            description.push_str(&Self::describe_pointer_channel(panes, value_taps, channel));
+        }
+
+        if let Some(channel_description) = channel_ref.description {
+            description.push('\n');
+            description.push_str(channel_description);
+        }
+
+        Some(description)
+    }
+
+    /// The number of references to the allocation the pointer channel most recently passed.
+    fn describe_pointer_channel(
+        panes: &FloatingPanes<'_, M, R, Self>,
+        value_taps: &ValueTaps,
+        channel: ChannelIdentifier,
+    ) -> String {
+        let output = match channel.channel_direction {
+            ChannelDirection::In => panes
+                .behaviour
+                .connections
+                .iter()
+                .find(|connection| connection.to() == channel)
+                .map(|connection| connection.from()),
+            ChannelDirection::Out => Some(channel),
+        };
+        let refcount = output
+            .and_then(|output| value_taps.pointer(output))
+            .and_then(|ptr| Allocator::get().refcount(ptr));
+
+        refcount.map(refcount_label).unwrap_or_else(|| "no allocation".to_string())
+    }
+
+    fn is_connected(&self, channel: ChannelIdentifier) -> bool {
+        self.connections.iter().any(|connection| connection.channel(channel.channel_direction) == channel)
+    }
+
+    /// The node whose title bar is at the point, the topmost one if the panes overlap.
+    fn title_bar_at(
+        panes: &FloatingPanes<'_, M, R, Self>,
+        layout: FloatingPanesLayout<'_>,
+        point: Point,
+    ) -> Option<NodeIndex> {
+        panes
+            .pane_at(layout, point)
+            .filter(|layout_index| !layout.pane_with_index(*layout_index).content().bounds().contains(point))
+            .map(|layout_index| *panes.children.get_index(layout_index).unwrap().0)
+    }
+
+    /// The connections from the outputs of one node to the unconnected inputs of another, see
+    /// [`pair_channels`].
+    fn propose_connections(
+        panes: &FloatingPanes<'_, M, R, Self>,
+        from: NodeIndex,
+        to: NodeIndex,
+    ) -> Vec<Connection> {
+        let configuration = |node_index: NodeIndex| {
+            &panes.children.get(&node_index).unwrap().behaviour_data.node_configuration
+        };
+
+        pair_channels(
+            (from, configuration(from)),
+            (to, configuration(to)),
+            |channel| panes.behaviour.is_connected(channel),
+            |connection| Self::can_connect(panes, connection.from(), connection.to()),
+        )
+    }
+
+    /// The connection passing closest to the center of the pane, along with the pairs of
+    /// connections through the node of the pane it can be replaced with, the one leading into the
+    /// node first. Only unconnected inputs of the node are considered. Only the connections the
+    /// [`HitTestIndex`] lists near the pane are tested, so it must be up to date.
+    fn splice_candidates(
+        panes: &FloatingPanes<'_, M, R, Self>,
+        layout: FloatingPanesLayout<'_>,
+        pane_index: usize,
+    ) -> Option<(Connection, Vec<[Connection; 2]>)> {
+        let (node_index, pane) = panes.children.get_index(pane_index)?;
+        let node_index = *node_index;
+        let pane_bounds = layout.pane_with_index(pane_index).bounds();
+        let center = Vec2::new(pane_bounds.center_x(), pane_bounds.center_y());
+        // The connection has to pass through the pane, rather than just touch its edge.
+        let max_distance = pane_bounds.width.min(pane_bounds.height) / 2.0;
+        let origin = layout.position();
+        let nearby_connections = panes.behaviour_state.hit_test_index.connections_within(Rectangle {
+            x: pane_bounds.x - origin.x,
+            y: pane_bounds.y - origin.y,
+            ..pane_bounds
+        });
+        let channels = |direction| {
+            pane.behaviour_data
+                .node_configuration
+                .channels(direction)
+                .map(move |channel_ref| channel_ref.into_identifier(node_index))
+        };
+        let get_channel = |channel: ChannelIdentifier| {
+            let pane = panes.children.get(&channel.node_index).unwrap();
+
+            pane.behaviour_data.node_configuration.channel(channel.channel_direction, channel.into())
+        };
+
+        nearby_connections
+            .into_iter()
+            .map(|index| &panes.behaviour.connections[index])
+            .filter(|connection| {
+                connection.from().node_index != node_index && connection.to().node_index != node_index
+            })
+            .filter_map(|connection| {
+                let distance_squared =
+                    panes.connection_curve(layout, connection).get_distance_squared(center, max_distance)?;
+                let mut candidates = Vec::new();
+
+                for input in
+                    channels(ChannelDirection::In).filter(|input| !panes.behaviour.is_connected(*input))
+                {
+                    for output in channels(ChannelDirection::Out) {
+                        let through = [
+                            Connection::try_from_identifiers([connection.from(), input]).unwrap(),
+                            Connection::try_from_identifiers([output, connection.to()]).unwrap(),
+                        ];
+                        // Whether outputs would be aliased once the connection is spliced.
+                        let is_aliased = |from: ChannelIdentifier| {
+                            let remaining_connections = panes
+                                .behaviour
+                                .connections
+                                .iter()
+                                .filter(|existing| existing.from() == from && *existing != connection)
+                                .count();
+                            let created_connections =
+                                through.iter().filter(|created| created.from() == from).count();
+
+                            remaining_connections + created_connections > 1
+                        };
+
+                        if through.iter().all(|created| {
+                            created.is_valid(&is_aliased, &get_channel, panes.behaviour.implicit_promotion)
+                        }) {
+                            candidates.push(through);
+                        }
+                    }
+                }
+
+                if candidates.is_empty() {
+                    None
+                } else {
+                    Some((distance_squared, connection.clone(), candidates))
+                }
+            })
+            .min_by(|(a, ..), (b, ..)| a.partial_cmp(b).unwrap())
+            .map(|(_, connection, candidates)| (connection, candidates))
+    }
+}
+
+impl<'a, M: Clone + 'a, R: 'a + WidgetRenderer> floating_panes::FloatingPanesBehaviour<'a, M, R>
+    for FloatingPanesBehaviour<M, R>
+{
+    type FloatingPaneIndex = NodeIndex;
+    type FloatingPaneBehaviourData = FloatingPaneBehaviourData;
+    type FloatingPaneBehaviourState = FloatingPaneBehaviourState;
+    type FloatingPanesBehaviourState = FloatingPanesBehaviourState;
+
+    fn draw_panes(
+        panes: &FloatingPanes<'a, M, R, Self>,
+        renderer: &mut R,
+        defaults: &<R as iced_native::Renderer>::Defaults,
+        layout: FloatingPanesLayout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> ContentDrawResult<R> {
+        <R as WidgetRenderer>::draw_panes(renderer, panes, defaults, layout, cursor_position, viewport)
+    }
+
+    fn hash_panes(_panes: &FloatingPanes<'a, M, R, Self>, _state: &mut Hasher) {
+        // This implementation of [`floating_panes::FloatingPanesBehaviour`] does not influence the
+        // layout of the floating panes.
+    }
+
+    fn on_event(
+        panes: &mut FloatingPanes<'a, M, R, Self>,
+        event: Event,
+        layout: FloatingPanesLayout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<M>,
+        renderer: &R,
+        clipboard: Option<&dyn Clipboard>,
+    ) -> Status {
+        // The tabs are drawn over the panes. The panes are the root widget, so the viewport starts
+        // at the origin.
+        if let Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) = event {
+            let target = panes
+                .behaviour
+                .document_tabs
+                .as_ref()
+                .and_then(|document_tabs| document_tabs.target(Point::ORIGIN, cursor_position));
+
+            if let Some(target) = target {
+                let document_tabs = panes.behaviour.document_tabs.as_ref().unwrap();
+
+                messages.push(match target {
+                    DocumentTabsTarget::Select(index) => (panes.behaviour.on_document_select)(index),
+                    DocumentTabsTarget::Pin(index) => {
+                        (panes.behaviour.on_document_pin)(index, !document_tabs.is_pinned(index))
+                    }
+                    DocumentTabsTarget::Close(index) => (panes.behaviour.on_document_close)(index),
+                    DocumentTabsTarget::Create => (panes.behaviour.on_document_create)(),
+                });
+                panes.behaviour_state.context_menu = None;
+
+                return Status::Captured;
+            }
+        }
+
+        match event {
+            Event::Mouse(MouseEvent::CursorMoved { x, y }) => {
+                let cursor_position = Vec2::new(x, y);
+
+                panes.behaviour_state.highlight = None;
+                panes.behaviour_state.refusal = None;
+
+                // Propose the connections to the node whose title bar is hovered, instead of
+                // highlighting channels
+                if let Some(from) = panes.behaviour_state.bulk_connection.as_ref().map(|bulk| bulk.from) {
+                    let to = FloatingPanesBehaviour::title_bar_at(
+                        panes,
+                        layout,
+                        cursor_position.into_array().into(),
+                    )
+                    .filter(|to| *to != from);
+                    let proposed = to
+                        .map(|to| FloatingPanesBehaviour::propose_connections(panes, from, to))
+                        .unwrap_or_default();
+
+                    panes.behaviour_state.bulk_connection = Some(BulkConnection { from, to, proposed });
+                    return Status::Ignored;
+                }
+
+                // Only the panes and connections near the cursor are tested exactly.
+                panes.update_hit_test_index(layout);
+
+                let origin = layout.position();
+                let candidates = panes
+                    .behaviour_state
+                    .hit_test_index
+                    .query(cursor_position - Vec2::new(origin.x, origin.y))
+                    .collect::<Vec<_>>();
+                let z_order = panes.z_order();
+                let hovered_channels =
+                    HitTestIndex::hovered_channels(&candidates, cursor_position, |node_index| {
+                        let layout_index = panes.get_layout_index_from_pane_index(&node_index)?;
+                        let pane_layout = layout.pane_with_index(layout_index);
+                        let node = panes.children.get(&node_index).unwrap();
+                        // Channels of collapsed groups cannot be selected.
+                        let channels = node
+                            .behaviour_data
+                            .visible_channel_layouts(pane_layout, ChannelDirection::In)
+                            .chain(
+                                node.behaviour_data
+                                    .visible_channel_layouts(pane_layout, ChannelDirection::Out),
+                            )
+                            .map(|(channel_layout, channel_ref)| {
+                                (channel_ref.into_identifier(node_index), channel_layout.bounds())
+                            })
+                            .collect();
+
+                        Some(HoverPane {
+                            z_index: z_order.iter().position(|z_index| *z_index == layout_index)?,
+                            bounds: pane_layout
+                                .bounds()
+                                .grow_symmetrical(style::consts::SPACING_HORIZONTAL as f32, 0.0),
+                            collapsed: node.behaviour_data.collapsed,
+                            channels,
+                        })
+                    });
+
+                // Highlight channel, if possible
+                for channel in hovered_channels {
+                    // If a new connection is being formed, make sure the target channel
+                    // can be connected to.
+                    let refusal = panes
+                        .behaviour_state
+                        .selected_channel
+                        .filter(|selected_channel| *selected_channel != channel)
+                        .and_then(|selected_channel| {
+                            FloatingPanesBehaviour::check_connection(panes, selected_channel, channel).err()
+                        });
+
+                    if let Some(refusal) = refusal {
+                        panes.behaviour_state.refusal = Some(refusal);
+                    } else if panes.behaviour_state.selected_channel != Some(channel) {
+                        panes.behaviour_state.highlight = Some(Highlight::Channel(channel));
+                    }
+                }
+
+                // Otherwise, highlight a connection, if one is not being created
+                if panes.behaviour_state.highlight.is_none()
+                    && panes.behaviour_state.selected_channel.is_none()
+                {
+                    let index = &panes.behaviour_state.hit_test_index;
+                    let closest_highlight =
+                        HitTestIndex::hovered_connection(&candidates, cursor_position, |group_index| {
+                            match panes.connection_layout(layout, index.group(group_index)) {
+                                ConnectionLayout::Single(connection, curve) => {
+                                    vec![(Highlight::Connection(connection.clone()), curve)]
+                                }
+                                // Within bundles, the stubs resolve to individual connections, while
+                                // the shared cable resolves to the whole bundle.
+                                ConnectionLayout::Bundle(bundle) => {
+                                    let cable = bundle.cable();
+                                    let mut curves = vec![(Highlight::Bundle(bundle.from, bundle.to), cable)];
+
+                                    for (connection, curve) in &bundle.connections {
+                                        for stub in &ConnectionBundle::stubs(&cable, curve) {
+                                            curves
+                                                .push((Highlight::Connection((*connection).clone()), *stub));
+                                        }
+                                    }
+
+                                    curves
+                                }
+                            }
+                        });
+
+                    if let Some(closest_highlight) = closest_highlight {
+                        panes.behaviour_state.highlight = Some(closest_highlight);
+                    }
+                }
+            }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left))
+                if panes.behaviour_state.context_menu.is_some() =>
+            {
+                // The context menu is closed once the button is released, so that the buttons of
+                // the menu receive the click.
+                return Status::Captured;
+            }
+            Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)) => {
+                panes.behaviour_state.context_menu = None;
+
+                // Create all proposed connections between the two nodes at once
+                if let Some(bulk_connection) = panes.behaviour_state.bulk_connection.take() {
+                    if !bulk_connection.proposed.is_empty() {
+                        messages.push((panes.behaviour.on_connections_create)(bulk_connection.proposed));
+                    }
+
+                    return Status::Captured;
+                }
+
+                // Move the arranged channel to the row it is dropped onto
+                if let Some((node, direction, channel)) = panes.behaviour_state.arranged_channel.take() {
+                    let target = layout
+                        .panes()
+                        .zip(panes.children.iter())
+                        .find(|(_, (node_index, _))| **node_index == node)
+                        .and_then(|(pane_layout, (_, pane))| {
+                            pane.behaviour_data.channel_at(pane_layout, direction, cursor_position)
+                        })
+                        .map(|(target, _)| target);
+
+                    if let Some(target) = target.filter(|target| *target != channel) {
+                        messages.push((panes.behaviour.on_channel_move)(node, direction, channel, target));
+                    }
+
+                    return Status::Captured;
+                }
+
+                // Splice a moved node into the connection it is dropped onto, unless Shift is held.
+                // The gesture is finished by the floating panes, so that the move is recorded.
+                if let Some(Gesture::GrabPane { pane_index, grab_state }) = panes.state.gesture.clone() {
+                    let moved = panes
+                        .children
+                        .get_index(pane_index)
+                        .map(|(_, pane)| pane.state.position != grab_state.grab_element_position)
+                        .unwrap_or(false);
+
+                    if moved && !panes.behaviour_state.modifiers.shift {
+                        panes.update_hit_test_index(layout);
+
+                        if let Some((connection, mut candidates)) =
+                            FloatingPanesBehaviour::splice_candidates(panes, layout, pane_index)
+                        {
+                            if candidates.len() == 1 {
+                                let through = candidates.pop().unwrap();
+
+                                messages.push((panes.behaviour.on_connection_splice)(connection, through));
+                            } else {
+                                // Let the user choose the channels to splice the node in through.
+                                panes.behaviour_state.context_menu = Some(ContextMenu::new(
+                                    ContextMenuTarget::Splice { connection, candidates },
+                                    panes.state.cursor_position,
+                                ));
+                            }
+                        }
+                    }
+                }
+
+                // Create a node connected to the pending connection, if it is dropped on empty
+                // space, unless Shift is held
+                if let Some(selected_channel) = panes.behaviour_state.selected_channel {
+                    let over_pane = layout.panes().any(|pane_layout| {
+                        pane_layout
+                            .bounds()
+                            .grow_symmetrical(style::consts::SPACING_HORIZONTAL as f32, 0.0)
+                            .contains(cursor_position)
+                    });
+
+                    if panes.behaviour.create_nodes_on_drop
+                        && !panes.behaviour_state.modifiers.shift
+                        && !over_pane
+                    {
+                        let panes_position: Vec2<f32> = Into::<[f32; 2]>::into(layout.position()).into();
+                        let position = panes.state.cursor_position - panes_position;
+
+                        messages.push((panes.behaviour.on_node_create)(selected_channel, position));
+                        panes.behaviour_state.selected_channel = None;
+                        return Status::Captured;
+                    }
+                }
+            }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left))
+                if panes.behaviour_state.modifiers.alt
+                    && panes
+                        .children
+                        .values()
+                        .any(|pane| pane.state.title_bar_status == InteractionStatus::Hovered) =>
+            {
+                // Start connecting the channels of the node to another node instead of moving it
+                let from = panes
+                    .children
+                    .iter()
+                    .find(|(_, pane)| pane.state.title_bar_status == InteractionStatus::Hovered)
+                    .map(|(node_index, _)| *node_index)
+                    .unwrap();
+
+                panes.behaviour_state.bulk_connection =
+                    Some(BulkConnection { from, to: None, proposed: Vec::new() });
+                panes.behaviour_state.selected_channel = None;
+
+                return Status::Captured;
+            }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left))
+                if panes.behaviour_state.modifiers.control
+                    && panes
+                        .children
+                        .values()
+                        .any(|pane| pane.state.title_bar_status == InteractionStatus::Hovered) =>
+            {
+                // Toggle the selection of the node instead of moving it
+                let (node_index, pane) = panes
+                    .children
+                    .iter()
+                    .find(|(_, pane)| pane.state.title_bar_status == InteractionStatus::Hovered)
+                    .unwrap();
+
+                messages.push((panes.behaviour.on_node_select)(*node_index, !pane.behaviour_data.selected));
+
+                return Status::Captured;
+            }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) => {
+                // Pick up the row of a channel, if the channels of its node are being arranged
+                let arranged_channel = layout.panes().zip(panes.children.iter()).find_map(
+                    |(pane_layout, (node_index, pane))| {
+                        if !pane.behaviour_data.arranging_channels {
+                            return None;
+                        }
+
+                        [ChannelDirection::In, ChannelDirection::Out].iter().find_map(|direction| {
+                            pane.behaviour_data
+                                .channel_at(pane_layout, *direction, cursor_position)
+                                .map(|(channel, _)| (*node_index, *direction, channel))
+                        })
+                    },
+                );
+
+                if arranged_channel.is_some() {
+                    panes.behaviour_state.arranged_channel = arranged_channel;
+                    panes.behaviour_state.selected_channel = None;
+                    return Status::Captured;
+                }
+
+                if let Some(highlight) = panes.behaviour_state.highlight.take() {
+                    match highlight {
+                        Highlight::Connection(highlighted_connection) => {
+                            panes.behaviour_state.selected_channel = Some(highlighted_connection.from());
+
+                            if !panes.behaviour_state.clones_connections() {
+                                messages.push((panes.behaviour.on_channel_disconnect)(
+                                    highlighted_connection.to(),
+                                ));
+                            }
+                        }
+                        // The connections of a bundle are only picked up by their stubs, so that it is
+                        // clear which one is disconnected.
+                        Highlight::Bundle(..) => (),
+                        Highlight::Channel(channel @ ChannelIdentifier { channel_direction, .. }) => {
+                            let disconnect = match channel_direction {
+                                ChannelDirection::In => panes.behaviour.is_connected(channel),
+                                ChannelDirection::Out => false,
+                            };
+
+                            // Is connection pending?
+                            if let Some(selected_channel) = panes.behaviour_state.selected_channel.clone() {
+                                if FloatingPanesBehaviour::can_connect(panes, selected_channel, channel) {
+                                    if disconnect {
+                                        messages.push((panes.behaviour.on_channel_disconnect)(channel));
+                                    }
+
+                                    let channels = match selected_channel.channel_direction {
+                                        ChannelDirection::In => [channel, selected_channel],
+                                        ChannelDirection::Out => [selected_channel, channel],
+                                    };
+
+                                    messages.push((panes.behaviour.on_connection_create)(
+                                        Connection::try_from_identifiers(channels).unwrap(),
+                                    ));
+                                    panes.behaviour_state.selected_channel = None;
+                                }
+                            } else {
+                                if disconnect {
+                                    let connection = panes
+                                        .behaviour
+                                        .connections
+                                        .iter()
+                                        .find(|connection| connection.contains_channel(channel));
+                                    if let Some(connection) = connection {
+                                        let other_channel =
+                                            connection.channel(channel.channel_direction.inverse());
+                                        panes.behaviour_state.selected_channel = Some(other_channel);
+
+                                        // Keep the connection, if it is being cloned.
+                                        if !panes.behaviour_state.clones_connections() {
+                                            messages.push((panes.behaviour.on_channel_disconnect)(channel));
+                                        }
+                                    }
+                                } else {
+                                    panes.behaviour_state.selected_channel = Some(channel);
+                                }
+                            }
+                        }
+                    }
+
+                    // Properly update the highlight
+                    Self::on_event(
+                        panes,
+                        Event::Mouse(MouseEvent::CursorMoved {
+                            x: panes.state.cursor_position.x,
+                            y: panes.state.cursor_position.y,
+                        }),
+                        layout,
+                        cursor_position,
+                        messages,
+                        renderer,
+                        clipboard,
+                    );
+                    return Status::Captured;
+                }
+
+                // Toggle a channel group, if its header is clicked
+                let clicked_group = layout.panes().zip(panes.children.iter()).find_map(
+                    |(pane_layout, (node_index, pane))| {
+                        // Headers scrolled out of view or of collapsed nodes cannot be clicked
+                        if pane.behaviour_data.collapsed
+                            || !pane_layout.content().bounds().contains(cursor_position)
+                        {
+                            return None;
+                        }
+
+                        [ChannelDirection::In, ChannelDirection::Out].iter().find_map(|direction| {
+                            let channels_layout =
+                                pane_layout.content().scrolled_content().channels_with_direction(*direction);
+
+                            pane.behaviour_data.channel_rows(*direction).rows().iter().enumerate().find_map(
+                                |(row_index, row)| match row {
+                                    ChannelRow::GroupHeader { group, .. }
+                                        if channels_layout
+                                            .channel(row_index)
+                                            .bounds()
+                                            .contains(cursor_position) =>
+                                    {
+                                        Some((*node_index, *direction, group.clone()))
+                                    }
+                                    _ => None,
+                                },
+                            )
+                        })
+                    },
+                );
+
+                if let Some((node, direction, group)) = clicked_group {
+                    messages.push((panes.behaviour.on_channel_group_toggle)(node, direction, group));
+                    return Status::Captured;
+                }
+
+                panes.behaviour_state.selected_channel = None;
+            }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Right)) => {
+                panes.behaviour_state.context_menu = None;
+
+                // Open the context menu of the channel, connection or bundle, if one is hovered
+                let target = match panes.behaviour_state.highlight.clone() {
+                    Some(Highlight::Channel(channel)) => Some(ContextMenuTarget::Channel(channel)),
+                    Some(Highlight::Connection(connection)) => {
+                        Some(ContextMenuTarget::Connection(connection))
+                    }
+                    Some(Highlight::Bundle(from, to)) => Some(ContextMenuTarget::Bundle(from, to)),
+                    None => None,
+                };
+
+                if let Some(target) = target {
+                    panes.behaviour_state.context_menu =
+                        Some(ContextMenu::new(target, panes.state.cursor_position));
+                    return Status::Captured;
+                }
+
+                // Open the context menu of the node, if the title bar is hovered
+                let hovered_node_index = panes
+                    .children
+                    .iter()
+                    .find(|(_, pane)| pane.state.title_bar_status == InteractionStatus::Hovered)
+                    .map(|(node_index, _)| *node_index);
+
+                if let Some(node_index) = hovered_node_index {
+                    panes.behaviour_state.context_menu = Some(ContextMenu::new(
+                        ContextMenuTarget::Node(node_index),
+                        panes.state.cursor_position,
+                    ));
+                    return Status::Captured;
+                }
+            }
+            Event::Keyboard(KeyboardEvent::ModifiersChanged(modifiers)) => {
+                panes.behaviour_state.modifiers = modifiers;
+            }
+            Event::Keyboard(KeyboardEvent::KeyPressed { key_code, modifiers }) => {
+                let action = Shortcut::new(key_code, modifiers)
+                    .and_then(|shortcut| panes.behaviour.keymap.action(shortcut, false));
+
+                match action {
+                    Some(Action::Cancel) if panes.behaviour_state.selected_channel.is_some() => {
+                        // Cancel the pending connection
+                        panes.behaviour_state.selected_channel = None;
+                        panes.behaviour_state.refusal = None;
+                        return Status::Captured;
+                    }
+                    Some(Action::Cancel) if panes.behaviour_state.bulk_connection.is_some() => {
+                        // Cancel connecting the two nodes
+                        panes.behaviour_state.bulk_connection = None;
+                        return Status::Captured;
+                    }
+                    Some(Action::RemoveNode) => {
+                        // Remove the node, if the title bar is hovered
+                        let hovered_node_index = panes
+                            .children
+                            .iter()
+                            .find(|(_, pane)| pane.state.title_bar_status == InteractionStatus::Hovered)
+                            .map(|(node_index, _)| *node_index);
+
+                        if let Some(node_index) = hovered_node_index {
+                            messages.push((panes.behaviour.on_node_remove)(node_index));
+                            return Status::Captured;
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+
+        Status::Ignored
+    }
+
+    fn overlay<'b>(
+        panes: &'b mut FloatingPanes<'a, M, R, Self>,
+        layout: Layout<'_>,
+    ) -> Option<overlay::Element<'b, M, R>> {
+        if panes.behaviour_state.context_menu.is_some() {
+            let context_menu = panes.behaviour_state.context_menu.as_mut().unwrap();
+            let children = &panes.children;
+            let channel_title = |channel: ChannelIdentifier| {
+                children
+                    .get(&channel.node_index)
+                    .unwrap()
+                    .behaviour_data
+                    .node_configuration
+                    .channel(channel.channel_direction, channel.into())
+                    .title
+                    .to_string()
+            };
+            let column = match context_menu.target.clone() {
+                ContextMenuTarget::Channel(channel) => {
+                    let candidates = panes
+                        .behaviour
+                        .reconnect_candidates
+                        .iter()
+                        .filter(|candidate| candidate.connection.contains_channel(channel))
+                        .collect::<Vec<_>>();
+
+                    context_menu.button_states.resize_with(candidates.len(), Default::default);
+
+                    let mut column = Column::<M, R>::new().max_width(320);
+
+                    if candidates.is_empty() {
+                        column = column.push(
+                            Text::new("No previous connections").size(style::consts::TEXT_SIZE_REGULAR),
+                        );
+                    }
+
+                    for (candidate, button_state) in
+                        candidates.into_iter().zip(context_menu.button_states.iter_mut())
+                    {
+                        column = column.push(context_menu_button(
+                            button_state,
+                            format!("Reconnect to {}", candidate.counterpart_label(channel)),
+                            Some((panes.behaviour.on_reconnect)(candidate.connection.clone())),
+                        ));
+                    }
+
+                    if channel.channel_direction == ChannelDirection::Out {
+                        let unsupported_reason = panes
+                            .children
+                            .get(&channel.node_index)
+                            .and_then(|pane| {
+                                pane.behaviour_data
+                                    .node_configuration
+                                    .channels(ChannelDirection::Out)
+                                    .find(|channel_ref| channel_ref.edge_endpoint == channel.into())
+                                    .map(|channel_ref| ValueSnapshot::unsupported_reason(channel_ref.ty))
+                            })
+                            .unwrap_or(Some("The channel no longer exists."));
+
+                        // The button is disabled, if the value cannot be frozen
+                        column = column.push(context_menu_button(
+                            &mut context_menu.freeze_button_state,
+                            "Freeze value",
+                            Some((panes.behaviour.on_value_freeze)(channel))
+                                .filter(|_| unsupported_reason.is_none()),
+                        ));
+
+                        if let Some(reason) = unsupported_reason {
+                            column = column.push(Text::new(reason).size(style::consts::TEXT_SIZE_REGULAR));
+                        }
+                    }
+
+                    column
+                }
+                ContextMenuTarget::Connection(connection) => {
+                    context_menu.button_states.resize_with(2, Default::default);
+
+                    let mut button_states = context_menu.button_states.iter_mut();
+                    let primary_button_state = button_states.next().unwrap();
+                    let disconnect_button_state = button_states.next().unwrap();
+                    let recipient = panes.behaviour.move_recipients.get(&connection.from());
+                    let primary = recipient
+                        .map(|recipient| recipient.primary && recipient.connection == connection)
+                        .unwrap_or(false);
+                    // Only connections requiring the value can be marked as primary
+                    let primary_button = context_menu_button(
+                        primary_button_state,
+                        "Move the value into this connection",
+                        Some((panes.behaviour.on_connection_primary)(connection.clone()))
+                            .filter(|_| connection.requires_exclusive_access() && !primary),
+                    );
+
+                    let status = match recipient {
+                        Some(recipient) if recipient.connection == connection => {
+                            if primary {
+                                "Marked as primary, the value is moved into this connection."
+                            } else {
+                                "The value is moved into this connection, which was created first."
+                            }
+                        }
+                        Some(_) => "The value is moved into another connection.",
+                        None => "The value is only borrowed.",
+                    };
+
+                    Column::<M, R>::new()
+                        .max_width(320)
+                        .push(Text::new(status).size(style::consts::TEXT_SIZE_REGULAR))
+                        .push(primary_button)
+                        .push(context_menu_button(
+                            disconnect_button_state,
+                            format!(
+                                "Disconnect {} → {}",
+                                channel_title(connection.from()),
+                                channel_title(connection.to()),
+                            ),
+                            Some((panes.behaviour.on_channel_disconnect)(connection.to())),
+                        ))
+                }
+                ContextMenuTarget::Bundle(from, to) => {
+                    let connections = panes
+                        .behaviour
                         .connections
                         .iter()
-                        .map(|connection| {
-                            let layout_from = layout
-                                .panes()
-                                .nth(
-                                    NodeElement::<M, R>::get_layout_index_from_channel(
-                                        panes,
-                                        connection.from(),
-                                    )
-                                    .unwrap(),
-                                )
-                                .unwrap();
-                            let layout_to = layout
-                                .panes()
-                                .nth(
-                                    NodeElement::<M, R>::get_layout_index_from_channel(
-                                        panes,
-                                        connection.to(),
-                                    )
-                                    .unwrap(),
-                                )
-                                .unwrap();
-                            let layout_outputs =
-                                layout_from.content().channels_with_direction(ChannelDirection::Out);
-                            let layout_inputs =
-                                layout_to.content().channels_with_direction(ChannelDirection::In);
-                            let layout_output = layout_outputs.channel(connection.from().channel_index);
-                            let layout_input = layout_inputs.channel(connection.to().channel_index);
-                            let connection_curve =
-                                ConnectionCurve::from_channel_layouts::<M, R>(layout_output, layout_input);
-                            let connection_distance_squared = connection_curve
-                                .get_distance_squared(cursor_position, MAX_CONNECTION_HIGHLIGHT_DISTANCE);
-
-                            (connection, connection_distance_squared)
+                        .filter(|connection| {
+                            connection.from().node_index == from && connection.to().node_index == to
                         })
-                        .filter_map(|(connection, distance_squared)| {
-                            distance_squared.map(move |distance_squared| (connection, distance_squared))
+                        .collect::<Vec<_>>();
+
+                    context_menu.button_states.resize_with(connections.len(), Default::default);
+
+                    let mut column = Column::<M, R>::new().max_width(320);
+
+                    for (connection, button_state) in
+                        connections.into_iter().zip(context_menu.button_states.iter_mut())
+                    {
+                        column = column.push(context_menu_button(
+                            button_state,
+                            format!(
+                                "Disconnect {} → {}",
+                                channel_title(connection.from()),
+                                channel_title(connection.to()),
+                            ),
+                            Some((panes.behaviour.on_channel_disconnect)(connection.to())),
+                        ));
+                    }
+
+                    column
+                }
+                ContextMenuTarget::Splice { connection, candidates } => {
+                    context_menu.button_states.resize_with(candidates.len(), Default::default);
+
+                    let mut column = Column::<M, R>::new()
+                        .max_width(320)
+                        .push(Text::new("Splice the node in through").size(style::consts::TEXT_SIZE_REGULAR));
+
+                    for (through, button_state) in
+                        candidates.into_iter().zip(context_menu.button_states.iter_mut())
+                    {
+                        column = column.push(context_menu_button(
+                            button_state,
+                            format!(
+                                "{} → {}",
+                                channel_title(through[0].to()),
+                                channel_title(through[1].from())
+                            ),
+                            Some((panes.behaviour.on_connection_splice)(connection.clone(), through)),
+                        ));
+                    }
+
+                    column
+                }
+                ContextMenuTarget::Node(node) => {
+                    let behaviour_data = children.get(&node).map(|pane| &pane.behaviour_data);
+                    let pane_state = children.get(&node).map(|pane| &*pane.state);
+                    let pinned = pane_state.map_or(false, |state| state.pinned);
+                    let click_through = pane_state.map_or(false, |state| state.click_through);
+                    let opacity = pane_state.map_or(1.0, |state| state.opacity);
+                    let muted = behaviour_data.and_then(|data| data.muted);
+                    let arranging_channels = behaviour_data.map_or(false, |data| data.arranging_channels);
+                    let channel_order_natural =
+                        behaviour_data.map_or(true, |data| data.channel_order_natural);
+                    let color = behaviour_data.and_then(|data| data.color);
+                    let items = &panes.behaviour.node_context_menu_items;
+                    // No color, followed by the colors of the palette.
+                    let swatches = std::iter::once(None).chain(NodeColor::VALUES.iter().copied().map(Some));
+
+                    context_menu
+                        .button_states
+                        .resize_with(8 + NodeColor::VALUES.len() + items.len(), Default::default);
+
+                    let mut button_states = context_menu.button_states.iter_mut();
+                    let mut palette = Row::<M, R>::new().spacing(style::consts::SPACING_HORIZONTAL);
+
+                    for (swatch, button_state) in swatches.zip(&mut button_states) {
+                        let text = match swatch {
+                            Some(swatch) => {
+                                Text::new(if Some(swatch) == color { "▣" } else { "■" }).color(swatch.color())
+                            }
+                            None => Text::new("∅"),
+                        };
+                        let button = Button::new(button_state, text.size(style::consts::TEXT_SIZE_REGULAR));
+
+                        // The current color cannot be picked again.
+                        palette = palette.push(if swatch == color {
+                            button
+                        } else {
+                            button.on_press((panes.behaviour.on_node_color)(node, swatch))
+                        });
+                    }
+
+                    let mut column = Column::<M, R>::new().max_width(320).push(palette);
+                    let mute_button_state = button_states.next().unwrap();
+
+                    // Only sinks can be muted
+                    if let Some(muted) = muted {
+                        column = column.push(context_menu_button(
+                            mute_button_state,
+                            if muted { "Unmute" } else { "Mute" },
+                            Some((panes.behaviour.on_node_mute)(node, !muted)),
+                        ));
+                    }
+
+                    let on_node_opacity = panes.behaviour.on_node_opacity;
+
+                    column = column.push(context_menu_button(
+                        button_states.next().unwrap(),
+                        if pinned { "Unpin" } else { "Pin on top" },
+                        Some((panes.behaviour.on_node_pin)(node, !pinned)),
+                    ));
+                    column = column.push(context_menu_button(
+                        button_states.next().unwrap(),
+                        if click_through { "Capture clicks" } else { "Click through" },
+                        Some((panes.behaviour.on_node_click_through)(node, !click_through)),
+                    ));
+                    column = column.push(
+                        DragValue::new(&mut context_menu.opacity_state, opacity * 100.0, move |opacity| {
+                            on_node_opacity(node, opacity as f32 / 100.0)
                         })
-                        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-                        .map(|(connection, _)| connection);
+                        .label("Opacity %")
+                        .range(MIN_PANE_OPACITY as f64 * 100.0, 100.0)
+                        .step(1.0)
+                        .speed(0.5)
+                        .text_size(style::consts::TEXT_SIZE_REGULAR)
+                        .width(Length::Fill),
+                    );
+
+                    column = column.push(context_menu_button(
+                        button_states.next().unwrap(),
+                        if arranging_channels { "Finish arranging channels" } else { "Arrange channels" },
+                        Some((panes.behaviour.on_channels_arrange)(node, !arranging_channels)),
+                    ));
+                    column = column.push(context_menu_button(
+                        button_states.next().unwrap(),
+                        "Reset channel order",
+                        Some((panes.behaviour.on_channel_order_reset)(node))
+                            .filter(|_| !channel_order_natural),
+                    ));
+                    column = column.push(context_menu_button(
+                        button_states.next().unwrap(),
+                        "Save as preset",
+                        Some((panes.behaviour.on_preset_save)(node)),
+                    ));
+                    column = column.push(context_menu_button(
+                        button_states.next().unwrap(),
+                        "Remove",
+                        Some((panes.behaviour.on_node_remove)(node)),
+                    ));
+
+                    if !items.is_empty() {
+                        column = column.push(Rule::horizontal(style::consts::SPACING_VERTICAL));
+                    }
 
-                    if let Some(closest_connection) = closest_connection {
-                        panes.behaviour_state.highlight =
-                            Some(Highlight::Connection(closest_connection.clone()));
+                    for (item, button_state) in items.iter().zip(button_states) {
+                        column = column.push(context_menu_button(
+                            button_state,
+                            item.label.clone(),
+                            Some(item.message.clone()).filter(|_| item.enabled),
+                        ));
                     }
+
+                    column
                 }
+            };
+
+            let mut container = Container::new(Margin::new(column, style::consts::SPACING));
+
+            if let Some(style) = panes.behaviour.tooltip_style.as_ref() {
+                container = container.style(style.container_style());
             }
-            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) => {
-                if let Some(highlight) = panes.behaviour_state.highlight.take() {
-                    match highlight {
-                        Highlight::Connection(highlighted_connection) => {
-                            panes.behaviour_state.selected_channel = Some(highlighted_connection.from());
-                            messages
-                                .push((panes.behaviour.on_channel_disconnect)(highlighted_connection.to()));
-                        }
-                        Highlight::Channel(channel @ ChannelIdentifier { channel_direction, .. }) => {
-                            let disconnect = match channel_direction {
-                                ChannelDirection::In => panes.behaviour.is_connected(channel),
-                                ChannelDirection::Out => false,
-                            };
 
-                            // Is connection pending?
-                            if let Some(selected_channel) = panes.behaviour_state.selected_channel.clone() {
-                                if FloatingPanesBehaviour::can_connect(panes, selected_channel, channel) {
-                                    if disconnect {
-                                        messages.push((panes.behaviour.on_channel_disconnect)(channel));
-                                    }
+            let position: Point = context_menu.position.into_array().into();
+            let overlay =
+                WidgetOverlay::<M, R, _>::new(container, WidgetOverlayAlignment { top: false, left: false });
 
-                                    let channels = match selected_channel.channel_direction {
-                                        ChannelDirection::In => [channel, selected_channel],
-                                        ChannelDirection::Out => [selected_channel, channel],
-                                    };
+            return Some(overlay::Element::new(position, Box::new(overlay)));
+        }
 
-                                    messages.push((panes.behaviour.on_connection_create)(
-                                        Connection::try_from_identifiers(channels).unwrap(),
-                                    ));
-                                    panes.behaviour_state.selected_channel = None;
-                                }
-                            } else {
-                                if disconnect {
-                                    let connection = panes
-                                        .behaviour
-                                        .connections
-                                        .iter()
-                                        .find(|connection| connection.contains_channel(channel));
-                                    if let Some(connection) = connection {
-                                        let other_channel =
-                                            connection.channel(channel.channel_direction.inverse());
-                                        panes.behaviour_state.selected_channel = Some(other_channel);
+        // The overlays of the node views, such as open dropdown menus, take precedence over the
+        // tooltips, so that they are not hidden while the cursor moves over other panes.
+        let pane_with_overlay = panes
+            .children
+            .iter_mut()
+            .zip(layout.children())
+            .position(|((_, pane), layout)| pane.element_tree.overlay(layout).is_some());
+
+        if let Some(pane_with_overlay) = pane_with_overlay {
+            let ((_, pane), layout) =
+                panes.children.iter_mut().zip(layout.children()).nth(pane_with_overlay).unwrap();
+
+            return pane.element_tree.overlay(layout);
+        }
+
+        // Summarize the pending connection or the reason it cannot be created
+        if let Some(selected_channel) = panes.behaviour_state.selected_channel {
+            let summary = match (&panes.behaviour_state.highlight, &panes.behaviour_state.refusal) {
+                (Some(Highlight::Channel(channel)), _) => {
+                    Connection::try_from_identifiers([selected_channel, *channel])
+                        .map(|connection| FloatingPanesBehaviour::describe_connection(panes, &connection))
+                }
+                (_, Some(refusal)) => Some(refusal.description().into_owned()),
+                _ => None,
+            };
+
+            if let Some(summary) = summary {
+                let mut container = Container::new(Margin::new(
+                    Text::new(summary).size(style::consts::TEXT_SIZE_REGULAR),
+                    style::consts::SPACING,
+                ));
+
+                if let Some(style) = panes.behaviour.tooltip_style.as_ref() {
+                    container = container.style(style.container_style());
+                }
+
+                // Offset the chip, so that it does not cover the hovered channel.
+                const CHIP_OFFSET: f32 = 12.0;
+
+                let position: Point =
+                    (panes.state.cursor_position + Vec2::broadcast(CHIP_OFFSET)).into_array().into();
+                let overlay = WidgetOverlay::<M, R, _>::new(
+                    container,
+                    WidgetOverlayAlignment { top: false, left: false },
+                );
+
+                return Some(overlay::Element::new(position, Box::new(overlay)));
+            }
+        }
+
+        let mut errors = panes
+            .behaviour_state
+            .highlight
+            .as_ref()
+            .and_then(|highlight| highlight.clone().into_graph_validation_error_affected_element())
+            .map(|element| panes.behaviour.graph_validation_errors.get_related_errors(element))
+            .unwrap_or(&[]);
+
+        if errors.is_empty() {
+            errors = panes
+                .children
+                .iter()
+                .find_map(|(pane_index, pane)| {
+                    if pane.state.title_bar_status > InteractionStatus::Idle {
+                        let errors = panes.behaviour.graph_validation_errors.get_related_errors(*pane_index);
+
+                        Some(errors)
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or(&[]);
+        }
+
+        if !errors.is_empty() {
+            let mut column = Column::<M, R>::new();
+
+            for error in errors {
+                let display = error.display();
+                let mut error_element = Column::<M, R>::new()
+                    .max_width(512)
+                    .push(Text::new(display.title.to_string()).size(style::consts::TEXT_SIZE_TITLE))
+                    .push(Text::new(display.description.to_string()).size(style::consts::TEXT_SIZE_REGULAR));
+
+                if let Some(suggestion) = display.suggestion.as_ref() {
+                    error_element = error_element.push(
+                        Text::new(format!("Suggestion: {}", suggestion))
+                            .size(style::consts::TEXT_SIZE_REGULAR),
+                    );
+                }
+
+                let mut container = Container::new(Margin::new(error_element, style::consts::SPACING));
+
+                if let Some(style) = panes.behaviour.tooltip_style.as_ref() {
+                    container = container.style(style.container_style());
+                }
+
+                column = column.push(container);
+            }
+
+            let position: Point = panes.state.cursor_position.into_array().into();
+            let overlay =
+                WidgetOverlay::<M, R, _>::new(column, WidgetOverlayAlignment { top: true, left: false });
+
+            return Some(overlay::Element::new(position, Box::new(overlay)));
+        }
+
+        // Describe the hovered channel, as its title may be ellipsized
+        if let Some(Highlight::Channel(channel)) = panes.behaviour_state.highlight {
+            if let Some(description) = FloatingPanesBehaviour::describe_channel(panes, channel) {
+                let mut container = Container::new(Margin::new(
+                    Text::new(description).size(style::consts::TEXT_SIZE_REGULAR),
+                    style::consts::SPACING,
+                ));
+
+                if let Some(style) = panes.behaviour.tooltip_style.as_ref() {
+                    container = container.style(style.container_style());
+                }
+
+                const CHIP_OFFSET: f32 = 12.0;
+
+                let position: Point =
+                    (panes.state.cursor_position + Vec2::broadcast(CHIP_OFFSET)).into_array().into();
+                let overlay = WidgetOverlay::<M, R, _>::new(
+                    container,
+                    WidgetOverlayAlignment { top: false, left: false },
+                );
+
+                return Some(overlay::Element::new(position, Box::new(overlay)));
+            }
+        }
+
+        None
+    }
+}
+
+pub struct FloatingPaneBehaviourData {
+    pub node_configuration: NodeConfiguration,
+    /// The color tag of the node, picked from the palette in its context menu.
+    pub color: Option<NodeColor>,
+    /// Whether the node is muted, `None` if it is not a sink, which cannot be muted.
+    pub muted: Option<bool>,
+    /// Whether the pane is collapsed, see [`FloatingPaneState::collapsed`].
+    pub collapsed: bool,
+    /// Whether the channel rows are dragged to reorder them, instead of to connect the channels.
+    pub arranging_channels: bool,
+    /// Whether the channels are displayed in the order of their indices.
+    pub channel_order_natural: bool,
+    /// Whether the node is selected, to be aligned with the other selected nodes.
+    pub selected: bool,
+    pub input_channel_rows: ChannelRows,
+    pub output_channel_rows: ChannelRows,
+}
+
+impl FloatingPaneBehaviourData {
+    pub fn channel_rows(&self, direction: ChannelDirection) -> &ChannelRows {
+        match direction {
+            ChannelDirection::In => &self.input_channel_rows,
+            ChannelDirection::Out => &self.output_channel_rows,
+        }
+    }
+
+    /// The layout of the row the channel is displayed in, which is the header of its group, if the
+    /// group is collapsed.
+    pub fn channel_layout<'b>(
+        &self,
+        pane_layout: FloatingPaneLayout<'b>,
+        channel: ChannelIdentifier,
+    ) -> ChannelLayout<'b> {
+        let global_channel_index = self.node_configuration.get_global_channel_index(channel.into());
+        let row = self.channel_rows(channel.channel_direction).row_of_channel(global_channel_index);
+
+        pane_layout
+            .content()
+            .scrolled_content()
+            .channels_with_direction(channel.channel_direction)
+            .channel(row)
+    }
+
+    /// The point connections to the channel are attached to. The point is kept within the content
+    /// of the pane, if the channel is scrolled out of view.
+    pub fn connection_point(
+        &self,
+        pane_layout: FloatingPaneLayout<'_>,
+        channel: ChannelIdentifier,
+    ) -> Vec2<f32> {
+        let viewport = pane_layout.content().bounds();
+        let mut connection_point = get_connection_point(
+            self.channel_layout(pane_layout, channel).bounds(),
+            channel.channel_direction,
+            self.collapsed,
+        );
+
+        connection_point.y =
+            util::partial_clamp(connection_point.y, [viewport.y, viewport.y + viewport.height]);
+
+        connection_point
+    }
+
+    /// The global index and the layout of the channel displayed in its own row at the point, unless
+    /// the row is scrolled out of view.
+    pub fn channel_at<'b>(
+        &self,
+        pane_layout: FloatingPaneLayout<'b>,
+        direction: ChannelDirection,
+        point: Point,
+    ) -> Option<(usize, ChannelLayout<'b>)> {
+        if self.collapsed || !pane_layout.content().bounds().contains(point) {
+            return None;
+        }
+
+        let channels_layout = pane_layout.content().scrolled_content().channels_with_direction(direction);
+
+        self.channel_rows(direction).rows().iter().enumerate().find_map(|(row_index, row)| match row {
+            ChannelRow::Channel(global_channel_index) => {
+                let channel_layout = channels_layout.channel(row_index);
+
+                if channel_layout.bounds().contains(point) {
+                    Some((*global_channel_index, channel_layout))
+                } else {
+                    None
+                }
+            }
+            ChannelRow::GroupHeader { .. } => None,
+        })
+    }
+
+    /// Pairs the channels displayed in their own rows, which are not scrolled out of view, with their
+    /// layouts.
+    pub fn visible_channel_layouts<'b>(
+        &'b self,
+        pane_layout: FloatingPaneLayout<'b>,
+        direction: ChannelDirection,
+    ) -> impl Iterator<Item = (ChannelLayout<'b>, ChannelRef<'b>)> + 'b {
+        let rows = self.channel_rows(direction);
+        let viewport = pane_layout.content().bounds();
+        let channels_layout = pane_layout.content().scrolled_content().channels_with_direction(direction);
+
+        self.node_configuration
+            .channels(direction)
+            .enumerate()
+            .filter(move |(global_channel_index, _)| rows.is_channel_visible(*global_channel_index))
+            .map(move |(global_channel_index, channel)| {
+                (channels_layout.channel(rows.row_of_channel(global_channel_index)), channel)
+            })
+            .filter(move |(channel_layout, _)| {
+                let center_y = channel_layout.bounds().center_y();
+
+                center_y >= viewport.y && center_y <= viewport.y + viewport.height
+            })
+    }
+}
+
+#[derive(Default)]
+pub struct FloatingPaneBehaviourState {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Highlight {
+    Channel(ChannelIdentifier),
+    Connection(Connection),
+    /// The shared cable of the connections from the first node to the second one.
+    Bundle(NodeIndex, NodeIndex),
+}
+
+impl Highlight {
+    pub fn into_graph_validation_error_affected_element(self) -> Option<GraphValidationErrorAffectedElement> {
+        use Highlight::*;
+        match self {
+            Channel(channel) => Some(GraphValidationErrorAffectedElement::Channel(channel)),
+            Connection(connection) => Some(GraphValidationErrorAffectedElement::Connection(connection)),
+            Bundle(..) => None,
+        }
+    }
+
+    /// Whether the connection is drawn highlighted.
+    pub fn contains_connection(&self, connection: &Connection) -> bool {
+        match self {
+            Highlight::Channel(channel) => connection.contains_channel(*channel),
+            Highlight::Connection(highlighted_connection) => highlighted_connection == connection,
+            Highlight::Bundle(from, to) => {
+                connection.from().node_index == *from && connection.to().node_index == *to
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContextMenuTarget {
+    Node(NodeIndex),
+    Channel(ChannelIdentifier),
+    Connection(Connection),
+    /// The connections bundled between the first node and the second one.
+    Bundle(NodeIndex, NodeIndex),
+    /// The connection a node has been dropped onto, with multiple pairs of its channels to splice
+    /// it in through.
+    Splice {
+        connection: Connection,
+        candidates: Vec<[Connection; 2]>,
+    },
+}
+
+/// The menu opened by right-clicking the title bar of a node, a channel, a connection or a bundle
+/// of connections, or by dropping a node onto a connection.
+pub struct ContextMenu {
+    pub target: ContextMenuTarget,
+    pub position: Vec2<f32>,
+    button_states: Vec<button::State>,
+    freeze_button_state: button::State,
+    opacity_state: drag_value::State,
+}
+
+impl ContextMenu {
+    pub fn new(target: ContextMenuTarget, position: Vec2<f32>) -> Self {
+        Self {
+            target,
+            position,
+            button_states: Vec::new(),
+            freeze_button_state: Default::default(),
+            opacity_state: Default::default(),
+        }
+    }
+}
+
+/// A button of a context menu, which is disabled unless it produces a message.
+fn context_menu_button<'a, M: Clone + 'a, R: WidgetRenderer + 'a>(
+    state: &'a mut button::State,
+    label: impl Into<String>,
+    on_press: Option<M>,
+) -> Button<'a, M, R> {
+    let button =
+        Button::new(state, Text::new(label).size(style::consts::TEXT_SIZE_REGULAR)).width(Length::Fill);
+
+    match on_press {
+        Some(message) => button.on_press(message),
+        None => button,
+    }
+}
+
+/// The connections proposed between two nodes, while dragging from the title bar of one of them.
+#[derive(Debug, Clone)]
+pub struct BulkConnection {
+    /// The node whose outputs are connected.
+    pub from: NodeIndex,
+    /// The node whose title bar is hovered, if it is not the one being dragged from.
+    pub to: Option<NodeIndex>,
+    pub proposed: Vec<Connection>,
+}
+
+#[derive(Default)]
+pub struct FloatingPanesBehaviourState {
+    pub selected_channel: Option<ChannelIdentifier>,
+    pub highlight: Option<Highlight>,
+    /// The reason why the hovered channel cannot be connected to the selected channel.
+    pub refusal: Option<ConnectionRefusal>,
+    pub context_menu: Option<ContextMenu>,
+    /// The node, the direction and the global index of the channel whose row is being dragged to
+    /// another row, while the channels of the node are being arranged.
+    pub arranged_channel: Option<(NodeIndex, ChannelDirection, usize)>,
+    /// The connections proposed while dragging from the title bar of a node with Alt held.
+    pub bulk_connection: Option<BulkConnection>,
+    /// The modifiers currently held, used to suppress the creation and splicing of nodes on drop and
+    /// to clone connections instead of picking them up.
+    pub modifiers: keyboard::ModifiersState,
+    pub hit_test_index: HitTestIndex,
+    /// Only locked while drawing.
+    pub crossings: Mutex<CrossingCache>,
+}
+
+impl FloatingPanesBehaviourState {
+    /// Whether picking up a connection starts a new pending connection from its output, instead of
+    /// disconnecting it, so that the output can be fanned out to several inputs. Alt or Ctrl.
+    pub fn clones_connections(&self) -> bool {
+        self.modifiers.alt || self.modifiers.control
+    }
+}
+
+/// Something the cursor may hover, as stored in the [`HitTestGrid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum HitTestEntry {
+    /// The pane, including the connection points of its channels.
+    Pane(NodeIndex),
+    /// The index of a group of connections within [`HitTestIndex::groups`].
+    Group(usize),
+}
 
-                                        messages.push((panes.behaviour.on_channel_disconnect)(channel));
-                                    }
-                                } else {
-                                    panes.behaviour_state.selected_channel = Some(channel);
-                                }
-                            }
-                        }
-                    }
+/// A pane near the cursor, as tested by [`HitTestIndex::hovered_channels`], in the coordinates of
+/// the viewport.
+struct HoverPane {
+    /// The position of the pane in the z-order, the bottommost pane first.
+    z_index: usize,
+    /// The bounds of the pane, grown by the reach of its channels.
+    bounds: Rectangle,
+    collapsed: bool,
+    /// The selectable channels along with their bounds.
+    channels: Vec<(ChannelIdentifier, Rectangle)>,
+}
 
-                    // Properly update the highlight
-                    Self::on_event(
-                        panes,
-                        Event::Mouse(MouseEvent::CursorMoved {
-                            x: panes.state.cursor_position.x,
-                            y: panes.state.cursor_position.y,
-                        }),
-                        layout,
-                        cursor_position,
-                        messages,
-                        renderer,
-                        clipboard,
-                    );
-                    return Status::Captured;
+/// A uniform grid over the coordinates of the panes, which lists the entries whose bounds overlap
+/// each cell.
+#[derive(Debug, Default)]
+struct HitTestGrid {
+    cells: HashMap<(i32, i32), Vec<HitTestEntry>>,
+    /// Entries spanning too many cells to be listed in each of them, returned by every query.
+    large: Vec<HitTestEntry>,
+}
+
+impl HitTestGrid {
+    const CELL_SIZE: f32 = 128.0;
+    const MAX_ENTRY_CELLS: i64 = 1024;
+
+    fn cell(point: Vec2<f32>) -> Vec2<i32> {
+        point.map(|coordinate| (coordinate / Self::CELL_SIZE).floor() as i32)
+    }
+
+    /// The cells overlapped by the bounds, or `None`, if there are too many of them.
+    fn cells(bounds: Rectangle) -> Option<impl Iterator<Item = (i32, i32)>> {
+        let min = Self::cell(bounds.min());
+        let max = Self::cell(bounds.max());
+        let count = (max.x as i64 - min.x as i64 + 1) * (max.y as i64 - min.y as i64 + 1);
+
+        if count > Self::MAX_ENTRY_CELLS {
+            return None;
+        }
+
+        Some((min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| (x, y))))
+    }
+
+    fn insert(&mut self, entry: HitTestEntry, bounds: Rectangle) {
+        match Self::cells(bounds) {
+            Some(cells) => {
+                for cell in cells {
+                    self.cells.entry(cell).or_default().push(entry);
                 }
+            }
+            None => self.large.push(entry),
+        }
+    }
 
-                panes.behaviour_state.selected_channel = None;
+    /// Removes the entry, which must have been inserted with the same bounds.
+    fn remove(&mut self, entry: HitTestEntry, bounds: Rectangle) {
+        match Self::cells(bounds) {
+            Some(cells) => {
+                for cell in cells {
+                    if let Some(entries) = self.cells.get_mut(&cell) {
+                        entries.retain(|existing| *existing != entry);
+                    }
+                }
             }
-            _ => (),
+            None => self.large.retain(|existing| *existing != entry),
         }
+    }
 
-        Status::Ignored
+    /// The entries whose bounds may contain the point.
+    fn query(&self, point: Vec2<f32>) -> impl Iterator<Item = HitTestEntry> + '_ {
+        let cell = Self::cell(point);
+
+        self.cells.get(&(cell.x, cell.y)).into_iter().flatten().chain(&self.large).copied()
     }
 
-    fn overlay<'b>(
-        panes: &'b mut FloatingPanes<'a, M, R, Self>,
-        layout: Layout<'_>,
-    ) -> Option<overlay::Element<'b, M, R>> {
-        let mut errors = panes
-            .behaviour_state
-            .highlight
-            .as_ref()
-            .map(|highlight| {
-                panes
-                    .behaviour
-                    .graph_validation_errors
-                    .get_related_errors(highlight.clone().into_graph_validation_error_affected_element())
-            })
-            .unwrap_or(&[]);
+    /// The entries whose bounds may overlap the bounds, each listed once.
+    fn query_bounds(&self, bounds: Rectangle) -> Vec<HitTestEntry> {
+        let mut entries = match Self::cells(bounds) {
+            Some(cells) => cells.filter_map(|cell| self.cells.get(&cell)).flatten().copied().collect(),
+            // Every entry overlaps some of the cells.
+            None => self.cells.values().flatten().copied().collect::<Vec<_>>(),
+        };
 
-        if errors.is_empty() {
-            errors = panes
-                .children
-                .iter()
-                .find_map(|(pane_index, pane)| {
-                    if pane.state.title_bar_status > InteractionStatus::Idle {
-                        let errors = panes.behaviour.graph_validation_errors.get_related_errors(*pane_index);
+        let mut listed = HashSet::new();
 
-                        Some(errors)
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or(&[]);
-        }
+        entries.extend(&self.large);
+        entries.retain(|entry| listed.insert(*entry));
+        entries
+    }
+}
 
-        if !errors.is_empty() {
-            let mut column = Column::<M, R>::new();
+/// The bounds of the panes and of the groups of connections, in the coordinates of the panes, so
+/// that hovering and splicing only test the channels and connections near the cursor or the dropped
+/// pane exactly. Brought up to date before it is queried, see
+/// [`FloatingPanes::update_hit_test_index`].
+#[derive(Debug, Default)]
+pub struct HitTestIndex {
+    /// The connections the groups were created from.
+    connections: Vec<Connection>,
+    /// The bounds of the panes, grown by the reach of their channels, in the order of the panes.
+    panes: IndexMap<NodeIndex, Rectangle>,
+    /// The indices of the connections of each group, along with the bounds of the group.
+    groups: Vec<(Vec<usize>, Rectangle)>,
+    grid: HitTestGrid,
+}
 
-            for error in errors {
-                let display = error.display();
-                let mut error_element = Column::<M, R>::new()
-                    .max_width(512)
-                    .push(Text::new(display.title.to_string()).size(style::consts::TEXT_SIZE_TITLE))
-                    .push(Text::new(display.description.to_string()).size(style::consts::TEXT_SIZE_REGULAR));
+impl HitTestIndex {
+    /// Covers the distance connections are highlighted from, see
+    /// [`MAX_CONNECTION_HIGHLIGHT_DISTANCE`].
+    const CONNECTION_MARGIN: f32 = 8.0;
 
-                if let Some(suggestion) = display.suggestion.as_ref() {
-                    error_element = error_element.push(
-                        Text::new(format!("Suggestion: {}", suggestion))
-                            .size(style::consts::TEXT_SIZE_REGULAR),
-                    );
-                }
+    /// Reindexes the panes whose bounds have changed along with the groups of their connections,
+    /// or the whole index, if the panes or the connections themselves have changed. The bounds are
+    /// in the coordinates of the panes and grown by the reach of the channels and of the curves.
+    fn update(
+        &mut self,
+        connections: &[Connection],
+        pane_bounds: IndexMap<NodeIndex, Rectangle>,
+        connection_groups: impl FnOnce() -> Vec<Vec<usize>>,
+        group_bounds: impl Fn(&[usize]) -> Rectangle,
+    ) {
+        if self.connections != connections || !self.panes.keys().eq(pane_bounds.keys()) {
+            *self = HitTestIndex { connections: connections.to_vec(), ..Default::default() };
+
+            for (node_index, bounds) in &pane_bounds {
+                self.grid.insert(HitTestEntry::Pane(*node_index), *bounds);
+            }
 
-                let mut container = Container::new(Margin::new(error_element, style::consts::SPACING));
+            self.groups = connection_groups()
+                .into_iter()
+                .map(|group| {
+                    let bounds = group_bounds(&group);
 
-                if let Some(style) = panes.behaviour.tooltip_style.as_ref() {
-                    container = container.style(style.container_style());
-                }
+                    (group, bounds)
+                })
+                .collect();
 
-                column = column.push(container);
+            for (group_index, (_, bounds)) in self.groups.iter().enumerate() {
+                self.grid.insert(HitTestEntry::Group(group_index), *bounds);
             }
+        } else {
+            let moved = pane_bounds
+                .iter()
+                .filter(|(node_index, bounds)| self.panes[*node_index] != **bounds)
+                .map(|(node_index, _)| *node_index)
+                .collect::<Vec<_>>();
 
-            let position: Point = panes.state.cursor_position.into_array().into();
-            let overlay =
-                WidgetOverlay::<M, R, _>::new(column, WidgetOverlayAlignment { top: true, left: false });
+            for node_index in &moved {
+                self.grid.remove(HitTestEntry::Pane(*node_index), self.panes[node_index]);
+                self.grid.insert(HitTestEntry::Pane(*node_index), pane_bounds[node_index]);
+            }
 
-            return Some(overlay::Element::new(position, Box::new(overlay)));
+            if !moved.is_empty() {
+                for (group_index, (group, bounds)) in self.groups.iter_mut().enumerate() {
+                    let connection = &connections[group[0]];
+
+                    if moved.contains(&connection.from().node_index)
+                        || moved.contains(&connection.to().node_index)
+                    {
+                        let new_bounds = group_bounds(group);
+
+                        self.grid.remove(HitTestEntry::Group(group_index), *bounds);
+                        self.grid.insert(HitTestEntry::Group(group_index), new_bounds);
+                        *bounds = new_bounds;
+                    }
+                }
+            }
         }
 
-        panes
-            .children
-            .iter_mut()
-            .zip(layout.children())
-            .filter_map(|((_, pane), layout)| pane.element_tree.overlay(layout))
-            .next()
+        self.panes = pane_bounds;
     }
-}
 
-pub struct FloatingPaneBehaviourData {
-    pub node_configuration: NodeConfiguration,
+    fn query(&self, point: Vec2<f32>) -> impl Iterator<Item = HitTestEntry> + '_ {
+        self.grid.query(point)
+    }
+
+    /// The indices of the connections whose curves may pass through the bounds, in ascending order.
+    fn connections_within(&self, bounds: Rectangle) -> Vec<usize> {
+        let mut connections = self
+            .grid
+            .query_bounds(bounds)
+            .into_iter()
+            .filter_map(|entry| match entry {
+                HitTestEntry::Group(group_index) => Some(self.group(group_index)),
+                HitTestEntry::Pane(_) => None,
+            })
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+
+        connections.sort_unstable();
+        connections
+    }
+
+    fn group(&self, group_index: usize) -> &[usize] {
+        &self.groups[group_index].0
+    }
+
+    /// The first hovered channel of each candidate pane containing the cursor, from the bottommost
+    /// pane up, so that the topmost hovered channel comes last. `pane` describes the pane of the
+    /// node, `None` if it has none.
+    fn hovered_channels(
+        candidates: &[HitTestEntry],
+        cursor_position: Vec2<f32>,
+        pane: impl Fn(NodeIndex) -> Option<HoverPane>,
+    ) -> Vec<ChannelIdentifier> {
+        let mut hovered_panes = candidates
+            .iter()
+            .filter_map(|entry| match entry {
+                HitTestEntry::Pane(node_index) => pane(*node_index),
+                HitTestEntry::Group(_) => None,
+            })
+            .filter(|pane| pane.bounds.contains(cursor_position.into_array().into()))
+            .collect::<Vec<_>>();
+
+        hovered_panes.sort_unstable_by_key(|pane| pane.z_index);
+        hovered_panes
+            .into_iter()
+            .filter_map(|pane| {
+                let collapsed = pane.collapsed;
+
+                pane.channels
+                    .into_iter()
+                    .find(|(channel, bounds)| {
+                        is_channel_selected(*bounds, channel.channel_direction, collapsed, cursor_position)
+                    })
+                    .map(|(channel, _)| channel)
+            })
+            .collect()
+    }
+
+    /// What hovering the candidate connection curve closest to the cursor highlights, if any curve
+    /// is within [`MAX_CONNECTION_HIGHLIGHT_DISTANCE`]. `curves` lists the curves of the group of
+    /// connections along with their highlights. Ties are resolved in the order of the groups and of
+    /// their curves, whichever the candidates are.
+    fn hovered_connection(
+        candidates: &[HitTestEntry],
+        cursor_position: Vec2<f32>,
+        curves: impl Fn(usize) -> Vec<(Highlight, ConnectionCurve)>,
+    ) -> Option<Highlight> {
+        let mut groups = candidates
+            .iter()
+            .filter_map(|entry| match entry {
+                HitTestEntry::Group(group_index) => Some(*group_index),
+                HitTestEntry::Pane(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        groups.sort_unstable();
+        groups
+            .into_iter()
+            .flat_map(curves)
+            .filter_map(|(highlight, curve)| {
+                curve
+                    .get_distance_squared(cursor_position, MAX_CONNECTION_HIGHLIGHT_DISTANCE)
+                    .map(|distance_squared| (highlight, distance_squared))
+            })
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(highlight, _)| highlight)
+    }
 }
 
-#[derive(Default)]
-pub struct FloatingPaneBehaviourState {}
+/// Which parts of the curves of connections pass over panes other than the ones they connect, to be
+/// dimmed. The parts are the line segments of the flattened curves, tested by their midpoints, and
+/// kept until a pane they may pass over, or one they connect, moves.
+#[derive(Debug, Default)]
+pub struct CrossingCache {
+    /// The position of the panes within the viewport, as of the last draw.
+    origin: Vec2<f32>,
+    /// The bounds of the panes, in the coordinates of the panes.
+    panes: IndexMap<NodeIndex, Rectangle>,
+    /// By the connection and the index of its curve.
+    curves: HashMap<(Connection, usize), CurveCrossings>,
+}
 
-#[derive(Debug, Clone)]
-pub enum Highlight {
-    Channel(ChannelIdentifier),
-    Connection(Connection),
+#[derive(Debug)]
+struct CurveCrossings {
+    /// The bounds of the curve, in the coordinates of the panes.
+    bounds: Rectangle,
+    /// Whether each line segment of the flattened curve passes over an unrelated pane.
+    crossing: Vec<bool>,
 }
 
-impl Highlight {
-    pub fn into_graph_validation_error_affected_element(self) -> GraphValidationErrorAffectedElement {
-        use Highlight::*;
-        match self {
-            Channel(channel) => GraphValidationErrorAffectedElement::Channel(channel),
-            Connection(connection) => GraphValidationErrorAffectedElement::Connection(connection),
+impl CrossingCache {
+    /// The distance from the ends of curves within which they are never dimmed.
+    const ENDPOINT_CLEARANCE: f32 = 24.0;
+
+    /// Forgets the curves which may be affected by the panes that have moved, appeared or
+    /// disappeared since the last draw.
+    fn update(&mut self, origin: Point, panes: impl Iterator<Item = (NodeIndex, Rectangle)>) {
+        let origin = Vec2::new(origin.x, origin.y);
+        let panes = panes
+            .map(|(node_index, bounds)| {
+                (node_index, Rectangle { x: bounds.x - origin.x, y: bounds.y - origin.y, ..bounds })
+            })
+            .collect::<IndexMap<_, _>>();
+        let mut moved_panes = HashSet::new();
+        let mut moved_bounds = Vec::new();
+
+        for (node_index, bounds) in &panes {
+            if self.panes.get(node_index) != Some(bounds) {
+                moved_panes.insert(*node_index);
+                moved_bounds.push(*bounds);
+                moved_bounds.extend(self.panes.get(node_index).copied());
+            }
+        }
+
+        for (node_index, bounds) in &self.panes {
+            if !panes.contains_key(node_index) {
+                moved_panes.insert(*node_index);
+                moved_bounds.push(*bounds);
+            }
+        }
+
+        if !moved_panes.is_empty() {
+            self.curves.retain(|(connection, _), curve| {
+                !moved_panes.contains(&connection.from().node_index)
+                    && !moved_panes.contains(&connection.to().node_index)
+                    && !moved_bounds.iter().any(|bounds| bounds.overlaps(&curve.bounds))
+            });
         }
+
+        self.origin = origin;
+        self.panes = panes;
     }
-}
 
-#[derive(Default)]
-pub struct FloatingPanesBehaviourState {
-    pub selected_channel: Option<ChannelIdentifier>,
-    pub highlight: Option<Highlight>,
+    /// Whether each line segment between the points of the flattened curve, in the coordinates of
+    /// the viewport, passes over a pane other than the ones of the connection.
+    fn classify(
+        &mut self,
+        connection: &Connection,
+        curve_index: usize,
+        points: &[lyon_geom::math::Point],
+    ) -> &[bool] {
+        let origin = self.origin;
+        let panes = &self.panes;
+        let curve = self
+            .curves
+            .entry((connection.clone(), curve_index))
+            .or_insert_with(|| CurveCrossings { bounds: Rectangle::default(), crossing: Vec::new() });
+
+        if curve.crossing.len() != points.len().saturating_sub(1) {
+            let points = points.iter().map(|point| Vec2::new(point.x, point.y) - origin).collect::<Vec<_>>();
+            let min = points
+                .iter()
+                .fold(Vec2::broadcast(f32::INFINITY), |min, point| min.map2(*point, util::partial_min));
+            let max = points
+                .iter()
+                .fold(Vec2::broadcast(f32::NEG_INFINITY), |max, point| max.map2(*point, util::partial_max));
+            let bounds = Rectangle::from_min_max(min, max);
+            let obstacles = panes
+                .iter()
+                .filter(|(node_index, pane_bounds)| {
+                    **node_index != connection.from().node_index
+                        && **node_index != connection.to().node_index
+                        && pane_bounds.overlaps(&bounds)
+                })
+                .map(|(_, pane_bounds)| *pane_bounds)
+                .collect::<Vec<_>>();
+            let (first, last) = (points[0], points[points.len() - 1]);
+
+            curve.bounds = bounds;
+            curve.crossing = points
+                .array_windows::<2>()
+                .map(|[from, to]| {
+                    let midpoint = (*from + *to) / 2.0;
+
+                    midpoint.distance(first) > Self::ENDPOINT_CLEARANCE
+                        && midpoint.distance(last) > Self::ENDPOINT_CLEARANCE
+                        && obstacles.iter().any(|obstacle| obstacle.contains(midpoint.into_array().into()))
+                })
+                .collect();
+        }
+
+        &curve.crossing
+    }
 }
 
 /// Good practice: Rendering is made to be generic over the backend using this trait, which
@@ -651,10 +2980,13 @@ pub struct FloatingPanesBehaviourState {
 pub trait WidgetRenderer:
     margin::WidgetRenderer
     + floating_panes::WidgetRenderer
+    + drag_value::WidgetRenderer
     + iced_native::Renderer
     + iced_native::text::Renderer
     + iced_native::column::Renderer
+    + iced_native::widget::button::Renderer
     + iced_native::widget::container::Renderer
+    + iced_native::widget::rule::Renderer
     + iced_native::widget::text_input::Renderer
     + Sized
 {
@@ -668,10 +3000,21 @@ pub trait WidgetRenderer:
         cursor_position: Point,
         viewport: &Rectangle,
     ) -> ContentDrawResult<Self>;
+
+    /// Clips the content of the node element to its bounds and draws a scrollbar, if the content
+    /// overflows the bounds.
+    fn draw_scrolled_content(
+        &mut self,
+        defaults: &Self::Defaults,
+        bounds: Rectangle,
+        content_bounds: Rectangle,
+        content: Self::Output,
+    ) -> Self::Output;
 }
 
 impl<B> WidgetRenderer for iced_graphics::Renderer<B>
-where B: Backend + iced_graphics::backend::Text
+where
+    B: Backend + iced_graphics::backend::Text,
 {
     type StyleTooltip = Box<dyn TooltipStyleSheet>;
 
@@ -686,116 +3029,101 @@ where B: Backend + iced_graphics::backend::Text
         let mut mouse_interaction = mouse::Interaction::default();
         let mut primitives = Vec::new();
 
-        primitives.extend(panes.children.iter().zip(layout.panes()).map(
-            |((_child_index, child), layout)| {
-                let (primitive, new_mouse_interaction) =
-                    child.element_tree.draw(self, defaults, layout.into(), cursor_position, viewport);
+        // Pinned panes and the panes pressed most recently are drawn last, above the others.
+        primitives.extend(panes.z_order().into_iter().map(|layout_index| {
+            let (_, child) = panes.children.get_index(layout_index).unwrap();
+            let pane_layout = layout.pane_with_index(layout_index);
+            let (mut primitive, new_mouse_interaction) =
+                child.element_tree.draw(self, defaults, pane_layout.into(), cursor_position, viewport);
 
-                if new_mouse_interaction > mouse_interaction {
-                    mouse_interaction = new_mouse_interaction;
-                }
+            if new_mouse_interaction > mouse_interaction {
+                mouse_interaction = new_mouse_interaction;
+            }
 
-                primitive
-            },
-        ));
+            if child.state.opacity < 1.0 {
+                util::fade_primitive(&mut primitive, child.state.opacity);
+            }
+
+            primitive
+        }));
 
         let mut frame = Frame::new(layout.bounds().size());
+        let status_colors = &panes.behaviour.status_colors;
 
-        // Highlight pane-related errors
+        // Highlight pane-related errors, and warnings in a less alarming color
         for ((node_index, _pane), pane_layout) in panes.children.iter().zip(layout.panes()) {
-            if panes.behaviour.graph_validation_errors.is_invalid(*node_index) {
-                let layout_bounds = pane_layout.bounds();
-                frame.stroke(
-                    &Path::rectangle(layout_bounds.min().into_array().into(), layout_bounds.size()),
-                    Stroke {
-                        color: Color::from_rgb(1.0, 0.0, 0.0),
-                        width: 2.0,
-                        line_cap: LineCap::Square,
-                        line_join: LineJoin::Miter,
-                    },
-                );
-            }
-        }
+            let color = if panes.behaviour.graph_validation_errors.is_invalid(*node_index) {
+                status_colors.error
+            } else if panes.behaviour.graph_validation_errors.has_warnings(*node_index) {
+                status_colors.pending
+            } else {
+                continue;
+            };
+            let layout_bounds = pane_layout.bounds();
 
-        // Draw existing connections
-        for connection in &panes.behaviour.connections {
-            let layout_from = layout.pane_with_index(
-                NodeElement::<M, Self>::get_layout_index_from_channel(panes, connection.from()).unwrap(),
-            );
-            let layout_to = layout.pane_with_index(
-                NodeElement::<M, Self>::get_layout_index_from_channel(panes, connection.to()).unwrap(),
+            frame.stroke(
+                &Path::rectangle(layout_bounds.min().into_array().into(), layout_bounds.size()),
+                Stroke { color, width: 2.0, line_cap: LineCap::Square, line_join: LineJoin::Miter },
             );
+            draw_error_glyph(&mut frame, layout_bounds, color, status_colors);
+        }
 
-            let layout_outputs = layout_from.content().channels_with_direction(ChannelDirection::Out);
-            let layout_inputs = layout_to.content().channels_with_direction(ChannelDirection::In);
-            let layout_output = layout_outputs.channel(connection.from().channel_index);
-            let layout_input = layout_inputs.channel(connection.to().channel_index);
-
-            // primitives.push(
-            //     draw_bounds(layout_output, Color::from_rgb(1.0, 0.0, 0.0))
-            // );
-            // primitives.push(
-            //     draw_bounds(layout_input, Color::from_rgb(0.0, 0.0, 1.0))
-            // );
-
-            let from = NodeElement::<M, Self>::get_connection_point(layout_output, ChannelDirection::Out);
-            let to = NodeElement::<M, Self>::get_connection_point(layout_input, ChannelDirection::In);
-
-            let highlighted = if let Some(highlight) = panes.behaviour_state.highlight.as_ref() {
-                match highlight {
-                    Highlight::Connection(highlighted_connection) => connection == highlighted_connection,
-                    Highlight::Channel(highlighted_channel) => {
-                        connection.contains_channel(highlighted_channel.clone())
-                    }
-                }
-            } else {
-                false
-            };
-            let mut stroke = if highlighted {
-                Stroke {
-                    color: Color::from_rgba(0.5, 1.0, 0.0, 1.0),
-                    width: 3.0,
-                    line_cap: LineCap::Round,
-                    line_join: LineJoin::Round,
-                }
-            } else {
+        // Outline the selected panes, outside of the error highlights
+        for ((_, pane), pane_layout) in panes.children.iter().zip(layout.panes()) {
+            if !pane.behaviour_data.selected {
+                continue;
+            }
+
+            let layout_bounds = pane_layout.bounds().grow_symmetrical(4.0, 4.0);
+
+            frame.stroke(
+                &Path::rectangle(layout_bounds.min().into_array().into(), layout_bounds.size()),
                 Stroke {
-                    color: Color::from_rgba(1.0, 1.0, 1.0, 1.0),
+                    color: status_colors.highlight,
                     width: 2.0,
-                    line_cap: LineCap::Round,
-                    line_join: LineJoin::Round,
-                }
-            };
+                    line_cap: LineCap::Square,
+                    line_join: LineJoin::Miter,
+                },
+            );
+        }
 
-            // Highlight connection-related errors
-            if panes.behaviour.graph_validation_errors.is_invalid(connection.clone()) {
-                stroke.color = Color::from_rgba(1.0, 0.0, 0.0, 1.0);
-            }
+        // Draw existing connections, with parallel connections between the same two nodes bundled
+        // into a single cable
+        let mut badges = Vec::new();
+        let mut crossings = if panes.behaviour.dim_crossing_connections {
+            let mut crossings = panes.behaviour_state.crossings.lock().unwrap();
 
-            // primitives.push(draw_point(from.into_array().into(), Color::from_rgb(1.0, 0.0, 0.0)));
-            // primitives.push(draw_point(to.into_array().into(), Color::from_rgb(0.0, 0.0, 1.0)));
-            let connection_pass_by =
-                ConnectionPassBy::derive_connection_pass_by(&get_is_aliased!(panes), connection);
+            crossings.update(
+                layout.position(),
+                panes.children.keys().copied().zip(layout.panes().map(|pane_layout| pane_layout.bounds())),
+            );
+            Some(crossings)
+        } else {
+            None
+        };
 
-            ConnectionCurve { from, to }.draw(&mut frame, stroke, connection_pass_by.get_stroke_type());
+        for connection_layout in panes.connection_layouts(layout) {
+            match connection_layout {
+                ConnectionLayout::Single(connection, curve) => {
+                    badges.extend(draw_throughput(panes, connection, &curve));
+                    badges.extend(draw_promotion(panes, connection, &curve));
+                    draw_connection(panes, &mut frame, connection, &[curve], crossings.as_deref_mut());
+                }
+                ConnectionLayout::Bundle(bundle) => {
+                    let cable = bundle.cable();
 
-            // Code to visualize finding the closest point to the curve
-            // {
-            //     // TODO: When checking whether the cursor is above a curve, first construct
-            //     // a bounding convex polygon or AABB that encloses the curve + the max distance
-            //     // at which the selection should be active
-            //     let segments = util::get_connection_curve(from, to);
-            //     let projection = segments.project_point(panes.state.cursor_position);
-            //     let projection = segments.sample(projection.t);
-            //     let radius = projection.distance(panes.state.cursor_position);
-
-            //     frame.stroke(
-            //         &Path::circle(panes.state.cursor_position.into_array().into(), radius),
-            //         Stroke { color: Color::WHITE, width: 1.0, ..Default::default() },
-            //     );
-            //     primitives
-            //         .push(util::draw_point(projection.into_array().into(), Color::from_rgb(1.0, 0.0, 1.0)));
-            // }
+                    for (connection, curve) in &bundle.connections {
+                        let stubs = ConnectionBundle::stubs(&cable, curve);
+
+                        // Labelled along the stub leading into the input, which is not shared.
+                        badges.extend(draw_throughput(panes, connection, &stubs[1]));
+                        badges.extend(draw_promotion(panes, connection, &stubs[1]));
+                        draw_connection(panes, &mut frame, connection, &stubs, crossings.as_deref_mut());
+                    }
+
+                    badges.push(draw_cable(panes, &mut frame, &bundle, &cable));
+                }
+            }
         }
 
         // Draw pending connection
@@ -804,14 +3132,9 @@ where B: Backend + iced_graphics::backend::Text
                 .panes()
                 .nth(NodeElement::<M, Self>::get_layout_index_from_channel(panes, *selected_channel).unwrap())
                 .unwrap();
-            let layout_channels =
-                pane_layout.content().channels_with_direction(selected_channel.channel_direction);
-            let layout_channel = layout_channels.channel(selected_channel.channel_index);
-
-            let connected_position = NodeElement::<M, Self>::get_connection_point(
-                layout_channel,
-                selected_channel.channel_direction,
-            );
+            let connected_position = panes
+                .behaviour_data(selected_channel.node_index)
+                .connection_point(pane_layout, *selected_channel);
             let (target_position, connection_pass_by) = if let Some(Highlight::Channel(highlighted_channel)) =
                 panes.behaviour_state.highlight.as_ref()
             {
@@ -822,23 +3145,24 @@ where B: Backend + iced_graphics::backend::Text
                             .unwrap(),
                     )
                     .unwrap();
-                let layout_channels =
-                    child_layout.content().channels_with_direction(highlighted_channel.channel_direction);
-                let layout_channel = layout_channels.channel(highlighted_channel.channel_index);
-                let target_position = NodeElement::<M, Self>::get_connection_point(
-                    layout_channel,
-                    highlighted_channel.channel_direction,
-                );
+                let target_position = panes
+                    .behaviour_data(highlighted_channel.node_index)
+                    .connection_point(child_layout, *highlighted_channel);
 
                 let connection =
                     Connection::try_from_identifiers([*selected_channel, *highlighted_channel]).unwrap();
-                let connection_pass_by =
-                    ConnectionPassBy::derive_connection_pass_by(&get_is_aliased!(panes), &connection);
+                // Preview the connection as it is going to be drawn once created, including the
+                // downgrade to an immutable connection, if the output becomes aliased.
+                let connection_pass_by = ConnectionPassBy::derive_connection_pass_by(
+                    &get_prospective_is_aliased!(panes, &connection),
+                    &connection,
+                );
 
                 (target_position, connection_pass_by)
             } else {
+                // The pending connection is going to be an additional connection of its output.
                 let connection_pass_by = ConnectionPassBy::derive_pending_connection_pass_by(
-                    &get_is_aliased!(panes),
+                    &get_pending_is_aliased!(panes),
                     *selected_channel,
                 );
 
@@ -850,37 +3174,140 @@ where B: Backend + iced_graphics::backend::Text
                 ChannelDirection::Out => (connected_position, target_position),
             };
 
-            let stroke = Stroke {
-                color: Color::from_rgba(1.0, 0.6, 0.0, 1.0),
-                width: 3.0,
-                line_cap: LineCap::Round,
-                line_join: LineJoin::Round,
+            let stroke = if let Some(Highlight::Channel(_)) = panes.behaviour_state.highlight.as_ref() {
+                // The hovered channel stays highlighted once the connection is created.
+                connection_stroke(true, status_colors)
+            } else {
+                Stroke {
+                    color: status_colors.pending,
+                    width: 3.0,
+                    line_cap: LineCap::Round,
+                    line_join: LineJoin::Round,
+                }
             };
 
             ConnectionCurve { from, to }.draw(&mut frame, stroke, connection_pass_by.get_stroke_type());
         }
 
+        // Draw the connections proposed between two nodes, or a pending connection from the title bar
+        // of the node, if none are proposed, refused if the hovered node cannot be connected to
+        if let Some(bulk_connection) = panes.behaviour_state.bulk_connection.as_ref() {
+            for connection in &bulk_connection.proposed {
+                let connection_point = |channel: ChannelIdentifier| {
+                    let pane_layout = layout
+                        .panes()
+                        .nth(NodeElement::<M, Self>::get_layout_index_from_channel(panes, channel).unwrap())
+                        .unwrap();
+
+                    panes.behaviour_data(channel.node_index).connection_point(pane_layout, channel)
+                };
+                let connection_pass_by = ConnectionPassBy::derive_connection_pass_by(
+                    &get_prospective_is_aliased!(panes, connection),
+                    connection,
+                );
+
+                ConnectionCurve {
+                    from: connection_point(connection.from()),
+                    to: connection_point(connection.to()),
+                }
+                .draw(
+                    &mut frame,
+                    connection_stroke(true, status_colors),
+                    connection_pass_by.get_stroke_type(),
+                );
+            }
+
+            let from_layout = panes
+                .get_layout_index_from_pane_index(&bulk_connection.from)
+                .and_then(|layout_index| layout.panes().nth(layout_index));
+
+            if let (true, Some(from_layout)) = (bulk_connection.proposed.is_empty(), from_layout) {
+                let bounds = from_layout.bounds();
+                let title_bar_height = bounds.height - from_layout.content().bounds().height;
+                let from = Vec2::new(bounds.x + bounds.width, bounds.y + title_bar_height / 2.0);
+                let color =
+                    if bulk_connection.to.is_some() { status_colors.error } else { status_colors.pending };
+                let stroke =
+                    Stroke { color, width: 3.0, line_cap: LineCap::Round, line_join: LineJoin::Round };
+
+                ConnectionCurve { from, to: panes.state.cursor_position }.draw(
+                    &mut frame,
+                    stroke,
+                    StrokeType::Contiguous,
+                );
+            }
+        }
+
+        // Mark where the arranged channel is going to be moved, above the hovered row if the channel
+        // moves up, below it otherwise
+        if let Some((node, direction, channel)) = panes.behaviour_state.arranged_channel {
+            let target = layout
+                .panes()
+                .zip(panes.children.iter())
+                .find(|(_, (node_index, _))| **node_index == node)
+                .and_then(|(pane_layout, (_, pane))| {
+                    let behaviour_data = &pane.behaviour_data;
+
+                    behaviour_data
+                        .channel_at(pane_layout, direction, cursor_position)
+                        .filter(|(target, _)| *target != channel)
+                        .map(|(target, target_layout)| {
+                            let rows = behaviour_data.channel_rows(direction);
+                            let below = rows.row_of_channel(target) > rows.row_of_channel(channel);
+
+                            (target_layout.bounds(), below)
+                        })
+                });
+
+            if let Some((bounds, below)) = target {
+                let spacing = style::consts::SPACING_VERTICAL as f32 / 2.0;
+                let y = if below { bounds.y + bounds.height + spacing } else { bounds.y - spacing };
+
+                frame.stroke(
+                    &Path::new(|builder| {
+                        builder.move_to(Point::new(bounds.x, y));
+                        builder.line_to(Point::new(bounds.x + bounds.width, y));
+                    }),
+                    Stroke {
+                        color: status_colors.pending,
+                        width: 2.0,
+                        line_cap: LineCap::Round,
+                        line_join: LineJoin::Round,
+                    },
+                );
+            }
+        }
+
         primitives.push(frame.into_geometry().into_primitive());
+        primitives.extend(badges);
+
+        if let Some(execution_order) = panes.behaviour.execution_order.as_ref() {
+            for (node_index, pane_layout) in panes.children.keys().zip(layout.panes()) {
+                primitives.extend(draw_execution_order(
+                    self,
+                    execution_order.get(node_index).copied(),
+                    pane_layout.bounds(),
+                    status_colors,
+                    panes.behaviour.tooltip_style.as_deref(),
+                ));
+            }
+        }
 
         // Draw connection points
         {
             for (pane_layout, node_index) in layout.panes().zip(panes.children.keys().copied()) {
                 let node = panes.children.get(&node_index).unwrap();
-                let inputs_layout = pane_layout
-                    .content()
-                    .channels_with_direction(ChannelDirection::In)
-                    .channels()
-                    .zip(node.behaviour_data.node_configuration.channels(ChannelDirection::In));
-                let outputs_layout = pane_layout
-                    .content()
-                    .channels_with_direction(ChannelDirection::Out)
-                    .channels()
-                    .zip(node.behaviour_data.node_configuration.channels(ChannelDirection::Out));
-                let channel_layouts = inputs_layout.chain(outputs_layout);
+                let channel_layouts = node
+                    .behaviour_data
+                    .visible_channel_layouts(pane_layout, ChannelDirection::In)
+                    .chain(node.behaviour_data.visible_channel_layouts(pane_layout, ChannelDirection::Out));
 
                 for (channel_layout, channel_ref) in channel_layouts {
-                    let position =
-                        NodeElement::<M, Self>::get_connection_point(channel_layout, channel_ref.direction);
+                    let position = get_connection_point(
+                        channel_layout.bounds(),
+                        channel_ref.direction,
+                        node.behaviour_data.collapsed,
+                    );
                     let channel = channel_ref.into_identifier(node_index);
                     let highlighted = if let Some(Highlight::Channel(highlighted_channel)) =
                         panes.behaviour_state.highlight.as_ref()
@@ -904,11 +3331,71 @@ where B: Backend + iced_graphics::backend::Text
             }
         }
 
+        if let Some(document_tabs) = panes.behaviour.document_tabs.as_ref() {
+            primitives.extend(draw_document_tabs(
+                document_tabs,
+                viewport,
+                cursor_position,
+                status_colors,
+                panes.behaviour.tooltip_style.as_deref(),
+            ));
+        }
+
+        if let Some(schedule_status) = panes.behaviour.schedule_status.as_ref() {
+            primitives.extend(draw_schedule_status(
+                self,
+                schedule_status,
+                viewport,
+                status_colors,
+                panes.behaviour.tooltip_style.as_deref(),
+            ));
+        }
+
         ContentDrawResult {
             override_parent_cursor: panes.behaviour_state.highlight.is_some(),
             output: (Primitive::Group { primitives }, mouse_interaction),
         }
     }
+
+    fn draw_scrolled_content(
+        &mut self,
+        defaults: &Self::Defaults,
+        bounds: Rectangle,
+        content_bounds: Rectangle,
+        (content, mouse_interaction): Self::Output,
+    ) -> Self::Output {
+        if content_bounds.height <= bounds.height {
+            return (content, mouse_interaction);
+        }
+
+        let thumb_height = util::partial_max(
+            bounds.height * bounds.height / content_bounds.height,
+            SCROLLBAR_MIN_THUMB_LENGTH,
+        );
+        let scroll_progress = (bounds.y - content_bounds.y) / (content_bounds.height - bounds.height);
+        let thumb_bounds = Rectangle {
+            x: bounds.x + bounds.width - SCROLLBAR_WIDTH,
+            y: bounds.y + (bounds.height - thumb_height) * scroll_progress,
+            width: SCROLLBAR_WIDTH,
+            height: thumb_height,
+        };
+
+        (
+            Primitive::Group {
+                primitives: vec![
+                    Primitive::Clip { bounds, offset: Vector::new(0, 0), content: Box::new(content) },
+                    Primitive::Quad {
+                        bounds: thumb_bounds,
+                        background: Background::Color(Color { a: 0.5, ..defaults.text.color }),
+                        border_radius: (SCROLLBAR_WIDTH / 2.0) as u16,
+                        border_width: 0,
+                        border_color: Color::TRANSPARENT,
+                    },
+                ],
+            },
+            mouse_interaction,
+        )
+    }
 }
 
 pub trait StyleTooltipBounds<R: WidgetRenderer> {
@@ -920,7 +3407,8 @@ pub trait TooltipStyleSheet {
 }
 
 impl<B> StyleTooltipBounds<iced_graphics::Renderer<B>> for Box<dyn TooltipStyleSheet>
-where B: Backend + iced_graphics::backend::Text
+where
+    B: Backend + iced_graphics::backend::Text,
 {
     fn container_style(&self) -> Box<(dyn iced::container::StyleSheet + 'static)> {
         self.style().container
@@ -948,38 +3436,490 @@ fn draw_connection_point<M: Clone, B>(
     B: Backend + iced_graphics::backend::Text,
 {
     let solid = channel_pass_by > ChannelPassBy::SharedReference;
-    let (radius, mut color) =
-        if highlighted { (5.0, Color::from_rgb(0.5, 1.0, 0.0)) } else { (3.5, Color::WHITE) };
+    let status_colors = &panes.behaviour.status_colors;
+    // Highlighted and invalid channels are enlarged, so that they stand out without relying on color
+    let (radius, mut color) = if highlighted {
+        (5.0, status_colors.highlight)
+    } else if error {
+        (5.0, status_colors.error)
+    } else {
+        (3.5, status_colors.idle)
+    };
+
+    let pane = panes.children.get(&node_index).unwrap();
 
-    if error {
-        color = Color::from_rgb(1.0, 0.0, 0.0);
+    if let (false, false, Some(tint)) = (highlighted, error, pane.tint) {
+        // Faintly tint the connection points, so that nodes of the same color read as clusters
+        color = util::mix_colors(color, tint, 0.35);
     }
 
     primitives.push(util::draw_point(position, color, radius));
 
     if !solid {
-        let pane = panes.children.get(&node_index).unwrap();
         let color = pane.style.as_ref().unwrap().style(style::InteractionStatus::Idle).body_background_color;
 
         primitives.push(util::draw_point(position, color, radius * (2.0 / 3.0)));
     }
 }
 
+/// Draws the connection along the curves, which are either the whole curve between its channels or
+/// the stubs of a bundled connection.
+fn draw_connection<M: Clone, B>(
+    panes: &FloatingPanes<
+        '_,
+        M,
+        iced_graphics::Renderer<B>,
+        FloatingPanesBehaviour<M, iced_graphics::Renderer<B>>,
+    >,
+    frame: &mut Frame,
+    connection: &Connection,
+    curves: &[ConnectionCurve],
+    crossings: Option<&mut CrossingCache>,
+) where
+    B: Backend + iced_graphics::backend::Text,
+{
+    let status_colors = &panes.behaviour.status_colors;
+    let highlighted = panes
+        .behaviour_state
+        .highlight
+        .as_ref()
+        .map(|highlight| highlight.contains_connection(connection))
+        .unwrap_or(false);
+    let mut stroke = connection_stroke(highlighted, status_colors);
+    // Highlight connection-related errors, by the shape of the curve as well as the color
+    let invalid = panes.behaviour.graph_validation_errors.is_invalid(connection.clone());
+
+    if invalid {
+        stroke.color = status_colors.error;
+    }
+
+    let connection_pass_by =
+        ConnectionPassBy::derive_connection_pass_by(&get_is_aliased!(panes, connection), connection);
+
+    // Highlighted and invalid connections stand out in full
+    let mut crossings = crossings.filter(|_| !highlighted && !invalid);
+
+    for (curve_index, curve) in curves.iter().enumerate() {
+        match crossings.as_deref_mut() {
+            Some(crossings) => curve.draw_dimmed(
+                frame,
+                stroke,
+                connection_pass_by.get_stroke_type(),
+                crossings,
+                (connection, curve_index),
+            ),
+            None => curve.draw(frame, stroke, connection_pass_by.get_stroke_type()),
+        }
+
+        if invalid {
+            curve.draw(
+                frame,
+                Stroke { width: 1.5, ..stroke },
+                StrokeType::ZigZag { amplitude: 3.0, wavelength: 8.0 },
+            );
+        }
+    }
+
+    // Point out which of the connections of the output the value is moved into
+    let aliased =
+        panes.behaviour.connections.iter().filter(|other| other.from() == connection.from()).count() > 1;
+
+    if aliased && panes.behaviour.is_move_recipient(connection) {
+        if let Some(curve) = curves.last() {
+            curve.draw_arrowhead(frame, stroke.color);
+        }
+    }
+
+    // Code to visualize finding the closest point to the curve
+    // {
+    //     // TODO: When checking whether the cursor is above a curve, first construct
+    //     // a bounding convex polygon or AABB that encloses the curve + the max distance
+    //     // at which the selection should be active
+    //     let segments = util::get_connection_curve(from, to);
+    //     let projection = segments.project_point(panes.state.cursor_position);
+    //     let projection = segments.sample(projection.t);
+    //     let radius = projection.distance(panes.state.cursor_position);
+
+    //     frame.stroke(
+    //         &Path::circle(panes.state.cursor_position.into_array().into(), radius),
+    //         Stroke { color: Color::WHITE, width: 1.0, ..Default::default() },
+    //     );
+    //     primitives
+    //         .push(util::draw_point(projection.into_array().into(), Color::from_rgb(1.0, 0.0, 1.0)));
+    // }
+}
+
+/// Draws the shared cable of the bundle, returning the label of the number of connections to be
+/// drawn on top of it.
+fn draw_cable<M: Clone, B>(
+    panes: &FloatingPanes<
+        '_,
+        M,
+        iced_graphics::Renderer<B>,
+        FloatingPanesBehaviour<M, iced_graphics::Renderer<B>>,
+    >,
+    frame: &mut Frame,
+    bundle: &ConnectionBundle<'_>,
+    cable: &ConnectionCurve,
+) -> Primitive
+where
+    B: Backend + iced_graphics::backend::Text,
+{
+    const BADGE_RADIUS: f32 = 8.0;
+
+    let status_colors = &panes.behaviour.status_colors;
+    let highlighted = panes
+        .behaviour_state
+        .highlight
+        .as_ref()
+        .map(|highlight| {
+            bundle.connections.iter().any(|(connection, _)| highlight.contains_connection(connection))
+        })
+        .unwrap_or(false);
+    let mut stroke = connection_stroke(highlighted, status_colors);
+
+    // The cable is thicker than individual connections, so that it reads as several of them.
+    stroke.width += 2.0;
+
+    if bundle
+        .connections
+        .iter()
+        .any(|(connection, _)| panes.behaviour.graph_validation_errors.is_invalid((*connection).clone()))
+    {
+        stroke.color = status_colors.error;
+    }
+
+    cable.draw(frame, stroke, StrokeType::Contiguous);
+
+    let center = (cable.from + cable.to) / 2.0;
+
+    frame.fill(
+        &Path::circle(center.into_array().into(), BADGE_RADIUS),
+        Fill { color: stroke.color, rule: FillRule::NonZero },
+    );
+
+    let pane = panes.children.get(&bundle.from).unwrap();
+    let color = pane.style.as_ref().unwrap().style(style::InteractionStatus::Idle).body_background_color;
+
+    Primitive::Text {
+        content: format!("×{}", bundle.connections.len()),
+        bounds: Rectangle { x: center.x, y: center.y, width: BADGE_RADIUS * 2.0, height: BADGE_RADIUS * 2.0 },
+        color,
+        size: BADGE_RADIUS * 1.5,
+        font: Font::Default,
+        horizontal_alignment: HorizontalAlignment::Center,
+        vertical_alignment: VerticalAlignment::Center,
+    }
+}
+
+/// Labels the connection with the number of values consumed through it per second, above the
+/// midpoint of the curve. Connections into disabled nodes are labelled as not consumed, dimmed.
+fn draw_throughput<M: Clone, B>(
+    panes: &FloatingPanes<
+        '_,
+        M,
+        iced_graphics::Renderer<B>,
+        FloatingPanesBehaviour<M, iced_graphics::Renderer<B>>,
+    >,
+    connection: &Connection,
+    curve: &ConnectionCurve,
+) -> Option<Primitive>
+where
+    B: Backend + iced_graphics::backend::Text,
+{
+    const TEXT_SIZE: f32 = 10.0;
+    const MAX_WIDTH: f32 = 64.0;
+
+    let rate = panes.behaviour.throughput.as_ref()?.get(connection).copied();
+    let color = panes.behaviour.status_colors.idle;
+    let (content, color) = match rate {
+        Some(rate) if rate >= 10.0 => (format!("{:.0}/s", rate), color),
+        Some(rate) => (format!("{:.1}/s", rate), color),
+        None => ("0/s".to_string(), Color { a: color.a * 0.4, ..color }),
+    };
+    let center = (curve.from + curve.to) / 2.0;
+
+    Some(Primitive::Text {
+        content,
+        bounds: Rectangle { x: center.x, y: center.y - TEXT_SIZE, width: MAX_WIDTH, height: TEXT_SIZE },
+        color,
+        size: TEXT_SIZE,
+        font: Font::Default,
+        horizontal_alignment: HorizontalAlignment::Center,
+        vertical_alignment: VerticalAlignment::Center,
+    })
+}
+
+/// Marks connections converting their values implicitly with an arrow below the midpoint of the
+/// curve.
+fn draw_promotion<M: Clone, B>(
+    panes: &FloatingPanes<
+        '_,
+        M,
+        iced_graphics::Renderer<B>,
+        FloatingPanesBehaviour<M, iced_graphics::Renderer<B>>,
+    >,
+    connection: &Connection,
+    curve: &ConnectionCurve,
+) -> Option<Primitive>
+where
+    B: Backend + iced_graphics::backend::Text,
+{
+    const TEXT_SIZE: f32 = 12.0;
+
+    FloatingPanesBehaviour::promotion(panes, connection)?;
+
+    let center = (curve.from + curve.to) / 2.0;
+
+    Some(Primitive::Text {
+        content: "↑".to_string(),
+        bounds: Rectangle { x: center.x, y: center.y + TEXT_SIZE / 2.0, width: TEXT_SIZE, height: TEXT_SIZE },
+        color: panes.behaviour.status_colors.idle,
+        size: TEXT_SIZE,
+        font: Font::Default,
+        horizontal_alignment: HorizontalAlignment::Center,
+        vertical_alignment: VerticalAlignment::Center,
+    })
+}
+
+/// The status is drawn over a background in the tooltip style, in the bottom right corner of the
+/// viewport, regardless of how the panes are panned.
+fn draw_schedule_status<B: Backend + iced_graphics::backend::Text>(
+    renderer: &iced_graphics::Renderer<B>,
+    schedule_status: &ScheduleStatus,
+    viewport: &Rectangle,
+    status_colors: &StatusColors,
+    tooltip_style: Option<&dyn TooltipStyleSheet>,
+) -> Vec<Primitive> {
+    let content = schedule_status.label();
+    let size = style::consts::TEXT_SIZE_REGULAR as f32;
+    let padding = Vec2::new(style::consts::SPACING_HORIZONTAL, style::consts::SPACING_VERTICAL).as_::<f32>();
+    let (text_width, text_height) = renderer.backend().measure(&content, size, Font::Default, Size::INFINITY);
+    let bounds = Rectangle {
+        x: viewport.x + viewport.width - text_width - padding.x * 3.0,
+        y: viewport.y + viewport.height - text_height - padding.y * 3.0,
+        width: text_width + padding.x * 2.0,
+        height: text_height + padding.y * 2.0,
+    };
+    let mut primitives = Vec::with_capacity(2);
+
+    if let Some(container_style) = tooltip_style.map(|style| style.style().container.style()) {
+        primitives.push(Primitive::Quad {
+            bounds,
+            background: container_style.background.unwrap_or(Background::Color(Color::TRANSPARENT)),
+            border_radius: container_style.border_radius,
+            border_width: container_style.border_width,
+            border_color: container_style.border_color,
+        });
+    }
+
+    primitives.push(Primitive::Text {
+        content,
+        bounds: Rectangle {
+            x: bounds.x + padding.x,
+            y: bounds.y + padding.y,
+            width: text_width,
+            height: text_height,
+        },
+        color: schedule_status.color(status_colors),
+        size,
+        font: Font::Default,
+        horizontal_alignment: HorizontalAlignment::Left,
+        vertical_alignment: VerticalAlignment::Top,
+    });
+
+    primitives
+}
+
+/// Numbers the pane by the position of its node in the order of execution, in a badge above its
+/// top left corner. Disabled nodes are marked with a dimmed dash, nodes missing from the schedule
+/// with a cross.
+fn draw_execution_order<B: Backend + iced_graphics::backend::Text>(
+    renderer: &iced_graphics::Renderer<B>,
+    order: Option<Option<usize>>,
+    pane_bounds: Rectangle,
+    status_colors: &StatusColors,
+    tooltip_style: Option<&dyn TooltipStyleSheet>,
+) -> Vec<Primitive> {
+    const TEXT_SIZE: f32 = 12.0;
+
+    let (content, color) = match order {
+        Some(Some(position)) => ((position + 1).to_string(), status_colors.highlight),
+        Some(None) => ("–".to_string(), Color { a: status_colors.idle.a * 0.4, ..status_colors.idle }),
+        None => ("×".to_string(), status_colors.pending),
+    };
+    let padding = style::consts::SPACING_VERTICAL as f32 / 2.0;
+    let (text_width, text_height) =
+        renderer.backend().measure(&content, TEXT_SIZE, Font::Default, Size::INFINITY);
+    let bounds = Rectangle {
+        x: pane_bounds.x,
+        y: pane_bounds.y - text_height - padding * 3.0,
+        width: text_width.max(text_height) + padding * 2.0,
+        height: text_height + padding * 2.0,
+    };
+    let mut primitives = Vec::with_capacity(2);
+
+    if let Some(container_style) = tooltip_style.map(|style| style.style().container.style()) {
+        primitives.push(Primitive::Quad {
+            bounds,
+            background: container_style.background.unwrap_or(Background::Color(Color::TRANSPARENT)),
+            border_radius: container_style.border_radius,
+            border_width: container_style.border_width,
+            border_color: container_style.border_color,
+        });
+    }
+
+    primitives.push(Primitive::Text {
+        content,
+        bounds: Rectangle { x: bounds.center_x(), y: bounds.center_y(), ..bounds },
+        color,
+        size: TEXT_SIZE,
+        font: Font::Default,
+        horizontal_alignment: HorizontalAlignment::Center,
+        vertical_alignment: VerticalAlignment::Center,
+    });
+
+    primitives
+}
+
+/// The tabs are drawn over backgrounds in the tooltip style, in the top left corner of the viewport,
+/// regardless of how the panes are panned. The focused tab is underlined.
+fn draw_document_tabs(
+    document_tabs: &DocumentTabs,
+    viewport: &Rectangle,
+    cursor_position: Point,
+    status_colors: &StatusColors,
+    tooltip_style: Option<&dyn TooltipStyleSheet>,
+) -> Vec<Primitive> {
+    const UNDERLINE_WIDTH: f32 = 2.0;
+
+    let origin = Point::new(viewport.x, viewport.y);
+    let size = style::consts::TEXT_SIZE_REGULAR as f32;
+    let padding = style::consts::SPACING_HORIZONTAL as f32;
+    let hovered = document_tabs.target(origin, cursor_position);
+    let container_style = tooltip_style.map(|style| style.style().container.style());
+    let button_color =
+        |target| if hovered == Some(target) { status_colors.highlight } else { status_colors.idle };
+    let centered_text = |content: &str, bounds: Rectangle, color| Primitive::Text {
+        content: content.to_string(),
+        bounds: Rectangle { x: bounds.center_x(), y: bounds.center_y(), ..bounds },
+        color,
+        size,
+        font: Font::Default,
+        horizontal_alignment: HorizontalAlignment::Center,
+        vertical_alignment: VerticalAlignment::Center,
+    };
+    let mut primitives = Vec::new();
+
+    for (index, bounds) in document_tabs.bounds(origin).enumerate() {
+        if let Some(container_style) = container_style.as_ref() {
+            primitives.push(Primitive::Quad {
+                bounds,
+                background: container_style.background.unwrap_or(Background::Color(Color::TRANSPARENT)),
+                border_radius: container_style.border_radius,
+                border_width: container_style.border_width,
+                border_color: container_style.border_color,
+            });
+        }
+
+        let title = if let Some(title) = document_tabs.titles.get(index) {
+            title
+        } else {
+            primitives.push(centered_text("+", bounds, button_color(DocumentTabsTarget::Create)));
+            continue;
+        };
+        let focused = index == document_tabs.focused;
+
+        primitives.push(Primitive::Text {
+            content: title.clone(),
+            bounds: Rectangle {
+                x: bounds.x + padding,
+                y: bounds.center_y(),
+                width: bounds.width - 2.0 * DocumentTabs::BUTTON_WIDTH - padding,
+                height: bounds.height,
+            },
+            color: if focused { status_colors.highlight } else { status_colors.idle },
+            size,
+            font: Font::Default,
+            horizontal_alignment: HorizontalAlignment::Left,
+            vertical_alignment: VerticalAlignment::Center,
+        });
+        primitives.push(centered_text(
+            if document_tabs.is_pinned(index) { "●" } else { "○" },
+            DocumentTabs::pin_button_bounds(bounds),
+            button_color(DocumentTabsTarget::Pin(index)),
+        ));
+        primitives.push(centered_text(
+            "×",
+            DocumentTabs::close_button_bounds(bounds),
+            button_color(DocumentTabsTarget::Close(index)),
+        ));
+
+        if focused {
+            primitives.push(Primitive::Quad {
+                bounds: Rectangle {
+                    y: bounds.y + bounds.height - UNDERLINE_WIDTH,
+                    height: UNDERLINE_WIDTH,
+                    ..bounds
+                },
+                background: Background::Color(status_colors.highlight),
+                border_radius: 0,
+                border_width: 0,
+                border_color: Color::TRANSPARENT,
+            });
+        }
+    }
+
+    primitives
+}
+
+/// The stroke of an existing connection.
+fn connection_stroke(highlighted: bool, status_colors: &StatusColors) -> Stroke {
+    if highlighted {
+        Stroke {
+            color: status_colors.highlight,
+            width: 3.0,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+        }
+    } else {
+        Stroke { color: status_colors.idle, width: 2.0, line_cap: LineCap::Round, line_join: LineJoin::Round }
+    }
+}
+
+/// Marks an invalid pane with an exclamation mark in the top right corner of its title bar.
+fn draw_error_glyph(frame: &mut Frame, pane_bounds: Rectangle, color: Color, status_colors: &StatusColors) {
+    const RADIUS: f32 = 6.0;
+
+    let offset = RADIUS + style::consts::SPACING_VERTICAL as f32;
+    let center = Point::new(pane_bounds.x + pane_bounds.width - offset, pane_bounds.y + offset);
+
+    frame.fill(&Path::circle(center, RADIUS), Fill { color, rule: FillRule::NonZero });
+    frame.stroke(
+        &Path::new(|builder| {
+            builder.move_to(Point::new(center.x, center.y - RADIUS * 0.55));
+            builder.line_to(Point::new(center.x, center.y + RADIUS * 0.1));
+        }),
+        Stroke {
+            color: status_colors.idle,
+            width: 1.5,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+        },
+    );
+    frame.fill(
+        &Path::circle(Point::new(center.x, center.y + RADIUS * 0.5), 1.0),
+        Fill { color: status_colors.idle, rule: FillRule::NonZero },
+    );
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct ConnectionCurve {
     pub from: Vec2<f32>,
     pub to: Vec2<f32>,
 }
 
 impl ConnectionCurve {
-    fn from_channel_layouts<M: Clone, R: WidgetRenderer>(
-        output: ChannelLayout,
-        input: ChannelLayout,
-    ) -> Self {
-        let from = NodeElement::<M, R>::get_connection_point(output, ChannelDirection::Out);
-        let to = NodeElement::<M, R>::get_connection_point(input, ChannelDirection::In);
-        Self { from, to }
-    }
-
     fn draw(&self, frame: &mut Frame, stroke: Stroke, stroke_type: StrokeType) {
         let segments = util::get_connection_curve(self.from, self.to);
         let path = Path::new(|builder| {
@@ -1006,6 +3946,64 @@ impl ConnectionCurve {
         frame.stroke(&path, stroke);
     }
 
+    /// Draws the curve like [`ConnectionCurve::draw`], with the parts passing over unrelated panes
+    /// dimmed, as classified by the [`CrossingCache`] for the connection and the index of the curve.
+    fn draw_dimmed(
+        &self,
+        frame: &mut Frame,
+        stroke: Stroke,
+        stroke_type: StrokeType,
+        crossings: &mut CrossingCache,
+        (connection, curve_index): (&Connection, usize),
+    ) {
+        const TOLERANCE: f32 = 0.1;
+        const DIMMED_ALPHA: f32 = 0.25;
+
+        let points = util::get_connection_curve(self.from, self.to).flattened(TOLERANCE);
+        let crossing = crossings.classify(connection, curve_index, &points);
+        let mut run_start = 0;
+
+        // Each run of equally classified line segments is stroked separately
+        for run_end in 1..=crossing.len() {
+            if run_end < crossing.len() && crossing[run_end] == crossing[run_start] {
+                continue;
+            }
+
+            let run = &points[run_start..=run_end];
+            let color = if crossing[run_start] {
+                Color { a: stroke.color.a * DIMMED_ALPHA, ..stroke.color }
+            } else {
+                stroke.color
+            };
+            let path = Path::new(|builder| {
+                builder.move_to(run[0].to_array().into());
+                util::stroke_polyline(builder, run, stroke_type);
+            });
+
+            frame.stroke(&path, Stroke { color, ..stroke });
+            run_start = run_end;
+        }
+    }
+
+    /// Draws an arrowhead pointing into the connection point at the end of the curve, which the
+    /// curve approaches horizontally.
+    fn draw_arrowhead(&self, frame: &mut Frame, color: Color) {
+        const LENGTH: f32 = 8.0;
+        const HALF_WIDTH: f32 = 4.0;
+        // Keeps the tip clear of enlarged connection points.
+        const INSET: f32 = 5.0;
+
+        let tip = self.to - Vec2::new(INSET, 0.0);
+        let path = Path::new(|builder| {
+            builder.move_to(tip.into_array().into());
+            builder.line_to((tip - Vec2::new(LENGTH, HALF_WIDTH)).into_array().into());
+            builder.line_to((tip - Vec2::new(LENGTH, -HALF_WIDTH)).into_array().into());
+            builder.close();
+        });
+
+        frame.fill(&path, Fill { color, rule: FillRule::NonZero });
+    }
+
     fn bounds_from_curve(segments: &Segments<QuadraticBezierSegment<f32>>) -> Rectangle {
         let min = Vec2::<f32>::new(
             [segments[0].from.x, segments[0].ctrl.x, segments[1].ctrl.x, segments[1].to.x]
@@ -1027,7 +4025,6 @@ impl ConnectionCurve {
         Rectangle::from_min_max(min, max)
     }
 
-    #[allow(dead_code)]
     fn bounds(&self) -> Rectangle {
         Self::bounds_from_curve(&util::get_connection_curve(self.from, self.to))
     }
@@ -1098,6 +4095,18 @@ impl<M: Clone, R: WidgetRenderer, W: Widget<M, R>> Overlay<M, R> for WidgetOverl
         self.widget.draw(renderer, defaults, layout, cursor_position, &layout.bounds())
     }
 
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<M>,
+        renderer: &R,
+        clipboard: Option<&dyn Clipboard>,
+    ) -> Status {
+        self.widget.on_event(event, layout, cursor_position, messages, renderer, clipboard)
+    }
+
     fn hash_layout(&self, state: &mut Hasher, position: Point) {
         OrderedFloat::from(position.x).hash(state);
         OrderedFloat::from(position.y).hash(state);
@@ -1106,10 +4115,24 @@ impl<M: Clone, R: WidgetRenderer, W: Widget<M, R>> Overlay<M, R> for WidgetOverl
 }
 
 typed_layout! {
-    type_name: Channels,
+    type_name: NodeContent,
     traverse: [
         {
             parent_type_name: FloatingPaneContent,
+            fn_name: scrolled_content,
+            fn_args: [],
+            fn: |parent: Layout<'a>| {
+                parent.children().nth(0).unwrap()
+            },
+        },
+    ],
+}
+
+typed_layout! {
+    type_name: Channels,
+    traverse: [
+        {
+            parent_type_name: NodeContent,
             fn_name: channels_with_direction,
             fn_args: [channel_direction: ChannelDirection],
             fn: |parent: Layout<'a>, channel_direction: ChannelDirection| {
@@ -1140,9 +4163,9 @@ typed_layout! {
         {
             parent_type_name: Channels,
             fn_name: channel,
-            fn_args: [channel_index: usize],
-            fn: |parent: Layout<'a>, channel_index: usize| {
-                parent.children().nth(channel_index).unwrap()
+            fn_args: [row_index: usize],
+            fn: |parent: Layout<'a>, row_index: usize| {
+                parent.children().nth(row_index).unwrap()
             },
         },
     ],
@@ -1151,3 +4174,371 @@ typed_layout! {
         fn_name: channels,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::behaviour::Prng;
+    use test::Bencher;
+
+    const NODE_COUNT: usize = 500;
+    const COLUMNS: usize = 25;
+    const CURSOR_POSITIONS: usize = 1000;
+    const CHANNEL_ROWS: usize = 3;
+
+    /// Panes laid out in rows, each connected to the next one and to the one below it, in the
+    /// coordinates of the panes, which coincide with the coordinates of the viewport.
+    struct SyntheticLayout {
+        panes: Vec<Rectangle>,
+        connections: Vec<Connection>,
+    }
+
+    impl SyntheticLayout {
+        fn new(node_count: usize) -> Self {
+            let panes = (0..node_count)
+                .map(|index| Rectangle {
+                    x: (index % COLUMNS) as f32 * 320.0,
+                    y: (index / COLUMNS) as f32 * 240.0,
+                    width: 200.0,
+                    height: 120.0,
+                })
+                .collect::<Vec<_>>();
+            // The connections from the left arrive at the first input, the ones from above at the
+            // second one.
+            let connections = (0..node_count)
+                .flat_map(|from| vec![(from, from + 1, 0), (from, from + COLUMNS, 1)])
+                .filter(|(_, to, _)| *to < node_count)
+                .map(|(from, to, input)| {
+                    Connection::try_from_identifiers([
+                        Self::channel(from, ChannelDirection::Out, 0),
+                        Self::channel(to, ChannelDirection::In, input),
+                    ])
+                    .unwrap()
+                })
+                .collect();
+
+            Self { panes, connections }
+        }
+
+        fn channel(
+            pane_index: usize,
+            channel_direction: ChannelDirection,
+            channel_index: usize,
+        ) -> ChannelIdentifier {
+            ChannelIdentifier {
+                node_index: NodeIndex::new(pane_index),
+                channel_direction,
+                channel_index,
+                pass_by: ChannelPassBy::Value,
+            }
+        }
+
+        /// The rows of the inputs on the left half of the pane and the ones of the outputs on the
+        /// right half, below the title bar.
+        fn channel_bounds(&self, channel: ChannelIdentifier) -> Rectangle {
+            let pane = self.panes[channel.node_index.index()];
+            let x = match channel.channel_direction {
+                ChannelDirection::In => pane.x,
+                ChannelDirection::Out => pane.center_x(),
+            };
+
+            Rectangle {
+                x,
+                y: pane.y + 40.0 + channel.channel_index as f32 * 20.0,
+                width: pane.width / 2.0,
+                height: 20.0,
+            }
+        }
+
+        fn curve(&self, connection_index: usize) -> ConnectionCurve {
+            let connection = &self.connections[connection_index];
+
+            ConnectionCurve {
+                from: get_connection_point(
+                    self.channel_bounds(connection.from()),
+                    ChannelDirection::Out,
+                    false,
+                ),
+                to: get_connection_point(self.channel_bounds(connection.to()), ChannelDirection::In, false),
+            }
+        }
+
+        fn pane_bounds(&self) -> IndexMap<NodeIndex, Rectangle> {
+            self.panes
+                .iter()
+                .enumerate()
+                .map(|(index, bounds)| {
+                    (
+                        NodeIndex::new(index),
+                        bounds.grow_symmetrical(style::consts::SPACING_HORIZONTAL as f32, 0.0),
+                    )
+                })
+                .collect()
+        }
+
+        /// Brings the index up to date the way [`FloatingPanes::update_hit_test_index`] does, with
+        /// each connection in a group of its own.
+        fn update_index(&self, index: &mut HitTestIndex) {
+            index.update(
+                &self.connections,
+                self.pane_bounds(),
+                || (0..self.connections.len()).map(|index| vec![index]).collect(),
+                |group| self.curve(group[0]).bounds().grow_uniform(HitTestIndex::CONNECTION_MARGIN),
+            );
+        }
+
+        fn index(&self) -> HitTestIndex {
+            let mut index = HitTestIndex::default();
+
+            self.update_index(&mut index);
+            index
+        }
+
+        fn hover_pane(&self, node_index: NodeIndex) -> Option<HoverPane> {
+            let pane_index = node_index.index();
+            let channels = [ChannelDirection::In, ChannelDirection::Out]
+                .iter()
+                .flat_map(|direction| {
+                    (0..CHANNEL_ROWS)
+                        .map(move |channel_index| Self::channel(pane_index, *direction, channel_index))
+                })
+                .map(|channel| (channel, self.channel_bounds(channel)))
+                .collect();
+
+            Some(HoverPane {
+                z_index: pane_index,
+                bounds: self.panes[pane_index]
+                    .grow_symmetrical(style::consts::SPACING_HORIZONTAL as f32, 0.0),
+                collapsed: false,
+                channels,
+            })
+        }
+
+        /// The hovered channels and connection, out of the candidates, tested the same way the
+        /// widget tests them.
+        fn hover(
+            &self,
+            candidates: &[HitTestEntry],
+            point: Vec2<f32>,
+        ) -> (Vec<ChannelIdentifier>, Option<Highlight>) {
+            let channels =
+                HitTestIndex::hovered_channels(candidates, point, |node_index| self.hover_pane(node_index));
+            let connection = HitTestIndex::hovered_connection(candidates, point, |group_index| {
+                vec![(Highlight::Connection(self.connections[group_index].clone()), self.curve(group_index))]
+            });
+
+            (channels, connection)
+        }
+
+        /// Tests all panes and connections.
+        fn hover_exhaustive(&self, point: Vec2<f32>) -> (Vec<ChannelIdentifier>, Option<Highlight>) {
+            let candidates = (0..self.panes.len())
+                .map(|index| HitTestEntry::Pane(NodeIndex::new(index)))
+                .chain((0..self.connections.len()).map(HitTestEntry::Group))
+                .collect::<Vec<_>>();
+
+            self.hover(&candidates, point)
+        }
+
+        /// Tests the panes and connections the index lists near the point, as the widget does.
+        fn hover_indexed(
+            &self,
+            index: &HitTestIndex,
+            point: Vec2<f32>,
+        ) -> (Vec<ChannelIdentifier>, Option<Highlight>) {
+            let candidates = index.query(point).collect::<Vec<_>>();
+
+            self.hover(&candidates, point)
+        }
+
+        /// Points scattered over the layout, along with points just next to each curve, so that
+        /// connections are hovered too.
+        fn cursor_positions(&self, count: usize) -> Vec<Vec2<f32>> {
+            let mut prng = Prng::new(1929);
+            let extent = self
+                .panes
+                .iter()
+                .fold(Vec2::<f32>::zero(), |extent, bounds| extent.map2(bounds.max(), util::partial_max));
+            let scattered = (0..count)
+                .map(|_| Vec2::new(prng.next_f64() as f32, prng.next_f64() as f32) * extent)
+                .collect::<Vec<_>>();
+            let near_curves = (0..self.connections.len()).flat_map(|index| {
+                let curve = self.curve(index);
+                let segments = util::get_connection_curve(curve.from, curve.to);
+
+                [0.25, 0.5, 0.75].iter().map(move |t| segments.sample(*t) + Vec2::new(0.0, 3.0))
+            });
+
+            scattered.into_iter().chain(near_curves).collect()
+        }
+    }
+
+    #[test]
+    fn indexed_hover_matches_exhaustive_hover() {
+        let layout = SyntheticLayout::new(NODE_COUNT);
+        let index = layout.index();
+        let mut hovered_channels = 0;
+        let mut hovered_connections = 0;
+
+        for point in layout.cursor_positions(CURSOR_POSITIONS) {
+            let expected = layout.hover_exhaustive(point);
+
+            assert_eq!(layout.hover_indexed(&index, point), expected, "{:?}", point);
+
+            hovered_channels += expected.0.len();
+            hovered_connections += expected.1.is_some() as usize;
+        }
+
+        assert!(hovered_channels > 0);
+        assert!(hovered_connections >= layout.connections.len());
+    }
+
+    /// Once a dragged pane is reindexed, the index lists every connection passing through the pane,
+    /// among which [`FloatingPanesBehaviour::splice_candidates`] looks for the one to splice the
+    /// node into, including the connections of the pane itself, which move along with it.
+    #[test]
+    fn connections_within_a_dropped_pane_include_every_crossing_connection() {
+        let mut layout = SyntheticLayout::new(NODE_COUNT);
+        let mut index = layout.index();
+        let mut prng = Prng::new(1929);
+        let dropped = COLUMNS * 2 + 7;
+        let mut spliceable_connections = 0;
+
+        for _ in 0..100 {
+            // Over the first rows, mostly between the other panes.
+            layout.panes[dropped].x = prng.next_f64() as f32 * 320.0 * 10.0;
+            layout.panes[dropped].y = prng.next_f64() as f32 * 240.0 * 4.0;
+            layout.update_index(&mut index);
+
+            let bounds = layout.panes[dropped];
+            let connections = index.connections_within(bounds);
+            let crossing = (0..layout.connections.len()).filter(|connection_index| {
+                let curve = layout.curve(*connection_index);
+
+                curve.bounds().overlaps(&bounds) && {
+                    let segments = util::get_connection_curve(curve.from, curve.to);
+
+                    (0..=200)
+                        .any(|step| bounds.contains(segments.sample(step as f32 / 200.0).into_array().into()))
+                }
+            });
+
+            for connection_index in crossing {
+                let connection = &layout.connections[connection_index];
+
+                assert!(connections.contains(&connection_index), "{} {:?}", connection_index, bounds);
+
+                if connection.from().node_index.index() != dropped
+                    && connection.to().node_index.index() != dropped
+                {
+                    spliceable_connections += 1;
+                }
+            }
+        }
+
+        assert!(spliceable_connections > 0);
+    }
+
+    #[test]
+    fn queried_entries_are_the_ones_whose_bounds_contain_the_point() {
+        let mut grid = HitTestGrid::default();
+        let small = Rectangle { x: 10.0, y: 10.0, width: 20.0, height: 20.0 };
+        let spanning = Rectangle { x: -200.0, y: 0.0, width: 400.0, height: 20.0 };
+        let huge = Rectangle { x: -1.0e6, y: -1.0e6, width: 2.0e6, height: 2.0e6 };
+
+        grid.insert(HitTestEntry::Group(0), small);
+        grid.insert(HitTestEntry::Group(1), spanning);
+        grid.insert(HitTestEntry::Group(2), huge);
+
+        let query = |point: [f32; 2]| grid.query(Vec2::from(point)).collect::<Vec<_>>();
+
+        assert_eq!(
+            query([15.0, 15.0]),
+            [HitTestEntry::Group(0), HitTestEntry::Group(1), HitTestEntry::Group(2)]
+        );
+        assert_eq!(query([-150.0, 10.0]), [HitTestEntry::Group(1), HitTestEntry::Group(2)]);
+        // Entries spanning too many cells are returned by every query.
+        assert_eq!(query([5.0e5, 5.0e5]), [HitTestEntry::Group(2)]);
+        assert_eq!(grid.large, [HitTestEntry::Group(2)]);
+    }
+
+    #[test]
+    fn removed_entries_are_not_queried() {
+        let mut grid = HitTestGrid::default();
+        let before = Rectangle { x: 0.0, y: 0.0, width: 100.0, height: 100.0 };
+        let after = Rectangle { x: 1000.0, ..before };
+        let pane = HitTestEntry::Pane(NodeIndex::new(0));
+
+        grid.insert(pane, before);
+        grid.remove(pane, before);
+        grid.insert(pane, after);
+
+        assert_eq!(grid.query(Vec2::new(50.0, 50.0)).count(), 0);
+        assert_eq!(grid.query(Vec2::new(1050.0, 50.0)).collect::<Vec<_>>(), [pane]);
+    }
+
+    /// Bounds spanning many cells list each overlapping entry once.
+    #[test]
+    fn entries_overlapping_bounds_are_listed_once() {
+        let layout = SyntheticLayout::new(NODE_COUNT);
+        let index = layout.index();
+        let pane = layout.panes[COLUMNS + 3];
+        let mut entries = index.grid.query_bounds(pane);
+        let overlapping = |bounds: Rectangle| {
+            bounds.x < pane.x + pane.width
+                && pane.x < bounds.x + bounds.width
+                && bounds.y < pane.y + pane.height
+                && pane.y < bounds.y + bounds.height
+        };
+
+        entries.sort_unstable_by_key(|entry| match entry {
+            HitTestEntry::Pane(node_index) => (0, node_index.index()),
+            HitTestEntry::Group(index) => (1, *index),
+        });
+
+        assert!(entries.windows(2).all(|pair| pair[0] != pair[1]));
+
+        for connection_index in 0..layout.connections.len() {
+            if overlapping(layout.curve(connection_index).bounds()) {
+                assert!(entries.contains(&HitTestEntry::Group(connection_index)), "{}", connection_index);
+            }
+        }
+
+        assert!(entries.contains(&HitTestEntry::Pane(NodeIndex::new(COLUMNS + 3))));
+        // Most of the layout is far from the pane.
+        assert!(entries.len() * 10 < layout.panes.len() + layout.connections.len(), "{:?}", entries);
+    }
+
+    fn bench_hover(
+        bencher: &mut Bencher,
+        hover: impl Fn(&SyntheticLayout, Vec2<f32>) -> (Vec<ChannelIdentifier>, Option<Highlight>),
+    ) {
+        let layout = SyntheticLayout::new(NODE_COUNT);
+        let cursor_positions = layout.cursor_positions(CURSOR_POSITIONS);
+
+        bencher.iter(|| {
+            for point in &cursor_positions {
+                test::black_box(hover(&layout, *point));
+            }
+        });
+    }
+
+    #[bench]
+    fn hover_500_nodes_indexed(bencher: &mut Bencher) {
+        let index = SyntheticLayout::new(NODE_COUNT).index();
+
+        bench_hover(bencher, |layout, point| layout.hover_indexed(&index, point));
+    }
+
+    #[bench]
+    fn hover_500_nodes_exhaustive(bencher: &mut Bencher) {
+        bench_hover(bencher, |layout, point| layout.hover_exhaustive(point));
+    }
+
+    #[bench]
+    fn index_500_nodes(bencher: &mut Bencher) {
+        let layout = SyntheticLayout::new(NODE_COUNT);
+
+        bencher.iter(|| layout.index());
+    }
+}
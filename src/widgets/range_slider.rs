@@ -0,0 +1,553 @@
+use iced_graphics::{self, Backend, Color, Primitive, Rectangle};
+use iced_native::event::Status;
+use iced_native::keyboard::{self, Event as KeyboardEvent, KeyCode};
+use iced_native::layout::{Layout, Limits, Node};
+use iced_native::mouse::{self, Button as MouseButton, Event as MouseEvent};
+use iced_native::widget::Widget;
+use iced_native::{self, Clipboard, Element, Event, Font, Hasher, Length, Point, Size};
+use iced_native::{HorizontalAlignment, VerticalAlignment};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+const HANDLE_HEIGHT: f32 = 16.0;
+/// Also the horizontal margin of the track, so that the handles at both ends are fully visible.
+const HANDLE_HALF_WIDTH: f32 = 5.0;
+const TRACK_HEIGHT: f32 = 4.0;
+/// The space between the handles and the values below them.
+const LABEL_SPACING: f32 = 2.0;
+/// The longest time between two presses of a handle, which enter the text edit mode.
+const DOUBLE_CLICK_DURATION: Duration = Duration::from_millis(400);
+/// The fraction of the track a handle is nudged by with the arrow keys, if there is no step.
+const NUDGE_FRACTION: f64 = 0.01;
+/// The factor of the nudges while Ctrl is held.
+const COARSE_FACTOR: f64 = 10.0;
+/// The most decimal places values are displayed with.
+const MAX_PRECISION: usize = 6;
+
+/// The state of a [`RangeSlider`], to be stored in the node behaviour.
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    /// The index of the handle being dragged.
+    dragging: Option<usize>,
+    /// The handle last pressed, which is nudged with the arrow keys until something else is
+    /// clicked.
+    focused: Option<usize>,
+    /// The text entered since a handle was double-clicked.
+    editing: Option<Editing>,
+    /// The handle last pressed and when.
+    last_press: Option<(usize, Instant)>,
+    modifiers: keyboard::ModifiersState,
+}
+
+#[derive(Debug, Clone)]
+struct Editing {
+    handle: usize,
+    text: String,
+    /// Whether the text was rejected when it was last committed.
+    invalid: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub track: Color,
+    /// The part of the track between the handles, or before the handle of a single-handle slider.
+    pub range: Color,
+    pub handle: Color,
+    pub focused_handle_border: Color,
+    pub text: Color,
+    /// The color of the text rejected when entered.
+    pub invalid_text: Color,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            track: Color::from_rgb(0.3, 0.3, 0.3),
+            range: Color::WHITE,
+            handle: Color::WHITE,
+            focused_handle_border: Color::WHITE,
+            text: Color::WHITE,
+            invalid_text: Color::from_rgb(1.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// A horizontal track with a draggable handle per value, the values being displayed below it.
+/// Pass two values for a range, or a single one for a plain slider. Clicking the track moves the
+/// closest handle there. The handle pressed last is nudged with the arrow keys, with Ctrl for
+/// coarser changes. Double-clicking a handle enters a text edit mode, committed with Enter or by
+/// clicking elsewhere and cancelled with Escape.
+///
+/// The widget does not keep the handles in order, the changes of each handle are emitted on their
+/// own for the owner to apply its own constraints.
+pub struct RangeSlider<'a, M> {
+    state: &'a mut State,
+    values: Vec<f64>,
+    on_change: Box<dyn Fn(usize, f64) -> M>,
+    min: f64,
+    max: f64,
+    step: Option<f64>,
+    width: Length,
+    text_size: Option<u16>,
+    style: Style,
+}
+
+impl<'a, M> RangeSlider<'a, M> {
+    /// Emits the index of the handle along with its new value.
+    pub fn new(
+        state: &'a mut State,
+        values: Vec<f64>,
+        min: f64,
+        max: f64,
+        on_change: impl Fn(usize, f64) -> M + 'static,
+    ) -> Self {
+        Self {
+            state,
+            values,
+            on_change: Box::new(on_change),
+            min,
+            max,
+            step: None,
+            width: Length::Fill,
+            text_size: None,
+            style: Default::default(),
+        }
+    }
+
+    /// Rounds the values emitted to the bounds of the track plus multiples of the step, including
+    /// the entered ones.
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = Some(step).filter(|step| *step > 0.0);
+        self
+    }
+
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn text_size(mut self, text_size: u16) -> Self {
+        self.text_size = Some(text_size);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// The number of decimal places displayed, derived from the step or the length of the track.
+    fn precision(&self) -> usize {
+        let increment = self.step.unwrap_or((self.max - self.min) * NUDGE_FRACTION);
+
+        if increment > 0.0 && increment.is_finite() {
+            (-increment.log10().floor()).max(0.0).min(MAX_PRECISION as f64) as usize
+        } else {
+            MAX_PRECISION
+        }
+    }
+
+    /// Rounds the value to the step and clamps it to the track.
+    fn constrain(&self, value: f64) -> f64 {
+        let value = match self.step {
+            Some(step) => self.min + ((value - self.min) / step).round() * step,
+            None => value,
+        };
+
+        value.max(self.min).min(self.max)
+    }
+
+    /// The value under the cursor, not constrained.
+    fn value_at(&self, bounds: Rectangle, cursor_position: Point) -> f64 {
+        let width = (bounds.width - HANDLE_HALF_WIDTH * 2.0).max(1.0);
+        let position = ((cursor_position.x - bounds.x - HANDLE_HALF_WIDTH) / width).max(0.0).min(1.0);
+
+        self.min + f64::from(position) * (self.max - self.min)
+    }
+
+    /// The handle closest to the cursor, horizontally. Of coinciding handles, the one on the side of
+    /// the cursor is picked, so that they can be pulled apart.
+    fn closest_handle(&self, bounds: Rectangle, cursor_position: Point) -> Option<(usize, f32)> {
+        let mut closest: Option<(usize, f32)> = None;
+
+        for (index, value) in self.values.iter().enumerate() {
+            let offset = cursor_position.x - handle_x(bounds, self.min, self.max, *value);
+            let distance = offset.abs();
+            let closer = closest.map_or(true, |(_, closest_distance)| {
+                distance < closest_distance || (distance == closest_distance && offset > 0.0)
+            });
+
+            if closer {
+                closest = Some((index, distance));
+            }
+        }
+
+        closest
+    }
+
+    /// The handle under the cursor, if any.
+    fn handle_at(&self, bounds: Rectangle, cursor_position: Point) -> Option<usize> {
+        if cursor_position.y < bounds.y || cursor_position.y > bounds.y + HANDLE_HEIGHT {
+            return None;
+        }
+
+        self.closest_handle(bounds, cursor_position)
+            .filter(|(_, distance)| *distance <= HANDLE_HALF_WIDTH)
+            .map(|(index, _)| index)
+    }
+
+    /// Emits the value of the handle, if it has changed.
+    fn emit(&self, handle: usize, value: f64, messages: &mut Vec<M>) {
+        let value = self.constrain(value);
+
+        if self.values.get(handle) != Some(&value) {
+            messages.push((self.on_change)(handle, value));
+        }
+    }
+
+    fn format(&self, value: f64) -> String {
+        format!("{:.*}", self.precision(), value)
+    }
+
+    /// Emits the entered value and leaves the text edit mode, if the text is a number.
+    fn commit(&mut self, messages: &mut Vec<M>) {
+        let (handle, parsed) = match self.state.editing.as_ref() {
+            Some(editing) => {
+                (editing.handle, editing.text.trim().parse::<f64>().ok().filter(|value| value.is_finite()))
+            }
+            None => return,
+        };
+
+        match parsed {
+            Some(value) => {
+                self.emit(handle, value, messages);
+                self.state.editing = None;
+            }
+            None => self.state.editing.as_mut().unwrap().invalid = true,
+        }
+    }
+
+    /// The texts displayed below the handles, the entered text in place of the edited value.
+    fn labels(&self) -> Vec<String> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| match &self.state.editing {
+                Some(editing) if editing.handle == index => format!("{}|", editing.text),
+                _ => self.format(*value),
+            })
+            .collect()
+    }
+}
+
+/// The horizontal position of the center of the handle of the value.
+fn handle_x(bounds: Rectangle, min: f64, max: f64, value: f64) -> f32 {
+    let width = bounds.width - HANDLE_HALF_WIDTH * 2.0;
+    let position = if max > min { ((value - min) / (max - min)).max(0.0).min(1.0) } else { 0.0 };
+
+    bounds.x + HANDLE_HALF_WIDTH + position as f32 * width
+}
+
+impl<'a, M, R> Widget<M, R> for RangeSlider<'a, M>
+where
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn layout(&self, renderer: &R, limits: &Limits) -> Node {
+        let text_size = self.text_size.unwrap_or(renderer.default_size());
+        let intrinsic_size =
+            Size::new(HANDLE_HALF_WIDTH * 2.0, HANDLE_HEIGHT + LABEL_SPACING + f32::from(text_size));
+
+        Node::new(limits.width(self.width).height(Length::Shrink).resolve(intrinsic_size))
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut R,
+        _defaults: &R::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) -> R::Output {
+        let bounds = layout.bounds();
+        let mouse_interaction = if self.state.dragging.is_some() {
+            mouse::Interaction::Grabbing
+        } else if self.handle_at(bounds, cursor_position).is_some() {
+            mouse::Interaction::Grab
+        } else if bounds.contains(cursor_position) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        };
+        let positions =
+            self.values.iter().map(|value| handle_x(bounds, self.min, self.max, *value)).collect::<Vec<_>>();
+
+        renderer.draw(
+            bounds,
+            &positions,
+            &self.labels(),
+            self.state.focused,
+            self.state.editing.as_ref().map(|editing| (editing.handle, editing.invalid)),
+            self.text_size.unwrap_or(renderer.default_size()),
+            &self.style,
+            mouse_interaction,
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.width.hash(state);
+        self.text_size.hash(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<M>,
+        _renderer: &R,
+        _clipboard: Option<&dyn Clipboard>,
+    ) -> Status {
+        let bounds = layout.bounds();
+        let hovered = bounds.contains(cursor_position);
+
+        match event {
+            Event::Keyboard(KeyboardEvent::ModifiersChanged(modifiers)) => self.state.modifiers = modifiers,
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) if !hovered => {
+                // Clicking elsewhere commits valid text and discards invalid text.
+                self.commit(messages);
+                self.state.editing = None;
+                self.state.focused = None;
+            }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) => {
+                self.commit(messages);
+                self.state.editing = None;
+
+                let now = Instant::now();
+
+                match self.handle_at(bounds, cursor_position) {
+                    Some(handle) => {
+                        let double_click =
+                            self.state.last_press.map_or(false, |(last_handle, last_press)| {
+                                last_handle == handle && now - last_press <= DOUBLE_CLICK_DURATION
+                            });
+
+                        if double_click {
+                            self.state.last_press = None;
+                            self.state.editing = Some(Editing {
+                                handle,
+                                text: self.format(self.values[handle]),
+                                invalid: false,
+                            });
+                        } else {
+                            self.state.last_press = Some((handle, now));
+                            self.state.dragging = Some(handle);
+                        }
+
+                        self.state.focused = Some(handle);
+                    }
+                    // Clicking the track moves the closest handle there, to be dragged further.
+                    None => {
+                        if let Some((handle, _)) = self.closest_handle(bounds, cursor_position) {
+                            self.emit(handle, self.value_at(bounds, cursor_position), messages);
+                            self.state.last_press = None;
+                            self.state.dragging = Some(handle);
+                            self.state.focused = Some(handle);
+                        }
+                    }
+                }
+
+                return Status::Captured;
+            }
+            // Captured for as long as a handle is dragged, so that panes are not dragged along.
+            Event::Mouse(MouseEvent::CursorMoved { .. }) if self.state.dragging.is_some() => {
+                let handle = self.state.dragging.unwrap();
+
+                self.emit(handle, self.value_at(bounds, cursor_position), messages);
+
+                return Status::Captured;
+            }
+            Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)) if self.state.dragging.is_some() => {
+                self.state.dragging = None;
+
+                return Status::Captured;
+            }
+            Event::Keyboard(KeyboardEvent::CharacterReceived(character)) if self.state.editing.is_some() => {
+                if !character.is_control() {
+                    let editing = self.state.editing.as_mut().unwrap();
+
+                    editing.text.push(character);
+                    editing.invalid = false;
+                }
+
+                return Status::Captured;
+            }
+            Event::Keyboard(KeyboardEvent::KeyPressed { key_code, .. }) if self.state.editing.is_some() => {
+                match key_code {
+                    KeyCode::Enter => self.commit(messages),
+                    KeyCode::Escape => self.state.editing = None,
+                    KeyCode::Backspace => {
+                        let editing = self.state.editing.as_mut().unwrap();
+
+                        editing.text.pop();
+                        editing.invalid = false;
+                    }
+                    _ => (),
+                }
+
+                // Shortcuts are not triggered while editing.
+                return Status::Captured;
+            }
+            Event::Keyboard(KeyboardEvent::KeyPressed { key_code, .. }) if self.state.focused.is_some() => {
+                let direction = match key_code {
+                    KeyCode::Left | KeyCode::Down => -1.0,
+                    KeyCode::Right | KeyCode::Up => 1.0,
+                    KeyCode::Escape => {
+                        self.state.focused = None;
+                        return Status::Captured;
+                    }
+                    _ => return Status::Ignored,
+                };
+                let handle = self.state.focused.unwrap();
+                let factor = if self.state.modifiers.control { COARSE_FACTOR } else { 1.0 };
+                let increment = self.step.unwrap_or((self.max - self.min) * NUDGE_FRACTION);
+
+                if let Some(value) = self.values.get(handle) {
+                    self.emit(handle, value + direction * increment * factor, messages);
+                }
+
+                return Status::Captured;
+            }
+            _ => (),
+        }
+
+        Status::Ignored
+    }
+}
+
+impl<'a, M, R> From<RangeSlider<'a, M>> for Element<'a, M, R>
+where
+    M: 'a,
+    R: 'a + WidgetRenderer,
+{
+    fn from(other: RangeSlider<'a, M>) -> Self {
+        Element::new(other)
+    }
+}
+
+pub trait WidgetRenderer: iced_native::Renderer + iced_native::text::Renderer + Sized {
+    /// `positions` are the horizontal centers of the handles. `editing` is the handle whose value is
+    /// being edited, along with whether the entered text has been rejected.
+    #[allow(clippy::too_many_arguments)]
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        positions: &[f32],
+        labels: &[String],
+        focused: Option<usize>,
+        editing: Option<(usize, bool)>,
+        text_size: u16,
+        style: &Style,
+        mouse_interaction: mouse::Interaction,
+    ) -> Self::Output;
+}
+
+impl<B> WidgetRenderer for iced_graphics::Renderer<B>
+where
+    B: Backend + iced_graphics::backend::Text,
+{
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        positions: &[f32],
+        labels: &[String],
+        focused: Option<usize>,
+        editing: Option<(usize, bool)>,
+        text_size: u16,
+        style: &Style,
+        mouse_interaction: mouse::Interaction,
+    ) -> Self::Output {
+        let track_bounds = Rectangle {
+            x: bounds.x + HANDLE_HALF_WIDTH,
+            y: bounds.y + (HANDLE_HEIGHT - TRACK_HEIGHT) / 2.0,
+            width: bounds.width - HANDLE_HALF_WIDTH * 2.0,
+            height: TRACK_HEIGHT,
+        };
+        let range_start = if positions.len() > 1 { positions[0] } else { track_bounds.x };
+        let range_end = positions.last().copied().unwrap_or(range_start);
+        let mut primitives = vec![
+            Primitive::Quad {
+                bounds: track_bounds,
+                background: style.track.into(),
+                border_radius: 2,
+                border_width: 0,
+                border_color: Color::TRANSPARENT,
+            },
+            Primitive::Quad {
+                bounds: Rectangle {
+                    x: range_start,
+                    width: (range_end - range_start).max(0.0),
+                    ..track_bounds
+                },
+                background: style.range.into(),
+                border_radius: 2,
+                border_width: 0,
+                border_color: Color::TRANSPARENT,
+            },
+        ];
+
+        // The focused handle is drawn last, on top of the others.
+        let order = (0..positions.len()).filter(|index| Some(*index) != focused).chain(focused);
+
+        for index in order {
+            primitives.push(Primitive::Quad {
+                bounds: Rectangle {
+                    x: positions[index] - HANDLE_HALF_WIDTH,
+                    y: bounds.y,
+                    width: HANDLE_HALF_WIDTH * 2.0,
+                    height: HANDLE_HEIGHT,
+                },
+                background: style.handle.into(),
+                border_radius: 1,
+                border_width: if Some(index) == focused { 2 } else { 0 },
+                border_color: style.focused_handle_border,
+            });
+        }
+
+        // The value of a single handle is centered, those of a range are aligned with the ends of
+        // the track, so that they do not overlap while the handles are close.
+        let label_y = bounds.y + HANDLE_HEIGHT + LABEL_SPACING;
+
+        for (index, label) in labels.iter().enumerate() {
+            let (x, horizontal_alignment) = match (index, labels.len()) {
+                (_, 1) => (bounds.center_x(), HorizontalAlignment::Center),
+                (0, _) => (bounds.x, HorizontalAlignment::Left),
+                _ => (bounds.x + bounds.width, HorizontalAlignment::Right),
+            };
+            let invalid = editing == Some((index, true));
+
+            primitives.push(Primitive::Text {
+                content: label.clone(),
+                bounds: Rectangle { x, y: label_y, width: bounds.width, height: f32::from(text_size) },
+                color: if invalid { style.invalid_text } else { style.text },
+                size: f32::from(text_size),
+                font: Font::Default,
+                horizontal_alignment,
+                vertical_alignment: VerticalAlignment::Top,
+            });
+        }
+
+        (Primitive::Group { primitives }, mouse_interaction)
+    }
+}
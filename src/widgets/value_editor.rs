@@ -0,0 +1,284 @@
+//! A structured editor of values with a safe binary representation.
+//!
+//! The fields of the editor are derived from the children of the type of the edited value, so
+//! that composite types are supported without the editor having to know about them. Only
+//! primitives, which are edited as text, and options, whose presence is toggled, are handled
+//! specifically. Primitives exactly representable by an `f64` can also be dragged, see
+//! [`DragValue`].
+
+use crate::node::{OptionType, PrimitiveKind, PrimitiveTypeEnum, TypeEnum, TypeExt, TypedBytes};
+use crate::style::{consts, Theme, Themeable};
+use crate::widgets::{drag_value, DragValue};
+use byteorder::LittleEndian;
+use iced::widget::checkbox::Checkbox;
+use iced::{
+    text_input::{self, TextInput},
+    Align, Column, Element, Length, Row, Space, Text,
+};
+use std::borrow::Cow;
+use std::io::Cursor;
+
+#[derive(Debug, Clone)]
+pub enum ValueEditorMessage {
+    /// The text of the primitive field with the index has been edited.
+    Edit { field: usize, text: String },
+    /// The option field with the index has been toggled.
+    ToggleSome { field: usize, some: bool },
+}
+
+#[derive(Debug, Clone)]
+enum FieldKind {
+    Primitive {
+        ty: PrimitiveTypeEnum,
+        state: text_input::State,
+        drag_state: drag_value::State,
+        text: String,
+        /// Whether the text is a valid value of the type, invalid text is not written.
+        valid: bool,
+    },
+    Option {
+        ty: OptionType,
+        some: bool,
+    },
+    /// A value consisting of the fields following it, one level deeper.
+    Composite,
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    label: String,
+    depth: u16,
+    /// The range of the bytes of the field within the edited value.
+    offset: usize,
+    size: usize,
+    kind: FieldKind,
+}
+
+impl Field {
+    /// Appends the fields of the value and of all its children, in pre-order.
+    fn collect(typed_bytes: TypedBytes<'_>, root: &[u8], label: String, depth: u16, fields: &mut Vec<Field>) {
+        let ty = typed_bytes.borrow().ty().into_owned();
+        let bytes = typed_bytes.borrow().bytes().bytes().unwrap();
+        let offset = bytes.as_ptr() as usize - root.as_ptr() as usize;
+        let kind = if let Some(primitive_ty) = ty.as_primitive_type_enum() {
+            let text = primitive_ty.read::<LittleEndian, _>(bytes).unwrap().value_to_string();
+
+            FieldKind::Primitive {
+                ty: primitive_ty,
+                state: Default::default(),
+                drag_state: Default::default(),
+                text,
+                valid: true,
+            }
+        } else if let TypeEnum::Option(option_ty) = &ty {
+            FieldKind::Option { some: option_ty.is_some_in(bytes), ty: option_ty.clone() }
+        } else {
+            FieldKind::Composite
+        };
+
+        fields.push(Field { label, depth, offset, size: bytes.len(), kind });
+
+        let child_label = |index: usize| match &ty {
+            TypeEnum::Option(_) => "value".to_string(),
+            _ => format!("[{}]", index),
+        };
+
+        // Safety: Types with a safe binary representation do not contain pointers.
+        for (index, child) in unsafe { typed_bytes.children() }.into_iter().enumerate() {
+            Field::collect(child, root, child_label(index), depth + 1, fields);
+        }
+    }
+}
+
+/// The state of an editor of a value with a safe binary representation.
+/// The edited value is only ever modified by whole fields, which have been validated first.
+#[derive(Debug, Clone)]
+pub struct ValueEditor {
+    ty: TypeEnum,
+    bytes: Vec<u8>,
+    fields: Vec<Field>,
+}
+
+impl ValueEditor {
+    /// Returns `None`, if the type does not have a safe binary representation or the bytes are
+    /// not of its size.
+    pub fn new(ty: TypeEnum, bytes: Vec<u8>) -> Option<Self> {
+        if !ty.has_safe_binary_representation() || ty.value_size_if_sized() != Some(bytes.len()) {
+            return None;
+        }
+
+        let mut editor = Self { ty, bytes, fields: Vec::new() };
+
+        editor.update_fields();
+        Some(editor)
+    }
+
+    /// An editor of the value of the type with all bytes set to zero, which is the default value
+    /// of primitives and `None` for options.
+    pub fn zeroed(ty: TypeEnum) -> Option<Self> {
+        let size = ty.value_size_if_sized()?;
+
+        Self::new(ty, vec![0; size])
+    }
+
+    /// An editor of a value of another type, with the text of the primitive fields carried over
+    /// from the corresponding fields of this editor. Only the text valid for the new type is
+    /// written.
+    pub fn retype(&self, ty: TypeEnum) -> Option<Self> {
+        let mut editor = Self::zeroed(ty)?;
+        let texts = self.fields.iter().filter_map(|field| match &field.kind {
+            FieldKind::Primitive { text, .. } => Some(text),
+            _ => None,
+        });
+        let indices = editor
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| matches!(field.kind, FieldKind::Primitive { .. }))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        for (index, text) in indices.into_iter().zip(texts) {
+            editor.edit(index, text.clone());
+        }
+
+        Some(editor)
+    }
+
+    pub fn ty(&self) -> &TypeEnum {
+        &self.ty
+    }
+
+    /// The binary representation of the edited value.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn update_fields(&mut self) {
+        let mut fields = Vec::new();
+        let typed_bytes = TypedBytes::from(&self.bytes[..], Cow::Borrowed(&self.ty), &());
+
+        Field::collect(typed_bytes, &self.bytes, String::new(), 0, &mut fields);
+        self.fields = fields;
+    }
+
+    /// Writes the value of the primitive field, if the text is valid, and returns whether the
+    /// edited value has changed.
+    fn edit(&mut self, index: usize, new_text: String) -> bool {
+        let field = &mut self.fields[index];
+
+        if let FieldKind::Primitive { ty, text, valid, .. } = &mut field.kind {
+            let value = ty.parse(&new_text);
+
+            *text = new_text;
+            *valid = value.is_some();
+
+            if let Some(value) = value {
+                // Encode the whole value before writing it, so that the edited value is never
+                // left partially written.
+                let mut encoded = vec![0; field.size];
+
+                value.write::<LittleEndian>(&mut Cursor::new(&mut encoded[..])).unwrap();
+                self.bytes[field.offset..field.offset + field.size].copy_from_slice(&encoded);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Applies the message and returns whether the edited value has changed.
+    pub fn update(&mut self, message: ValueEditorMessage) -> bool {
+        match message {
+            ValueEditorMessage::Edit { field, text } => self.edit(field, text),
+            ValueEditorMessage::ToggleSome { field, some } => {
+                let field = &self.fields[field];
+                let ty =
+                    if let FieldKind::Option { ty, .. } = &field.kind { ty.clone() } else { return false };
+
+                ty.set_some_in(&mut self.bytes[field.offset..field.offset + field.size], some);
+                // The inner fields appear or disappear.
+                self.update_fields();
+                true
+            }
+        }
+    }
+
+    pub fn view(&mut self, theme: &dyn Theme) -> Element<ValueEditorMessage> {
+        /// The indentation of each level of nested fields.
+        const INDENT: u16 = 12;
+
+        let mut column = Column::new().theme(theme).width(Length::Fill);
+
+        for (index, field) in self.fields.iter_mut().enumerate() {
+            let row = Row::new()
+                .theme(theme)
+                .push(Space::with_width(Length::Units(INDENT * field.depth.saturating_sub(1))))
+                .align_items(Align::Center)
+                .width(Length::Fill);
+            let label = Text::new(&field.label).size(consts::TEXT_SIZE_REGULAR);
+
+            let row = match &mut field.kind {
+                FieldKind::Primitive { ty, state, drag_state, text, valid } => {
+                    let ty = *ty;
+                    let row = if field.label.is_empty() { row } else { row.push(label) };
+                    // Large integers would lose precision as an `f64`.
+                    let draggable_value = Some(ty)
+                        .filter(|ty| *ty == PrimitiveTypeEnum::F64 || ty.promotes_to(PrimitiveTypeEnum::F64))
+                        .and_then(|ty| ty.parse(text.as_str()))
+                        .map(|value| value.to_f64());
+                    let row = match draggable_value {
+                        Some(value) => {
+                            let integer = ty.kind() != PrimitiveKind::Float;
+                            let drag_value =
+                                DragValue::new(drag_state, value, move |value| ValueEditorMessage::Edit {
+                                    field: index,
+                                    text: ty.from_f64(value).value_to_string(),
+                                })
+                                .range(
+                                    ty.from_f64(f64::NEG_INFINITY).to_f64(),
+                                    ty.from_f64(f64::INFINITY).to_f64(),
+                                )
+                                .theme(theme)
+                                .width(Length::Fill);
+
+                            row.push(if integer { drag_value.speed(0.1).step(1.0) } else { drag_value })
+                        }
+                        None => row.push(
+                            TextInput::new(state, "", text.as_str(), move |text| ValueEditorMessage::Edit {
+                                field: index,
+                                text,
+                            })
+                            .theme(theme)
+                            .width(Length::Fill),
+                        ),
+                    };
+
+                    if *valid {
+                        row
+                    } else {
+                        row.push(Text::new("Invalid").size(consts::TEXT_SIZE_REGULAR))
+                    }
+                }
+                FieldKind::Option { some, .. } => {
+                    let label = if field.label.is_empty() { "Some" } else { &field.label };
+
+                    row.push(
+                        Checkbox::new(*some, label, move |some| ValueEditorMessage::ToggleSome {
+                            field: index,
+                            some,
+                        })
+                        .theme(theme),
+                    )
+                }
+                // The root value is not labeled.
+                FieldKind::Composite if field.depth == 0 => continue,
+                FieldKind::Composite => row.push(label),
+            };
+
+            column = column.push(row);
+        }
+
+        column.into()
+    }
+}